@@ -160,6 +160,21 @@ fn impl_processor_component_macro(ast: &syn::DeriveInput) -> TokenStream {
                     );
                 )*
             }
+
+            fn take_over(&mut self, previous: &mut Self) {
+                #(
+                    ::flosion::core::sound::soundprocessor::CompiledProcessorComponent::take_over(
+                        &mut self.#component_field_names,
+                        &mut previous.#component_field_names
+                    );
+                )*
+                #(
+                    ::flosion::core::sound::soundprocessor::ProcessorState::take_over(
+                        &mut self.#state_field_names,
+                        &mut previous.#state_field_names
+                    );
+                )*
+            }
         }
 
         impl<'ctx> ::flosion::core::sound::soundprocessor::StartOver for #compiled_name <'ctx> {
@@ -0,0 +1,29 @@
+/// The peak amplitude of the noise produced by AntiDenormal, around
+/// -400 dBFS. This is far below anything audible, yet far above the
+/// smallest normal f32 value of around 1e-38.
+pub(crate) const ANTI_DENORMAL_LEVEL: f32 = 1e-20;
+
+/// A source of extremely quiet noise for injecting into feedback paths.
+/// Without it, a signal decaying away in a feedback loop eventually
+/// reaches the denormal range, where arithmetic is drastically slower
+/// on CPUs that don't flush denormals to zero. With the noise added, the
+/// signal instead settles at the level of the noise and stays normal.
+pub(crate) struct AntiDenormal {
+    state: u32,
+}
+
+impl AntiDenormal {
+    pub(crate) fn new() -> AntiDenormal {
+        AntiDenormal { state: 0x9E3779B9 }
+    }
+
+    /// The next sample of noise, between plus and minus ANTI_DENORMAL_LEVEL
+    pub(crate) fn next_sample(&mut self) -> f32 {
+        // Xorshift, which is cheap enough to run for every sample
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        let unit = (self.state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+        unit * ANTI_DENORMAL_LEVEL
+    }
+}
@@ -1,3 +1,6 @@
+use std::path::{Path, PathBuf};
+
+use hashstash::{Stashable, Stasher, UnstashError, Unstashable, Unstasher};
 use symphonia::core::{
     audio::{AudioBuffer, Signal},
     codecs::{DecoderOptions, CODEC_TYPE_NULL},
@@ -11,7 +14,88 @@ use crate::core::{samplefrequency::SAMPLE_FREQUENCY, soundchunk::CHUNK_SIZE};
 
 use super::soundbuffer::SoundBuffer;
 
-pub(crate) fn load_audio_file(path: &std::path::Path) -> Result<SoundBuffer, String> {
+/// The directory against which audio files' paths are made relative
+/// when no other is given, which is the current working directory
+pub(crate) fn default_base_directory() -> PathBuf {
+    std::env::current_dir().unwrap_or_default()
+}
+
+/// The location of an audio file which a patch refers to. The path is
+/// kept relative to a base directory when the file lies within it, so
+/// that a patch which is moved along with its audio files can still
+/// find them, and absolute otherwise or as a fallback.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioFilePath {
+    /// The path relative to the base directory, if the file is inside it
+    relative: Option<PathBuf>,
+
+    /// The path as last resolved
+    absolute: PathBuf,
+}
+
+impl AudioFilePath {
+    /// Refer to the given file, which is taken to be relative to the
+    /// given base directory if it isn't absolute
+    pub(crate) fn new(path: &Path, base_directory: &Path) -> AudioFilePath {
+        let absolute = base_directory.join(path);
+        let relative = absolute
+            .strip_prefix(base_directory)
+            .ok()
+            .map(Path::to_path_buf);
+        AudioFilePath { relative, absolute }
+    }
+
+    pub fn absolute(&self) -> &Path {
+        &self.absolute
+    }
+
+    pub fn relative(&self) -> Option<&Path> {
+        self.relative.as_deref()
+    }
+
+    pub(crate) fn exists(&self) -> bool {
+        self.absolute.exists()
+    }
+
+    /// Find the file again after the patch may have moved, such that it
+    /// now lives in the given base directory. The relative path is tried
+    /// first, followed by the absolute path. If neither exists, the file
+    /// is assumed to have moved with the patch, if it was inside it.
+    pub(crate) fn resolve(&self, base_directory: &Path) -> AudioFilePath {
+        let moved = self.relative.as_ref().map(|p| base_directory.join(p));
+        match moved {
+            Some(moved) if moved.exists() || !self.absolute.exists() => AudioFilePath {
+                relative: self.relative.clone(),
+                absolute: moved,
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+impl Stashable for AudioFilePath {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.bool(self.relative.is_some());
+        if let Some(relative) = &self.relative {
+            stasher.string(&relative.to_string_lossy());
+        }
+        stasher.string(&self.absolute.to_string_lossy());
+    }
+}
+
+impl Unstashable for AudioFilePath {
+    fn unstash(unstasher: &mut Unstasher) -> Result<AudioFilePath, UnstashError> {
+        let relative = if unstasher.bool()? {
+            Some(PathBuf::from(unstasher.string()?))
+        } else {
+            None
+        };
+        let absolute = PathBuf::from(unstasher.string()?);
+        Ok(AudioFilePath { relative, absolute })
+    }
+}
+
+pub(crate) fn load_audio_file(path: &Path) -> Result<SoundBuffer, String> {
     // Open the media source
     let src = std::fs::File::open(&path).map_err(|_| "Failed to open file".to_string())?;
 
@@ -136,3 +220,54 @@ pub(crate) fn load_audio_file(path: &std::path::Path) -> Result<SoundBuffer, Str
 
     Ok(soundbuffer)
 }
+
+/// Write the given channels of audio to a wav file as 32-bit floating
+/// point samples at the program's sample rate. All channels must have
+/// the same length.
+pub(crate) fn save_wav_file(path: &Path, channels: &[&[f32]]) -> Result<(), String> {
+    let num_channels = channels.len();
+    if num_channels == 0 {
+        return Err("No channels to write".to_string());
+    }
+    let num_frames = channels[0].len();
+    if channels.iter().any(|c| c.len() != num_frames) {
+        return Err("Channels have different lengths".to_string());
+    }
+
+    const FORMAT_IEEE_FLOAT: u16 = 3;
+    let bytes_per_sample = std::mem::size_of::<f32>();
+    let block_align = (num_channels * bytes_per_sample) as u16;
+    let byte_rate = SAMPLE_FREQUENCY as u32 * block_align as u32;
+    let data_len = (num_frames * block_align as usize) as u32;
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(58 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(50 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    // Formats other than integer PCM have an extended format chunk
+    // and are followed by a fact chunk holding the number of frames
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&18_u32.to_le_bytes());
+    bytes.extend_from_slice(&FORMAT_IEEE_FLOAT.to_le_bytes());
+    bytes.extend_from_slice(&(num_channels as u16).to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_FREQUENCY as u32).to_le_bytes());
+    bytes.extend_from_slice(&byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&((bytes_per_sample * 8) as u16).to_le_bytes());
+    bytes.extend_from_slice(&0_u16.to_le_bytes());
+
+    bytes.extend_from_slice(b"fact");
+    bytes.extend_from_slice(&4_u32.to_le_bytes());
+    bytes.extend_from_slice(&(num_frames as u32).to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for i in 0..num_frames {
+        for channel in channels {
+            bytes.extend_from_slice(&channel[i].to_le_bytes());
+        }
+    }
+
+    std::fs::write(path, bytes).map_err(|e| format!("Failed to write file: {}", e))
+}
@@ -0,0 +1,214 @@
+use hashstash::{Order, Stashable, Stasher, UnstashError, Unstashable, Unstasher};
+
+/// How the value of an automation lane moves from one breakpoint
+/// to the next
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interpolation {
+    /// Keep the breakpoint's value until the next breakpoint, then jump
+    Step,
+
+    /// Move at a constant rate towards the next breakpoint's value
+    Linear,
+
+    /// Ease in and out of both breakpoints' values
+    Smooth,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 3] = [
+        Interpolation::Step,
+        Interpolation::Linear,
+        Interpolation::Smooth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Interpolation::Step => "step",
+            Interpolation::Linear => "linear",
+            Interpolation::Smooth => "smooth",
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Interpolation::Step => 0,
+            Interpolation::Linear => 1,
+            Interpolation::Smooth => 2,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<Interpolation> {
+        match x {
+            0 => Some(Interpolation::Step),
+            1 => Some(Interpolation::Linear),
+            2 => Some(Interpolation::Smooth),
+            _ => None,
+        }
+    }
+
+    /// Interpolate between the two values, where t is between 0 and 1
+    fn interpolate(self, from: f32, to: f32, t: f32) -> f32 {
+        let t = match self {
+            Interpolation::Step => 0.0,
+            Interpolation::Linear => t,
+            Interpolation::Smooth => t * t * (3.0 - 2.0 * t),
+        };
+        from + (to - from) * t
+    }
+}
+
+/// A point through which an automation lane passes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Breakpoint {
+    /// The position on the timeline, in bars
+    pub position: f64,
+
+    /// The value at that position
+    pub value: f32,
+
+    /// How the value moves from here to the next breakpoint
+    pub interpolation: Interpolation,
+}
+
+impl Breakpoint {
+    pub fn new(position: f64, value: f32, interpolation: Interpolation) -> Breakpoint {
+        Breakpoint {
+            position,
+            value,
+            interpolation,
+        }
+    }
+}
+
+/// A series of breakpoints describing how a single value changes
+/// over the timeline. Before the first breakpoint, the lane holds
+/// the first breakpoint's value, and after the last, it holds the
+/// last breakpoint's value.
+#[derive(Default)]
+pub struct AutomationLane {
+    /// Breakpoints sorted by position, with at most one per position
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl AutomationLane {
+    pub fn new() -> AutomationLane {
+        AutomationLane {
+            breakpoints: Vec::new(),
+        }
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Add a breakpoint, replacing any existing breakpoint at the same position
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        match self
+            .breakpoints
+            .binary_search_by(|b| b.position.total_cmp(&breakpoint.position))
+        {
+            Ok(i) => self.breakpoints[i] = breakpoint,
+            Err(i) => self.breakpoints.insert(i, breakpoint),
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) -> Breakpoint {
+        self.breakpoints.remove(index)
+    }
+
+    /// Move the breakpoint at the given index to a new position and value,
+    /// keeping its interpolation mode. Any other breakpoint already at the
+    /// new position is replaced. Returns the breakpoint's new index.
+    pub fn move_breakpoint(&mut self, index: usize, position: f64, value: f32) -> usize {
+        let interpolation = self.remove_breakpoint(index).interpolation;
+        self.add_breakpoint(Breakpoint::new(position, value, interpolation));
+        self.breakpoints
+            .binary_search_by(|b| b.position.total_cmp(&position))
+            .unwrap()
+    }
+
+    pub fn set_interpolation(&mut self, index: usize, interpolation: Interpolation) {
+        self.breakpoints[index].interpolation = interpolation;
+    }
+
+    /// The value of the lane at the given position in bars, or
+    /// None if the lane has no breakpoints
+    pub fn value_at(&self, position: f64) -> Option<f32> {
+        let next = self.breakpoints.partition_point(|b| b.position <= position);
+        self.value_between(next, position)
+    }
+
+    /// Fill the slice with the values of the lane, one per sample, starting
+    /// at the given position in bars and advancing by the given number of
+    /// bars per sample. The slice is left untouched if the lane has no
+    /// breakpoints.
+    pub fn fill(&self, dst: &mut [f32], start_position: f64, bars_per_sample: f64) {
+        if self.breakpoints.is_empty() {
+            return;
+        }
+        let mut next = self
+            .breakpoints
+            .partition_point(|b| b.position <= start_position);
+        for (i, x) in dst.iter_mut().enumerate() {
+            let position = start_position + i as f64 * bars_per_sample;
+            while next < self.breakpoints.len() && self.breakpoints[next].position <= position {
+                next += 1;
+            }
+            *x = self.value_between(next, position).unwrap();
+        }
+    }
+
+    /// The value at the given position, where `next` is the index of the
+    /// first breakpoint after that position
+    fn value_between(&self, next: usize, position: f64) -> Option<f32> {
+        if next == 0 {
+            return self.breakpoints.first().map(|b| b.value);
+        }
+        let previous = &self.breakpoints[next - 1];
+        let Some(following) = self.breakpoints.get(next) else {
+            return Some(previous.value);
+        };
+        let t = (position - previous.position) / (following.position - previous.position);
+        Some(
+            previous
+                .interpolation
+                .interpolate(previous.value, following.value, t as f32),
+        )
+    }
+}
+
+impl Stashable for Breakpoint {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.f64(self.position);
+        stasher.f32(self.value);
+        stasher.u8(self.interpolation.to_u8());
+    }
+}
+
+impl Unstashable for Breakpoint {
+    fn unstash(unstasher: &mut Unstasher) -> Result<Breakpoint, UnstashError> {
+        let position = unstasher.f64()?;
+        let value = unstasher.f32()?;
+        let interpolation =
+            Interpolation::from_u8(unstasher.u8()?).ok_or(UnstashError::Corrupted)?;
+        Ok(Breakpoint {
+            position,
+            value,
+            interpolation,
+        })
+    }
+}
+
+impl Stashable for AutomationLane {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.array_of_objects_slice(&self.breakpoints, Order::Ordered);
+    }
+}
+
+impl Unstashable for AutomationLane {
+    fn unstash(unstasher: &mut Unstasher) -> Result<AutomationLane, UnstashError> {
+        Ok(AutomationLane {
+            breakpoints: unstasher.array_of_objects_vec()?,
+        })
+    }
+}
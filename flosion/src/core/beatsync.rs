@@ -0,0 +1,121 @@
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::core::{soundchunk::CHUNK_SIZE, tempo::Tempo};
+
+/// How often a processor is retriggered in time with the music
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RetriggerInterval {
+    Never,
+    Beat,
+    Bar,
+}
+
+impl RetriggerInterval {
+    pub const ALL: [RetriggerInterval; 3] = [
+        RetriggerInterval::Never,
+        RetriggerInterval::Beat,
+        RetriggerInterval::Bar,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RetriggerInterval::Never => "never",
+            RetriggerInterval::Beat => "beat",
+            RetriggerInterval::Bar => "bar",
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            RetriggerInterval::Never => 0,
+            RetriggerInterval::Beat => 1,
+            RetriggerInterval::Bar => 2,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<RetriggerInterval> {
+        match x {
+            0 => Some(RetriggerInterval::Never),
+            1 => Some(RetriggerInterval::Beat),
+            2 => Some(RetriggerInterval::Bar),
+            _ => None,
+        }
+    }
+}
+
+/// Retriggers a stateful processor on every beat or bar boundary, as
+/// counted from the moment the processor started at the given tempo.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BeatSync {
+    pub tempo: Tempo,
+    pub interval: RetriggerInterval,
+}
+
+impl BeatSync {
+    pub fn new() -> BeatSync {
+        BeatSync {
+            tempo: Tempo::new(120.0, 4),
+            interval: RetriggerInterval::Never,
+        }
+    }
+
+    fn samples_per_interval(&self) -> Option<f64> {
+        let samples = match self.interval {
+            RetriggerInterval::Never => return None,
+            RetriggerInterval::Beat => self.tempo.samples_per_beat(),
+            RetriggerInterval::Bar => self.tempo.samples_per_bar(),
+        };
+        // Retriggering more than once per sample is meaningless
+        if samples.is_finite() && samples >= 1.0 {
+            Some(samples)
+        } else {
+            None
+        }
+    }
+
+    /// Find which samples of the chunk beginning at the given sample
+    /// are retriggered. A sample is retriggered if it is the first at
+    /// or after a boundary, other than the very first sample, at which
+    /// the processor is freshly started anyway.
+    pub fn retriggered_samples(&self, first_sample: usize) -> [bool; CHUNK_SIZE] {
+        let mut retriggered = [false; CHUNK_SIZE];
+        let Some(interval) = self.samples_per_interval() else {
+            return retriggered;
+        };
+        for (i, r) in retriggered.iter_mut().enumerate() {
+            let sample = first_sample + i;
+            if sample == 0 {
+                continue;
+            }
+            *r = (sample as f64 / interval).floor() > ((sample - 1) as f64 / interval).floor();
+        }
+        retriggered
+    }
+}
+
+impl Default for BeatSync {
+    fn default() -> BeatSync {
+        BeatSync::new()
+    }
+}
+
+impl Stashable for BeatSync {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.f64(self.tempo.beats_per_minute);
+        stasher.u32(self.tempo.beats_per_bar);
+        stasher.u8(self.interval.to_u8());
+    }
+}
+
+impl UnstashableInplace for BeatSync {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.f64_inplace(&mut self.tempo.beats_per_minute)?;
+        unstasher.u32_inplace(&mut self.tempo.beats_per_bar)?;
+        let interval =
+            RetriggerInterval::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.interval = interval;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::core::samplefrequency::SAMPLE_FREQUENCY;
+
+/// Coefficients of a second-order IIR ("biquad") filter, normalized
+/// such that the leading feedback coefficient is 1. Designs follow
+/// Robert Bristow-Johnson's Audio EQ Cookbook.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadCoefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl BiquadCoefficients {
+    /// A bandpass filter centred on the given frequency in Hz, whose
+    /// gain at the centre frequency is exactly 1
+    pub fn bandpass(frequency: f32, q: f32) -> BiquadCoefficients {
+        let w0 = std::f32::consts::TAU * frequency / SAMPLE_FREQUENCY as f32;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        BiquadCoefficients {
+            b0: alpha / a0,
+            b1: 0.0,
+            b2: -alpha / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+
+    /// A lowpass filter with the given cutoff frequency in Hz. A q of
+    /// 1/sqrt(2) gives a maximally flat passband which is 3 dB down
+    /// at the cutoff.
+    pub fn lowpass(frequency: f32, q: f32) -> BiquadCoefficients {
+        let w0 = std::f32::consts::TAU * frequency / SAMPLE_FREQUENCY as f32;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos_w0 = w0.cos();
+        let a0 = 1.0 + alpha;
+        BiquadCoefficients {
+            b0: 0.5 * (1.0 - cos_w0) / a0,
+            b1: (1.0 - cos_w0) / a0,
+            b2: 0.5 * (1.0 - cos_w0) / a0,
+            a1: -2.0 * cos_w0 / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+
+    /// The complex gain of the filter at the given frequency in Hz,
+    /// as a (real, imaginary) pair
+    pub fn frequency_response(&self, frequency: f32) -> (f32, f32) {
+        let w = std::f32::consts::TAU * frequency / SAMPLE_FREQUENCY as f32;
+        let (sin_w, cos_w) = w.sin_cos();
+        let (sin_2w, cos_2w) = (2.0 * w).sin_cos();
+        let num_re = self.b0 + self.b1 * cos_w + self.b2 * cos_2w;
+        let num_im = -(self.b1 * sin_w + self.b2 * sin_2w);
+        let den_re = 1.0 + self.a1 * cos_w + self.a2 * cos_2w;
+        let den_im = -(self.a1 * sin_w + self.a2 * sin_2w);
+        let den_sqr = den_re * den_re + den_im * den_im;
+        (
+            (num_re * den_re + num_im * den_im) / den_sqr,
+            (num_im * den_re - num_re * den_im) / den_sqr,
+        )
+    }
+}
+
+/// The past inputs and outputs of a single biquad filter. Coefficients
+/// are passed separately so that they can change between samples without
+/// discarding the filter's history.
+#[derive(Clone, Copy, Default)]
+pub struct BiquadState {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl BiquadState {
+    pub fn new() -> BiquadState {
+        BiquadState::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = BiquadState::default();
+    }
+
+    /// Filter a single sample
+    pub fn process(&mut self, coefficients: &BiquadCoefficients, x: f32) -> f32 {
+        let c = coefficients;
+        let y = c.b0 * x + c.b1 * self.x1 + c.b2 * self.x2 - c.a1 * self.y1 - c.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
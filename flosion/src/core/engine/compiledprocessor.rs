@@ -1,4 +1,5 @@
 use std::{
+    any::TypeId,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -9,13 +10,13 @@ use crate::core::{
     jit::argumentstack::{ArgumentStack, ArgumentStackView},
     sound::{
         context::{AudioContext, AudioStack},
-        soundinput::{InputContext, InputTiming, SoundInputLocation},
+        soundinput::{DisconnectBehavior, InputContext, InputTiming, SoundInputLocation},
         soundprocessor::{
             CompiledComponentVisitor, CompiledProcessorComponent, ProcessorTiming, SoundProcessor,
             SoundProcessorId, StartOver, StreamStatus,
         },
     },
-    soundchunk::SoundChunk,
+    soundchunk::{SoundChunk, CHUNK_SIZE},
 };
 
 use super::{
@@ -96,6 +97,15 @@ pub(crate) trait AnyCompiledProcessorData<'ctx>: Send {
 
     fn visit(&self, visitor: &mut dyn CompiledComponentVisitor);
 
+    /// Take over from the given compiled processor, which this one replaces
+    /// after the graph was edited. This has no effect unless the previous
+    /// processor belongs to the same sound processor.
+    fn take_over(&mut self, previous: &mut dyn AnyCompiledProcessorData<'ctx>);
+
+    /// The type of the sound processor, used to check that a processor
+    /// being taken over from has the same compiled type
+    fn processor_type(&self) -> TypeId;
+
     /// Used for book-keeping optimizations, e.g. to avoid visiting shared nodes twice
     /// and because comparing trait objects (fat pointers) for equality is fraught
     fn address(&self) -> *const ();
@@ -134,6 +144,22 @@ impl<'ctx, T: 'static + SoundProcessor> AnyCompiledProcessorData<'ctx>
         self.processor.visit(visitor);
     }
 
+    fn take_over(&mut self, previous: &mut dyn AnyCompiledProcessorData<'ctx>) {
+        if previous.id() != self.id || previous.processor_type() != TypeId::of::<T>() {
+            return;
+        }
+        let previous: *mut dyn AnyCompiledProcessorData<'ctx> = previous;
+        // SAFETY: CompiledProcessorData is the only implementation of
+        // AnyCompiledProcessorData, and the previous processor's type
+        // was just found to be T
+        let previous = unsafe { &mut *(previous as *mut CompiledProcessorData<'ctx, T>) };
+        self.processor.take_over(&mut previous.processor);
+    }
+
+    fn processor_type(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
     fn address(&self) -> *const () {
         let ptr: *const CompiledProcessorData<T> = self;
         ptr as *const ()
@@ -180,6 +206,11 @@ impl<'ctx> UniqueCompiledProcessor<'ctx> {
         self.processor.start_over();
     }
 
+    /// Take over from the given processor, which this one replaces
+    fn take_over(&mut self, previous: &mut UniqueCompiledProcessor<'ctx>) {
+        self.processor.take_over(&mut *previous.processor);
+    }
+
     fn process_audio(
         &mut self,
         dst: &mut SoundChunk,
@@ -220,6 +251,12 @@ impl<'ctx> SharedCompiledProcessorCache<'ctx> {
         &*self.processor
     }
 
+    /// The most recent chunk of audio produced by the processor
+    #[cfg(test)]
+    pub(crate) fn cached_output(&self) -> &SoundChunk {
+        &self.cached_output
+    }
+
     /// Register a new sound input that co-owns the cache. This sound input
     /// will be expected to call on the shared node to process audio in step
     /// with the rest of the group of inputs that own it.
@@ -398,6 +435,15 @@ impl<'ctx> SharedCompiledProcessor<'ctx> {
         *stream_status
     }
 
+    /// Take over from the given processor, which this one replaces. This
+    /// locks both caches, and is only done while editing the compiled graph.
+    pub(crate) fn take_over(&mut self, previous: &mut SharedCompiledProcessor<'ctx>) {
+        debug_assert!(!Arc::ptr_eq(&self.cache, &previous.cache));
+        let mut data = self.cache.lock();
+        let mut previous_data = previous.cache.lock();
+        data.processor.take_over(&mut *previous_data.processor);
+    }
+
     /// Make the processor start over
     fn start_over(&mut self) {
         let mut data = self.cache.lock();
@@ -450,20 +496,45 @@ pub struct CompiledSoundInputNode<'ctx> {
     location: SoundInputLocation,
     timing: InputTiming,
     link: CompiledProcessorLink<'ctx>,
+    disconnect_behavior: DisconnectBehavior,
+
+    /// The most recent chunk produced through the link, kept for
+    /// fading out in case the link is replaced with an empty one,
+    /// including by a different node after the graph was edited
+    held_chunk: SoundChunk,
+
+    /// Whether the held chunk has yet to be faded out
+    holding: bool,
+
+    /// Delays the input's audio to line up with other inputs of the same
+    /// processor whose audio has more latency, if needed
+    compensation: Option<CompensationDelay>,
 }
 
 impl<'ctx> CompiledSoundInputNode<'ctx> {
     /// Compile a new CompiledSoundInputBranch.
     pub(crate) fn new<'a>(
         location: SoundInputLocation,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
         link: CompiledProcessorLink<'ctx>,
     ) -> CompiledSoundInputNode<'ctx> {
+        // The input timing's speed is that of the processors connected
+        // to the input relative to the processor owning it, which is
+        // the reciprocal of how fast the owner's time appears to them
+        let mut timing = InputTiming::default();
+        timing.set_time_speed(1.0 / speed);
+
         // Create empty link first and then swap in the given
         // link, in order to reuse shared caching logic
         let mut compiled_input = CompiledSoundInputNode {
             location,
-            timing: InputTiming::default(),
+            timing,
             link: CompiledProcessorLink::Empty,
+            disconnect_behavior,
+            held_chunk: SoundChunk::new(),
+            holding: false,
+            compensation: None,
         };
 
         compiled_input.swap_link(link);
@@ -471,6 +542,20 @@ impl<'ctx> CompiledSoundInputNode<'ctx> {
         compiled_input
     }
 
+    /// Delay all audio passing through the input by the given number of
+    /// samples, to compensate for latency along parallel paths
+    pub(crate) fn with_latency_compensation(
+        mut self,
+        samples: usize,
+    ) -> CompiledSoundInputNode<'ctx> {
+        self.compensation = if samples > 0 {
+            Some(CompensationDelay::new(samples))
+        } else {
+            None
+        };
+        self
+    }
+
     /// Access the input timing
     // TODO: consider hiding inputtiming and publicly re-exposing only those functions which make sense
     pub(crate) fn timing(&self) -> &InputTiming {
@@ -511,6 +596,14 @@ impl<'ctx> CompiledSoundInputNode<'ctx> {
     /// processor.
     pub(crate) fn start_over_at(&mut self, sample_offset: usize) {
         self.timing.start_over(sample_offset);
+        // A disconnected input keeps holding, so that audio which was
+        // playing before the graph was edited still gets faded out
+        if !matches!(self.link, CompiledProcessorLink::Empty) {
+            self.holding = false;
+        }
+        if let Some(compensation) = &mut self.compensation {
+            compensation.reset();
+        }
         match &mut self.link {
             CompiledProcessorLink::Unique(proc) => proc.start_over(),
             CompiledProcessorLink::Shared(proc) => proc.start_over(),
@@ -518,6 +611,23 @@ impl<'ctx> CompiledSoundInputNode<'ctx> {
         }
     }
 
+    /// Take over from the given node of the same sound input, which this
+    /// one replaces, so that audio which was playing through that node is
+    /// faded out if this node's link is empty. Any uniquely owned processor
+    /// takes over from the one it replaces in turn.
+    pub(crate) fn take_over(&mut self, previous: &mut CompiledSoundInputNode<'ctx>) {
+        debug_assert!(previous.location == self.location);
+        if self.disconnect_behavior == DisconnectBehavior::HoldAndFade {
+            self.held_chunk = previous.held_chunk;
+            self.holding = previous.holding;
+        }
+        if let (CompiledProcessorLink::Unique(proc), CompiledProcessorLink::Unique(previous_proc)) =
+            (&mut self.link, &mut previous.link)
+        {
+            proc.take_over(previous_proc);
+        }
+    }
+
     /// Process the next chunk of audio
     pub(crate) fn step(&mut self, dst: &mut SoundChunk, ctx: InputContext) -> StreamStatus {
         // TODO: validate context for pushed arguments? see compiled expression
@@ -530,13 +640,13 @@ impl<'ctx> CompiledSoundInputNode<'ctx> {
             dst.silence();
             return StreamStatus::Done;
         }
-        let release_pending = self.timing.pending_release().is_some();
+        let pending_release = self.timing.pending_release();
 
         let stack = ctx
             .audio_context()
             .push_frame(self.location.input(), &mut self.timing);
 
-        let status = match &mut self.link {
+        let mut status = match &mut self.link {
             CompiledProcessorLink::Unique(proc) => proc.process_audio(
                 dst,
                 stack,
@@ -550,13 +660,29 @@ impl<'ctx> CompiledSoundInputNode<'ctx> {
                 ctx.argument_stack(),
             ),
             CompiledProcessorLink::Empty => {
+                if self.holding {
+                    self.holding = false;
+                    fade_out_reversed(&self.held_chunk, dst);
+                    return StreamStatus::Playing;
+                }
                 dst.silence();
                 self.timing.mark_as_done();
                 StreamStatus::Done
             }
         };
+        if let Some(compensation) = &mut self.compensation {
+            status = compensation.process(dst, status);
+        }
+        if self.disconnect_behavior == DisconnectBehavior::HoldAndFade {
+            self.held_chunk = *dst;
+            self.holding = status == StreamStatus::Playing;
+        }
         let was_released = self.timing.was_released();
-        if release_pending && !was_released {
+        if let (Some(offset), false) = (pending_release, was_released) {
+            // The processor doesn't respond to being released, so it
+            // stops abruptly at exactly the requested sample
+            dst.l[offset..].fill(0.0);
+            dst.r[offset..].fill(0.0);
             self.timing.mark_as_done();
             return StreamStatus::Done;
         }
@@ -564,6 +690,75 @@ impl<'ctx> CompiledSoundInputNode<'ctx> {
     }
 }
 
+/// A fixed delay applied to the audio passing through a sound input
+struct CompensationDelay {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    index: usize,
+
+    /// The number of samples still held in the delay after the input
+    /// finished, which are played out before it is considered done
+    tail: usize,
+}
+
+impl CompensationDelay {
+    fn new(samples: usize) -> CompensationDelay {
+        CompensationDelay {
+            left: vec![0.0; samples],
+            right: vec![0.0; samples],
+            index: 0,
+            tail: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.left.fill(0.0);
+        self.right.fill(0.0);
+        self.index = 0;
+        self.tail = 0;
+    }
+
+    /// Delay the chunk in place. Returns the status of the delayed
+    /// audio given that of the chunk, which keeps playing until the
+    /// last delayed samples have been played out.
+    fn process(&mut self, chunk: &mut SoundChunk, status: StreamStatus) -> StreamStatus {
+        let len = self.left.len();
+        for (l, r) in chunk.l.iter_mut().zip(chunk.r.iter_mut()) {
+            std::mem::swap(l, &mut self.left[self.index]);
+            std::mem::swap(r, &mut self.right[self.index]);
+            self.index = (self.index + 1) % len;
+        }
+        match status {
+            StreamStatus::Playing => {
+                self.tail = len;
+                StreamStatus::Playing
+            }
+            StreamStatus::Done if self.tail > 0 => {
+                self.tail = self.tail.saturating_sub(CHUNK_SIZE);
+                StreamStatus::Playing
+            }
+            StreamStatus::Done => StreamStatus::Done,
+        }
+    }
+}
+
+/// Write the given chunk into dst in reverse, fading linearly from
+/// full volume to silence
+fn fade_out_reversed(chunk: &SoundChunk, dst: &mut SoundChunk) {
+    for c in 0..SoundChunk::NUM_CHANNELS {
+        let src = chunk.channel(c);
+        let n = src.len();
+        for (i, (d, s)) in dst
+            .channel_mut(c)
+            .iter_mut()
+            .zip(src.iter().rev())
+            .enumerate()
+        {
+            *d = s * (1.0 - i as f32 / n as f32);
+        }
+    }
+}
+
 impl<'ctx> Drop for CompiledSoundInputNode<'ctx> {
     fn drop(&mut self) {
         // Remove input id from shared node target if needed
@@ -1,11 +1,55 @@
-use crate::core::sound::soundprocessor::SoundProcessorId;
+use crate::core::sound::soundprocessor::{CompiledComponentVisitor, SoundProcessorId};
 
 use super::{
-    compiledprocessor::SharedCompiledProcessor,
+    compiledprocessor::{
+        AnyCompiledProcessorData, CompiledProcessorLink, CompiledSoundInputNode,
+        SharedCompiledProcessor,
+    },
     compiledsoundgraphedit::CompiledSoundGraphEdit,
     garbage::{Garbage, GarbageChute},
 };
 
+/// Count the compiled processor nodes reachable from the given static
+/// processors, e.g. those of a compiled sound graph or those about to be
+/// added to one. Shared nodes are counted once per input that they are
+/// reached through.
+pub(super) fn count_processor_nodes<'a, 'ctx: 'a>(
+    static_processors: impl IntoIterator<Item = &'a SharedCompiledProcessor<'ctx>>,
+) -> usize {
+    struct Visitor {
+        count: usize,
+    }
+
+    impl Visitor {
+        fn processor(&mut self, processor: &dyn AnyCompiledProcessorData) {
+            self.count += 1;
+            processor.visit(self);
+        }
+    }
+
+    impl CompiledComponentVisitor for Visitor {
+        fn input_node(&mut self, input: &CompiledSoundInputNode) {
+            match input.link() {
+                CompiledProcessorLink::Unique(node) => self.processor(node.processor()),
+                CompiledProcessorLink::Shared(node) => {
+                    self.processor(node.borrow_cache().processor())
+                }
+                CompiledProcessorLink::Empty => (),
+            }
+        }
+    }
+
+    let mut visitor = Visitor { count: 0 };
+
+    for node in static_processors {
+        if node.is_entry_point() {
+            visitor.processor(node.borrow_cache().processor());
+        }
+    }
+
+    visitor.count
+}
+
 /// A directed acyclic graph of nodes representing invidual sound processors,
 /// their state, and any cached intermediate outputs. Static processors are
 /// always at the top of each sub-graph, and represent a top-level view into
@@ -29,6 +73,14 @@ impl<'ctx> CompiledSoundGraph<'ctx> {
         &self.static_processors
     }
 
+    /// Count the compiled processor nodes reachable from the static
+    /// processors. Shared nodes are counted once per input that
+    /// they are reached through.
+    #[cfg(test)]
+    pub(super) fn num_processor_nodes(&self) -> usize {
+        count_processor_nodes(&self.static_processors)
+    }
+
     /// Apply an edit, tossing any stale and unwanted
     /// data down the given garbage chute if it could involve heap
     /// deallocation to drop directly.
@@ -44,7 +96,15 @@ impl<'ctx> CompiledSoundGraph<'ctx> {
             CompiledSoundGraphEdit::RemoveStaticSoundProcessor(spid) => {
                 self.remove_static_processor(spid, garbage_chute)
             }
+            CompiledSoundGraphEdit::ReplaceStaticSoundProcessor(node) => {
+                self.replace_static_processor(node, garbage_chute)
+            }
             CompiledSoundGraphEdit::DebugInspection(f) => f(self),
+            // Scratch space is kept by the SoundEngine itself, which handles
+            // this edit before it gets here. Otherwise, it is of no use.
+            CompiledSoundGraphEdit::ReserveScratchSpace(reservation) => {
+                garbage_chute.send_box(reservation)
+            }
         }
     }
 
@@ -75,6 +135,30 @@ impl<'ctx> CompiledSoundGraph<'ctx> {
         let old_node = self.static_processors.remove(i);
         old_node.toss(garbage_chute);
     }
+
+    /// Replace a previously added static processor node with a new node
+    /// for the same processor, which takes over from the old one.
+    fn replace_static_processor(
+        &mut self,
+        mut node: SharedCompiledProcessor<'ctx>,
+        garbage_chute: &GarbageChute<'ctx>,
+    ) {
+        debug_assert_eq!(
+            self.static_processors
+                .iter()
+                .filter(|n| n.id() == node.id())
+                .count(),
+            1
+        );
+        let old_node = self
+            .static_processors
+            .iter_mut()
+            .find(|n| n.id() == node.id())
+            .unwrap();
+        node.take_over(old_node);
+        std::mem::swap(old_node, &mut node);
+        node.toss(garbage_chute);
+    }
 }
 
 impl<'ctx> Garbage<'ctx> for CompiledSoundGraph<'ctx> {
@@ -1,6 +1,9 @@
 use crate::core::sound::soundprocessor::SoundProcessorId;
 
-use super::{compiledprocessor::SharedCompiledProcessor, compiledsoundgraph::CompiledSoundGraph};
+use super::{
+    compiledprocessor::SharedCompiledProcessor, compiledsoundgraph::CompiledSoundGraph,
+    scratcharena::ScratchReservation,
+};
 
 /// Edits to be made to the compiled sound graph on the audio thread. These are heavily
 /// focused on efficiently inserting pre-allocated data, rather
@@ -42,6 +45,18 @@ pub(crate) enum CompiledSoundGraphEdit<'ctx> {
     /// processors it may depend on.
     RemoveStaticSoundProcessor(SoundProcessorId),
 
+    /// Replace the node of a static sound processor which is already present
+    /// with a newly compiled one, as with removing the old node and adding the
+    /// new one. In between, the new node takes over whatever state must carry
+    /// on from the old node, such as the last chunk of audio which each of
+    /// its sound inputs played, by moving it across on the audio thread.
+    ReplaceStaticSoundProcessor(SharedCompiledProcessor<'ctx>),
+
+    /// Add pre-allocated scratch space to the SoundEngine's scratch arena
+    /// so that it can grow to suit the new graph without allocating on the
+    /// audio thread. This doesn't edit the compiled sound graph itself.
+    ReserveScratchSpace(Box<ScratchReservation>),
+
     /// Debugging aid. Calls the given function with the current compiled sound graph,
     /// e.g. to test its invariants and whether it matches a desired state.
     /// Does not perform any actual edits and not intended to be used beyond
@@ -1,18 +1,24 @@
 use crate::core::{
     engine::{compiledsoundgraph::CompiledSoundGraph, validation::verify_compiled_sound_graph},
     jit::cache::JitCache,
-    sound::soundgraph::SoundGraph,
+    sound::{soundgraph::SoundGraph, soundprocessor::SoundProcessorId},
 };
 
 use super::{
-    compiledprocessor::CompiledProcessorLink, compiledsoundgraphedit::CompiledSoundGraphEdit,
+    compiledsoundgraphedit::CompiledSoundGraphEdit,
+    soundengine::{BlockSize, PanicSwitch, TestTone},
     soundgraphcompiler::SoundGraphCompiler,
+    voicelimit::VoiceLimit,
 };
 
 pub(crate) fn diff_sound_graph<'ctx>(
     graph_before: &SoundGraph,
     graph_after: &SoundGraph,
     jit_cache: &JitCache<'ctx>,
+    panic_switch: &PanicSwitch,
+    test_tone: &TestTone,
+    voice_limit: &VoiceLimit,
+    block_size: &BlockSize,
 ) -> Vec<CompiledSoundGraphEdit<'ctx>> {
     let mut edits = Vec::new();
 
@@ -35,20 +41,37 @@ pub(crate) fn diff_sound_graph<'ctx>(
     // }
 
     // TODO: diff current and new topology and create a list of fine-grained state graph edits
-    // HACK deleting everything and then adding it back
+    // HACK deleting everything and then adding it back. Static processors
+    // which are in both graphs are replaced instead, so that their new
+    // nodes can take over from the old ones
+    let is_static_in = |graph: &SoundGraph, id: SoundProcessorId| {
+        graph
+            .sound_processors()
+            .get(&id)
+            .is_some_and(|proc| proc.is_static())
+    };
     for proc in graph_before.sound_processors().values() {
-        if proc.is_static() {
+        if proc.is_static() && !is_static_in(graph_after, proc.id()) {
             edits.push(CompiledSoundGraphEdit::RemoveStaticSoundProcessor(
                 proc.id(),
             ));
         }
     }
-    // all should be deleted now
+    // only those being replaced should remain now
     #[cfg(debug_assertions)]
     {
+        let replaced: Vec<SoundProcessorId> = graph_after
+            .sound_processors()
+            .values()
+            .filter(|proc| proc.is_static() && is_static_in(graph_before, proc.id()))
+            .map(|proc| proc.id())
+            .collect();
         edits.push(CompiledSoundGraphEdit::DebugInspection(Box::new(
-            |sg: &CompiledSoundGraph<'ctx>| {
-                debug_assert!(sg.static_processors().is_empty());
+            move |sg: &CompiledSoundGraph<'ctx>| {
+                debug_assert!(sg
+                    .static_processors()
+                    .iter()
+                    .all(|node| replaced.contains(&node.id())));
             },
         )));
     }
@@ -57,15 +80,19 @@ pub(crate) fn diff_sound_graph<'ctx>(
     // Note that SoundGraphCompiler will cache and reuse shared static processor
     // nodes, and so no extra book-keeping is needed here to ensure
     // that static processors are allocated only once and reused.
-    let mut compiler = SoundGraphCompiler::new(&graph_after, jit_cache);
+    let mut compiler = SoundGraphCompiler::new(&graph_after, jit_cache)
+        .with_panic_switch(panic_switch.clone())
+        .with_test_tone(test_tone.clone())
+        .with_voice_limit(voice_limit.clone())
+        .with_block_size(block_size.clone());
     for proc in graph_after.sound_processors().values() {
         if proc.is_static() {
-            let CompiledProcessorLink::Shared(node) =
-                compiler.compile_sound_processor(Some(proc.id()))
-            else {
-                panic!("Static sound processors must compile to shared nodes");
-            };
-            edits.push(CompiledSoundGraphEdit::AddStaticSoundProcessor(node));
+            let node = compiler.compile_static_processor(proc.id());
+            if is_static_in(graph_before, proc.id()) {
+                edits.push(CompiledSoundGraphEdit::ReplaceStaticSoundProcessor(node));
+            } else {
+                edits.push(CompiledSoundGraphEdit::AddStaticSoundProcessor(node));
+            }
         }
     }
 
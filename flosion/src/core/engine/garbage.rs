@@ -1,9 +1,15 @@
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    mpsc::{sync_channel, Receiver, SyncSender},
-    Arc,
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
 };
 
+use parking_lot::Mutex;
+
 /// Garbage is a trait for types consisting of things that may be
 /// expensive to dispove of. The single method `toss` is intended
 /// to consume unpack that object and send those which need
@@ -38,7 +44,17 @@ enum WrappedDroppable<'ctx> {
 /// original thread.
 pub(crate) struct GarbageChute<'ctx> {
     sender: SyncSender<WrappedDroppable<'ctx>>,
+    /// Items which didn't fit into the channel because the receiving
+    /// end is falling behind. These are held onto and sent again
+    /// later rather than blocking or panicking. Space for as many items
+    /// as the channel holds is allocated up front and the overflow
+    /// never grows beyond that, so that overflowing never allocates.
+    overflow: RefCell<VecDeque<WrappedDroppable<'ctx>>>,
+    /// Where the overflow is left for the disposer when the chute
+    /// is dropped, so that its items aren't dropped along with it
+    leftovers: Arc<Mutex<VecDeque<WrappedDroppable<'ctx>>>>,
     backlog: Arc<AtomicUsize>,
+    leaked: Arc<AtomicUsize>,
     capacity: usize,
 }
 
@@ -47,21 +63,56 @@ impl<'ctx> GarbageChute<'ctx> {
     /// When the garbage is cleared, the inner item will be dropped
     /// immediately.
     pub(crate) fn send_box(&self, item: Box<dyn 'ctx + Droppable>) {
-        self.sender.try_send(WrappedDroppable::Box(item)).unwrap();
-        let backlog = self.backlog.fetch_add(1, Ordering::Relaxed);
-        if backlog * 4 > self.capacity {
-            println!(
-                "Warning: garbage chute is filling up: {} undisposed items",
-                backlog
-            );
-        }
+        self.send(WrappedDroppable::Box(item));
     }
 
     /// Send an item which lives in an Arc down the chute
     /// When the garbage is cleared, the inner item will be dropped
     /// immediately only if the Arc holds the last strong reference.
     pub(crate) fn send_arc(&self, item: Arc<dyn 'ctx + Sync + Droppable>) {
-        self.sender.try_send(WrappedDroppable::Arc(item)).unwrap();
+        self.send(WrappedDroppable::Arc(item));
+    }
+
+    /// Try again to send any items which previously didn't fit into
+    /// the channel. This is intended to be called regularly, e.g.
+    /// once per chunk, so that overflowing items don't linger.
+    pub(crate) fn retry_overflow(&self) {
+        let mut overflow = self.overflow.borrow_mut();
+        while let Some(item) = overflow.pop_front() {
+            match self.sender.try_send(item) {
+                Ok(()) => (),
+                Err(TrySendError::Full(item)) | Err(TrySendError::Disconnected(item)) => {
+                    overflow.push_front(item);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send an item down the chute, or hold onto it until the next
+    /// retry if the chute is full. Items are always sent in order.
+    /// If the overflow is full as well, the item is leaked instead,
+    /// since it can neither be dropped here nor held onto without
+    /// allocating. Leaked items are counted by the disposer.
+    fn send(&self, item: WrappedDroppable<'ctx>) {
+        self.retry_overflow();
+        let mut overflow = self.overflow.borrow_mut();
+        let item = if overflow.is_empty() {
+            match self.sender.try_send(item) {
+                Ok(()) => None,
+                Err(TrySendError::Full(item)) | Err(TrySendError::Disconnected(item)) => Some(item),
+            }
+        } else {
+            Some(item)
+        };
+        if let Some(item) = item {
+            if overflow.len() >= self.capacity {
+                std::mem::forget(item);
+                self.leaked.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            overflow.push_back(item);
+        }
         let backlog = self.backlog.fetch_add(1, Ordering::Relaxed);
         if backlog * 4 > self.capacity {
             println!(
@@ -72,23 +123,72 @@ impl<'ctx> GarbageChute<'ctx> {
     }
 }
 
+impl<'ctx> Drop for GarbageChute<'ctx> {
+    fn drop(&mut self) {
+        // Leave anything which never made it down the chute for the
+        // disposer, rather than dropping it here
+        let overflow = std::mem::take(self.overflow.get_mut());
+        *self.leftovers.lock() = overflow;
+    }
+}
+
 /// The receiving end of a GarbageChute. Its only responsibility is to
-/// periodically be cleared, thereby disposing of and dropping everything
-/// that has been sent down the chute so far.
+/// periodically be cleared, thereby disposing of and dropping what
+/// has been sent down the chute so far.
 pub(crate) struct GarbageDisposer<'ctx> {
     receiver: Receiver<WrappedDroppable<'ctx>>,
+    leftovers: Arc<Mutex<VecDeque<WrappedDroppable<'ctx>>>>,
     backlog: Arc<AtomicUsize>,
+    leaked: Arc<AtomicUsize>,
 }
 
 impl<'ctx> GarbageDisposer<'ctx> {
-    /// Dispose of and drop everything that has come down the chute so far.
-    pub(crate) fn clear(&self) {
+    /// Dispose of and drop everything that has come down the chute so far,
+    /// before returning. Returns the number of items disposed of.
+    pub(crate) fn flush(&self) -> usize {
+        self.clear_at_most(usize::MAX)
+    }
+
+    /// Dispose of and drop at most the given number of items in the order
+    /// they came down the chute, leaving the rest for later. This bounds
+    /// the time spent disposing when a large batch of garbage arrives at
+    /// once. Returns the number of items disposed of.
+    pub(crate) fn clear_at_most(&self, max_items: usize) -> usize {
         let mut count: usize = 0;
-        while let Ok(item) = self.receiver.try_recv() {
+        while count < max_items {
+            let Ok(item) = self.receiver.try_recv() else {
+                break;
+            };
             std::mem::drop(item);
             count += 1;
         }
+        if count < max_items {
+            // Whatever the chute was still holding onto when it was
+            // dropped came after everything in the channel
+            let mut leftovers = self.leftovers.lock();
+            while count < max_items {
+                let Some(item) = leftovers.pop_front() else {
+                    break;
+                };
+                std::mem::drop(item);
+                count += 1;
+            }
+        }
         self.backlog.fetch_sub(count, Ordering::Relaxed);
+        count
+    }
+
+    /// The number of items which have come down the chute and
+    /// not yet been disposed of, including any which the chute is
+    /// still holding onto because they didn't fit
+    pub(crate) fn pending(&self) -> usize {
+        self.backlog.load(Ordering::Relaxed)
+    }
+
+    /// The number of items which were leaked because neither the chute
+    /// nor its overflow had room for them
+    pub(crate) fn leaked(&self) -> usize {
+        self.leaked.load(Ordering::Relaxed)
     }
 }
 
@@ -96,15 +196,22 @@ impl<'ctx> GarbageDisposer<'ctx> {
 pub(crate) fn new_garbage_disposer<'ctx>() -> (GarbageChute<'ctx>, GarbageDisposer<'ctx>) {
     let capacity = 1024;
     let (box_sender, box_receiver) = sync_channel(capacity);
+    let leftovers = Arc::new(Mutex::new(VecDeque::new()));
     let backlog = Arc::new(AtomicUsize::new(0));
+    let leaked = Arc::new(AtomicUsize::new(0));
     let chute = GarbageChute {
         sender: box_sender,
+        overflow: RefCell::new(VecDeque::with_capacity(capacity)),
+        leftovers: Arc::clone(&leftovers),
         backlog: Arc::clone(&backlog),
+        leaked: Arc::clone(&leaked),
         capacity,
     };
     let disposer = GarbageDisposer {
         receiver: box_receiver,
+        leftovers,
         backlog,
+        leaked,
     };
     (chute, disposer)
 }
@@ -4,8 +4,13 @@ pub(crate) mod compiledsoundgraph;
 pub(crate) mod compiledsoundgraphedit;
 pub(crate) mod diffgraph;
 pub(crate) mod garbage;
+pub(crate) mod offlinerender;
 pub(crate) mod scratcharena;
 pub mod soundengine;
 pub(crate) mod soundenginereport;
 pub mod soundgraphcompiler;
 pub(crate) mod validation;
+pub(crate) mod voicelimit;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,70 @@
+use crate::core::{
+    jit::{argumentstack::ArgumentStack, cache::JitCache},
+    sound::{
+        context::{AudioContext, AudioStack},
+        soundgraph::SoundGraph,
+        soundinput::{DisconnectBehavior, InputContext, ProcessorInputId, SoundInputLocation},
+        soundprocessor::{ProcessorTiming, SoundProcessorId},
+    },
+    soundbuffer::SoundBuffer,
+    soundchunk::{SoundChunk, CHUNK_SIZE},
+};
+
+use super::{
+    compiledprocessor::CompiledSoundInputNode, scratcharena::ScratchArena,
+    soundgraphcompiler::SoundGraphCompiler,
+};
+
+/// Render the output of a single sound processor into a buffer of the
+/// given length, faster than real time and without a running sound engine.
+/// The processor is compiled on its own through a stand-in sound input,
+/// regardless of whether other processors are soloed. If it finishes
+/// early, the remainder of the buffer is silent.
+pub(crate) fn render_processor_offline(
+    graph: &SoundGraph,
+    jit_cache: &JitCache,
+    processor_id: SoundProcessorId,
+    num_samples: usize,
+) -> SoundBuffer {
+    let mut compiler = SoundGraphCompiler::new(graph, jit_cache);
+
+    let owner_id = SoundProcessorId::new_unique();
+    let location = SoundInputLocation::new(owner_id, ProcessorInputId::new_unique());
+    let mut node = CompiledSoundInputNode::new(
+        location,
+        1.0,
+        DisconnectBehavior::Silence,
+        compiler.compile_processor(processor_id),
+    );
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut buffer = SoundBuffer::new_with_capacity(num_samples.div_ceil(CHUNK_SIZE));
+    let mut chunk = SoundChunk::new();
+
+    while buffer.sample_len() < num_samples {
+        let context = AudioContext::new(
+            owner_id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        node.step(&mut chunk, InputContext::new(&context));
+
+        let remaining = num_samples - buffer.sample_len();
+        if remaining >= CHUNK_SIZE {
+            buffer.push_chunk(&chunk);
+        } else {
+            for (l, r) in chunk.l[..remaining].iter().zip(&chunk.r[..remaining]) {
+                buffer.push_sample(*l, *r);
+            }
+        }
+
+        processor_timing.advance_one_chunk();
+    }
+
+    buffer
+}
@@ -68,10 +68,9 @@ impl Drop for BorrowedSlice {
         // This does not allocate if the queue's Vec already
         // has held this many elements and thus has sufficient
         // capacity to store this slice.
-        self.queue
-            .borrow_mut()
-            .slices
-            .push(self.slice.take().unwrap())
+        let mut queue = self.queue.borrow_mut();
+        queue.slices.push(self.slice.take().unwrap());
+        queue.num_borrowed -= 1;
     }
 }
 
@@ -79,12 +78,59 @@ impl Drop for BorrowedSlice {
 /// requests for BorrowedSlice instances.
 struct SliceQueue {
     slices: Vec<Box<[f32]>>,
+
+    /// The number of slices currently borrowed from the queue
+    num_borrowed: usize,
+
+    /// The greatest number of slices that have been borrowed
+    /// from the queue at the same time
+    high_water_mark: usize,
 }
 
 impl SliceQueue {
     /// Creates a new SliceQueue that is empty
     fn new() -> SliceQueue {
-        SliceQueue { slices: Vec::new() }
+        SliceQueue {
+            slices: Vec::new(),
+            num_borrowed: 0,
+            high_water_mark: 0,
+        }
+    }
+
+    /// The total number of slices allocated for the queue,
+    /// whether currently borrowed or not
+    fn num_allocated(&self) -> usize {
+        self.slices.len() + self.num_borrowed
+    }
+}
+
+/// Slices of f32 allocated ahead of time, e.g. on a different thread,
+/// with which a ScratchArena can grow without allocating
+pub(crate) struct ScratchReservation {
+    /// The size of slice being reserved
+    size: usize,
+
+    /// The newly allocated slices, stored in a Vec with enough capacity
+    /// to also hold every slice that the arena already has
+    slices: Vec<Box<[f32]>>,
+}
+
+impl ScratchReservation {
+    /// Allocate enough slices of the given size for an arena which
+    /// already holds `already_reserved` of them to hold `count` in total
+    pub(crate) fn new(size: usize, already_reserved: usize, count: usize) -> ScratchReservation {
+        let len = 1_usize << ilog2(size);
+        let mut slices = Vec::with_capacity(count);
+        for _ in already_reserved..count {
+            slices.push(vec![0.0; len].into_boxed_slice());
+        }
+        ScratchReservation { size, slices }
+    }
+
+    /// The number of newly allocated slices
+    #[cfg(test)]
+    pub(crate) fn num_slices(&self) -> usize {
+        self.slices.len()
     }
 }
 
@@ -110,6 +156,66 @@ impl ScratchArena {
         }
     }
 
+    /// Get the slice queue for slices of the given size, creating
+    /// it if needed. Returns the queue and its length of slice.
+    fn queue(&self, size: usize) -> (Rc<RefCell<SliceQueue>>, usize) {
+        // Allocate and index according to the next largest
+        // power of 2
+        let k = ilog2(size);
+        let mut qs = self.queues.borrow_mut();
+        let q = qs
+            .entry(k)
+            .or_insert_with(|| Rc::new(RefCell::new(SliceQueue::new())));
+        (Rc::clone(q), 1_usize << k)
+    }
+
+    /// Allocate slices ahead of time such that at least `count` slices of
+    /// the given size can be borrowed at once without further allocation.
+    pub(crate) fn reserve(&self, size: usize, count: usize) {
+        let (q, len) = self.queue(size);
+        let mut q = q.borrow_mut();
+        let missing = count.saturating_sub(q.num_allocated());
+        // Reserve room for borrowed slices too, so that returning
+        // them to the queue doesn't allocate either
+        let additional = count.saturating_sub(q.slices.len());
+        q.slices.reserve(additional);
+        for _ in 0..missing {
+            q.slices.push(vec![0.0; len].into_boxed_slice());
+        }
+    }
+
+    /// Add the slices of the given reservation to the arena. This does not
+    /// allocate, provided that the arena held no more slices of that size
+    /// than the reservation was made for, and that a slice of that size was
+    /// requested or reserved before. Afterwards, the reservation holds the
+    /// arena's old and empty Vec, which should be disposed of elsewhere.
+    pub(crate) fn add_reservation(&self, reservation: &mut ScratchReservation) {
+        let (q, _) = self.queue(reservation.size);
+        let mut q = q.borrow_mut();
+        reservation.slices.append(&mut q.slices);
+        std::mem::swap(&mut q.slices, &mut reservation.slices);
+    }
+
+    /// The number of slices of the given size which have been allocated,
+    /// whether currently borrowed or not
+    pub(crate) fn reserved_slices(&self, size: usize) -> usize {
+        let k = ilog2(size);
+        self.queues
+            .borrow()
+            .get(&k)
+            .map_or(0, |q| q.borrow().num_allocated())
+    }
+
+    /// The greatest number of slices of the given size that have been
+    /// borrowed at the same time
+    pub(crate) fn high_water_mark(&self, size: usize) -> usize {
+        let k = ilog2(size);
+        self.queues
+            .borrow()
+            .get(&k)
+            .map_or(0, |q| q.borrow().high_water_mark)
+    }
+
     /// Request and receive an owned slice of f32 data of the given
     /// size. If a slice of similar length has previously been
     /// allocated and then dropped, it will get reused. The arena stores
@@ -119,28 +225,27 @@ impl ScratchArena {
     /// allocated on the heap, but will be returned to the arena
     /// and available for reuse when it is dropped.
     pub(crate) fn borrow_slice(&self, size: usize) -> BorrowedSlice {
-        // Allocate and index according to the next largest
-        // power of 2
-        let k = ilog2(size);
-        let s = 1_usize << k;
-        let mut qs = self.queues.borrow_mut();
-
         // If a similar size has been requested, use the existing
         // slice queue and do not allocate. Otherwise, allocate a
         // new (empty) slice queue.
-        let q = qs
-            .entry(k)
-            .or_insert_with(|| Rc::new(RefCell::new(SliceQueue::new())));
+        let (q, len) = self.queue(size);
+
+        let s = {
+            let mut qm = q.borrow_mut();
+
+            qm.num_borrowed += 1;
+            qm.high_water_mark = qm.high_water_mark.max(qm.num_borrowed);
 
-        // If a slice is available in the queue, take it. Keep the
-        // queue's Vec to retain its capacity when the slice is
-        // returned later. Otherwise, allocate a new one/
-        let s = match q.borrow_mut().slices.pop() {
-            Some(s) => s,
-            None => {
-                let mut v = Vec::new();
-                v.resize(s, 0.0);
-                v.into_boxed_slice()
+            // If a slice is available in the queue, take it. Keep the
+            // queue's Vec to retain its capacity when the slice is
+            // returned later. Otherwise, allocate a new one/
+            match qm.slices.pop() {
+                Some(s) => s,
+                None => {
+                    let mut v = Vec::new();
+                    v.resize(len, 0.0);
+                    v.into_boxed_slice()
+                }
             }
         };
 
@@ -150,7 +255,7 @@ impl ScratchArena {
         BorrowedSlice {
             slice: Some(s),
             size,
-            queue: Rc::clone(&q),
+            queue: q,
         }
     }
 }
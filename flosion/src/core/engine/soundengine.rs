@@ -2,23 +2,25 @@ use std::{
     borrow::Borrow,
     ops::Deref,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
         mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
         Arc,
     },
     time::{Duration, Instant},
 };
 
+use atomic_float::AtomicF32;
 use hashstash::{stash_clone_with_context, ObjectHash, Stash};
 use parking_lot::RwLock;
 
 use super::{
-    compiledsoundgraph::CompiledSoundGraph,
+    compiledsoundgraph::{count_processor_nodes, CompiledSoundGraph},
     compiledsoundgraphedit::CompiledSoundGraphEdit,
     diffgraph::diff_sound_graph,
     garbage::{new_garbage_disposer, Garbage, GarbageChute, GarbageDisposer},
-    scratcharena::ScratchArena,
+    scratcharena::{ScratchArena, ScratchReservation},
     soundenginereport::SoundEngineReport,
+    voicelimit::VoiceLimit,
 };
 
 use crate::core::{
@@ -30,6 +32,23 @@ use crate::core::{
     stashing::{StashingContext, UnstashingContext},
 };
 
+/// The number of chunk-sized scratch slices reserved for each compiled
+/// processor node. Processors currently borrow at most one slice at a
+/// time, the rest is headroom.
+pub(super) const SCRATCH_SLICES_PER_PROCESSOR: usize = 2;
+
+/// The number of chunk-sized scratch slices needed to process the compiled
+/// graph which results from the given edits. Since edits currently replace
+/// the entire graph at once, this counts the nodes being added or replaced.
+pub(super) fn scratch_slices_needed(edits: &[CompiledSoundGraphEdit]) -> usize {
+    let added_nodes = edits.iter().filter_map(|edit| match edit {
+        CompiledSoundGraphEdit::AddStaticSoundProcessor(node) => Some(node),
+        CompiledSoundGraphEdit::ReplaceStaticSoundProcessor(node) => Some(node),
+        _ => None,
+    });
+    count_processor_nodes(added_nodes) * SCRATCH_SLICES_PER_PROCESSOR
+}
+
 /// A thread-safe signaling mechanism used to communicate
 /// 'keep going' or 'stop', to allow infinite loops on
 /// multiple threads to terminate together. Uses an atomic
@@ -64,6 +83,428 @@ impl Clone for StopButton {
     }
 }
 
+/// A thread-safe switch for silencing all audio output at once, e.g.
+/// when a feedback loop runs away. While the switch is engaged, every
+/// output quickly fades to silence and no new voices are started.
+/// Uses an atomic boolean internally.
+pub(crate) struct PanicSwitch(Arc<AtomicBool>);
+
+impl PanicSwitch {
+    /// Create a new PanicSwitch in its default, disengaged state.
+    /// To share the same switch, simply clone it.
+    pub(crate) fn new() -> PanicSwitch {
+        PanicSwitch(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Engage or disengage the switch. All clones of the switch
+    /// on all threads will see the change.
+    pub(crate) fn set_engaged(&self, engaged: bool) {
+        self.0.store(engaged, Ordering::Relaxed);
+    }
+
+    /// Check whether the switch is currently engaged
+    pub(crate) fn is_engaged(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for PanicSwitch {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// The signals which the sound engine's test tone can play
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TestToneSignal {
+    /// A sine wave at TEST_TONE_FREQUENCY
+    Sine,
+    /// Pink noise, with equal power in every octave
+    PinkNoise,
+}
+
+impl TestToneSignal {
+    fn to_u8(self) -> u8 {
+        match self {
+            TestToneSignal::Sine => 0,
+            TestToneSignal::PinkNoise => 1,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<TestToneSignal> {
+        match x {
+            0 => Some(TestToneSignal::Sine),
+            1 => Some(TestToneSignal::PinkNoise),
+            _ => None,
+        }
+    }
+}
+
+/// The frequency of the sine test tone, in Hz
+pub(crate) const TEST_TONE_FREQUENCY: f32 = 1000.0;
+
+/// The level at which the test tone plays by default, as RMS in dBFS.
+/// This is a common alignment level for calibrating monitoring.
+pub(crate) const TEST_TONE_DEFAULT_LEVEL: f32 = -18.0;
+
+struct TestToneSettings {
+    enabled: AtomicBool,
+    signal: AtomicU8,
+    level: AtomicF32,
+}
+
+/// A thread-safe control for the sound engine's built-in test tone.
+/// While enabled, the audio leaving the sound graph through every output
+/// is replaced by a steady signal at a known level, for checking the
+/// monitoring chain. To share the same control, simply clone it.
+pub(crate) struct TestTone(Arc<TestToneSettings>);
+
+impl TestTone {
+    /// Create a new TestTone control, disabled and playing a sine
+    /// wave at the default level
+    pub(crate) fn new() -> TestTone {
+        TestTone(Arc::new(TestToneSettings {
+            enabled: AtomicBool::new(false),
+            signal: AtomicU8::new(TestToneSignal::Sine.to_u8()),
+            level: AtomicF32::new(TEST_TONE_DEFAULT_LEVEL),
+        }))
+    }
+
+    /// Whether the test tone is replacing the output of the sound graph
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.0.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn signal(&self) -> TestToneSignal {
+        TestToneSignal::from_u8(self.0.signal.load(Ordering::Relaxed)).unwrap()
+    }
+
+    pub(crate) fn set_signal(&self, signal: TestToneSignal) {
+        self.0.signal.store(signal.to_u8(), Ordering::Relaxed);
+    }
+
+    /// The RMS level of the test tone in each channel, in dBFS,
+    /// where 0 dBFS is an RMS amplitude of 1
+    pub(crate) fn level(&self) -> f32 {
+        self.0.level.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_level(&self, level: f32) {
+        self.0.level.store(level, Ordering::Relaxed);
+    }
+}
+
+impl Clone for TestTone {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// An event stamped with the time, in samples, at which it takes effect
+#[derive(Clone, Copy, Default)]
+struct TimedEvent<T> {
+    time: u64,
+    event: T,
+}
+
+/// Creates a queue through which events such as parameter changes and
+/// notes are sent to the audio thread, each taking effect at the exact
+/// sample for which it was scheduled rather than at the start of whichever
+/// chunk happens to be processed next. Times are counted in samples by
+/// the queue's own clock, which the receiver advances by one chunk at a
+/// time. The sender can read the clock to schedule events relative to
+/// the chunk currently being processed.
+pub(crate) fn event_queue<T: Copy + Default>(
+    capacity: usize,
+) -> (EventSender<T>, EventReceiver<T>) {
+    let (reader, writer) = spmcq::ring_buffer(capacity);
+    let clock = Arc::new(AtomicU64::new(0));
+    let sender = EventSender {
+        writer,
+        clock: Arc::clone(&clock),
+    };
+    let receiver = EventReceiver {
+        reader,
+        clock,
+        pending: None,
+    };
+    (sender, receiver)
+}
+
+/// The sending half of an event queue, see `event_queue`
+pub(crate) struct EventSender<T> {
+    writer: spmcq::Writer<TimedEvent<T>>,
+    clock: Arc<AtomicU64>,
+}
+
+impl<T: Copy> EventSender<T> {
+    /// The time in samples at which the chunk currently being
+    /// received started
+    pub(crate) fn now(&self) -> u64 {
+        self.clock.load(Ordering::Relaxed)
+    }
+
+    /// Send an event which takes effect as soon as possible, at the
+    /// start of the next chunk to be received
+    pub(crate) fn send(&mut self, event: T) {
+        self.send_at(0, event);
+    }
+
+    /// Send an event which takes effect at the given time in samples.
+    /// Events must be sent in order of time, since an event is only
+    /// received after all events sent before it. Events whose time
+    /// has already passed take effect at the start of the next chunk.
+    pub(crate) fn send_at(&mut self, time: u64, event: T) {
+        self.writer.write(TimedEvent { time, event });
+    }
+}
+
+/// The receiving half of an event queue, see `event_queue`. To share
+/// the same queue and clock, simply clone it.
+pub(crate) struct EventReceiver<T> {
+    reader: spmcq::Reader<TimedEvent<T>>,
+    clock: Arc<AtomicU64>,
+
+    /// An event which was read but which takes effect in a later chunk
+    pending: Option<TimedEvent<T>>,
+}
+
+impl<T: Copy> EventReceiver<T> {
+    /// Receive the next event which takes effect within the current
+    /// chunk, along with the offset in samples into the chunk at which
+    /// it takes effect. Returns None once all such events have been
+    /// received.
+    pub(crate) fn receive(&mut self) -> Option<(usize, T)> {
+        let timed_event = match self.pending.take() {
+            Some(e) => e,
+            None => self.reader.read().value()?,
+        };
+        let chunk_start = self.clock.load(Ordering::Relaxed);
+        if timed_event.time >= chunk_start + CHUNK_SIZE as u64 {
+            self.pending = Some(timed_event);
+            return None;
+        }
+        let offset = timed_event.time.saturating_sub(chunk_start) as usize;
+        Some((offset, timed_event.event))
+    }
+
+    /// Advance the clock to the next chunk. This should be called once
+    /// per chunk, after its events have been received.
+    pub(crate) fn finish_chunk(&mut self) {
+        self.clock.fetch_add(CHUNK_SIZE as u64, Ordering::Relaxed);
+    }
+
+    /// Take over the position in the queue of the given receiver, which
+    /// this one replaces, e.g. when the receiver's owner is compiled anew.
+    /// Events already received by the previous receiver are not received
+    /// again and its pending event is not lost.
+    pub(crate) fn take_over(&mut self, previous: &mut EventReceiver<T>) {
+        std::mem::swap(&mut self.reader, &mut previous.reader);
+        self.pending = previous.pending.take();
+    }
+}
+
+impl<T: Copy> Clone for EventReceiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            reader: self.reader.clone(),
+            clock: Arc::clone(&self.clock),
+            pending: self.pending,
+        }
+    }
+}
+
+/// The number of frames which the sound engine processes at once when
+/// first created
+pub(crate) const DEFAULT_BLOCK_SIZE: usize = 2 * CHUNK_SIZE;
+
+/// The most frames which the sound engine can be made to process at once
+pub(crate) const MAX_BLOCK_SIZE: usize = 16 * CHUNK_SIZE;
+
+/// A thread-safe setting for the number of frames which the sound engine
+/// processes at once, before waiting for as long as they take to play.
+/// Blocks always consist of whole chunks, which are what sound processors
+/// see, and are passed on to the output device whole no matter what buffer
+/// size the device asks for. Larger blocks make it less likely that audio
+/// drops out when the engine is held up, at the cost of latency. To share
+/// the same setting, simply clone it.
+pub(crate) struct BlockSize(Arc<AtomicUsize>);
+
+impl BlockSize {
+    /// Create a new BlockSize setting with the default size
+    pub(crate) fn new() -> BlockSize {
+        BlockSize(Arc::new(AtomicUsize::new(DEFAULT_BLOCK_SIZE)))
+    }
+
+    /// The number of frames in each block
+    pub(crate) fn frames(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// The number of chunks in each block
+    pub(crate) fn chunks(&self) -> usize {
+        self.frames() / CHUNK_SIZE
+    }
+
+    /// Set the number of frames in each block. This is rounded up to a
+    /// whole number of chunks and limited to at most MAX_BLOCK_SIZE.
+    /// The sound engine changes over at the start of its next block.
+    pub(crate) fn set_frames(&self, frames: usize) {
+        let frames = frames.next_multiple_of(CHUNK_SIZE);
+        self.0
+            .store(frames.clamp(CHUNK_SIZE, MAX_BLOCK_SIZE), Ordering::Relaxed);
+    }
+}
+
+impl Clone for BlockSize {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// A lock-free ring buffer of stereo audio frames between a single writer,
+/// such as a processor sending audio out of the sound engine, and a single
+/// reader, such as an output device's callback. Neither side ever blocks
+/// or allocates: writes which don't fit and reads of more than is
+/// available simply fail, leaving the ring as it was.
+pub(crate) struct AudioRing {
+    left: Box<[AtomicF32]>,
+    right: Box<[AtomicF32]>,
+
+    /// The total number of frames written so far
+    written: AtomicUsize,
+
+    /// The total number of frames read so far
+    read: AtomicUsize,
+}
+
+impl AudioRing {
+    /// Create a new, empty AudioRing which holds up to the given
+    /// number of frames
+    pub(crate) fn new(capacity: usize) -> AudioRing {
+        assert!(capacity > 0);
+        AudioRing {
+            left: (0..capacity).map(|_| AtomicF32::new(0.0)).collect(),
+            right: (0..capacity).map(|_| AtomicF32::new(0.0)).collect(),
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.left.len()
+    }
+
+    /// The number of frames written which have not been read yet
+    pub(crate) fn available(&self) -> usize {
+        let read = self.read.load(Ordering::Acquire);
+        self.written.load(Ordering::Acquire) - read
+    }
+
+    /// Append the given left and right channels, which must have the
+    /// same length. Returns false and writes nothing if there isn't
+    /// room for all of them. Must only be called by one thread at a time.
+    pub(crate) fn write(&self, left: &[f32], right: &[f32]) -> bool {
+        debug_assert_eq!(left.len(), right.len());
+        let written = self.written.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+        if self.capacity() - (written - read) < left.len() {
+            return false;
+        }
+        for (i, (l, r)) in left.iter().zip(right).enumerate() {
+            let index = (written + i) % self.capacity();
+            self.left[index].store(*l, Ordering::Relaxed);
+            self.right[index].store(*r, Ordering::Relaxed);
+        }
+        self.written.store(written + left.len(), Ordering::Release);
+        true
+    }
+
+    /// Fill the given left and right channels, which must have the same
+    /// length, with the oldest frames not read yet. Returns false and
+    /// reads nothing if fewer frames than that are available. Must only
+    /// be called by one thread at a time.
+    pub(crate) fn read(&self, left: &mut [f32], right: &mut [f32]) -> bool {
+        debug_assert_eq!(left.len(), right.len());
+        let read = self.read.load(Ordering::Relaxed);
+        let written = self.written.load(Ordering::Acquire);
+        if written - read < left.len() {
+            return false;
+        }
+        for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let index = (read + i) % self.capacity();
+            *l = self.left[index].load(Ordering::Relaxed);
+            *r = self.right[index].load(Ordering::Relaxed);
+        }
+        self.read.store(read + left.len(), Ordering::Release);
+        true
+    }
+}
+
+/// Bridges between the whole blocks of audio which the sound engine
+/// writes to an AudioRing and device callbacks of arbitrary and possibly
+/// varying size. Frames are handed out one at a time from a buffer which
+/// is only refilled with the next whole block once every frame of the
+/// current one has been read. To ride out the engine being a little late,
+/// nothing is read after starting or after the ring runs dry until a
+/// second block is waiting behind the first, and silence is played
+/// in the meantime.
+pub(crate) struct BlockBridge {
+    left: Vec<f32>,
+    right: Vec<f32>,
+
+    /// The number of frames in the current block
+    block_length: usize,
+
+    /// The index of the next frame to read. When this equals the block
+    /// length, every frame has been read and the next block is needed.
+    read_index: usize,
+
+    /// Whether enough audio was waiting to start reading from the ring
+    primed: bool,
+}
+
+impl BlockBridge {
+    /// Create a new, empty BlockBridge for blocks of up to MAX_BLOCK_SIZE
+    /// frames
+    pub(crate) fn new() -> BlockBridge {
+        BlockBridge {
+            left: vec![0.0; MAX_BLOCK_SIZE],
+            right: vec![0.0; MAX_BLOCK_SIZE],
+            block_length: 0,
+            read_index: 0,
+            primed: false,
+        }
+    }
+
+    /// Read the next stereo frame. If every frame of the current block
+    /// has been read, the next block of the given number of frames is
+    /// read from the ring first. If it isn't there yet, silence is
+    /// returned instead.
+    pub(crate) fn next_frame(&mut self, ring: &AudioRing, block_size: usize) -> (f32, f32) {
+        debug_assert!(block_size <= MAX_BLOCK_SIZE);
+        if self.read_index == self.block_length {
+            if !self.primed && ring.available() < 2 * block_size {
+                return (0.0, 0.0);
+            }
+            if !ring.read(&mut self.left[..block_size], &mut self.right[..block_size]) {
+                self.primed = false;
+                return (0.0, 0.0);
+            }
+            self.primed = true;
+            self.block_length = block_size;
+            self.read_index = 0;
+        }
+        let frame = (self.left[self.read_index], self.right[self.read_index]);
+        self.read_index += 1;
+        frame
+    }
+}
+
 /// Constructs a new sound engine, interface for the sound engine,
 /// and a garbage disposer.
 ///
@@ -95,6 +536,8 @@ pub(crate) fn create_sound_engine<'ctx>(
 
     let report = Arc::new(RwLock::new(SoundEngineReport::new()));
 
+    let underrun_count = Arc::new(AtomicUsize::new(0));
+
     let current_graph = SoundGraph::new();
     let current_hash = ObjectHash::from_stashable_and_context(
         &current_graph,
@@ -104,17 +547,25 @@ pub(crate) fn create_sound_engine<'ctx>(
         current_graph,
         current_hash,
         stop_button: stop_button.clone(),
+        panic_switch: PanicSwitch::new(),
+        test_tone: TestTone::new(),
+        voice_limit: VoiceLimit::new(),
+        block_size: BlockSize::new(),
+        scratch_slices_reserved: 0,
         edit_queue: edit_sender,
         report: Arc::clone(&report),
+        underrun_count: Arc::clone(&underrun_count),
     };
 
     let se = SoundEngine {
         compiled_graph: CompiledSoundGraph::new(),
         stop_button: stop_button.clone(),
+        block_size: se_interface.block_size.clone(),
         edit_queue: edit_receiver,
         deadline_warning_issued: false,
         garbage_chute,
         report,
+        underrun_count,
     };
 
     (se_interface, se, garbage_disposer)
@@ -134,8 +585,15 @@ pub(crate) struct SoundEngineInterface<'ctx> {
     current_graph: SoundGraph,
     current_hash: ObjectHash,
     stop_button: StopButton,
+    panic_switch: PanicSwitch,
+    test_tone: TestTone,
+    voice_limit: VoiceLimit,
+    block_size: BlockSize,
+    /// The number of chunk-sized scratch slices sent to the audio thread so far
+    scratch_slices_reserved: usize,
     edit_queue: SyncSender<CompiledSoundGraphEdit<'ctx>>,
     report: Arc<RwLock<SoundEngineReport>>,
+    underrun_count: Arc<AtomicUsize>,
 }
 
 impl<'ctx> SoundEngineInterface<'ctx> {
@@ -159,7 +617,28 @@ impl<'ctx> SoundEngineInterface<'ctx> {
             return Ok(());
         }
 
-        let edits = diff_sound_graph(&self.current_graph, &new_graph, jit_cache);
+        let mut edits = diff_sound_graph(
+            &self.current_graph,
+            &new_graph,
+            jit_cache,
+            &self.panic_switch,
+            &self.test_tone,
+            &self.voice_limit,
+            &self.block_size,
+        );
+
+        // Allocate any additional scratch space the new graph needs here
+        // rather than on the audio thread, and send it along first
+        let scratch_slices = scratch_slices_needed(&edits);
+        if scratch_slices > self.scratch_slices_reserved {
+            let reservation =
+                ScratchReservation::new(CHUNK_SIZE, self.scratch_slices_reserved, scratch_slices);
+            edits.insert(
+                0,
+                CompiledSoundGraphEdit::ReserveScratchSpace(Box::new(reservation)),
+            );
+            self.scratch_slices_reserved = scratch_slices;
+        }
 
         for edit in edits {
             match self.edit_queue.try_send(edit) {
@@ -189,9 +668,38 @@ impl<'ctx> SoundEngineInterface<'ctx> {
         Ok(())
     }
 
+    /// The switch through which all output of the SoundEngine can be silenced
+    pub(crate) fn panic_switch(&self) -> &PanicSwitch {
+        &self.panic_switch
+    }
+
+    /// The control through which all output of the SoundEngine can be
+    /// replaced by a test tone
+    pub(crate) fn test_tone(&self) -> &TestTone {
+        &self.test_tone
+    }
+
+    /// The cap on the number of voices played at once across the
+    /// whole SoundEngine
+    pub(crate) fn voice_limit(&self) -> &VoiceLimit {
+        &self.voice_limit
+    }
+
+    /// The number of frames which the SoundEngine processes at once
+    pub(crate) fn block_size(&self) -> &BlockSize {
+        &self.block_size
+    }
+
     pub(crate) fn report<'a>(&'a self) -> impl 'a + Deref<Target = SoundEngineReport> {
         self.report.read()
     }
+
+    /// The number of blocks that the SoundEngine has failed to produce
+    /// on time since it started, each of which likely caused an audible
+    /// dropout
+    pub(crate) fn underrun_count(&self) -> usize {
+        self.underrun_count.load(Ordering::Relaxed)
+    }
 }
 
 impl<'ctx> Drop for SoundEngineInterface<'ctx> {
@@ -215,6 +723,9 @@ pub(crate) struct SoundEngine<'ctx> {
     /// to things happening on other threads
     stop_button: StopButton,
 
+    /// The number of frames to process at once
+    block_size: BlockSize,
+
     /// Inbound edits to the compiled graph, received from diffing and
     /// compiling graphs in the associated SoundEngineInterface.
     edit_queue: Receiver<CompiledSoundGraphEdit<'ctx>>,
@@ -230,6 +741,9 @@ pub(crate) struct SoundEngine<'ctx> {
 
     /// Shared report for inspecting how the graph is performing
     report: Arc<RwLock<SoundEngineReport>>,
+
+    /// Shared count of blocks which were finished after their deadline
+    underrun_count: Arc<AtomicUsize>,
 }
 
 impl<'ctx> SoundEngine<'ctx> {
@@ -247,20 +761,23 @@ impl<'ctx> SoundEngine<'ctx> {
         let chunks_per_sec = (SAMPLE_FREQUENCY as f64) / (CHUNK_SIZE as f64);
         let chunk_duration = Duration::from_micros((1_000_000.0 / chunks_per_sec) as u64);
 
-        let mut deadline = Instant::now() + chunk_duration;
+        // Set up the arena for chunk-sized scratch space before starting,
+        // so that reserving more of it later doesn't need to
+        Self::SCRATCH_SPACE.with(|scratch_space| scratch_space.reserve(CHUNK_SIZE, 0));
 
-        loop {
-            // Receive and incorporate any edits from the SoundEngineInterface
-            self.flush_updates();
+        let mut deadline = Instant::now();
 
-            // Invoke the sound processors
-            self.process_audio();
+        loop {
+            let chunks = self.process_block();
+            deadline += chunk_duration * chunks as u32;
             if self.stop_button.was_stopped() {
                 break;
             }
 
             let now = Instant::now();
             if now > deadline {
+                self.underrun_count.fetch_add(1, Ordering::Relaxed);
+
                 // If we just fell behind schedule, issue a warning
                 // because audio dropouts are happening.
                 if !self.deadline_warning_issued {
@@ -270,29 +787,59 @@ impl<'ctx> SoundEngine<'ctx> {
             } else {
                 // Try updating the report
                 if let Some(mut report) = self.report.try_write() {
-                    report.regenerate(&self.compiled_graph);
+                    Self::SCRATCH_SPACE.with(|scratch_space| {
+                        report.regenerate(&self.compiled_graph, scratch_space);
+                    });
                 }
 
                 // If we're on schedule, sleep for precisely the
-                // amount of time remaining until the next chunk
+                // amount of time remaining until the next block
                 // needs to start.
                 self.deadline_warning_issued = false;
                 let delta = deadline.duration_since(now);
                 spin_sleep::sleep(delta);
             }
-            deadline += chunk_duration;
         }
 
         // Throw out the graph to ensure resource cleanup (particularly of
-        // LLVM resources) happens on the correct thread
+        // LLVM resources) happens on the correct thread. Dropping the
+        // garbage chute afterwards leaves anything which didn't fit down
+        // it for the disposer.
         self.compiled_graph.toss(&self.garbage_chute);
     }
 
+    /// Receive and incorporate any edits from the SoundEngineInterface,
+    /// then process one block's worth of chunks. Returns the number of
+    /// chunks processed.
+    pub(crate) fn process_block(&mut self) -> usize {
+        self.flush_updates();
+
+        // The block size is read once per block so that blocks are
+        // always processed whole
+        let chunks = self.block_size.chunks();
+        for _ in 0..chunks {
+            self.process_audio();
+        }
+        chunks
+    }
+
     /// Receive and incorporate any edits from
     /// the edit queue. Toss any old data down the garbage chute.
     fn flush_updates(&mut self) {
+        // Pass on any garbage which didn't fit down the chute earlier
+        self.garbage_chute.retry_overflow();
+
         while let Ok(edit) = self.edit_queue.try_recv() {
-            self.compiled_graph.make_edit(edit, &self.garbage_chute);
+            match edit {
+                CompiledSoundGraphEdit::ReserveScratchSpace(mut reservation) => {
+                    Self::SCRATCH_SPACE.with(|scratch_space| {
+                        scratch_space.add_reservation(&mut reservation);
+                    });
+                    // The reservation now holds the arena's old Vec
+                    self.garbage_chute.send_box(reservation);
+                }
+                edit => self.compiled_graph.make_edit(edit, &self.garbage_chute),
+            }
         }
     }
 
@@ -6,10 +6,55 @@ use crate::core::{
     soundchunk::CHUNK_SIZE,
 };
 
-use super::{compiledprocessor::CompiledSoundInputNode, compiledsoundgraph::CompiledSoundGraph};
+use super::{
+    compiledprocessor::CompiledSoundInputNode, compiledsoundgraph::CompiledSoundGraph,
+    scratcharena::ScratchArena,
+};
+
+/// A snapshot of one playing voice of a polyphonic processor
+#[derive(Clone, Copy, Debug)]
+pub struct VoiceReport {
+    note: f32,
+    age_samples: usize,
+    released: bool,
+    level: f32,
+}
+
+impl VoiceReport {
+    pub(crate) fn new(note: f32, age_samples: usize, released: bool, level: f32) -> VoiceReport {
+        VoiceReport {
+            note,
+            age_samples,
+            released,
+            level,
+        }
+    }
+
+    /// The MIDI-style note number which the voice is playing
+    pub(crate) fn note(&self) -> f32 {
+        self.note
+    }
+
+    /// How long ago the voice was started, in samples
+    pub(crate) fn age_samples(&self) -> usize {
+        self.age_samples
+    }
+
+    /// Whether the voice's key has been let go and the voice is
+    /// only playing until its input finishes
+    pub(crate) fn released(&self) -> bool {
+        self.released
+    }
+
+    /// The peak amplitude of the voice's most recent chunk of audio
+    pub(crate) fn level(&self) -> f32 {
+        self.level
+    }
+}
 
 pub(crate) struct CompiledProcessorReport {
     times_samples: Vec<usize>,
+    voices: Vec<VoiceReport>,
 }
 
 impl CompiledProcessorReport {
@@ -17,27 +62,47 @@ impl CompiledProcessorReport {
     pub(crate) fn times_samples(&self) -> &[usize] {
         &self.times_samples
     }
+
+    /// The voices currently being played by each compiled instance of the processor
+    pub(crate) fn voices(&self) -> &[VoiceReport] {
+        &self.voices
+    }
 }
 
 pub(crate) struct SoundEngineReport {
     processors: HashMap<SoundProcessorId, CompiledProcessorReport>,
+    scratch_chunks_high_water_mark: usize,
+    scratch_chunks_reserved: usize,
 }
 
 impl SoundEngineReport {
     pub(crate) fn new() -> SoundEngineReport {
         SoundEngineReport {
             processors: HashMap::new(),
+            scratch_chunks_high_water_mark: 0,
+            scratch_chunks_reserved: 0,
         }
     }
 
-    pub(crate) fn regenerate(&mut self, compiled_graph: &CompiledSoundGraph) {
+    pub(crate) fn regenerate(
+        &mut self,
+        compiled_graph: &CompiledSoundGraph,
+        scratch_arena: &ScratchArena,
+    ) {
+        self.scratch_chunks_high_water_mark = scratch_arena.high_water_mark(CHUNK_SIZE);
+        self.scratch_chunks_reserved = scratch_arena.reserved_slices(CHUNK_SIZE);
+
         // Clear all samples
         for proc_report in self.processors.values_mut() {
             proc_report.times_samples.clear();
+            proc_report.voices.clear();
         }
 
         struct Visitor<'a> {
             report: &'a mut SoundEngineReport,
+
+            /// The processor whose components are currently being visited
+            current_processor: Option<SoundProcessorId>,
         }
 
         impl<'a> Visitor<'a> {
@@ -49,10 +114,13 @@ impl SoundEngineReport {
                         .entry(processor.id())
                         .or_insert_with(|| CompiledProcessorReport {
                             times_samples: Vec::new(),
+                            voices: Vec::new(),
                         });
                 proc_report.times_samples.push(elapsed_samples);
 
+                let outer_processor = self.current_processor.replace(processor.id());
                 processor.visit(self);
+                self.current_processor = outer_processor;
             }
         }
 
@@ -66,9 +134,21 @@ impl SoundEngineReport {
                     CompiledProcessorLink::Empty => (),
                 }
             }
+
+            fn voice(&mut self, voice: VoiceReport) {
+                let Some(processor_id) = self.current_processor else {
+                    return;
+                };
+                if let Some(proc_report) = self.report.processors.get_mut(&processor_id) {
+                    proc_report.voices.push(voice);
+                }
+            }
         }
 
-        let mut visitor = Visitor { report: self };
+        let mut visitor = Visitor {
+            report: self,
+            current_processor: None,
+        };
 
         for node in compiled_graph.static_processors() {
             if node.is_entry_point() {
@@ -81,6 +161,18 @@ impl SoundEngineReport {
         self.processors.retain(|_, r| !r.times_samples.is_empty());
     }
 
+    /// The greatest number of chunk-sized scratch slices which
+    /// the sound engine has needed at once
+    pub(crate) fn scratch_chunks_high_water_mark(&self) -> usize {
+        self.scratch_chunks_high_water_mark
+    }
+
+    /// The number of chunk-sized scratch slices which the sound
+    /// engine has allocated
+    pub(crate) fn scratch_chunks_reserved(&self) -> usize {
+        self.scratch_chunks_reserved
+    }
+
     pub(crate) fn processor_report(
         &self,
         processor_id: SoundProcessorId,
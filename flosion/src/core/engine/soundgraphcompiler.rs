@@ -1,15 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::core::{
     jit::{cache::JitCache, compiledexpression::CompiledExpressionFunction, jit::JitMode},
     sound::{
-        expression::ProcessorExpressionLocation, soundgraph::SoundGraph,
+        expression::ProcessorExpressionLocation,
+        latency::{latency_compensation, processor_latencies},
+        soundgraph::SoundGraph,
+        soundinput::SoundInputLocation,
         soundprocessor::SoundProcessorId,
     },
 };
 
-use super::compiledprocessor::{
-    CompiledProcessorLink, SharedCompiledProcessor, UniqueCompiledProcessor,
+use super::{
+    compiledprocessor::{CompiledProcessorLink, SharedCompiledProcessor, UniqueCompiledProcessor},
+    soundengine::{BlockSize, PanicSwitch, TestTone},
+    voicelimit::VoiceLimit,
 };
 
 /// Struct through which compilation of sound graph components for direct
@@ -28,6 +33,28 @@ pub struct SoundGraphCompiler<'a, 'ctx> {
     // TODO: when implementing partial edits, make sure this is
     // maintained between graph updates.
     static_processor_nodes: HashMap<SoundProcessorId, SharedCompiledProcessor<'ctx>>,
+
+    /// The processors which may be heard, if any processors are soloed.
+    /// Inputs connected to any other processor are compiled as if they
+    /// were disconnected, and so produce silence.
+    audible_processors: Option<HashSet<SoundProcessorId>>,
+
+    /// The total latency of every processor's output, in samples
+    latencies: HashMap<SoundProcessorId, usize>,
+
+    /// The switch through which the sound engine silences all output
+    panic_switch: PanicSwitch,
+
+    /// The control through which the sound engine replaces all output
+    /// with a test tone
+    test_tone: TestTone,
+
+    /// The engine-wide limit through which polyphonic processors
+    /// start and end their voices
+    voice_limit: VoiceLimit,
+
+    /// The number of frames which the sound engine processes at once
+    block_size: BlockSize,
 }
 
 impl<'a, 'ctx> SoundGraphCompiler<'a, 'ctx> {
@@ -42,12 +69,82 @@ impl<'a, 'ctx> SoundGraphCompiler<'a, 'ctx> {
             graph,
             jit_cache,
             static_processor_nodes: HashMap::new(),
+            audible_processors: graph.audible_processors(),
+            latencies: processor_latencies(graph),
+            panic_switch: PanicSwitch::new(),
+            test_tone: TestTone::new(),
+            voice_limit: VoiceLimit::new(),
+            block_size: BlockSize::new(),
         }
     }
 
-    /// Compile a sound processor, creating an executable compiled node.
+    /// Use the given panic switch, such as that of a running sound engine,
+    /// instead of a new switch which is never engaged.
+    pub(crate) fn with_panic_switch(
+        mut self,
+        panic_switch: PanicSwitch,
+    ) -> SoundGraphCompiler<'a, 'ctx> {
+        self.panic_switch = panic_switch;
+        self
+    }
+
+    /// The panic switch which compiled processors should respond to
+    pub(crate) fn panic_switch(&self) -> &PanicSwitch {
+        &self.panic_switch
+    }
+
+    /// The number of samples by which audio arriving through the given
+    /// sound input must be delayed to line up with the audio arriving
+    /// through the other inputs of the same processor
+    pub(crate) fn latency_compensation(&self, location: SoundInputLocation) -> usize {
+        latency_compensation(self.graph, &self.latencies, location)
+    }
+
+    /// Use the given test tone control, such as that of a running sound
+    /// engine, instead of a new control which is never enabled.
+    pub(crate) fn with_test_tone(mut self, test_tone: TestTone) -> SoundGraphCompiler<'a, 'ctx> {
+        self.test_tone = test_tone;
+        self
+    }
+
+    /// The test tone control which compiled outputs should respond to
+    pub(crate) fn test_tone(&self) -> &TestTone {
+        &self.test_tone
+    }
+
+    /// Use the given voice limit, such as that of a running sound engine,
+    /// instead of a new limit which never limits anything.
+    pub(crate) fn with_voice_limit(
+        mut self,
+        voice_limit: VoiceLimit,
+    ) -> SoundGraphCompiler<'a, 'ctx> {
+        self.voice_limit = voice_limit;
+        self
+    }
+
+    /// The voice limit which compiled polyphonic processors should respect
+    pub(crate) fn voice_limit(&self) -> &VoiceLimit {
+        &self.voice_limit
+    }
+
+    /// Use the given block size, such as that of a running sound engine,
+    /// instead of a new setting with the default size.
+    pub(crate) fn with_block_size(mut self, block_size: BlockSize) -> SoundGraphCompiler<'a, 'ctx> {
+        self.block_size = block_size;
+        self
+    }
+
+    /// The number of frames which the sound engine processes at once, in
+    /// which compiled outputs should pass audio on to their devices
+    pub(crate) fn block_size(&self) -> &BlockSize {
+        &self.block_size
+    }
+
+    /// Compile the target of a sound input, creating an executable compiled node.
     /// If the processor is static, its node will be cached to ensure that multiple
     /// requests for the same static node receive the same (single) shared node.
+    /// If the target is silenced because other processors are soloed, the
+    /// input is left empty.
     pub(crate) fn compile_sound_processor(
         &mut self,
         target: Option<SoundProcessorId>,
@@ -55,6 +152,32 @@ impl<'a, 'ctx> SoundGraphCompiler<'a, 'ctx> {
         let Some(processor_id) = target else {
             return CompiledProcessorLink::Empty;
         };
+        if let Some(audible) = &self.audible_processors {
+            if !audible.contains(&processor_id) {
+                return CompiledProcessorLink::Empty;
+            }
+        }
+        self.compile_processor(processor_id)
+    }
+
+    /// Compile a static sound processor on its own, regardless of whether
+    /// it is soloed, producing its single shared node.
+    pub(crate) fn compile_static_processor(
+        &mut self,
+        processor_id: SoundProcessorId,
+    ) -> SharedCompiledProcessor<'ctx> {
+        let CompiledProcessorLink::Shared(node) = self.compile_processor(processor_id) else {
+            panic!("Static sound processors must compile to shared nodes");
+        };
+        node
+    }
+
+    /// Compile any sound processor on its own, regardless of whether it
+    /// is soloed. Static processors are cached as with compile_sound_processor.
+    pub(crate) fn compile_processor(
+        &mut self,
+        processor_id: SoundProcessorId,
+    ) -> CompiledProcessorLink<'ctx> {
         let proc = self.graph.sound_processor(processor_id).unwrap();
         if proc.is_static() {
             if let Some(node) = self.static_processor_nodes.get(&processor_id) {
@@ -72,6 +195,10 @@ impl<'a, 'ctx> SoundGraphCompiler<'a, 'ctx> {
         }
     }
 
+    /// Get the compiled function for the given expression, if the JIT
+    /// cache has compiled it yet. An expression which failed to compile
+    /// yields a function producing the default values of its results,
+    /// see JitCache::compilation_error for the reason it failed.
     pub(crate) fn get_compiled_expression(
         &self,
         location: ProcessorExpressionLocation,
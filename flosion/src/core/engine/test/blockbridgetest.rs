@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stash, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        engine::soundengine::{create_sound_engine, AudioRing, BlockBridge, StopButton},
+        expression::expressionobject::ExpressionObjectFactory,
+        jit::cache::JitCache,
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            context::AudioContext,
+            engineblocksize::EngineBlockSize,
+            soundgraph::SoundGraph,
+            soundobject::SoundObjectFactory,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, SoundProcessorWithId, StartOver, StateMarker,
+                StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A static processor which counts up from zero, one per frame, and
+/// writes everything it produces to a ring in place of an output device
+#[derive(ProcessorComponent)]
+struct RingSink {
+    block_size: EngineBlockSize,
+
+    #[not_a_component]
+    ring: Arc<AudioRing>,
+
+    #[state]
+    state: StateMarker<RingSinkState>,
+}
+
+struct RingSinkState {
+    ring: Arc<AudioRing>,
+    next_value: usize,
+}
+
+impl ProcessorState for RingSinkState {
+    type Processor = RingSink;
+
+    fn new(processor: &RingSink) -> Self {
+        RingSinkState {
+            ring: Arc::clone(&processor.ring),
+            next_value: 0,
+        }
+    }
+}
+
+impl StartOver for RingSinkState {
+    fn start_over(&mut self) {}
+}
+
+impl SoundProcessor for RingSink {
+    fn new(_args: &ParsedArguments) -> RingSink {
+        RingSink {
+            block_size: EngineBlockSize,
+            ring: Arc::new(AudioRing::new(64 * CHUNK_SIZE)),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn process_audio(
+        sink: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        _context: &mut AudioContext,
+    ) -> StreamStatus {
+        // The engine's block size is always seen whole
+        assert_eq!(sink.block_size.frames() % CHUNK_SIZE, 0);
+        let state = &mut sink.state;
+        for (l, r) in dst.l.iter_mut().zip(dst.r.iter_mut()) {
+            *l = state.next_value as f32;
+            *r = -(state.next_value as f32);
+            state.next_value += 1;
+        }
+        assert!(state.ring.write(&dst.l, &dst.r));
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for RingSink {
+    const TYPE: ObjectType = ObjectType::new("ringsink");
+}
+
+impl Stashable<StashingContext> for RingSink {
+    fn stash(&self, _stasher: &mut Stasher<StashingContext>) {}
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for RingSink {
+    fn unstash_inplace(
+        &mut self,
+        _unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_audio_ring_reads_and_writes_all_or_nothing() {
+    let ring = AudioRing::new(8);
+
+    assert!(ring.write(&[1.0, 2.0, 3.0, 4.0, 5.0], &[-1.0, -2.0, -3.0, -4.0, -5.0]));
+    assert_eq!(ring.available(), 5);
+
+    // Not enough room
+    assert!(!ring.write(&[0.0; 4], &[0.0; 4]));
+    assert_eq!(ring.available(), 5);
+
+    // Not enough waiting
+    let mut l = [0.0; 6];
+    let mut r = [0.0; 6];
+    assert!(!ring.read(&mut l, &mut r));
+    assert_eq!(ring.available(), 5);
+
+    assert!(ring.read(&mut l[..3], &mut r[..3]));
+    assert_eq!(&l[..3], &[1.0, 2.0, 3.0]);
+    assert_eq!(&r[..3], &[-1.0, -2.0, -3.0]);
+
+    // Now there's room, which wraps around the end of the ring
+    assert!(ring.write(&[6.0, 7.0, 8.0, 9.0], &[-6.0, -7.0, -8.0, -9.0]));
+    assert_eq!(ring.available(), 6);
+    assert!(ring.read(&mut l, &mut r));
+    assert_eq!(l, [4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+    assert_eq!(r, [-4.0, -5.0, -6.0, -7.0, -8.0, -9.0]);
+    assert_eq!(ring.available(), 0);
+}
+
+#[test]
+fn test_block_bridge_waits_for_a_second_block() {
+    const BLOCK_SIZE: usize = 64;
+
+    let ring = AudioRing::new(4 * BLOCK_SIZE);
+    let mut bridge = BlockBridge::new();
+
+    let values: Vec<f32> = (0..(2 * BLOCK_SIZE)).map(|i| (i + 1) as f32).collect();
+
+    ring.write(&values[..BLOCK_SIZE], &values[..BLOCK_SIZE]);
+    assert_eq!(bridge.next_frame(&ring, BLOCK_SIZE), (0.0, 0.0));
+    assert_eq!(ring.available(), BLOCK_SIZE);
+
+    ring.write(&values[BLOCK_SIZE..], &values[BLOCK_SIZE..]);
+    for v in &values[..BLOCK_SIZE] {
+        assert_eq!(bridge.next_frame(&ring, BLOCK_SIZE), (*v, *v));
+    }
+
+    // Only the first block was taken
+    assert_eq!(ring.available(), BLOCK_SIZE);
+
+    // Once started, a single block waiting is enough
+    for v in &values[BLOCK_SIZE..] {
+        assert_eq!(bridge.next_frame(&ring, BLOCK_SIZE), (*v, *v));
+    }
+
+    // After running dry, it waits for two blocks again
+    assert_eq!(bridge.next_frame(&ring, BLOCK_SIZE), (0.0, 0.0));
+    ring.write(&values[..BLOCK_SIZE], &values[..BLOCK_SIZE]);
+    assert_eq!(bridge.next_frame(&ring, BLOCK_SIZE), (0.0, 0.0));
+}
+
+#[test]
+fn test_engine_delivers_whole_blocks_to_device_callbacks_of_any_size() {
+    const BLOCK_SIZE: usize = 3 * CHUNK_SIZE;
+
+    let inkwell_context = inkwell::context::Context::create();
+
+    let stop_button = StopButton::new();
+    let (mut engine_interface, mut engine, garbage_disposer) = create_sound_engine(&stop_button);
+
+    engine_interface.block_size().set_frames(BLOCK_SIZE);
+
+    let mut sound_object_factory = SoundObjectFactory::new_empty();
+    sound_object_factory.register::<SoundProcessorWithId<RingSink>>();
+    let expression_object_factory = ExpressionObjectFactory::new_empty();
+
+    let sink = SoundProcessorWithId::<RingSink>::new_default();
+    let ring = Arc::clone(&sink.ring);
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(sink));
+
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    engine_interface
+        .update(
+            &graph,
+            &jit_cache,
+            &Stash::new(),
+            &sound_object_factory,
+            &expression_object_factory,
+        )
+        .unwrap();
+
+    // Device callbacks of irregular sizes, some smaller and some larger
+    // than a block, and some empty
+    let callback_sizes = [1, 1023, 3072, 3073, 0, 5000, 7, 128, 9000, 3, 2048];
+
+    let mut bridge = BlockBridge::new();
+    let mut frames_played: usize = 0;
+
+    for size in callback_sizes {
+        // The engine runs just far enough ahead of the device, and
+        // always delivers a whole block at a time
+        while ring.available() < size + 2 * BLOCK_SIZE {
+            let available_before = ring.available();
+            assert_eq!(engine.process_block(), BLOCK_SIZE / CHUNK_SIZE);
+            assert_eq!(ring.available(), available_before + BLOCK_SIZE);
+        }
+
+        // Frames come out without gaps in the order they were produced
+        for _ in 0..size {
+            let (l, r) = bridge.next_frame(&ring, BLOCK_SIZE);
+            assert_eq!(l, frames_played as f32);
+            assert_eq!(r, -(frames_played as f32));
+            frames_played += 1;
+        }
+
+        // The device only ever takes whole blocks out of the ring
+        assert_eq!(ring.available() % BLOCK_SIZE, 0);
+    }
+
+    assert_eq!(frames_played, callback_sizes.iter().sum::<usize>());
+
+    drop(engine);
+    garbage_disposer.flush();
+}
@@ -0,0 +1,234 @@
+use crate::{
+    core::{
+        engine::{
+            compiledprocessor::{CompiledProcessorLink, CompiledSoundInputNode},
+            compiledsoundgraph::CompiledSoundGraph,
+            diffgraph::diff_sound_graph,
+            garbage::{new_garbage_disposer, Garbage, GarbageChute},
+            scratcharena::ScratchArena,
+            soundengine::{BlockSize, PanicSwitch, TestTone},
+            soundgraphcompiler::SoundGraphCompiler,
+            voicelimit::VoiceLimit,
+        },
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundinput::{
+                AnyProcessorInput, DisconnectBehavior, InputContext, ProcessorInputId,
+                SoundInputLocation,
+            },
+            soundprocessor::{
+                ProcessorTiming, SoundProcessorId, SoundProcessorWithId, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::wavegenerator::WaveGenerator,
+};
+
+use super::testobjects::TestStaticPassthrough;
+
+/// Render a chunk of a sine wave through a sound input with the given
+/// disconnect behavior, then disconnect the input and render two more
+/// chunks. Returns all three chunks along with the status of the last.
+fn render_disconnect(behavior: DisconnectBehavior) -> ([SoundChunk; 3], StreamStatus) {
+    let wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let wavegen_id = wavegen.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(wavegen));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let owner_id = SoundProcessorId::new_unique();
+    let location = SoundInputLocation::new(owner_id, ProcessorInputId::new_unique());
+    let mut node = CompiledSoundInputNode::new(
+        location,
+        1.0,
+        behavior,
+        compiler.compile_sound_processor(Some(wavegen_id)),
+    );
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let processor_timing = ProcessorTiming::new();
+    let context = AudioContext::new(
+        owner_id,
+        &processor_timing,
+        &scratch_arena,
+        argument_stack.view_at_bottom(),
+        AudioStack::Root,
+    );
+
+    let mut chunks = [SoundChunk::new(); 3];
+    assert!(node.step(&mut chunks[0], InputContext::new(&context)) == StreamStatus::Playing);
+
+    let _old_link = node.swap_link(CompiledProcessorLink::Empty);
+
+    node.step(&mut chunks[1], InputContext::new(&context));
+    let status = node.step(&mut chunks[2], InputContext::new(&context));
+
+    (chunks, status)
+}
+
+/// The largest difference between adjacent samples of the given chunks,
+/// played one after another
+fn largest_jump(chunks: &[SoundChunk]) -> f32 {
+    let samples: Vec<f32> = chunks.iter().flat_map(|c| c.l.iter().cloned()).collect();
+    samples
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_disconnect_silence_cuts_off() {
+    let (chunks, status) = render_disconnect(DisconnectBehavior::Silence);
+
+    assert!(chunks[1].l.iter().chain(&chunks[1].r).all(|x| *x == 0.0));
+    assert!(status == StreamStatus::Done);
+
+    // The signal jumps straight from wherever it was to zero
+    let last = *chunks[0].l.last().unwrap();
+    assert!(last.abs() > 0.1);
+    assert!(largest_jump(&chunks[0..2]) > 2.0 * largest_jump(&chunks[0..1]));
+}
+
+#[test]
+fn test_disconnect_hold_fades_smoothly() {
+    let (chunks, status) = render_disconnect(DisconnectBehavior::HoldAndFade);
+
+    // The fade picks up exactly where the input left off
+    assert_eq!(chunks[1].l[0], *chunks[0].l.last().unwrap());
+    assert_eq!(chunks[1].r[0], *chunks[0].r.last().unwrap());
+
+    // No step is much larger than those of the sine wave itself
+    let sine_jump = largest_jump(&chunks[0..1]);
+    assert!(largest_jump(&chunks[0..2]) < 1.1 * sine_jump);
+
+    // The fade ends at silence, after which the input is done
+    let end = *chunks[1].l.last().unwrap();
+    assert!(end.abs() < 0.01);
+    assert!(chunks[2].l.iter().chain(&chunks[2].r).all(|x| *x == 0.0));
+    assert!(status == StreamStatus::Done);
+}
+
+/// Compile the changes between the two graphs and apply them to the
+/// compiled graph, as the sound engine does
+pub(super) fn apply_diff<'ctx>(
+    compiled_graph: &mut CompiledSoundGraph<'ctx>,
+    graph_before: &SoundGraph,
+    graph_after: &SoundGraph,
+    jit_cache: &JitCache<'ctx>,
+    garbage_chute: &GarbageChute<'ctx>,
+) {
+    for edit in diff_sound_graph(
+        graph_before,
+        graph_after,
+        jit_cache,
+        &PanicSwitch::new(),
+        &TestTone::new(),
+        &VoiceLimit::new(),
+        &BlockSize::new(),
+    ) {
+        compiled_graph.make_edit(edit, garbage_chute);
+    }
+}
+
+/// Render a chunk of a sine wave through a static processor whose input
+/// has the given disconnect behavior, then disconnect the input by editing
+/// the graph as the sound engine does and render two more chunks. Returns
+/// the output of the static processor for all three chunks.
+fn render_disconnect_by_editing(behavior: DisconnectBehavior) -> [SoundChunk; 3] {
+    let mut passthrough = SoundProcessorWithId::<TestStaticPassthrough>::new_default();
+    let wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let passthrough_id = passthrough.id();
+
+    passthrough.input.set_target(Some(wavegen.id()));
+    passthrough.input.set_disconnect_behavior(behavior);
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(passthrough));
+    graph.add_sound_processor(Box::new(wavegen));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let (garbage_chute, garbage_disposer) = new_garbage_disposer();
+
+    let mut compiled_graph = CompiledSoundGraph::new();
+
+    apply_diff(
+        &mut compiled_graph,
+        &SoundGraph::new(),
+        &graph,
+        &jit_cache,
+        &garbage_chute,
+    );
+
+    let arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut chunks = [SoundChunk::new(); 3];
+
+    let render = |compiled_graph: &CompiledSoundGraph, chunk: &mut SoundChunk| {
+        let node = &compiled_graph.static_processors()[0];
+        assert_eq!(node.id(), passthrough_id);
+        node.invoke_externally(&arena, &argument_stack);
+        *chunk = *node.borrow_cache().cached_output();
+    };
+
+    render(&compiled_graph, &mut chunks[0]);
+
+    // Disconnecting the input replaces every compiled node
+    graph
+        .sound_processor_mut(passthrough_id)
+        .unwrap()
+        .downcast_mut::<TestStaticPassthrough>()
+        .unwrap()
+        .input
+        .set_target(None);
+    jit_cache.refresh(&graph);
+    apply_diff(
+        &mut compiled_graph,
+        &graph,
+        &graph,
+        &jit_cache,
+        &garbage_chute,
+    );
+
+    render(&compiled_graph, &mut chunks[1]);
+    render(&compiled_graph, &mut chunks[2]);
+
+    compiled_graph.toss(&garbage_chute);
+    garbage_disposer.flush();
+
+    chunks
+}
+
+#[test]
+fn test_disconnect_hold_fades_after_graph_edit() {
+    let chunks = render_disconnect_by_editing(DisconnectBehavior::HoldAndFade);
+
+    // The fade picks up where the old compiled input left off
+    assert!(chunks[0].l.last().unwrap().abs() > 0.1);
+    assert_eq!(chunks[1].l[0], *chunks[0].l.last().unwrap());
+    let sine_jump = largest_jump(&chunks[0..1]);
+    assert!(largest_jump(&chunks[0..2]) < 1.1 * sine_jump);
+
+    // The fade ends at silence
+    assert!(chunks[1].l.last().unwrap().abs() < 0.01);
+    assert!(chunks[2].l.iter().chain(&chunks[2].r).all(|x| *x == 0.0));
+}
+
+#[test]
+fn test_disconnect_silence_after_graph_edit() {
+    let chunks = render_disconnect_by_editing(DisconnectBehavior::Silence);
+
+    assert!(chunks[0].l.last().unwrap().abs() > 0.1);
+    assert!(chunks[1].l.iter().chain(&chunks[1].r).all(|x| *x == 0.0));
+}
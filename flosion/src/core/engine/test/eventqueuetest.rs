@@ -0,0 +1,169 @@
+use crate::{
+    core::{
+        engine::{
+            compiledsoundgraph::CompiledSoundGraph, garbage::new_garbage_disposer,
+            garbage::Garbage, scratcharena::ScratchArena, soundengine::event_queue,
+        },
+        expression::expressiongraph::ExpressionTarget,
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            argument::ProcessorArgumentLocation,
+            expression::ExpressionParameterTarget,
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::SoundProcessorWithId,
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::{
+        keyboard::{KeyId, Keyboard},
+        wavegenerator::{WaveGenerator, WaveGeneratorWaveform},
+    },
+};
+
+use super::disconnectbehaviortest::apply_diff;
+
+#[test]
+fn test_events_are_received_at_their_sample_offset() {
+    let (mut sender, mut receiver) = event_queue::<usize>(16);
+
+    let k = 37;
+    assert_eq!(sender.now(), 0);
+    sender.send(1);
+    sender.send_at((2 * CHUNK_SIZE + k) as u64, 2);
+
+    // Immediate events take effect at the start of the next chunk
+    assert_eq!(receiver.receive(), Some((0, 1)));
+    assert_eq!(receiver.receive(), None);
+    receiver.finish_chunk();
+
+    // The scheduled event is held back until its chunk
+    assert_eq!(sender.now(), CHUNK_SIZE as u64);
+    assert_eq!(receiver.receive(), None);
+    receiver.finish_chunk();
+
+    assert_eq!(receiver.receive(), Some((k, 2)));
+    assert_eq!(receiver.receive(), None);
+    receiver.finish_chunk();
+
+    // Late events take effect as soon as possible
+    sender.send_at(CHUNK_SIZE as u64, 3);
+    assert_eq!(receiver.receive(), Some((0, 3)));
+}
+
+#[test]
+fn test_receiver_takes_over_position_in_the_queue() {
+    // The receiver kept by the queue's owner, which is cloned into
+    // each compiled instance of the owner but never reads by itself
+    let (mut sender, receiver) = event_queue::<usize>(16);
+
+    sender.send(1);
+    sender.send_at((CHUNK_SIZE + 5) as u64, 2);
+
+    let mut compiled = receiver.clone();
+    assert_eq!(compiled.receive(), Some((0, 1)));
+    // The second event is read but held back until the next chunk
+    assert_eq!(compiled.receive(), None);
+    compiled.finish_chunk();
+
+    // When the owner is compiled anew, the event already received must
+    // not arrive again and the pending event must not be lost
+    let mut recompiled = receiver.clone();
+    recompiled.take_over(&mut compiled);
+    drop(compiled);
+    assert_eq!(recompiled.receive(), Some((5, 2)));
+    assert_eq!(recompiled.receive(), None);
+}
+
+#[test]
+fn test_recompiled_keyboard_continues_from_its_position_in_the_queue() {
+    let keyboard = SoundProcessorWithId::<Keyboard>::new_default();
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    wavegen.set_waveform(WaveGeneratorWaveform::Expression);
+
+    let keyboard_id = keyboard.id();
+    let wavegen_id = wavegen.id();
+
+    // The wave generator's output is simply the key's velocity
+    let velocity = ProcessorArgumentLocation::new(keyboard_id, keyboard.key_velocity.id());
+    let param_id = wavegen
+        .amplitude
+        .add_target(ExpressionParameterTarget::Argument(velocity));
+    let expr_graph = wavegen.amplitude.graph_mut();
+    expr_graph
+        .connect_result(
+            expr_graph.results()[0].id(),
+            ExpressionTarget::Parameter(param_id),
+        )
+        .unwrap();
+
+    let input_location = SoundInputLocation::new(keyboard_id, keyboard.input.id());
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(keyboard));
+    graph.add_sound_processor(Box::new(wavegen));
+    graph
+        .connect_sound_input(input_location, wavegen_id)
+        .unwrap();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let (garbage_chute, garbage_disposer) = new_garbage_disposer();
+    let mut compiled_graph = CompiledSoundGraph::new();
+    apply_diff(
+        &mut compiled_graph,
+        &SoundGraph::new(),
+        &graph,
+        &jit_cache,
+        &garbage_chute,
+    );
+
+    let arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let render = |compiled_graph: &CompiledSoundGraph| {
+        let node = &compiled_graph.static_processors()[0];
+        assert_eq!(node.id(), keyboard_id);
+        node.invoke_externally(&arena, &argument_stack);
+        let chunk: SoundChunk = *node.borrow_cache().cached_output();
+        chunk
+    };
+
+    let keyboard = graph
+        .sound_processor(keyboard_id)
+        .unwrap()
+        .downcast::<Keyboard>()
+        .unwrap();
+
+    // The first key is received before the keyboard is compiled anew,
+    // the second one is still pending by then
+    let k = 37;
+    keyboard.start_key_at(k as u64, KeyId(0), 440.0, 0.75);
+    keyboard.start_key_at((2 * CHUNK_SIZE + k) as u64, KeyId(1), 440.0, 0.5);
+
+    let chunk = render(&compiled_graph);
+    assert_eq!(chunk.l[k - 1], 0.0);
+    assert!(chunk.l[k] > 0.0);
+
+    // Replace the keyboard's compiled node, which starts without any
+    // playing keys, and must neither receive the first key again nor
+    // lose the second one
+    apply_diff(
+        &mut compiled_graph,
+        &graph,
+        &graph,
+        &jit_cache,
+        &garbage_chute,
+    );
+
+    let chunk = render(&compiled_graph);
+    assert!(chunk.l.iter().all(|x| *x == 0.0));
+
+    let chunk = render(&compiled_graph);
+    assert_eq!(chunk.l[k - 1], 0.0);
+    assert!(chunk.l[k] > 0.0);
+
+    compiled_graph.toss(&garbage_chute);
+    garbage_disposer.flush();
+}
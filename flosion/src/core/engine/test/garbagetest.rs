@@ -0,0 +1,166 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, ThreadId},
+};
+
+use crate::core::engine::garbage::new_garbage_disposer;
+
+/// An item which records the thread it was dropped on
+struct DropRecorder {
+    drops: Arc<Mutex<Vec<ThreadId>>>,
+}
+
+impl Drop for DropRecorder {
+    fn drop(&mut self) {
+        self.drops.lock().unwrap().push(thread::current().id());
+    }
+}
+
+const NUM_ITEMS: usize = 500;
+
+#[test]
+fn test_flush_drops_everything_on_disposer_thread() {
+    let drops = Arc::new(Mutex::new(Vec::new()));
+
+    let (chute, disposer) = new_garbage_disposer();
+
+    // Toss many items from a different thread, standing in for the audio thread
+    let audio_thread_id = thread::scope(|scope| {
+        let drops = &drops;
+        scope
+            .spawn(move || {
+                for _ in 0..NUM_ITEMS {
+                    chute.send_box(Box::new(DropRecorder {
+                        drops: Arc::clone(drops),
+                    }));
+                }
+                thread::current().id()
+            })
+            .join()
+            .unwrap()
+    });
+
+    assert!(drops.lock().unwrap().is_empty());
+    assert_eq!(disposer.pending(), NUM_ITEMS);
+
+    assert_eq!(disposer.flush(), NUM_ITEMS);
+    assert_eq!(disposer.pending(), 0);
+
+    let drops = drops.lock().unwrap();
+    assert_eq!(drops.len(), NUM_ITEMS);
+    assert!(drops.iter().all(|id| *id == thread::current().id()));
+    assert!(drops.iter().all(|id| *id != audio_thread_id));
+}
+
+#[test]
+fn test_clear_at_most_is_bounded() {
+    let drops = Arc::new(Mutex::new(Vec::new()));
+
+    let (chute, disposer) = new_garbage_disposer();
+
+    for _ in 0..NUM_ITEMS {
+        chute.send_box(Box::new(DropRecorder {
+            drops: Arc::clone(&drops),
+        }));
+    }
+
+    assert_eq!(disposer.clear_at_most(64), 64);
+    assert_eq!(drops.lock().unwrap().len(), 64);
+    assert_eq!(disposer.pending(), NUM_ITEMS - 64);
+
+    // Clearing in bounded batches eventually disposes of everything
+    while disposer.clear_at_most(64) > 0 {}
+    assert_eq!(drops.lock().unwrap().len(), NUM_ITEMS);
+    assert_eq!(disposer.pending(), 0);
+}
+
+#[test]
+fn test_arc_is_dropped_only_with_last_reference() {
+    let drops = Arc::new(Mutex::new(Vec::new()));
+
+    let (chute, disposer) = new_garbage_disposer();
+
+    let item = Arc::new(DropRecorder {
+        drops: Arc::clone(&drops),
+    });
+    chute.send_arc(Arc::clone(&item) as _);
+
+    assert_eq!(disposer.flush(), 1);
+    assert!(drops.lock().unwrap().is_empty());
+
+    std::mem::drop(item);
+    assert_eq!(drops.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_overflowing_chute_holds_onto_items() {
+    const NUM_OVERFLOWING_ITEMS: usize = 3000;
+
+    let drops = Arc::new(Mutex::new(Vec::new()));
+
+    let (chute, disposer) = new_garbage_disposer();
+
+    // Toss far more items than the chute holds while the receiving end
+    // only clears a few of them at a time
+    for i in 0..NUM_OVERFLOWING_ITEMS {
+        chute.send_box(Box::new(DropRecorder {
+            drops: Arc::clone(&drops),
+        }));
+        if i % 100 == 0 {
+            disposer.clear_at_most(64);
+        }
+    }
+
+    assert_eq!(
+        disposer.pending() + drops.lock().unwrap().len(),
+        NUM_OVERFLOWING_ITEMS
+    );
+
+    // Retrying once per chunk eventually gets everything through
+    loop {
+        chute.retry_overflow();
+        if disposer.clear_at_most(64) == 0 {
+            break;
+        }
+    }
+    assert_eq!(disposer.pending(), 0);
+    assert_eq!(drops.lock().unwrap().len(), NUM_OVERFLOWING_ITEMS);
+}
+
+#[test]
+fn test_overflow_past_capacity_is_leaked_and_rest_is_disposed_after_chute_is_dropped() {
+    const NUM_OVERFLOWING_ITEMS: usize = 3000;
+
+    let drops = Arc::new(Mutex::new(Vec::new()));
+
+    let (chute, disposer) = new_garbage_disposer();
+
+    // Toss more items than both the chute and its overflow hold, then
+    // drop the chute on the same thread, as the audio thread does when
+    // it shuts down
+    thread::scope(|scope| {
+        let drops = &drops;
+        scope.spawn(move || {
+            for _ in 0..NUM_OVERFLOWING_ITEMS {
+                chute.send_box(Box::new(DropRecorder {
+                    drops: Arc::clone(drops),
+                }));
+            }
+            std::mem::drop(chute);
+        });
+    });
+
+    assert!(drops.lock().unwrap().is_empty());
+    let held = disposer.pending();
+    assert!(held < NUM_OVERFLOWING_ITEMS);
+    assert_eq!(held + disposer.leaked(), NUM_OVERFLOWING_ITEMS);
+
+    // Everything which was held onto, including what was still in the
+    // overflow, is dropped by the disposer
+    assert_eq!(disposer.flush(), held);
+    assert_eq!(disposer.pending(), 0);
+
+    let drops = drops.lock().unwrap();
+    assert_eq!(drops.len(), held);
+    assert!(drops.iter().all(|id| *id == thread::current().id()));
+}
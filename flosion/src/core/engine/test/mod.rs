@@ -0,0 +1,10 @@
+mod blockbridgetest;
+mod disconnectbehaviortest;
+mod eventqueuetest;
+mod garbagetest;
+mod scratcharenatest;
+mod solotest;
+mod soundenginereporttest;
+mod soundenginetest;
+mod testobjects;
+mod voicelimittest;
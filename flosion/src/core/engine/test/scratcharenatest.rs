@@ -0,0 +1,153 @@
+use crate::{
+    core::{
+        engine::{
+            compiledsoundgraph::CompiledSoundGraph,
+            diffgraph::diff_sound_graph,
+            garbage::{new_garbage_disposer, Garbage},
+            scratcharena::{ScratchArena, ScratchReservation},
+            soundengine::{
+                scratch_slices_needed, BlockSize, PanicSwitch, TestTone,
+                SCRATCH_SLICES_PER_PROCESSOR,
+            },
+            voicelimit::VoiceLimit,
+        },
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            soundgraph::SoundGraph, soundinput::AnyProcessorInput,
+            soundprocessor::SoundProcessorWithId,
+        },
+        soundchunk::CHUNK_SIZE,
+    },
+    objects::{definitions::Definitions, whitenoise::WhiteNoise},
+};
+
+use super::testobjects::TestStaticPassthrough;
+
+#[test]
+fn test_reserve_slices() {
+    let arena = ScratchArena::new();
+
+    arena.reserve(CHUNK_SIZE, 3);
+    assert_eq!(arena.reserved_slices(CHUNK_SIZE), 3);
+
+    // Borrowing up to the reserved amount doesn't allocate more
+    {
+        let _a = arena.borrow_slice(CHUNK_SIZE);
+        let _b = arena.borrow_slice(CHUNK_SIZE);
+        assert_eq!(arena.reserved_slices(CHUNK_SIZE), 3);
+        assert_eq!(arena.high_water_mark(CHUNK_SIZE), 2);
+    }
+
+    // Reserving no more than what's there does nothing
+    arena.reserve(CHUNK_SIZE, 2);
+    assert_eq!(arena.reserved_slices(CHUNK_SIZE), 3);
+
+    // Other sizes are counted separately
+    assert_eq!(arena.reserved_slices(16), 0);
+    assert_eq!(arena.high_water_mark(16), 0);
+}
+
+#[test]
+fn test_high_water_mark() {
+    let arena = ScratchArena::new();
+
+    {
+        let _a = arena.borrow_slice(CHUNK_SIZE);
+        let _b = arena.borrow_slice(CHUNK_SIZE);
+        let _c = arena.borrow_slice(CHUNK_SIZE);
+    }
+    {
+        let _a = arena.borrow_slice(CHUNK_SIZE);
+    }
+
+    assert_eq!(arena.high_water_mark(CHUNK_SIZE), 3);
+    assert_eq!(arena.reserved_slices(CHUNK_SIZE), 3);
+}
+
+#[test]
+fn test_add_reservation() {
+    let arena = ScratchArena::new();
+    arena.reserve(CHUNK_SIZE, 2);
+
+    // Only the slices which the arena doesn't have yet are allocated
+    let mut reservation = ScratchReservation::new(CHUNK_SIZE, 2, 5);
+    assert_eq!(reservation.num_slices(), 3);
+
+    arena.add_reservation(&mut reservation);
+    assert_eq!(arena.reserved_slices(CHUNK_SIZE), 5);
+
+    // The reservation is left with the arena's old, empty Vec
+    assert_eq!(reservation.num_slices(), 0);
+
+    {
+        let _slices: Vec<_> = (0..5).map(|_| arena.borrow_slice(CHUNK_SIZE)).collect();
+        assert_eq!(arena.reserved_slices(CHUNK_SIZE), 5);
+        assert_eq!(arena.high_water_mark(CHUNK_SIZE), 5);
+    }
+    assert_eq!(arena.reserved_slices(CHUNK_SIZE), 5);
+}
+
+#[test]
+fn test_reserve_for_compiled_graph() {
+    // static passthrough -> definitions -> white noise
+    let mut passthrough = SoundProcessorWithId::<TestStaticPassthrough>::new_default();
+    let mut definitions = SoundProcessorWithId::<Definitions>::new_default();
+    let whitenoise = SoundProcessorWithId::<WhiteNoise>::new_default();
+
+    passthrough.input.set_target(Some(definitions.id()));
+    definitions.sound_input.set_target(Some(whitenoise.id()));
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(passthrough));
+    graph.add_sound_processor(Box::new(definitions));
+    graph.add_sound_processor(Box::new(whitenoise));
+
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let (garbage_chute, garbage_disposer) = new_garbage_disposer();
+
+    let edits = diff_sound_graph(
+        &SoundGraph::new(),
+        &graph,
+        &jit_cache,
+        &PanicSwitch::new(),
+        &TestTone::new(),
+        &VoiceLimit::new(),
+        &BlockSize::new(),
+    );
+
+    let scratch_slices = scratch_slices_needed(&edits);
+    assert_eq!(scratch_slices, 3 * SCRATCH_SLICES_PER_PROCESSOR);
+
+    let mut compiled_graph = CompiledSoundGraph::new();
+    for edit in edits {
+        compiled_graph.make_edit(edit, &garbage_chute);
+    }
+
+    assert_eq!(compiled_graph.num_processor_nodes(), 3);
+
+    let arena = ScratchArena::new();
+    arena.add_reservation(&mut ScratchReservation::new(CHUNK_SIZE, 0, scratch_slices));
+
+    assert_eq!(arena.reserved_slices(CHUNK_SIZE), scratch_slices);
+
+    // Processing the graph stays within the reserved space
+    let argument_stack = ArgumentStack::new();
+    for _ in 0..4 {
+        for node in compiled_graph.static_processors() {
+            node.invoke_externally(&arena, &argument_stack);
+        }
+    }
+    assert!(arena.high_water_mark(CHUNK_SIZE) <= arena.reserved_slices(CHUNK_SIZE));
+    assert_eq!(
+        arena.reserved_slices(CHUNK_SIZE),
+        3 * SCRATCH_SLICES_PER_PROCESSOR
+    );
+
+    compiled_graph.toss(&garbage_chute);
+    garbage_disposer.flush();
+}
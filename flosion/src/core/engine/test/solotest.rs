@@ -0,0 +1,109 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::{
+                ProcessorTiming, SoundProcessor, SoundProcessorId, SoundProcessorWithId,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::{mixer::Mixer, whitenoise::WhiteNoise},
+};
+
+const NUM_CHUNKS: usize = 4;
+
+/// Compile the given processor within the graph and render several chunks from it
+fn render<T: 'static + SoundProcessor>(graph: &SoundGraph, id: SoundProcessorId) -> Vec<f32> {
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(graph);
+    let mut compiler = SoundGraphCompiler::new(graph, &jit_cache);
+
+    let processor = graph.sound_processor(id).unwrap().downcast::<T>().unwrap();
+    let mut compiled = processor.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut samples = Vec::new();
+    for _ in 0..NUM_CHUNKS {
+        let mut context = AudioContext::new(
+            id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        T::process_audio(&mut compiled, &mut chunk, &mut context);
+        samples.extend_from_slice(&chunk.l);
+        samples.extend_from_slice(&chunk.r);
+        processor_timing.advance_one_chunk();
+    }
+    samples
+}
+
+#[test]
+fn test_soloing_one_of_two_mixed_sources() {
+    let mut graph = SoundGraph::new();
+
+    let mut noise1 = SoundProcessorWithId::<WhiteNoise>::new_default();
+    noise1.set_seed(1);
+    let mut noise2 = SoundProcessorWithId::<WhiteNoise>::new_default();
+    noise2.set_seed(2);
+    let mixer = SoundProcessorWithId::<Mixer>::new_default();
+
+    let noise1_id = noise1.id();
+    let noise2_id = noise2.id();
+    let mixer_id = mixer.id();
+    let mixer_inputs: Vec<SoundInputLocation> = mixer
+        .inputs()
+        .iter()
+        .map(|i| SoundInputLocation::new(mixer_id, i.id()))
+        .collect();
+
+    graph.add_sound_processor(Box::new(noise1));
+    graph.add_sound_processor(Box::new(noise2));
+    graph.add_sound_processor(Box::new(mixer));
+
+    // Each source on its own, before connecting anything
+    let alone1 = render::<WhiteNoise>(&graph, noise1_id);
+    let alone2 = render::<WhiteNoise>(&graph, noise2_id);
+
+    graph
+        .connect_sound_input(mixer_inputs[0], noise1_id)
+        .unwrap();
+    graph
+        .connect_sound_input(mixer_inputs[1], noise2_id)
+        .unwrap();
+    assert_eq!(graph.validate(), Ok(()));
+
+    let both: Vec<f32> = alone1.iter().zip(&alone2).map(|(a, b)| a + b).collect();
+    assert_eq!(render::<Mixer>(&graph, mixer_id), both);
+
+    // Soloing the first source leaves only it at the output
+    graph.set_soloed(noise1_id, true);
+    assert_eq!(render::<Mixer>(&graph, mixer_id), alone1);
+
+    // Soloing both sums them again
+    graph.set_soloed(noise2_id, true);
+    assert_eq!(render::<Mixer>(&graph, mixer_id), both);
+
+    // Unsoloing the first leaves only the second
+    graph.set_soloed(noise1_id, false);
+    assert_eq!(render::<Mixer>(&graph, mixer_id), alone2);
+
+    // Connections are untouched throughout
+    for (input, target) in mixer_inputs.iter().zip([noise1_id, noise2_id]) {
+        assert_eq!(
+            graph.with_sound_input(*input, |i| i.target()),
+            Some(Some(target))
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    core::{
+        engine::{
+            compiledsoundgraph::CompiledSoundGraph,
+            diffgraph::diff_sound_graph,
+            garbage::{new_garbage_disposer, Garbage},
+            scratcharena::ScratchArena,
+            soundengine::{BlockSize, PanicSwitch, TestTone},
+            soundenginereport::SoundEngineReport,
+            voicelimit::VoiceLimit,
+        },
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            soundgraph::SoundGraph, soundinput::AnyProcessorInput,
+            soundprocessor::SoundProcessorWithId,
+        },
+        soundchunk::CHUNK_SIZE,
+    },
+    objects::{
+        keyboard::{KeyId, Keyboard},
+        wavegenerator::WaveGenerator,
+    },
+};
+
+#[test]
+fn test_report_keyboard_voices() {
+    let mut keyboard = SoundProcessorWithId::<Keyboard>::new_default();
+    let wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let keyboard_id = keyboard.id();
+
+    keyboard.input.set_target(Some(wavegen.id()));
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(keyboard));
+    graph.add_sound_processor(Box::new(wavegen));
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let (garbage_chute, garbage_disposer) = new_garbage_disposer();
+
+    let mut compiled_graph = CompiledSoundGraph::new();
+    for edit in diff_sound_graph(
+        &SoundGraph::new(),
+        &graph,
+        &jit_cache,
+        &PanicSwitch::new(),
+        &TestTone::new(),
+        &VoiceLimit::new(),
+        &BlockSize::new(),
+    ) {
+        compiled_graph.make_edit(edit, &garbage_chute);
+    }
+
+    let arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut report = SoundEngineReport::new();
+
+    let process_chunk = || {
+        for node in compiled_graph.static_processors() {
+            node.invoke_externally(&arena, &argument_stack);
+        }
+    };
+
+    // Nothing is playing at first
+    process_chunk();
+    report.regenerate(&compiled_graph, &arena);
+    assert!(report
+        .processor_report(keyboard_id)
+        .unwrap()
+        .voices()
+        .is_empty());
+
+    // A4 and A5
+    let keyboard = graph
+        .sound_processor(keyboard_id)
+        .unwrap()
+        .downcast::<Keyboard>()
+        .unwrap();
+    keyboard.start_key(KeyId(0), 440.0, 1.0);
+    keyboard.start_key(KeyId(1), 880.0, 1.0);
+
+    process_chunk();
+    process_chunk();
+    report.regenerate(&compiled_graph, &arena);
+
+    let voices = report.processor_report(keyboard_id).unwrap().voices();
+    assert_eq!(voices.len(), 2);
+
+    let mut notes: Vec<f32> = voices.iter().map(|v| v.note()).collect();
+    notes.sort_by(f32::total_cmp);
+    assert!((notes[0] - 69.0).abs() < 1e-4);
+    assert!((notes[1] - 81.0).abs() < 1e-4);
+
+    for voice in voices {
+        assert_eq!(voice.age_samples(), 2 * CHUNK_SIZE);
+        assert!(!voice.released());
+        // The wave generator plays at full amplitude
+        assert!(voice.level() > 0.9 && voice.level() <= 1.0);
+    }
+
+    // Releasing a key is reported until the voice finishes
+    keyboard.release_key(KeyId(0));
+    process_chunk();
+    report.regenerate(&compiled_graph, &arena);
+    let voices = report.processor_report(keyboard_id).unwrap().voices();
+    assert_eq!(voices.len(), 1);
+    assert!((voices[0].note() - 81.0).abs() < 1e-4);
+
+    compiled_graph.toss(&garbage_chute);
+    garbage_disposer.flush();
+}
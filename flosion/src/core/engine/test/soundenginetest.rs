@@ -0,0 +1,112 @@
+use std::{thread, time::Duration};
+
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stash, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        engine::soundengine::{create_sound_engine, StopButton},
+        expression::expressionobject::ExpressionObjectFactory,
+        jit::cache::JitCache,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            context::AudioContext,
+            soundgraph::SoundGraph,
+            soundobject::SoundObjectFactory,
+            soundprocessor::{SoundProcessor, SoundProcessorWithId, StreamStatus},
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A static processor which takes far longer than the duration
+/// of a chunk to produce each chunk
+#[derive(ProcessorComponent)]
+struct SlowProcessor {}
+
+impl SoundProcessor for SlowProcessor {
+    fn new(_args: &ParsedArguments) -> SlowProcessor {
+        SlowProcessor {}
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn process_audio(
+        _processor: &mut Self::CompiledType<'_>,
+        _dst: &mut SoundChunk,
+        _context: &mut AudioContext,
+    ) -> StreamStatus {
+        let chunk_duration = CHUNK_SIZE as f64 / SAMPLE_FREQUENCY as f64;
+        thread::sleep(Duration::from_secs_f64(2.0 * chunk_duration));
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for SlowProcessor {
+    const TYPE: ObjectType = ObjectType::new("slowprocessor");
+}
+
+impl Stashable<StashingContext> for SlowProcessor {
+    fn stash(&self, _stasher: &mut Stasher<StashingContext>) {}
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for SlowProcessor {
+    fn unstash_inplace(
+        &mut self,
+        _unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_underruns_are_counted() {
+    let inkwell_context = inkwell::context::Context::create();
+
+    let stop_button = StopButton::new();
+    let (mut engine_interface, engine, garbage_disposer) = create_sound_engine(&stop_button);
+
+    let mut sound_object_factory = SoundObjectFactory::new_empty();
+    sound_object_factory.register::<SoundProcessorWithId<SlowProcessor>>();
+    let expression_object_factory = ExpressionObjectFactory::new_empty();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(
+        SoundProcessorWithId::<SlowProcessor>::new_default(),
+    ));
+
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let stash = Stash::new();
+
+    assert_eq!(engine_interface.underrun_count(), 0);
+
+    thread::scope(|scope| {
+        let audio_thread = scope.spawn(move || engine.run());
+
+        engine_interface
+            .update(
+                &graph,
+                &jit_cache,
+                &stash,
+                &sound_object_factory,
+                &expression_object_factory,
+            )
+            .unwrap();
+
+        thread::sleep(Duration::from_millis(250));
+
+        stop_button.stop();
+        audio_thread.join().unwrap();
+    });
+
+    garbage_disposer.flush();
+
+    assert!(engine_interface.underrun_count() > 0);
+}
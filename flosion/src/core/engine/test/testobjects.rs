@@ -0,0 +1,64 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{SoundProcessor, StreamStatus},
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A static processor which simply passes its input through
+#[derive(ProcessorComponent)]
+pub(super) struct TestStaticPassthrough {
+    pub(super) input: SingleInput,
+}
+
+impl SoundProcessor for TestStaticPassthrough {
+    fn new(_args: &ParsedArguments) -> TestStaticPassthrough {
+        TestStaticPassthrough {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn process_audio(
+        processor: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        processor.input.step(dst, InputContext::new(context))
+    }
+}
+
+impl WithObjectType for TestStaticPassthrough {
+    const TYPE: ObjectType = ObjectType::new("teststaticpassthrough");
+}
+
+impl Stashable<StashingContext> for TestStaticPassthrough {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for TestStaticPassthrough {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{
+    core::{
+        engine::{
+            compiledprocessor::CompiledSoundInputNode,
+            scratcharena::ScratchArena,
+            soundenginereport::VoiceReport,
+            soundgraphcompiler::SoundGraphCompiler,
+            voicelimit::{VoiceLimit, VoiceLimitPolicy},
+        },
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::{
+                CompiledComponentVisitor, CompiledProcessorComponent, ProcessorComponent,
+                ProcessorTiming, SoundProcessor, SoundProcessorId, SoundProcessorWithId,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::{
+        keyboard::{KeyId, Keyboard},
+        wavegenerator::WaveGenerator,
+    },
+};
+
+/// Add a keyboard playing a wave generator with the given amplitude
+/// to the graph
+fn add_keyboard(graph: &mut SoundGraph, amplitude: f32) -> SoundProcessorId {
+    let keyboard = SoundProcessorWithId::<Keyboard>::new_default();
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    wavegen.amplitude.graph_mut().results_mut()[0].set_default_value(amplitude);
+    let keyboard_id = keyboard.id();
+    let wavegen_id = wavegen.id();
+    let input_location = SoundInputLocation::new(keyboard_id, keyboard.input.id());
+    graph.add_sound_processor(Box::new(keyboard));
+    graph.add_sound_processor(Box::new(wavegen));
+    graph
+        .connect_sound_input(input_location, wavegen_id)
+        .unwrap();
+    keyboard_id
+}
+
+struct NoteCollector(Vec<i32>);
+
+impl CompiledComponentVisitor for NoteCollector {
+    fn input_node(&mut self, _input: &CompiledSoundInputNode) {}
+
+    fn voice(&mut self, voice: VoiceReport) {
+        self.0.push(voice.note().round() as i32);
+    }
+}
+
+/// Play keys one chunk apart on two keyboards, a loud one and a quiet
+/// one, which share a limit of two voices. Each key is given as the
+/// index of the keyboard and the MIDI note to play. Returns the notes
+/// which are still playing afterwards, in order.
+fn notes_playing_after(policy: VoiceLimitPolicy, keys: &[(usize, i32)]) -> Vec<i32> {
+    let mut graph = SoundGraph::new();
+    let keyboard_ids = [add_keyboard(&mut graph, 1.0), add_keyboard(&mut graph, 0.1)];
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let voice_limit = VoiceLimit::new();
+    voice_limit.set_max_voices(Some(2));
+    voice_limit.set_policy(policy);
+
+    let mut compiler =
+        SoundGraphCompiler::new(&graph, &jit_cache).with_voice_limit(voice_limit.clone());
+
+    let keyboards: Vec<_> = keyboard_ids
+        .iter()
+        .map(|id| {
+            graph
+                .sound_processor(*id)
+                .unwrap()
+                .downcast::<Keyboard>()
+                .unwrap()
+        })
+        .collect();
+    let mut compiled: Vec<_> = keyboards
+        .iter()
+        .zip(keyboard_ids)
+        .map(|(keyboard, id)| keyboard.compile(id, &mut compiler))
+        .collect();
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut timings = [ProcessorTiming::new(), ProcessorTiming::new()];
+
+    for (key, (keyboard_index, note)) in keys.iter().enumerate() {
+        let frequency = 440.0 * 2.0_f32.powf((*note - 69) as f32 / 12.0);
+        keyboards[*keyboard_index].start_key(KeyId(key), frequency, 1.0);
+        for i in 0..2 {
+            let mut context = AudioContext::new(
+                keyboard_ids[i],
+                &timings[i],
+                &scratch_arena,
+                argument_stack.view_at_bottom(),
+                AudioStack::Root,
+            );
+            let mut chunk = SoundChunk::new();
+            Keyboard::process_audio(&mut compiled[i], &mut chunk, &mut context);
+            timings[i].advance_one_chunk();
+        }
+        assert!(voice_limit.active_voices() <= 2);
+    }
+
+    let mut collector = NoteCollector(Vec::new());
+    for c in &compiled {
+        c.visit(&mut collector);
+    }
+    assert_eq!(voice_limit.active_voices(), collector.0.len());
+    collector.0.sort();
+    collector.0
+}
+
+#[test]
+fn test_voice_limit_steals_oldest() {
+    let notes = notes_playing_after(VoiceLimitPolicy::StealOldest, &[(0, 57), (1, 63), (0, 69)]);
+    assert_eq!(notes, vec![63, 69]);
+}
+
+#[test]
+fn test_voice_limit_steals_quietest() {
+    let notes = notes_playing_after(
+        VoiceLimitPolicy::StealQuietest,
+        &[(0, 57), (1, 63), (0, 69)],
+    );
+    assert_eq!(notes, vec![57, 69]);
+}
+
+#[test]
+fn test_voice_limit_rejects_new() {
+    let notes = notes_playing_after(VoiceLimitPolicy::RejectNew, &[(0, 57), (1, 63), (0, 69)]);
+    assert_eq!(notes, vec![57, 63]);
+}
+
+#[test]
+fn test_restarted_voice_keeps_its_place() {
+    let voice_limit = VoiceLimit::new();
+    voice_limit.set_max_voices(Some(1));
+    voice_limit.set_policy(VoiceLimitPolicy::RejectNew);
+
+    // A voice which is reused for a new note keeps its place
+    let first = voice_limit.start_voice().unwrap();
+    voice_limit.restart_voice(first);
+    assert!(!voice_limit.is_stolen(first));
+    assert_eq!(voice_limit.start_voice(), None);
+
+    voice_limit.end_voice(first);
+    assert_eq!(voice_limit.active_voices(), 0);
+    assert!(voice_limit.start_voice().is_some());
+}
+
+/// Play a key on one keyboard, then steal its voice by playing a key on
+/// another keyboard under a limit of one voice. Returns the first
+/// keyboard's last sample before being stolen and its next chunk.
+fn chunk_after_stolen_voice(release_fade_samples: usize) -> (f32, Vec<f32>) {
+    let mut graph = SoundGraph::new();
+    let keyboard_ids = [add_keyboard(&mut graph, 1.0), add_keyboard(&mut graph, 1.0)];
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let voice_limit = VoiceLimit::new();
+    voice_limit.set_max_voices(Some(1));
+    voice_limit.set_policy(VoiceLimitPolicy::StealOldest);
+    voice_limit.set_release_fade_samples(release_fade_samples);
+
+    let mut compiler =
+        SoundGraphCompiler::new(&graph, &jit_cache).with_voice_limit(voice_limit.clone());
+
+    let keyboards: Vec<_> = keyboard_ids
+        .iter()
+        .map(|id| {
+            graph
+                .sound_processor(*id)
+                .unwrap()
+                .downcast::<Keyboard>()
+                .unwrap()
+        })
+        .collect();
+    let mut compiled: Vec<_> = keyboards
+        .iter()
+        .zip(keyboard_ids)
+        .map(|(keyboard, id)| keyboard.compile(id, &mut compiler))
+        .collect();
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut timings = [ProcessorTiming::new(), ProcessorTiming::new()];
+
+    let mut process = |i: usize| -> SoundChunk {
+        let mut context = AudioContext::new(
+            keyboard_ids[i],
+            &timings[i],
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        Keyboard::process_audio(&mut compiled[i], &mut chunk, &mut context);
+        timings[i].advance_one_chunk();
+        chunk
+    };
+
+    keyboards[0].start_key(KeyId(0), 100.0, 1.0);
+    process(0);
+    let last_sample = *process(0).l.last().unwrap();
+
+    // Starting a key on the second keyboard steals the first's voice
+    keyboards[1].start_key(KeyId(1), 100.0, 1.0);
+    process(1);
+    assert_eq!(voice_limit.active_voices(), 1);
+
+    (last_sample, process(0).l.to_vec())
+}
+
+#[test]
+fn test_stolen_voice_fades_out() {
+    let fade_samples = 100;
+    let (last_sample, samples) = chunk_after_stolen_voice(fade_samples);
+    assert!(last_sample.abs() > 1e-3);
+
+    // The fade picks up where the voice left off...
+    assert!((samples[0] - last_sample).abs() < 1e-6);
+
+    // ...ramps steadily towards zero...
+    let step = last_sample.abs() / fade_samples as f32;
+    for pair in samples[..fade_samples].windows(2) {
+        assert!(pair[1].abs() <= pair[0].abs());
+        assert!((pair[0] - pair[1]).abs() <= step + 1e-6);
+    }
+
+    // ...and is silent once the fade has passed
+    assert!(samples[fade_samples..].iter().all(|s| *s == 0.0));
+}
+
+#[test]
+fn test_stolen_voice_stops_immediately_without_fade() {
+    let (_, samples) = chunk_after_stolen_voice(0);
+    assert!(samples.iter().all(|s| *s == 0.0));
+}
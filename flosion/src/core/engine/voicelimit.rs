@@ -0,0 +1,271 @@
+use std::sync::{
+    atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering},
+    Arc,
+};
+
+use atomic_float::AtomicF32;
+
+use crate::core::samplefrequency::SAMPLE_FREQUENCY;
+
+/// The greatest number of voices which can play at once across the
+/// whole sound engine, no matter the limit. Each voice occupies one
+/// pre-allocated slot.
+pub(crate) const MAX_TRACKED_VOICES: usize = 1024;
+
+/// The number of samples over which a voice which is cut off fades out
+/// by default. This is a few milliseconds, which is short enough not to
+/// be heard as a release but long enough to avoid a click.
+pub(crate) const DEFAULT_RELEASE_FADE_SAMPLES: usize = SAMPLE_FREQUENCY / 200;
+
+/// The longest release fade which may be configured, in samples
+pub(crate) const MAX_RELEASE_FADE_SAMPLES: usize = SAMPLE_FREQUENCY / 10;
+
+/// What to do when a voice is started while the engine-wide voice
+/// limit has been reached
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VoiceLimitPolicy {
+    /// Stop the voice, anywhere in the engine, which was started longest ago
+    StealOldest,
+    /// Stop the voice, anywhere in the engine, whose most recent audio
+    /// was the quietest
+    StealQuietest,
+    /// Don't start the new voice
+    RejectNew,
+}
+
+impl VoiceLimitPolicy {
+    fn to_u8(self) -> u8 {
+        match self {
+            VoiceLimitPolicy::StealOldest => 0,
+            VoiceLimitPolicy::StealQuietest => 1,
+            VoiceLimitPolicy::RejectNew => 2,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<VoiceLimitPolicy> {
+        match x {
+            0 => Some(VoiceLimitPolicy::StealOldest),
+            1 => Some(VoiceLimitPolicy::StealQuietest),
+            2 => Some(VoiceLimitPolicy::RejectNew),
+            _ => None,
+        }
+    }
+}
+
+struct VoiceSlot {
+    /// Incremented whenever the slot is claimed or freed, so that it
+    /// is odd while the slot is in use and the owner of a voice can
+    /// tell whether its voice has since been stolen
+    generation: AtomicU64,
+
+    /// The order in which the voice started, increasing over time
+    started: AtomicU64,
+
+    /// The peak amplitude of the voice's most recent chunk of audio
+    level: AtomicF32,
+}
+
+struct VoiceLimitState {
+    /// The greatest number of voices playing at once, or zero for no limit
+    max_voices: AtomicUsize,
+    policy: AtomicU8,
+    release_fade_samples: AtomicUsize,
+    active_voices: AtomicUsize,
+    next_start: AtomicU64,
+    slots: Vec<VoiceSlot>,
+}
+
+/// A voice started through the voice limit, which stays valid until
+/// the voice is ended or stolen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct Voice {
+    index: usize,
+    generation: u64,
+}
+
+/// A thread-safe, engine-wide cap on the number of voices played at
+/// once by all polyphonic processors together, which bounds the CPU
+/// spent on them. Processors start and end every voice through it, and
+/// when the limit is reached, a voice anywhere in the engine may be
+/// stolen to make room. Voices are only ever stolen after a processor
+/// has applied its own polyphony. Voices which are cut off, rather than
+/// ending by themselves, briefly fade out. To share the same limit,
+/// simply clone it.
+pub(crate) struct VoiceLimit(Arc<VoiceLimitState>);
+
+impl VoiceLimit {
+    /// Create a new VoiceLimit without any limit, which steals the
+    /// oldest voice once a limit is set and reached
+    pub(crate) fn new() -> VoiceLimit {
+        VoiceLimit(Arc::new(VoiceLimitState {
+            max_voices: AtomicUsize::new(0),
+            policy: AtomicU8::new(VoiceLimitPolicy::StealOldest.to_u8()),
+            release_fade_samples: AtomicUsize::new(DEFAULT_RELEASE_FADE_SAMPLES),
+            active_voices: AtomicUsize::new(0),
+            next_start: AtomicU64::new(0),
+            slots: (0..MAX_TRACKED_VOICES)
+                .map(|_| VoiceSlot {
+                    generation: AtomicU64::new(0),
+                    started: AtomicU64::new(0),
+                    level: AtomicF32::new(0.0),
+                })
+                .collect(),
+        }))
+    }
+
+    /// The greatest number of voices playing at once, if limited
+    pub(crate) fn max_voices(&self) -> Option<usize> {
+        match self.0.max_voices.load(Ordering::Relaxed) {
+            0 => None,
+            n => Some(n),
+        }
+    }
+
+    pub(crate) fn set_max_voices(&self, max_voices: Option<usize>) {
+        let n = max_voices.map_or(0, |n| n.clamp(1, MAX_TRACKED_VOICES));
+        self.0.max_voices.store(n, Ordering::Relaxed);
+    }
+
+    pub(crate) fn policy(&self) -> VoiceLimitPolicy {
+        VoiceLimitPolicy::from_u8(self.0.policy.load(Ordering::Relaxed)).unwrap()
+    }
+
+    pub(crate) fn set_policy(&self, policy: VoiceLimitPolicy) {
+        self.0.policy.store(policy.to_u8(), Ordering::Relaxed);
+    }
+
+    /// The number of samples over which a voice fades out when it is
+    /// stolen or otherwise cut off, or zero to stop it immediately
+    pub(crate) fn release_fade_samples(&self) -> usize {
+        self.0.release_fade_samples.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_release_fade_samples(&self, samples: usize) {
+        self.0
+            .release_fade_samples
+            .store(samples.min(MAX_RELEASE_FADE_SAMPLES), Ordering::Relaxed);
+    }
+
+    /// The number of voices currently playing across the engine
+    pub(crate) fn active_voices(&self) -> usize {
+        self.0.active_voices.load(Ordering::Relaxed)
+    }
+
+    /// Start a new voice, first stealing another if the limit has
+    /// been reached and the policy permits it. Returns None if the
+    /// voice may not be started.
+    pub(crate) fn start_voice(&self) -> Option<Voice> {
+        let limit = self.max_voices().unwrap_or(MAX_TRACKED_VOICES);
+        while self.active_voices() >= limit {
+            if !self.steal_voice() {
+                return None;
+            }
+        }
+        for (index, slot) in self.0.slots.iter().enumerate() {
+            let generation = slot.generation.load(Ordering::Acquire);
+            if generation % 2 == 1 {
+                continue;
+            }
+            if slot
+                .generation
+                .compare_exchange(
+                    generation,
+                    generation + 1,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                self.0.active_voices.fetch_add(1, Ordering::Relaxed);
+                let voice = Voice {
+                    index,
+                    generation: generation + 1,
+                };
+                self.restart_voice(voice);
+                return Some(voice);
+            }
+        }
+        None
+    }
+
+    /// Reuse a voice which is still playing for a new note, which
+    /// counts as starting it anew for the sake of stealing
+    pub(crate) fn restart_voice(&self, voice: Voice) {
+        let slot = &self.0.slots[voice.index];
+        let start = self.0.next_start.fetch_add(1, Ordering::Relaxed);
+        slot.started.store(start, Ordering::Relaxed);
+        slot.level.store(0.0, Ordering::Relaxed);
+    }
+
+    /// Whether the voice was stolen to make room for another, in
+    /// which case it should stop playing immediately
+    pub(crate) fn is_stolen(&self, voice: Voice) -> bool {
+        self.0.slots[voice.index].generation.load(Ordering::Acquire) != voice.generation
+    }
+
+    /// Record the peak amplitude of the voice's most recent audio
+    pub(crate) fn set_level(&self, voice: Voice, level: f32) {
+        self.0.slots[voice.index]
+            .level
+            .store(level, Ordering::Relaxed);
+    }
+
+    /// End a voice which has finished playing, freeing its place.
+    /// Does nothing if the voice was stolen.
+    pub(crate) fn end_voice(&self, voice: Voice) {
+        self.free_slot(voice.index, voice.generation);
+    }
+
+    fn free_slot(&self, index: usize, generation: u64) -> bool {
+        let freed = self.0.slots[index]
+            .generation
+            .compare_exchange(
+                generation,
+                generation + 1,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+        if freed {
+            self.0.active_voices.fetch_sub(1, Ordering::Relaxed);
+        }
+        freed
+    }
+
+    /// Free the place of the playing voice chosen by the policy.
+    /// Returns false if no voice could be stolen.
+    fn steal_voice(&self) -> bool {
+        let policy = self.policy();
+        if policy == VoiceLimitPolicy::RejectNew {
+            return false;
+        }
+        let mut victim: Option<(usize, u64, (f32, u64))> = None;
+        for (index, slot) in self.0.slots.iter().enumerate() {
+            let generation = slot.generation.load(Ordering::Acquire);
+            if generation % 2 == 0 {
+                continue;
+            }
+            // Lower is stolen first, with older voices going first
+            // among equally quiet ones
+            let started = slot.started.load(Ordering::Relaxed);
+            let priority = match policy {
+                VoiceLimitPolicy::StealOldest => (0.0, started),
+                VoiceLimitPolicy::StealQuietest => (slot.level.load(Ordering::Relaxed), started),
+                VoiceLimitPolicy::RejectNew => unreachable!(),
+            };
+            if victim.is_none_or(|(_, _, p)| priority < p) {
+                victim = Some((index, generation, priority));
+            }
+        }
+        match victim {
+            Some((index, generation, _)) => self.free_slot(index, generation),
+            None => false,
+        }
+    }
+}
+
+impl Clone for VoiceLimit {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
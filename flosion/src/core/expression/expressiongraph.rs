@@ -83,6 +83,12 @@ impl ExpressionGraph {
         &self.nodes
     }
 
+    pub(crate) fn nodes_mut(
+        &mut self,
+    ) -> &mut HashMap<ExpressionNodeId, Box<dyn AnyExpressionNode>> {
+        &mut self.nodes
+    }
+
     pub(crate) fn parameters(&self) -> &[ExpressionGraphParameterId] {
         &self.parameters
     }
@@ -123,6 +129,38 @@ impl ExpressionGraph {
         input_locations
     }
 
+    /// Find all parameters which the results depend on, directly or through
+    /// other nodes. Parameters which aren't reachable from any result
+    /// are omitted.
+    pub(crate) fn reachable_parameters(&self) -> HashSet<ExpressionGraphParameterId> {
+        let mut parameters = HashSet::new();
+        let mut visited_nodes = HashSet::new();
+        let mut pending: Vec<ExpressionTarget> =
+            self.results.iter().filter_map(|r| r.target()).collect();
+
+        while let Some(target) = pending.pop() {
+            match target {
+                ExpressionTarget::Parameter(param_id) => {
+                    parameters.insert(param_id);
+                }
+                ExpressionTarget::Node(node_id) => {
+                    if !visited_nodes.insert(node_id) {
+                        continue;
+                    }
+                    if let Some(node) = self.nodes.get(&node_id) {
+                        node.foreach_input(|input, _| {
+                            if let Some(t) = input.target() {
+                                pending.push(t);
+                            }
+                        });
+                    }
+                }
+            }
+        }
+
+        parameters
+    }
+
     pub fn add_expression_node(&mut self, node: Box<dyn AnyExpressionNode>) {
         let prev = self.nodes.insert(node.id(), node);
         debug_assert!(prev.is_none());
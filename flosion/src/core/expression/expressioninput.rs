@@ -49,6 +49,10 @@ impl ExpressionInput {
     pub(crate) fn default_value(&self) -> f32 {
         self.default_value
     }
+
+    pub(crate) fn set_default_value(&mut self, value: f32) {
+        self.default_value = value;
+    }
 }
 
 impl Stashable<StashingContext> for ExpressionInput {
@@ -0,0 +1,204 @@
+use std::rc::Rc;
+
+use hashstash::{Order, Stashable, Stasher, UnstashError, Unstashable, Unstasher};
+
+use crate::core::stashing::{ExpressionUnstashingContext, StashingContext, UnstashingContext};
+
+use super::expressiongraph::{ExpressionGraph, ExpressionGraphParameterId, ExpressionTarget};
+
+/// A named, reusable expression with named inputs. Each input is a
+/// parameter of the macro's expression graph, which has exactly one
+/// result. Uses of a macro within other expressions are expanded
+/// inline when those expressions are compiled.
+pub struct ExpressionMacro {
+    name: String,
+    inputs: Vec<(String, ExpressionGraphParameterId)>,
+    graph: ExpressionGraph,
+}
+
+impl ExpressionMacro {
+    /// Create a new macro with the given named inputs and an
+    /// empty body
+    pub fn new(name: String, input_names: &[&str]) -> ExpressionMacro {
+        let mut graph = ExpressionGraph::new();
+        let inputs = input_names
+            .iter()
+            .map(|n| (n.to_string(), graph.add_parameter()))
+            .collect();
+        graph.add_result(0.0);
+        ExpressionMacro {
+            name,
+            inputs,
+            graph,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn input_name(&self, index: usize) -> &str {
+        &self.inputs[index].0
+    }
+
+    /// The graph parameter standing for the input at the given index
+    pub fn input_parameter(&self, index: usize) -> ExpressionGraphParameterId {
+        self.inputs[index].1
+    }
+
+    /// The body of the macro, whose only result is the macro's value
+    pub fn graph(&self) -> &ExpressionGraph {
+        &self.graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut ExpressionGraph {
+        &mut self.graph
+    }
+
+    /// Compute the value of the macro directly from the given constant
+    /// input values, if every node in its body supports this
+    pub fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        fn evaluate_target(
+            target: Option<ExpressionTarget>,
+            default_value: f32,
+            expr_macro: &ExpressionMacro,
+            inputs: &[f32],
+        ) -> Option<f32> {
+            match target {
+                None => Some(default_value),
+                Some(ExpressionTarget::Parameter(param)) => {
+                    let index = expr_macro.inputs.iter().position(|(_, p)| *p == param)?;
+                    Some(inputs.get(index).copied().unwrap_or(0.0))
+                }
+                Some(ExpressionTarget::Node(node_id)) => {
+                    let node = expr_macro.graph.node(node_id)?;
+                    let mut node_inputs = Vec::new();
+                    let mut all_constant = true;
+                    node.foreach_input(|input, _| {
+                        match evaluate_target(
+                            input.target(),
+                            input.default_value(),
+                            expr_macro,
+                            inputs,
+                        ) {
+                            Some(v) => node_inputs.push(v),
+                            None => all_constant = false,
+                        }
+                    });
+                    if !all_constant {
+                        return None;
+                    }
+                    node.evaluate_constant(&node_inputs)
+                }
+            }
+        }
+
+        let result = &self.graph.results()[0];
+        evaluate_target(result.target(), result.default_value(), self, inputs)
+    }
+}
+
+impl Stashable<StashingContext> for ExpressionMacro {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.string(&self.name);
+        stasher.array_of_proxy_objects(
+            self.inputs.iter(),
+            |(name, param), stasher| {
+                stasher.string(name);
+                stasher.u64(param.value() as _);
+            },
+            Order::Ordered,
+        );
+        stasher.object(&self.graph);
+    }
+}
+
+impl Unstashable<UnstashingContext<'_>> for ExpressionMacro {
+    fn unstash(
+        unstasher: &mut Unstasher<UnstashingContext>,
+    ) -> Result<ExpressionMacro, UnstashError> {
+        let name = unstasher.string()?;
+        let mut inputs = Vec::new();
+        unstasher.array_of_proxy_objects(|unstasher| {
+            let name = unstasher.string()?;
+            let param = ExpressionGraphParameterId::new(unstasher.u64()? as _);
+            inputs.push((name, param));
+            Ok(())
+        })?;
+        let graph = unstasher.object_with_context(ExpressionUnstashingContext::new(
+            unstasher.context().expression_object_factory(),
+        ))?;
+        Ok(ExpressionMacro {
+            name,
+            inputs,
+            graph,
+        })
+    }
+}
+
+/// The set of macros available to all expressions in a sound graph,
+/// each with a distinct name
+#[derive(Clone, Default)]
+pub struct ExpressionMacroLibrary {
+    macros: Vec<Rc<ExpressionMacro>>,
+}
+
+impl ExpressionMacroLibrary {
+    pub fn new() -> ExpressionMacroLibrary {
+        ExpressionMacroLibrary { macros: Vec::new() }
+    }
+
+    pub fn macros(&self) -> &[Rc<ExpressionMacro>] {
+        &self.macros
+    }
+
+    /// Look up a macro by its name
+    pub fn get(&self, name: &str) -> Option<&Rc<ExpressionMacro>> {
+        self.macros.iter().find(|m| m.name() == name)
+    }
+
+    /// Add a macro, replacing any existing macro with the same name
+    pub fn define(&mut self, expr_macro: ExpressionMacro) {
+        let expr_macro = Rc::new(expr_macro);
+        match self
+            .macros
+            .iter_mut()
+            .find(|m| m.name() == expr_macro.name())
+        {
+            Some(existing) => *existing = expr_macro,
+            None => self.macros.push(expr_macro),
+        }
+    }
+
+    /// Remove the macro with the given name, if there is one
+    pub fn remove(&mut self, name: &str) {
+        self.macros.retain(|m| m.name() != name);
+    }
+}
+
+impl Stashable<StashingContext> for ExpressionMacroLibrary {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.array_of_proxy_objects(
+            self.macros.iter(),
+            |expr_macro, stasher| stasher.object::<ExpressionMacro>(expr_macro),
+            Order::Ordered,
+        );
+    }
+}
+
+impl Unstashable<UnstashingContext<'_>> for ExpressionMacroLibrary {
+    fn unstash(
+        unstasher: &mut Unstasher<UnstashingContext>,
+    ) -> Result<ExpressionMacroLibrary, UnstashError> {
+        let mut macros = Vec::new();
+        unstasher.array_of_proxy_objects(|unstasher| {
+            macros.push(Rc::new(unstasher.object::<ExpressionMacro>()?));
+            Ok(())
+        })?;
+        Ok(ExpressionMacroLibrary { macros })
+    }
+}
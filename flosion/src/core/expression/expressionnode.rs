@@ -44,6 +44,21 @@ pub trait PureExpressionNode: WithObjectType {
     // Generate instructions to compute a value from the given inputs
     fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx>;
 
+    // Compute a value directly from the given constant inputs, if the
+    // node supports it. This allows constant subexpressions to be folded
+    // into a single value at compile time.
+    fn evaluate_constant(&self, _inputs: &[f32]) -> Option<f32> {
+        None
+    }
+
+    // Whether the node's value is determined entirely by its type and its
+    // inputs, such that two nodes of the same type with identical inputs
+    // can share a single compiled value. Nodes with any other configuration
+    // (e.g. a stored value) must return false.
+    fn depends_only_on_inputs(&self) -> bool {
+        false
+    }
+
     fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor);
     fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut);
 }
@@ -63,6 +78,10 @@ pub trait AnyExpressionNode {
         state_ptrs: &[PointerValue<'ctx>],
     ) -> FloatValue<'ctx>;
 
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32>;
+
+    fn depends_only_on_inputs(&self) -> bool;
+
     fn as_any(&self) -> &dyn Any;
     fn as_mut_any(&mut self) -> &mut dyn Any;
 
@@ -126,6 +145,19 @@ pub trait ExpressionNode {
         compile_state: &Self::CompileState<'ctx>,
     ) -> FloatValue<'ctx>;
 
+    // Compute a value directly from the given constant inputs, if possible.
+    // Nodes with state variables are never evaluated this way.
+    fn evaluate_constant(&self, _inputs: &[f32]) -> Option<f32> {
+        None
+    }
+
+    // Whether nodes of this type with identical inputs may share a single
+    // compiled value. Nodes with state variables are never shared, since
+    // each must keep its own state.
+    fn depends_only_on_inputs(&self) -> bool {
+        false
+    }
+
     fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor);
     fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut);
 }
@@ -167,6 +199,14 @@ impl<T: PureExpressionNode> ExpressionNode for T {
         T::compile(self, jit, inputs)
     }
 
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        T::evaluate_constant(self, inputs)
+    }
+
+    fn depends_only_on_inputs(&self) -> bool {
+        T::depends_only_on_inputs(self)
+    }
+
     fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
         T::visit(self, visitor);
     }
@@ -309,6 +349,20 @@ where
         loop_value
     }
 
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        if T::NUM_VARIABLES > 0 {
+            return None;
+        }
+        self.instance.evaluate_constant(inputs)
+    }
+
+    fn depends_only_on_inputs(&self) -> bool {
+        if T::NUM_VARIABLES > 0 {
+            return false;
+        }
+        self.instance.depends_only_on_inputs()
+    }
+
     fn as_graph_object(&self) -> &dyn ExpressionObject {
         self
     }
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use hashstash::{InplaceUnstasher, Order, Stashable, Stasher, UnstashError, UnstashableInplace};
+use inkwell::values::FloatValue;
+
+use crate::{
+    core::{
+        expression::{
+            expressioninput::ExpressionInput,
+            expressionmacro::ExpressionMacro,
+            expressionnode::{ExpressionNodeVisitor, ExpressionNodeVisitorMut, PureExpressionNode},
+        },
+        jit::jit::Jit,
+        objecttype::{ObjectType, WithObjectType},
+        stashing::StashingContext,
+    },
+    ui_core::arguments::{NaturalNumberArgument, ParsedArguments, StringIdentifierArgument},
+};
+
+/// A use of an expression macro, which is expanded inline when the
+/// expression containing it is compiled. The macro is referred to by
+/// name, and its definition is looked up from the sound graph's macro
+/// library whenever that changes.
+pub struct MacroCall {
+    name: String,
+    inputs: Vec<ExpressionInput>,
+    definition: Option<Rc<ExpressionMacro>>,
+}
+
+impl MacroCall {
+    pub const ARG_NAME: StringIdentifierArgument = StringIdentifierArgument("name");
+    pub const ARG_NUM_INPUTS: NaturalNumberArgument = NaturalNumberArgument("inputs");
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn definition(&self) -> Option<&Rc<ExpressionMacro>> {
+        self.definition.as_ref()
+    }
+
+    pub fn set_definition(&mut self, definition: Option<Rc<ExpressionMacro>>) {
+        self.definition = definition;
+    }
+}
+
+impl PureExpressionNode for MacroCall {
+    fn new(args: &ParsedArguments) -> MacroCall {
+        let name = args.get(&MacroCall::ARG_NAME).unwrap_or_default();
+        let num_inputs = args.get(&MacroCall::ARG_NUM_INPUTS).unwrap_or(0);
+        MacroCall {
+            name,
+            inputs: (0..num_inputs).map(|_| ExpressionInput::new(0.0)).collect(),
+            definition: None,
+        }
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        match &self.definition {
+            Some(definition) => jit.compile_macro(definition, inputs),
+            None => jit.types.f32_type.const_zero(),
+        }
+    }
+
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        self.definition.as_ref()?.evaluate_constant(inputs)
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        for input in &self.inputs {
+            visitor.input(input);
+        }
+    }
+
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        for input in &mut self.inputs {
+            visitor.input(input);
+        }
+    }
+}
+
+impl Stashable<StashingContext> for MacroCall {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.string(&self.name);
+        stasher.array_of_objects_slice(&self.inputs, Order::Ordered);
+        if stasher.context().checking_recompilation() {
+            // The definition is looked up again after unstashing, but
+            // changing it still requires recompiling
+            match &self.definition {
+                Some(definition) => {
+                    stasher.u8(1);
+                    stasher.object::<ExpressionMacro>(definition);
+                }
+                None => stasher.u8(0),
+            }
+        }
+    }
+}
+
+impl UnstashableInplace for MacroCall {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.string_inplace(&mut self.name)?;
+        unstasher.array_of_objects_vec_inplace(&mut self.inputs)?;
+        Ok(())
+    }
+}
+
+impl WithObjectType for MacroCall {
+    const TYPE: ObjectType = ObjectType::new("macrocall");
+}
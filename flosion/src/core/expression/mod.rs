@@ -3,8 +3,10 @@ pub mod expressiongraph;
 pub mod expressiongrapherror;
 pub mod expressiongraphvalidation;
 pub mod expressioninput;
+pub mod expressionmacro;
 pub mod expressionnode;
 pub mod expressionobject;
+pub mod macrocall;
 
 #[cfg(test)]
 mod test;
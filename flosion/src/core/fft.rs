@@ -0,0 +1,141 @@
+use std::ops::{Add, Mul, Sub};
+
+/// A complex number, as used by the fourier transform
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex { re: 0.0, im: 0.0 };
+
+    pub fn new(re: f32, im: f32) -> Complex {
+        Complex { re, im }
+    }
+
+    /// The point on the unit circle at the given angle in radians
+    pub fn from_angle(angle: f32) -> Complex {
+        Complex {
+            re: angle.cos(),
+            im: angle.sin(),
+        }
+    }
+
+    pub fn conj(self) -> Complex {
+        Complex {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    pub fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn scale(self, s: f32) -> Complex {
+        Complex {
+            re: self.re * s,
+            im: self.im * s,
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// An in-place radix-2 fast fourier transform of a fixed power-of-two
+/// length. Twiddle factors are computed once up front so that no
+/// trigonometry or allocation happens per transform.
+pub struct Fft {
+    size: usize,
+    twiddles: Vec<Complex>,
+}
+
+impl Fft {
+    pub fn new(size: usize) -> Fft {
+        assert!(size.is_power_of_two());
+        let twiddles = (0..(size / 2))
+            .map(|k| Complex::from_angle(-std::f32::consts::TAU * k as f32 / size as f32))
+            .collect();
+        Fft { size, twiddles }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Replace the data with its discrete fourier transform. No
+    /// normalization is applied.
+    pub fn forward(&self, data: &mut [Complex]) {
+        self.transform(data, false);
+    }
+
+    /// Replace the data with its inverse discrete fourier transform,
+    /// scaled by 1/N such that `inverse` undoes `forward`
+    pub fn inverse(&self, data: &mut [Complex]) {
+        self.transform(data, true);
+        let scale = 1.0 / self.size as f32;
+        for x in data {
+            *x = x.scale(scale);
+        }
+    }
+
+    fn transform(&self, data: &mut [Complex], inverse: bool) {
+        assert_eq!(data.len(), self.size);
+        let n = self.size;
+        if n <= 1 {
+            return;
+        }
+
+        // bit-reversal permutation
+        let bits = n.trailing_zeros();
+        for i in 0..n {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if j > i {
+                data.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let w = self.twiddles[k * stride];
+                    let w = if inverse { w.conj() } else { w };
+                    let a = data[start + k];
+                    let b = data[start + k + half] * w;
+                    data[start + k] = a + b;
+                    data[start + k + half] = a - b;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
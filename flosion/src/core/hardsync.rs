@@ -0,0 +1,51 @@
+/// Detects rising edges in a sync signal, at which an oscillator's phase
+/// is reset to the start of its cycle. An edge occurs when the signal
+/// goes from zero or below to above zero, and its position between
+/// samples is estimated by linear interpolation.
+pub struct HardSync {
+    previous: f32,
+}
+
+impl HardSync {
+    pub fn new() -> HardSync {
+        HardSync { previous: 0.0 }
+    }
+
+    /// Forget the previous value of the sync signal
+    pub fn reset(&mut self) {
+        self.previous = 0.0;
+    }
+
+    /// Advance by one sample with the given value of the sync signal.
+    /// If a rising edge occurred since the previous sample, returns how
+    /// long ago it occurred as a fraction of a sample, in (0, 1].
+    pub fn step(&mut self, value: f32) -> Option<f32> {
+        let previous = self.previous;
+        self.previous = if value.is_finite() { value } else { 0.0 };
+        if previous > 0.0 || self.previous <= 0.0 {
+            return None;
+        }
+        let crossing = -previous / (self.previous - previous);
+        Some(1.0 - crossing)
+    }
+}
+
+impl Default for HardSync {
+    fn default() -> HardSync {
+        HardSync::new()
+    }
+}
+
+/// The corrections which smooth a jump of the given height between two
+/// samples, in the manner of a polynomial bandlimited step (PolyBLEP).
+/// `elapsed` is how long before the later sample the jump occurred, as a
+/// fraction of a sample, such as is returned by `HardSync::step`. The
+/// first correction is to be added to the sample before the jump, and
+/// the second to the sample after it.
+pub fn step_residuals(jump: f32, elapsed: f32) -> (f32, f32) {
+    let since_previous = 1.0 - elapsed;
+    (
+        0.5 * jump * elapsed * elapsed,
+        -0.5 * jump * since_previous * since_previous,
+    )
+}
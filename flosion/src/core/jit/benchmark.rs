@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use crate::core::{
+    sound::{expression::ProcessorExpression, soundgraph::SoundGraph},
+    soundchunk::CHUNK_SIZE,
+};
+
+use super::{
+    compiledexpression::{CompiledExpressionFunction, Discretization},
+    jit::{ExpressionTestDomain, Jit, JitError, JitMode},
+};
+
+/// The speed at which a compiled expression produced samples, as
+/// measured by evaluating it repeatedly over a large buffer
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Throughput {
+    samples: usize,
+    elapsed: Duration,
+}
+
+impl Throughput {
+    /// The number of samples produced in each of the expression's results
+    pub(crate) fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub(crate) fn samples_per_second(&self) -> f64 {
+        self.samples as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Evaluate the compiled expression over buffers of any length, one
+/// chunk at a time as the sound engine would. There must be one buffer
+/// per result of the expression, all of the same length.
+pub(crate) fn run_over_buffer(function: &mut CompiledExpressionFunction, buffers: &mut [Vec<f32>]) {
+    let len = buffers.first().map_or(0, |b| b.len());
+    let mut start = 0;
+    while start < len {
+        let end = (start + CHUNK_SIZE).min(len);
+        let mut chunks: Vec<&mut [f32]> = buffers.iter_mut().map(|b| &mut b[start..end]).collect();
+        function.eval_in_test_mode(&mut chunks, Discretization::samplewise_temporal());
+        start = end;
+    }
+}
+
+/// Measure the throughput of the compiled expression by evaluating it
+/// over buffers of the given length the given number of times, after
+/// evaluating it once beforehand to warm up
+pub(crate) fn measure_throughput(
+    function: &mut CompiledExpressionFunction,
+    buffer_len: usize,
+    repetitions: usize,
+) -> Throughput {
+    let mut buffers = vec![vec![0.0; buffer_len]; function.num_destination_arrays()];
+    run_over_buffer(function, &mut buffers);
+
+    let start = Instant::now();
+    for _ in 0..repetitions {
+        run_over_buffer(function, &mut buffers);
+        std::hint::black_box(&mut buffers);
+    }
+
+    Throughput {
+        samples: buffer_len * repetitions,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Compile the expression on its own, with time passing at the audio
+/// sample rate, and measure the throughput of the compiled function.
+/// Optimizations are only applied in release builds, and so only
+/// those measurements are representative.
+pub(crate) fn benchmark_expression(
+    inkwell_context: &inkwell::context::Context,
+    expression: &ProcessorExpression,
+    buffer_len: usize,
+    repetitions: usize,
+) -> Result<Throughput, JitError> {
+    let artefact = Jit::new(inkwell_context).compile_expression(
+        expression.graph(),
+        expression.mapping(),
+        &SoundGraph::new(),
+        JitMode::Test(ExpressionTestDomain::Temporal),
+    )?;
+    let mut function = artefact.make_function();
+    Ok(measure_throughput(&mut function, buffer_len, repetitions))
+}
@@ -13,11 +13,14 @@ use crate::core::{
 
 use super::{
     compiledexpression::{CompiledExpressionArtefact, CompiledExpressionFunction},
-    jit::{Jit, JitMode},
+    jit::{Jit, JitError, JitMode},
 };
 
 struct Entry<'ctx> {
+    /// The compiled expression, or if compilation failed, a stand-in
+    /// which produces the default value of every result
     artefact: CompiledExpressionArtefact<'ctx>,
+    error: Option<JitError>,
     location: ProcessorExpressionLocation,
     // TODO: memory usage tracking. Does LLVM report that in any way?
     // TODO: info about how recently the entry was used,
@@ -49,6 +52,10 @@ impl<'ctx> JitCache<'ctx> {
         }
     }
 
+    pub(crate) fn inkwell_context(&self) -> &'ctx inkwell::context::Context {
+        self.inkwell_context
+    }
+
     pub(crate) fn refresh(&mut self, graph: &SoundGraph) {
         // Remove any expressions no longer in the graph.
         self.cache.retain(|_, entry| graph.contains(entry.location));
@@ -63,13 +70,13 @@ impl<'ctx> JitCache<'ctx> {
                 };
                 self.cache.entry(key).or_insert_with(|| {
                     let jit = Jit::new(self.inkwell_context);
-                    let artefact = jit.compile_expression(
+                    let result = jit.compile_expression(
                         expr.graph(),
                         expr.mapping(),
                         graph,
                         JitMode::Normal,
                     );
-                    Entry { artefact, location }
+                    Self::make_entry(self.inkwell_context, result, expr.graph(), location)
                 });
             });
         }
@@ -88,10 +95,12 @@ impl<'ctx> JitCache<'ctx> {
                         mode,
                     };
                     self.cache.entry(key).or_insert_with(|| {
-                        let jit = Jit::new(self.inkwell_context);
-                        let artefact =
+                        let mut jit = Jit::new(self.inkwell_context);
+                        // Let plots show where an expression is non-finite
+                        jit.set_guard_non_finite_outputs(mode == JitMode::Normal);
+                        let result =
                             jit.compile_expression(expr.graph(), expr.mapping(), graph, mode);
-                        Entry { artefact, location }
+                        Self::make_entry(self.inkwell_context, result, expr.graph(), location)
                     });
                 })
                 .unwrap();
@@ -122,6 +131,57 @@ impl<'ctx> JitCache<'ctx> {
         }
     }
 
+    /// Look up why the given expression failed to compile normally, if
+    /// it did. Expressions which failed to compile still produce the
+    /// default values of their results, and so remain safe to evaluate.
+    pub(crate) fn compilation_error(
+        &self,
+        expr_graph: &ExpressionGraph,
+        mapping: &ExpressionParameterMapping,
+    ) -> Option<&JitError> {
+        let key = ExpressionKey {
+            hash: Self::hash_expr(expr_graph, mapping),
+            mode: JitMode::Normal,
+        };
+        self.cache.get(&key).and_then(|entry| entry.error.as_ref())
+    }
+
+    fn make_entry(
+        inkwell_context: &'ctx inkwell::context::Context,
+        result: Result<CompiledExpressionArtefact<'ctx>, JitError>,
+        expr_graph: &ExpressionGraph,
+        location: ProcessorExpressionLocation,
+    ) -> Entry<'ctx> {
+        match result {
+            Ok(artefact) => Entry {
+                artefact,
+                error: None,
+                location,
+            },
+            Err(error) => {
+                // Substitute an expression with the same results, all
+                // of which are disconnected
+                let mut fallback_graph = ExpressionGraph::new();
+                for result in expr_graph.results() {
+                    fallback_graph.add_result(result.default_value());
+                }
+                let artefact = Jit::new(inkwell_context)
+                    .compile_expression(
+                        &fallback_graph,
+                        &ExpressionParameterMapping::new(),
+                        &SoundGraph::new(),
+                        JitMode::Normal,
+                    )
+                    .expect("An expression with only disconnected results should always compile");
+                Entry {
+                    artefact,
+                    error: Some(error),
+                    location,
+                }
+            }
+        }
+    }
+
     fn hash_expr(expr_graph: &ExpressionGraph, mapping: &ExpressionParameterMapping) -> ObjectHash {
         ObjectHash::with_stasher(|stasher| {
             stasher.object_with_context(expr_graph, StashingContext::new_checking_recompilation());
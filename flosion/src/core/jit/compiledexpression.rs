@@ -113,6 +113,12 @@ impl Discretization {
         Discretization::Temporal(SAMPLE_TIME_STEP)
     }
 
+    /// Like samplewise_temporal, but for arrays containing the given
+    /// number of samples per audio sample, as when oversampling
+    pub fn oversampled_temporal(ratio: usize) -> Discretization {
+        Discretization::Temporal(SAMPLE_TIME_STEP / ratio as f32)
+    }
+
     pub fn chunkwise_temporal() -> Discretization {
         Discretization::Temporal(SAMPLE_TIME_STEP * CHUNK_SIZE as f32)
     }
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use crate::core::{
+    expression::{
+        expressiongraph::{ExpressionGraph, ExpressionTarget},
+        expressionnode::ExpressionNodeId,
+    },
+    samplefrequency::SAMPLE_TIME_STEP,
+    sound::{expression::ExpressionParameterMapping, soundgraph::SoundGraph},
+};
+
+use super::{
+    compiledexpression::Discretization,
+    jit::{ExpressionTestDomain, Jit, JitMode},
+};
+
+/// The number of samples over which a subexpression is evaluated
+/// to confirm that it holds still over time
+const NUM_TEST_SAMPLES: usize = 256;
+
+/// Evaluate the subexpression rooted at the given node, returning its
+/// value if it is constant. A subexpression is not constant if anything
+/// within it depends on a graph parameter (such as time or a processor
+/// argument), if it changes from one sample to the next under
+/// `JitMode::Test(ExpressionTestDomain::Temporal)` (as stateful nodes
+/// may do), or if it produces a non-finite value.
+pub(crate) fn evaluate_constant_subexpression(
+    inkwell_context: &inkwell::context::Context,
+    expression_graph: &mut ExpressionGraph,
+    parameter_mapping: &ExpressionParameterMapping,
+    root: ExpressionNodeId,
+) -> Option<f32> {
+    let mut visited: HashSet<ExpressionNodeId> = HashSet::new();
+    let mut to_visit = vec![root];
+    let mut depends_on_parameter = false;
+    while let Some(id) = to_visit.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        expression_graph
+            .node(id)?
+            .foreach_input(|input, _| match input.target() {
+                Some(ExpressionTarget::Node(input_node)) => to_visit.push(input_node),
+                Some(ExpressionTarget::Parameter(_)) => depends_on_parameter = true,
+                None => (),
+            });
+    }
+    if depends_on_parameter {
+        return None;
+    }
+
+    // Compile the subexpression alongside the graph's own results by
+    // temporarily adding a result which points at it
+    let result_id = expression_graph.add_result(0.0);
+    expression_graph
+        .connect_result(result_id, ExpressionTarget::Node(root))
+        .unwrap();
+
+    let compiled = Jit::new(inkwell_context).compile_expression(
+        expression_graph,
+        parameter_mapping,
+        &SoundGraph::new(),
+        JitMode::Test(ExpressionTestDomain::Temporal),
+    );
+
+    let num_results = expression_graph.results().len();
+
+    expression_graph.remove_result(result_id).unwrap();
+
+    let artefact = compiled.ok()?;
+
+    // The temporary result was added last, and so is evaluated last
+    let mut dsts: Vec<Vec<f32>> = Vec::new();
+    dsts.resize_with(num_results, || vec![0.0; NUM_TEST_SAMPLES]);
+    let mut dst_slices: Vec<&mut [f32]> = dsts.iter_mut().map(|v| &mut v[..]).collect();
+
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut dst_slices, Discretization::Temporal(SAMPLE_TIME_STEP));
+
+    let values = dsts.last().unwrap();
+    let value = values[0];
+    if !value.is_finite() || values.iter().any(|v| *v != value) {
+        return None;
+    }
+
+    Some(value)
+}
@@ -13,14 +13,14 @@ use inkwell::{
     intrinsics::Intrinsic,
     module::Module,
     values::{BasicValue, FloatValue, FunctionValue, IntValue, PointerValue},
-    AtomicOrdering,
+    AtomicOrdering, FloatPredicate,
 };
 
 use crate::core::{
     engine::garbage::Droppable,
     expression::{
         expressiongraph::ExpressionGraph, expressiongraph::ExpressionTarget,
-        expressionnode::ExpressionNodeId,
+        expressionmacro::ExpressionMacro, expressionnode::ExpressionNodeId,
     },
     sound::{
         argument::{ProcessorArgumentId, ProcessorArgumentLocation},
@@ -68,17 +68,66 @@ pub struct Jit<'ctx> {
     pub(super) wrapper_functions: WrapperFunctions<'ctx>,
     pub(super) builder: Builder<'ctx>,
     pub(super) module: Module<'ctx>,
+    inkwell_context: &'ctx inkwell::context::Context,
     execution_engine: ExecutionEngine<'ctx>,
     function_name: String,
     pub(super) atomic_captures: Vec<Arc<dyn Sync + Droppable>>,
     pub(super) compiled_targets: HashMap<ExpressionTarget, FloatValue<'ctx>>,
+    constant_targets: HashMap<ExpressionTarget, f32>,
+    shared_values: HashMap<(&'static str, Vec<FloatValue<'ctx>>), FloatValue<'ctx>>,
     num_state_variables: usize,
     state_array_offsets: Vec<(ExpressionNodeId, usize)>,
+    guard_non_finite_outputs: bool,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Interval {
-    Linear { from: f32, to: f32 },
+    Linear {
+        from: f32,
+        to: f32,
+    },
+    /// Spaced evenly in proportion, e.g. for frequencies. Both
+    /// endpoints must be positive.
+    Logarithmic {
+        from: f32,
+        to: f32,
+    },
+}
+
+impl Interval {
+    /// The value at the given fraction of the way through the interval
+    pub fn value_at(&self, fraction: f32) -> f32 {
+        match *self {
+            Interval::Linear { from, to } => from + fraction * (to - from),
+            Interval::Logarithmic { from, to } => from * (to / from).powf(fraction),
+        }
+    }
+
+    /// The fraction of the way through the interval at which the given
+    /// value lies. This is the inverse of `value_at`.
+    pub fn fraction_of(&self, value: f32) -> f32 {
+        match *self {
+            Interval::Linear { from, to } => (value - from) / (to - from),
+            Interval::Logarithmic { from, to } => (value / from).ln() / (to / from).ln(),
+        }
+    }
+
+    /// The value of the sample at the given index when evaluating over
+    /// the interval in test mode into an array of the given length
+    pub fn sample_value(&self, index: usize, len: usize) -> f32 {
+        self.value_at(index as f32 / len as f32)
+    }
+
+    /// The index of the sample whose value is nearest the given value
+    /// when evaluating over the interval into an array of the given length.
+    /// For logarithmic intervals, nearness is in proportion.
+    pub fn nearest_sample(&self, value: f32, len: usize) -> usize {
+        let index = (self.fraction_of(value) * len as f32).round();
+        if index.is_nan() {
+            return 0;
+        }
+        (index.max(0.0) as usize).min(len.saturating_sub(1))
+    }
 }
 
 // Darn f32's don't want to implement Eq
@@ -89,7 +138,7 @@ impl Hash for Interval {
     fn hash<H: Hasher>(&self, state: &mut H) {
         core::mem::discriminant(self).hash(state);
         match self {
-            Interval::Linear { from, to } => {
+            Interval::Linear { from, to } | Interval::Logarithmic { from, to } => {
                 state.write_u32(from.to_bits());
                 state.write_u32(to.to_bits());
             }
@@ -109,6 +158,28 @@ pub enum JitMode {
     Test(ExpressionTestDomain),
 }
 
+/// The reasons that JIT compilation of an expression may fail
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum JitError {
+    /// LLVM rejected the generated IR module, for the given reason
+    InvalidModule(String),
+    /// The execution engine could not produce the compiled function
+    ExecutionEngine(String),
+}
+
+impl JitError {
+    pub(crate) fn explain(&self) -> String {
+        match self {
+            JitError::InvalidModule(reason) => {
+                format!("The expression could not be compiled:\n{}", reason)
+            }
+            JitError::ExecutionEngine(reason) => {
+                format!("The JIT compiler could not be run:\n{}", reason)
+            }
+        }
+    }
+}
+
 impl<'ctx> Jit<'ctx> {
     pub(crate) fn new(inkwell_context: &'ctx inkwell::context::Context) -> Jit<'ctx> {
         Self::new_inner(inkwell_context).unwrap()
@@ -290,15 +361,22 @@ impl<'ctx> Jit<'ctx> {
             wrapper_functions,
             builder,
             module,
+            inkwell_context,
             execution_engine,
             atomic_captures: Vec::new(),
             compiled_targets: HashMap::new(),
+            constant_targets: HashMap::new(),
+            shared_values: HashMap::new(),
             num_state_variables: 0,
             state_array_offsets: Vec::new(),
+            guard_non_finite_outputs: true,
         })
     }
 
-    pub(super) fn finish(self, num_dsts: usize) -> CompiledExpressionArtefact<'ctx> {
+    pub(super) fn finish(
+        self,
+        num_dsts: usize,
+    ) -> Result<CompiledExpressionArtefact<'ctx>, JitError> {
         if let Err(err_msg) = self.module().verify() {
             let module_str = self.module().print_to_string();
             println!("===================== start of module =====================");
@@ -310,7 +388,7 @@ impl<'ctx> Jit<'ctx> {
             for line in err_msg.lines() {
                 println!("    {}", line);
             }
-            panic!();
+            return Err(JitError::InvalidModule(err_msg));
         }
 
         // Apply optimizations in release mode
@@ -335,17 +413,17 @@ impl<'ctx> Jit<'ctx> {
         let compiled_fn = match unsafe { self.execution_engine.get_function(&self.function_name) } {
             Ok(f) => f,
             Err(e) => {
-                panic!("Unable to run JIT compiler:\n    {:?}", e);
+                return Err(JitError::ExecutionEngine(format!("{:?}", e)));
             }
         };
 
-        CompiledExpressionArtefact::new(
+        Ok(CompiledExpressionArtefact::new(
             self.execution_engine,
             compiled_fn,
             self.num_state_variables,
             num_dsts,
             self.atomic_captures,
-        )
+        ))
     }
 
     fn visit_target(
@@ -361,6 +439,7 @@ impl<'ctx> Jit<'ctx> {
                 let expr_node_data = graph.node(expr_node_id).unwrap();
 
                 let mut input_values = Vec::new();
+                let mut constant_inputs = Vec::new();
                 expr_node_data.foreach_input(|input, _| {
                     let input_value = match input.target() {
                         Some(target) => self.visit_target(target, graph),
@@ -370,8 +449,45 @@ impl<'ctx> Jit<'ctx> {
                             .const_float(input.default_value().into()),
                     };
                     input_values.push(input_value);
+                    constant_inputs.push(match input.target() {
+                        Some(target) => self.constant_targets.get(&target).copied(),
+                        None => Some(input.default_value()),
+                    });
                 });
 
+                // If every input is known at compile time, try to evaluate
+                // the node now. Parameters are never treated as constants,
+                // which keeps any test instrumentation of arguments intact.
+                let constant_inputs: Option<Vec<f32>> = constant_inputs.into_iter().collect();
+                if let Some(value) =
+                    constant_inputs.and_then(|inputs| expr_node_data.evaluate_constant(&inputs))
+                {
+                    let v = self.types.f32_type.const_float(value as f64);
+                    self.constant_targets
+                        .insert(ExpressionTarget::Node(expr_node_id), value);
+                    self.compiled_targets
+                        .insert(ExpressionTarget::Node(expr_node_id), v);
+                    return v;
+                }
+
+                // If an equivalent node with the same inputs was already
+                // compiled, reuse its value instead of compiling it again.
+                // Since inputs are visited first, this also applies to
+                // entire equivalent subexpressions.
+                let shared_key = if expr_node_data.depends_only_on_inputs() {
+                    let type_name = expr_node_data.as_graph_object().get_dynamic_type().name();
+                    let key = (type_name, input_values.clone());
+                    if let Some(v) = self.shared_values.get(&key) {
+                        let v = *v;
+                        self.compiled_targets
+                            .insert(ExpressionTarget::Node(expr_node_id), v);
+                        return v;
+                    }
+                    Some(key)
+                } else {
+                    None
+                };
+
                 let num_variables = expr_node_data.num_variables();
 
                 let base_state_index = self.num_state_variables;
@@ -408,6 +524,9 @@ impl<'ctx> Jit<'ctx> {
 
                 let v = expr_node_data.compile(self, &input_values, &state_ptrs);
 
+                if let Some(key) = shared_key {
+                    self.shared_values.insert(key, v);
+                }
                 self.compiled_targets
                     .insert(ExpressionTarget::Node(expr_node_id), v);
                 v
@@ -418,6 +537,48 @@ impl<'ctx> Jit<'ctx> {
         }
     }
 
+    /// Expand the body of an expression macro inline with the given
+    /// input values. Each expansion compiles the body afresh, so that
+    /// every use of the macro is independent of the others.
+    pub(crate) fn compile_macro(
+        &mut self,
+        definition: &ExpressionMacro,
+        inputs: &[FloatValue<'ctx>],
+    ) -> FloatValue<'ctx> {
+        // The macro body's node ids are the same for every use, so
+        // compile it with its own targets in place of the outer ones
+        let outer_compiled_targets = std::mem::take(&mut self.compiled_targets);
+        let outer_constant_targets = std::mem::take(&mut self.constant_targets);
+
+        for i in 0..definition.num_inputs() {
+            let value = match inputs.get(i) {
+                Some(v) => *v,
+                None => self.types.f32_type.const_zero(),
+            };
+            self.compiled_targets.insert(
+                ExpressionTarget::Parameter(definition.input_parameter(i)),
+                value,
+            );
+        }
+
+        let result = &definition.graph().results()[0];
+        let value = match result.target() {
+            Some(target) => self.visit_target(target, definition.graph()),
+            None => self
+                .types
+                .f32_type
+                .const_float(result.default_value().into()),
+        };
+
+        self.compiled_targets = outer_compiled_targets;
+        self.constant_targets = outer_constant_targets;
+
+        // The body's nodes may have moved the builder elsewhere
+        self.builder.position_at_end(self.blocks.loop_body);
+
+        value
+    }
+
     pub fn module(&self) -> &Module<'ctx> {
         &self.module
     }
@@ -438,6 +599,75 @@ impl<'ctx> Jit<'ctx> {
         self.blocks.loop_body = block;
     }
 
+    /// Branch within the loop body on the given boolean (i1) condition,
+    /// emitting the code produced by `then_fn` when it is true and by
+    /// `else_fn` when it is false, and return the value of whichever
+    /// branch was taken. Each closure is called with the builder
+    /// positioned in its own block and may itself branch further.
+    /// Values computed within either branch are not available after it,
+    /// only the returned value is. Any targets compiled within a branch
+    /// are forgotten again afterwards, so that they are compiled anew if
+    /// they are needed elsewhere.
+    pub fn build_if_else<T, E>(
+        &mut self,
+        cond: IntValue<'ctx>,
+        then_fn: T,
+        else_fn: E,
+    ) -> FloatValue<'ctx>
+    where
+        T: FnOnce(&mut Jit<'ctx>) -> FloatValue<'ctx>,
+        E: FnOnce(&mut Jit<'ctx>) -> FloatValue<'ctx>,
+    {
+        let bb_then = self.context().append_basic_block(self.function, "if_then");
+        let bb_else = self.context().append_basic_block(self.function, "if_else");
+        let bb_merge = self.context().append_basic_block(self.function, "if_merge");
+
+        // loop_body -> if cond then if_then else if_else
+        self.builder.position_at_end(self.blocks.loop_body);
+        self.builder
+            .build_conditional_branch(cond, bb_then, bb_else)
+            .unwrap();
+
+        // Values compiled within one branch don't dominate the other
+        // branch or anything after the merge, so they must not be reused
+        let outer_compiled_targets = self.compiled_targets.clone();
+        let outer_constant_targets = self.constant_targets.clone();
+        let outer_shared_values = self.shared_values.clone();
+
+        // if_then -> if_merge
+        self.replace_loop_body(bb_then);
+        self.builder.position_at_end(bb_then);
+        let then_value = then_fn(self);
+        self.compiled_targets = outer_compiled_targets.clone();
+        self.constant_targets = outer_constant_targets.clone();
+        self.shared_values = outer_shared_values.clone();
+        // The closure may have replaced the loop body with a later block
+        let bb_then_end = self.blocks.loop_body;
+        self.builder.position_at_end(bb_then_end);
+        self.builder.build_unconditional_branch(bb_merge).unwrap();
+
+        // if_else -> if_merge
+        self.replace_loop_body(bb_else);
+        self.builder.position_at_end(bb_else);
+        let else_value = else_fn(self);
+        self.compiled_targets = outer_compiled_targets;
+        self.constant_targets = outer_constant_targets;
+        self.shared_values = outer_shared_values;
+        let bb_else_end = self.blocks.loop_body;
+        self.builder.position_at_end(bb_else_end);
+        self.builder.build_unconditional_branch(bb_merge).unwrap();
+
+        // The rest of the loop body continues from if_merge
+        self.replace_loop_body(bb_merge);
+        self.builder.position_at_end(bb_merge);
+        let phi = self
+            .builder
+            .build_phi(self.types.f32_type, "if_value")
+            .unwrap();
+        phi.add_incoming(&[(&then_value, bb_then_end), (&else_value, bb_else_end)]);
+        phi.as_basic_value().into_float_value()
+    }
+
     pub fn context(&self) -> ContextRef<'ctx> {
         self.module.get_context()
     }
@@ -752,70 +982,136 @@ impl<'ctx> Jit<'ctx> {
         load.into_float_value()
     }
 
+    /// Keep the given value alive for as long as the compiled expression
+    /// exists, e.g. because the generated code holds pointers into it
+    pub fn keep_alive(&mut self, value: Arc<dyn Sync + Droppable>) {
+        self.atomic_captures.push(value);
+    }
+
+    /// Replace the given value with zero if it is NaN or infinite
+    fn build_non_finite_guard(&mut self, value: FloatValue<'ctx>) -> FloatValue<'ctx> {
+        // Constant results can be checked right away
+        if let Some((v, _)) = value.get_constant() {
+            return if v.is_finite() {
+                value
+            } else {
+                self.types.f32_type.const_zero()
+            };
+        }
+        let abs_value = self.build_unary_intrinsic_call("llvm.fabs", value);
+        // Ordered comparison, which is false for NaN
+        let is_finite = self
+            .builder
+            .build_float_compare(
+                FloatPredicate::OLT,
+                abs_value,
+                self.types.f32_type.const_float(f64::INFINITY),
+                "is_finite",
+            )
+            .unwrap();
+        self.builder
+            .build_select(
+                is_finite,
+                value,
+                self.types.f32_type.const_zero(),
+                "guarded_value",
+            )
+            .unwrap()
+            .into_float_value()
+    }
+
     pub fn time_step(&self) -> FloatValue<'ctx> {
         self.local_variables.time_step
     }
 
+    /// The length of the destination array being evaluated into, as a float
+    pub fn build_block_size(&mut self) -> FloatValue<'ctx> {
+        self.builder
+            .build_unsigned_int_to_float(
+                self.local_variables.dst_len,
+                self.types.f32_type,
+                "block_size",
+            )
+            .unwrap()
+    }
+
     fn compile_interval(&mut self, interval: Interval) -> FloatValue<'ctx> {
         match interval {
-            Interval::Linear {
-                from: interval_begin,
-                to: interval_end,
-            } => {
-                let arr_size_f32 = self
-                    .builder()
-                    .build_signed_int_to_float(
-                        self.local_variables.dst_len,
-                        self.types.f32_type,
-                        "dst_len_f32",
-                    )
-                    .unwrap();
-                let interval_len_over_arr_size = self
-                    .builder()
-                    .build_float_div(
-                        self.types
-                            .f32_type
-                            .const_float((interval_end - interval_begin) as _),
-                        arr_size_f32,
-                        "internal_len_over_arr_size",
-                    )
-                    .unwrap();
-                let loop_counter_f32 = self
-                    .builder()
-                    .build_signed_int_to_float(
-                        self.local_variables.loop_counter,
-                        self.types.f32_type,
-                        "loop_counter_f32",
-                    )
-                    .unwrap();
-                let interval_val_from_zero = self
-                    .builder()
-                    .build_float_mul(
-                        loop_counter_f32,
-                        interval_len_over_arr_size,
-                        "interval_val_from_zero",
-                    )
-                    .unwrap();
-                let interval_val = self
-                    .builder()
-                    .build_float_add(
-                        interval_val_from_zero,
-                        self.types.f32_type.const_float(interval_begin as _),
-                        "interval_val",
-                    )
-                    .unwrap();
-                interval_val
+            Interval::Linear { from, to } => self.compile_linear_interval(from, to),
+            Interval::Logarithmic { from, to } => {
+                // Interpolate linearly between the logarithms of the endpoints
+                let log_val = self.compile_linear_interval(from.ln(), to.ln());
+                self.build_unary_intrinsic_call("llvm.exp", log_val)
             }
         }
     }
 
-    fn compile_all_parameters(
+    fn compile_linear_interval(
+        &mut self,
+        interval_begin: f32,
+        interval_end: f32,
+    ) -> FloatValue<'ctx> {
+        let arr_size_f32 = self
+            .builder()
+            .build_signed_int_to_float(
+                self.local_variables.dst_len,
+                self.types.f32_type,
+                "dst_len_f32",
+            )
+            .unwrap();
+        let interval_len_over_arr_size = self
+            .builder()
+            .build_float_div(
+                self.types
+                    .f32_type
+                    .const_float((interval_end - interval_begin) as _),
+                arr_size_f32,
+                "internal_len_over_arr_size",
+            )
+            .unwrap();
+        let loop_counter_f32 = self
+            .builder()
+            .build_signed_int_to_float(
+                self.local_variables.loop_counter,
+                self.types.f32_type,
+                "loop_counter_f32",
+            )
+            .unwrap();
+        let interval_val_from_zero = self
+            .builder()
+            .build_float_mul(
+                loop_counter_f32,
+                interval_len_over_arr_size,
+                "interval_val_from_zero",
+            )
+            .unwrap();
+        let interval_val = self
+            .builder()
+            .build_float_add(
+                interval_val_from_zero,
+                self.types.f32_type.const_float(interval_begin as _),
+                "interval_val",
+            )
+            .unwrap();
+        interval_val
+    }
+
+    pub(super) fn compile_all_parameters(
         &mut self,
         graph: &SoundGraph,
+        expression_graph: &ExpressionGraph,
         parameter_mapping: &ExpressionParameterMapping,
         mode: JitMode,
     ) {
+        // Parameters that no result depends on would only generate
+        // dead loads and calls, so skip them entirely
+        let reachable_parameters = expression_graph.reachable_parameters();
+
         for (param_id, target) in parameter_mapping.items() {
+            if !reachable_parameters.contains(param_id) {
+                continue;
+            }
+
             self.builder().position_at_end(self.blocks.loop_body);
 
             let param_value = match mode {
@@ -873,15 +1169,52 @@ impl<'ctx> Jit<'ctx> {
         }
     }
 
+    /// Set whether NaN and infinite results are replaced with zero
+    /// before being written to the destination. This is on by default,
+    /// since a single non-finite value written to an audio-rate output
+    /// can otherwise poison everything downstream of it.
+    pub(crate) fn set_guard_non_finite_outputs(&mut self, guard: bool) {
+        self.guard_non_finite_outputs = guard;
+    }
+
     pub(crate) fn compile_expression(
         mut self,
         expression_graph: &ExpressionGraph,
         parameter_mapping: &ExpressionParameterMapping,
         graph: &SoundGraph,
         mode: JitMode,
-    ) -> CompiledExpressionArtefact<'ctx> {
-        // pre-compile all expression graph arguments
-        self.compile_all_parameters(graph, parameter_mapping, mode);
+    ) -> Result<CompiledExpressionArtefact<'ctx>, JitError> {
+        match parameter_mapping.lookup_table() {
+            Some(table) if table.applies_to(expression_graph, parameter_mapping) => {
+                let values =
+                    table.fill(self.inkwell_context, expression_graph, parameter_mapping)?;
+                self.build_lookup_table_expression(
+                    expression_graph,
+                    parameter_mapping,
+                    graph,
+                    mode,
+                    table,
+                    values,
+                );
+            }
+            _ => self.build_expression(expression_graph, parameter_mapping, graph, mode),
+        }
+        self.finish(expression_graph.results().len())
+    }
+
+    /// Generate the IR for the given expression graph without finishing
+    /// compilation. Only nodes and parameters which are reachable from
+    /// the graph's results are compiled, since nodes are visited on demand
+    /// starting from the results and unreachable parameters are skipped.
+    pub(super) fn build_expression(
+        &mut self,
+        expression_graph: &ExpressionGraph,
+        parameter_mapping: &ExpressionParameterMapping,
+        graph: &SoundGraph,
+        mode: JitMode,
+    ) {
+        // pre-compile all reachable expression graph arguments
+        self.compile_all_parameters(graph, expression_graph, parameter_mapping, mode);
 
         let final_values: Vec<FloatValue<'ctx>> = expression_graph
             .results()
@@ -896,6 +1229,13 @@ impl<'ctx> Jit<'ctx> {
             })
             .collect();
 
+        self.build_expression_results(final_values);
+    }
+
+    /// Generate the IR for writing the given values of each result to
+    /// the destination arrays and for the loop surrounding them. The
+    /// values must have been computed within the loop body.
+    pub(super) fn build_expression_results(&mut self, final_values: Vec<FloatValue<'ctx>>) {
         let dst_ptrs: Vec<PointerValue<'ctx>> = (0..final_values.len())
             .map(|i| {
                 let dst_ptr_ptr_i = unsafe {
@@ -976,6 +1316,11 @@ impl<'ctx> Jit<'ctx> {
         self.builder.position_at_end(self.blocks.loop_end);
         {
             for (final_value, dst_ptr) in final_values.into_iter().zip(dst_ptrs) {
+                let final_value = if self.guard_non_finite_outputs {
+                    self.build_non_finite_guard(final_value)
+                } else {
+                    final_value
+                };
                 let dst_elem_ptr = unsafe {
                     self.builder.build_gep(
                         self.types.f32_type,
@@ -1033,7 +1378,5 @@ impl<'ctx> Jit<'ctx> {
         {
             self.builder.build_return(None).unwrap();
         }
-
-        self.finish(expression_graph.results().len())
     }
 }
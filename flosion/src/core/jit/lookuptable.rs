@@ -0,0 +1,354 @@
+use std::sync::Arc;
+
+use hashstash::{Stashable, Stasher, UnstashError, Unstashable, Unstasher};
+use inkwell::values::{FloatValue, IntValue};
+
+use crate::core::{
+    expression::expressiongraph::{ExpressionGraph, ExpressionTarget},
+    sound::{
+        argument::ProcessorArgumentLocation,
+        expression::{ExpressionParameterMapping, ExpressionParameterTarget},
+        soundgraph::SoundGraph,
+    },
+};
+
+use super::{
+    compiledexpression::Discretization,
+    jit::{ExpressionTestDomain, Interval, Jit, JitError, JitMode},
+};
+
+/// Instructions for evaluating an expression by reading from a table of
+/// its values rather than evaluating the expression itself. The table is
+/// filled once at compile time by evaluating the expression over an
+/// interval of a single argument, and is read by clamping the argument
+/// to that interval and interpolating linearly between adjacent entries.
+/// This trades accuracy for speed, and only suits expressions which
+/// depend on nothing but that argument and which are smooth over the
+/// interval.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct LookupTable {
+    argument: ProcessorArgumentLocation,
+    interval: Interval,
+    size: usize,
+}
+
+impl LookupTable {
+    pub const MIN_SIZE: usize = 2;
+    pub const MAX_SIZE: usize = 1 << 16;
+    pub const DEFAULT_SIZE: usize = 256;
+
+    /// Create a table of the given number of entries, which are spaced
+    /// evenly over the interval, including both of its endpoints. The
+    /// size is clamped to lie between MIN_SIZE and MAX_SIZE.
+    pub(crate) fn new(
+        argument: ProcessorArgumentLocation,
+        interval: Interval,
+        size: usize,
+    ) -> LookupTable {
+        LookupTable {
+            argument,
+            interval,
+            size: size.clamp(Self::MIN_SIZE, Self::MAX_SIZE),
+        }
+    }
+
+    pub(crate) fn argument(&self) -> ProcessorArgumentLocation {
+        self.argument
+    }
+
+    pub(crate) fn interval(&self) -> Interval {
+        self.interval
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the table can stand in for the expression, which is
+    /// only the case if the expression's results depend on nothing
+    /// besides the table's argument
+    pub(crate) fn applies_to(
+        &self,
+        expression_graph: &ExpressionGraph,
+        parameter_mapping: &ExpressionParameterMapping,
+    ) -> bool {
+        parameter_mapping
+            .dependencies(expression_graph)
+            .into_iter()
+            .all(|target| target == ExpressionParameterTarget::Argument(self.argument))
+    }
+
+    /// Evaluate the expression directly over the table's interval,
+    /// producing one table per result. Each table has one more entry
+    /// than the table's size, repeating the last value, so that reads
+    /// interpolating towards the next entry always stay in bounds.
+    pub(super) fn fill(
+        &self,
+        inkwell_context: &inkwell::context::Context,
+        expression_graph: &ExpressionGraph,
+        parameter_mapping: &ExpressionParameterMapping,
+    ) -> Result<Vec<Arc<Vec<f32>>>, JitError> {
+        let mut direct_mapping = parameter_mapping.clone();
+        direct_mapping.set_lookup_table(None);
+
+        // Test mode places the sample at index i a fraction i/len of the
+        // way through the interval, while the table's entries include
+        // both endpoints. Stretch the interval so that the last entry
+        // lands exactly on its end.
+        let stretched_interval = {
+            let end = self
+                .interval
+                .value_at(self.size as f32 / (self.size - 1) as f32);
+            match self.interval {
+                Interval::Linear { from, .. } => Interval::Linear { from, to: end },
+                Interval::Logarithmic { from, .. } => Interval::Logarithmic { from, to: end },
+            }
+        };
+
+        let artefact = Jit::new(inkwell_context).compile_expression(
+            expression_graph,
+            &direct_mapping,
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::WithRespectTo(
+                self.argument,
+                stretched_interval,
+            )),
+        )?;
+
+        let mut tables: Vec<Vec<f32>> = Vec::new();
+        tables.resize_with(expression_graph.results().len(), || vec![0.0; self.size]);
+        let mut dst_slices: Vec<&mut [f32]> = tables.iter_mut().map(|v| &mut v[..]).collect();
+
+        artefact
+            .make_function()
+            .eval_in_test_mode(&mut dst_slices, Discretization::None);
+
+        Ok(tables
+            .into_iter()
+            .map(|mut table| {
+                table.push(*table.last().unwrap());
+                Arc::new(table)
+            })
+            .collect())
+    }
+}
+
+impl<'ctx> Jit<'ctx> {
+    /// Generate the IR for reading the given tables, which were filled
+    /// by `LookupTable::fill`, in place of evaluating the expression
+    pub(super) fn build_lookup_table_expression(
+        &mut self,
+        expression_graph: &ExpressionGraph,
+        parameter_mapping: &ExpressionParameterMapping,
+        graph: &SoundGraph,
+        mode: JitMode,
+        table: LookupTable,
+        values: Vec<Arc<Vec<f32>>>,
+    ) {
+        // The argument is produced as usual according to the mode, e.g.
+        // swept over an interval in test mode
+        self.compile_all_parameters(graph, expression_graph, parameter_mapping, mode);
+
+        let argument_value = parameter_mapping
+            .parameter_from_target(ExpressionParameterTarget::Argument(table.argument))
+            .and_then(|param_id| {
+                self.compiled_targets
+                    .get(&ExpressionTarget::Parameter(param_id))
+                    .cloned()
+            });
+
+        self.builder.position_at_end(self.blocks.loop_body);
+
+        let argument_value = match argument_value {
+            Some(v) => v,
+            // The results don't depend on the argument, so any entry will do
+            None => self.types.f32_type.const_zero(),
+        };
+
+        let (index, fraction) = self.build_lookup_table_position(argument_value, table);
+
+        let final_values = values
+            .into_iter()
+            .map(|table_values| self.build_lookup_table_read(table_values, index, fraction))
+            .collect();
+
+        self.build_expression_results(final_values);
+    }
+
+    /// Find the index of the table entry at or before the given value
+    /// and how far the value lies between that entry and the next one
+    fn build_lookup_table_position(
+        &mut self,
+        value: FloatValue<'ctx>,
+        table: LookupTable,
+    ) -> (IntValue<'ctx>, FloatValue<'ctx>) {
+        let f32_type = self.types.f32_type;
+
+        let interval_fraction = match table.interval {
+            Interval::Linear { from, to } => {
+                let offset = self
+                    .builder
+                    .build_float_sub(value, f32_type.const_float(from as _), "table_offset")
+                    .unwrap();
+                self.builder
+                    .build_float_mul(
+                        offset,
+                        f32_type.const_float((1.0 / (to - from)) as _),
+                        "table_fraction",
+                    )
+                    .unwrap()
+            }
+            Interval::Logarithmic { from, to } => {
+                // Non-positive values yield -inf or NaN here, both of
+                // which are clamped to the start of the table below
+                let log_value = self.build_unary_intrinsic_call("llvm.log", value);
+                let offset = self
+                    .builder
+                    .build_float_sub(
+                        log_value,
+                        f32_type.const_float(from.ln() as _),
+                        "table_offset",
+                    )
+                    .unwrap();
+                self.builder
+                    .build_float_mul(
+                        offset,
+                        f32_type.const_float((1.0 / (to / from).ln()) as _),
+                        "table_fraction",
+                    )
+                    .unwrap()
+            }
+        };
+
+        let last_index = (table.size - 1) as f32;
+
+        let position = self
+            .builder
+            .build_float_mul(
+                interval_fraction,
+                f32_type.const_float(last_index as _),
+                "table_position",
+            )
+            .unwrap();
+
+        // maxnum returns the other operand if one is NaN
+        let position =
+            self.build_binary_intrinsic_call("llvm.maxnum", position, f32_type.const_zero());
+        let position = self.build_binary_intrinsic_call(
+            "llvm.minnum",
+            position,
+            f32_type.const_float(last_index as _),
+        );
+
+        let position_floor = self.build_unary_intrinsic_call("llvm.floor", position);
+
+        let fraction = self
+            .builder
+            .build_float_sub(position, position_floor, "table_lerp_fraction")
+            .unwrap();
+
+        let index = self
+            .builder
+            .build_float_to_unsigned_int(position_floor, self.types.usize_type, "table_index")
+            .unwrap();
+
+        (index, fraction)
+    }
+
+    fn build_lookup_table_read(
+        &mut self,
+        values: Arc<Vec<f32>>,
+        index: IntValue<'ctx>,
+        fraction: FloatValue<'ctx>,
+    ) -> FloatValue<'ctx> {
+        let f32_type = self.types.f32_type;
+
+        let addr_val = self
+            .types
+            .usize_type
+            .const_int(values.as_ptr() as u64, false);
+        let ptr_table = self
+            .builder
+            .build_int_to_ptr(addr_val, self.types.pointer_type, "p_table")
+            .unwrap();
+
+        let next_index = self
+            .builder
+            .build_int_add(
+                index,
+                self.types.usize_type.const_int(1, false),
+                "table_next_index",
+            )
+            .unwrap();
+
+        let load_entry = |i: IntValue<'ctx>, name: &str| {
+            let ptr_entry = unsafe {
+                self.builder
+                    .build_gep(f32_type, ptr_table, &[i], "p_table_entry")
+                    .unwrap()
+            };
+            self.builder
+                .build_load(f32_type, ptr_entry, name)
+                .unwrap()
+                .into_float_value()
+        };
+
+        let value = load_entry(index, "table_value");
+        let next_value = load_entry(next_index, "table_next_value");
+
+        // Store an Arc to the table to ensure it stays alive
+        self.atomic_captures.push(values);
+
+        // value + fraction * (next_value - value)
+        let difference = self
+            .builder
+            .build_float_sub(next_value, value, "table_difference")
+            .unwrap();
+        let scaled_difference = self
+            .builder
+            .build_float_mul(fraction, difference, "table_scaled_difference")
+            .unwrap();
+        self.builder
+            .build_float_add(value, scaled_difference, "table_interpolated_value")
+            .unwrap()
+    }
+}
+
+impl Stashable for LookupTable {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.object(&self.argument);
+        let (from, to) = match self.interval {
+            Interval::Linear { from, to } => {
+                stasher.u8(0);
+                (from, to)
+            }
+            Interval::Logarithmic { from, to } => {
+                stasher.u8(1);
+                (from, to)
+            }
+        };
+        stasher.f32(from);
+        stasher.f32(to);
+        stasher.u64(self.size as _);
+    }
+}
+
+impl Unstashable for LookupTable {
+    fn unstash(unstasher: &mut Unstasher) -> Result<Self, UnstashError> {
+        let argument = ProcessorArgumentLocation::unstash(unstasher)?;
+        let logarithmic = match unstasher.u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(UnstashError::Corrupted),
+        };
+        let from = unstasher.f32()?;
+        let to = unstasher.f32()?;
+        let interval = if logarithmic {
+            Interval::Logarithmic { from, to }
+        } else {
+            Interval::Linear { from, to }
+        };
+        let size = unstasher.u64()? as usize;
+        Ok(LookupTable::new(argument, interval, size))
+    }
+}
@@ -1,6 +1,15 @@
 pub mod argumentstack;
 pub(crate) mod cache;
 pub mod compiledexpression;
+pub(crate) mod constantsubexpression;
 pub mod jit;
+pub(crate) mod lookuptable;
 pub mod types;
 pub(crate) mod wrappers;
+
+// Only run through tests, see test/benchmarktest.rs
+#[cfg(test)]
+pub(crate) mod benchmark;
+
+#[cfg(test)]
+mod test;
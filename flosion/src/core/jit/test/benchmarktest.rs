@@ -0,0 +1,188 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::{ExpressionGraph, ExpressionTarget},
+            expressionnode::{AnyExpressionNode, ExpressionNodeId, ExpressionNodeWithId},
+        },
+        jit::{
+            benchmark::{benchmark_expression, run_over_buffer},
+            jit::{ExpressionTestDomain, Jit, JitMode},
+        },
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{argument::ArgumentScope, expression::ProcessorExpression, soundgraph::SoundGraph},
+        soundchunk::CHUNK_SIZE,
+    },
+    objects::{
+        purefunctions::{Add, Constant, Multiply, SineWave},
+        statefulfunctions::WrappingIntegrator,
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+fn add_constant(graph: &mut ExpressionGraph, value: f64) -> ExpressionNodeId {
+    let node = ExpressionNodeWithId::<Constant>::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, value),
+    );
+    let id = node.id();
+    graph.add_expression_node(Box::new(node));
+    id
+}
+
+/// Add the node to the graph with its inputs connected to the given
+/// nodes, in order
+fn add_node(
+    graph: &mut ExpressionGraph,
+    node: Box<dyn AnyExpressionNode>,
+    inputs: &[ExpressionNodeId],
+) -> ExpressionNodeId {
+    let id = node.id();
+    let input_locations = node.input_locations();
+    graph.add_expression_node(node);
+    for (location, input) in input_locations.into_iter().zip(inputs) {
+        graph
+            .connect_input(location, Some(ExpressionTarget::Node(*input)))
+            .unwrap();
+    }
+    id
+}
+
+/// Build an expression with a single result computed by the given function
+fn make_expression<F: FnOnce(&mut ExpressionGraph) -> ExpressionNodeId>(
+    f: F,
+) -> ProcessorExpression {
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+    let graph = expr.graph_mut();
+    let result = f(graph);
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(result))
+        .unwrap();
+    expr
+}
+
+/// A phase which wraps around at the given frequency
+fn phasor(graph: &mut ExpressionGraph, frequency: f64) -> ExpressionNodeId {
+    let frequency = add_constant(graph, frequency);
+    add_node(
+        graph,
+        Box::new(ExpressionNodeWithId::<WrappingIntegrator>::new_default()),
+        &[frequency],
+    )
+}
+
+fn phasor_expression() -> ProcessorExpression {
+    make_expression(|graph| phasor(graph, 440.0))
+}
+
+fn sine_expression() -> ProcessorExpression {
+    make_expression(|graph| {
+        let phase = phasor(graph, 440.0);
+        add_node(
+            graph,
+            Box::new(ExpressionNodeWithId::<SineWave>::new_default()),
+            &[phase],
+        )
+    })
+}
+
+/// A sine wave whose frequency is modulated by another sine wave
+fn fm_expression() -> ProcessorExpression {
+    make_expression(|graph| {
+        let modulator_phase = phasor(graph, 5.0);
+        let modulator = add_node(
+            graph,
+            Box::new(ExpressionNodeWithId::<SineWave>::new_default()),
+            &[modulator_phase],
+        );
+        let depth = add_constant(graph, 10.0);
+        let deviation = add_node(
+            graph,
+            Box::new(ExpressionNodeWithId::<Multiply>::new_default()),
+            &[modulator, depth],
+        );
+        let base_frequency = add_constant(graph, 440.0);
+        let frequency = add_node(
+            graph,
+            Box::new(ExpressionNodeWithId::<Add>::new_default()),
+            &[base_frequency, deviation],
+        );
+        let phase = add_node(
+            graph,
+            Box::new(ExpressionNodeWithId::<WrappingIntegrator>::new_default()),
+            &[frequency],
+        );
+        add_node(
+            graph,
+            Box::new(ExpressionNodeWithId::<SineWave>::new_default()),
+            &[phase],
+        )
+    })
+}
+
+fn representative_expressions() -> Vec<(&'static str, ProcessorExpression)> {
+    vec![
+        ("phasor", phasor_expression()),
+        ("sine", sine_expression()),
+        ("fm", fm_expression()),
+    ]
+}
+
+#[test]
+fn test_run_over_buffer_evaluates_every_sample() {
+    let expr = phasor_expression();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::Temporal),
+        )
+        .unwrap();
+    let mut function = artefact.make_function();
+
+    // Deliberately not a whole number of chunks
+    let len = 3 * CHUNK_SIZE + 100;
+    let mut buffers = vec![vec![-1.0; len]];
+    run_over_buffer(&mut function, &mut buffers);
+
+    // The phase advances steadily across chunk boundaries
+    let step = 440.0 / SAMPLE_FREQUENCY as f32;
+    for pair in buffers[0].windows(2) {
+        let delta = (pair[1] - pair[0]).rem_euclid(1.0);
+        assert!((delta - step).abs() < 1e-4, "{} != {}", delta, step);
+    }
+}
+
+#[test]
+fn test_benchmark_reports_positive_throughput() {
+    let inkwell_context = inkwell::context::Context::create();
+    for (name, expr) in representative_expressions() {
+        let throughput = benchmark_expression(&inkwell_context, &expr, 4 * CHUNK_SIZE, 2).unwrap();
+        assert_eq!(throughput.samples(), 8 * CHUNK_SIZE);
+        assert!(
+            throughput.samples_per_second() > 0.0,
+            "Expected {} to have positive throughput",
+            name
+        );
+    }
+}
+
+/// Measure the throughput of every representative expression. This is
+/// slow and only meaningful in release builds, run it with
+/// `cargo test --release benchmark_jit_throughput -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn benchmark_jit_throughput() {
+    let inkwell_context = inkwell::context::Context::create();
+    for (name, expr) in representative_expressions() {
+        let throughput = benchmark_expression(&inkwell_context, &expr, 1 << 20, 64).unwrap();
+        println!(
+            "{:>8}: {:>8.1} million samples/sec ({} samples in {:?})",
+            name,
+            throughput.samples_per_second() / 1e6,
+            throughput.samples(),
+            throughput.elapsed()
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    core::{
+        expression::{expressiongraph::ExpressionTarget, expressionnode::ExpressionNodeWithId},
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Jit, JitMode},
+        },
+        sound::{argument::ArgumentScope, expression::ProcessorExpression, soundgraph::SoundGraph},
+    },
+    objects::purefunctions::BlockSize,
+};
+
+/// Evaluate a lone BlockSize node into an array of the given length
+fn eval_block_size(len: usize) -> Vec<f32> {
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let graph = expr.graph_mut();
+    let node = ExpressionNodeWithId::<BlockSize>::new_default();
+    let node_id = node.id();
+    graph.add_expression_node(Box::new(node));
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+        .unwrap();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let jit = Jit::new(&inkwell_context);
+    let artefact = jit
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::Temporal),
+        )
+        .unwrap();
+
+    let mut output = vec![0.0; len];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+    output
+}
+
+#[test]
+fn test_block_size_reports_destination_length() {
+    for len in [1, 7, 256] {
+        let output = eval_block_size(len);
+        assert!(
+            output.iter().all(|v| *v == len as f32),
+            "Expected every value to be {} but got {:?}",
+            len,
+            output
+        );
+    }
+}
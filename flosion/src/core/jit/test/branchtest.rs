@@ -0,0 +1,305 @@
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use inkwell::{values::FloatValue, FloatPredicate};
+
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressioninput::ExpressionInput,
+            expressionmacro::ExpressionMacro,
+            expressionnode::{
+                AnyExpressionNode, ExpressionNodeVisitor, ExpressionNodeVisitorMut,
+                ExpressionNodeWithId, PureExpressionNode,
+            },
+        },
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+        },
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+        stashing::StashingContext,
+    },
+    objects::purefunctions::Multiply,
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Doubles positive inputs and negates all others, using one branch
+struct DoubleOrNegate {
+    input: ExpressionInput,
+}
+
+impl PureExpressionNode for DoubleOrNegate {
+    fn new(_args: &ParsedArguments) -> Self {
+        DoubleOrNegate {
+            input: ExpressionInput::new(0.0),
+        }
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 1);
+        let x = inputs[0];
+        let zero = jit.types.f32_type.const_zero();
+        let is_positive = jit
+            .builder()
+            .build_float_compare(FloatPredicate::OGT, x, zero, "is_positive")
+            .unwrap();
+        jit.build_if_else(
+            is_positive,
+            |jit| jit.builder().build_float_add(x, x, "doubled").unwrap(),
+            |jit| jit.builder().build_float_neg(x, "negated").unwrap(),
+        )
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input);
+    }
+
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input);
+    }
+}
+
+impl WithObjectType for DoubleOrNegate {
+    const TYPE: ObjectType = ObjectType::new("doubleornegate");
+}
+
+impl Stashable<StashingContext> for DoubleOrNegate {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace for DoubleOrNegate {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)
+    }
+}
+
+/// Computes the sign of its input using nested branches
+struct NestedSign {
+    input: ExpressionInput,
+}
+
+impl PureExpressionNode for NestedSign {
+    fn new(_args: &ParsedArguments) -> Self {
+        NestedSign {
+            input: ExpressionInput::new(0.0),
+        }
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 1);
+        let x = inputs[0];
+        let zero = jit.types.f32_type.const_zero();
+        let is_positive = jit
+            .builder()
+            .build_float_compare(FloatPredicate::OGT, x, zero, "is_positive")
+            .unwrap();
+        jit.build_if_else(
+            is_positive,
+            |jit| jit.types.f32_type.const_float(1.0),
+            |jit| {
+                let is_negative = jit
+                    .builder()
+                    .build_float_compare(FloatPredicate::OLT, x, zero, "is_negative")
+                    .unwrap();
+                jit.build_if_else(
+                    is_negative,
+                    |jit| jit.types.f32_type.const_float(-1.0),
+                    |jit| jit.types.f32_type.const_zero(),
+                )
+            },
+        )
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input);
+    }
+
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input);
+    }
+}
+
+impl WithObjectType for NestedSign {
+    const TYPE: ObjectType = ObjectType::new("nestedsign");
+}
+
+impl Stashable<StashingContext> for NestedSign {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace for NestedSign {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)
+    }
+}
+
+/// Squares its input once within a branch taken for positive inputs
+/// and once more after the branch, adding the two when the branch is
+/// taken. Both squares come from expanding the same macro, so the
+/// second expansion visits the same targets as the first.
+struct SquareInAndAfterBranch {
+    input: ExpressionInput,
+    square: ExpressionMacro,
+}
+
+impl PureExpressionNode for SquareInAndAfterBranch {
+    fn new(_args: &ParsedArguments) -> Self {
+        let mut square = ExpressionMacro::new("sq".to_string(), &["x"]);
+        let x = square.input_parameter(0);
+        let graph = square.graph_mut();
+        let multiply = ExpressionNodeWithId::<Multiply>::new_default();
+        let multiply_id = multiply.id();
+        let multiply_inputs = (&multiply as &dyn AnyExpressionNode).input_locations();
+        graph.add_expression_node(Box::new(multiply));
+        for input in multiply_inputs {
+            graph
+                .connect_input(input, Some(ExpressionTarget::Parameter(x)))
+                .unwrap();
+        }
+        graph
+            .connect_result(graph.results()[0].id(), ExpressionTarget::Node(multiply_id))
+            .unwrap();
+
+        SquareInAndAfterBranch {
+            input: ExpressionInput::new(0.0),
+            square,
+        }
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 1);
+        let x = inputs[0];
+        let zero = jit.types.f32_type.const_zero();
+        let is_positive = jit
+            .builder()
+            .build_float_compare(FloatPredicate::OGT, x, zero, "is_positive")
+            .unwrap();
+        let in_branch = jit.build_if_else(
+            is_positive,
+            |jit| jit.compile_macro(&self.square, &[x]),
+            |jit| jit.types.f32_type.const_zero(),
+        );
+        let after_branch = jit.compile_macro(&self.square, &[x]);
+        jit.builder()
+            .build_float_add(in_branch, after_branch, "sum")
+            .unwrap()
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input);
+    }
+
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input);
+    }
+}
+
+impl WithObjectType for SquareInAndAfterBranch {
+    const TYPE: ObjectType = ObjectType::new("squareinandafterbranch");
+}
+
+impl Stashable<StashingContext> for SquareInAndAfterBranch {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace for SquareInAndAfterBranch {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)
+    }
+}
+
+/// Compile an expression consisting of a single node of type T whose
+/// input is an argument, and evaluate it over the integers from -4 to 3
+/// in test mode with respect to that argument
+fn eval_over_integers<T>() -> Vec<f32>
+where
+    T: 'static
+        + PureExpressionNode
+        + WithObjectType
+        + Stashable<StashingContext>
+        + UnstashableInplace,
+{
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let node = ExpressionNodeWithId::<T>::new_default();
+    let node_id = node.id();
+    let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(node));
+
+    graph
+        .connect_input(
+            input_locations[0],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+        .unwrap();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::WithRespectTo(
+                argument_location,
+                Interval::Linear {
+                    from: -4.0,
+                    to: 4.0,
+                },
+            )),
+        )
+        .unwrap();
+
+    let mut output = [0.0; 8];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+    output.to_vec()
+}
+
+#[test]
+fn test_if_else_takes_both_branches() {
+    assert_eq!(
+        eval_over_integers::<DoubleOrNegate>(),
+        vec![4.0, 3.0, 2.0, 1.0, 0.0, 2.0, 4.0, 6.0]
+    );
+}
+
+#[test]
+fn test_nested_if_else() {
+    assert_eq!(
+        eval_over_integers::<NestedSign>(),
+        vec![-1.0, -1.0, -1.0, -1.0, 0.0, 1.0, 1.0, 1.0]
+    );
+}
+
+#[test]
+fn test_values_compiled_in_branch_are_not_reused_after_it() {
+    assert_eq!(
+        eval_over_integers::<SquareInAndAfterBranch>(),
+        vec![16.0, 9.0, 4.0, 1.0, 0.0, 2.0, 8.0, 18.0]
+    );
+}
@@ -0,0 +1,102 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::jit::{Jit, JitMode},
+        sound::{
+            argument::ArgumentScope,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::{Constant, Floor, Multiply},
+    ui_core::arguments::ParsedArguments,
+};
+
+fn build_ir(expr: &ProcessorExpression) -> String {
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    jit.build_expression(
+        expr.graph(),
+        expr.mapping(),
+        &SoundGraph::new(),
+        JitMode::Normal,
+    );
+    jit.module().print_to_string().to_string()
+}
+
+fn make_constant(value: f64) -> ExpressionNodeWithId<Constant> {
+    ExpressionNodeWithId::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, value),
+    )
+}
+
+/// Builds floor(x * 1.75) where x is either the constant 2 or the processor time
+fn make_floor_expression(constant_input: bool) -> ProcessorExpression {
+    let proc_id = SoundProcessorId::new(1);
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let time_param = expr.add_target(ExpressionParameterTarget::ProcessorTime(proc_id));
+
+    let graph = expr.graph_mut();
+
+    let two = make_constant(2.0);
+    let two_id = two.id();
+    graph.add_expression_node(Box::new(two));
+
+    let one_and_three_quarters = make_constant(1.75);
+    let one_and_three_quarters_id = one_and_three_quarters.id();
+    graph.add_expression_node(Box::new(one_and_three_quarters));
+
+    let multiply = ExpressionNodeWithId::<Multiply>::new_default();
+    let multiply_id = multiply.id();
+    let multiply_inputs = (&multiply as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(multiply));
+
+    let floor = ExpressionNodeWithId::<Floor>::new_default();
+    let floor_id = floor.id();
+    let floor_inputs = (&floor as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(floor));
+
+    let x = if constant_input {
+        ExpressionTarget::Node(two_id)
+    } else {
+        ExpressionTarget::Parameter(time_param)
+    };
+    graph.connect_input(multiply_inputs[0], Some(x)).unwrap();
+    graph
+        .connect_input(
+            multiply_inputs[1],
+            Some(ExpressionTarget::Node(one_and_three_quarters_id)),
+        )
+        .unwrap();
+    graph
+        .connect_input(floor_inputs[0], Some(ExpressionTarget::Node(multiply_id)))
+        .unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(floor_id))
+        .unwrap();
+
+    expr
+}
+
+#[test]
+fn test_constant_expression_is_folded() {
+    let ir = build_ir(&make_floor_expression(true));
+
+    assert!(!ir.contains("call float @llvm.floor"));
+    assert!(!ir.contains("fmul"));
+    assert!(ir.contains("store float 3.000000e+00"));
+}
+
+#[test]
+fn test_expression_with_parameter_is_not_folded() {
+    let ir = build_ir(&make_floor_expression(false));
+
+    assert!(ir.contains("call float @llvm.floor"));
+    assert!(ir.contains("fmul"));
+}
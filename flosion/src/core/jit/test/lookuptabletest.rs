@@ -0,0 +1,186 @@
+use std::f32::consts::PI;
+
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+            lookuptable::LookupTable,
+        },
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::Sin,
+};
+
+const NUM_SAMPLES: usize = 1000;
+
+/// Create the expression sin(x) for a single argument x
+fn make_sine_expression() -> (ProcessorExpression, ProcessorArgumentLocation) {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let node = ExpressionNodeWithId::<Sin>::new_default();
+    let node_id = node.id();
+    let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(node));
+
+    graph
+        .connect_input(
+            input_locations[0],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+        .unwrap();
+
+    (expr, argument_location)
+}
+
+/// Evaluate the expression's only result with its argument swept
+/// over the given interval
+fn eval_over(
+    expr: &ProcessorExpression,
+    argument: ProcessorArgumentLocation,
+    interval: Interval,
+) -> Vec<f32> {
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::WithRespectTo(argument, interval)),
+        )
+        .unwrap();
+
+    let mut output = vec![0.0; NUM_SAMPLES];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+    output
+}
+
+#[test]
+fn test_lookup_table_matches_direct_evaluation() {
+    let (mut expr, argument) = make_sine_expression();
+
+    let interval = Interval::Linear {
+        from: 0.0,
+        to: 2.0 * PI,
+    };
+
+    let direct = eval_over(&expr, argument, interval);
+
+    for size in [16, 64, 256, 1024] {
+        expr.parts_mut()
+            .0
+            .set_lookup_table(Some(LookupTable::new(argument, interval, size)));
+
+        let from_table = eval_over(&expr, argument, interval);
+
+        // Linear interpolation between entries h apart is off by at
+        // most h^2 / 8 times the largest second derivative, which is 1
+        let spacing = 2.0 * PI / (size - 1) as f32;
+        let tolerance = spacing * spacing / 8.0 + 1e-5;
+
+        for (i, (a, b)) in direct.iter().zip(&from_table).enumerate() {
+            assert!(
+                (a - b).abs() <= tolerance,
+                "With a table of size {}, sample {} was {} but should be {}",
+                size,
+                i,
+                b,
+                a
+            );
+        }
+    }
+}
+
+#[test]
+fn test_lookup_table_is_exact_at_entries() {
+    let (mut expr, argument) = make_sine_expression();
+
+    let interval = Interval::Logarithmic {
+        from: 0.1,
+        to: 10.0,
+    };
+
+    expr.parts_mut()
+        .0
+        .set_lookup_table(Some(LookupTable::new(argument, interval, 3)));
+
+    // Entries lie at 0.1, 1, and 10, which are the first sample and
+    // the sample halfway through the interval
+    let from_table = eval_over(&expr, argument, interval);
+
+    for i in [0, NUM_SAMPLES / 2] {
+        let x = interval.sample_value(i, NUM_SAMPLES);
+        assert!((from_table[i] - x.sin()).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_lookup_table_clamps_outside_interval() {
+    let (mut expr, argument) = make_sine_expression();
+
+    let table_interval = Interval::Linear { from: 1.0, to: 2.0 };
+
+    expr.parts_mut()
+        .0
+        .set_lookup_table(Some(LookupTable::new(argument, table_interval, 64)));
+
+    let from_table = eval_over(
+        &expr,
+        argument,
+        Interval::Linear {
+            from: -4.0,
+            to: 6.0,
+        },
+    );
+
+    for (i, value) in from_table.iter().enumerate() {
+        let x = -4.0 + 10.0 * i as f32 / NUM_SAMPLES as f32;
+        if x <= 1.0 {
+            assert!((value - 1.0_f32.sin()).abs() < 1e-5);
+        } else if x >= 2.0 {
+            assert!((value - 2.0_f32.sin()).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn test_lookup_table_is_ignored_with_other_dependencies() {
+    let (mut expr, argument) = make_sine_expression();
+
+    let table = LookupTable::new(argument, Interval::Linear { from: 0.0, to: 1.0 }, 16);
+    assert!(table.applies_to(expr.graph(), expr.mapping()));
+
+    // Depend on time as well
+    let time_param = expr.add_target(ExpressionParameterTarget::ProcessorTime(
+        argument.processor(),
+    ));
+    let time_result = expr.graph_mut().add_result(0.0);
+    expr.graph_mut()
+        .connect_result(time_result, ExpressionTarget::Parameter(time_param))
+        .unwrap();
+
+    assert!(!table.applies_to(expr.graph(), expr.mapping()));
+}
@@ -0,0 +1,181 @@
+use std::rc::Rc;
+
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::{ExpressionGraph, ExpressionTarget},
+            expressionmacro::ExpressionMacro,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+            macrocall::MacroCall,
+        },
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+        },
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::{Add, Constant, Multiply},
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Defines sq(x) = x * x
+fn make_square_macro() -> ExpressionMacro {
+    let mut expr_macro = ExpressionMacro::new("sq".to_string(), &["x"]);
+    let x = expr_macro.input_parameter(0);
+    let graph = expr_macro.graph_mut();
+
+    let multiply = ExpressionNodeWithId::<Multiply>::new_default();
+    let multiply_id = multiply.id();
+    let multiply_inputs = (&multiply as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(multiply));
+
+    for input in multiply_inputs {
+        graph
+            .connect_input(input, Some(ExpressionTarget::Parameter(x)))
+            .unwrap();
+    }
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(multiply_id))
+        .unwrap();
+
+    expr_macro
+}
+
+/// Adds a call to the given macro to the graph, with its only input
+/// connected to the given target, and returns the call's id
+fn add_macro_call(
+    graph: &mut ExpressionGraph,
+    definition: &Rc<ExpressionMacro>,
+    input_target: ExpressionTarget,
+) -> ExpressionTarget {
+    let args = ParsedArguments::new_empty()
+        .add_or_replace(&MacroCall::ARG_NAME, definition.name().to_string())
+        .add_or_replace(&MacroCall::ARG_NUM_INPUTS, definition.num_inputs());
+    let mut call = ExpressionNodeWithId::<MacroCall>::new_from_args(&args);
+    call.set_definition(Some(Rc::clone(definition)));
+    let call_id = call.id();
+    let call_inputs = (&call as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(call));
+
+    graph
+        .connect_input(call_inputs[0], Some(input_target))
+        .unwrap();
+
+    ExpressionTarget::Node(call_id)
+}
+
+/// Builds sq(x) + sq(x + 1) where x is a processor argument
+fn make_expression() -> (ProcessorExpression, ProcessorArgumentLocation) {
+    let definition = Rc::new(make_square_macro());
+
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let one = ExpressionNodeWithId::<Constant>::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, 1.0),
+    );
+    let one_id = one.id();
+    graph.add_expression_node(Box::new(one));
+
+    let plus_one = ExpressionNodeWithId::<Add>::new_default();
+    let plus_one_id = plus_one.id();
+    let plus_one_inputs = (&plus_one as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(plus_one));
+    graph
+        .connect_input(
+            plus_one_inputs[0],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+    graph
+        .connect_input(plus_one_inputs[1], Some(ExpressionTarget::Node(one_id)))
+        .unwrap();
+
+    let sq_x = add_macro_call(graph, &definition, ExpressionTarget::Parameter(arg_param));
+    let sq_x_plus_one = add_macro_call(graph, &definition, ExpressionTarget::Node(plus_one_id));
+
+    let sum = ExpressionNodeWithId::<Add>::new_default();
+    let sum_id = sum.id();
+    let sum_inputs = (&sum as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(sum));
+    graph.connect_input(sum_inputs[0], Some(sq_x)).unwrap();
+    graph
+        .connect_input(sum_inputs[1], Some(sq_x_plus_one))
+        .unwrap();
+
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(sum_id))
+        .unwrap();
+
+    (expr, argument_location)
+}
+
+/// Evaluates the expression for x in -2, -1, 0, 1
+fn test_mode(argument_location: ProcessorArgumentLocation) -> JitMode {
+    JitMode::Test(ExpressionTestDomain::WithRespectTo(
+        argument_location,
+        Interval::Linear {
+            from: -2.0,
+            to: 2.0,
+        },
+    ))
+}
+
+#[test]
+fn test_macro_used_twice_compiles_to_two_squarings() {
+    let (expr, argument_location) = make_expression();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    jit.build_expression(
+        expr.graph(),
+        expr.mapping(),
+        &SoundGraph::new(),
+        test_mode(argument_location),
+    );
+    let ir = jit.module().print_to_string().to_string();
+
+    // Each use squares its own input
+    assert_eq!(
+        ir.matches("= fmul float %interval_val, %interval_val")
+            .count(),
+        1
+    );
+    assert_eq!(ir.matches("= fmul float %sum, %sum").count(), 1);
+}
+
+#[test]
+fn test_macro_uses_are_independent() {
+    let (expr, argument_location) = make_expression();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            test_mode(argument_location),
+        )
+        .unwrap();
+
+    let mut output = [0.0; 4];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+
+    // x^2 + (x + 1)^2
+    assert_eq!(output, [5.0, 1.0, 1.0, 5.0]);
+}
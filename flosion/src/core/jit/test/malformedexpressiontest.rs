@@ -0,0 +1,45 @@
+use crate::core::{
+    jit::jit::{Jit, JitError, JitMode},
+    sound::{argument::ArgumentScope, expression::ProcessorExpression, soundgraph::SoundGraph},
+};
+
+#[test]
+fn test_valid_expression_compiles() {
+    let expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    jit.build_expression(
+        expr.graph(),
+        expr.mapping(),
+        &SoundGraph::new(),
+        JitMode::Normal,
+    );
+
+    assert!(jit.finish(expr.graph().results().len()).is_ok());
+}
+
+#[test]
+fn test_malformed_expression_yields_error() {
+    let expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    jit.build_expression(
+        expr.graph(),
+        expr.mapping(),
+        &SoundGraph::new(),
+        JitMode::Normal,
+    );
+
+    // A basic block without a terminator is not valid IR
+    jit.context()
+        .append_basic_block(jit.function(), "malformed");
+
+    let result = jit.finish(expr.graph().results().len());
+
+    assert!(
+        matches!(result, Err(JitError::InvalidModule(_))),
+        "Expected a malformed module to be rejected"
+    );
+}
@@ -0,0 +1,10 @@
+mod benchmarktest;
+mod blocksizetest;
+mod branchtest;
+mod constantfoldingtest;
+mod lookuptabletest;
+mod macrotest;
+mod malformedexpressiontest;
+mod nonfiniteguardtest;
+mod sharedsubexpressiontest;
+mod unreachabletest;
@@ -0,0 +1,103 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+        },
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::Divide,
+};
+
+/// Evaluate a / x for x in -2, -1, 0, 1 where a is x itself if
+/// `divide_by_self` is set and 1 otherwise, optionally guarding
+/// against non-finite outputs
+fn eval_division(divide_by_self: bool, guard: bool) -> [f32; 4] {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let node = ExpressionNodeWithId::<Divide>::new_default();
+    let node_id = node.id();
+    let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(node));
+
+    if divide_by_self {
+        graph
+            .connect_input(
+                input_locations[0],
+                Some(ExpressionTarget::Parameter(arg_param)),
+            )
+            .unwrap();
+    }
+    graph
+        .connect_input(
+            input_locations[1],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+        .unwrap();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    if !guard {
+        jit.set_guard_non_finite_outputs(false);
+    }
+    let artefact = jit
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::WithRespectTo(
+                argument_location,
+                Interval::Linear {
+                    from: -2.0,
+                    to: 2.0,
+                },
+            )),
+        )
+        .unwrap();
+
+    let mut output = [0.0; 4];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+    output
+}
+
+#[test]
+fn test_infinity_is_written_as_zero() {
+    assert_eq!(eval_division(false, true), [-0.5, -1.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_nan_is_written_as_zero() {
+    assert_eq!(eval_division(true, true), [1.0, 1.0, 0.0, 1.0]);
+}
+
+#[test]
+fn test_unguarded_outputs_are_written_as_is() {
+    let output = eval_division(false, false);
+    assert_eq!(output[2], f32::INFINITY);
+
+    let output = eval_division(true, false);
+    assert!(output[2].is_nan());
+}
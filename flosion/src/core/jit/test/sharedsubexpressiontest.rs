@@ -0,0 +1,100 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNode, ExpressionNodeWithId},
+        },
+        jit::jit::{Jit, JitMode},
+        sound::{
+            argument::ArgumentScope,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::{
+        purefunctions::{Add, Multiply, Sin},
+        statefulfunctions::Integrator,
+    },
+};
+
+fn build_ir(expr: &ProcessorExpression) -> String {
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    jit.build_expression(
+        expr.graph(),
+        expr.mapping(),
+        &SoundGraph::new(),
+        JitMode::Normal,
+    );
+    jit.module().print_to_string().to_string()
+}
+
+/// Builds f(g(time)) + f(g(time)) out of two separate copies of f(g(...))
+fn make_duplicated_expression<F, G>() -> ProcessorExpression
+where
+    F: 'static + ExpressionNode,
+    G: 'static + ExpressionNode,
+    ExpressionNodeWithId<F>: AnyExpressionNode,
+    ExpressionNodeWithId<G>: AnyExpressionNode,
+{
+    let proc_id = SoundProcessorId::new(1);
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let time_param = expr.add_target(ExpressionParameterTarget::ProcessorTime(proc_id));
+
+    let graph = expr.graph_mut();
+
+    let add = ExpressionNodeWithId::<Add>::new_default();
+    let add_id = add.id();
+    let add_inputs = (&add as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(add));
+
+    for add_input in add_inputs {
+        let inner = ExpressionNodeWithId::<G>::new_default();
+        let inner_id = inner.id();
+        let inner_inputs = (&inner as &dyn AnyExpressionNode).input_locations();
+        graph.add_expression_node(Box::new(inner));
+
+        let outer = ExpressionNodeWithId::<F>::new_default();
+        let outer_id = outer.id();
+        let outer_inputs = (&outer as &dyn AnyExpressionNode).input_locations();
+        graph.add_expression_node(Box::new(outer));
+
+        for input in inner_inputs {
+            graph
+                .connect_input(input, Some(ExpressionTarget::Parameter(time_param)))
+                .unwrap();
+        }
+        graph
+            .connect_input(outer_inputs[0], Some(ExpressionTarget::Node(inner_id)))
+            .unwrap();
+        graph
+            .connect_input(add_input, Some(ExpressionTarget::Node(outer_id)))
+            .unwrap();
+    }
+
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(add_id))
+        .unwrap();
+
+    expr
+}
+
+#[test]
+fn test_identical_subexpressions_are_compiled_once() {
+    let ir = build_ir(&make_duplicated_expression::<Sin, Multiply>());
+
+    assert_eq!(ir.matches("call float @llvm.sin").count(), 1);
+    assert_eq!(ir.matches("%product = fmul").count(), 1);
+    assert!(ir.contains("fadd float %llvm.sin_call, %llvm.sin_call"));
+}
+
+#[test]
+fn test_stateful_nodes_are_not_shared() {
+    let ir = build_ir(&make_duplicated_expression::<Integrator, Sin>());
+
+    assert_eq!(ir.matches("call float @llvm.sin").count(), 1);
+    assert_eq!(ir.matches("_state0 = alloca").count(), 2);
+}
@@ -0,0 +1,76 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::jit::{Jit, JitMode},
+        sound::{
+            argument::ArgumentScope,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::Sin,
+};
+
+fn build_ir(expr: &ProcessorExpression) -> String {
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit = Jit::new(&inkwell_context);
+    jit.build_expression(
+        expr.graph(),
+        expr.mapping(),
+        &SoundGraph::new(),
+        JitMode::Normal,
+    );
+    jit.module().print_to_string().to_string()
+}
+
+fn make_expression_with_sin_node(connect_result: bool) -> ProcessorExpression {
+    let proc_id = SoundProcessorId::new(1);
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let time_param = expr.add_target(ExpressionParameterTarget::ProcessorTime(proc_id));
+
+    let graph = expr.graph_mut();
+
+    let node = ExpressionNodeWithId::<Sin>::new_default();
+    let node_id = node.id();
+    let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(node));
+
+    graph
+        .connect_input(
+            input_locations[0],
+            Some(ExpressionTarget::Parameter(time_param)),
+        )
+        .unwrap();
+
+    if connect_result {
+        graph
+            .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+            .unwrap();
+    }
+
+    expr
+}
+
+#[test]
+fn test_reachable_node_emits_ir() {
+    let ir = build_ir(&make_expression_with_sin_node(true));
+
+    assert!(ir.contains("call float @llvm.sin"));
+    assert!(ir.contains("%adjusted_time_step"));
+}
+
+#[test]
+fn test_unreachable_node_emits_no_ir() {
+    let ir = build_ir(&make_expression_with_sin_node(false));
+
+    // Neither the disconnected node nor the parameter which
+    // only it reads from should have been compiled
+    assert!(!ir.contains("llvm.sin"));
+    assert!(!ir.contains("%adjusted_time_step"));
+}
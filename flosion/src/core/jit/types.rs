@@ -4,6 +4,7 @@ pub(crate) struct JitTypes<'ctx> {
     pub(crate) void_type: VoidType<'ctx>,
     pub(crate) pointer_type: PointerType<'ctx>,
     pub(crate) u8_type: IntType<'ctx>,
+    pub(crate) u32_type: IntType<'ctx>,
     pub(crate) u64_type: IntType<'ctx>,
     pub(crate) f32_type: FloatType<'ctx>,
     pub(crate) usize_type: IntType<'ctx>,
@@ -20,6 +21,7 @@ impl<'ctx> JitTypes<'ctx> {
         let void_type = inkwell_context.void_type();
         let pointer_type = inkwell_context.ptr_type(address_space);
         let u8_type = inkwell_context.i8_type();
+        let u32_type = inkwell_context.i32_type();
         let u64_type = inkwell_context.i64_type();
         let f32_type = inkwell_context.f32_type();
         let usize_type = inkwell_context.ptr_sized_int_type(target_data, Some(address_space));
@@ -28,6 +30,7 @@ impl<'ctx> JitTypes<'ctx> {
             void_type,
             pointer_type,
             u8_type,
+            u32_type,
             u64_type,
             f32_type,
             usize_type,
@@ -1,14 +1,27 @@
+pub mod biquad;
 pub mod expression;
 pub mod sound;
 // pub mod graphserialization;
+pub(crate) mod antidenormal;
 pub(crate) mod audiofileio;
+pub mod automation;
+pub mod beatsync;
 pub(crate) mod engine;
+pub mod fft;
+pub mod hardsync;
 pub mod jit;
 pub mod objecttype;
+pub mod pinknoise;
 pub mod resample;
 pub mod samplefrequency;
+pub mod smoothing;
 pub mod soundbuffer;
 pub mod soundchunk;
+pub mod stft;
 // pub mod timepoint;
 pub mod stashing;
+pub mod tempo;
 pub(crate) mod uniqueid;
+
+#[cfg(test)]
+mod test;
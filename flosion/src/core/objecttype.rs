@@ -17,4 +17,10 @@ impl ObjectType {
 // because doing so would make them not object safe.
 pub trait WithObjectType {
     const TYPE: ObjectType;
+
+    /// The version of the object's stashed form. Whenever the way an
+    /// object is stashed changes, its version should be increased and a
+    /// migration from the previous version registered so that older
+    /// patches can still be unstashed.
+    const VERSION: u32 = 0;
 }
@@ -0,0 +1,60 @@
+use rand::{rngs::SmallRng, Rng};
+
+/// Number of octaves of random values summed to produce pink noise
+const PINK_NUM_ROWS: usize = 16;
+
+/// Pink noise generator using the Voss-McCartney algorithm, in which
+/// a set of random values are summed and each is updated at half the
+/// rate of the previous one. Its output has the same power as uniform
+/// white noise from -1 to 1 once every row holds a random value.
+pub(crate) struct PinkNoise {
+    rows: [f32; PINK_NUM_ROWS],
+    running_sum: f32,
+    counter: u32,
+}
+
+impl PinkNoise {
+    /// Create a new generator whose rows are all zero, such that
+    /// the lowest octaves fade in as their rows are first updated
+    pub(crate) fn new() -> PinkNoise {
+        PinkNoise {
+            rows: [0.0; PINK_NUM_ROWS],
+            running_sum: 0.0,
+            counter: 0,
+        }
+    }
+
+    /// Create a new generator whose rows are already filled with random
+    /// values, such that it has its full power from the very first sample
+    pub(crate) fn new_settled(rng: &mut SmallRng) -> PinkNoise {
+        let rows: [f32; PINK_NUM_ROWS] = std::array::from_fn(|_| rng.gen_range(-1.0..=1.0));
+        PinkNoise {
+            rows,
+            running_sum: rows.iter().sum(),
+            counter: 0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = PinkNoise::new();
+    }
+
+    pub(crate) fn next(&mut self, rng: &mut SmallRng) -> f32 {
+        self.counter = self.counter.wrapping_add(1);
+
+        // The row to update is given by the number of trailing zeros
+        // in the counter, such that row i is updated every 2^(i+1) samples
+        let row = self.counter.trailing_zeros() as usize;
+        if row < PINK_NUM_ROWS {
+            let value: f32 = rng.gen_range(-1.0..=1.0);
+            self.running_sum += value - self.rows[row];
+            self.rows[row] = value;
+        }
+
+        // Add one more white sample to fill in the highest octave
+        let white: f32 = rng.gen_range(-1.0..=1.0);
+
+        // Scale so that the overall power is comparable to white noise
+        (self.running_sum + white) / ((PINK_NUM_ROWS + 1) as f32).sqrt()
+    }
+}
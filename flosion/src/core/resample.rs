@@ -1,3 +1,5 @@
+use crate::ui_core::arguments::ArgumentEnum;
+
 pub fn resample_interleave<F: FnMut() -> (f32, f32)>(
     output: &mut [f32],
     mut get_next_input_sample: F,
@@ -21,3 +23,217 @@ pub fn resample_interleave<F: FnMut() -> (f32, f32)>(
         remainder += ratio;
     }
 }
+
+/// Read a value at a fractional position within a circular buffer by
+/// linearly interpolating between the two nearest samples. Positions
+/// outside of the buffer wrap around.
+pub fn read_fractional(buffer: &[f32], position: f32) -> f32 {
+    debug_assert!(!buffer.is_empty());
+    let len = buffer.len();
+    let position = position.rem_euclid(len as f32);
+    let i0 = (position.floor() as usize) % len;
+    let i1 = (i0 + 1) % len;
+    lerp(buffer[i0], buffer[i1], position.fract())
+}
+
+/// Linearly interpolate between two adjacent samples, at the
+/// fraction t of the way from a to b
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+/// The number of filter taps in each phase of the polyphase filters
+/// used by Oversampler. Longer filters have a sharper transition but
+/// cost more and add more latency.
+pub(crate) const OVERSAMPLE_TAPS_PER_PHASE: usize = 32;
+
+/// How many times faster than the audio rate an Oversampler runs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OversampleFactor {
+    None,
+    X2,
+    X4,
+}
+
+impl OversampleFactor {
+    pub fn ratio(self) -> usize {
+        match self {
+            OversampleFactor::None => 1,
+            OversampleFactor::X2 => 2,
+            OversampleFactor::X4 => 4,
+        }
+    }
+
+    /// The delay in samples at the audio rate of upsampling and then
+    /// downsampling with an Oversampler at this factor
+    pub fn latency(self) -> usize {
+        let ratio = self.ratio();
+        if ratio == 1 {
+            return 0;
+        }
+        // Each filter delays by half its length at the oversampled rate
+        let len = ratio * OVERSAMPLE_TAPS_PER_PHASE;
+        (len - 1 + ratio / 2) / ratio
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            OversampleFactor::None => 0,
+            OversampleFactor::X2 => 1,
+            OversampleFactor::X4 => 2,
+        }
+    }
+
+    pub fn from_u8(x: u8) -> Option<OversampleFactor> {
+        match x {
+            0 => Some(OversampleFactor::None),
+            1 => Some(OversampleFactor::X2),
+            2 => Some(OversampleFactor::X4),
+            _ => None,
+        }
+    }
+}
+
+impl ArgumentEnum for OversampleFactor {
+    fn all_values() -> &'static [OversampleFactor] {
+        &[
+            OversampleFactor::None,
+            OversampleFactor::X2,
+            OversampleFactor::X4,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            OversampleFactor::None => "1x",
+            OversampleFactor::X2 => "2x",
+            OversampleFactor::X4 => "4x",
+        }
+    }
+}
+
+/// A Blackman-windowed sinc lowpass filter for the given oversampling
+/// ratio, with its cutoff just below the original Nyquist frequency
+/// and a gain of one at DC
+fn oversampling_filter(ratio: usize) -> Vec<f32> {
+    let len = ratio * OVERSAMPLE_TAPS_PER_PHASE;
+    let cutoff = 0.45 / ratio as f64;
+    let centre = (len - 1) as f64 / 2.0;
+    let mut taps: Vec<f64> = (0..len)
+        .map(|i| {
+            let x = i as f64 - centre;
+            let sinc = if x == 0.0 {
+                2.0 * cutoff
+            } else {
+                (2.0 * std::f64::consts::PI * cutoff * x).sin() / (std::f64::consts::PI * x)
+            };
+            let phase = 2.0 * std::f64::consts::PI * i as f64 / (len - 1) as f64;
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            sinc * window
+        })
+        .collect();
+    let sum: f64 = taps.iter().sum();
+    for t in &mut taps {
+        *t /= sum;
+    }
+    taps.into_iter().map(|t| t as f32).collect()
+}
+
+/// Converts a single channel of audio to and from a multiple of the
+/// audio rate using polyphase FIR filters, so that nonlinear processing
+/// can happen at the higher rate. Harmonics generated above the original
+/// Nyquist frequency are then filtered out when coming back down instead
+/// of folding back as aliasing.
+pub struct Oversampler {
+    factor: OversampleFactor,
+    filter: Vec<f32>,
+    up_history: Vec<f32>,
+    up_index: usize,
+    down_history: Vec<f32>,
+    down_index: usize,
+}
+
+impl Oversampler {
+    pub fn new(factor: OversampleFactor) -> Oversampler {
+        let ratio = factor.ratio();
+        let filter = if ratio > 1 {
+            oversampling_filter(ratio)
+        } else {
+            Vec::new()
+        };
+        Oversampler {
+            factor,
+            down_history: vec![0.0; filter.len()],
+            filter,
+            up_history: vec![0.0; OVERSAMPLE_TAPS_PER_PHASE],
+            up_index: 0,
+            down_index: 0,
+        }
+    }
+
+    pub fn factor(&self) -> OversampleFactor {
+        self.factor
+    }
+
+    pub fn reset(&mut self) {
+        self.up_history.fill(0.0);
+        self.down_history.fill(0.0);
+        self.up_index = 0;
+        self.down_index = 0;
+    }
+
+    /// Interpolate the input up to the oversampled rate. The output
+    /// must be exactly the oversampling ratio times as long as the input.
+    pub fn upsample(&mut self, input: &[f32], output: &mut [f32]) {
+        let ratio = self.factor.ratio();
+        debug_assert_eq!(output.len(), input.len() * ratio);
+        if ratio == 1 {
+            output.copy_from_slice(input);
+            return;
+        }
+        let taps = OVERSAMPLE_TAPS_PER_PHASE;
+        for (x, out) in input.iter().zip(output.chunks_exact_mut(ratio)) {
+            self.up_history[self.up_index] = *x;
+            // Each phase of the filter produces one of the interpolated
+            // samples between this input sample and the next, and the
+            // zeros that would otherwise be stuffed in between are skipped
+            for (phase, y) in out.iter_mut().enumerate() {
+                let mut sum = 0.0;
+                for k in 0..taps {
+                    let h = self.filter[phase + k * ratio];
+                    sum += h * self.up_history[(self.up_index + taps - k) % taps];
+                }
+                *y = sum * ratio as f32;
+            }
+            self.up_index = (self.up_index + 1) % taps;
+        }
+    }
+
+    /// Filter and decimate the oversampled input back down to the audio
+    /// rate. The input must be exactly the oversampling ratio times as long as the
+    /// output.
+    pub fn downsample(&mut self, input: &[f32], output: &mut [f32]) {
+        let ratio = self.factor.ratio();
+        debug_assert_eq!(input.len(), output.len() * ratio);
+        if ratio == 1 {
+            output.copy_from_slice(input);
+            return;
+        }
+        let len = self.filter.len();
+        for (xs, y) in input.chunks_exact(ratio).zip(output.iter_mut()) {
+            for x in xs {
+                self.down_history[self.down_index] = *x;
+                self.down_index = (self.down_index + 1) % len;
+            }
+            // Only every ratio-th output of the filter is kept, so only
+            // those are computed
+            let newest = self.down_index + len - 1;
+            *y = self
+                .filter
+                .iter()
+                .enumerate()
+                .map(|(j, h)| h * self.down_history[(newest - j) % len])
+                .sum();
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::core::samplefrequency::SAMPLE_FREQUENCY;
+
+/// A one-pole lowpass filter which smooths out abrupt changes to a value
+/// that is updated much less often than once per sample, such as a setting
+/// changed from the UI. Applying such a value directly would produce
+/// audible steps ("zipper noise").
+pub struct SmoothedValue {
+    current: f32,
+    coefficient: f32,
+}
+
+impl SmoothedValue {
+    /// Creates a smoothed value starting at the given value which
+    /// approaches new targets with the given time constant, in seconds
+    pub fn new(initial_value: f32, time_constant: f32) -> SmoothedValue {
+        let samples = (time_constant * SAMPLE_FREQUENCY as f32).max(1.0);
+        SmoothedValue {
+            current: initial_value,
+            coefficient: 1.0 - (-1.0 / samples).exp(),
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Jump immediately to the given value without smoothing
+    pub fn reset(&mut self, value: f32) {
+        self.current = value;
+    }
+
+    /// Advance by one sample towards the target, returning the new value
+    pub fn step(&mut self, target: f32) -> f32 {
+        self.current += self.coefficient * (target - self.current);
+        self.current
+    }
+}
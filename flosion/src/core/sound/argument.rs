@@ -176,6 +176,7 @@ impl<T> StartOver for CompiledProcessorArgument<T> {
 
 pub trait AnyProcessorArgument {
     fn id(&self) -> ProcessorArgumentId;
+    fn set_id(&mut self, id: ProcessorArgumentId);
 
     fn compile_evaluation<'ctx>(&self, jit: &mut Jit<'ctx>) -> FloatValue<'ctx>;
 }
@@ -185,6 +186,10 @@ impl<T: ArgumentTranslation> AnyProcessorArgument for ProcessorArgument<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: ProcessorArgumentId) {
+        self.id = id;
+    }
+
     fn compile_evaluation<'ctx>(&self, jit: &mut Jit<'ctx>) -> FloatValue<'ctx> {
         ProcessorArgument::compile_evaluation(self, jit)
     }
@@ -211,6 +216,10 @@ impl ArgumentScope {
     pub(crate) fn arguments(&self) -> &[ProcessorArgumentId] {
         &self.available_arguments
     }
+
+    pub(crate) fn arguments_mut(&mut self) -> &mut [ProcessorArgumentId] {
+        &mut self.available_arguments
+    }
 }
 
 impl Stashable<StashingContext> for ArgumentScope {
@@ -0,0 +1,48 @@
+use crate::core::engine::{soundengine::BlockSize, soundgraphcompiler::SoundGraphCompiler};
+
+use super::soundprocessor::{
+    CompiledComponentVisitor, CompiledProcessorComponent, ProcessorComponent,
+    ProcessorComponentVisitor, ProcessorComponentVisitorMut, SoundProcessorId, StartOver,
+};
+
+/// A processor component which follows the sound engine's block size.
+/// Processors sending audio out of the sound graph use it to pass their
+/// audio on in the same whole blocks which the engine processes.
+pub struct EngineBlockSize;
+
+impl ProcessorComponent for EngineBlockSize {
+    type CompiledType<'ctx> = CompiledEngineBlockSize;
+
+    fn visit(&self, _visitor: &mut dyn ProcessorComponentVisitor) {}
+
+    fn visit_mut(&mut self, _visitor: &mut dyn ProcessorComponentVisitorMut) {}
+
+    fn compile<'ctx>(
+        &self,
+        _processor_id: SoundProcessorId,
+        compiler: &mut SoundGraphCompiler<'_, 'ctx>,
+    ) -> CompiledEngineBlockSize {
+        CompiledEngineBlockSize {
+            block_size: compiler.block_size().clone(),
+        }
+    }
+}
+
+pub struct CompiledEngineBlockSize {
+    block_size: BlockSize,
+}
+
+impl CompiledEngineBlockSize {
+    /// The number of frames in each of the sound engine's blocks
+    pub fn frames(&self) -> usize {
+        self.block_size.frames()
+    }
+}
+
+impl CompiledProcessorComponent for CompiledEngineBlockSize {
+    fn visit(&self, _visitor: &mut dyn CompiledComponentVisitor) {}
+}
+
+impl StartOver for CompiledEngineBlockSize {
+    fn start_over(&mut self) {}
+}
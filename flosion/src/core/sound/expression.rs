@@ -7,6 +7,7 @@ use hashstash::{
 use crate::core::{
     engine::{compiledexpression::CompiledExpression, soundgraphcompiler::SoundGraphCompiler},
     expression::expressiongraph::{ExpressionGraph, ExpressionGraphParameterId},
+    jit::lookuptable::LookupTable,
     stashing::{StashingContext, UnstashingContext},
     uniqueid::UniqueId,
 };
@@ -108,12 +109,16 @@ impl Unstashable for ExpressionParameterTarget {
 #[derive(Clone)]
 pub(crate) struct ExpressionParameterMapping {
     mapping: HashMap<ExpressionGraphParameterId, ExpressionParameterTarget>,
+    /// If set, the expression is evaluated by reading from a table
+    /// precomputed over one of its arguments
+    lookup_table: Option<LookupTable>,
 }
 
 impl ExpressionParameterMapping {
     pub(crate) fn new() -> ExpressionParameterMapping {
         ExpressionParameterMapping {
             mapping: HashMap::new(),
+            lookup_table: None,
         }
     }
 
@@ -165,9 +170,36 @@ impl ExpressionParameterMapping {
         expr_graph.remove_parameter(giid).unwrap();
         let prev = self.mapping.remove(&giid);
         debug_assert!(prev.is_some());
+        if let ExpressionParameterTarget::Argument(arg) = target {
+            if self.lookup_table.map(|t| t.argument()) == Some(arg) {
+                self.lookup_table = None;
+            }
+        }
         debug_assert!(self.check_invariants(expr_graph));
     }
 
+    /// Point the parameter which currently refers to one target at
+    /// another target instead, leaving the expression graph untouched
+    pub(crate) fn retarget(
+        &mut self,
+        old_target: ExpressionParameterTarget,
+        new_target: ExpressionParameterTarget,
+    ) {
+        let giid = self.parameter_from_target(old_target).unwrap();
+        self.mapping.insert(giid, new_target);
+    }
+
+    pub(crate) fn lookup_table(&self) -> Option<LookupTable> {
+        self.lookup_table
+    }
+
+    /// Evaluate the expression using the given lookup table, or directly
+    /// if None. The table is ignored if the expression turns out to depend
+    /// on anything besides the table's argument.
+    pub(crate) fn set_lookup_table(&mut self, table: Option<LookupTable>) {
+        self.lookup_table = table;
+    }
+
     fn check_invariants(&self, graph: &ExpressionGraph) -> bool {
         let mapped_params: HashSet<ExpressionGraphParameterId> =
             self.mapping.keys().cloned().collect();
@@ -184,6 +216,20 @@ impl ExpressionParameterMapping {
     pub(crate) fn items(&self) -> &HashMap<ExpressionGraphParameterId, ExpressionParameterTarget> {
         &self.mapping
     }
+
+    /// Find the targets whose values the expression graph's results actually
+    /// depend on. Targets whose parameters are not reachable from any result
+    /// are omitted. The order of the returned targets is unspecified.
+    pub(crate) fn dependencies(
+        &self,
+        expr_graph: &ExpressionGraph,
+    ) -> Vec<ExpressionParameterTarget> {
+        expr_graph
+            .reachable_parameters()
+            .into_iter()
+            .filter_map(|param_id| self.target_from_parameter(param_id))
+            .collect()
+    }
 }
 
 impl Stashable for ExpressionParameterMapping {
@@ -196,6 +242,13 @@ impl Stashable for ExpressionParameterMapping {
             },
             hashstash::Order::Unordered,
         );
+        match &self.lookup_table {
+            Some(table) => {
+                stasher.bool(true);
+                stasher.object(table);
+            }
+            None => stasher.bool(false),
+        }
     }
 }
 
@@ -218,6 +271,15 @@ impl UnstashableInplace for ExpressionParameterMapping {
             Ok(())
         })?;
 
+        let lookup_table = if unstasher.bool_always()? {
+            Some(unstasher.object_always::<LookupTable>()?)
+        } else {
+            None
+        };
+        if time_to_write {
+            self.lookup_table = lookup_table;
+        }
+
         Ok(())
     }
 }
@@ -249,10 +311,18 @@ impl ProcessorExpression {
         self.id
     }
 
+    pub(crate) fn set_id(&mut self, id: ProcessorExpressionId) {
+        self.id = id;
+    }
+
     pub(crate) fn scope(&self) -> &ArgumentScope {
         &self.scope
     }
 
+    pub(crate) fn scope_mut(&mut self) -> &mut ArgumentScope {
+        &mut self.scope
+    }
+
     pub(crate) fn mapping(&self) -> &ExpressionParameterMapping {
         &self.param_mapping
     }
@@ -281,6 +351,14 @@ impl ProcessorExpression {
         self.param_mapping
             .remove_target(target, &mut self.expression_graph);
     }
+
+    pub(crate) fn retarget(
+        &mut self,
+        old_target: ExpressionParameterTarget,
+        new_target: ExpressionParameterTarget,
+    ) {
+        self.param_mapping.retarget(old_target, new_target);
+    }
 }
 
 impl ProcessorComponent for ProcessorExpression {
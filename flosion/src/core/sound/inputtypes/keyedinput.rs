@@ -7,8 +7,8 @@ use crate::core::{
     sound::{
         argument::ArgumentScope,
         soundinput::{
-            InputContext, InputTiming, ProcessorInput, SoundInputBackend, SoundInputCategory,
-            SoundInputLocation,
+            DisconnectBehavior, InputContext, InputTiming, ProcessorInput, SoundInputBackend,
+            SoundInputCategory, SoundInputLocation,
         },
         soundprocessor::{
             CompiledComponentVisitor, CompiledProcessorComponent, SoundProcessorId, StartOver,
@@ -52,15 +52,21 @@ impl<S: Send> SoundInputBackend for KeyedInputBackend<S> {
         &self,
         location: SoundInputLocation,
         target: Option<SoundProcessorId>,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
         compiler: &mut SoundGraphCompiler<'_, 'ctx>,
     ) -> Self::CompiledType<'ctx> {
+        let compensation = compiler.latency_compensation(location);
         CompiledKeyedInput {
             items: (0..self.num_keys)
                 .map(|_| CompiledKeyedInputItem {
                     node: CompiledSoundInputNode::new(
                         location,
+                        speed,
+                        disconnect_behavior,
                         compiler.compile_sound_processor(target),
-                    ),
+                    )
+                    .with_latency_compensation(compensation),
                     state: None,
                 })
                 .collect(),
@@ -138,6 +144,12 @@ impl<'ctx, S> CompiledProcessorComponent for CompiledKeyedInput<'ctx, S> {
             visitor.input_node(&item.node);
         }
     }
+
+    fn take_over(&mut self, previous: &mut Self) {
+        for (item, previous_item) in self.items.iter_mut().zip(previous.items.iter_mut()) {
+            item.node.take_over(&mut previous_item.node);
+        }
+    }
 }
 
 impl<'ctx, S> StartOver for CompiledKeyedInput<'ctx, S> {
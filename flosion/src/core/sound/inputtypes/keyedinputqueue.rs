@@ -3,12 +3,18 @@ use std::marker::PhantomData;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
 
 use crate::core::{
-    engine::{compiledprocessor::CompiledSoundInputNode, soundgraphcompiler::SoundGraphCompiler},
+    engine::{
+        compiledprocessor::CompiledSoundInputNode,
+        soundenginereport::VoiceReport,
+        soundgraphcompiler::SoundGraphCompiler,
+        voicelimit::{Voice, VoiceLimit},
+    },
     sound::{
         argument::ArgumentScope,
         context::AudioContext,
         soundinput::{
-            InputContext, ProcessorInput, SoundInputBackend, SoundInputCategory, SoundInputLocation,
+            DisconnectBehavior, InputContext, ProcessorInput, SoundInputBackend,
+            SoundInputCategory, SoundInputLocation,
         },
         soundprocessor::{
             CompiledComponentVisitor, CompiledProcessorComponent, SoundProcessorId, StartOver,
@@ -18,6 +24,13 @@ use crate::core::{
     stashing::{StashingContext, UnstashingContext},
 };
 
+/// Per-key state which can describe the note being played, so that
+/// the playing keys can be inspected while debugging and visualized
+pub trait KeyNote {
+    /// The MIDI-style note number of the key
+    fn note(&self) -> f32;
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum KeyReuse {
     FinishOldCancelNew,
@@ -41,6 +54,14 @@ enum QueuedKeyState<S> {
     Playing(KeyPlayingData<S>),
 }
 
+/// A short ramp from the last sample of a key which was cut off down
+/// to silence, which is played in place of the rest of the key
+struct ReleaseFade {
+    from: (f32, f32),
+    remaining: usize,
+    length: usize,
+}
+
 pub struct KeyedInputQueueBackend<S> {
     num_keys: usize,
     phantom_data: PhantomData<S>,
@@ -63,7 +84,7 @@ impl<S> KeyedInputQueueBackend<S> {
     }
 }
 
-impl<S: Send> SoundInputBackend for KeyedInputQueueBackend<S> {
+impl<S: Send + KeyNote> SoundInputBackend for KeyedInputQueueBackend<S> {
     type CompiledType<'ctx> = CompiledKeyedInputQueue<'ctx, S>;
 
     fn category(&self) -> SoundInputCategory {
@@ -74,18 +95,31 @@ impl<S: Send> SoundInputBackend for KeyedInputQueueBackend<S> {
         &self,
         location: SoundInputLocation,
         target: Option<SoundProcessorId>,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
         compiler: &mut SoundGraphCompiler<'_, 'ctx>,
     ) -> Self::CompiledType<'ctx> {
+        let compensation = compiler.latency_compensation(location);
         CompiledKeyedInputQueue {
             items: (0..self.num_keys)
                 .map(|_| CompiledKeyedInputQueueItem {
                     node: CompiledSoundInputNode::new(
                         location,
+                        speed,
+                        disconnect_behavior,
                         compiler.compile_sound_processor(target),
-                    ),
+                    )
+                    .with_latency_compensation(compensation),
                     state: QueuedKeyState::NotPlaying,
+                    level: 0.0,
+                    start_offset: 0,
+                    carry: SoundChunk::new(),
+                    voice: None,
+                    last_sample: (0.0, 0.0),
+                    release_fade: None,
                 })
                 .collect(),
+            voice_limit: compiler.voice_limit().clone(),
         }
     }
 }
@@ -112,21 +146,115 @@ impl<S> UnstashableInplace<UnstashingContext<'_>> for KeyedInputQueueBackend<S>
 struct CompiledKeyedInputQueueItem<'ctx, S> {
     node: CompiledSoundInputNode<'ctx>,
     state: QueuedKeyState<S>,
+
+    /// The peak amplitude of the key's most recent chunk of audio
+    level: f32,
+
+    /// The offset in samples into the chunk at which the key started.
+    /// The key's audio is delayed by this much, with the end of each
+    /// of its chunks carried over into the next.
+    start_offset: usize,
+
+    /// The key's previous chunk of audio, the last `start_offset`
+    /// samples of which are still to be played
+    carry: SoundChunk,
+
+    /// The key's place among all voices in the engine, while playing
+    voice: Option<Voice>,
+
+    /// The most recent sample of the key's audio
+    last_sample: (f32, f32),
+
+    /// The fade out of the key's previous note, if it was cut off
+    release_fade: Option<ReleaseFade>,
+}
+
+impl<'ctx, S> CompiledKeyedInputQueueItem<'ctx, S> {
+    /// Delay the key's chunk of audio by its start offset, playing
+    /// the end of the previous chunk first
+    fn delay_by_start_offset(&mut self, chunk: &mut SoundChunk) {
+        let offset = self.start_offset;
+        for (samples, carry) in [
+            (&mut chunk.l, &mut self.carry.l),
+            (&mut chunk.r, &mut self.carry.r),
+        ] {
+            samples.rotate_right(offset);
+            samples[..offset].swap_with_slice(&mut carry[..offset]);
+        }
+    }
+
+    /// Fade out from the key's most recent sample over the given
+    /// number of samples, since its audio is being cut off
+    fn begin_release_fade(&mut self, length: usize) {
+        self.release_fade = (length > 0).then_some(ReleaseFade {
+            from: self.last_sample,
+            remaining: length,
+            length,
+        });
+    }
+
+    /// Add what remains of the release fade, if any, to the key's
+    /// chunk of audio, and remember the chunk's last sample
+    fn apply_release_fade(&mut self, chunk: &mut SoundChunk) {
+        if let Some(fade) = &mut self.release_fade {
+            for (l, r) in chunk.l.iter_mut().zip(chunk.r.iter_mut()) {
+                if fade.remaining == 0 {
+                    break;
+                }
+                let gain = fade.remaining as f32 / fade.length as f32;
+                *l += fade.from.0 * gain;
+                *r += fade.from.1 * gain;
+                fade.remaining -= 1;
+            }
+            if fade.remaining == 0 {
+                self.release_fade = None;
+            }
+        }
+        self.last_sample = (chunk.l[CHUNK_SIZE - 1], chunk.r[CHUNK_SIZE - 1]);
+    }
 }
 
 pub struct CompiledKeyedInputQueue<'ctx, S> {
     items: Vec<CompiledKeyedInputQueueItem<'ctx, S>>,
+    voice_limit: VoiceLimit,
 }
 
 impl<'ctx, S> CompiledKeyedInputQueue<'ctx, S> {
     pub fn start_key(
         &mut self,
         duration_samples: Option<usize>,
+        sample_offset: usize,
         id: usize,
         state: S,
         reuse: KeyReuse,
     ) {
-        let mut oldest_key_index_and_age = None;
+        self.start_key_stealing(
+            duration_samples,
+            sample_offset,
+            id,
+            state,
+            reuse,
+            |_, age| age,
+        );
+    }
+
+    /// Start a new key, whose audio begins the given number of samples
+    /// into the next chunk. If all keys are in use and `reuse` permits it,
+    /// the playing key for which `steal_priority` is greatest is stopped
+    /// and replaced. The priority function receives each playing key's
+    /// state and its age in chunks. Every other new key counts towards
+    /// the engine-wide voice limit, and is not started if that limit
+    /// refuses it.
+    pub fn start_key_stealing<K: PartialOrd, F: Fn(&S, usize) -> K>(
+        &mut self,
+        duration_samples: Option<usize>,
+        sample_offset: usize,
+        id: usize,
+        state: S,
+        reuse: KeyReuse,
+        steal_priority: F,
+    ) {
+        let mut stolen_key_index_and_priority: Option<(usize, K)> = None;
         let mut available_index = None;
         for (i, d) in self.items.iter_mut().enumerate() {
             if let QueuedKeyState::Playing(key_data) = &mut d.state {
@@ -137,15 +265,16 @@ impl<'ctx, S> CompiledKeyedInputQueue<'ctx, S> {
                 //     };
                 //     return;
                 // }
-                oldest_key_index_and_age = match oldest_key_index_and_age {
-                    Some((j, s)) => {
-                        if key_data.age > s {
-                            Some((i, key_data.age))
+                let priority = steal_priority(&key_data.state, key_data.age);
+                stolen_key_index_and_priority = match stolen_key_index_and_priority {
+                    Some((j, p)) => {
+                        if priority > p {
+                            Some((i, priority))
                         } else {
-                            Some((j, s))
+                            Some((j, p))
                         }
                     }
-                    None => Some((i, key_data.age)),
+                    None => Some((i, priority)),
                 };
             } else {
                 if available_index.is_none() {
@@ -160,13 +289,33 @@ impl<'ctx, S> CompiledKeyedInputQueue<'ctx, S> {
                 if reuse == KeyReuse::FinishOldCancelNew {
                     return;
                 }
-                oldest_key_index_and_age.unwrap().0
+                stolen_key_index_and_priority.unwrap().0
             }
         };
 
         let data = &mut self.items[index];
 
-        data.node.start_over_at(0); // TODO: sample offset
+        if let QueuedKeyState::Playing(_) = data.state {
+            // The key's current note is being cut off
+            data.begin_release_fade(self.voice_limit.release_fade_samples());
+        }
+
+        let voice = match data.voice {
+            Some(voice) if !self.voice_limit.is_stolen(voice) => {
+                self.voice_limit.restart_voice(voice);
+                voice
+            }
+            _ => match self.voice_limit.start_voice() {
+                Some(voice) => voice,
+                None => return,
+            },
+        };
+        data.voice = Some(voice);
+
+        debug_assert!(sample_offset < CHUNK_SIZE);
+        data.node.start_over_at(sample_offset);
+        data.start_offset = sample_offset;
+        data.carry.silence();
         let key_data = KeyPlayingData {
             id,
             state,
@@ -177,13 +326,37 @@ impl<'ctx, S> CompiledKeyedInputQueue<'ctx, S> {
             },
         };
         data.state = QueuedKeyState::Playing(key_data);
+        data.level = 0.0;
+    }
+
+    /// The number of keys which are playing and have not been released
+    pub fn num_held_keys(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d.state,
+                    QueuedKeyState::Playing(KeyPlayingData {
+                        duration: KeyDuration::Forever,
+                        ..
+                    })
+                )
+            })
+            .count()
     }
 
     pub fn release_key(&mut self, id: usize) {
+        self.release_key_at(id, 0);
+    }
+
+    /// Release the key the given number of samples into the next chunk
+    pub fn release_key_at(&mut self, id: usize, sample_offset: usize) {
         for d in &mut self.items {
             if let QueuedKeyState::Playing(key_data) = &mut d.state {
                 if key_data.id == id {
-                    key_data.duration = KeyDuration::Samples(0);
+                    // The key's audio is delayed by its own start offset
+                    key_data.duration =
+                        KeyDuration::Samples(sample_offset.saturating_sub(d.start_offset));
                 }
             }
         }
@@ -203,54 +376,125 @@ impl<'ctx, S> CompiledKeyedInputQueue<'ctx, S> {
         context: &'a AudioContext<'a>,
         mut f: F,
     ) {
-        // TODO: allow per-key chunk sample offsets, store remaining chunk in state
-
         dst.silence();
         let mut temp_chunk = SoundChunk::new();
         for d in &mut self.items {
-            if let QueuedKeyState::Playing(key_data) = &mut d.state {
-                // TODO: allow keys to stack (after ignoring key repeats in keyboard_ui)
-                if let KeyDuration::Samples(s) = &mut key_data.duration {
-                    if *s < CHUNK_SIZE {
-                        d.node.timing_mut().request_release(*s);
-                        *s = 0;
-                    } else {
-                        *s -= CHUNK_SIZE;
-                    }
+            if let (QueuedKeyState::Playing(_), Some(voice)) = (&d.state, d.voice) {
+                if self.voice_limit.is_stolen(voice) {
+                    // Another key somewhere in the engine took its place
+                    d.state = QueuedKeyState::NotPlaying;
+                    d.voice = None;
+                    d.start_offset = 0;
+                    d.begin_release_fade(self.voice_limit.release_fade_samples());
                 }
+            }
+            match &mut d.state {
+                QueuedKeyState::Playing(key_data) => {
+                    // TODO: allow keys to stack (after ignoring key repeats in keyboard_ui)
+                    if let KeyDuration::Samples(s) = &mut key_data.duration {
+                        if *s < CHUNK_SIZE {
+                            d.node.timing_mut().request_release(*s);
+                            *s = 0;
+                        } else {
+                            *s -= CHUNK_SIZE;
+                        }
+                    }
 
-                d.node.step(
-                    &mut temp_chunk,
-                    f(&mut key_data.state, InputContext::new(context)),
-                );
+                    d.node.step(
+                        &mut temp_chunk,
+                        f(&mut key_data.state, InputContext::new(context)),
+                    );
 
-                key_data.age += 1;
-                if d.node.timing().is_done() {
-                    d.state = QueuedKeyState::NotPlaying;
-                }
+                    d.level = temp_chunk
+                        .l
+                        .iter()
+                        .chain(&temp_chunk.r)
+                        .fold(0.0, |peak, x| x.abs().max(peak));
+
+                    if let Some(voice) = d.voice {
+                        self.voice_limit.set_level(voice, d.level);
+                    }
+
+                    key_data.age += 1;
+                    if d.node.timing().is_done() {
+                        d.state = QueuedKeyState::NotPlaying;
+                        if let Some(voice) = d.voice.take() {
+                            self.voice_limit.end_voice(voice);
+                        }
+                    }
 
-                // TODO: how to make this adjustable?
-                slicemath::mul_scalar_inplace(&mut temp_chunk.l, 0.1);
-                slicemath::mul_scalar_inplace(&mut temp_chunk.r, 0.1);
-                slicemath::add_inplace(&mut dst.l, &temp_chunk.l);
-                slicemath::add_inplace(&mut dst.r, &temp_chunk.r);
+                    if d.start_offset > 0 {
+                        d.delay_by_start_offset(&mut temp_chunk);
+                    }
+                }
+                QueuedKeyState::NotPlaying => {
+                    if d.start_offset == 0 && d.release_fade.is_none() {
+                        continue;
+                    }
+                    // The key has finished but the end of its last
+                    // chunk was carried over and is still to be played,
+                    // or it was cut off and is still fading out
+                    temp_chunk.silence();
+                    if d.start_offset > 0 {
+                        d.delay_by_start_offset(&mut temp_chunk);
+                        d.start_offset = 0;
+                    }
+                }
             }
+
+            d.apply_release_fade(&mut temp_chunk);
+
+            // TODO: how to make this adjustable?
+            slicemath::mul_scalar_inplace(&mut temp_chunk.l, 0.1);
+            slicemath::mul_scalar_inplace(&mut temp_chunk.r, 0.1);
+            slicemath::add_inplace(&mut dst.l, &temp_chunk.l);
+            slicemath::add_inplace(&mut dst.r, &temp_chunk.r);
         }
     }
 }
 
-impl<'ctx, S> CompiledProcessorComponent for CompiledKeyedInputQueue<'ctx, S> {
+impl<'ctx, S: KeyNote> CompiledProcessorComponent for CompiledKeyedInputQueue<'ctx, S> {
     fn visit(&self, visitor: &mut dyn CompiledComponentVisitor) {
         for item in &self.items {
+            if let QueuedKeyState::Playing(key_data) = &item.state {
+                visitor.voice(VoiceReport::new(
+                    key_data.state.note(),
+                    key_data.age * CHUNK_SIZE,
+                    !matches!(key_data.duration, KeyDuration::Forever),
+                    item.level,
+                ));
+            }
             visitor.input_node(&item.node);
         }
     }
+
+    fn take_over(&mut self, previous: &mut Self) {
+        for (item, previous_item) in self.items.iter_mut().zip(previous.items.iter_mut()) {
+            item.node.take_over(&mut previous_item.node);
+        }
+    }
 }
 
 impl<'ctx, S> StartOver for CompiledKeyedInputQueue<'ctx, S> {
     fn start_over(&mut self) {
         for item in &mut self.items {
-            item.state = QueuedKeyState::NotPlaying
+            item.state = QueuedKeyState::NotPlaying;
+            item.start_offset = 0;
+            item.last_sample = (0.0, 0.0);
+            item.release_fade = None;
+            if let Some(voice) = item.voice.take() {
+                self.voice_limit.end_voice(voice);
+            }
+        }
+    }
+}
+
+impl<'ctx, S> Drop for CompiledKeyedInputQueue<'ctx, S> {
+    fn drop(&mut self) {
+        for item in &mut self.items {
+            if let Some(voice) = item.voice.take() {
+                self.voice_limit.end_voice(voice);
+            }
         }
     }
 }
@@ -7,7 +7,8 @@ use crate::core::{
     sound::{
         argument::ArgumentScope,
         soundinput::{
-            InputContext, ProcessorInput, SoundInputBackend, SoundInputCategory, SoundInputLocation,
+            DisconnectBehavior, InputContext, ProcessorInput, SoundInputBackend,
+            SoundInputCategory, SoundInputLocation,
         },
         soundprocessor::{
             CompiledComponentVisitor, CompiledProcessorComponent, SoundProcessorId, StartOver,
@@ -127,10 +128,19 @@ impl SoundInputBackend for ScheduledInputBackend {
         &self,
         location: SoundInputLocation,
         target: Option<SoundProcessorId>,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
         compiler: &mut SoundGraphCompiler<'_, 'ctx>,
     ) -> Self::CompiledType<'ctx> {
+        let compensation = compiler.latency_compensation(location);
         CompiledScheduledInput {
-            node: CompiledSoundInputNode::new(location, compiler.compile_sound_processor(target)),
+            node: CompiledSoundInputNode::new(
+                location,
+                speed,
+                disconnect_behavior,
+                compiler.compile_sound_processor(target),
+            )
+            .with_latency_compensation(compensation),
             schedule: self.schedule.clone(),
             scratch_buffer: SoundChunk::new(),
             scratch_offset: 0,
@@ -235,6 +245,10 @@ impl<'ctx> CompiledProcessorComponent for CompiledScheduledInput<'ctx> {
     fn visit(&self, visitor: &mut dyn CompiledComponentVisitor) {
         visitor.input_node(&self.node);
     }
+
+    fn take_over(&mut self, previous: &mut Self) {
+        self.node.take_over(&mut previous.node);
+    }
 }
 
 impl<'ctx> StartOver for CompiledScheduledInput<'ctx> {
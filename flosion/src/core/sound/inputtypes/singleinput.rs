@@ -7,8 +7,8 @@ use crate::core::{
     sound::{
         argument::ArgumentScope,
         soundinput::{
-            InputContext, InputTiming, ProcessorInput, SoundInputBackend, SoundInputCategory,
-            SoundInputLocation,
+            DisconnectBehavior, InputContext, InputTiming, ProcessorInput, SoundInputBackend,
+            SoundInputCategory, SoundInputLocation,
         },
         soundprocessor::{
             CompiledComponentVisitor, CompiledProcessorComponent, SoundProcessorId, StartOver,
@@ -37,12 +37,20 @@ impl SoundInputBackend for SingleInputBackend {
         &self,
         location: SoundInputLocation,
         target: Option<SoundProcessorId>,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
         compiler: &mut SoundGraphCompiler<'_, 'ctx>,
     ) -> Self::CompiledType<'ctx> {
-        CompiledSingleInput::new(CompiledSoundInputNode::new(
-            location,
-            compiler.compile_sound_processor(target),
-        ))
+        let compensation = compiler.latency_compensation(location);
+        CompiledSingleInput::new(
+            CompiledSoundInputNode::new(
+                location,
+                speed,
+                disconnect_behavior,
+                compiler.compile_sound_processor(target),
+            )
+            .with_latency_compensation(compensation),
+        )
     }
 }
 
@@ -100,6 +108,10 @@ impl<'ctx> CompiledProcessorComponent for CompiledSingleInput<'ctx> {
     fn visit(&self, visitor: &mut dyn CompiledComponentVisitor) {
         visitor.input_node(&self.node);
     }
+
+    fn take_over(&mut self, previous: &mut Self) {
+        self.node.take_over(&mut previous.node);
+    }
 }
 
 impl<'ctx> StartOver for CompiledSingleInput<'ctx> {
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use super::{
+    soundgraph::SoundGraph, soundinput::SoundInputLocation, soundprocessor::SoundProcessorId,
+};
+
+/// Find the total latency of every processor's output, in samples. This
+/// is the processor's own latency plus the greatest total latency among
+/// the processors connected to its inputs, since audio arriving through
+/// its other inputs is delayed to match.
+pub(crate) fn processor_latencies(graph: &SoundGraph) -> HashMap<SoundProcessorId, usize> {
+    fn visit(
+        graph: &SoundGraph,
+        processor_id: SoundProcessorId,
+        latencies: &mut HashMap<SoundProcessorId, usize>,
+    ) -> usize {
+        if let Some(latency) = latencies.get(&processor_id) {
+            return *latency;
+        }
+        let processor = graph.sound_processor(processor_id).unwrap();
+        let mut targets = Vec::new();
+        processor.foreach_input(|input, _| targets.extend(input.target()));
+        let input_latency = targets
+            .into_iter()
+            .map(|target| visit(graph, target, latencies))
+            .max()
+            .unwrap_or(0);
+        let latency = input_latency + processor.latency();
+        latencies.insert(processor_id, latency);
+        latency
+    }
+
+    let mut latencies = HashMap::new();
+    for processor_id in graph.sound_processors().keys() {
+        visit(graph, *processor_id, &mut latencies);
+    }
+    latencies
+}
+
+/// The number of samples by which audio arriving through the given input
+/// must be delayed to line up with that arriving through the other inputs
+/// of the same processor, given the total latencies of all processors
+pub(crate) fn latency_compensation(
+    graph: &SoundGraph,
+    latencies: &HashMap<SoundProcessorId, usize>,
+    location: SoundInputLocation,
+) -> usize {
+    let Some(processor) = graph.sound_processor(location.processor()) else {
+        return 0;
+    };
+    let mut greatest_latency = 0;
+    let mut own_latency = 0;
+    processor.foreach_input(|input, input_location| {
+        let latency = input
+            .target()
+            .and_then(|target| latencies.get(&target).cloned())
+            .unwrap_or(0);
+        greatest_latency = greatest_latency.max(latency);
+        if input_location == location {
+            own_latency = latency;
+        }
+    });
+    greatest_latency - own_latency
+}
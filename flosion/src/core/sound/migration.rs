@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use hashstash::{InplaceUnstasher, Stashable, UnstashError, UnstashableInplace};
+
+use crate::core::{
+    objecttype::WithObjectType,
+    stashing::{StashingContext, UnstashingContext},
+};
+
+use super::soundprocessor::{AnySoundProcessor, SoundProcessor};
+
+/// Reads the contents of a sound processor which were stashed under an
+/// older version of its object type into the current processor
+type Migration = Box<
+    dyn Fn(
+        &mut dyn AnySoundProcessor,
+        &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError>,
+>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MigrationError {
+    /// The object was stashed under an older version of its type, and
+    /// no migration from that version has been registered
+    NoMigrationPath {
+        object_type: &'static str,
+        stashed_version: u32,
+        current_version: u32,
+    },
+    /// The object was stashed under a newer version of its type than
+    /// this program knows about
+    NewerVersion {
+        object_type: &'static str,
+        stashed_version: u32,
+        current_version: u32,
+    },
+}
+
+impl MigrationError {
+    pub(crate) fn explain(&self) -> String {
+        match self {
+            MigrationError::NoMigrationPath {
+                object_type,
+                stashed_version,
+                current_version,
+            } => format!(
+                "A \"{}\" was saved at version {} but the current version is {}, \
+                and there is no way to upgrade it",
+                object_type, stashed_version, current_version
+            ),
+            MigrationError::NewerVersion {
+                object_type,
+                stashed_version,
+                current_version,
+            } => format!(
+                "A \"{}\" was saved at version {} by a newer program, but only \
+                versions up to {} are understood",
+                object_type, stashed_version, current_version
+            ),
+        }
+    }
+}
+
+/// Why a sound processor couldn't be unstashed
+#[derive(Debug, Eq, PartialEq)]
+pub enum ProcessorUnstashError {
+    /// The processor was stashed under a version of its type which
+    /// can't be migrated to the current version
+    Migration(MigrationError),
+    /// The processor's stashed contents couldn't be read
+    Unstash(UnstashError),
+}
+
+impl From<UnstashError> for ProcessorUnstashError {
+    fn from(error: UnstashError) -> ProcessorUnstashError {
+        ProcessorUnstashError::Unstash(error)
+    }
+}
+
+/// The migrations through which sound processors stashed under older
+/// versions of their object types are upgraded while being unstashed.
+/// Each migration reads one older stashed form directly into the current
+/// form of the processor, so that no intermediate versions are needed.
+pub struct MigrationRegistry {
+    migrations: HashMap<(&'static str, u32), Migration>,
+}
+
+impl MigrationRegistry {
+    pub(crate) fn new_empty() -> MigrationRegistry {
+        MigrationRegistry {
+            migrations: HashMap::new(),
+        }
+    }
+
+    /// Register a function which reads the contents of a processor of type
+    /// T stashed under the given older version of T's object type
+    pub fn register<T>(
+        &mut self,
+        from_version: u32,
+        migrate: fn(&mut T, &mut InplaceUnstasher<UnstashingContext>) -> Result<(), UnstashError>,
+    ) where
+        T: 'static
+            + SoundProcessor
+            + WithObjectType
+            + Stashable<StashingContext>
+            + for<'a> UnstashableInplace<UnstashingContext<'a>>,
+    {
+        debug_assert!(from_version < T::VERSION);
+        self.migrations.insert(
+            (T::TYPE.name(), from_version),
+            Box::new(move |processor, unstasher| {
+                processor
+                    .downcast_mut::<T>()
+                    .unwrap()
+                    .unstash_inplace_migrating(unstasher, migrate)
+            }),
+        );
+    }
+
+    /// Check whether an object of the given type, whose current version
+    /// is given, can be unstashed from the given older or equal version
+    pub(crate) fn check(
+        &self,
+        object_type: &'static str,
+        current_version: u32,
+        stashed_version: u32,
+    ) -> Result<(), MigrationError> {
+        if stashed_version > current_version {
+            Err(MigrationError::NewerVersion {
+                object_type,
+                stashed_version,
+                current_version,
+            })
+        } else if stashed_version < current_version
+            && !self
+                .migrations
+                .contains_key(&(object_type, stashed_version))
+        {
+            Err(MigrationError::NoMigrationPath {
+                object_type,
+                stashed_version,
+                current_version,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Unstash a processor which was stashed under the given version of
+    /// its object type, migrating it if that version is out of date
+    pub(crate) fn unstash_processor_inplace(
+        &self,
+        processor: &mut dyn AnySoundProcessor,
+        stashed_version: u32,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), ProcessorUnstashError> {
+        let object_type = processor.as_graph_object().get_dynamic_type().name();
+        let current_version = processor.as_graph_object().get_dynamic_version();
+        self.check(object_type, current_version, stashed_version)
+            .map_err(ProcessorUnstashError::Migration)?;
+        if stashed_version == current_version {
+            processor.unstash_inplace(unstasher)?;
+        } else {
+            let migrate = &self.migrations[&(object_type, stashed_version)];
+            migrate(processor, unstasher)?;
+        }
+        Ok(())
+    }
+}
@@ -1,15 +1,21 @@
 pub mod argument;
 pub mod argumenttypes;
 pub mod context;
+pub mod engineblocksize;
 pub mod expression;
 pub mod inputtypes;
+pub(crate) mod latency;
+pub mod migration;
+pub mod panicfade;
 pub mod sounderror;
 pub mod soundgraph;
+pub mod soundgraphdiff;
 pub mod soundgraphid;
 pub mod soundgraphproperties;
 pub(crate) mod soundgraphvalidation;
 pub mod soundinput;
 pub mod soundobject;
 pub mod soundprocessor;
+pub mod testtone;
 #[cfg(test)]
 mod test;
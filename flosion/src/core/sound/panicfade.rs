@@ -0,0 +1,87 @@
+use crate::core::{
+    engine::{soundengine::PanicSwitch, soundgraphcompiler::SoundGraphCompiler},
+    samplefrequency::SAMPLE_FREQUENCY,
+    soundchunk::SoundChunk,
+};
+
+use super::soundprocessor::{
+    CompiledComponentVisitor, CompiledProcessorComponent, ProcessorComponent,
+    ProcessorComponentVisitor, ProcessorComponentVisitorMut, SoundProcessorId, StartOver,
+};
+
+/// The number of samples over which audio fades out when the sound
+/// engine's panic switch is engaged, and back in when it's disengaged.
+/// This is a few milliseconds, which is short enough to be immediate
+/// but long enough to avoid a click.
+pub const PANIC_FADE_SAMPLES: usize = SAMPLE_FREQUENCY / 200;
+
+/// A processor component which responds to the sound engine's panic
+/// switch, which silences all output at once. Processors sending audio
+/// out of the sound graph use it to fade out after all other processing,
+/// and processors starting voices use it to stop doing so.
+pub struct PanicFade;
+
+impl ProcessorComponent for PanicFade {
+    type CompiledType<'ctx> = CompiledPanicFade;
+
+    fn visit(&self, _visitor: &mut dyn ProcessorComponentVisitor) {}
+
+    fn visit_mut(&mut self, _visitor: &mut dyn ProcessorComponentVisitorMut) {}
+
+    fn compile<'ctx>(
+        &self,
+        _processor_id: SoundProcessorId,
+        compiler: &mut SoundGraphCompiler<'_, 'ctx>,
+    ) -> CompiledPanicFade {
+        let switch = compiler.panic_switch().clone();
+        let level = if switch.is_engaged() {
+            0
+        } else {
+            PANIC_FADE_SAMPLES
+        };
+        CompiledPanicFade { switch, level }
+    }
+}
+
+pub struct CompiledPanicFade {
+    switch: PanicSwitch,
+
+    /// The number of samples into the fade, from zero when silent
+    /// up to PANIC_FADE_SAMPLES at full volume
+    level: usize,
+}
+
+impl CompiledPanicFade {
+    /// Whether the panic switch is engaged, in which case no
+    /// new voices should be started
+    pub fn is_engaged(&self) -> bool {
+        self.switch.is_engaged()
+    }
+
+    /// Apply the current gain to the chunk, ramping towards silence
+    /// if the panic switch is engaged and towards full volume if not
+    pub fn process(&mut self, chunk: &mut SoundChunk) {
+        let engaged = self.switch.is_engaged();
+        if !engaged && self.level == PANIC_FADE_SAMPLES {
+            return;
+        }
+        for (l, r) in chunk.l.iter_mut().zip(chunk.r.iter_mut()) {
+            if engaged {
+                self.level = self.level.saturating_sub(1);
+            } else {
+                self.level = (self.level + 1).min(PANIC_FADE_SAMPLES);
+            }
+            let gain = self.level as f32 / PANIC_FADE_SAMPLES as f32;
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+}
+
+impl CompiledProcessorComponent for CompiledPanicFade {
+    fn visit(&self, _visitor: &mut dyn CompiledComponentVisitor) {}
+}
+
+impl StartOver for CompiledPanicFade {
+    fn start_over(&mut self) {}
+}
@@ -7,8 +7,15 @@ use super::{
 pub enum SoundError {
     ProcessorNotFound(SoundProcessorId),
     SoundInputNotFound(SoundInputLocation),
-    CircularDependency,
+    /// The listed processors form a cycle, each one's input being
+    /// connected to the next and the last one's to the first
+    CircularDependency {
+        cycle: Vec<SoundProcessorId>,
+    },
     ConnectionNotIsochronic(SoundInputLocation),
+    /// The synchronous input has no branches and so never evaluates
+    /// the processor it is connected to
+    InputHasNoBranches(SoundInputLocation),
     StateNotInScope {
         bad_dependencies: Vec<(ProcessorArgumentLocation, ProcessorExpressionLocation)>,
     },
@@ -27,7 +34,17 @@ impl SoundError {
                     loc.processor().value()
                 )
             }
-            SoundError::CircularDependency => "The graph contains a cycle ".to_string(),
+            SoundError::CircularDependency { cycle } => {
+                let names: Vec<String> = cycle
+                    .iter()
+                    .chain(cycle.first())
+                    .map(|spid| match graph.sound_processor(*spid) {
+                        Some(proc) => proc.as_graph_object().friendly_name(),
+                        None => format!("#{}", spid.value()),
+                    })
+                    .collect();
+                format!("The sound graph contains a cycle: {}", names.join(" -> "))
+            }
             SoundError::ConnectionNotIsochronic(loc) => format!(
                 "Sound input #{} of processor {} is connected to a processor which is logically
                 static, but the input is not isochronic",
@@ -38,6 +55,25 @@ impl SoundError {
                     .as_graph_object()
                     .friendly_name()
             ),
+            SoundError::InputHasNoBranches(loc) => {
+                let target_name = graph
+                    .with_sound_input(*loc, |i| i.target())
+                    .flatten()
+                    .and_then(|spid| graph.sound_processor(spid))
+                    .map(|proc| proc.as_graph_object().friendly_name())
+                    .unwrap_or("the connected processor".to_string());
+                format!(
+                    "Sound input #{} of processor {} has no branches, so {} is never \
+                    evaluated and produces no sound",
+                    loc.input().value(),
+                    graph
+                        .sound_processor(loc.processor())
+                        .unwrap()
+                        .as_graph_object()
+                        .friendly_name(),
+                    target_name
+                )
+            }
             SoundError::StateNotInScope {
                 bad_dependencies: _,
             } => {
@@ -7,13 +7,18 @@ use hashstash::{
 
 use crate::{
     core::{
-        expression::expressionobject::ExpressionObjectFactory,
+        expression::{
+            expressionmacro::{ExpressionMacro, ExpressionMacroLibrary},
+            expressionobject::ExpressionObjectFactory,
+            macrocall::MacroCall,
+        },
         stashing::{StashingContext, UnstashingContext},
     },
     ui_core::arguments::ParsedArguments,
 };
 
 use super::{
+    migration::ProcessorUnstashError,
     sounderror::SoundError,
     soundgraphid::{SoundGraphComponentLocation, SoundObjectId},
     soundgraphvalidation::find_sound_error,
@@ -24,12 +29,23 @@ use super::{
 
 pub struct SoundGraph {
     sound_processors: HashMap<SoundProcessorId, Box<dyn AnySoundProcessor>>,
+
+    /// Processors which are soloed. While any processors are soloed,
+    /// only their subgraphs are heard, and all other processors are
+    /// silenced without being disconnected.
+    soloed_processors: HashSet<SoundProcessorId>,
+
+    /// Expression macros which may be used within any expression
+    /// in the graph
+    expression_macros: ExpressionMacroLibrary,
 }
 
 impl SoundGraph {
     pub fn new() -> SoundGraph {
         SoundGraph {
             sound_processors: HashMap::new(),
+            soloed_processors: HashSet::new(),
+            expression_macros: ExpressionMacroLibrary::new(),
         }
     }
 
@@ -48,6 +64,13 @@ impl SoundGraph {
         }
     }
 
+    /// Take ownership of every sound processor in the graph
+    pub(crate) fn into_sound_processors(
+        self,
+    ) -> HashMap<SoundProcessorId, Box<dyn AnySoundProcessor>> {
+        self.sound_processors
+    }
+
     pub(crate) fn sound_processor_mut(
         &mut self,
         id: SoundProcessorId,
@@ -104,10 +127,116 @@ impl SoundGraph {
         }
 
         self.sound_processors.remove(&processor_id);
+        self.soloed_processors.remove(&processor_id);
 
         Ok(())
     }
 
+    /// Check whether the given processor is soloed
+    pub fn is_soloed(&self, processor_id: SoundProcessorId) -> bool {
+        self.soloed_processors.contains(&processor_id)
+    }
+
+    /// Solo or unsolo the given processor. Soloing only changes which
+    /// processors are heard and leaves all connections in place.
+    pub fn set_soloed(&mut self, processor_id: SoundProcessorId, soloed: bool) {
+        debug_assert!(self.sound_processors.contains_key(&processor_id));
+        if soloed {
+            self.soloed_processors.insert(processor_id);
+        } else {
+            self.soloed_processors.remove(&processor_id);
+        }
+    }
+
+    /// The ids of soloed processors in a consistent order for stashing
+    fn stashed_solos(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self
+            .soloed_processors
+            .iter()
+            .map(|id| id.value() as u64)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn expression_macros(&self) -> &ExpressionMacroLibrary {
+        &self.expression_macros
+    }
+
+    /// Add an expression macro, replacing any existing macro with
+    /// the same name, and update all uses of it
+    pub fn define_expression_macro(&mut self, expr_macro: ExpressionMacro) {
+        self.expression_macros.define(expr_macro);
+        self.resolve_expression_macros();
+    }
+
+    /// Remove the expression macro with the given name. Any remaining
+    /// uses of it evaluate to zero until it is defined again.
+    pub fn remove_expression_macro(&mut self, name: &str) {
+        self.expression_macros.remove(name);
+        self.resolve_expression_macros();
+    }
+
+    /// Point every macro call in every expression at the current
+    /// definition of the macro it names
+    fn resolve_expression_macros(&mut self) {
+        let library = &self.expression_macros;
+        for proc in self.sound_processors.values_mut() {
+            proc.foreach_expression_mut(|expr, _| {
+                for node in expr.graph_mut().nodes_mut().values_mut() {
+                    if let Some(call) = node.downcast_mut::<MacroCall>() {
+                        let definition = library.get(call.name()).cloned();
+                        call.set_definition(definition);
+                    }
+                }
+            });
+        }
+    }
+
+    /// If any processors are soloed, find all processors which may
+    /// still be heard. These are the soloed processors themselves, the
+    /// processors they depend on, and the processors through which they
+    /// reach the output. Audio from any other processor is silenced.
+    /// If nothing is soloed, everything can be heard and None is returned.
+    pub(crate) fn audible_processors(&self) -> Option<HashSet<SoundProcessorId>> {
+        if self.soloed_processors.is_empty() {
+            return None;
+        }
+
+        let mut audible = HashSet::new();
+
+        // Everything upstream of a soloed processor
+        let mut to_visit: Vec<SoundProcessorId> = self.soloed_processors.iter().cloned().collect();
+        while let Some(id) = to_visit.pop() {
+            if !audible.insert(id) {
+                continue;
+            }
+            if let Some(proc) = self.sound_processor(id) {
+                proc.foreach_input(|input, _| {
+                    if let Some(target) = input.target() {
+                        to_visit.push(target);
+                    }
+                });
+            }
+        }
+
+        // Everything downstream of a soloed processor
+        let mut downstream = HashSet::new();
+        let mut to_visit: Vec<SoundProcessorId> = self.soloed_processors.iter().cloned().collect();
+        while let Some(id) = to_visit.pop() {
+            if !downstream.insert(id) {
+                continue;
+            }
+            for location in self.inputs_connected_to(id) {
+                to_visit.push(location.processor());
+            }
+        }
+
+        audible.extend(downstream);
+
+        Some(audible)
+    }
+
     /// Connect the given sound input to the given sound processor.
     /// Both the input and the processor must exist and the input
     /// must be unoccupied. No additional checks are performed.
@@ -258,11 +387,18 @@ impl Stashable<StashingContext> for SoundGraph {
                 // type name (needed for factory during unstashing)
                 stasher.string(processor.as_graph_object().get_dynamic_type().name());
 
+                // version (needed to migrate older stashed contents)
+                stasher.u32(processor.as_graph_object().get_dynamic_version());
+
                 // contents
                 stasher.object_proxy(|stasher| processor.stash(stasher));
             },
             Order::Unordered,
         );
+
+        stasher.array_of_u64_slice(&self.stashed_solos());
+
+        stasher.object(&self.expression_macros);
     }
 }
 
@@ -279,12 +415,45 @@ impl Stashable<()> for SoundGraph {
                 // type name (needed for factory during unstashing)
                 stasher.string(processor.as_graph_object().get_dynamic_type().name());
 
+                // version (needed to migrate older stashed contents)
+                stasher.u32(processor.as_graph_object().get_dynamic_version());
+
                 // contents
                 stasher.object_proxy(|stasher| processor.stash(stasher));
             },
             Order::Unordered,
             StashingContext::new_stashing_normally(),
         );
+
+        stasher.array_of_u64_slice(&self.stashed_solos());
+
+        stasher.object_with_context(
+            &self.expression_macros,
+            StashingContext::new_stashing_normally(),
+        );
+    }
+}
+
+/// Unstash the contents of a sound processor which were stashed under the
+/// given version of its object type, migrating them if needed. If they
+/// can't be migrated, the reason is reported through the context.
+fn unstash_processor_contents(
+    processor: &mut dyn AnySoundProcessor,
+    version: u32,
+    unstasher: &mut InplaceUnstasher<UnstashingContext>,
+) -> Result<(), UnstashError> {
+    let context = unstasher.context();
+    match context
+        .sound_object_factory()
+        .migrations()
+        .unstash_processor_inplace(processor, version, unstasher)
+    {
+        Ok(()) => Ok(()),
+        Err(ProcessorUnstashError::Migration(err)) => {
+            context.report_migration_error(err);
+            Err(UnstashError::Corrupted)
+        }
+        Err(ProcessorUnstashError::Unstash(err)) => Err(err),
     }
 }
 
@@ -298,15 +467,20 @@ impl Unstashable<UnstashingContext<'_>> for SoundGraph {
             // type name
             let type_name = unstasher.string()?;
 
-            let mut processor = unstasher
-                .context()
-                .sound_object_factory()
+            // version
+            let version = unstasher.u32()?;
+
+            let factory = unstasher.context().sound_object_factory();
+
+            let mut processor = factory
                 .create(&type_name, &ParsedArguments::new_empty())
                 .into_boxed_sound_processor()
                 .unwrap();
 
             // contents
-            unstasher.object_proxy_inplace(|unstasher| processor.unstash_inplace(unstasher))?;
+            unstasher.object_proxy_inplace(|unstasher| {
+                unstash_processor_contents(&mut *processor, version, unstasher)
+            })?;
 
             debug_assert_eq!(processor.id(), id);
 
@@ -315,6 +489,14 @@ impl Unstashable<UnstashingContext<'_>> for SoundGraph {
             Ok(())
         })?;
 
+        graph.soloed_processors = unstasher
+            .array_of_u64_iter()?
+            .map(|id| SoundProcessorId::new(id as _))
+            .collect();
+
+        graph.expression_macros = unstasher.object()?;
+        graph.resolve_expression_macros();
+
         Ok(graph)
     }
 }
@@ -336,19 +518,25 @@ impl UnstashableInplace<UnstashingContext<'_>> for SoundGraph {
             // type name
             let type_name = unstasher.string()?;
 
+            // version
+            let version = unstasher.u32()?;
+
+            let factory = unstasher.context().sound_object_factory();
+
             if let Some(existing_proc) = self.sound_processor_mut(id) {
-                unstasher
-                    .object_proxy_inplace(|unstasher| existing_proc.unstash_inplace(unstasher))?;
+                unstasher.object_proxy_inplace(|unstasher| {
+                    unstash_processor_contents(existing_proc, version, unstasher)
+                })?;
             } else {
-                let mut proc = unstasher
-                    .context()
-                    .sound_object_factory()
+                let mut proc = factory
                     .create(&type_name, &ParsedArguments::new_empty())
                     .into_boxed_sound_processor()
                     .unwrap();
 
                 // contents
-                unstasher.object_proxy_inplace(|unstasher| proc.unstash_inplace(unstasher))?;
+                unstasher.object_proxy_inplace(|unstasher| {
+                    unstash_processor_contents(&mut *proc, version, unstasher)
+                })?;
 
                 if time_to_write {
                     self.add_sound_processor(proc);
@@ -360,10 +548,21 @@ impl UnstashableInplace<UnstashingContext<'_>> for SoundGraph {
             Ok(())
         })?;
 
+        let soloed_processors: HashSet<SoundProcessorId> = unstasher
+            .array_of_u64_iter()?
+            .map(|id| SoundProcessorId::new(id as _))
+            .collect();
+
+        unstasher.object_replace(&mut self.expression_macros)?;
+
         if time_to_write {
             // remove processors which were not stashed
             self.sound_processors
                 .retain(|id, _| procs_to_keep.contains(id));
+
+            self.soloed_processors = soloed_processors;
+
+            self.resolve_expression_macros();
         }
 
         Ok(())
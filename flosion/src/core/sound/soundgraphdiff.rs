@@ -0,0 +1,155 @@
+use std::{collections::HashMap, hash::Hash};
+
+use hashstash::{ObjectHash, Stash, StashHandle, UnstashError};
+
+use crate::core::stashing::{StashingContext, UnstashingContext};
+
+use super::{
+    expression::ProcessorExpressionLocation, soundgraph::SoundGraph,
+    soundinput::SoundInputLocation, soundprocessor::SoundProcessorId,
+};
+
+/// The revision of every processor, input, and expression in a sound
+/// graph at one moment. Revisions are cheap to keep around and can be
+/// compared against those of a later (or earlier) state of the graph
+/// to find out what changed in between.
+pub struct SoundGraphRevisions {
+    processors: HashMap<SoundProcessorId, ObjectHash>,
+    inputs: HashMap<SoundInputLocation, ObjectHash>,
+    expressions: HashMap<ProcessorExpressionLocation, ObjectHash>,
+}
+
+impl SoundGraphRevisions {
+    pub fn new(graph: &SoundGraph) -> SoundGraphRevisions {
+        let mut processors = HashMap::new();
+        let mut inputs = HashMap::new();
+        let mut expressions = HashMap::new();
+
+        for proc in graph.sound_processors().values() {
+            // A processor's revision covers all of its state, including
+            // that of its inputs and expressions
+            processors.insert(
+                proc.id(),
+                ObjectHash::with_stasher_and_context(
+                    |stasher| proc.stash(stasher),
+                    StashingContext::new_stashing_normally(),
+                ),
+            );
+
+            proc.foreach_input(|input, location| {
+                inputs.insert(
+                    location,
+                    ObjectHash::with_stasher(|stasher| {
+                        match input.target() {
+                            Some(target) => {
+                                stasher.u8(1);
+                                stasher.u64(target.value() as _);
+                            }
+                            None => stasher.u8(0),
+                        }
+                        stasher.f32(input.speed());
+                        stasher.u8(input.disconnect_behavior().to_u8());
+                    }),
+                );
+            });
+
+            proc.foreach_expression(|expr, location| {
+                expressions.insert(
+                    location,
+                    ObjectHash::from_stashable_and_context(
+                        expr,
+                        StashingContext::new_stashing_normally(),
+                    ),
+                );
+            });
+        }
+
+        SoundGraphRevisions {
+            processors,
+            inputs,
+            expressions,
+        }
+    }
+
+    /// Find the revisions of a previously stashed sound graph
+    pub fn from_snapshot(
+        stash: &Stash,
+        snapshot: &StashHandle<SoundGraph>,
+        context: UnstashingContext,
+    ) -> Result<SoundGraphRevisions, UnstashError> {
+        let graph: SoundGraph = stash.unstash_with_context(snapshot, context)?;
+        Ok(SoundGraphRevisions::new(&graph))
+    }
+
+    /// Find everything that differs in `newer` compared to `self`
+    pub fn diff(&self, newer: &SoundGraphRevisions) -> SoundGraphDiff {
+        SoundGraphDiff {
+            processors: ElementChanges::between(&self.processors, &newer.processors),
+            inputs: ElementChanges::between(&self.inputs, &newer.inputs),
+            expressions: ElementChanges::between(&self.expressions, &newer.expressions),
+        }
+    }
+}
+
+/// Elements of a single kind which were added, removed, or modified
+/// between two states of a sound graph
+pub struct ElementChanges<I> {
+    pub added: Vec<I>,
+    pub removed: Vec<I>,
+    pub modified: Vec<I>,
+}
+
+impl<I: Copy + Eq + Hash> ElementChanges<I> {
+    fn between(old: &HashMap<I, ObjectHash>, new: &HashMap<I, ObjectHash>) -> ElementChanges<I> {
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let mut modified = Vec::new();
+
+        for (id, new_revision) in new {
+            match old.get(id) {
+                Some(old_revision) => {
+                    if old_revision != new_revision {
+                        modified.push(*id);
+                    }
+                }
+                None => added.push(*id),
+            }
+        }
+
+        for id in old.keys() {
+            if !new.contains_key(id) {
+                removed.push(*id);
+            }
+        }
+
+        ElementChanges {
+            added,
+            removed,
+            modified,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Everything that differs between two states of a sound graph.
+/// Changes to an input or expression also count as a modification
+/// of the processor that owns it.
+pub struct SoundGraphDiff {
+    pub processors: ElementChanges<SoundProcessorId>,
+    pub inputs: ElementChanges<SoundInputLocation>,
+    pub expressions: ElementChanges<ProcessorExpressionLocation>,
+}
+
+impl SoundGraphDiff {
+    /// Compare two sound graphs directly
+    pub fn between(old: &SoundGraph, new: &SoundGraph) -> SoundGraphDiff {
+        SoundGraphRevisions::new(old).diff(&SoundGraphRevisions::new(new))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty() && self.inputs.is_empty() && self.expressions.is_empty()
+    }
+}
@@ -2,12 +2,13 @@ use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use super::{
     argument::ProcessorArgumentLocation, expression::ProcessorExpressionLocation,
-    sounderror::SoundError, soundgraph::SoundGraph, soundprocessor::SoundProcessorId,
+    sounderror::SoundError, soundgraph::SoundGraph, soundinput::SoundInputCategory,
+    soundprocessor::SoundProcessorId,
 };
 
 pub(super) fn find_sound_error(graph: &SoundGraph) -> Option<SoundError> {
-    if find_sound_cycle(graph) {
-        return Some(SoundError::CircularDependency);
+    if let Some(cycle) = find_sound_cycle(graph) {
+        return Some(SoundError::CircularDependency { cycle });
     }
     if let Some(err) = validate_sound_connections(graph) {
         return Some(err);
@@ -19,16 +20,23 @@ pub(super) fn find_sound_error(graph: &SoundGraph) -> Option<SoundError> {
     None
 }
 
-pub(super) fn find_sound_cycle(graph: &SoundGraph) -> bool {
+/// Find a cycle of processors in the graph, if there is one. The
+/// processors are listed in the order in which each one's input is
+/// connected to the next, and the last one's input is connected to
+/// the first.
+pub(super) fn find_sound_cycle(graph: &SoundGraph) -> Option<Vec<SoundProcessorId>> {
     fn find_cycle(
         proc_id: SoundProcessorId,
         current_path: &mut Vec<SoundProcessorId>,
         all_visited_procs: &mut HashSet<SoundProcessorId>,
-        found_a_cyle: &mut bool,
+        found_cycle: &mut Option<Vec<SoundProcessorId>>,
         graph: &SoundGraph,
     ) {
-        if current_path.contains(&proc_id) {
-            *found_a_cyle = true;
+        if found_cycle.is_some() {
+            return;
+        }
+        if let Some(i) = current_path.iter().position(|p| *p == proc_id) {
+            *found_cycle = Some(current_path[i..].to_vec());
             return;
         }
         if all_visited_procs.contains(&proc_id) {
@@ -45,7 +53,7 @@ pub(super) fn find_sound_cycle(graph: &SoundGraph) -> bool {
                         target_id,
                         current_path,
                         all_visited_procs,
-                        found_a_cyle,
+                        found_cycle,
                         graph,
                     );
                     current_path.pop();
@@ -56,25 +64,22 @@ pub(super) fn find_sound_cycle(graph: &SoundGraph) -> bool {
     let mut visited_procs: HashSet<SoundProcessorId> = HashSet::new();
 
     loop {
-        let Some(proc_to_visit) = graph
+        let proc_to_visit = graph
             .sound_processors()
             .keys()
             .find(|pid| !visited_procs.contains(&pid))
-            .cloned()
-        else {
-            return false;
-        };
+            .cloned()?;
         let mut path = Vec::new();
-        let mut found_a_cycle = false;
+        let mut found_cycle = None;
         find_cycle(
             proc_to_visit,
             &mut path,
             &mut visited_procs,
-            &mut found_a_cycle,
+            &mut found_cycle,
             graph,
         );
-        if found_a_cycle {
-            return true;
+        if found_cycle.is_some() {
+            return found_cycle;
         }
         visited_procs.insert(proc_to_visit);
     }
@@ -233,6 +238,40 @@ pub(super) fn validate_sound_connections(graph: &SoundGraph) -> Option<SoundErro
     None
 }
 
+/// Find synchronous sound inputs, i.e. those which are isochronic or
+/// branched and which call on their target processor once per branch
+/// in lockstep, whose branch count is inconsistent with the target.
+/// An input with no branches never evaluates the dynamic processor it
+/// is connected to, which then silently produces no sound. Unlike the
+/// errors of find_sound_error, these are allowed to exist in the graph,
+/// since e.g. an ensemble may be given zero voices while being edited.
+pub(crate) fn find_branch_count_mismatches(graph: &SoundGraph) -> Vec<SoundError> {
+    let mut mismatches = Vec::new();
+
+    for proc in graph.sound_processors().values() {
+        proc.foreach_input(|input, location| {
+            let Some(target) = input.target() else {
+                return;
+            };
+            let branches = match input.category() {
+                SoundInputCategory::Isochronic => 1,
+                SoundInputCategory::Branched(n) => n,
+                SoundInputCategory::Anisochronic | SoundInputCategory::Scheduled => return,
+            };
+            // Connections to static processors are already required
+            // to be isochronic
+            if graph.sound_processor(target).unwrap().is_static() {
+                return;
+            }
+            if branches == 0 {
+                mismatches.push(SoundError::InputHasNoBranches(location));
+            }
+        });
+    }
+
+    mismatches
+}
+
 pub(super) fn find_invalid_expression_arguments(
     graph: &SoundGraph,
 ) -> Vec<(ProcessorArgumentLocation, ProcessorExpressionLocation)> {
@@ -131,6 +131,37 @@ impl<'a> Unstashable<UnstashingContext<'a>> for SoundInputCategory {
     }
 }
 
+/// What a sound input produces once the processor it was connected
+/// to is disconnected while audio is playing
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DisconnectBehavior {
+    /// Immediately produce silence
+    Silence,
+
+    /// Play back the last chunk that was produced in reverse while
+    /// fading it out, so that the audio continues smoothly from
+    /// where it left off and then dies away. Reversing the chunk
+    /// means that its first sample is exactly the last one heard.
+    HoldAndFade,
+}
+
+impl DisconnectBehavior {
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            DisconnectBehavior::Silence => 0,
+            DisconnectBehavior::HoldAndFade => 1,
+        }
+    }
+
+    pub(crate) fn from_u8(x: u8) -> Option<DisconnectBehavior> {
+        match x {
+            0 => Some(DisconnectBehavior::Silence),
+            1 => Some(DisconnectBehavior::HoldAndFade),
+            _ => None,
+        }
+    }
+}
+
 // TODO: this state should probably only be stored for those inputs
 // that actually care about releases. The audio stack should allow
 // such events to be passed through multiple processors without individual
@@ -278,24 +309,33 @@ pub trait SoundInputBackend {
         &self,
         location: SoundInputLocation,
         target: Option<SoundProcessorId>,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
         compiler: &mut SoundGraphCompiler<'_, 'ctx>,
     ) -> Self::CompiledType<'ctx>;
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(PartialEq, Debug)]
 pub struct ProcessorInput<T> {
     id: ProcessorInputId,
     target: Option<SoundProcessorId>,
     argument_scope: ArgumentScope,
+    speed: f32,
+    disconnect_behavior: DisconnectBehavior,
     backend: T,
 }
 
+// The speed is always finite and positive, so it's never NaN
+impl<T: Eq> Eq for ProcessorInput<T> {}
+
 impl<T> ProcessorInput<T> {
     pub fn new_from_parts(argument_scope: ArgumentScope, backend: T) -> ProcessorInput<T> {
         ProcessorInput {
             id: ProcessorInputId::new_unique(),
             target: None,
             argument_scope,
+            speed: 1.0,
+            disconnect_behavior: DisconnectBehavior::Silence,
             backend,
         }
     }
@@ -303,11 +343,26 @@ impl<T> ProcessorInput<T> {
 
 pub trait AnyProcessorInput {
     fn id(&self) -> ProcessorInputId;
+    fn set_id(&mut self, id: ProcessorInputId);
 
     fn target(&self) -> Option<SoundProcessorId>;
     fn set_target(&mut self, target: Option<SoundProcessorId>);
 
     fn argument_scope(&self) -> &ArgumentScope;
+    fn argument_scope_mut(&mut self) -> &mut ArgumentScope;
+
+    /// Multiplier on the rate at which time passes for the processor
+    /// owning this input and for those further down the audio stack,
+    /// as seen by the expressions of processors connected to the input.
+    /// For example, a speed of 0.5 makes that time advance at half its
+    /// usual rate, for slow-motion effects.
+    fn speed(&self) -> f32;
+    fn set_speed(&mut self, speed: f32);
+
+    /// What the input produces when its target is disconnected
+    /// while audio is playing
+    fn disconnect_behavior(&self) -> DisconnectBehavior;
+    fn set_disconnect_behavior(&mut self, behavior: DisconnectBehavior);
 
     fn category(&self) -> SoundInputCategory;
 }
@@ -317,6 +372,10 @@ impl<T: SoundInputBackend> AnyProcessorInput for ProcessorInput<T> {
         self.id
     }
 
+    fn set_id(&mut self, id: ProcessorInputId) {
+        self.id = id;
+    }
+
     fn target(&self) -> Option<SoundProcessorId> {
         self.target
     }
@@ -329,6 +388,27 @@ impl<T: SoundInputBackend> AnyProcessorInput for ProcessorInput<T> {
         &self.argument_scope
     }
 
+    fn argument_scope_mut(&mut self) -> &mut ArgumentScope {
+        &mut self.argument_scope
+    }
+
+    fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        assert!(speed.is_finite() && speed > 0.0);
+        self.speed = speed;
+    }
+
+    fn disconnect_behavior(&self) -> DisconnectBehavior {
+        self.disconnect_behavior
+    }
+
+    fn set_disconnect_behavior(&mut self, behavior: DisconnectBehavior) {
+        self.disconnect_behavior = behavior;
+    }
+
     fn category(&self) -> SoundInputCategory {
         self.backend.category()
     }
@@ -353,6 +433,8 @@ impl<T: SoundInputBackend> ProcessorComponent for ProcessorInput<T> {
         self.backend.compile(
             SoundInputLocation::new(processor_id, self.id),
             self.target,
+            self.speed,
+            self.disconnect_behavior,
             compiler,
         )
     }
@@ -383,6 +465,8 @@ impl<T: Stashable<StashingContext>> Stashable<StashingContext> for ProcessorInpu
             }
         }
         stasher.object(&self.argument_scope);
+        stasher.f32(self.speed);
+        stasher.u8(self.disconnect_behavior.to_u8());
         stasher.object(&self.backend);
     }
 }
@@ -398,11 +482,19 @@ impl<'a, T: 'static + Unstashable<UnstashingContext<'a>>> Unstashable<Unstashing
             _ => panic!(),
         };
         let argument_scope = unstasher.object()?;
+        let speed = unstasher.f32()?;
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(UnstashError::Corrupted);
+        }
+        let disconnect_behavior =
+            DisconnectBehavior::from_u8(unstasher.u8()?).ok_or(UnstashError::Corrupted)?;
         let backend = unstasher.object()?;
         Ok(ProcessorInput {
             id,
             target,
             argument_scope,
+            speed,
+            disconnect_behavior,
             backend,
         })
     }
@@ -428,6 +520,18 @@ impl<'a, T: 'static + UnstashableInplace<UnstashingContext<'a>>>
         }
 
         unstasher.object_inplace(&mut self.argument_scope)?;
+        let speed = unstasher.f32_always()?;
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(UnstashError::Corrupted);
+        }
+        if unstasher.time_to_write() {
+            self.speed = speed;
+        }
+        let disconnect_behavior =
+            DisconnectBehavior::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.disconnect_behavior = disconnect_behavior;
+        }
         unstasher.object_inplace(&mut self.backend)?;
 
         Ok(())
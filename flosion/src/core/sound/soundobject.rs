@@ -2,7 +2,9 @@ use std::{any::Any, collections::HashMap};
 
 use crate::{core::objecttype::ObjectType, ui_core::arguments::ParsedArguments};
 
-use super::{soundgraphid::SoundObjectId, soundprocessor::AnySoundProcessor};
+use super::{
+    migration::MigrationRegistry, soundgraphid::SoundObjectId, soundprocessor::AnySoundProcessor,
+};
 
 pub trait SoundGraphObject {
     fn create<'a>(args: &ParsedArguments) -> Self
@@ -17,6 +19,9 @@ pub trait SoundGraphObject {
 
     fn get_dynamic_type(&self) -> ObjectType;
 
+    /// The version of the object's stashed form, see `WithObjectType::VERSION`
+    fn get_dynamic_version(&self) -> u32;
+
     fn friendly_name(&self) -> String;
 
     fn as_sound_processor(&self) -> Option<&dyn AnySoundProcessor>;
@@ -36,12 +41,14 @@ struct SoundObjectCreator {
 
 pub struct SoundObjectFactory {
     mapping: HashMap<&'static str, SoundObjectCreator>,
+    migrations: MigrationRegistry,
 }
 
 impl SoundObjectFactory {
     pub fn new_empty() -> SoundObjectFactory {
         SoundObjectFactory {
             mapping: HashMap::new(),
+            migrations: MigrationRegistry::new_empty(),
         }
     }
 
@@ -54,6 +61,15 @@ impl SoundObjectFactory {
         );
     }
 
+    /// The migrations for upgrading objects stashed under older versions
+    pub(crate) fn migrations(&self) -> &MigrationRegistry {
+        &self.migrations
+    }
+
+    pub fn migrations_mut(&mut self) -> &mut MigrationRegistry {
+        &mut self.migrations
+    }
+
     pub(crate) fn create(
         &self,
         object_type_str: &str,
@@ -15,6 +15,7 @@ use crate::{
             compiledprocessor::{
                 AnyCompiledProcessorData, CompiledProcessorData, CompiledSoundInputNode,
             },
+            soundenginereport::VoiceReport,
             soundgraphcompiler::SoundGraphCompiler,
         },
         objecttype::{ObjectType, WithObjectType},
@@ -111,6 +112,12 @@ impl<T: CompiledProcessorComponent> CompiledProcessorComponent for Vec<T> {
             item.visit(visitor);
         }
     }
+
+    fn take_over(&mut self, previous: &mut Self) {
+        for (item, previous_item) in self.iter_mut().zip(previous.iter_mut()) {
+            item.take_over(previous_item);
+        }
+    }
 }
 
 pub trait ProcessorComponentVisitor {
@@ -128,11 +135,23 @@ pub trait ProcessorComponentVisitorMut {
 pub trait CompiledProcessorComponent: StartOver {
     fn visit(&self, visitor: &mut dyn CompiledComponentVisitor);
 
+    /// Take over from the component which this one replaces after the
+    /// graph was edited, moving across whatever state must carry on.
+    /// This is called on the audio thread and must not block or allocate.
+    fn take_over(&mut self, _previous: &mut Self)
+    where
+        Self: Sized,
+    {
+    }
+
     // fn update(&mut self, type_erased_preallocated_data: ()); ???
 }
 
 pub trait CompiledComponentVisitor {
     fn input_node(&mut self, _input: &CompiledSoundInputNode);
+
+    /// Describe one playing voice of a polyphonic processor
+    fn voice(&mut self, _voice: VoiceReport) {}
 }
 
 impl CompiledProcessorComponent for () {
@@ -143,6 +162,11 @@ pub trait ProcessorState: Send {
     type Processor: SoundProcessor;
 
     fn new(processor: &Self::Processor) -> Self;
+
+    /// Take over from the state of the compiled processor which this
+    /// one's replaces, see `CompiledProcessorComponent::take_over`.
+    /// By default, nothing carries on.
+    fn take_over(&mut self, _previous: &mut Self) {}
 }
 
 pub struct StateMarker<T: ProcessorState> {
@@ -164,6 +188,13 @@ pub trait SoundProcessor: ProcessorComponent {
 
     fn is_static(&self) -> bool;
 
+    /// The number of samples by which the processor delays the audio
+    /// passing through it, e.g. due to look-ahead or block processing.
+    /// Parallel paths through other processors are delayed to match.
+    fn latency(&self) -> usize {
+        0
+    }
+
     fn process_audio(
         processor: &mut Self::CompiledType<'_>,
         dst: &mut SoundChunk,
@@ -194,6 +225,29 @@ impl<T: SoundProcessor> SoundProcessorWithId<T> {
     pub fn id(&self) -> SoundProcessorId {
         self.id
     }
+
+    /// Unstash the processor, whose contents were stashed in an older
+    /// form and are read by the given migration function instead of
+    /// the processor's own unstashing
+    pub(crate) fn unstash_inplace_migrating<'a>(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+        migrate: fn(
+            &mut T,
+            &mut InplaceUnstasher<UnstashingContext<'a>>,
+        ) -> Result<(), UnstashError>,
+    ) -> Result<(), UnstashError> {
+        // id
+        let id = SoundProcessorId::new(unstasher.u64_always()? as _);
+        if unstasher.time_to_write() {
+            self.id = id;
+        }
+
+        // contents
+        unstasher.object_proxy_inplace(|unstasher| migrate(&mut self.processor, unstasher))?;
+
+        Ok(())
+    }
 }
 
 impl<T: SoundProcessor> Deref for SoundProcessorWithId<T> {
@@ -212,13 +266,17 @@ impl<T: SoundProcessor> DerefMut for SoundProcessorWithId<T> {
 
 impl<T: SoundProcessor + WithObjectType> WithObjectType for SoundProcessorWithId<T> {
     const TYPE: ObjectType = T::TYPE;
+    const VERSION: u32 = T::VERSION;
 }
 
 pub trait AnySoundProcessor {
     fn id(&self) -> SoundProcessorId;
+    fn set_id(&mut self, id: SoundProcessorId);
 
     fn is_static(&self) -> bool;
 
+    fn latency(&self) -> usize;
+
     fn as_graph_object(&self) -> &dyn SoundGraphObject;
     fn as_graph_object_mut(&mut self) -> &mut dyn SoundGraphObject;
 
@@ -252,6 +310,10 @@ where
         self.id
     }
 
+    fn set_id(&mut self, id: SoundProcessorId) {
+        self.id = id;
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -263,6 +325,10 @@ where
         T::is_static(&self.processor)
     }
 
+    fn latency(&self) -> usize {
+        T::latency(&self.processor)
+    }
+
     fn as_graph_object(&self) -> &dyn SoundGraphObject {
         self
     }
@@ -556,6 +622,32 @@ impl<'a> dyn AnySoundProcessor + 'a {
         });
     }
 
+    pub(crate) fn foreach_expression_mut<
+        F: FnMut(&mut ProcessorExpression, ProcessorExpressionLocation),
+    >(
+        &mut self,
+        f: F,
+    ) {
+        struct Visitor<F2> {
+            processor_id: SoundProcessorId,
+            f: F2,
+        }
+
+        impl<F2: FnMut(&mut ProcessorExpression, ProcessorExpressionLocation)>
+            ProcessorComponentVisitorMut for Visitor<F2>
+        {
+            fn expression(&mut self, expression: &mut ProcessorExpression) {
+                let location = ProcessorExpressionLocation::new(self.processor_id, expression.id());
+                (self.f)(expression, location)
+            }
+        }
+
+        self.visit_mut(&mut Visitor {
+            processor_id: self.id(),
+            f,
+        });
+    }
+
     pub(crate) fn foreach_argument<
         F: FnMut(&dyn AnyProcessorArgument, ProcessorArgumentLocation),
     >(
@@ -641,6 +733,10 @@ where
         T::TYPE
     }
 
+    fn get_dynamic_version(&self) -> u32 {
+        T::VERSION
+    }
+
     fn as_sound_processor(&self) -> Option<&dyn AnySoundProcessor> {
         Some(self)
     }
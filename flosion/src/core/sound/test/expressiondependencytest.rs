@@ -0,0 +1,99 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::Add,
+};
+
+fn dependencies(expr: &ProcessorExpression) -> Vec<ExpressionParameterTarget> {
+    expr.mapping().dependencies(expr.graph())
+}
+
+#[test]
+fn test_time_only_expression_has_no_argument_dependencies() {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let time_param = expr.add_target(ExpressionParameterTarget::ProcessorTime(proc_id));
+
+    // The argument is mapped to a parameter but nothing reads from it
+    expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(time_param),
+        )
+        .unwrap();
+
+    assert_eq!(
+        dependencies(&expr),
+        vec![ExpressionParameterTarget::ProcessorTime(proc_id)]
+    );
+    assert!(!dependencies(&expr).contains(&ExpressionParameterTarget::Argument(argument_location)));
+}
+
+#[test]
+fn test_dependencies_through_nodes() {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let time_param = expr.add_target(ExpressionParameterTarget::ProcessorTime(proc_id));
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let node = ExpressionNodeWithId::<Add>::new_default();
+    let node_id = node.id();
+    let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(node));
+
+    graph
+        .connect_input(
+            input_locations[0],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+
+    // Nothing is connected to the result yet
+    assert!(dependencies(&expr).is_empty());
+
+    let graph = expr.graph_mut();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+        .unwrap();
+
+    assert_eq!(
+        dependencies(&expr),
+        vec![ExpressionParameterTarget::Argument(argument_location)]
+    );
+
+    let graph = expr.graph_mut();
+    graph
+        .connect_input(
+            input_locations[1],
+            Some(ExpressionTarget::Parameter(time_param)),
+        )
+        .unwrap();
+
+    let dependencies = dependencies(&expr);
+    assert_eq!(dependencies.len(), 2);
+    assert!(dependencies.contains(&ExpressionParameterTarget::ProcessorTime(proc_id)));
+    assert!(dependencies.contains(&ExpressionParameterTarget::Argument(argument_location)));
+}
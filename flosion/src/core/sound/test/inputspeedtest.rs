@@ -0,0 +1,106 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        expression::expressiongraph::ExpressionTarget,
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            context::{AudioContext, AudioStack},
+            expression::ExpressionParameterTarget,
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::{
+        stereowidth::StereoWidth,
+        wavegenerator::{WaveGenerator, WaveGeneratorWaveform},
+    },
+};
+
+/// Connect a wave generator to a stereo width processor's input
+/// with the given speed, and make the wave generator output the
+/// stereo width processor's time. Returns the first chunk of output.
+fn render_time_through_input(speed: f32) -> SoundChunk {
+    let mut stereo_width = SoundProcessorWithId::<StereoWidth>::new_default();
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    wavegen.set_waveform(WaveGeneratorWaveform::Expression);
+
+    let stereo_width_id = stereo_width.id();
+    let wavegen_id = wavegen.id();
+
+    stereo_width.input.set_speed(speed);
+
+    let time_param = wavegen
+        .amplitude
+        .add_target(ExpressionParameterTarget::ProcessorTime(stereo_width_id));
+    let graph = wavegen.amplitude.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(time_param),
+        )
+        .unwrap();
+
+    let input_location = SoundInputLocation::new(stereo_width_id, stereo_width.input.id());
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(stereo_width));
+    graph.add_sound_processor(Box::new(wavegen));
+    graph
+        .connect_sound_input(input_location, wavegen_id)
+        .unwrap();
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let stereo_width = graph
+        .sound_processor(stereo_width_id)
+        .unwrap()
+        .downcast::<StereoWidth>()
+        .unwrap();
+
+    let mut compiled = stereo_width.compile(stereo_width_id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let processor_timing = ProcessorTiming::new();
+
+    let mut context = AudioContext::new(
+        stereo_width_id,
+        &processor_timing,
+        &scratch_arena,
+        argument_stack.view_at_bottom(),
+        AudioStack::Root,
+    );
+    let mut chunk = SoundChunk::new();
+    StereoWidth::process_audio(&mut compiled, &mut chunk, &mut context);
+    chunk
+}
+
+/// The average amount by which the time advanced per sample
+fn time_step(chunk: &SoundChunk) -> f32 {
+    let n = chunk.l.len();
+    (chunk.l[n - 1] - chunk.l[0]) / ((n - 1) as f32)
+}
+
+#[test]
+fn test_default_speed_advances_in_real_time() {
+    let step = time_step(&render_time_through_input(1.0));
+    let expected = 1.0 / SAMPLE_FREQUENCY as f32;
+    assert!((step - expected).abs() < 1e-3 * expected);
+}
+
+#[test]
+fn test_half_speed_halves_time_step() {
+    let step = time_step(&render_time_through_input(0.5));
+    let expected = 0.5 / SAMPLE_FREQUENCY as f32;
+    assert!((step - expected).abs() < 1e-3 * expected);
+}
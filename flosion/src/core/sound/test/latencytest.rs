@@ -0,0 +1,185 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        engine::offlinerender::render_processor_offline,
+        jit::cache::JitCache,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            latency::{latency_compensation, processor_latencies},
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, InputContext, SoundInputLocation},
+            soundprocessor::{
+                ProcessorState, SoundProcessor, SoundProcessorWithId, StartOver, StateMarker,
+                StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    objects::{mixer::Mixer, wavegenerator::WaveGenerator},
+    ui_core::arguments::ParsedArguments,
+};
+
+const LOOKAHEAD: usize = 64;
+
+/// Passes its input through unchanged except for a fixed delay, as a
+/// processor which looks ahead by that many samples would
+#[derive(ProcessorComponent)]
+struct LookAhead {
+    input: SingleInput,
+
+    #[state]
+    state: StateMarker<LookAheadState>,
+}
+
+struct LookAheadState {
+    delay_l: [f32; LOOKAHEAD],
+    delay_r: [f32; LOOKAHEAD],
+    position: usize,
+}
+
+impl ProcessorState for LookAheadState {
+    type Processor = LookAhead;
+
+    fn new(_processor: &LookAhead) -> Self {
+        LookAheadState {
+            delay_l: [0.0; LOOKAHEAD],
+            delay_r: [0.0; LOOKAHEAD],
+            position: 0,
+        }
+    }
+}
+
+impl StartOver for LookAheadState {
+    fn start_over(&mut self) {
+        self.delay_l = [0.0; LOOKAHEAD];
+        self.delay_r = [0.0; LOOKAHEAD];
+        self.position = 0;
+    }
+}
+
+impl SoundProcessor for LookAhead {
+    fn new(_args: &ParsedArguments) -> LookAhead {
+        LookAhead {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn latency(&self) -> usize {
+        LOOKAHEAD
+    }
+
+    fn process_audio(
+        lookahead: &mut CompiledLookAhead,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = lookahead.input.step(dst, InputContext::new(context));
+        let state = &mut lookahead.state;
+        for (l, r) in dst.samples_mut() {
+            std::mem::swap(l, &mut state.delay_l[state.position]);
+            std::mem::swap(r, &mut state.delay_r[state.position]);
+            state.position = (state.position + 1) % LOOKAHEAD;
+        }
+        status
+    }
+}
+
+impl WithObjectType for LookAhead {
+    const TYPE: ObjectType = ObjectType::new("lookahead");
+}
+
+impl Stashable<StashingContext> for LookAhead {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for LookAhead {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)
+    }
+}
+
+#[test]
+fn test_parallel_dry_path_is_delayed_to_match() {
+    let mut graph = SoundGraph::new();
+
+    let wet_source = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let dry_source = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let lookahead = SoundProcessorWithId::<LookAhead>::new_default();
+    let mixer = SoundProcessorWithId::<Mixer>::new_default();
+
+    let wet_source_id = wet_source.id();
+    let dry_source_id = dry_source.id();
+    let lookahead_id = lookahead.id();
+    let mixer_id = mixer.id();
+    let lookahead_input = SoundInputLocation::new(lookahead_id, lookahead.input.id());
+    let wet_input = SoundInputLocation::new(mixer_id, mixer.inputs()[0].id());
+    let dry_input = SoundInputLocation::new(mixer_id, mixer.inputs()[1].id());
+
+    graph.add_sound_processor(Box::new(wet_source));
+    graph.add_sound_processor(Box::new(dry_source));
+    graph.add_sound_processor(Box::new(lookahead));
+    graph.add_sound_processor(Box::new(mixer));
+
+    graph
+        .connect_sound_input(lookahead_input, wet_source_id)
+        .unwrap();
+    graph.connect_sound_input(wet_input, lookahead_id).unwrap();
+    graph.connect_sound_input(dry_input, dry_source_id).unwrap();
+
+    // The look-ahead's latency carries through to the mixer
+    let latencies = processor_latencies(&graph);
+    assert_eq!(latencies[&wet_source_id], 0);
+    assert_eq!(latencies[&lookahead_id], LOOKAHEAD);
+    assert_eq!(latencies[&mixer_id], LOOKAHEAD);
+
+    // Only the dry path needs delaying
+    assert_eq!(latency_compensation(&graph, &latencies, wet_input), 0);
+    assert_eq!(
+        latency_compensation(&graph, &latencies, dry_input),
+        LOOKAHEAD
+    );
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let buffer = render_processor_offline(&graph, &jit_cache, mixer_id, SAMPLE_FREQUENCY / 10);
+
+    // Both paths line up, so the mixer plays the same sine wave twice
+    // over, delayed by the look-ahead. The generator's phase advances
+    // before each sample is produced.
+    let frequency = 250.0;
+    for (i, [l, r]) in buffer.samples().take(buffer.sample_len()).enumerate() {
+        let expected = if i < LOOKAHEAD {
+            0.0
+        } else {
+            let t = (i - LOOKAHEAD + 1) as f32 / SAMPLE_FREQUENCY as f32;
+            2.0 * (std::f32::consts::TAU * frequency * t).sin()
+        };
+        assert!(
+            (l - expected).abs() < 1e-2,
+            "sample {} is {} instead of {}",
+            i,
+            l,
+            expected
+        );
+        assert_eq!(l, r);
+    }
+}
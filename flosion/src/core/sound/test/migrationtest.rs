@@ -0,0 +1,259 @@
+use std::cell::Cell;
+
+use flosion_macros::ProcessorComponent;
+use hashstash::{
+    InplaceUnstasher, Stash, StashHandle, Stashable, Stasher, UnstashError, UnstashableInplace,
+};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            context::AudioContext,
+            migration::MigrationError,
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                SoundProcessor, SoundProcessorId, SoundProcessorWithId, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::{arguments::ParsedArguments, factories::Factories},
+};
+
+/// An older version of `Level`, as it was before it stored its
+/// level as an amplitude instead of in decibels
+#[derive(ProcessorComponent)]
+struct LevelV1 {
+    #[not_a_component]
+    decibels: f32,
+}
+
+/// A processor whose stashed form changed between versions
+#[derive(ProcessorComponent)]
+struct Level {
+    #[not_a_component]
+    amplitude: f32,
+
+    #[not_a_component]
+    muted: bool,
+}
+
+impl SoundProcessor for LevelV1 {
+    fn new(_args: &ParsedArguments) -> LevelV1 {
+        LevelV1 { decibels: 0.0 }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        _level: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        _context: &mut AudioContext,
+    ) -> StreamStatus {
+        dst.silence();
+        StreamStatus::Done
+    }
+}
+
+impl SoundProcessor for Level {
+    fn new(_args: &ParsedArguments) -> Level {
+        Level {
+            amplitude: 1.0,
+            muted: false,
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        _level: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        _context: &mut AudioContext,
+    ) -> StreamStatus {
+        dst.silence();
+        StreamStatus::Done
+    }
+}
+
+impl WithObjectType for LevelV1 {
+    const TYPE: ObjectType = ObjectType::new("level");
+    const VERSION: u32 = 1;
+}
+
+impl WithObjectType for Level {
+    const TYPE: ObjectType = ObjectType::new("level");
+    const VERSION: u32 = 2;
+}
+
+impl Stashable<StashingContext> for LevelV1 {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.f32(self.decibels);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for LevelV1 {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.f32_inplace(&mut self.decibels)
+    }
+}
+
+impl Stashable<StashingContext> for Level {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.f32(self.amplitude);
+        stasher.bool(self.muted);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for Level {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.f32_inplace(&mut self.amplitude)?;
+        unstasher.bool_inplace(&mut self.muted)?;
+        Ok(())
+    }
+}
+
+/// Read a level stashed at version 1
+fn migrate_level_from_v1(
+    level: &mut Level,
+    unstasher: &mut InplaceUnstasher<UnstashingContext>,
+) -> Result<(), UnstashError> {
+    let decibels = unstasher.f32_always()?;
+    if unstasher.time_to_write() {
+        level.amplitude = 10.0_f32.powf(decibels / 20.0);
+        level.muted = false;
+    }
+    Ok(())
+}
+
+fn current_factories(with_migration: bool) -> Factories {
+    let mut factories = Factories::new_empty();
+    factories
+        .sound_objects_mut()
+        .register::<SoundProcessorWithId<Level>>();
+    if with_migration {
+        factories
+            .sound_objects_mut()
+            .migrations_mut()
+            .register::<Level>(1, migrate_level_from_v1);
+    }
+    factories
+}
+
+/// Stash a graph containing a single level at version 1, as it would
+/// have been stashed before the level's stashed form changed
+fn stash_old_graph(stash: &Stash) -> (StashHandle<SoundGraph>, SoundProcessorId) {
+    let mut old_level = SoundProcessorWithId::<LevelV1>::new_default();
+    old_level.decibels = -20.0;
+    let id = old_level.id();
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(old_level));
+    let handle = stash.stash_with_context(&graph, StashingContext::new_stashing_normally());
+    (handle, id)
+}
+
+#[test]
+fn test_old_patch_is_migrated() {
+    let stash = Stash::new();
+    let (handle, id) = stash_old_graph(&stash);
+
+    let factories = current_factories(true);
+    let graph: SoundGraph = stash
+        .unstash_with_context(
+            &handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        )
+        .unwrap();
+
+    let level = graph
+        .sound_processor(id)
+        .unwrap()
+        .downcast::<Level>()
+        .unwrap();
+    assert!((level.amplitude - 0.1).abs() < 1e-6);
+    assert!(!level.muted);
+
+    // Once unstashed, the level is stashed at the current version
+    let handle = stash.stash_with_context(&graph, StashingContext::new_stashing_normally());
+    let graph: SoundGraph = stash
+        .unstash_with_context(
+            &handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        )
+        .unwrap();
+    let level = graph
+        .sound_processor(id)
+        .unwrap()
+        .downcast::<Level>()
+        .unwrap();
+    assert!((level.amplitude - 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn test_old_patch_without_migration_fails() {
+    let stash = Stash::new();
+    let (handle, _) = stash_old_graph(&stash);
+
+    let factories = current_factories(false);
+    let migration_error = Cell::new(None);
+    let result: Result<SoundGraph, _> = stash.unstash_with_context(
+        &handle,
+        UnstashingContext::new(factories.sound_objects(), factories.expression_objects())
+            .with_migration_error(&migration_error),
+    );
+    assert!(matches!(result, Err(UnstashError::Corrupted)));
+    assert_eq!(
+        migration_error.get(),
+        Some(MigrationError::NoMigrationPath {
+            object_type: "level",
+            stashed_version: 1,
+            current_version: 2
+        })
+    );
+
+    // Unstashing in place into an existing graph fails the same way
+    let mut graph = SoundGraph::new();
+    let migration_error = Cell::new(None);
+    let result = stash.unstash_inplace_with_context(
+        &handle,
+        &mut graph,
+        UnstashingContext::new(factories.sound_objects(), factories.expression_objects())
+            .with_migration_error(&migration_error),
+    );
+    assert_eq!(result, Err(UnstashError::Corrupted));
+    assert!(matches!(
+        migration_error.get(),
+        Some(MigrationError::NoMigrationPath { .. })
+    ));
+    assert!(graph.sound_processors().is_empty());
+
+    let migrations = factories.sound_objects().migrations();
+    assert_eq!(
+        migrations.check("level", Level::VERSION, 1),
+        Err(MigrationError::NoMigrationPath {
+            object_type: "level",
+            stashed_version: 1,
+            current_version: 2
+        })
+    );
+    assert_eq!(
+        migrations.check("level", Level::VERSION, 3),
+        Err(MigrationError::NewerVersion {
+            object_type: "level",
+            stashed_version: 3,
+            current_version: 2
+        })
+    );
+    assert_eq!(migrations.check("level", Level::VERSION, 2), Ok(()));
+}
@@ -1,3 +1,11 @@
+mod expressiondependencytest;
+mod inputspeedtest;
+mod latencytest;
+mod migrationtest;
+mod panicfadetest;
+mod soundgraphdifftest;
 mod soundgraphstashtest;
 mod soundgraphvalidationtest;
+mod startovertest;
 mod testobjects;
+mod testtonetest;
@@ -0,0 +1,68 @@
+use crate::core::{
+    engine::{soundengine::PanicSwitch, soundgraphcompiler::SoundGraphCompiler},
+    jit::cache::JitCache,
+    sound::{
+        panicfade::{PanicFade, PANIC_FADE_SAMPLES},
+        soundgraph::SoundGraph,
+        soundprocessor::{ProcessorComponent, SoundProcessorId},
+    },
+    soundchunk::{SoundChunk, CHUNK_SIZE},
+};
+
+/// Pass enough chunks of constant full-scale audio through the
+/// fade to cover the fade time twice over, and return all samples
+fn fade_constant_signal(
+    fade: &mut <PanicFade as ProcessorComponent>::CompiledType<'_>,
+) -> Vec<f32> {
+    let num_chunks = 2 * PANIC_FADE_SAMPLES.div_ceil(CHUNK_SIZE);
+    let mut samples = Vec::new();
+    for _ in 0..num_chunks {
+        let mut chunk = SoundChunk::new();
+        chunk.l.fill(1.0);
+        chunk.r.fill(1.0);
+        fade.process(&mut chunk);
+        assert_eq!(chunk.l, chunk.r);
+        samples.extend_from_slice(&chunk.l);
+    }
+    samples
+}
+
+#[test]
+fn test_panic_fades_output_to_zero() {
+    let inkwell_context = inkwell::context::Context::create();
+    let jit_cache = JitCache::new(&inkwell_context);
+    let graph = SoundGraph::new();
+    let panic_switch = PanicSwitch::new();
+    let mut compiler =
+        SoundGraphCompiler::new(&graph, &jit_cache).with_panic_switch(panic_switch.clone());
+
+    let mut fade = PanicFade.compile(SoundProcessorId::new_unique(), &mut compiler);
+
+    // Audio passes through untouched normally
+    assert!(!fade.is_engaged());
+    assert!(fade_constant_signal(&mut fade).iter().all(|s| *s == 1.0));
+
+    panic_switch.set_engaged(true);
+    assert!(fade.is_engaged());
+    let samples = fade_constant_signal(&mut fade);
+
+    // The output ramps down without jumping...
+    for pair in samples.windows(2) {
+        assert!(pair[1] <= pair[0]);
+        assert!(pair[0] - pair[1] <= 1.0 / PANIC_FADE_SAMPLES as f32 + 1e-6);
+    }
+    assert!(samples[0] > 0.0);
+
+    // ...and is silent once the fade time has passed
+    assert!(samples[(PANIC_FADE_SAMPLES - 1)..]
+        .iter()
+        .all(|s| *s == 0.0));
+
+    // Disengaging the switch fades back in
+    panic_switch.set_engaged(false);
+    let samples = fade_constant_signal(&mut fade);
+    assert!(samples[0] < 1.0);
+    assert!(samples[(PANIC_FADE_SAMPLES - 1)..]
+        .iter()
+        .all(|s| *s == 1.0));
+}
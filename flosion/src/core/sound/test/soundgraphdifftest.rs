@@ -0,0 +1,112 @@
+use hashstash::Stash;
+
+use crate::{
+    core::{
+        sound::{
+            argument::ArgumentScope,
+            soundgraph::SoundGraph,
+            soundgraphdiff::{SoundGraphDiff, SoundGraphRevisions},
+            soundinput::SoundInputCategory,
+            soundprocessor::SoundProcessorWithId,
+            test::testobjects::TestSoundInput,
+        },
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::factories::Factories,
+};
+
+use super::testobjects::{TestDynamicSoundProcessor, TestStaticSoundProcessor};
+
+#[test]
+fn diff_of_unchanged_graph_is_empty() {
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(
+        SoundProcessorWithId::<TestStaticSoundProcessor>::new_default(),
+    ));
+
+    assert!(SoundGraphDiff::between(&graph, &graph).is_empty());
+}
+
+#[test]
+fn diff_reports_one_added_processor() {
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(
+        SoundProcessorWithId::<TestStaticSoundProcessor>::new_default(),
+    ));
+
+    let before = SoundGraphRevisions::new(&graph);
+
+    let proc = SoundProcessorWithId::<TestDynamicSoundProcessor>::new_default();
+    let proc_id = proc.id();
+    graph.add_sound_processor(Box::new(proc));
+
+    let diff = before.diff(&SoundGraphRevisions::new(&graph));
+
+    assert_eq!(diff.processors.added, vec![proc_id]);
+    assert!(diff.processors.removed.is_empty());
+    assert!(diff.processors.modified.is_empty());
+    assert!(diff.inputs.is_empty());
+    assert!(diff.expressions.is_empty());
+}
+
+#[test]
+fn diff_reports_connected_input_as_modified() {
+    let mut graph = SoundGraph::new();
+
+    let mut proc = SoundProcessorWithId::<TestDynamicSoundProcessor>::new_default();
+    proc.inputs.push(TestSoundInput::new(
+        SoundInputCategory::Branched(2),
+        ArgumentScope::new_empty(),
+    ));
+    let proc_id = proc.id();
+    graph.add_sound_processor(Box::new(proc));
+
+    let other_proc = SoundProcessorWithId::<TestStaticSoundProcessor>::new_default();
+    let other_proc_id = other_proc.id();
+    graph.add_sound_processor(Box::new(other_proc));
+
+    let before = SoundGraphRevisions::new(&graph);
+
+    let input_location = graph.sound_processor(proc_id).unwrap().input_locations()[0];
+    graph
+        .connect_sound_input(input_location, other_proc_id)
+        .unwrap();
+
+    let diff = before.diff(&SoundGraphRevisions::new(&graph));
+
+    assert_eq!(diff.inputs.modified, vec![input_location]);
+    assert!(diff.inputs.added.is_empty());
+    assert!(diff.inputs.removed.is_empty());
+    assert_eq!(diff.processors.modified, vec![proc_id]);
+}
+
+#[test]
+fn diff_against_stashed_snapshot_reports_removal() {
+    let mut graph = SoundGraph::new();
+    let proc = SoundProcessorWithId::<TestStaticSoundProcessor>::new_default();
+    let proc_id = proc.id();
+    graph.add_sound_processor(Box::new(proc));
+
+    let stash = Stash::new();
+    let snapshot = stash.stash_with_context(&graph, StashingContext::new_stashing_normally());
+
+    graph.remove_sound_processor(proc_id).unwrap();
+
+    let mut factories = Factories::new_empty();
+    factories
+        .sound_objects_mut()
+        .register::<SoundProcessorWithId<TestStaticSoundProcessor>>();
+
+    let before = SoundGraphRevisions::from_snapshot(
+        &stash,
+        &snapshot,
+        UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+    )
+    .unwrap();
+
+    let diff = before.diff(&SoundGraphRevisions::new(&graph));
+
+    assert_eq!(diff.processors.removed, vec![proc_id]);
+    assert!(diff.processors.added.is_empty());
+    assert!(diff.processors.modified.is_empty());
+}
@@ -1,11 +1,15 @@
-use hashstash::{stash_clone_with_context, Stash};
+use hashstash::{
+    stash_clone_with_context, InplaceUnstasher, Stash, Stashable, Stasher, UnstashError,
+    Unstashable, UnstashableInplace, Unstasher,
+};
 
 use crate::{
     core::{
         sound::{
             argument::ArgumentScope,
+            inputtypes::singleinput::SingleInput,
             soundgraph::SoundGraph,
-            soundinput::SoundInputCategory,
+            soundinput::{AnyProcessorInput, SoundInputCategory},
             soundprocessor::{SoundProcessor, SoundProcessorWithId},
             test::testobjects::TestSoundInput,
         },
@@ -186,3 +190,91 @@ fn stash_clone_graph_with_one_dynamic_processor() {
     assert_eq!(new_proc.id(), proc.id());
     assert_eq!(new_proc.inputs, proc.inputs);
 }
+
+/// An input which is stashed with the given speed in place of its own,
+/// so that speeds which can't be set can still be unstashed
+struct InputWithStashedSpeed {
+    input: SingleInput,
+    speed: f32,
+}
+
+impl Stashable<StashingContext> for InputWithStashedSpeed {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        // Same as ProcessorInput, except for the speed
+        stasher.u64(self.input.id().value() as _);
+        stasher.u8(0);
+        stasher.object(self.input.argument_scope());
+        stasher.f32(self.speed);
+        stasher.u8(self.input.disconnect_behavior().to_u8());
+        stasher.object(&*self.input);
+    }
+}
+
+impl<'a> Unstashable<UnstashingContext<'a>> for InputWithStashedSpeed {
+    fn unstash(unstasher: &mut Unstasher<UnstashingContext<'a>>) -> Result<Self, UnstashError> {
+        let input = SingleInput::unstash(unstasher)?;
+        let speed = input.speed();
+        Ok(InputWithStashedSpeed { input, speed })
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for InputWithStashedSpeed {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        self.input.unstash_inplace(unstasher)
+    }
+}
+
+#[test]
+fn unstash_input_with_invalid_speed() {
+    let stash = Stash::new();
+    let factories = test_sound_object_factories();
+
+    let new_input = || SingleInput::new_isochronic(ArgumentScope::new_empty());
+
+    for speed in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        let handle = stash.stash_with_context(
+            &InputWithStashedSpeed {
+                input: new_input(),
+                speed,
+            },
+            StashingContext::new_stashing_normally(),
+        );
+
+        let res = stash.unstash_with_context(
+            &handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        );
+        assert!(matches!(res, Err(UnstashError::Corrupted)));
+
+        let mut existing = InputWithStashedSpeed {
+            input: new_input(),
+            speed: 1.0,
+        };
+        let res = stash.unstash_inplace_with_context(
+            &handle,
+            &mut existing,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        );
+        assert_eq!(res, Err(UnstashError::Corrupted));
+        assert_eq!(existing.input.speed(), 1.0);
+    }
+
+    // Valid speeds are unstashed as usual
+    let handle = stash.stash_with_context(
+        &InputWithStashedSpeed {
+            input: new_input(),
+            speed: 0.5,
+        },
+        StashingContext::new_stashing_normally(),
+    );
+    let unstashed = stash
+        .unstash_with_context(
+            &handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        )
+        .unwrap();
+    assert_eq!(unstashed.input.speed(), 0.5);
+}
@@ -1,13 +1,37 @@
-use crate::core::sound::{
-    argument::ArgumentScope,
-    sounderror::SoundError,
-    soundgraph::SoundGraph,
-    soundgraphvalidation::find_sound_error,
-    soundinput::{AnyProcessorInput, SoundInputCategory, SoundInputLocation},
-    soundprocessor::SoundProcessorWithId,
-    test::testobjects::{TestDynamicSoundProcessor, TestSoundInput, TestStaticSoundProcessor},
+use hashstash::Stash;
+
+use crate::{
+    core::sound::{
+        argument::ArgumentScope,
+        sounderror::SoundError,
+        soundgraph::SoundGraph,
+        soundgraphvalidation::{find_branch_count_mismatches, find_sound_error},
+        soundinput::{AnyProcessorInput, SoundInputCategory, SoundInputLocation},
+        soundprocessor::{SoundProcessorId, SoundProcessorWithId},
+        test::testobjects::{TestDynamicSoundProcessor, TestSoundInput, TestStaticSoundProcessor},
+    },
+    ui_core::factories::Factories,
 };
 
+/// Assert that the error is a cycle through exactly the given processors
+/// in the given order, starting from any one of them
+fn assert_cycle(error: Option<SoundError>, expected_cycle: &[SoundProcessorId]) {
+    let Some(SoundError::CircularDependency { cycle }) = error else {
+        panic!("Expected a circular dependency, got {:?}", error);
+    };
+    assert_eq!(cycle.len(), expected_cycle.len());
+    let offset = expected_cycle
+        .iter()
+        .position(|p| *p == cycle[0])
+        .expect("Cycle contains an unexpected processor");
+    for (i, proc_id) in cycle.iter().enumerate() {
+        assert_eq!(
+            *proc_id,
+            expected_cycle[(i + offset) % expected_cycle.len()]
+        );
+    }
+}
+
 #[test]
 fn find_error_empty_graph() {
     let graph = SoundGraph::new();
@@ -54,7 +78,9 @@ fn find_error_static_to_self_cycle() {
 
     assert_eq!(
         find_sound_error(&graph),
-        Some(SoundError::CircularDependency),
+        Some(SoundError::CircularDependency {
+            cycle: vec![proc_id]
+        }),
     );
 }
 
@@ -450,15 +476,14 @@ fn find_error_dynamic_to_dynamic_to_static_cycle() {
     ));
     proc3.inputs[0].set_target(Some(proc1.id()));
 
+    let expected_cycle = [proc1.id(), proc2.id(), proc3.id()];
+
     let mut graph = SoundGraph::new();
     graph.add_sound_processor(Box::new(proc1));
     graph.add_sound_processor(Box::new(proc2));
     graph.add_sound_processor(Box::new(proc3));
 
-    assert_eq!(
-        find_sound_error(&graph),
-        Some(SoundError::CircularDependency)
-    );
+    assert_cycle(find_sound_error(&graph), &expected_cycle);
 }
 
 #[test]
@@ -636,3 +661,90 @@ fn find_error_dynamic_to_static_two_inputs_with_side_proc() {
 
     assert_eq!(find_sound_error(&graph), None);
 }
+
+#[test]
+fn connecting_input_to_downstream_processor_is_rejected() {
+    let mut upstream_proc = SoundProcessorWithId::<TestStaticSoundProcessor>::new_default();
+    let mut downstream_proc = SoundProcessorWithId::<TestStaticSoundProcessor>::new_default();
+
+    upstream_proc.inputs.push(TestSoundInput::new(
+        SoundInputCategory::Isochronic,
+        ArgumentScope::new_empty(),
+    ));
+    downstream_proc.inputs.push(TestSoundInput::new(
+        SoundInputCategory::Isochronic,
+        ArgumentScope::new_empty(),
+    ));
+    downstream_proc.inputs[0].set_target(Some(upstream_proc.id()));
+
+    let upstream_input = SoundInputLocation::new(upstream_proc.id(), upstream_proc.inputs[0].id());
+    let upstream_id = upstream_proc.id();
+    let downstream_id = downstream_proc.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(upstream_proc));
+    graph.add_sound_processor(Box::new(downstream_proc));
+
+    let stash = Stash::new();
+    let mut factories = Factories::new_empty();
+    factories
+        .sound_objects_mut()
+        .register::<SoundProcessorWithId<TestStaticSoundProcessor>>();
+
+    let res = graph.try_make_change(
+        &stash,
+        factories.sound_objects(),
+        factories.expression_objects(),
+        |graph| graph.connect_sound_input(upstream_input, downstream_id),
+    );
+
+    assert_cycle(res.err(), &[upstream_id, downstream_id]);
+
+    // The change was rolled back
+    assert_eq!(
+        graph.with_sound_input(upstream_input, |input| input.target()),
+        Some(None)
+    );
+}
+
+#[test]
+fn find_branch_count_mismatch_dynamic_to_dynamic_no_branches() {
+    let mut proc1 = SoundProcessorWithId::<TestDynamicSoundProcessor>::new_default();
+    let proc2 = SoundProcessorWithId::<TestDynamicSoundProcessor>::new_default();
+
+    proc1.inputs.push(TestSoundInput::new(
+        SoundInputCategory::Branched(0),
+        ArgumentScope::new_empty(),
+    ));
+    proc1.inputs[0].set_target(Some(proc2.id()));
+    let input_loc = SoundInputLocation::new(proc1.id(), proc1.inputs[0].id());
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(proc1));
+    graph.add_sound_processor(Box::new(proc2));
+
+    // The graph is valid, but the mismatch is flagged
+    assert_eq!(find_sound_error(&graph), None);
+    assert_eq!(
+        find_branch_count_mismatches(&graph),
+        vec![SoundError::InputHasNoBranches(input_loc)]
+    );
+}
+
+#[test]
+fn find_branch_count_mismatch_dynamic_to_dynamic_two_branches() {
+    let mut proc1 = SoundProcessorWithId::<TestDynamicSoundProcessor>::new_default();
+    let proc2 = SoundProcessorWithId::<TestDynamicSoundProcessor>::new_default();
+
+    proc1.inputs.push(TestSoundInput::new(
+        SoundInputCategory::Branched(2),
+        ArgumentScope::new_empty(),
+    ));
+    proc1.inputs[0].set_target(Some(proc2.id()));
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(proc1));
+    graph.add_sound_processor(Box::new(proc2));
+
+    assert_eq!(find_branch_count_mismatches(&graph), Vec::new());
+}
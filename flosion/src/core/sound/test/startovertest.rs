@@ -0,0 +1,294 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        engine::{
+            compiledprocessor::AnyCompiledProcessorData, scratcharena::ScratchArena,
+            soundgraphcompiler::SoundGraphCompiler,
+        },
+        expression::expressiongraph::ExpressionTarget,
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ProcessorArgumentLocation,
+            context::{AudioContext, AudioStack},
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorState, ProcessorTiming, SoundProcessor, SoundProcessorWithId, StartOver,
+                StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    objects::{
+        noise::{Noise, NoiseColor},
+        wavegenerator::WaveGenerator,
+        whitenoise::WhiteNoise,
+    },
+    ui_core::{arguments::ParsedArguments, factories::Factories},
+};
+
+/// Number of chunks rendered before and after starting over
+const NUM_CHUNKS: usize = 8;
+
+/// Compile the given processor, render several chunks from it, start it
+/// over, render the same number of chunks again, and assert that both
+/// renders are identical. Processors whose output depends on any state
+/// not reset by start_over will fail this.
+fn assert_start_over_resets<T>(processor: SoundProcessorWithId<T>)
+where
+    T: 'static
+        + SoundProcessor
+        + WithObjectType
+        + Stashable<StashingContext>
+        + for<'a> UnstashableInplace<UnstashingContext<'a>>,
+{
+    let id = processor.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(processor));
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let processor = graph.sound_processor(id).unwrap().downcast::<T>().unwrap();
+
+    let mut compiled = processor.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let render = |compiled: &mut T::CompiledType<'_>, timing: &mut ProcessorTiming| {
+        let mut chunks = Vec::new();
+        for _ in 0..NUM_CHUNKS {
+            let mut context = AudioContext::new(
+                id,
+                timing,
+                &scratch_arena,
+                argument_stack.view_at_bottom(),
+                AudioStack::Root,
+            );
+            let mut chunk = SoundChunk::new();
+            T::process_audio(compiled, &mut chunk, &mut context);
+            chunks.push(chunk);
+            timing.advance_one_chunk();
+        }
+        chunks
+    };
+
+    let first = render(&mut compiled, &mut processor_timing);
+
+    compiled.start_over();
+    processor_timing.start_over();
+
+    let second = render(&mut compiled, &mut processor_timing);
+
+    for (i, (a, b)) in first.iter().zip(&second).enumerate() {
+        assert!(
+            a.l == b.l && a.r == b.r,
+            "Chunk {} differed after starting over",
+            i
+        );
+    }
+}
+
+/// Make the expression's only result refer directly to the given target
+fn connect_result_to(expression: &mut ProcessorExpression, target: ExpressionParameterTarget) {
+    let param_id = expression.add_target(target);
+    let graph = expression.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(param_id),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_start_over_whitenoise() {
+    let mut whitenoise = SoundProcessorWithId::<WhiteNoise>::new_default();
+    whitenoise.set_seed(3);
+    assert_start_over_resets(whitenoise);
+}
+
+#[test]
+fn test_start_over_noise() {
+    for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+        let mut noise = SoundProcessorWithId::<Noise>::new_default();
+        noise.set_color(color);
+        noise.set_seed(3);
+        assert_start_over_resets(noise);
+    }
+}
+
+#[test]
+fn test_start_over_wavegenerator() {
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+
+    // Output the phase directly, which depends on all previous chunks
+    let phase_target = ExpressionParameterTarget::Argument(ProcessorArgumentLocation::new(
+        wavegen.id(),
+        wavegen.phase.id(),
+    ));
+    connect_result_to(&mut wavegen.amplitude, phase_target);
+
+    assert_start_over_resets(wavegen);
+}
+
+/// Render several chunks from an already-compiled processor
+fn render_compiled(compiled: &mut dyn AnyCompiledProcessorData) -> Vec<SoundChunk> {
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    (0..NUM_CHUNKS)
+        .map(|_| {
+            let mut chunk = SoundChunk::new();
+            compiled.process_audio(
+                &mut chunk,
+                AudioStack::Root,
+                &scratch_arena,
+                argument_stack.view_at_bottom(),
+            );
+            chunk
+        })
+        .collect()
+}
+
+/// For every registered dynamic processor, created with its default
+/// arguments and with white noise connected to each of its inputs,
+/// render several chunks, start it over, and assert that it then
+/// renders the same as a freshly-compiled instance of the processor.
+/// Static processors are skipped, since they don't start over.
+#[test]
+fn test_start_over_all_processors() {
+    let factories = Factories::new_all_objects();
+    let inkwell_context = inkwell::context::Context::create();
+
+    for object_ui in factories.sound_uis().all_object_uis() {
+        let name = object_ui.object_type().name();
+        let processor = factories
+            .sound_objects()
+            .create(name, &ParsedArguments::new_empty())
+            .into_boxed_sound_processor()
+            .unwrap();
+        if processor.is_static() {
+            continue;
+        }
+        let id = processor.id();
+
+        let mut graph = SoundGraph::new();
+        graph.add_sound_processor(processor);
+        for location in graph.sound_processor(id).unwrap().input_locations() {
+            let mut noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+            noise.set_seed(3);
+            let noise_id = noise.id();
+            graph.add_sound_processor(Box::new(noise));
+            graph.connect_sound_input(location, noise_id).unwrap();
+        }
+        assert_eq!(graph.validate(), Ok(()));
+
+        let mut jit_cache = JitCache::new(&inkwell_context);
+        jit_cache.refresh(&graph);
+
+        let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+        let processor = graph.sound_processor(id).unwrap();
+        let mut started_over = processor.compile(&mut compiler);
+        let mut fresh = processor.compile(&mut compiler);
+
+        render_compiled(&mut *started_over);
+        started_over.start_over();
+
+        let expected = render_compiled(&mut *fresh);
+        let actual = render_compiled(&mut *started_over);
+
+        for (i, (a, b)) in expected.iter().zip(&actual).enumerate() {
+            assert!(
+                a.l == b.l && a.r == b.r,
+                "Chunk {} of {} differed from a fresh instance after starting over",
+                i,
+                name
+            );
+        }
+    }
+}
+
+//------------------------
+
+pub(super) struct CounterState {
+    count: f32,
+}
+
+impl ProcessorState for CounterState {
+    type Processor = ForgetfulCounter;
+
+    fn new(_processor: &ForgetfulCounter) -> CounterState {
+        CounterState { count: 0.0 }
+    }
+}
+
+impl StartOver for CounterState {
+    fn start_over(&mut self) {
+        // Deliberately forgets to reset the count
+    }
+}
+
+/// A processor which outputs the number of chunks produced so far,
+/// but which doesn't reset that number when starting over
+#[derive(ProcessorComponent)]
+pub(super) struct ForgetfulCounter {
+    #[state]
+    state: StateMarker<CounterState>,
+}
+
+impl SoundProcessor for ForgetfulCounter {
+    fn new(_args: &ParsedArguments) -> ForgetfulCounter {
+        ForgetfulCounter {
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        counter: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        _context: &mut AudioContext,
+    ) -> StreamStatus {
+        counter.state.count += 1.0;
+        dst.l.fill(counter.state.count);
+        dst.r.fill(counter.state.count);
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for ForgetfulCounter {
+    const TYPE: ObjectType = ObjectType::new("forgetfulcounter");
+}
+
+impl Stashable<StashingContext> for ForgetfulCounter {
+    fn stash(&self, _stasher: &mut Stasher<StashingContext>) {}
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for ForgetfulCounter {
+    fn unstash_inplace(
+        &mut self,
+        _unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        Ok(())
+    }
+}
+
+#[test]
+#[should_panic(expected = "differed after starting over")]
+fn test_start_over_harness_catches_unreset_state() {
+    assert_start_over_resets(SoundProcessorWithId::<ForgetfulCounter>::new_default());
+}
@@ -11,7 +11,8 @@ use crate::{
             argument::ArgumentScope,
             context::AudioContext,
             soundinput::{
-                ProcessorInput, SoundInputBackend, SoundInputCategory, SoundInputLocation,
+                DisconnectBehavior, ProcessorInput, SoundInputBackend, SoundInputCategory,
+                SoundInputLocation,
             },
             soundprocessor::{
                 CompiledComponentVisitor, CompiledProcessorComponent, ProcessorComponent,
@@ -195,6 +196,8 @@ impl SoundInputBackend for TestSoundInputBackend {
         &self,
         _location: SoundInputLocation,
         _target: Option<SoundProcessorId>,
+        _speed: f32,
+        _disconnect_behavior: DisconnectBehavior,
         _compiler: &mut SoundGraphCompiler<'_, 'ctx>,
     ) -> Self::CompiledType<'ctx> {
         ()
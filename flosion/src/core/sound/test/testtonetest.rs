@@ -0,0 +1,82 @@
+use crate::core::{
+    engine::{
+        soundengine::{TestTone, TestToneSignal, TEST_TONE_DEFAULT_LEVEL},
+        soundgraphcompiler::SoundGraphCompiler,
+    },
+    jit::cache::JitCache,
+    samplefrequency::SAMPLE_FREQUENCY,
+    sound::{
+        soundgraph::SoundGraph,
+        soundprocessor::{ProcessorComponent, SoundProcessorId},
+        testtone::TestToneOverride,
+    },
+    soundchunk::{SoundChunk, CHUNK_SIZE},
+};
+
+/// Pass a few seconds of constant audio through the override
+/// and return the samples of both channels
+fn render_override(
+    test_tone: &mut <TestToneOverride as ProcessorComponent>::CompiledType<'_>,
+) -> (Vec<f32>, Vec<f32>) {
+    let num_chunks = (4 * SAMPLE_FREQUENCY).div_ceil(CHUNK_SIZE);
+    let mut l = Vec::new();
+    let mut r = Vec::new();
+    for _ in 0..num_chunks {
+        let mut chunk = SoundChunk::new();
+        chunk.l.fill(0.5);
+        chunk.r.fill(0.5);
+        test_tone.process(&mut chunk);
+        l.extend_from_slice(&chunk.l);
+        r.extend_from_slice(&chunk.r);
+    }
+    (l, r)
+}
+
+fn rms_dbfs(samples: &[f32]) -> f32 {
+    let mean_square = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    10.0 * mean_square.log10()
+}
+
+#[test]
+fn test_test_tone_level() {
+    let inkwell_context = inkwell::context::Context::create();
+    let jit_cache = JitCache::new(&inkwell_context);
+    let graph = SoundGraph::new();
+    let test_tone = TestTone::new();
+    let mut compiler =
+        SoundGraphCompiler::new(&graph, &jit_cache).with_test_tone(test_tone.clone());
+
+    let mut tone_override = TestToneOverride.compile(SoundProcessorId::new_unique(), &mut compiler);
+
+    // Audio passes through untouched while the tone is off
+    assert!(!tone_override.is_enabled());
+    let (l, r) = render_override(&mut tone_override);
+    assert!(l.iter().chain(&r).all(|s| *s == 0.5));
+
+    test_tone.set_enabled(true);
+    assert!(tone_override.is_enabled());
+
+    // The sine tone is at the default level in both channels
+    let (l, r) = render_override(&mut tone_override);
+    assert_eq!(l, r);
+    let level = rms_dbfs(&l);
+    assert!(
+        (level - TEST_TONE_DEFAULT_LEVEL).abs() < 0.01,
+        "Sine level was {} dBFS",
+        level
+    );
+
+    // Pink noise is at the chosen level in each channel
+    test_tone.set_signal(TestToneSignal::PinkNoise);
+    test_tone.set_level(-20.0);
+    let (l, r) = render_override(&mut tone_override);
+    assert!(l != r);
+    for samples in [&l, &r] {
+        let level = rms_dbfs(samples);
+        assert!(
+            (level + 20.0).abs() < 0.5,
+            "Pink noise level was {} dBFS",
+            level
+        );
+    }
+}
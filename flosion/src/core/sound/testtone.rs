@@ -0,0 +1,110 @@
+use rand::{rngs::SmallRng, SeedableRng};
+
+use crate::core::{
+    engine::{
+        soundengine::{TestTone, TestToneSignal, TEST_TONE_FREQUENCY},
+        soundgraphcompiler::SoundGraphCompiler,
+    },
+    pinknoise::PinkNoise,
+    samplefrequency::SAMPLE_FREQUENCY,
+    soundchunk::SoundChunk,
+};
+
+use super::soundprocessor::{
+    CompiledComponentVisitor, CompiledProcessorComponent, ProcessorComponent,
+    ProcessorComponentVisitor, ProcessorComponentVisitorMut, SoundProcessorId, StartOver,
+};
+
+/// The RMS amplitude of the pink noise generator's output before scaling,
+/// which is that of uniform white noise from -1 to 1
+const PINK_NOISE_RMS: f32 = 0.57735026; // 1 / sqrt(3)
+
+/// A processor component which responds to the sound engine's test tone
+/// control. Processors sending audio out of the sound graph use it to
+/// replace their audio with the test tone while it is enabled, after all
+/// other processing, so that the tone arrives at a calibrated level.
+pub struct TestToneOverride;
+
+impl ProcessorComponent for TestToneOverride {
+    type CompiledType<'ctx> = CompiledTestToneOverride;
+
+    fn visit(&self, _visitor: &mut dyn ProcessorComponentVisitor) {}
+
+    fn visit_mut(&mut self, _visitor: &mut dyn ProcessorComponentVisitorMut) {}
+
+    fn compile<'ctx>(
+        &self,
+        _processor_id: SoundProcessorId,
+        compiler: &mut SoundGraphCompiler<'_, 'ctx>,
+    ) -> CompiledTestToneOverride {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let pink = [
+            PinkNoise::new_settled(&mut rng),
+            PinkNoise::new_settled(&mut rng),
+        ];
+        CompiledTestToneOverride {
+            tone: compiler.test_tone().clone(),
+            phase: 0.0,
+            rng,
+            pink,
+        }
+    }
+}
+
+pub struct CompiledTestToneOverride {
+    tone: TestTone,
+
+    /// The phase of the sine tone, from 0 to 1
+    phase: f32,
+
+    rng: SmallRng,
+
+    /// Independent pink noise for each channel
+    pink: [PinkNoise; 2],
+}
+
+impl CompiledTestToneOverride {
+    /// Whether the test tone is enabled, in which case all audio
+    /// is being replaced
+    pub fn is_enabled(&self) -> bool {
+        self.tone.is_enabled()
+    }
+
+    /// Replace the chunk with the next chunk of the test tone,
+    /// if it is enabled
+    pub fn process(&mut self, chunk: &mut SoundChunk) {
+        if !self.tone.is_enabled() {
+            return;
+        }
+        let rms = 10.0_f32.powf(self.tone.level() / 20.0);
+        match self.tone.signal() {
+            TestToneSignal::Sine => {
+                let amplitude = rms * std::f32::consts::SQRT_2;
+                let phase_increment = TEST_TONE_FREQUENCY / SAMPLE_FREQUENCY as f32;
+                for (l, r) in chunk.l.iter_mut().zip(chunk.r.iter_mut()) {
+                    let x = amplitude * (std::f32::consts::TAU * self.phase).sin();
+                    *l = x;
+                    *r = x;
+                    self.phase = (self.phase + phase_increment).fract();
+                }
+            }
+            TestToneSignal::PinkNoise => {
+                let gain = rms / PINK_NOISE_RMS;
+                for s in &mut chunk.l {
+                    *s = gain * self.pink[0].next(&mut self.rng);
+                }
+                for s in &mut chunk.r {
+                    *s = gain * self.pink[1].next(&mut self.rng);
+                }
+            }
+        }
+    }
+}
+
+impl CompiledProcessorComponent for CompiledTestToneOverride {
+    fn visit(&self, _visitor: &mut dyn CompiledComponentVisitor) {}
+}
+
+impl StartOver for CompiledTestToneOverride {
+    fn start_over(&mut self) {}
+}
@@ -1,10 +1,27 @@
 use hashstash::{Stashable, Stasher, UnstashError, Unstashable, Unstasher};
 
 use super::{
+    resample::{OversampleFactor, Oversampler, OVERSAMPLE_TAPS_PER_PHASE},
     soundchunk::{SoundChunk, CHUNK_SIZE},
     stashing::{StashingContext, UnstashingContext},
 };
 
+/// The loudest points of a buffer, in decibels relative to full scale
+#[derive(Clone, Copy, Debug)]
+pub struct PeakLevels {
+    /// The level of the largest sample
+    pub sample_peak: f32,
+
+    /// The level of the largest point of the signal reconstructed
+    /// between samples, measured at 4x oversampling. This can exceed
+    /// the sample peak, and is what a DAC may actually output.
+    pub true_peak: f32,
+}
+
+fn amplitude_to_dbfs(amplitude: f32) -> f32 {
+    20.0 * amplitude.log10()
+}
+
 pub struct SoundBuffer {
     chunks: Vec<SoundChunk>,
     sample_len: usize, // to account for unused portion of last chunk
@@ -64,6 +81,49 @@ impl SoundBuffer {
             .map(|(l, r)| [l, r])
     }
 
+    /// Measure the sample peak and true peak across both channels.
+    /// An empty or silent buffer has peaks of negative infinity.
+    pub fn peak_levels(&self) -> PeakLevels {
+        let factor = OversampleFactor::X4;
+        let mut input = Vec::with_capacity(CHUNK_SIZE);
+        let mut oversampled = vec![0.0; CHUNK_SIZE * factor.ratio()];
+
+        let mut sample_peak: f32 = 0.0;
+        let mut true_peak: f32 = 0.0;
+
+        for c in 0..SoundChunk::NUM_CHANNELS {
+            let mut oversampler = Oversampler::new(factor);
+            let samples = self
+                .chunks
+                .iter()
+                .flat_map(|ch| ch.channel(c).iter().cloned())
+                .take(self.sample_len);
+            // Trailing zeros flush the interpolation filter so that
+            // the very end of the buffer is measured too
+            let flush = std::iter::repeat_n(0.0, OVERSAMPLE_TAPS_PER_PHASE);
+
+            let mut samples = samples.chain(flush).peekable();
+            while samples.peek().is_some() {
+                input.clear();
+                input.extend(samples.by_ref().take(CHUNK_SIZE));
+                let oversampled = &mut oversampled[..input.len() * factor.ratio()];
+                oversampler.upsample(&input, oversampled);
+
+                for x in &input {
+                    sample_peak = sample_peak.max(x.abs());
+                }
+                for x in oversampled.iter() {
+                    true_peak = true_peak.max(x.abs());
+                }
+            }
+        }
+
+        PeakLevels {
+            sample_peak: amplitude_to_dbfs(sample_peak),
+            true_peak: amplitude_to_dbfs(true_peak.max(sample_peak)),
+        }
+    }
+
     pub fn push_chunk(&mut self, ch: &SoundChunk) {
         let offset = self.sample_len % CHUNK_SIZE;
         let split_ch = CHUNK_SIZE - offset;
@@ -7,6 +7,9 @@ pub struct SoundChunk {
 }
 
 impl SoundChunk {
+    /// The number of audio channels held by every chunk
+    pub const NUM_CHANNELS: usize = 2;
+
     pub fn new() -> SoundChunk {
         SoundChunk {
             l: [0.0; CHUNK_SIZE],
@@ -34,6 +37,22 @@ impl SoundChunk {
     pub fn samples_mut<'a>(&'a mut self) -> impl 'a + Iterator<Item = (&mut f32, &mut f32)> {
         self.l.iter_mut().zip(self.r.iter_mut())
     }
+
+    pub fn channel(&self, index: usize) -> &[f32; CHUNK_SIZE] {
+        match index {
+            0 => &self.l,
+            1 => &self.r,
+            _ => panic!("Channel index {} is out of range", index),
+        }
+    }
+
+    pub fn channel_mut(&mut self, index: usize) -> &mut [f32; CHUNK_SIZE] {
+        match index {
+            0 => &mut self.l,
+            1 => &mut self.r,
+            _ => panic!("Channel index {} is out of range", index),
+        }
+    }
 }
 
 impl Default for SoundChunk {
@@ -1,7 +1,14 @@
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+};
+
 use hashstash::{Stashable, Stasher};
 
 use super::{
-    expression::expressionobject::ExpressionObjectFactory, sound::soundobject::SoundObjectFactory,
+    audiofileio::default_base_directory,
+    expression::expressionobject::ExpressionObjectFactory,
+    sound::{migration::MigrationError, soundobject::SoundObjectFactory},
 };
 
 #[derive(Copy, Clone)]
@@ -37,6 +44,14 @@ impl Stashable<()> for StashingContext {
 pub struct UnstashingContext<'a> {
     sound_object_factory: &'a SoundObjectFactory,
     expression_object_factory: &'a ExpressionObjectFactory,
+
+    /// Where to put the reason that a sound processor couldn't be
+    /// migrated from the version it was stashed under, if anywhere
+    migration_error: Option<&'a Cell<Option<MigrationError>>>,
+
+    /// The directory which audio files referred to by relative paths are
+    /// looked for in, if other than the default
+    base_directory: Option<&'a Path>,
 }
 
 impl<'a> UnstashingContext<'a> {
@@ -47,9 +62,31 @@ impl<'a> UnstashingContext<'a> {
         UnstashingContext {
             sound_object_factory,
             expression_object_factory,
+            migration_error: None,
+            base_directory: None,
         }
     }
 
+    /// Put the reason that any sound processor couldn't be migrated into
+    /// the given cell, since unstashing then fails only as corrupted.
+    /// Otherwise, the reason is printed.
+    #[cfg(test)]
+    pub(crate) fn with_migration_error(
+        mut self,
+        migration_error: &'a Cell<Option<MigrationError>>,
+    ) -> UnstashingContext<'a> {
+        self.migration_error = Some(migration_error);
+        self
+    }
+
+    /// Look for audio files referred to by relative paths in the given
+    /// directory, as when the patch has moved there
+    #[cfg(test)]
+    pub(crate) fn with_base_directory(mut self, base_directory: &'a Path) -> UnstashingContext<'a> {
+        self.base_directory = Some(base_directory);
+        self
+    }
+
     pub(crate) fn sound_object_factory(&self) -> &'a SoundObjectFactory {
         self.sound_object_factory
     }
@@ -57,6 +94,20 @@ impl<'a> UnstashingContext<'a> {
     pub(crate) fn expression_object_factory(&self) -> &'a ExpressionObjectFactory {
         self.expression_object_factory
     }
+
+    pub(crate) fn base_directory(&self) -> PathBuf {
+        match self.base_directory {
+            Some(base_directory) => base_directory.to_path_buf(),
+            None => default_base_directory(),
+        }
+    }
+
+    pub(crate) fn report_migration_error(&self, error: MigrationError) {
+        match self.migration_error {
+            Some(migration_error) => migration_error.set(Some(error)),
+            None => println!("{}", error.explain()),
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -0,0 +1,113 @@
+use crate::core::fft::{Complex, Fft};
+
+/// A streaming short-time fourier transform which analyzes its input
+/// in overlapping Hann-windowed frames, lets the caller modify each
+/// frame's spectrum, and resynthesizes the result by overlap-add.
+/// Frames hop by a quarter of their size, for which Hann analysis and
+/// synthesis windows sum to a constant, so leaving the spectrum as-is
+/// reconstructs the input exactly, delayed by `latency()` samples.
+pub struct Stft {
+    fft: Fft,
+    hop: usize,
+    window: Vec<f32>,
+    /// The most recent frame's worth of input samples
+    input: Vec<f32>,
+    /// Overlap-add accumulator, whose first `hop` samples are the next
+    /// to be output
+    output: Vec<f32>,
+    /// Number of samples taken since the last frame was processed
+    fill: usize,
+    bins: Vec<Complex>,
+}
+
+impl Stft {
+    /// Create a new STFT with the given frame size, which must be a
+    /// power of two and at least 4
+    pub fn new(size: usize) -> Stft {
+        assert!(size.is_power_of_two() && size >= 4);
+        let window = (0..size)
+            .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / size as f32).cos())
+            .collect();
+        Stft {
+            fft: Fft::new(size),
+            hop: size / 4,
+            window,
+            input: vec![0.0; size],
+            output: vec![0.0; size],
+            fill: 0,
+            bins: vec![Complex::ZERO; size],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.fft.size()
+    }
+
+    /// The number of bins passed to the spectrum callback, from DC up
+    /// to and including the Nyquist frequency
+    pub fn bin_count(&self) -> usize {
+        self.size() / 2 + 1
+    }
+
+    /// The delay in samples between input and output
+    pub fn latency(&self) -> usize {
+        self.size()
+    }
+
+    /// The factor by which a bin's magnitude is multiplied to give the
+    /// amplitude of a sinusoid centred on that bin
+    pub fn amplitude_scale(&self) -> f32 {
+        2.0 / self.window.iter().sum::<f32>()
+    }
+
+    /// Forget all buffered input and output
+    pub fn reset(&mut self) {
+        self.input.fill(0.0);
+        self.output.fill(0.0);
+        self.fill = 0;
+    }
+
+    /// Replace the given samples with the resynthesized output. Each time
+    /// a new frame is complete, `modify` is called with its positive
+    /// frequency bins, which it may change in place.
+    pub fn process<F: FnMut(&mut [Complex])>(&mut self, samples: &mut [f32], mut modify: F) {
+        let size = self.size();
+        for s in samples {
+            self.input[size - self.hop + self.fill] = *s;
+            *s = self.output[self.fill];
+            self.fill += 1;
+            if self.fill == self.hop {
+                self.process_frame(&mut modify);
+                self.fill = 0;
+            }
+        }
+    }
+
+    fn process_frame<F: FnMut(&mut [Complex])>(&mut self, modify: &mut F) {
+        let size = self.size();
+        let half = size / 2;
+
+        for ((b, x), w) in self.bins.iter_mut().zip(&self.input).zip(&self.window) {
+            *b = Complex::new(x * w, 0.0);
+        }
+        self.fft.forward(&mut self.bins);
+
+        modify(&mut self.bins[..=half]);
+
+        // Restore conjugate symmetry so that the output is real
+        for k in 1..half {
+            self.bins[size - k] = self.bins[k].conj();
+        }
+        self.fft.inverse(&mut self.bins);
+
+        self.output.copy_within(self.hop.., 0);
+        self.output[(size - self.hop)..].fill(0.0);
+        // Squared Hann windows at a quarter-frame hop sum to 3/2
+        let scale = 2.0 / 3.0;
+        for ((o, b), w) in self.output.iter_mut().zip(&self.bins).zip(&self.window) {
+            *o += b.re * w * scale;
+        }
+
+        self.input.copy_within(self.hop.., 0);
+    }
+}
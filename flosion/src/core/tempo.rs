@@ -0,0 +1,38 @@
+use crate::core::samplefrequency::SAMPLE_FREQUENCY;
+
+/// The speed and meter of musical time, through which beats and
+/// bars are related to time in samples
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Tempo {
+    pub beats_per_minute: f64,
+    pub beats_per_bar: u32,
+}
+
+impl Tempo {
+    pub fn new(beats_per_minute: f64, beats_per_bar: u32) -> Tempo {
+        Tempo {
+            beats_per_minute,
+            beats_per_bar,
+        }
+    }
+
+    /// The number of samples which make up one beat
+    pub fn samples_per_beat(&self) -> f64 {
+        60.0 / self.beats_per_minute * SAMPLE_FREQUENCY as f64
+    }
+
+    /// The number of samples which make up one bar
+    pub fn samples_per_bar(&self) -> f64 {
+        self.samples_per_beat() * self.beats_per_bar as f64
+    }
+
+    /// The number of bars which pass each second
+    pub fn bars_per_second(&self) -> f64 {
+        self.beats_per_minute / (60.0 * self.beats_per_bar as f64)
+    }
+
+    /// The fraction of a bar which passes with each sample
+    pub fn bars_per_sample(&self) -> f64 {
+        1.0 / self.samples_per_bar()
+    }
+}
@@ -0,0 +1,73 @@
+use hashstash::Stash;
+
+use crate::core::{
+    automation::{AutomationLane, Breakpoint, Interpolation},
+    tempo::Tempo,
+};
+
+#[test]
+fn test_ramp_over_one_bar_reaches_half_at_midpoint() {
+    let tempo = Tempo::new(120.0, 4);
+
+    let mut lane = AutomationLane::new();
+    lane.add_breakpoint(Breakpoint::new(0.0, 0.0, Interpolation::Linear));
+    lane.add_breakpoint(Breakpoint::new(1.0, 1.0, Interpolation::Linear));
+
+    // At 120 beats per minute, a bar of four beats lasts two seconds
+    let samples_per_bar = tempo.samples_per_bar() as usize;
+    let mut values = vec![0.0; samples_per_bar + 1];
+    lane.fill(&mut values, 0.0, tempo.bars_per_sample());
+
+    assert_eq!(values[0], 0.0);
+    assert!((values[samples_per_bar / 2] - 0.5).abs() < 1e-6);
+    assert!((values[samples_per_bar] - 1.0).abs() < 1e-6);
+
+    // Sample-accurate evaluation agrees with evaluating at single positions
+    for i in [1, 100, samples_per_bar / 3, samples_per_bar - 1] {
+        let position = i as f64 * tempo.bars_per_sample();
+        assert_eq!(values[i], lane.value_at(position).unwrap());
+    }
+}
+
+#[test]
+fn test_interpolation_modes() {
+    let mut lane = AutomationLane::new();
+    lane.add_breakpoint(Breakpoint::new(0.0, 2.0, Interpolation::Step));
+    lane.add_breakpoint(Breakpoint::new(1.0, 4.0, Interpolation::Smooth));
+    lane.add_breakpoint(Breakpoint::new(2.0, 0.0, Interpolation::Linear));
+
+    // Values are held outside of the breakpoints
+    assert_eq!(lane.value_at(-1.0), Some(2.0));
+    assert_eq!(lane.value_at(5.0), Some(0.0));
+
+    // Stepping holds the first value until the next breakpoint
+    assert_eq!(lane.value_at(0.99), Some(2.0));
+    assert_eq!(lane.value_at(1.0), Some(4.0));
+
+    // Smooth interpolation eases out of and into each breakpoint
+    assert_eq!(lane.value_at(1.5), Some(2.0));
+    assert!(lane.value_at(1.1).unwrap() > 4.0 - 0.4);
+    assert!(lane.value_at(1.9).unwrap() < 0.4);
+
+    assert_eq!(AutomationLane::new().value_at(0.0), None);
+}
+
+#[test]
+fn test_adding_breakpoints_keeps_them_sorted() {
+    let mut lane = AutomationLane::new();
+    lane.add_breakpoint(Breakpoint::new(2.0, 1.0, Interpolation::Linear));
+    lane.add_breakpoint(Breakpoint::new(0.0, 1.0, Interpolation::Linear));
+    lane.add_breakpoint(Breakpoint::new(1.0, 1.0, Interpolation::Linear));
+
+    // Adding at an existing position replaces that breakpoint
+    lane.add_breakpoint(Breakpoint::new(1.0, 3.0, Interpolation::Step));
+
+    let positions: Vec<f64> = lane.breakpoints().iter().map(|b| b.position).collect();
+    assert_eq!(positions, vec![0.0, 1.0, 2.0]);
+    assert_eq!(lane.breakpoints()[1].value, 3.0);
+
+    let stash = Stash::new();
+    let handle = stash.stash(&lane);
+    let unstashed: AutomationLane = stash.unstash(&handle).unwrap();
+    assert_eq!(unstashed.breakpoints(), lane.breakpoints());
+}
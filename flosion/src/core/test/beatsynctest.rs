@@ -0,0 +1,41 @@
+use crate::core::{
+    beatsync::{BeatSync, RetriggerInterval},
+    soundchunk::CHUNK_SIZE,
+    tempo::Tempo,
+};
+
+/// Every sample within the first `len` samples which is retriggered
+fn retriggered_samples(beat_sync: &BeatSync, len: usize) -> Vec<usize> {
+    (0..len.div_ceil(CHUNK_SIZE))
+        .flat_map(|c| {
+            let first = c * CHUNK_SIZE;
+            beat_sync
+                .retriggered_samples(first)
+                .into_iter()
+                .enumerate()
+                .filter(|(_, r)| *r)
+                .map(move |(i, _)| first + i)
+        })
+        .filter(|s| *s < len)
+        .collect()
+}
+
+#[test]
+fn test_retriggers_on_boundaries() {
+    // A beat at 97 beats per minute is 27278.35 samples long, so each
+    // boundary falls between samples and the next sample is retriggered
+    let mut beat_sync = BeatSync {
+        tempo: Tempo::new(97.0, 3),
+        interval: RetriggerInterval::Beat,
+    };
+    assert_eq!(
+        retriggered_samples(&beat_sync, 100_000),
+        vec![27279, 54557, 81836]
+    );
+
+    beat_sync.interval = RetriggerInterval::Bar;
+    assert_eq!(retriggered_samples(&beat_sync, 100_000), vec![81836]);
+
+    beat_sync.interval = RetriggerInterval::Never;
+    assert!(retriggered_samples(&beat_sync, 100_000).is_empty());
+}
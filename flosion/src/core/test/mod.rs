@@ -0,0 +1,5 @@
+mod automationtest;
+mod beatsynctest;
+mod oversampletest;
+mod soundbuffertest;
+mod stfttest;
@@ -0,0 +1,95 @@
+use crate::core::{
+    fft::{Complex, Fft},
+    resample::{OversampleFactor, Oversampler},
+};
+
+const ANALYSIS_SIZE: usize = 4096;
+
+/// Frequency bin of the test tone, roughly 5 kHz at 44.1 kHz
+const TONE_BIN: usize = 467;
+
+/// Pass a loud sine wave through a hard clipper at the given
+/// oversampling factor and return the steady-state output
+fn clip_sine(factor: OversampleFactor) -> Vec<f32> {
+    let len = 3 * ANALYSIS_SIZE;
+    let input: Vec<f32> = (0..len)
+        .map(|i| {
+            let phase = std::f32::consts::TAU * (TONE_BIN * i) as f32 / ANALYSIS_SIZE as f32;
+            4.0 * phase.sin()
+        })
+        .collect();
+
+    let mut oversampler = Oversampler::new(factor);
+    let mut oversampled = vec![0.0; len * factor.ratio()];
+    oversampler.upsample(&input, &mut oversampled);
+    for x in &mut oversampled {
+        *x = x.clamp(-1.0, 1.0);
+    }
+    let mut output = vec![0.0; len];
+    oversampler.downsample(&oversampled, &mut output);
+
+    output.split_off(len - ANALYSIS_SIZE)
+}
+
+/// The fraction of the signal's energy which lies away from the
+/// harmonics of the tone that are below Nyquist, i.e. which was
+/// folded back from above Nyquist
+fn aliasing_energy(signal: &[f32]) -> f32 {
+    let mut bins: Vec<Complex> = signal.iter().map(|x| Complex::new(*x, 0.0)).collect();
+    Fft::new(ANALYSIS_SIZE).forward(&mut bins);
+
+    let is_harmonic = |k: usize| {
+        (1..)
+            .step_by(2)
+            .map(|h| h * TONE_BIN)
+            .take_while(|f| *f <= ANALYSIS_SIZE / 2)
+            .any(|f| k.abs_diff(f) <= 1)
+    };
+
+    let mut total = 0.0;
+    let mut aliased = 0.0;
+    for (k, b) in bins.iter().enumerate().take(ANALYSIS_SIZE / 2 + 1) {
+        let energy = b.norm() * b.norm();
+        total += energy;
+        if k > 0 && !is_harmonic(k) {
+            aliased += energy;
+        }
+    }
+    aliased / total
+}
+
+#[test]
+fn test_oversampling_reduces_clipping_aliases() {
+    let plain = aliasing_energy(&clip_sine(OversampleFactor::None));
+    let x2 = aliasing_energy(&clip_sine(OversampleFactor::X2));
+    let x4 = aliasing_energy(&clip_sine(OversampleFactor::X4));
+
+    assert!(plain > 0.01, "plain clipping aliased only {}", plain);
+    assert!(x2 < 0.5 * plain, "2x aliasing {} vs {} without", x2, plain);
+    assert!(x4 < 0.1 * plain, "4x aliasing {} vs {} without", x4, plain);
+    assert!(x4 < x2);
+}
+
+#[test]
+fn test_oversampling_passes_audio_band() {
+    for factor in [OversampleFactor::X2, OversampleFactor::X4] {
+        let len = 4096;
+        let input: Vec<f32> = (0..len).map(|i| (i as f32 * 0.1).sin()).collect();
+
+        let mut oversampler = Oversampler::new(factor);
+        let mut oversampled = vec![0.0; len * factor.ratio()];
+        oversampler.upsample(&input, &mut oversampled);
+        let mut output = vec![0.0; len];
+        oversampler.downsample(&oversampled, &mut output);
+
+        // Ignore the start while the filters fill up
+        let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+        let ratio = rms(&output[1024..]) / rms(&input[1024..]);
+        assert!(
+            (ratio - 1.0).abs() < 0.01,
+            "{:?} changed level by {}",
+            factor,
+            ratio
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use crate::core::soundbuffer::SoundBuffer;
+
+#[test]
+fn test_true_peak_exceeds_sample_peak() {
+    // A full-scale sine at a quarter of the sample rate, offset by an
+    // eighth of a cycle, is only ever sampled at +/- 0.707 while the
+    // wave itself passes through +/- 1 between samples
+    let mut buffer = SoundBuffer::new_empty();
+    for i in 0..4096 {
+        let phase = std::f32::consts::FRAC_PI_2 * i as f32 + std::f32::consts::FRAC_PI_4;
+        let x = phase.sin();
+        buffer.push_sample(x, x);
+    }
+
+    let peaks = buffer.peak_levels();
+
+    assert!((peaks.sample_peak - (-3.01)).abs() < 0.05, "{:?}", peaks);
+    assert!(peaks.true_peak > peaks.sample_peak + 2.0, "{:?}", peaks);
+    assert!(peaks.true_peak.abs() < 0.5, "{:?}", peaks);
+}
+
+#[test]
+fn test_peak_of_low_frequency_signal() {
+    // Slowly varying signals have no meaningful inter-sample peaks.
+    // Whole cycles are used so that the signal doesn't stop abruptly,
+    // which would ring and overshoot.
+    let mut buffer = SoundBuffer::new_empty();
+    for i in 0..3000 {
+        let x = 0.5 * (std::f32::consts::TAU * i as f32 / 1000.0).sin();
+        buffer.push_sample(x, -x);
+    }
+
+    let peaks = buffer.peak_levels();
+
+    assert!((peaks.sample_peak - (-6.02)).abs() < 0.05, "{:?}", peaks);
+    assert!(
+        (peaks.true_peak - peaks.sample_peak).abs() < 0.1,
+        "{:?}",
+        peaks
+    );
+}
+
+#[test]
+fn test_peak_of_silence() {
+    let peaks = SoundBuffer::new_empty().peak_levels();
+    assert_eq!(peaks.sample_peak, f32::NEG_INFINITY);
+    assert_eq!(peaks.true_peak, f32::NEG_INFINITY);
+}
@@ -0,0 +1,65 @@
+use crate::core::{
+    fft::{Complex, Fft},
+    stft::Stft,
+};
+
+#[test]
+fn test_fft_matches_naive_dft() {
+    let size = 64;
+    let signal: Vec<Complex> = (0..size)
+        .map(|i| Complex::new((i as f32 * 0.37).sin(), (i as f32 * 1.3).cos() * 0.5))
+        .collect();
+
+    let mut transformed = signal.clone();
+    let fft = Fft::new(size);
+    fft.forward(&mut transformed);
+
+    for (k, actual) in transformed.iter().enumerate() {
+        let expected = signal
+            .iter()
+            .enumerate()
+            .fold(Complex::ZERO, |acc, (n, x)| {
+                let angle = -std::f32::consts::TAU * (k * n) as f32 / size as f32;
+                acc + *x * Complex::from_angle(angle)
+            });
+        assert!(
+            (*actual - expected).norm() < 1e-3,
+            "bin {} was {:?}, expected {:?}",
+            k,
+            actual,
+            expected
+        );
+    }
+
+    fft.inverse(&mut transformed);
+    for (actual, expected) in transformed.iter().zip(&signal) {
+        assert!((*actual - *expected).norm() < 1e-5);
+    }
+}
+
+#[test]
+fn test_stft_reconstructs_delayed_input() {
+    let mut stft = Stft::new(256);
+    let latency = stft.latency();
+    let len = 4000;
+    let signal: Vec<f32> = (0..len)
+        .map(|i| (i as f32 * 0.05).sin() + 0.3 * (i as f32 * 0.71).cos())
+        .collect();
+
+    // Process in uneven pieces to exercise frames straddling calls
+    let mut output = signal.clone();
+    for piece in output.chunks_mut(100) {
+        stft.process(piece, |_| ());
+    }
+
+    assert!(output[..latency].iter().all(|x| x.abs() < 1e-6));
+    for i in (2 * latency)..len {
+        assert!(
+            (output[i] - signal[i - latency]).abs() < 1e-4,
+            "sample {} was {}, expected {}",
+            i,
+            output[i],
+            signal[i - latency]
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use std::cell::RefCell;
+
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        beatsync::BeatSync,
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    objects::keyboard::KeyId,
+    ui_core::arguments::{ArgumentEnum, EnumArgument, ParsedArguments},
+};
+
+/// The maximum number of notes which can be held at once
+const ARPEGGIATOR_MAX_NOTES: usize = 16;
+
+/// The order in which held notes are stepped through
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArpeggiatorPattern {
+    /// From the lowest note to the highest, then repeat
+    Up,
+    /// From the highest note to the lowest, then repeat
+    Down,
+    /// From the lowest note to the highest and back again,
+    /// without repeating the highest and lowest notes
+    UpDown,
+    /// A randomly chosen held note at each step
+    Random,
+}
+
+impl ArpeggiatorPattern {
+    fn to_u8(self) -> u8 {
+        match self {
+            ArpeggiatorPattern::Up => 0,
+            ArpeggiatorPattern::Down => 1,
+            ArpeggiatorPattern::UpDown => 2,
+            ArpeggiatorPattern::Random => 3,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<ArpeggiatorPattern> {
+        match x {
+            0 => Some(ArpeggiatorPattern::Up),
+            1 => Some(ArpeggiatorPattern::Down),
+            2 => Some(ArpeggiatorPattern::UpDown),
+            3 => Some(ArpeggiatorPattern::Random),
+            _ => None,
+        }
+    }
+}
+
+impl ArgumentEnum for ArpeggiatorPattern {
+    fn all_values() -> &'static [ArpeggiatorPattern] {
+        &[
+            ArpeggiatorPattern::Up,
+            ArpeggiatorPattern::Down,
+            ArpeggiatorPattern::UpDown,
+            ArpeggiatorPattern::Random,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ArpeggiatorPattern::Up => "up",
+            ArpeggiatorPattern::Down => "down",
+            ArpeggiatorPattern::UpDown => "updown",
+            ArpeggiatorPattern::Random => "random",
+        }
+    }
+}
+
+// TODO: remove 'Default from spmcq, allow uninit
+#[derive(Clone, Copy, Default)]
+enum ArpeggiatorCommand {
+    StartKey {
+        id: KeyId,
+        frequency: f32,
+    },
+    ReleaseKey {
+        id: KeyId,
+    },
+    #[default]
+    ReleaseAllKeys,
+}
+
+pub struct ArpeggiatorState {
+    command_reader: spmcq::Reader<ArpeggiatorCommand>,
+    pattern: ArpeggiatorPattern,
+    beat_sync: BeatSync,
+
+    /// The held notes, sorted by ascending frequency
+    notes: Vec<(KeyId, f32)>,
+
+    /// The number of steps taken since the first note was held
+    step: usize,
+
+    /// Index into the held notes of the current note
+    index: usize,
+
+    /// Fraction of the current step which has elapsed
+    phase: f32,
+
+    /// Whether a step began at the very end of the previous chunk,
+    /// such that the input is yet to be started over for it
+    retrigger_pending: bool,
+
+    rng: SmallRng,
+}
+
+impl ArpeggiatorState {
+    fn start_key(&mut self, id: KeyId, frequency: f32) {
+        if let Some(note) = self.notes.iter_mut().find(|(i, _)| *i == id) {
+            note.1 = frequency;
+        } else if self.notes.len() < ARPEGGIATOR_MAX_NOTES {
+            self.notes.push((id, frequency));
+        }
+        self.notes.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+    }
+
+    fn release_key(&mut self, id: KeyId) {
+        self.notes.retain(|(i, _)| *i != id);
+    }
+
+    /// The index of the held note to play at the current step
+    fn note_index(&mut self) -> usize {
+        let n = self.notes.len();
+        match self.pattern {
+            ArpeggiatorPattern::Up => self.step % n,
+            ArpeggiatorPattern::Down => n - 1 - (self.step % n),
+            ArpeggiatorPattern::UpDown => {
+                if n == 1 {
+                    return 0;
+                }
+                let period = 2 * n - 2;
+                let position = self.step % period;
+                if position < n {
+                    position
+                } else {
+                    period - position
+                }
+            }
+            ArpeggiatorPattern::Random => self.rng.gen_range(0..n),
+        }
+    }
+}
+
+impl ProcessorState for ArpeggiatorState {
+    type Processor = Arpeggiator;
+
+    fn new(processor: &Arpeggiator) -> Self {
+        ArpeggiatorState {
+            command_reader: processor.command_reader.clone(),
+            pattern: processor.pattern,
+            beat_sync: processor.beat_sync,
+            notes: Vec::with_capacity(ARPEGGIATOR_MAX_NOTES),
+            step: 0,
+            index: 0,
+            phase: 0.0,
+            retrigger_pending: false,
+            rng: SmallRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl StartOver for ArpeggiatorState {
+    fn start_over(&mut self) {
+        self.notes.clear();
+        self.step = 0;
+        self.index = 0;
+        self.phase = 0.0;
+        self.retrigger_pending = false;
+        self.rng = SmallRng::seed_from_u64(0);
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Arpeggiator {
+    pub input: SingleInput,
+    pub note_frequency: ProcessorArgument<PlainF32ArrayArgument>,
+    pub rate: ProcessorExpression,
+
+    #[not_a_component]
+    pattern: ArpeggiatorPattern,
+
+    #[not_a_component]
+    beat_sync: BeatSync,
+
+    #[not_a_component]
+    command_reader: spmcq::Reader<ArpeggiatorCommand>,
+
+    #[not_a_component]
+    command_writer: RefCell<spmcq::Writer<ArpeggiatorCommand>>,
+
+    #[state]
+    state: StateMarker<ArpeggiatorState>,
+}
+
+impl Arpeggiator {
+    pub const ARG_PATTERN: EnumArgument<ArpeggiatorPattern> = EnumArgument::new("pattern");
+
+    pub fn pattern(&self) -> ArpeggiatorPattern {
+        self.pattern
+    }
+
+    pub fn set_pattern(&mut self, pattern: ArpeggiatorPattern) {
+        self.pattern = pattern;
+    }
+
+    pub fn beat_sync(&self) -> BeatSync {
+        self.beat_sync
+    }
+
+    /// Restart the pattern from its first step on every beat or bar
+    pub fn set_beat_sync(&mut self, beat_sync: BeatSync) {
+        self.beat_sync = beat_sync;
+    }
+
+    pub fn start_key(&self, id: KeyId, frequency: f32) {
+        self.command_writer
+            .borrow_mut()
+            .write(ArpeggiatorCommand::StartKey { id, frequency });
+    }
+
+    pub fn release_key(&self, id: KeyId) {
+        self.command_writer
+            .borrow_mut()
+            .write(ArpeggiatorCommand::ReleaseKey { id });
+    }
+
+    pub fn release_all_keys(&self) {
+        self.command_writer
+            .borrow_mut()
+            .write(ArpeggiatorCommand::ReleaseAllKeys);
+    }
+}
+
+impl SoundProcessor for Arpeggiator {
+    fn new(args: &ParsedArguments) -> Arpeggiator {
+        let message_queue_size = 16; // idk
+        let note_frequency = ProcessorArgument::new();
+        let (command_reader, command_writer) = spmcq::ring_buffer(message_queue_size);
+        Arpeggiator {
+            input: SingleInput::new_anisochronic(ArgumentScope::new(vec![note_frequency.id()])),
+            note_frequency,
+            rate: ProcessorExpression::new(&[8.0], ArgumentScope::new_empty()),
+            pattern: args
+                .get(&Arpeggiator::ARG_PATTERN)
+                .unwrap_or(ArpeggiatorPattern::Up),
+            beat_sync: BeatSync::new(),
+            command_reader,
+            command_writer: RefCell::new(command_writer),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn process_audio(
+        arp: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let was_holding = !arp.state.notes.is_empty();
+        while let Some(msg) = arp.state.command_reader.read().value() {
+            match msg {
+                ArpeggiatorCommand::StartKey { id, frequency } => {
+                    arp.state.start_key(id, frequency);
+                }
+                ArpeggiatorCommand::ReleaseKey { id } => {
+                    arp.state.release_key(id);
+                }
+                ArpeggiatorCommand::ReleaseAllKeys => {
+                    arp.state.notes.clear();
+                }
+            }
+        }
+
+        if arp.state.notes.is_empty() {
+            dst.silence();
+            return StreamStatus::Playing;
+        }
+
+        let mut retrigger_offset = None;
+        if !was_holding {
+            // Start from the first step as soon as any note is held
+            arp.state.step = 0;
+            arp.state.phase = 0.0;
+            arp.state.index = arp.state.note_index();
+            retrigger_offset = Some(0);
+        } else if arp.state.retrigger_pending {
+            retrigger_offset = Some(0);
+        }
+        arp.state.retrigger_pending = false;
+
+        // Notes may have been released since the last step
+        arp.state.index = arp.state.index.min(arp.state.notes.len() - 1);
+
+        let rate = arp
+            .rate
+            .eval_scalar(
+                Discretization::chunkwise_temporal(),
+                ExpressionContext::new(context),
+            )
+            .max(0.0);
+        let phase_step = rate / SAMPLE_FREQUENCY as f32;
+
+        let first_sample = context.current_processor_timing().elapsed_chunks() * CHUNK_SIZE;
+        let retriggered = arp.state.beat_sync.retriggered_samples(first_sample);
+
+        let mut frequency = [0.0; CHUNK_SIZE];
+        for (i, f) in frequency.iter_mut().enumerate() {
+            if retriggered[i] {
+                arp.state.step = 0;
+                arp.state.phase = 0.0;
+                arp.state.index = arp.state.note_index();
+                retrigger_offset.get_or_insert(i);
+            }
+            *f = arp.state.notes[arp.state.index].1;
+            arp.state.phase += phase_step;
+            if arp.state.phase >= 1.0 {
+                arp.state.phase -= 1.0;
+                arp.state.step += 1;
+                arp.state.index = arp.state.note_index();
+
+                // The input is started over at most once per chunk, at
+                // the first sample of the chunk which has a new note
+                if i + 1 < CHUNK_SIZE {
+                    retrigger_offset.get_or_insert(i + 1);
+                } else {
+                    arp.state.retrigger_pending = true;
+                }
+            }
+        }
+
+        if let Some(offset) = retrigger_offset {
+            arp.input.start_over_at(offset);
+        }
+
+        arp.input.step(
+            dst,
+            InputContext::new(context).push(arp.note_frequency, &frequency),
+        );
+
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for Arpeggiator {
+    const TYPE: ObjectType = ObjectType::new("arpeggiator");
+}
+
+impl Stashable<StashingContext> for Arpeggiator {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.note_frequency);
+        stasher.object(&self.rate);
+        stasher.u8(self.pattern.to_u8());
+        stasher.object_with_context(&self.beat_sync, ());
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for Arpeggiator {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext<'_>>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.note_frequency)?;
+        unstasher.object_inplace(&mut self.rate)?;
+        let pattern =
+            ArpeggiatorPattern::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.pattern = pattern;
+        }
+        unstasher.object_inplace_with_context(&mut self.beat_sync, ())?;
+        Ok(())
+    }
+}
@@ -1,5 +1,13 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    ops::Deref,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
+use atomic_float::AtomicF32;
 use flosion_macros::ProcessorComponent;
 use hashstash::{
     HashCache, InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace,
@@ -8,8 +16,9 @@ use parking_lot::Mutex;
 
 use crate::{
     core::{
-        audiofileio::load_audio_file,
+        audiofileio::{default_base_directory, load_audio_file, AudioFilePath},
         objecttype::{ObjectType, WithObjectType},
+        resample::lerp,
         sound::{
             context::AudioContext,
             soundprocessor::{
@@ -23,11 +32,43 @@ use crate::{
     ui_core::arguments::{FilePathArgument, ParsedArguments},
 };
 
+/// The slowest and fastest playback speeds of an AudioClip
+pub const AUDIOCLIP_MIN_SPEED: f32 = 0.125;
+pub const AUDIOCLIP_MAX_SPEED: f32 = 8.0;
+
+/// Playback settings, shared with the audio thread
+pub struct AudioClipSettings {
+    /// The number of samples over which the end of the clip is
+    /// blended into its start when looping
+    loop_crossfade: AtomicUsize,
+
+    reverse: AtomicBool,
+
+    speed: AtomicF32,
+}
+
 #[derive(ProcessorComponent)]
 pub struct AudioClip {
     #[not_a_component]
     data: Arc<Mutex<HashCache<SoundBuffer>>>,
 
+    #[not_a_component]
+    settings: Arc<AudioClipSettings>,
+
+    /// The file which the clip was loaded from, if any
+    #[not_a_component]
+    source: Option<AudioFilePath>,
+
+    /// Whether the source file could not be found when the clip was
+    /// created or unstashed, and so needs relinking
+    #[not_a_component]
+    source_missing: bool,
+
+    /// Why loading the source file failed when the clip was created,
+    /// if it did
+    #[not_a_component]
+    load_error: Option<String>,
+
     #[state]
     state: StateMarker<AudioClipState>,
 }
@@ -40,6 +81,71 @@ impl AudioClip {
     pub fn get_data<'a>(&'a self) -> impl 'a + Deref<Target = HashCache<SoundBuffer>> {
         self.data.lock()
     }
+
+    /// Load the clip's data from the given audio file, which then becomes
+    /// its source. On failure, the clip is left unchanged.
+    pub fn load_file(&mut self, file: AudioFilePath) -> Result<(), String> {
+        let buffer = load_audio_file(file.absolute())?;
+        self.set_data(buffer);
+        self.source = Some(file);
+        self.source_missing = false;
+        self.load_error = None;
+        Ok(())
+    }
+
+    /// The file which the clip was loaded from, if any
+    pub fn source(&self) -> Option<&AudioFilePath> {
+        self.source.as_ref()
+    }
+
+    /// The source file, if it could not be found when the clip was
+    /// created or unstashed
+    pub fn missing_file(&self) -> Option<&Path> {
+        self.source
+            .as_ref()
+            .filter(|_| self.source_missing)
+            .map(AudioFilePath::absolute)
+    }
+
+    /// Why loading the source file failed when the clip was created,
+    /// if it did
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    /// The number of samples over which the loop boundary is crossfaded,
+    /// or zero to wrap around abruptly
+    pub fn loop_crossfade(&self) -> usize {
+        self.settings.loop_crossfade.load(Ordering::Relaxed)
+    }
+
+    pub fn set_loop_crossfade(&self, samples: usize) {
+        self.settings
+            .loop_crossfade
+            .store(samples, Ordering::Relaxed);
+    }
+
+    /// Whether the clip is played back to front
+    pub fn reverse(&self) -> bool {
+        self.settings.reverse.load(Ordering::Relaxed)
+    }
+
+    pub fn set_reverse(&self, reverse: bool) {
+        self.settings.reverse.store(reverse, Ordering::Relaxed);
+    }
+
+    /// The rate of playback, where 1 is the original speed and 0.5 is
+    /// half speed and an octave lower
+    pub fn speed(&self) -> f32 {
+        self.settings.speed.load(Ordering::Relaxed)
+    }
+
+    pub fn set_speed(&self, speed: f32) {
+        self.settings.speed.store(
+            speed.clamp(AUDIOCLIP_MIN_SPEED, AUDIOCLIP_MAX_SPEED),
+            Ordering::Relaxed,
+        );
+    }
 }
 
 impl AudioClip {
@@ -49,7 +155,12 @@ impl AudioClip {
 pub struct AudioClipState {
     // TODO: make this nicer
     data: Arc<Mutex<HashCache<SoundBuffer>>>,
-    playhead: usize,
+    settings: Arc<AudioClipSettings>,
+
+    /// The fractional position within the clip, or None if playback
+    /// hasn't begun yet and should start from whichever end of the
+    /// clip the playback direction calls for
+    playhead: Option<f64>,
 }
 
 impl ProcessorState for AudioClipState {
@@ -58,33 +169,43 @@ impl ProcessorState for AudioClipState {
     fn new(processor: &AudioClip) -> Self {
         AudioClipState {
             data: Arc::clone(&processor.data),
-            playhead: 0,
+            settings: Arc::clone(&processor.settings),
+            playhead: None,
         }
     }
 }
 
 impl StartOver for AudioClipState {
     fn start_over(&mut self) {
-        self.playhead = 0;
+        self.playhead = None;
     }
 }
 
 impl SoundProcessor for AudioClip {
     fn new(args: &ParsedArguments) -> AudioClip {
-        let buffer = if let Some(path) = args.get(&Self::ARG_PATH) {
-            if let Ok(b) = load_audio_file(&path) {
-                b
-            } else {
-                println!("Failed to load audio file from \"{}\"", path.display());
-                SoundBuffer::new_empty()
-            }
-        } else {
-            SoundBuffer::new_empty()
-        };
-        AudioClip {
-            data: Arc::new(Mutex::new(HashCache::new(buffer))),
+        let mut audioclip = AudioClip {
+            data: Arc::new(Mutex::new(HashCache::new(SoundBuffer::new_empty()))),
+            settings: Arc::new(AudioClipSettings {
+                loop_crossfade: AtomicUsize::new(0),
+                reverse: AtomicBool::new(false),
+                speed: AtomicF32::new(1.0),
+            }),
+            source: None,
+            source_missing: false,
+            load_error: None,
             state: StateMarker::new(),
+        };
+        if let Some(path) = args.get(&Self::ARG_PATH) {
+            let file = AudioFilePath::new(&path, &default_base_directory());
+            // The clip is left empty but remembers where it was meant to
+            // come from, so that it can be relinked later
+            if let Err(e) = audioclip.load_file(file.clone()) {
+                audioclip.source_missing = !file.exists();
+                audioclip.load_error = Some(e);
+                audioclip.source = Some(file);
+            }
         }
+        audioclip
     }
 
     fn is_static(&self) -> bool {
@@ -102,27 +223,77 @@ impl SoundProcessor for AudioClip {
             dst.silence();
             return StreamStatus::Done;
         }
-        if audioclip.state.playhead >= data.sample_len() {
-            audioclip.state.playhead = 0;
-        }
+        let len = data.sample_len();
+        let settings = &*audioclip.state.settings;
+        // The crossfade can't be longer than the part of the clip
+        // which is played on every pass
+        let crossfade = settings.loop_crossfade.load(Ordering::Relaxed).min(len / 2);
+        let reverse = settings.reverse.load(Ordering::Relaxed);
+        let speed = settings.speed.load(Ordering::Relaxed) as f64;
+
+        // After the first pass, playback cycles through the part of the
+        // clip from just after the crossfade to the end
+        let loop_start = crossfade as f64;
+        let loop_length = (len - crossfade) as f64;
+
+        let mut playhead = audioclip.state.playhead.unwrap_or(match reverse {
+            false => 0.0,
+            true => (len - 1) as f64,
+        });
+
         for i in 0..CHUNK_SIZE {
-            // TODO: don't repeat this every sample
-            let ci = audioclip.state.playhead / CHUNK_SIZE;
-            let si = audioclip.state.playhead % CHUNK_SIZE;
-            let c = &data.chunks()[ci];
-            audioclip.state.playhead += 1;
-            if audioclip.state.playhead >= data.sample_len() {
-                // TODO: add an option to enable/disable looping
-                audioclip.state.playhead = 0;
+            let index = (playhead as usize).min(len - 1);
+            let next_index = if index + 1 < len {
+                index + 1
+            } else {
+                crossfade
+            };
+            let t = (playhead - index as f64) as f32;
+            let (l0, r0) = looped_sample_at(&data, index, crossfade);
+            let (l1, r1) = looped_sample_at(&data, next_index, crossfade);
+            dst.l[i] = lerp(l0, l1, t);
+            dst.r[i] = lerp(r0, r1, t);
+
+            // TODO: add an option to enable/disable looping
+            if reverse {
+                playhead -= speed;
+                if playhead < loop_start {
+                    playhead += loop_length;
+                }
+            } else {
+                playhead += speed;
+                if playhead >= len as f64 {
+                    playhead -= loop_length;
+                }
             }
-            debug_assert!(audioclip.state.playhead < data.sample_len());
-            dst.l[i] = c.l[si];
-            dst.r[i] = c.r[si];
         }
+        audioclip.state.playhead = Some(playhead);
         StreamStatus::Playing
     }
 }
 
+fn sample_at(data: &SoundBuffer, index: usize) -> (f32, f32) {
+    let c = &data.chunks()[index / CHUNK_SIZE];
+    let i = index % CHUNK_SIZE;
+    (c.l[i], c.r[i])
+}
+
+/// The sample at the given index as heard when looping with the given
+/// crossfade. Towards the end of the clip, samples are blended with
+/// those just before where playback resumes after the end, so that the
+/// last sample blended is exactly the one preceding the loop's restart
+fn looped_sample_at(data: &SoundBuffer, index: usize, crossfade: usize) -> (f32, f32) {
+    let (l, r) = sample_at(data, index);
+    let fade_start = data.sample_len() - crossfade;
+    if index < fade_start {
+        return (l, r);
+    }
+    let k = index - fade_start;
+    let t = (k + 1) as f32 / crossfade as f32;
+    let (l2, r2) = sample_at(data, k);
+    (lerp(l, l2, t), lerp(r, r2, t))
+}
+
 impl WithObjectType for AudioClip {
     const TYPE: ObjectType = ObjectType::new("audioclip");
 }
@@ -132,6 +303,20 @@ impl Stashable<StashingContext> for AudioClip {
         let buffer = self.data.lock();
         let buffer: &HashCache<SoundBuffer> = &buffer;
         stasher.object(buffer);
+        if stasher.context().checking_recompilation() {
+            // Settings are read atomically on the audio thread, so
+            // changing them doesn't require recompiling anything
+            let ptr: *const AudioClipSettings = &*self.settings;
+            stasher.u64((ptr as usize) as _);
+        } else {
+            stasher.u64(self.loop_crossfade() as _);
+            stasher.bool(self.reverse());
+            stasher.f32(self.speed());
+            stasher.bool(self.source.is_some());
+            if let Some(file) = &self.source {
+                stasher.object_with_context(file, ());
+            }
+        }
     }
 }
 
@@ -140,11 +325,33 @@ impl<'a> UnstashableInplace<UnstashingContext<'a>> for AudioClip {
         &mut self,
         unstasher: &mut InplaceUnstasher<UnstashingContext>,
     ) -> Result<(), UnstashError> {
-        let mut buffer = self.data.lock();
-        let buffer: &mut HashCache<SoundBuffer> = &mut buffer;
-        // TODO: make this better
-        println!("TODO: stop deserializing the entire audio buffer");
-        unstasher.object_replace(buffer)
+        {
+            let mut buffer = self.data.lock();
+            let buffer: &mut HashCache<SoundBuffer> = &mut buffer;
+            // TODO: make this better
+            println!("TODO: stop deserializing the entire audio buffer");
+            unstasher.object_replace(buffer)?;
+        }
+        let loop_crossfade = unstasher.u64_always()? as usize;
+        let reverse = unstasher.bool_always()?;
+        let speed = unstasher.f32_always()?;
+        let source: Option<AudioFilePath> = if unstasher.bool_always()? {
+            Some(unstasher.object_always_with_context(())?)
+        } else {
+            None
+        };
+        if unstasher.time_to_write() {
+            self.set_loop_crossfade(loop_crossfade);
+            self.set_reverse(reverse);
+            self.set_speed(speed);
+            // The clip's data is stashed along with it, but its file may
+            // have moved along with the patch or gone away in the meantime
+            let base_directory = unstasher.context().base_directory();
+            let source = source.map(|f| f.resolve(&base_directory));
+            self.source_missing = source.as_ref().is_some_and(|f| !f.exists());
+            self.source = source;
+        }
+        Ok(())
     }
 }
 
@@ -178,6 +385,9 @@ mod test {
                 sb.push_sample(0.0, 0.0);
                 sb.push_sample(-1.0, -1.0);
                 audioclip.set_data(sb);
+                audioclip.set_loop_crossfade(2);
+                audioclip.set_reverse(true);
+                audioclip.set_speed(0.5);
             },
             StashingContext::new_stashing_normally(),
             UnstashingContext::new(&obj_fac, &expr_fac),
@@ -0,0 +1,199 @@
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use inkwell::{values::FloatValue, FloatPredicate};
+
+use crate::{
+    core::{
+        automation::{AutomationLane, Interpolation},
+        expression::{
+            expressioninput::ExpressionInput,
+            expressionnode::{ExpressionNodeVisitor, ExpressionNodeVisitorMut, PureExpressionNode},
+        },
+        jit::jit::Jit,
+        objecttype::{ObjectType, WithObjectType},
+        stashing::StashingContext,
+        tempo::Tempo,
+    },
+    ui_core::arguments::{FloatArgument, ParsedArguments},
+};
+
+/// Plays back an automation lane. The input is the time in seconds,
+/// typically that of the processor whose parameter is being automated,
+/// which is converted to bars at the given tempo. Since the time is
+/// evaluated once per sample, so is the lane.
+pub struct Automation {
+    pub input: ExpressionInput,
+    lane: AutomationLane,
+    tempo: Tempo,
+}
+
+impl Automation {
+    pub const ARG_BPM: FloatArgument = FloatArgument("bpm");
+
+    pub fn lane(&self) -> &AutomationLane {
+        &self.lane
+    }
+
+    /// Changes to the lane are compiled into the expression and so
+    /// require recompilation
+    pub fn lane_mut(&mut self) -> &mut AutomationLane {
+        &mut self.lane
+    }
+
+    pub fn tempo(&self) -> Tempo {
+        self.tempo
+    }
+
+    pub fn set_tempo(&mut self, tempo: Tempo) {
+        self.tempo = tempo;
+    }
+
+    /// The value of the lane at the given time in seconds, which
+    /// matches the compiled expression node
+    pub fn value_at_time(&self, seconds: f32) -> f32 {
+        let position = seconds as f64 * self.tempo.bars_per_second();
+        self.lane.value_at(position).unwrap_or(0.0)
+    }
+}
+
+impl PureExpressionNode for Automation {
+    fn new(args: &ParsedArguments) -> Automation {
+        let beats_per_minute = args.get(&Automation::ARG_BPM).unwrap_or(120.0);
+        Automation {
+            input: ExpressionInput::new(0.0),
+            lane: AutomationLane::new(),
+            tempo: Tempo::new(beats_per_minute, 4),
+        }
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 1);
+        let f32_type = jit.types.f32_type;
+        let breakpoints = self.lane.breakpoints();
+
+        let Some(first) = breakpoints.first() else {
+            return f32_type.const_float(0.0);
+        };
+
+        let position = jit
+            .builder()
+            .build_float_mul(
+                inputs[0],
+                f32_type.const_float(self.tempo.bars_per_second()),
+                "position",
+            )
+            .unwrap();
+
+        // Starting from the value held before the first breakpoint, each
+        // breakpoint's segment takes over once the position reaches it
+        let mut value = f32_type.const_float(first.value as f64);
+        for (i, breakpoint) in breakpoints.iter().enumerate() {
+            let segment_value = match breakpoints.get(i + 1) {
+                None => f32_type.const_float(breakpoint.value as f64),
+                Some(next) => {
+                    let from = f32_type.const_float(breakpoint.value as f64);
+                    let b = jit.builder();
+                    let t = b
+                        .build_float_sub(
+                            position,
+                            f32_type.const_float(breakpoint.position),
+                            "offset",
+                        )
+                        .unwrap();
+                    let t = b
+                        .build_float_mul(
+                            t,
+                            f32_type.const_float(1.0 / (next.position - breakpoint.position)),
+                            "t",
+                        )
+                        .unwrap();
+                    let t = match breakpoint.interpolation {
+                        Interpolation::Step => None,
+                        Interpolation::Linear => Some(t),
+                        Interpolation::Smooth => {
+                            // t * t * (3 - 2 * t)
+                            let two_t = b
+                                .build_float_mul(f32_type.const_float(2.0), t, "two_t")
+                                .unwrap();
+                            let three_minus_two_t = b
+                                .build_float_sub(
+                                    f32_type.const_float(3.0),
+                                    two_t,
+                                    "three_minus_two_t",
+                                )
+                                .unwrap();
+                            let t_squared = b.build_float_mul(t, t, "t_squared").unwrap();
+                            Some(
+                                b.build_float_mul(t_squared, three_minus_two_t, "t_smooth")
+                                    .unwrap(),
+                            )
+                        }
+                    };
+                    match t {
+                        None => from,
+                        Some(t) => {
+                            let diff = f32_type.const_float((next.value - breakpoint.value) as f64);
+                            let scaled_diff = b.build_float_mul(t, diff, "scaled_diff").unwrap();
+                            b.build_float_add(from, scaled_diff, "segment_value")
+                                .unwrap()
+                        }
+                    }
+                }
+            };
+            let reached = jit
+                .builder()
+                .build_float_compare(
+                    FloatPredicate::OGE,
+                    position,
+                    f32_type.const_float(breakpoint.position),
+                    "reached",
+                )
+                .unwrap();
+            value = jit
+                .builder()
+                .build_select(reached, segment_value, value, "value")
+                .unwrap()
+                .into_float_value();
+        }
+        value
+    }
+
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        debug_assert_eq!(inputs.len(), 1);
+        Some(self.value_at_time(inputs[0]))
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input);
+    }
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input);
+    }
+}
+
+impl Stashable<StashingContext> for Automation {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.f64(self.tempo.beats_per_minute);
+        stasher.u32(self.tempo.beats_per_bar);
+        // The breakpoints are compiled into the expression, so they
+        // are stashed even when only checking for recompilation
+        stasher.object_with_context(&self.lane, ());
+    }
+}
+
+impl UnstashableInplace for Automation {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.f64_inplace(&mut self.tempo.beats_per_minute)?;
+        unstasher.u32_inplace(&mut self.tempo.beats_per_bar)?;
+        let lane = unstasher.object_always_with_context(())?;
+        if unstasher.time_to_write() {
+            self.lane = lane;
+        }
+        Ok(())
+    }
+}
+
+impl WithObjectType for Automation {
+    const TYPE: ObjectType = ObjectType::new("automation");
+}
@@ -0,0 +1,314 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Order, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        resample::read_fractional,
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundbuffer::SoundBuffer,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// The radius of an average adult head, in metres
+const HEAD_RADIUS: f32 = 0.0875;
+
+/// The speed of sound in air, in metres per second
+const SPEED_OF_SOUND: f32 = 343.0;
+
+/// The level difference between the ears, in decibels, for a source
+/// directly to one side
+pub const MAX_LEVEL_DIFFERENCE: f32 = 10.0;
+
+/// The number of samples in each head-related impulse response. Audio
+/// files are split into consecutive responses of this length when
+/// loaded as an HRTF table.
+pub const HRIR_LENGTH: usize = 256;
+
+/// The most impulse responses which are kept when loading an HRTF table
+pub const HRTF_MAX_RESPONSES: usize = 360;
+
+/// The delay, in seconds, of the left ear relative to the right ear
+/// for a source at the given azimuth in degrees, following Woodworth's
+/// spherical head model. Sources behind the listener are mirrored to
+/// the front, since the model can't tell the two apart.
+pub(crate) fn interaural_time_difference(azimuth: f32) -> f32 {
+    let lateral = azimuth.to_radians().sin().clamp(-1.0, 1.0).asin();
+    HEAD_RADIUS / SPEED_OF_SOUND * (lateral + lateral.sin())
+}
+
+/// The attenuation, in decibels, of the left ear relative to the
+/// right ear for a source at the given azimuth in degrees
+pub(crate) fn interaural_level_difference(azimuth: f32) -> f32 {
+    MAX_LEVEL_DIFFERENCE * azimuth.to_radians().sin()
+}
+
+/// Measured head-related impulse responses for each ear, for azimuths
+/// spaced evenly around the listener, starting straight ahead and
+/// proceeding clockwise towards the right
+#[derive(Clone)]
+pub struct HrtfTable {
+    left: Vec<Vec<f32>>,
+    right: Vec<Vec<f32>>,
+}
+
+impl HrtfTable {
+    /// Split a stereo audio buffer into consecutive impulse responses,
+    /// one per azimuth. Returns None if the buffer is too short to
+    /// hold a single response.
+    pub fn from_audio(buffer: &SoundBuffer) -> Option<HrtfTable> {
+        let split = |samples: Vec<f32>| -> Vec<Vec<f32>> {
+            samples
+                .chunks_exact(HRIR_LENGTH)
+                .take(HRTF_MAX_RESPONSES)
+                .map(|c| c.to_vec())
+                .collect()
+        };
+        let left = split(buffer.samples_l().collect());
+        let right = split(buffer.samples_r().collect());
+        if left.is_empty() {
+            return None;
+        }
+        Some(HrtfTable { left, right })
+    }
+
+    pub fn num_responses(&self) -> usize {
+        self.left.len()
+    }
+
+    /// The two responses on either side of the azimuth in degrees, and
+    /// how far the azimuth lies between them
+    fn neighbours(&self, azimuth: f32) -> (usize, usize, f32) {
+        let n = self.num_responses();
+        let position = (azimuth.rem_euclid(360.0) / 360.0) * n as f32;
+        let i0 = (position.floor() as usize).min(n - 1);
+        let i1 = (i0 + 1) % n;
+        (i0, i1, position - position.floor())
+    }
+}
+
+/// Convolve the most recent samples of a circular history buffer with
+/// an impulse response
+fn convolve(history: &[f32], newest_index: usize, response: &[f32]) -> f32 {
+    let len = history.len();
+    response
+        .iter()
+        .enumerate()
+        .map(|(k, h)| h * history[(newest_index + len - k) % len])
+        .sum()
+}
+
+/// Places a mono signal around the listener, either by delaying and
+/// attenuating the far ear or by convolving with an HRTF table
+pub(crate) struct BinauralRenderer {
+    history: Vec<f32>,
+    write_index: usize,
+}
+
+impl BinauralRenderer {
+    pub(crate) fn new() -> BinauralRenderer {
+        let max_delay = interaural_time_difference(90.0) * SAMPLE_FREQUENCY as f32;
+        let len = (max_delay.ceil() as usize + 2).max(HRIR_LENGTH);
+        BinauralRenderer {
+            history: vec![0.0; len],
+            write_index: 0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.write_index = 0;
+    }
+
+    /// Downmix the chunk to mono and place it at the per-sample azimuth,
+    /// in degrees. An azimuth of -90 is hard left and 90 is hard right.
+    pub(crate) fn process(
+        &mut self,
+        chunk: &mut SoundChunk,
+        azimuth: &[f32],
+        hrtf: Option<&HrtfTable>,
+    ) {
+        let len = self.history.len();
+        for (i, azimuth) in azimuth.iter().enumerate() {
+            let azimuth = if azimuth.is_finite() { *azimuth } else { 0.0 };
+            let (l, r) = chunk.sample(i);
+            self.history[self.write_index] = 0.5 * (l + r);
+
+            let (l, r) = match hrtf {
+                Some(hrtf) => {
+                    let (i0, i1, t) = hrtf.neighbours(azimuth);
+                    let ear = |responses: &[Vec<f32>]| {
+                        let y0 = convolve(&self.history, self.write_index, &responses[i0]);
+                        let y1 = convolve(&self.history, self.write_index, &responses[i1]);
+                        y0 + t * (y1 - y0)
+                    };
+                    (ear(&hrtf.left), ear(&hrtf.right))
+                }
+                None => {
+                    let itd = interaural_time_difference(azimuth) * SAMPLE_FREQUENCY as f32;
+                    let ild = interaural_level_difference(azimuth);
+                    let ear = |delay: f32, attenuation: f32| {
+                        let gain = 10.0_f32.powf(-attenuation.max(0.0) / 20.0);
+                        gain * read_fractional(&self.history, self.write_index as f32 - delay)
+                    };
+                    (ear(itd.max(0.0), ild), ear((-itd).max(0.0), -ild))
+                }
+            };
+            chunk.l[i] = l;
+            chunk.r[i] = r;
+
+            self.write_index = (self.write_index + 1) % len;
+        }
+    }
+}
+
+pub struct BinauralState {
+    renderer: BinauralRenderer,
+    hrtf: Option<HrtfTable>,
+}
+
+impl ProcessorState for BinauralState {
+    type Processor = Binaural;
+
+    fn new(processor: &Binaural) -> Self {
+        BinauralState {
+            renderer: BinauralRenderer::new(),
+            hrtf: processor.hrtf.clone(),
+        }
+    }
+}
+
+impl StartOver for BinauralState {
+    fn start_over(&mut self) {
+        self.renderer.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Binaural {
+    pub input: SingleInput,
+
+    /// The direction of the source, in degrees clockwise from straight ahead
+    pub azimuth: ProcessorExpression,
+
+    #[not_a_component]
+    hrtf: Option<HrtfTable>,
+
+    #[state]
+    state: StateMarker<BinauralState>,
+}
+
+impl Binaural {
+    /// The HRTF table used instead of simple time and level differences
+    pub fn hrtf(&self) -> Option<&HrtfTable> {
+        self.hrtf.as_ref()
+    }
+
+    pub fn set_hrtf(&mut self, hrtf: Option<HrtfTable>) {
+        self.hrtf = hrtf;
+    }
+}
+
+impl SoundProcessor for Binaural {
+    fn new(_args: &ParsedArguments) -> Binaural {
+        Binaural {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            azimuth: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            hrtf: None,
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        binaural: &mut CompiledBinaural,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = binaural.input.step(dst, InputContext::new(context));
+
+        let mut azimuth = [0.0; CHUNK_SIZE];
+        binaural.azimuth.eval(
+            &mut [&mut azimuth],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        let state = &mut binaural.state;
+        state.renderer.process(dst, &azimuth, state.hrtf.as_ref());
+
+        status
+    }
+}
+
+impl WithObjectType for Binaural {
+    const TYPE: ObjectType = ObjectType::new("binaural");
+}
+
+impl Stashable<StashingContext> for Binaural {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.azimuth);
+        stasher.bool(self.hrtf.is_some());
+        if let Some(hrtf) = &self.hrtf {
+            for responses in [&hrtf.left, &hrtf.right] {
+                stasher.array_of_proxy_objects(
+                    responses.iter(),
+                    |response, stasher| stasher.array_of_f32_slice(response),
+                    Order::Ordered,
+                );
+            }
+        }
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Binaural {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.azimuth)?;
+        let hrtf = if unstasher.bool_always()? {
+            let mut ears = [Vec::new(), Vec::new()];
+            for responses in &mut ears {
+                unstasher.array_of_proxy_objects(|unstasher| {
+                    responses.push(unstasher.array_of_f32_iter()?.collect::<Vec<f32>>());
+                    Ok(())
+                })?;
+            }
+            let [left, right] = ears;
+            if left.is_empty()
+                || left.len() != right.len()
+                || left.iter().chain(&right).any(|r| r.len() != HRIR_LENGTH)
+            {
+                return Err(UnstashError::Corrupted);
+            }
+            Some(HrtfTable { left, right })
+        } else {
+            None
+        };
+        if unstasher.time_to_write() {
+            self.hrtf = hrtf;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        antidenormal::AntiDenormal,
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        resample::read_fractional,
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// The longest delay time, in seconds, that the chorus can produce
+pub(crate) const CHORUS_MAX_DELAY: f32 = 0.1;
+
+/// Per-sample values of the chorus's expressions for one chunk
+pub(crate) struct ChorusParameters<'a> {
+    /// Centre delay time, in seconds
+    pub delay: &'a [f32],
+    /// LFO rate, in Hz
+    pub rate: &'a [f32],
+    /// Amount by which the LFO moves the delay time, in seconds
+    pub depth: &'a [f32],
+    /// Fraction of the delayed signal fed back into the delay line
+    pub feedback: &'a [f32],
+    /// Balance between the dry (0) and delayed (1) signals
+    pub mix: &'a [f32],
+}
+
+/// A stereo delay line whose delay time is modulated by a sine LFO
+pub(crate) struct ModulatedDelay {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    write_index: usize,
+    phase: f32,
+
+    /// Noise added to the feedback path, if enabled
+    anti_denormal: Option<AntiDenormal>,
+}
+
+impl ModulatedDelay {
+    pub(crate) fn new() -> ModulatedDelay {
+        // Leave room for interpolating past the longest delay
+        let len = (CHORUS_MAX_DELAY * SAMPLE_FREQUENCY as f32).ceil() as usize + 2;
+        ModulatedDelay {
+            left: vec![0.0; len],
+            right: vec![0.0; len],
+            write_index: 0,
+            phase: 0.0,
+            anti_denormal: None,
+        }
+    }
+
+    /// Enable or disable adding inaudible noise to the feedback path,
+    /// which keeps the decaying delayed signal out of the denormal range
+    pub(crate) fn set_anti_denormal(&mut self, enabled: bool) {
+        self.anti_denormal = enabled.then(AntiDenormal::new);
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.left.fill(0.0);
+        self.right.fill(0.0);
+        self.write_index = 0;
+        self.phase = 0.0;
+    }
+
+    pub(crate) fn process(&mut self, chunk: &mut SoundChunk, parameters: ChorusParameters) {
+        let len = self.left.len();
+        let max_delay = (len - 2) as f32;
+        for (i, (l, r)) in chunk.samples_mut().enumerate() {
+            let lfo = (self.phase * std::f32::consts::TAU).sin();
+            let delay = parameters.delay[i] + parameters.depth[i] * lfo;
+            let delay_samples = (delay * SAMPLE_FREQUENCY as f32).clamp(1.0, max_delay);
+            let position = self.write_index as f32 - delay_samples;
+
+            let wet_l = read_fractional(&self.left, position);
+            let wet_r = read_fractional(&self.right, position);
+
+            let feedback = parameters.feedback[i].clamp(-0.99, 0.99);
+            let noise = self.anti_denormal.as_mut().map_or(0.0, |a| a.next_sample());
+            self.left[self.write_index] = *l + feedback * wet_l + noise;
+            self.right[self.write_index] = *r + feedback * wet_r + noise;
+            self.write_index = (self.write_index + 1) % len;
+
+            let mix = parameters.mix[i].clamp(0.0, 1.0);
+            *l = (1.0 - mix) * *l + mix * wet_l;
+            *r = (1.0 - mix) * *r + mix * wet_r;
+
+            self.phase =
+                (self.phase + parameters.rate[i] / SAMPLE_FREQUENCY as f32).rem_euclid(1.0);
+        }
+    }
+}
+
+pub struct ChorusState {
+    delay: ModulatedDelay,
+}
+
+impl ProcessorState for ChorusState {
+    type Processor = Chorus;
+
+    fn new(processor: &Chorus) -> Self {
+        let mut delay = ModulatedDelay::new();
+        delay.set_anti_denormal(processor.anti_denormal);
+        ChorusState { delay }
+    }
+}
+
+impl StartOver for ChorusState {
+    fn start_over(&mut self) {
+        self.delay.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Chorus {
+    pub input: SingleInput,
+    pub delay: ProcessorExpression,
+    pub rate: ProcessorExpression,
+    pub depth: ProcessorExpression,
+    pub feedback: ProcessorExpression,
+    pub mix: ProcessorExpression,
+
+    #[not_a_component]
+    anti_denormal: bool,
+
+    #[state]
+    state: StateMarker<ChorusState>,
+}
+
+impl Chorus {
+    /// Whether inaudible noise is added to the feedback path to keep
+    /// it out of the denormal range, where processing is much slower
+    /// on some platforms
+    pub fn anti_denormal(&self) -> bool {
+        self.anti_denormal
+    }
+
+    pub fn set_anti_denormal(&mut self, enabled: bool) {
+        self.anti_denormal = enabled;
+    }
+}
+
+impl SoundProcessor for Chorus {
+    fn new(_args: &ParsedArguments) -> Chorus {
+        Chorus {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            delay: ProcessorExpression::new(&[0.02], ArgumentScope::new_empty()),
+            rate: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+            depth: ProcessorExpression::new(&[0.003], ArgumentScope::new_empty()),
+            feedback: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            mix: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+            anti_denormal: false,
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        chorus: &mut CompiledChorus,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = chorus.input.step(dst, InputContext::new(context));
+
+        let mut delay = [0.0; CHUNK_SIZE];
+        let mut rate = [0.0; CHUNK_SIZE];
+        let mut depth = [0.0; CHUNK_SIZE];
+        let mut feedback = [0.0; CHUNK_SIZE];
+        let mut mix = [0.0; CHUNK_SIZE];
+        for (expr, values) in [
+            (&mut chorus.delay, &mut delay),
+            (&mut chorus.rate, &mut rate),
+            (&mut chorus.depth, &mut depth),
+            (&mut chorus.feedback, &mut feedback),
+            (&mut chorus.mix, &mut mix),
+        ] {
+            expr.eval(
+                &mut [&mut values[..]],
+                Discretization::samplewise_temporal(),
+                ExpressionContext::new(context),
+            );
+        }
+
+        chorus.state.delay.process(
+            dst,
+            ChorusParameters {
+                delay: &delay,
+                rate: &rate,
+                depth: &depth,
+                feedback: &feedback,
+                mix: &mix,
+            },
+        );
+
+        status
+    }
+}
+
+impl WithObjectType for Chorus {
+    const TYPE: ObjectType = ObjectType::new("chorus");
+}
+
+impl Stashable<StashingContext> for Chorus {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.delay);
+        stasher.object(&self.rate);
+        stasher.object(&self.depth);
+        stasher.object(&self.feedback);
+        stasher.object(&self.mix);
+        stasher.bool(self.anti_denormal);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Chorus {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.delay)?;
+        unstasher.object_inplace(&mut self.rate)?;
+        unstasher.object_inplace(&mut self.depth)?;
+        unstasher.object_inplace(&mut self.feedback)?;
+        unstasher.object_inplace(&mut self.mix)?;
+        unstasher.bool_inplace(&mut self.anti_denormal)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use atomic_float::AtomicF32;
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A snapshot of the compressor's settings, as used on the audio thread
+#[derive(Clone, Copy)]
+pub(crate) struct CompressorParameters {
+    /// The level, in decibels, above which gain reduction is applied
+    pub(crate) threshold: f32,
+
+    /// The ratio of input level to output level above the threshold
+    pub(crate) ratio: f32,
+
+    /// The time constant, in seconds, with which the detected level rises
+    pub(crate) attack: f32,
+
+    /// The time constant, in seconds, with which the detected level falls
+    pub(crate) release: f32,
+}
+
+fn time_constant_coefficient(seconds: f32) -> f32 {
+    let samples = (seconds * SAMPLE_FREQUENCY as f32).max(1.0);
+    1.0 - (-1.0 / samples).exp()
+}
+
+/// Follows the level of a key signal and reduces the gain of
+/// another signal whenever that level exceeds the threshold
+pub(crate) struct EnvelopeFollower {
+    envelope: f32,
+}
+
+impl EnvelopeFollower {
+    pub(crate) fn new() -> EnvelopeFollower {
+        EnvelopeFollower { envelope: 0.0 }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.envelope = 0.0;
+    }
+
+    /// Reduce the gain of `main` according to the level of `key`.
+    /// The two may be copies of the same signal.
+    pub(crate) fn process(
+        &mut self,
+        main: &mut SoundChunk,
+        key: &SoundChunk,
+        parameters: CompressorParameters,
+    ) {
+        let attack = time_constant_coefficient(parameters.attack);
+        let release = time_constant_coefficient(parameters.release);
+        let slope = 1.0 - 1.0 / parameters.ratio.max(1.0);
+
+        for i in 0..CHUNK_SIZE {
+            let (key_l, key_r) = key.sample(i);
+            let level = key_l.abs().max(key_r.abs());
+            let coefficient = if level > self.envelope {
+                attack
+            } else {
+                release
+            };
+            self.envelope += coefficient * (level - self.envelope);
+
+            let envelope_db = 20.0 * self.envelope.max(1e-6).log10();
+            let overshoot = (envelope_db - parameters.threshold).max(0.0);
+            let gain = 10.0_f32.powf(-overshoot * slope / 20.0);
+
+            main.l[i] *= gain;
+            main.r[i] *= gain;
+        }
+    }
+}
+
+pub struct CompressorSettings {
+    threshold: AtomicF32,
+    ratio: AtomicF32,
+    attack: AtomicF32,
+    release: AtomicF32,
+    sidechain_enabled: AtomicBool,
+}
+
+impl CompressorSettings {
+    fn parameters(&self) -> CompressorParameters {
+        CompressorParameters {
+            threshold: self.threshold.load(Ordering::Relaxed),
+            ratio: self.ratio.load(Ordering::Relaxed),
+            attack: self.attack.load(Ordering::Relaxed),
+            release: self.release.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub struct CompressorState {
+    settings: Arc<CompressorSettings>,
+    envelope: EnvelopeFollower,
+    key: SoundChunk,
+}
+
+impl ProcessorState for CompressorState {
+    type Processor = Compressor;
+
+    fn new(processor: &Compressor) -> Self {
+        CompressorState {
+            settings: Arc::clone(&processor.settings),
+            envelope: EnvelopeFollower::new(),
+            key: SoundChunk::new(),
+        }
+    }
+}
+
+impl StartOver for CompressorState {
+    fn start_over(&mut self) {
+        self.envelope.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Compressor {
+    pub input: SingleInput,
+    pub sidechain: SingleInput,
+
+    #[not_a_component]
+    settings: Arc<CompressorSettings>,
+
+    #[state]
+    state: StateMarker<CompressorState>,
+}
+
+impl Compressor {
+    pub fn threshold(&self) -> f32 {
+        self.settings.threshold.load(Ordering::Relaxed)
+    }
+
+    pub fn set_threshold(&self, decibels: f32) {
+        self.settings.threshold.store(decibels, Ordering::Relaxed);
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.settings.ratio.load(Ordering::Relaxed)
+    }
+
+    pub fn set_ratio(&self, ratio: f32) {
+        self.settings.ratio.store(ratio.max(1.0), Ordering::Relaxed);
+    }
+
+    pub fn attack(&self) -> f32 {
+        self.settings.attack.load(Ordering::Relaxed)
+    }
+
+    pub fn set_attack(&self, seconds: f32) {
+        self.settings.attack.store(seconds, Ordering::Relaxed);
+    }
+
+    pub fn release(&self) -> f32 {
+        self.settings.release.load(Ordering::Relaxed)
+    }
+
+    pub fn set_release(&self, seconds: f32) {
+        self.settings.release.store(seconds, Ordering::Relaxed);
+    }
+
+    /// Whether the sidechain input, rather than the main input,
+    /// determines how much gain reduction is applied
+    pub fn sidechain_enabled(&self) -> bool {
+        self.settings.sidechain_enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_sidechain_enabled(&self, enabled: bool) {
+        self.settings
+            .sidechain_enabled
+            .store(enabled, Ordering::Relaxed);
+    }
+}
+
+impl SoundProcessor for Compressor {
+    fn new(_args: &ParsedArguments) -> Compressor {
+        Compressor {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            sidechain: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            settings: Arc::new(CompressorSettings {
+                threshold: AtomicF32::new(-20.0),
+                ratio: AtomicF32::new(4.0),
+                attack: AtomicF32::new(0.01),
+                release: AtomicF32::new(0.1),
+                sidechain_enabled: AtomicBool::new(false),
+            }),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        compressor: &mut CompiledCompressor,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = compressor.input.step(dst, InputContext::new(context));
+
+        // The sidechain is always stepped, even when it is disabled,
+        // so that it stays in time with the main input and can be
+        // switched on at any moment
+        let state = &mut compressor.state;
+        compressor
+            .sidechain
+            .step(&mut state.key, InputContext::new(context));
+
+        let parameters = state.settings.parameters();
+        if state.settings.sidechain_enabled.load(Ordering::Relaxed) {
+            state.envelope.process(dst, &state.key, parameters);
+        } else {
+            let key = *dst;
+            state.envelope.process(dst, &key, parameters);
+        }
+
+        status
+    }
+}
+
+impl WithObjectType for Compressor {
+    const TYPE: ObjectType = ObjectType::new("compressor");
+}
+
+impl Stashable<StashingContext> for Compressor {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.sidechain);
+        if stasher.context().checking_recompilation() {
+            // Settings are read atomically on the audio thread, so
+            // changing them doesn't require recompiling anything
+            let ptr: *const CompressorSettings = &*self.settings;
+            stasher.u64((ptr as usize) as _);
+        } else {
+            stasher.f32(self.threshold());
+            stasher.f32(self.ratio());
+            stasher.f32(self.attack());
+            stasher.f32(self.release());
+            stasher.bool(self.sidechain_enabled());
+        }
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Compressor {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.sidechain)?;
+        let threshold = unstasher.f32_always()?;
+        let ratio = unstasher.f32_always()?;
+        let attack = unstasher.f32_always()?;
+        let release = unstasher.f32_always()?;
+        let sidechain_enabled = unstasher.bool_always()?;
+        if unstasher.time_to_write() {
+            self.set_threshold(threshold);
+            self.set_ratio(ratio);
+            self.set_attack(attack);
+            self.set_release(release);
+            self.set_sidechain_enabled(sidechain_enabled);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{SoundProcessor, StreamStatus},
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Blend the two chunks into dst with an equal-power crossfade, where
+/// a mix of 0 yields only `a` and a mix of 1 yields only `b`. Mix values
+/// outside of [0, 1] are clamped.
+pub(crate) fn crossfade(a: &SoundChunk, b: &SoundChunk, mix: &[f32], dst: &mut SoundChunk) {
+    debug_assert_eq!(mix.len(), CHUNK_SIZE);
+    for i in 0..SoundChunk::NUM_CHANNELS {
+        let a = a.channel(i);
+        let b = b.channel(i);
+        let dst = dst.channel_mut(i);
+        for j in 0..CHUNK_SIZE {
+            let angle = mix[j].clamp(0.0, 1.0) * std::f32::consts::FRAC_PI_2;
+            dst[j] = angle.cos() * a[j] + angle.sin() * b[j];
+        }
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Crossfade {
+    pub input_a: SingleInput,
+    pub input_b: SingleInput,
+    pub mix: ProcessorExpression,
+}
+
+impl SoundProcessor for Crossfade {
+    fn new(_args: &ParsedArguments) -> Crossfade {
+        Crossfade {
+            input_a: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            input_b: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            mix: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        crossfade: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let mut chunk_a = SoundChunk::new();
+        let mut chunk_b = SoundChunk::new();
+        let status_a = crossfade
+            .input_a
+            .step(&mut chunk_a, InputContext::new(context));
+        let status_b = crossfade
+            .input_b
+            .step(&mut chunk_b, InputContext::new(context));
+
+        let mut mix = [0.0; CHUNK_SIZE];
+        crossfade.mix.eval(
+            &mut [&mut mix],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        self::crossfade(&chunk_a, &chunk_b, &mix, dst);
+
+        if status_a == StreamStatus::Done && status_b == StreamStatus::Done {
+            StreamStatus::Done
+        } else {
+            StreamStatus::Playing
+        }
+    }
+}
+
+impl WithObjectType for Crossfade {
+    const TYPE: ObjectType = ObjectType::new("crossfade");
+}
+
+impl Stashable<StashingContext> for Crossfade {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input_a);
+        stasher.object(&self.input_b);
+        stasher.object(&self.mix);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for Crossfade {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input_a)?;
+        unstasher.object_inplace(&mut self.input_b)?;
+        unstasher.object_inplace(&mut self.mix)?;
+        Ok(())
+    }
+}
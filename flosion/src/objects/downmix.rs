@@ -0,0 +1,86 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{SoundProcessor, StreamStatus},
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Sums all channels of its input into a single mono signal,
+/// attenuated by 3 dB, which is written to every channel
+#[derive(ProcessorComponent)]
+pub struct Downmix {
+    pub input: SingleInput,
+}
+
+/// The gain applied to the sum of all channels, approximately -3 dB
+pub(crate) const DOWNMIX_GAIN: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Replace every channel of the chunk with the attenuated sum of all channels
+pub(crate) fn downmix(chunk: &mut SoundChunk) {
+    let mut mono = [0.0; CHUNK_SIZE];
+    for i in 0..SoundChunk::NUM_CHANNELS {
+        for (m, s) in mono.iter_mut().zip(chunk.channel(i)) {
+            *m += *s;
+        }
+    }
+    for m in &mut mono {
+        *m *= DOWNMIX_GAIN;
+    }
+    for i in 0..SoundChunk::NUM_CHANNELS {
+        *chunk.channel_mut(i) = mono;
+    }
+}
+
+impl SoundProcessor for Downmix {
+    fn new(_args: &ParsedArguments) -> Downmix {
+        Downmix {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        downmix: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = downmix.input.step(dst, InputContext::new(context));
+        self::downmix(dst);
+        status
+    }
+}
+
+impl WithObjectType for Downmix {
+    const TYPE: ObjectType = ObjectType::new("downmix");
+}
+
+impl Stashable<StashingContext> for Downmix {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for Downmix {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        Ok(())
+    }
+}
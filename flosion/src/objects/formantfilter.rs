@@ -0,0 +1,248 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        biquad::{BiquadCoefficients, BiquadState},
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A single resonance of the vocal tract
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Formant {
+    /// Centre frequency, in Hz
+    pub frequency: f32,
+    /// Bandwidth, in Hz
+    pub bandwidth: f32,
+    /// Relative level, in decibels
+    pub level: f32,
+}
+
+const fn formant(frequency: f32, bandwidth: f32, level: f32) -> Formant {
+    Formant {
+        frequency,
+        bandwidth,
+        level,
+    }
+}
+
+pub(crate) const NUM_FORMANTS: usize = 3;
+
+/// Names of the preset vowels, in the order in which the vowel
+/// expression morphs between them
+pub const VOWEL_NAMES: [&str; 5] = ["A", "E", "I", "O", "U"];
+
+/// The first three formants of each preset vowel for an adult male voice
+pub(crate) const VOWEL_FORMANTS: [[Formant; NUM_FORMANTS]; VOWEL_NAMES.len()] = [
+    [
+        formant(600.0, 60.0, 0.0),
+        formant(1040.0, 70.0, -7.0),
+        formant(2250.0, 110.0, -9.0),
+    ],
+    [
+        formant(400.0, 40.0, 0.0),
+        formant(1620.0, 80.0, -12.0),
+        formant(2400.0, 100.0, -9.0),
+    ],
+    [
+        formant(250.0, 60.0, 0.0),
+        formant(1750.0, 90.0, -30.0),
+        formant(2600.0, 100.0, -16.0),
+    ],
+    [
+        formant(400.0, 40.0, 0.0),
+        formant(750.0, 80.0, -11.0),
+        formant(2400.0, 100.0, -21.0),
+    ],
+    [
+        formant(350.0, 40.0, 0.0),
+        formant(600.0, 80.0, -20.0),
+        formant(2400.0, 100.0, -32.0),
+    ],
+];
+
+/// Interpolate the formants of the preset vowels, where whole numbers
+/// select a single vowel and fractional values lie between adjacent
+/// vowels. Values outside of the range of vowels are clamped.
+pub(crate) fn formants_at(vowel: f32) -> [Formant; NUM_FORMANTS] {
+    let last = (VOWEL_FORMANTS.len() - 1) as f32;
+    let vowel = if vowel.is_nan() {
+        0.0
+    } else {
+        vowel.clamp(0.0, last)
+    };
+    let i = (vowel.floor() as usize).min(VOWEL_FORMANTS.len() - 2);
+    let t = vowel - i as f32;
+    let lerp = |a: f32, b: f32| a + t * (b - a);
+    std::array::from_fn(|j| {
+        let a = VOWEL_FORMANTS[i][j];
+        let b = VOWEL_FORMANTS[i + 1][j];
+        Formant {
+            frequency: lerp(a.frequency, b.frequency),
+            bandwidth: lerp(a.bandwidth, b.bandwidth),
+            level: lerp(a.level, b.level),
+        }
+    })
+}
+
+/// Three parallel bandpass resonators, one per formant, whose outputs
+/// are summed according to each formant's level
+pub(crate) struct FormantBank {
+    coefficients: [BiquadCoefficients; NUM_FORMANTS],
+    gains: [f32; NUM_FORMANTS],
+    states: [[BiquadState; NUM_FORMANTS]; SoundChunk::NUM_CHANNELS],
+}
+
+impl FormantBank {
+    pub(crate) fn new(vowel: f32) -> FormantBank {
+        let mut bank = FormantBank {
+            coefficients: [BiquadCoefficients::bandpass(1000.0, 1.0); NUM_FORMANTS],
+            gains: [0.0; NUM_FORMANTS],
+            states: [[BiquadState::new(); NUM_FORMANTS]; SoundChunk::NUM_CHANNELS],
+        };
+        bank.set_vowel(vowel);
+        bank
+    }
+
+    pub(crate) fn set_vowel(&mut self, vowel: f32) {
+        for (j, f) in formants_at(vowel).into_iter().enumerate() {
+            self.coefficients[j] =
+                BiquadCoefficients::bandpass(f.frequency, f.frequency / f.bandwidth);
+            self.gains[j] = 10.0_f32.powf(f.level / 20.0);
+        }
+    }
+
+    /// The combined complex gain of the resonators at the given
+    /// frequency in Hz, as a (real, imaginary) pair
+    pub(crate) fn frequency_response(&self, frequency: f32) -> (f32, f32) {
+        let mut sum = (0.0, 0.0);
+        for (coefficients, gain) in self.coefficients.iter().zip(&self.gains) {
+            let (re, im) = coefficients.frequency_response(frequency);
+            sum.0 += gain * re;
+            sum.1 += gain * im;
+        }
+        sum
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for channel in &mut self.states {
+            for state in channel {
+                state.reset();
+            }
+        }
+    }
+
+    pub(crate) fn process(&mut self, chunk: &mut SoundChunk) {
+        for (i, states) in self.states.iter_mut().enumerate() {
+            for s in chunk.channel_mut(i) {
+                let x = *s;
+                *s = 0.0;
+                for ((state, coefficients), gain) in
+                    states.iter_mut().zip(&self.coefficients).zip(&self.gains)
+                {
+                    *s += gain * state.process(coefficients, x);
+                }
+            }
+        }
+    }
+}
+
+pub struct FormantFilterState {
+    bank: FormantBank,
+}
+
+impl ProcessorState for FormantFilterState {
+    type Processor = FormantFilter;
+
+    fn new(_processor: &FormantFilter) -> Self {
+        FormantFilterState {
+            bank: FormantBank::new(0.0),
+        }
+    }
+}
+
+impl StartOver for FormantFilterState {
+    fn start_over(&mut self) {
+        self.bank.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct FormantFilter {
+    pub input: SingleInput,
+    pub vowel: ProcessorExpression,
+
+    #[state]
+    state: StateMarker<FormantFilterState>,
+}
+
+impl SoundProcessor for FormantFilter {
+    fn new(_args: &ParsedArguments) -> FormantFilter {
+        FormantFilter {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            vowel: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        formant_filter: &mut CompiledFormantFilter,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = formant_filter.input.step(dst, InputContext::new(context));
+
+        // Redesigning the filters is comparatively expensive, so the
+        // vowel is only updated once per chunk
+        let vowel = formant_filter.vowel.eval_scalar(
+            Discretization::chunkwise_temporal(),
+            ExpressionContext::new(context),
+        );
+        formant_filter.state.bank.set_vowel(vowel);
+        formant_filter.state.bank.process(dst);
+
+        status
+    }
+}
+
+impl WithObjectType for FormantFilter {
+    const TYPE: ObjectType = ObjectType::new("formantfilter");
+}
+
+impl Stashable<StashingContext> for FormantFilter {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.vowel);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for FormantFilter {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.vowel)?;
+        Ok(())
+    }
+}
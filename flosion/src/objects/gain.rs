@@ -0,0 +1,153 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use atomic_float::AtomicF32;
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        smoothing::SmoothedValue,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{FloatArgument, ParsedArguments},
+};
+
+/// The time constant, in seconds, with which the gain follows changes
+pub(crate) const GAIN_SMOOTHING_TIME: f32 = 0.02;
+
+pub(crate) fn decibels_to_amplitude(decibels: f32) -> f32 {
+    10.0_f32.powf(decibels / 20.0)
+}
+
+/// Scale every sample of the chunk by the smoothed gain, which moves
+/// towards the target amplitude by one step per sample
+pub(crate) fn apply_gain(chunk: &mut SoundChunk, gain: &mut SmoothedValue, target: f32) {
+    for (l, r) in chunk.samples_mut() {
+        let g = gain.step(target);
+        *l *= g;
+        *r *= g;
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Gain {
+    pub input: SingleInput,
+
+    #[not_a_component]
+    decibels: Arc<AtomicF32>,
+
+    #[state]
+    state: StateMarker<GainState>,
+}
+
+pub struct GainState {
+    decibels: Arc<AtomicF32>,
+    gain: SmoothedValue,
+}
+
+impl GainState {
+    fn target(&self) -> f32 {
+        decibels_to_amplitude(self.decibels.load(Ordering::Relaxed))
+    }
+}
+
+impl ProcessorState for GainState {
+    type Processor = Gain;
+
+    fn new(processor: &Gain) -> Self {
+        let decibels = Arc::clone(&processor.decibels);
+        let initial_gain = decibels_to_amplitude(decibels.load(Ordering::Relaxed));
+        GainState {
+            decibels,
+            gain: SmoothedValue::new(initial_gain, GAIN_SMOOTHING_TIME),
+        }
+    }
+}
+
+impl StartOver for GainState {
+    fn start_over(&mut self) {
+        // Don't fade in from the previous gain when starting over
+        self.gain.reset(self.target());
+    }
+}
+
+impl Gain {
+    pub const ARG_DECIBELS: FloatArgument = FloatArgument("db");
+
+    pub fn decibels(&self) -> f32 {
+        self.decibels.load(Ordering::Relaxed)
+    }
+
+    pub fn set_decibels(&self, decibels: f32) {
+        self.decibels.store(decibels, Ordering::Relaxed);
+    }
+}
+
+impl SoundProcessor for Gain {
+    fn new(args: &ParsedArguments) -> Gain {
+        let decibels = args.get(&Gain::ARG_DECIBELS).unwrap_or(0.0) as f32;
+        Gain {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            decibels: Arc::new(AtomicF32::new(decibels)),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        gain: &mut CompiledGain,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = gain.input.step(dst, InputContext::new(context));
+        let target = gain.state.target();
+        apply_gain(dst, &mut gain.state.gain, target);
+        status
+    }
+}
+
+impl WithObjectType for Gain {
+    const TYPE: ObjectType = ObjectType::new("gain");
+}
+
+impl Stashable<StashingContext> for Gain {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        if stasher.context().checking_recompilation() {
+            // The gain is read atomically on the audio thread, so
+            // changing it doesn't require recompiling anything
+            let ptr: *const AtomicF32 = &*self.decibels;
+            stasher.u64((ptr as usize) as _);
+        } else {
+            stasher.f32(self.decibels());
+        }
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Gain {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        let decibels = unstasher.f32_always()?;
+        if unstasher.time_to_write() {
+            self.set_decibels(decibels);
+        }
+        Ok(())
+    }
+}
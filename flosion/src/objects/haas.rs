@@ -0,0 +1,218 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        resample::read_fractional,
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{ArgumentEnum, EnumArgument, ParsedArguments},
+};
+
+/// The shortest delay time, in seconds, that the Haas effect uses
+pub const HAAS_MIN_DELAY: f32 = 0.001;
+
+/// The longest delay time, in seconds, that the Haas effect uses.
+/// Beyond roughly this point, the delayed channel starts to be heard
+/// as a distinct echo rather than as a wider image.
+pub const HAAS_MAX_DELAY: f32 = 0.04;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HaasSide {
+    Left,
+    Right,
+}
+
+impl HaasSide {
+    fn to_u8(self) -> u8 {
+        match self {
+            HaasSide::Left => 0,
+            HaasSide::Right => 1,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<HaasSide> {
+        match x {
+            0 => Some(HaasSide::Left),
+            1 => Some(HaasSide::Right),
+            _ => None,
+        }
+    }
+
+    fn channel(self) -> usize {
+        match self {
+            HaasSide::Left => 0,
+            HaasSide::Right => 1,
+        }
+    }
+}
+
+impl ArgumentEnum for HaasSide {
+    fn all_values() -> &'static [HaasSide] {
+        &[HaasSide::Left, HaasSide::Right]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            HaasSide::Left => "left",
+            HaasSide::Right => "right",
+        }
+    }
+}
+
+/// A single-channel delay line long enough for the longest Haas delay
+pub(crate) struct HaasDelay {
+    buffer: Vec<f32>,
+    write_index: usize,
+}
+
+impl HaasDelay {
+    pub(crate) fn new() -> HaasDelay {
+        let len = (HAAS_MAX_DELAY * SAMPLE_FREQUENCY as f32).ceil() as usize + 2;
+        HaasDelay {
+            buffer: vec![0.0; len],
+            write_index: 0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_index = 0;
+    }
+
+    /// Delay the channel by the per-sample delay time, in seconds
+    pub(crate) fn process(&mut self, channel: &mut [f32], delay: &[f32]) {
+        let len = self.buffer.len();
+        for (s, delay) in channel.iter_mut().zip(delay) {
+            let delay_samples =
+                delay.clamp(HAAS_MIN_DELAY, HAAS_MAX_DELAY) * SAMPLE_FREQUENCY as f32;
+            self.buffer[self.write_index] = *s;
+            *s = read_fractional(&self.buffer, self.write_index as f32 - delay_samples);
+            self.write_index = (self.write_index + 1) % len;
+        }
+    }
+}
+
+pub struct HaasState {
+    side: HaasSide,
+    delay: HaasDelay,
+}
+
+impl ProcessorState for HaasState {
+    type Processor = Haas;
+
+    fn new(processor: &Haas) -> Self {
+        HaasState {
+            side: processor.side,
+            delay: HaasDelay::new(),
+        }
+    }
+}
+
+impl StartOver for HaasState {
+    fn start_over(&mut self) {
+        self.delay.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Haas {
+    pub input: SingleInput,
+    pub delay: ProcessorExpression,
+
+    #[not_a_component]
+    side: HaasSide,
+
+    #[state]
+    state: StateMarker<HaasState>,
+}
+
+impl Haas {
+    pub const ARG_SIDE: EnumArgument<HaasSide> = EnumArgument::new("side");
+
+    /// The channel which is delayed
+    pub fn side(&self) -> HaasSide {
+        self.side
+    }
+
+    pub fn set_side(&mut self, side: HaasSide) {
+        self.side = side;
+    }
+}
+
+impl SoundProcessor for Haas {
+    fn new(args: &ParsedArguments) -> Haas {
+        Haas {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            delay: ProcessorExpression::new(&[0.015], ArgumentScope::new_empty()),
+            side: args.get(&Haas::ARG_SIDE).unwrap_or(HaasSide::Right),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        haas: &mut CompiledHaas,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = haas.input.step(dst, InputContext::new(context));
+
+        let mut delay = [0.0; CHUNK_SIZE];
+        haas.delay.eval(
+            &mut [&mut delay],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        let channel = dst.channel_mut(haas.state.side.channel());
+        haas.state.delay.process(channel, &delay);
+
+        status
+    }
+}
+
+impl WithObjectType for Haas {
+    const TYPE: ObjectType = ObjectType::new("haas");
+}
+
+impl Stashable<StashingContext> for Haas {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.delay);
+        stasher.u8(self.side.to_u8());
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Haas {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.delay)?;
+        let side = HaasSide::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.side = side;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        antidenormal::AntiDenormal,
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        resample::read_fractional,
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{NaturalNumberArgument, ParsedArguments},
+};
+
+/// The lowest frequency, in Hz, to which the string can be tuned
+pub(crate) const STRING_MIN_FREQUENCY: f32 = 20.0;
+
+/// Peak amplitude of the noise burst which plucks the string
+const PLUCK_AMPLITUDE: f32 = 0.5;
+
+/// Coefficient of the lowpass filter at full damping
+const STRING_MAX_DAMPING: f32 = 0.7;
+
+/// Fraction of the signal retained on every trip around the delay
+/// line, independently of the damping, so that even an undamped
+/// string eventually dies away
+const STRING_DECAY: f32 = 0.999;
+
+/// A plucked string synthesized with the Karplus-Strong algorithm:
+/// a delay line filled with noise is fed back into itself through a
+/// lowpass filter, which gradually smooths the noise into a tone
+/// whose period is the length of the loop.
+pub(crate) struct PluckedString {
+    buffer: Vec<f32>,
+    write_index: usize,
+    previous: f32,
+    seed: u64,
+    rng: SmallRng,
+
+    /// Noise added to the feedback path, if enabled
+    anti_denormal: Option<AntiDenormal>,
+}
+
+impl PluckedString {
+    pub(crate) fn new(seed: u64) -> PluckedString {
+        let len = (SAMPLE_FREQUENCY as f32 / STRING_MIN_FREQUENCY).ceil() as usize + 2;
+        let mut string = PluckedString {
+            buffer: vec![0.0; len],
+            write_index: 0,
+            previous: 0.0,
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+            anti_denormal: None,
+        };
+        string.pluck();
+        string
+    }
+
+    /// Replace the contents of the delay line with a burst of noise.
+    /// Every pluck uses the same noise.
+    pub(crate) fn pluck(&mut self) {
+        self.rng = SmallRng::seed_from_u64(self.seed);
+        for s in &mut self.buffer {
+            *s = PLUCK_AMPLITUDE * self.rng.gen_range(-1.0..=1.0);
+        }
+        self.write_index = 0;
+        self.previous = 0.0;
+    }
+
+    /// Enable or disable adding inaudible noise to the feedback path,
+    /// which keeps the decaying string out of the denormal range
+    pub(crate) fn set_anti_denormal(&mut self, enabled: bool) {
+        self.anti_denormal = enabled.then(AntiDenormal::new);
+    }
+
+    /// Produce the next samples of the string, using the per-sample
+    /// frequency (in Hz) and damping (from 0 to 1) to tune the delay
+    /// line and its lowpass filter
+    pub(crate) fn process(&mut self, dst: &mut [f32], frequency: &[f32], damping: &[f32]) {
+        let len = self.buffer.len();
+        let max_delay = (len - 2) as f32;
+        for ((s, frequency), damping) in dst.iter_mut().zip(frequency).zip(damping) {
+            // The one-pole lowpass filter delays the fundamental by a
+            // fraction of a sample, which is subtracted from the delay
+            // line so that the loop as a whole stays in tune
+            let frequency = frequency.max(STRING_MIN_FREQUENCY);
+            let a = STRING_MAX_DAMPING * damping.clamp(0.0, 1.0);
+            let w = std::f32::consts::TAU * frequency / SAMPLE_FREQUENCY as f32;
+            let filter_delay = (a * w.sin()).atan2(1.0 - a * w.cos()) / w;
+            let period = SAMPLE_FREQUENCY as f32 / frequency;
+            let delay = (period - filter_delay).clamp(1.0, max_delay);
+
+            let delayed = read_fractional(&self.buffer, self.write_index as f32 - delay);
+            let filtered = (1.0 - a) * delayed + a * self.previous;
+            self.previous = filtered;
+
+            let noise = self.anti_denormal.as_mut().map_or(0.0, |a| a.next_sample());
+            self.buffer[self.write_index] = STRING_DECAY * filtered + noise;
+            self.write_index = (self.write_index + 1) % len;
+
+            *s = filtered;
+        }
+    }
+}
+
+pub struct KarplusStrongState {
+    string: PluckedString,
+}
+
+impl ProcessorState for KarplusStrongState {
+    type Processor = KarplusStrong;
+
+    fn new(processor: &KarplusStrong) -> Self {
+        let mut string = PluckedString::new(processor.seed);
+        string.set_anti_denormal(processor.anti_denormal);
+        KarplusStrongState { string }
+    }
+}
+
+impl StartOver for KarplusStrongState {
+    fn start_over(&mut self) {
+        self.string.pluck();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct KarplusStrong {
+    pub frequency: ProcessorExpression,
+    pub damping: ProcessorExpression,
+
+    #[not_a_component]
+    seed: u64,
+
+    #[not_a_component]
+    anti_denormal: bool,
+
+    #[state]
+    state: StateMarker<KarplusStrongState>,
+}
+
+impl KarplusStrong {
+    pub const ARG_SEED: NaturalNumberArgument = NaturalNumberArgument("seed");
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Whether inaudible noise is added to the feedback path to keep
+    /// it out of the denormal range, where processing is much slower
+    /// on some platforms
+    pub fn anti_denormal(&self) -> bool {
+        self.anti_denormal
+    }
+
+    pub fn set_anti_denormal(&mut self, enabled: bool) {
+        self.anti_denormal = enabled;
+    }
+}
+
+impl SoundProcessor for KarplusStrong {
+    fn new(args: &ParsedArguments) -> KarplusStrong {
+        KarplusStrong {
+            frequency: ProcessorExpression::new(&[220.0], ArgumentScope::new_empty()),
+            damping: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+            seed: args.get(&KarplusStrong::ARG_SEED).unwrap_or(0) as u64,
+            anti_denormal: false,
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        karplus_strong: &mut CompiledKarplusStrong,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let mut frequency = [0.0; CHUNK_SIZE];
+        let mut damping = [0.0; CHUNK_SIZE];
+        karplus_strong.frequency.eval(
+            &mut [&mut frequency],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        karplus_strong.damping.eval(
+            &mut [&mut damping],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        karplus_strong
+            .state
+            .string
+            .process(&mut dst.l, &frequency, &damping);
+        dst.r = dst.l;
+
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for KarplusStrong {
+    const TYPE: ObjectType = ObjectType::new("karplusstrong");
+}
+
+impl Stashable<StashingContext> for KarplusStrong {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.frequency);
+        stasher.object(&self.damping);
+        stasher.u64(self.seed);
+        stasher.bool(self.anti_denormal);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for KarplusStrong {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.frequency)?;
+        unstasher.object_inplace(&mut self.damping)?;
+        unstasher.u64_inplace(&mut self.seed)?;
+        unstasher.bool_inplace(&mut self.anti_denormal)?;
+        Ok(())
+    }
+}
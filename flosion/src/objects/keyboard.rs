@@ -1,43 +1,178 @@
-use std::cell::RefCell;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
+use atomic_float::AtomicF32;
 use flosion_macros::ProcessorComponent;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
 
 use crate::{
     core::{
+        engine::soundengine::{event_queue, EventReceiver, EventSender},
         objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
         sound::{
             argument::{ArgumentScope, ProcessorArgument},
-            argumenttypes::f32argument::F32Argument,
+            argumenttypes::{f32argument::F32Argument, plainf32array::PlainF32ArrayArgument},
             context::AudioContext,
-            inputtypes::keyedinputqueue::{KeyReuse, KeyedInputQueue},
+            inputtypes::keyedinputqueue::{KeyNote, KeyReuse, KeyedInputQueue},
+            panicfade::PanicFade,
             soundprocessor::{
                 ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
             },
         },
-        soundchunk::SoundChunk,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
         stashing::{StashingContext, UnstashingContext},
     },
-    ui_core::arguments::ParsedArguments,
+    ui_core::arguments::{ArgumentEnum, EnumArgument, ParsedArguments},
 };
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct KeyId(pub usize);
 
+/// Which playing voice to replace when a key is started while
+/// all voices are in use
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VoiceStealing {
+    /// Replace the voice which was started longest ago
+    Oldest,
+    /// Replace the voice with the lowest velocity
+    Quietest,
+    /// Replace the voice with the lowest frequency
+    Lowest,
+    /// Replace the voice with the highest frequency
+    Highest,
+}
+
+impl VoiceStealing {
+    fn to_u8(self) -> u8 {
+        match self {
+            VoiceStealing::Oldest => 0,
+            VoiceStealing::Quietest => 1,
+            VoiceStealing::Lowest => 2,
+            VoiceStealing::Highest => 3,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<VoiceStealing> {
+        match x {
+            0 => Some(VoiceStealing::Oldest),
+            1 => Some(VoiceStealing::Quietest),
+            2 => Some(VoiceStealing::Lowest),
+            3 => Some(VoiceStealing::Highest),
+            _ => None,
+        }
+    }
+
+    /// The priority with which the given playing voice is replaced,
+    /// where the voice with the greatest priority is replaced first
+    fn priority(self, key: &KeyboardKeyState, age: usize) -> f32 {
+        match self {
+            VoiceStealing::Oldest => age as f32,
+            VoiceStealing::Quietest => -key.velocity,
+            VoiceStealing::Lowest => -key.glide.to,
+            VoiceStealing::Highest => key.glide.to,
+        }
+    }
+}
+
+impl ArgumentEnum for VoiceStealing {
+    fn all_values() -> &'static [VoiceStealing] {
+        &[
+            VoiceStealing::Oldest,
+            VoiceStealing::Quietest,
+            VoiceStealing::Lowest,
+            VoiceStealing::Highest,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            VoiceStealing::Oldest => "oldest",
+            VoiceStealing::Quietest => "quietest",
+            VoiceStealing::Lowest => "lowest",
+            VoiceStealing::Highest => "highest",
+        }
+    }
+}
+
+/// The MIDI-style note number of the given frequency, where
+/// 69 is A4 at 440 Hz and each semitone is one unit apart
+fn note_of_frequency(frequency: f32) -> f32 {
+    69.0 + 12.0 * (frequency / 440.0).log2()
+}
+
+/// A pitch glide from one frequency to another which takes a fixed
+/// number of samples and moves at a constant rate in octaves
+struct Glide {
+    from: f32,
+    to: f32,
+    elapsed: usize,
+    duration: usize,
+}
+
+impl Glide {
+    fn new(from: f32, to: f32, duration: usize) -> Glide {
+        Glide {
+            from,
+            to,
+            elapsed: 0,
+            duration,
+        }
+    }
+
+    fn fill(&mut self, dst: &mut [f32]) {
+        for x in dst {
+            if self.elapsed >= self.duration {
+                *x = self.to;
+                continue;
+            }
+            let t = self.elapsed as f32 / self.duration as f32;
+            *x = self.from * (self.to / self.from).powf(t);
+            self.elapsed += 1;
+        }
+    }
+}
+
+/// Per-voice state. The velocity and note are latched when the key is
+/// started and remain unchanged for the duration of the note, while
+/// the frequency may glide from that of the previous note.
 pub struct KeyboardKeyState {
-    frequency: f32,
+    glide: Glide,
+    frequency: [f32; CHUNK_SIZE],
+    velocity: f32,
+    note: f32,
 }
 
 impl StartOver for KeyboardKeyState {
     fn start_over(&mut self) {
-        self.frequency = 0.0;
+        self.glide = Glide::new(0.0, 0.0, 0);
+        self.frequency = [0.0; CHUNK_SIZE];
+        self.velocity = 0.0;
+        self.note = 0.0;
+    }
+}
+
+impl KeyNote for KeyboardKeyState {
+    fn note(&self) -> f32 {
+        self.note
     }
 }
 
 #[derive(Clone, Copy)]
 enum KeyboardCommand {
-    StartKey { id: KeyId, frequency: f32 },
-    ReleaseKey { id: KeyId },
+    StartKey {
+        id: KeyId,
+        frequency: f32,
+        velocity: f32,
+    },
+    ReleaseKey {
+        id: KeyId,
+    },
     ReleaseAllKeys,
 }
 
@@ -48,8 +183,31 @@ impl Default for KeyboardCommand {
     }
 }
 
+pub struct KeyboardSettings {
+    glide_time: AtomicF32,
+    legato_only: AtomicBool,
+}
+
 pub struct KeyboardState {
-    command_reader: spmcq::Reader<KeyboardCommand>,
+    command_receiver: EventReceiver<KeyboardCommand>,
+    voice_stealing: VoiceStealing,
+    settings: Arc<KeyboardSettings>,
+    previous_frequency: Option<f32>,
+}
+
+impl KeyboardState {
+    /// The glide for a new key with the given frequency, given
+    /// whether any other keys are currently being held
+    fn glide_to(&self, frequency: f32, legato: bool) -> Glide {
+        let glide_time = self.settings.glide_time.load(Ordering::Relaxed);
+        let legato_only = self.settings.legato_only.load(Ordering::Relaxed);
+        let from = match self.previous_frequency {
+            Some(f) if glide_time > 0.0 && (legato || !legato_only) => f,
+            _ => frequency,
+        };
+        let duration = (glide_time * SAMPLE_FREQUENCY as f32).round() as usize;
+        Glide::new(from, frequency, duration)
+    }
 }
 
 impl ProcessorState for KeyboardState {
@@ -57,67 +215,159 @@ impl ProcessorState for KeyboardState {
 
     fn new(processor: &Keyboard) -> Self {
         KeyboardState {
-            command_reader: processor.command_reader.clone(),
+            command_receiver: processor.command_receiver.clone(),
+            voice_stealing: processor.voice_stealing,
+            settings: Arc::clone(&processor.settings),
+            previous_frequency: None,
         }
     }
+
+    fn take_over(&mut self, previous: &mut KeyboardState) {
+        self.command_receiver
+            .take_over(&mut previous.command_receiver);
+    }
 }
 
 impl StartOver for KeyboardState {
     fn start_over(&mut self) {
-        // ???
+        self.previous_frequency = None;
     }
 }
 
 #[derive(ProcessorComponent)]
 pub struct Keyboard {
     pub input: KeyedInputQueue<KeyboardKeyState>,
-    pub key_frequency: ProcessorArgument<F32Argument>,
+    pub key_frequency: ProcessorArgument<PlainF32ArrayArgument>,
+    pub key_velocity: ProcessorArgument<F32Argument>,
+    pub key_note: ProcessorArgument<F32Argument>,
+
+    panic_fade: PanicFade,
+
+    #[not_a_component]
+    voice_stealing: VoiceStealing,
+
+    #[not_a_component]
+    settings: Arc<KeyboardSettings>,
 
     #[not_a_component]
-    command_reader: spmcq::Reader<KeyboardCommand>,
+    command_receiver: EventReceiver<KeyboardCommand>,
 
     #[not_a_component]
-    command_writer: RefCell<spmcq::Writer<KeyboardCommand>>,
+    command_sender: RefCell<EventSender<KeyboardCommand>>,
 
     #[state]
     state: StateMarker<KeyboardState>,
 }
 
 impl Keyboard {
-    pub fn start_key(&self, id: KeyId, frequency: f32) {
-        self.command_writer
+    pub const ARG_STEALING: EnumArgument<VoiceStealing> = EnumArgument::new("stealing");
+
+    pub fn voice_stealing(&self) -> VoiceStealing {
+        self.voice_stealing
+    }
+
+    pub fn set_voice_stealing(&mut self, voice_stealing: VoiceStealing) {
+        self.voice_stealing = voice_stealing;
+    }
+
+    /// The time taken, in seconds, for the frequency of a new
+    /// key to glide from that of the previous key
+    pub fn glide_time(&self) -> f32 {
+        self.settings.glide_time.load(Ordering::Relaxed)
+    }
+
+    pub fn set_glide_time(&self, seconds: f32) {
+        self.settings.glide_time.store(seconds, Ordering::Relaxed);
+    }
+
+    /// Whether new keys only glide while another key is being held
+    pub fn legato_only(&self) -> bool {
+        self.settings.legato_only.load(Ordering::Relaxed)
+    }
+
+    pub fn set_legato_only(&self, legato_only: bool) {
+        self.settings
+            .legato_only
+            .store(legato_only, Ordering::Relaxed);
+    }
+
+    /// The time in samples, as counted by the keyboard since it was
+    /// created, at which the chunk currently being played started.
+    /// Keys can be scheduled relative to this time.
+    pub fn current_time(&self) -> u64 {
+        self.command_sender.borrow().now()
+    }
+
+    pub fn start_key(&self, id: KeyId, frequency: f32, velocity: f32) {
+        self.command_sender
             .borrow_mut()
-            .write(KeyboardCommand::StartKey { id, frequency });
+            .send(KeyboardCommand::StartKey {
+                id,
+                frequency,
+                velocity,
+            });
+    }
+
+    /// Start a key at exactly the given time in samples. Keys must be
+    /// started and released in order of time.
+    pub fn start_key_at(&self, time: u64, id: KeyId, frequency: f32, velocity: f32) {
+        self.command_sender.borrow_mut().send_at(
+            time,
+            KeyboardCommand::StartKey {
+                id,
+                frequency,
+                velocity,
+            },
+        );
     }
 
     pub fn release_key(&self, id: KeyId) {
-        self.command_writer
+        self.command_sender
             .borrow_mut()
-            .write(KeyboardCommand::ReleaseKey { id });
+            .send(KeyboardCommand::ReleaseKey { id });
+    }
+
+    /// Release a key at exactly the given time in samples
+    pub fn release_key_at(&self, time: u64, id: KeyId) {
+        self.command_sender
+            .borrow_mut()
+            .send_at(time, KeyboardCommand::ReleaseKey { id });
     }
 
     pub fn release_all_keys(&self) {
-        self.command_writer
+        self.command_sender
             .borrow_mut()
-            .write(KeyboardCommand::ReleaseAllKeys);
+            .send(KeyboardCommand::ReleaseAllKeys);
     }
 }
 
 impl SoundProcessor for Keyboard {
-    fn new(_args: &ParsedArguments) -> Keyboard {
+    fn new(args: &ParsedArguments) -> Keyboard {
         let message_queue_size = 16; // idk
         let input_queue_size = 8; // idk
         let key_frequency = ProcessorArgument::new();
-        let (command_reader, command_writer) = spmcq::ring_buffer(message_queue_size);
+        let key_velocity = ProcessorArgument::new();
+        let key_note = ProcessorArgument::new();
+        let (command_sender, command_receiver) = event_queue(message_queue_size);
         let input = KeyedInputQueue::new(
             input_queue_size,
-            ArgumentScope::new(vec![key_frequency.id()]),
+            ArgumentScope::new(vec![key_frequency.id(), key_velocity.id(), key_note.id()]),
         );
         Keyboard {
             input,
             key_frequency,
-            command_writer: RefCell::new(command_writer),
-            command_reader: command_reader,
+            key_velocity,
+            key_note,
+            panic_fade: PanicFade,
+            voice_stealing: args
+                .get(&Keyboard::ARG_STEALING)
+                .unwrap_or(VoiceStealing::Oldest),
+            settings: Arc::new(KeyboardSettings {
+                glide_time: AtomicF32::new(0.0),
+                legato_only: AtomicBool::new(false),
+            }),
+            command_sender: RefCell::new(command_sender),
+            command_receiver,
             state: StateMarker::new(),
         }
     }
@@ -132,24 +382,54 @@ impl SoundProcessor for Keyboard {
         context: &mut AudioContext,
     ) -> StreamStatus {
         let reuse = KeyReuse::StopOldStartNew;
-        while let Some(msg) = keyboard.state.command_reader.read().value() {
+        let voice_stealing = keyboard.state.voice_stealing;
+        while let Some((offset, msg)) = keyboard.state.command_receiver.receive() {
             match msg {
-                KeyboardCommand::StartKey { id, frequency } => {
-                    keyboard
-                        .input
-                        .start_key(None, id.0, KeyboardKeyState { frequency }, reuse);
+                KeyboardCommand::StartKey { .. } if keyboard.panic_fade.is_engaged() => {
+                    // No new voices are started while panicking
+                }
+                KeyboardCommand::StartKey {
+                    id,
+                    frequency,
+                    velocity,
+                } => {
+                    let legato = keyboard.input.num_held_keys() > 0;
+                    let key_state = KeyboardKeyState {
+                        glide: keyboard.state.glide_to(frequency, legato),
+                        frequency: [frequency; CHUNK_SIZE],
+                        velocity,
+                        note: note_of_frequency(frequency),
+                    };
+                    keyboard.input.start_key_stealing(
+                        None,
+                        offset,
+                        id.0,
+                        key_state,
+                        reuse,
+                        |s, age| voice_stealing.priority(s, age),
+                    );
+                    keyboard.state.previous_frequency = Some(frequency);
                 }
                 KeyboardCommand::ReleaseKey { id } => {
-                    keyboard.input.release_key(id.0);
+                    keyboard.input.release_key_at(id.0, offset);
                 }
                 KeyboardCommand::ReleaseAllKeys => {
                     keyboard.input.release_all_keys();
                 }
             }
         }
+        keyboard.state.command_receiver.finish_chunk();
+
+        // Let go of any held keys as soon as the panic starts
+        if keyboard.panic_fade.is_engaged() && keyboard.input.num_held_keys() > 0 {
+            keyboard.input.release_all_keys();
+        }
 
         keyboard.input.step_active_keys(dst, context, |s, ctx| {
-            ctx.push(keyboard.key_frequency, s.frequency)
+            s.glide.fill(&mut s.frequency);
+            ctx.push(keyboard.key_frequency, &s.frequency)
+                .push(keyboard.key_velocity, s.velocity)
+                .push(keyboard.key_note, s.note)
         });
 
         StreamStatus::Playing
@@ -164,6 +444,18 @@ impl Stashable<StashingContext> for Keyboard {
     fn stash(&self, stasher: &mut Stasher<StashingContext>) {
         stasher.object(&self.input);
         stasher.object(&self.key_frequency);
+        stasher.object(&self.key_velocity);
+        stasher.object(&self.key_note);
+        stasher.u8(self.voice_stealing.to_u8());
+        if stasher.context().checking_recompilation() {
+            // Settings are read atomically on the audio thread, so
+            // changing them doesn't require recompiling anything
+            let ptr: *const KeyboardSettings = &*self.settings;
+            stasher.u64((ptr as usize) as _);
+        } else {
+            stasher.f32(self.glide_time());
+            stasher.bool(self.legato_only());
+        }
     }
 }
 
@@ -174,6 +466,19 @@ impl UnstashableInplace<UnstashingContext<'_>> for Keyboard {
     ) -> Result<(), UnstashError> {
         unstasher.object_inplace(&mut self.input)?;
         unstasher.object_inplace(&mut self.key_frequency)?;
+        unstasher.object_inplace(&mut self.key_velocity)?;
+        unstasher.object_inplace(&mut self.key_note)?;
+        let voice_stealing =
+            VoiceStealing::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.voice_stealing = voice_stealing;
+        }
+        let glide_time = unstasher.f32_always()?;
+        let legato_only = unstasher.bool_always()?;
+        if unstasher.time_to_write() {
+            self.set_glide_time(glide_time);
+            self.set_legato_only(legato_only);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,331 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        beatsync::BeatSync,
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// One chunk of every shape produced by an LFO, each from -1 to 1
+pub(crate) struct LfoOutputs {
+    pub sine: [f32; CHUNK_SIZE],
+    pub triangle: [f32; CHUNK_SIZE],
+    pub ramp: [f32; CHUNK_SIZE],
+    pub square: [f32; CHUNK_SIZE],
+    pub sample_and_hold: [f32; CHUNK_SIZE],
+}
+
+impl LfoOutputs {
+    pub(crate) fn new() -> LfoOutputs {
+        LfoOutputs {
+            sine: [0.0; CHUNK_SIZE],
+            triangle: [0.0; CHUNK_SIZE],
+            ramp: [0.0; CHUNK_SIZE],
+            square: [0.0; CHUNK_SIZE],
+            sample_and_hold: [0.0; CHUNK_SIZE],
+        }
+    }
+}
+
+/// The phase and held random value of an LFO
+pub(crate) struct LfoOscillator {
+    /// Kept in double precision, since the tiny per-sample increments
+    /// at low rates would otherwise accumulate noticeable drift
+    phase: f64,
+    previous_phase: f32,
+    held: f32,
+    rng: SmallRng,
+}
+
+impl LfoOscillator {
+    pub(crate) fn new() -> LfoOscillator {
+        let mut osc = LfoOscillator {
+            phase: 0.0,
+            previous_phase: 0.0,
+            held: 0.0,
+            rng: SmallRng::seed_from_u64(0),
+        };
+        osc.reset();
+        osc
+    }
+
+    /// Go back to the start of the cycle, taking a new held value
+    pub(crate) fn retrigger(&mut self) {
+        self.phase = 0.0;
+        self.previous_phase = 1.0;
+    }
+
+    /// Go back to the start of the first cycle
+    pub(crate) fn reset(&mut self) {
+        self.phase = 0.0;
+        self.previous_phase = 0.0;
+        self.rng = SmallRng::seed_from_u64(0);
+        self.held = self.rng.gen_range(-1.0..=1.0);
+    }
+
+    /// Produce one chunk of every shape, given per-sample rates in Hz,
+    /// shapes and phase offsets in cycles. The shape is the fraction of
+    /// each cycle which the triangle spends rising and which the square
+    /// spends high. The sample-and-hold output takes a new random value
+    /// at the start of every cycle. The cycle starts over at every
+    /// sample which is marked as retriggered.
+    pub(crate) fn generate(
+        &mut self,
+        rate: &[f32],
+        shape: &[f32],
+        phase_offset: &[f32],
+        retriggered: &[bool],
+        outputs: &mut LfoOutputs,
+    ) {
+        debug_assert_eq!(rate.len(), CHUNK_SIZE);
+        debug_assert_eq!(shape.len(), CHUNK_SIZE);
+        debug_assert_eq!(phase_offset.len(), CHUNK_SIZE);
+        debug_assert_eq!(retriggered.len(), CHUNK_SIZE);
+        for i in 0..CHUNK_SIZE {
+            if retriggered[i] {
+                self.retrigger();
+            }
+            let offset = if phase_offset[i].is_finite() {
+                phase_offset[i]
+            } else {
+                0.0
+            };
+            let p = (self.phase + offset as f64).rem_euclid(1.0) as f32;
+            if p < self.previous_phase {
+                self.held = self.rng.gen_range(-1.0..=1.0);
+            }
+            self.previous_phase = p;
+
+            let shape = if shape[i].is_finite() {
+                shape[i].clamp(1e-6, 1.0 - 1e-6)
+            } else {
+                0.5
+            };
+
+            outputs.sine[i] = (std::f32::consts::TAU * p).sin();
+            outputs.triangle[i] = if p < shape {
+                2.0 * p / shape - 1.0
+            } else {
+                1.0 - 2.0 * (p - shape) / (1.0 - shape)
+            };
+            outputs.ramp[i] = 2.0 * p - 1.0;
+            outputs.square[i] = if p < shape { 1.0 } else { -1.0 };
+            outputs.sample_and_hold[i] = self.held;
+
+            self.phase = (self.phase + rate[i] as f64 / SAMPLE_FREQUENCY as f64).rem_euclid(1.0);
+            if !self.phase.is_finite() {
+                self.phase = 0.0;
+            }
+        }
+    }
+}
+
+pub struct LfoState {
+    sync_to_time: bool,
+    beat_sync: BeatSync,
+    oscillator: LfoOscillator,
+}
+
+impl ProcessorState for LfoState {
+    type Processor = Lfo;
+
+    fn new(processor: &Lfo) -> Self {
+        LfoState {
+            sync_to_time: processor.sync_to_time,
+            beat_sync: processor.beat_sync,
+            oscillator: LfoOscillator::new(),
+        }
+    }
+}
+
+impl StartOver for LfoState {
+    fn start_over(&mut self) {
+        if self.sync_to_time {
+            self.oscillator.reset();
+        }
+    }
+}
+
+/// A low-frequency oscillator whose sine, triangle, ramp, square, and
+/// sample-and-hold shapes are all available at once as arguments to
+/// the expressions of its input.
+#[derive(ProcessorComponent)]
+pub struct Lfo {
+    pub input: SingleInput,
+    pub rate: ProcessorExpression,
+    pub shape: ProcessorExpression,
+    pub phase_offset: ProcessorExpression,
+
+    pub sine: ProcessorArgument<PlainF32ArrayArgument>,
+    pub triangle: ProcessorArgument<PlainF32ArrayArgument>,
+    pub ramp: ProcessorArgument<PlainF32ArrayArgument>,
+    pub square: ProcessorArgument<PlainF32ArrayArgument>,
+    pub sample_and_hold: ProcessorArgument<PlainF32ArrayArgument>,
+
+    /// Whether the phase follows the processor's time, starting over
+    /// whenever the processor does, rather than running freely
+    #[not_a_component]
+    sync_to_time: bool,
+
+    #[not_a_component]
+    beat_sync: BeatSync,
+
+    #[state]
+    state: StateMarker<LfoState>,
+}
+
+impl Lfo {
+    pub fn sync_to_time(&self) -> bool {
+        self.sync_to_time
+    }
+
+    pub fn set_sync_to_time(&mut self, sync_to_time: bool) {
+        self.sync_to_time = sync_to_time;
+    }
+
+    pub fn beat_sync(&self) -> BeatSync {
+        self.beat_sync
+    }
+
+    pub fn set_beat_sync(&mut self, beat_sync: BeatSync) {
+        self.beat_sync = beat_sync;
+    }
+}
+
+impl SoundProcessor for Lfo {
+    fn new(_args: &ParsedArguments) -> Lfo {
+        let sine = ProcessorArgument::new();
+        let triangle = ProcessorArgument::new();
+        let ramp = ProcessorArgument::new();
+        let square = ProcessorArgument::new();
+        let sample_and_hold = ProcessorArgument::new();
+        let scope = ArgumentScope::new(vec![
+            sine.id(),
+            triangle.id(),
+            ramp.id(),
+            square.id(),
+            sample_and_hold.id(),
+        ]);
+        Lfo {
+            input: SingleInput::new_isochronic(scope),
+            rate: ProcessorExpression::new(&[1.0], ArgumentScope::new_empty()),
+            shape: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+            phase_offset: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            sine,
+            triangle,
+            ramp,
+            square,
+            sample_and_hold,
+            sync_to_time: true,
+            beat_sync: BeatSync::new(),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        lfo: &mut CompiledLfo,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let mut rate = [0.0; CHUNK_SIZE];
+        let mut shape = [0.0; CHUNK_SIZE];
+        let mut phase_offset = [0.0; CHUNK_SIZE];
+        lfo.rate.eval(
+            &mut [&mut rate],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        lfo.shape.eval(
+            &mut [&mut shape],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        lfo.phase_offset.eval(
+            &mut [&mut phase_offset],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        let first_sample = context.current_processor_timing().elapsed_chunks() * CHUNK_SIZE;
+        let retriggered = lfo.state.beat_sync.retriggered_samples(first_sample);
+
+        let mut outputs = LfoOutputs::new();
+        lfo.state
+            .oscillator
+            .generate(&rate, &shape, &phase_offset, &retriggered, &mut outputs);
+
+        lfo.input.step(
+            dst,
+            InputContext::new(context)
+                .push(lfo.sine, &outputs.sine)
+                .push(lfo.triangle, &outputs.triangle)
+                .push(lfo.ramp, &outputs.ramp)
+                .push(lfo.square, &outputs.square)
+                .push(lfo.sample_and_hold, &outputs.sample_and_hold),
+        )
+    }
+}
+
+impl WithObjectType for Lfo {
+    const TYPE: ObjectType = ObjectType::new("lfo");
+}
+
+impl Stashable<StashingContext> for Lfo {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.rate);
+        stasher.object(&self.shape);
+        stasher.object(&self.phase_offset);
+        stasher.object(&self.sine);
+        stasher.object(&self.triangle);
+        stasher.object(&self.ramp);
+        stasher.object(&self.square);
+        stasher.object(&self.sample_and_hold);
+        stasher.bool(self.sync_to_time);
+        stasher.object_with_context(&self.beat_sync, ());
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Lfo {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.rate)?;
+        unstasher.object_inplace(&mut self.shape)?;
+        unstasher.object_inplace(&mut self.phase_offset)?;
+        unstasher.object_inplace(&mut self.sine)?;
+        unstasher.object_inplace(&mut self.triangle)?;
+        unstasher.object_inplace(&mut self.ramp)?;
+        unstasher.object_inplace(&mut self.square)?;
+        unstasher.object_inplace(&mut self.sample_and_hold)?;
+        unstasher.bool_inplace(&mut self.sync_to_time)?;
+        unstasher.object_inplace_with_context(&mut self.beat_sync, ())?;
+        Ok(())
+    }
+}
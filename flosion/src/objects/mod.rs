@@ -1,23 +1,46 @@
 pub mod adsr;
+pub mod arpeggiator;
 pub mod audioclip;
+pub mod automation;
+pub mod binaural;
+pub mod chorus;
+pub mod compressor;
+pub mod crossfade;
 pub mod definitions;
+pub mod downmix;
 pub mod ensemble;
+pub mod formantfilter;
+pub mod gain;
+pub mod haas;
+pub mod karplusstrong;
 pub mod input;
 pub mod keyboard;
+pub mod lfo;
 // pub mod melody;
 pub mod mixer;
+pub mod noise;
 pub mod oscilloscope;
 pub mod output;
 pub mod purefunctions;
+pub mod quantizetoscale;
 pub mod readwritewaveform;
 // pub mod recorder;
 pub mod resampler;
 pub mod sampler1d;
+pub mod sampler2d;
 pub mod scatter;
 pub mod scheduler;
+pub mod slew;
+pub mod spectralgate;
 pub mod statefulfunctions;
+pub mod stereowidth;
+pub mod tap;
+pub mod tremolo;
+pub mod vocoder;
 pub mod wavegenerator;
+pub mod wavetable;
 pub mod whitenoise;
+pub mod widen;
 pub mod writewaveform;
 
 #[cfg(test)]
@@ -0,0 +1,259 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        pinknoise::PinkNoise,
+        sound::{
+            context::AudioContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{ArgumentEnum, EnumArgument, NaturalNumberArgument, ParsedArguments},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NoiseColor {
+    /// Equal power at all frequencies
+    White,
+    /// Power falls off by 3 dB per octave
+    Pink,
+    /// Power falls off by 6 dB per octave
+    Brown,
+}
+
+impl NoiseColor {
+    fn to_u8(self) -> u8 {
+        match self {
+            NoiseColor::White => 0,
+            NoiseColor::Pink => 1,
+            NoiseColor::Brown => 2,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<NoiseColor> {
+        match x {
+            0 => Some(NoiseColor::White),
+            1 => Some(NoiseColor::Pink),
+            2 => Some(NoiseColor::Brown),
+            _ => None,
+        }
+    }
+}
+
+impl ArgumentEnum for NoiseColor {
+    fn all_values() -> &'static [NoiseColor] {
+        &[NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            NoiseColor::White => "white",
+            NoiseColor::Pink => "pink",
+            NoiseColor::Brown => "brown",
+        }
+    }
+}
+
+/// Peak amplitude of the white noise from which all colors are derived
+const NOISE_AMPLITUDE: f32 = 0.1;
+
+/// Brown noise generator using a leaky integrator of white noise
+struct BrownNoise {
+    level: f32,
+}
+
+impl BrownNoise {
+    fn new() -> BrownNoise {
+        BrownNoise { level: 0.0 }
+    }
+
+    fn reset(&mut self) {
+        self.level = 0.0;
+    }
+
+    fn next(&mut self, rng: &mut SmallRng) -> f32 {
+        let white: f32 = rng.gen_range(-1.0..=1.0);
+        self.level = (self.level + 0.02 * white) / 1.02;
+        // Scale so that the overall power is comparable to white noise
+        3.5 * self.level
+    }
+}
+
+pub struct NoiseState {
+    color: NoiseColor,
+    seed: u64,
+    rng: SmallRng,
+    pink: [PinkNoise; 2],
+    brown: [BrownNoise; 2],
+}
+
+impl NoiseState {
+    fn fill(&mut self, dst: &mut [f32], channel: usize) {
+        match self.color {
+            NoiseColor::White => {
+                for s in dst.iter_mut() {
+                    *s = self.rng.gen_range(-1.0..=1.0);
+                }
+            }
+            NoiseColor::Pink => {
+                for s in dst.iter_mut() {
+                    *s = self.pink[channel].next(&mut self.rng);
+                }
+            }
+            NoiseColor::Brown => {
+                for s in dst.iter_mut() {
+                    *s = self.brown[channel].next(&mut self.rng);
+                }
+            }
+        }
+        slicemath::mul_scalar_inplace(dst, NOISE_AMPLITUDE);
+    }
+}
+
+impl ProcessorState for NoiseState {
+    type Processor = Noise;
+
+    fn new(processor: &Noise) -> Self {
+        NoiseState {
+            color: processor.color,
+            seed: processor.seed,
+            rng: SmallRng::seed_from_u64(processor.seed),
+            pink: [PinkNoise::new(), PinkNoise::new()],
+            brown: [BrownNoise::new(), BrownNoise::new()],
+        }
+    }
+}
+
+impl StartOver for NoiseState {
+    fn start_over(&mut self) {
+        self.rng = SmallRng::seed_from_u64(self.seed);
+        for p in &mut self.pink {
+            p.reset();
+        }
+        for b in &mut self.brown {
+            b.reset();
+        }
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Noise {
+    #[not_a_component]
+    color: NoiseColor,
+
+    #[not_a_component]
+    seed: u64,
+
+    #[state]
+    state: StateMarker<NoiseState>,
+}
+
+impl Noise {
+    pub const ARG_COLOR: EnumArgument<NoiseColor> = EnumArgument::new("color");
+    pub const ARG_SEED: NaturalNumberArgument = NaturalNumberArgument("seed");
+
+    pub fn color(&self) -> NoiseColor {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: NoiseColor) {
+        self.color = color;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+}
+
+impl SoundProcessor for Noise {
+    fn new(args: &ParsedArguments) -> Noise {
+        Noise {
+            color: args.get(&Noise::ARG_COLOR).unwrap_or(NoiseColor::White),
+            seed: args.get(&Noise::ARG_SEED).unwrap_or(0) as u64,
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        noise: &mut CompiledNoise,
+        dst: &mut SoundChunk,
+        _context: &mut AudioContext,
+    ) -> StreamStatus {
+        noise.state.fill(&mut dst.l, 0);
+        noise.state.fill(&mut dst.r, 1);
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for Noise {
+    const TYPE: ObjectType = ObjectType::new("noise");
+}
+
+impl Stashable<StashingContext> for Noise {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.u8(self.color.to_u8());
+        stasher.u64(self.seed);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Noise {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        let color = NoiseColor::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.color = color;
+        }
+        unstasher.u64_inplace(&mut self.seed)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hashstash::test_stash_roundtrip_inplace;
+
+    use crate::{
+        core::{
+            expression::expressionobject::ExpressionObjectFactory,
+            sound::{soundobject::SoundObjectFactory, soundprocessor::SoundProcessor},
+            stashing::{StashingContext, UnstashingContext},
+        },
+        ui_core::arguments::ParsedArguments,
+    };
+
+    use super::{Noise, NoiseColor};
+
+    #[test]
+    fn test_stash() {
+        let obj_fac = SoundObjectFactory::new_empty();
+        let expr_fac = ExpressionObjectFactory::new_empty();
+
+        test_stash_roundtrip_inplace(
+            || Noise::new(&ParsedArguments::new_empty()),
+            |noise| {
+                noise.set_color(NoiseColor::Brown);
+                noise.set_seed(1234);
+            },
+            StashingContext::new_stashing_normally(),
+            UnstashingContext::new(&obj_fac, &expr_fac),
+        )
+        .unwrap();
+    }
+}
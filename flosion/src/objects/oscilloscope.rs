@@ -1,8 +1,5 @@
-use std::sync::Arc;
-
 use flosion_macros::ProcessorComponent;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
-use parking_lot::Mutex;
 
 use crate::{
     core::{
@@ -19,6 +16,7 @@ use crate::{
         soundchunk::SoundChunk,
         stashing::{StashingContext, UnstashingContext},
     },
+    objects::tap::{tap_ring, TapReader, TapWriter},
     ui_core::arguments::ParsedArguments,
 };
 
@@ -27,26 +25,23 @@ pub struct Oscilloscope {
     pub input: SingleInput,
 
     #[not_a_component]
-    chunk_reader: spmcq::Reader<SoundChunk>,
-    // NOTE: using Arc<Mutex<...>> because spmcq::Writer can't be cloned.
-    // It might be worth using a different queue or somehow guaranteeing
-    // at the type system level that only once instance of a static processor's
-    // state exists at one time
+    chunk_reader: TapReader,
+
     #[not_a_component]
-    chunk_writer: Arc<Mutex<spmcq::Writer<SoundChunk>>>,
+    chunk_writer: TapWriter,
 
     #[state]
     state: StateMarker<OscilloscopeState>,
 }
 
 impl Oscilloscope {
-    pub fn get_buffer_reader(&self) -> spmcq::Reader<SoundChunk> {
+    pub fn get_buffer_reader(&self) -> TapReader {
         self.chunk_reader.clone()
     }
 }
 
 pub struct OscilloscopeState {
-    chunk_writer: Arc<Mutex<spmcq::Writer<SoundChunk>>>,
+    chunk_writer: TapWriter,
 }
 
 impl ProcessorState for OscilloscopeState {
@@ -54,7 +49,7 @@ impl ProcessorState for OscilloscopeState {
 
     fn new(processor: &Self::Processor) -> Self {
         OscilloscopeState {
-            chunk_writer: Arc::clone(&processor.chunk_writer),
+            chunk_writer: processor.chunk_writer.clone(),
         }
     }
 }
@@ -67,11 +62,11 @@ impl StartOver for OscilloscopeState {
 
 impl SoundProcessor for Oscilloscope {
     fn new(_args: &ParsedArguments) -> Oscilloscope {
-        let (reader, writer) = spmcq::ring_buffer(64);
+        let (writer, reader) = tap_ring();
         Oscilloscope {
             input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
             chunk_reader: reader,
-            chunk_writer: Arc::new(Mutex::new(writer)),
+            chunk_writer: writer,
             state: StateMarker::new(),
         }
     }
@@ -86,7 +81,7 @@ impl SoundProcessor for Oscilloscope {
         context: &mut AudioContext,
     ) -> StreamStatus {
         oscilloscope.input.step(dst, InputContext::new(context));
-        oscilloscope.state.chunk_writer.lock().write(*dst);
+        oscilloscope.state.chunk_writer.write(dst);
         StreamStatus::Playing
     }
 }
@@ -1,42 +1,332 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
-    Arc, Barrier,
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+        Arc, Barrier,
+    },
 };
 
 use crate::{
     core::{
+        engine::soundengine::{AudioRing, BlockBridge, DEFAULT_BLOCK_SIZE, MAX_BLOCK_SIZE},
         objecttype::{ObjectType, WithObjectType},
         resample::resample_interleave,
         samplefrequency::SAMPLE_FREQUENCY,
         sound::{
             argument::ArgumentScope,
             context::AudioContext,
+            engineblocksize::EngineBlockSize,
             inputtypes::singleinput::SingleInput,
+            panicfade::PanicFade,
             soundinput::InputContext,
             soundprocessor::{
                 ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
             },
+            testtone::TestToneOverride,
         },
-        soundchunk::{SoundChunk, CHUNK_SIZE},
+        soundchunk::SoundChunk,
         stashing::{StashingContext, UnstashingContext},
     },
     ui_core::arguments::ParsedArguments,
 };
 
+use atomic_float::AtomicF32;
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
-    SampleRate, StreamConfig, StreamError,
+    BufferSize, SampleRate, StreamConfig, StreamError, SupportedBufferSize,
+    SupportedStreamConfigRange,
 };
 use flosion_macros::ProcessorComponent;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
-use parking_lot::Mutex;
+
+/// The number of frames which can be waiting to be played by the output
+/// device. This leaves room for a couple of the largest blocks which the
+/// sound engine processes, on top of whatever the device is playing.
+const OUTPUT_RING_CAPACITY: usize = 4 * MAX_BLOCK_SIZE;
+
+/// Cutoff frequency of the DC-blocking highpass filter, in Hz
+const DC_BLOCKER_CUTOFF: f32 = 20.0;
+
+/// One-pole highpass filter which removes any constant offset from
+/// a signal while passing audible frequencies mostly unchanged
+pub(crate) struct DcBlocker {
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl DcBlocker {
+    pub(crate) fn new() -> DcBlocker {
+        DcBlocker {
+            previous_input: 0.0,
+            previous_output: 0.0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.previous_input = 0.0;
+        self.previous_output = 0.0;
+    }
+
+    pub(crate) fn process(&mut self, samples: &mut [f32]) {
+        let pole = 1.0 - std::f32::consts::TAU * DC_BLOCKER_CUTOFF / (SAMPLE_FREQUENCY as f32);
+        for s in samples {
+            let x = *s;
+            let y = x - self.previous_input + pole * self.previous_output;
+            self.previous_input = x;
+            self.previous_output = y;
+            *s = y;
+        }
+    }
+}
+
+/// Number of samples by which the limiter delays its output, which gives
+/// it time to turn the gain down ahead of each peak
+pub(crate) const LIMITER_LOOKAHEAD: usize = 64;
+
+/// Stereo-linked peak limiter which delays the signal slightly so that
+/// the gain can be ramped down smoothly before any sample would exceed
+/// the ceiling, and then lets the gain recover exponentially
+pub(crate) struct Limiter {
+    delay_l: [f32; LIMITER_LOOKAHEAD],
+    delay_r: [f32; LIMITER_LOOKAHEAD],
+    delay_position: usize,
+    /// Gains needed to bring each sample in the delay line, as well as
+    /// the incoming sample, down to the ceiling
+    required_gains: [f32; LIMITER_LOOKAHEAD + 1],
+    required_gain_position: usize,
+    gain: f32,
+}
+
+impl Limiter {
+    pub(crate) fn new() -> Limiter {
+        Limiter {
+            delay_l: [0.0; LIMITER_LOOKAHEAD],
+            delay_r: [0.0; LIMITER_LOOKAHEAD],
+            delay_position: 0,
+            required_gains: [1.0; LIMITER_LOOKAHEAD + 1],
+            required_gain_position: 0,
+            gain: 1.0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Limiter::new();
+    }
+
+    pub(crate) fn process(
+        &mut self,
+        l: &mut [f32],
+        r: &mut [f32],
+        ceiling: f32,
+        release_seconds: f32,
+    ) {
+        debug_assert_eq!(l.len(), r.len());
+        let release_samples = (release_seconds * SAMPLE_FREQUENCY as f32).max(1.0);
+        let release_coeff = 1.0 - (-1.0 / release_samples).exp();
+
+        for (sl, sr) in l.iter_mut().zip(r.iter_mut()) {
+            let peak = sl.abs().max(sr.abs());
+            self.required_gains[self.required_gain_position] =
+                if peak > ceiling { ceiling / peak } else { 1.0 };
+            self.required_gain_position =
+                (self.required_gain_position + 1) % (LIMITER_LOOKAHEAD + 1);
+
+            // Ramp the gain down linearly towards each required gain so
+            // that it arrives just as the corresponding sample leaves the
+            // delay line, rather than jumping down and causing zipper noise.
+            // The oldest required gain, which belongs to the sample about
+            // to be output, is at the current position. The gain only
+            // recovers while there is nothing left to ramp down towards,
+            // and never beyond what any pending sample allows.
+            let mut next_gain = self.gain + (1.0 - self.gain) * release_coeff;
+            for distance in 0..=LIMITER_LOOKAHEAD {
+                let required_gain = self.required_gains
+                    [(self.required_gain_position + distance) % (LIMITER_LOOKAHEAD + 1)];
+                let allowed_gain = if required_gain < self.gain {
+                    self.gain - (self.gain - required_gain) / ((distance + 1) as f32)
+                } else {
+                    required_gain
+                };
+                next_gain = next_gain.min(allowed_gain);
+            }
+            self.gain = next_gain;
+
+            let delayed_l = std::mem::replace(&mut self.delay_l[self.delay_position], *sl);
+            let delayed_r = std::mem::replace(&mut self.delay_r[self.delay_position], *sr);
+            self.delay_position = (self.delay_position + 1) % LIMITER_LOOKAHEAD;
+
+            *sl = delayed_l * self.gain;
+            *sr = delayed_r * self.gain;
+        }
+    }
+}
+
+/// Time constant, in seconds, with which the meters' peak and RMS levels fall
+const METER_DECAY: f32 = 0.3;
+
+/// Peak and RMS levels of one output channel, written by the audio thread
+/// and read by the ui. The clip indicator stays set once any sample exceeds
+/// full scale until it is reset.
+pub struct ChannelMeter {
+    peak: AtomicF32,
+    mean_square: AtomicF32,
+    clipped: AtomicBool,
+}
+
+impl ChannelMeter {
+    pub(crate) fn new() -> ChannelMeter {
+        ChannelMeter {
+            peak: AtomicF32::new(0.0),
+            mean_square: AtomicF32::new(0.0),
+            clipped: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn update(&self, samples: &[f32]) {
+        let chunk_peak = samples.iter().fold(0.0_f32, |p, s| p.max(s.abs()));
+        let chunk_mean_square =
+            samples.iter().map(|s| s * s).sum::<f32>() / (samples.len().max(1) as f32);
+
+        let decay = (-(samples.len() as f32) / (METER_DECAY * SAMPLE_FREQUENCY as f32)).exp();
+
+        let peak = self.peak.load(Ordering::Relaxed);
+        self.peak
+            .store(chunk_peak.max(peak * decay), Ordering::Relaxed);
+
+        let mean_square = self.mean_square.load(Ordering::Relaxed);
+        self.mean_square.store(
+            chunk_mean_square + (mean_square - chunk_mean_square) * decay,
+            Ordering::Relaxed,
+        );
+
+        if !chunk_peak.is_finite() || chunk_peak > 1.0 {
+            self.clipped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn peak(&self) -> f32 {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    pub fn rms(&self) -> f32 {
+        self.mean_square.load(Ordering::Relaxed).sqrt()
+    }
+
+    pub fn clipped(&self) -> bool {
+        self.clipped.load(Ordering::Relaxed)
+    }
+
+    pub fn reset_clip(&self) {
+        self.clipped.store(false, Ordering::Relaxed);
+    }
+}
+
+/// A range of stream configurations supported by an output device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputDeviceConfigRange {
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// The supported range of buffer sizes in frames, if known
+    pub buffer_sizes: Option<RangeInclusive<u32>>,
+}
+
+impl OutputDeviceConfigRange {
+    fn from_cpal(range: &SupportedStreamConfigRange) -> OutputDeviceConfigRange {
+        OutputDeviceConfigRange {
+            min_sample_rate: range.min_sample_rate().0,
+            max_sample_rate: range.max_sample_rate().0,
+            buffer_sizes: match range.buffer_size() {
+                SupportedBufferSize::Range { min, max } => Some(*min..=*max),
+                SupportedBufferSize::Unknown => None,
+            },
+        }
+    }
+
+    fn supports_sample_rate(&self, sample_rate: u32) -> bool {
+        (self.min_sample_rate..=self.max_sample_rate).contains(&sample_rate)
+    }
+}
+
+/// Query the stream configurations supported by the default output device.
+/// Returns an empty list if there is no such device or it can't be queried.
+pub fn supported_output_configs() -> Vec<OutputDeviceConfigRange> {
+    let Some(device) = cpal::default_host().default_output_device() else {
+        return Vec::new();
+    };
+    match device.supported_output_configs() {
+        Ok(configs) => configs
+            .map(|c| OutputDeviceConfigRange::from_cpal(&c))
+            .collect(),
+        Err(e) => {
+            println!("Error while querying output configs: {:?}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Choose a stereo stream configuration among those supported, using the
+/// requested sample rate and buffer size where possible. Requests which
+/// can't be satisfied fall back to the device's defaults. Returns None
+/// only if no configurations are supported at all.
+pub(crate) fn choose_stream_config(
+    supported: &[OutputDeviceConfigRange],
+    requested_sample_rate: Option<u32>,
+    requested_buffer_size: Option<u32>,
+) -> Option<StreamConfig> {
+    let default_range = supported.first()?;
+
+    let requested_range = requested_sample_rate.and_then(|sr| {
+        let range = supported.iter().find(|r| r.supports_sample_rate(sr));
+        if range.is_none() {
+            println!(
+                "Output sample rate of {} Hz is not supported, using the default instead",
+                sr
+            );
+        }
+        range.map(|r| (sr, r))
+    });
+
+    let (sample_rate, range) = requested_range.unwrap_or_else(|| {
+        let sr = (SAMPLE_FREQUENCY as u32)
+            .clamp(default_range.min_sample_rate, default_range.max_sample_rate);
+        (sr, default_range)
+    });
+
+    let buffer_size = match requested_buffer_size {
+        Some(bs) => match &range.buffer_sizes {
+            Some(sizes) if sizes.contains(&bs) => BufferSize::Fixed(bs),
+            _ => {
+                println!(
+                    "Output buffer size of {} frames is not supported, using the default instead",
+                    bs
+                );
+                BufferSize::Default
+            }
+        },
+        None => BufferSize::Default,
+    };
+
+    Some(StreamConfig {
+        channels: 2,
+        sample_rate: SampleRate(sample_rate),
+        buffer_size,
+    })
+}
 
 pub struct OutputData {
     pending_startover: AtomicBool,
-    chunk_sender: SyncSender<SoundChunk>,
-    // TODO: improve this
-    chunk_receiver: Mutex<Receiver<SoundChunk>>,
+    /// The sample rate of the currently open stream, or zero if none is open
+    active_sample_rate: AtomicU32,
+    dc_blocker_enabled: AtomicBool,
+    limiter_enabled: AtomicBool,
+    limiter_ceiling: AtomicF32,
+    limiter_release: AtomicF32,
+    meters: [ChannelMeter; 2],
+    /// Audio which has been processed but not yet played by the device
+    ring: AudioRing,
+    /// The number of frames in each of the sound engine's blocks, in
+    /// which audio is passed on to the device
+    block_size: AtomicUsize,
 }
 
 // TODO: rename to e.g. "SoundOut", "Output" is too vague and overloaded
@@ -45,9 +335,21 @@ pub struct OutputData {
 pub struct Output {
     pub input: SingleInput,
 
+    test_tone: TestToneOverride,
+
+    panic_fade: PanicFade,
+
+    block_size: EngineBlockSize,
+
     #[not_a_component]
     shared_data: Arc<OutputData>,
 
+    #[not_a_component]
+    device_sample_rate: Option<u32>,
+
+    #[not_a_component]
+    device_buffer_size: Option<u32>,
+
     #[state]
     state: StateMarker<OutputState>,
 }
@@ -58,38 +360,137 @@ impl Output {
             .pending_startover
             .store(true, Ordering::SeqCst);
     }
+
+    /// The sample rate requested of the output device, or None to use its default.
+    /// Sound is always processed at SAMPLE_FREQUENCY and resampled to this rate.
+    pub fn device_sample_rate(&self) -> Option<u32> {
+        self.device_sample_rate
+    }
+
+    pub fn set_device_sample_rate(&mut self, sample_rate: Option<u32>) {
+        self.device_sample_rate = sample_rate;
+    }
+
+    /// The buffer size in frames requested of the output device, or None to use its default
+    pub fn device_buffer_size(&self) -> Option<u32> {
+        self.device_buffer_size
+    }
+
+    pub fn set_device_buffer_size(&mut self, buffer_size: Option<u32>) {
+        self.device_buffer_size = buffer_size;
+    }
+
+    /// The sample rate of the currently open output stream, if any
+    pub fn active_sample_rate(&self) -> Option<u32> {
+        match self.shared_data.active_sample_rate.load(Ordering::Relaxed) {
+            0 => None,
+            sr => Some(sr),
+        }
+    }
+
+    /// Meters for the left and right channels, in that order
+    pub fn meters(&self) -> &[ChannelMeter; 2] {
+        &self.shared_data.meters
+    }
+
+    pub fn dc_blocker_enabled(&self) -> bool {
+        self.shared_data.dc_blocker_enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_dc_blocker_enabled(&self, enabled: bool) {
+        self.shared_data
+            .dc_blocker_enabled
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn limiter_enabled(&self) -> bool {
+        self.shared_data.limiter_enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_limiter_enabled(&self, enabled: bool) {
+        self.shared_data
+            .limiter_enabled
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// The peak amplitude which the limiter does not let the output exceed
+    pub fn limiter_ceiling(&self) -> f32 {
+        self.shared_data.limiter_ceiling.load(Ordering::Relaxed)
+    }
+
+    pub fn set_limiter_ceiling(&self, ceiling: f32) {
+        self.shared_data
+            .limiter_ceiling
+            .store(ceiling, Ordering::Relaxed);
+    }
+
+    /// The time constant, in seconds, with which the limiter's gain recovers
+    pub fn limiter_release(&self) -> f32 {
+        self.shared_data.limiter_release.load(Ordering::Relaxed)
+    }
+
+    pub fn set_limiter_release(&self, release: f32) {
+        self.shared_data
+            .limiter_release
+            .store(release, Ordering::Relaxed);
+    }
 }
 
 pub struct OutputState {
     shared_data: Arc<OutputData>,
+    /// The sample rate of the stream which this state opened
+    sample_rate: u32,
     stream_end_barrier: Arc<Barrier>,
+    dc_blockers: [DcBlocker; 2],
+    limiter: Limiter,
 }
 
 impl StartOver for OutputState {
     fn start_over(&mut self) {
         // ???
+        for b in &mut self.dc_blockers {
+            b.reset();
+        }
+        self.limiter.reset();
     }
 }
 
 impl Drop for OutputState {
     fn drop(&mut self) {
+        // Only clear the sample rate if it wasn't since set by a newer
+        // state, e.g. one which replaced this state after a graph edit
+        let _ = self.shared_data.active_sample_rate.compare_exchange(
+            self.sample_rate,
+            0,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
         self.stream_end_barrier.wait();
     }
 }
 
 impl SoundProcessor for Output {
     fn new(_args: &ParsedArguments) -> Output {
-        let (tx, rx) = sync_channel::<SoundChunk>(0);
-
         let shared_data = Arc::new(OutputData {
             pending_startover: AtomicBool::new(false),
-            chunk_sender: tx,
-            chunk_receiver: Mutex::new(rx),
+            active_sample_rate: AtomicU32::new(0),
+            dc_blocker_enabled: AtomicBool::new(false),
+            limiter_enabled: AtomicBool::new(false),
+            limiter_ceiling: AtomicF32::new(1.0),
+            limiter_release: AtomicF32::new(0.1),
+            meters: [ChannelMeter::new(), ChannelMeter::new()],
+            ring: AudioRing::new(OUTPUT_RING_CAPACITY),
+            block_size: AtomicUsize::new(DEFAULT_BLOCK_SIZE),
         });
 
         Output {
             input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            test_tone: TestToneOverride,
+            panic_fade: PanicFade,
+            block_size: EngineBlockSize,
             shared_data,
+            device_sample_rate: None,
+            device_buffer_size: None,
             state: StateMarker::new(),
         }
     }
@@ -98,6 +499,14 @@ impl SoundProcessor for Output {
         true
     }
 
+    fn latency(&self) -> usize {
+        if self.limiter_enabled() {
+            LIMITER_LOOKAHEAD
+        } else {
+            0
+        }
+    }
+
     fn process_audio(
         output: &mut CompiledOutput,
         dst: &mut SoundChunk,
@@ -113,12 +522,43 @@ impl SoundProcessor for Output {
         }
         output.input.step(dst, InputContext::new(context));
 
-        if let Err(e) = output.state.shared_data.chunk_sender.try_send(*dst) {
-            match e {
-                TrySendError::Full(_) => println!("Output sound processor dropped a chunk"),
-                TrySendError::Disconnected(_) => panic!("Idk what to do, maybe nothing?"),
+        let state = &mut output.state;
+        if state.shared_data.dc_blocker_enabled.load(Ordering::Relaxed) {
+            state.dc_blockers[0].process(&mut dst.l);
+            state.dc_blockers[1].process(&mut dst.r);
+        } else {
+            for b in &mut state.dc_blockers {
+                b.reset();
             }
         }
+
+        let shared_data = &state.shared_data;
+        if shared_data.limiter_enabled.load(Ordering::Relaxed) {
+            state.limiter.process(
+                &mut dst.l,
+                &mut dst.r,
+                shared_data.limiter_ceiling.load(Ordering::Relaxed),
+                shared_data.limiter_release.load(Ordering::Relaxed),
+            );
+        } else {
+            state.limiter.reset();
+        }
+
+        // Replace everything with the sound engine's test tone if it's on
+        output.test_tone.process(dst);
+
+        // Fade out after everything else if the sound engine is panicking
+        output.panic_fade.process(dst);
+
+        shared_data.meters[0].update(&dst.l);
+        shared_data.meters[1].update(&dst.r);
+
+        shared_data
+            .block_size
+            .store(output.block_size.frames(), Ordering::Relaxed);
+        if !shared_data.ring.write(&dst.l, &dst.r) {
+            println!("Output sound processor dropped a chunk");
+        }
         StreamStatus::Playing
     }
 }
@@ -133,50 +573,44 @@ impl ProcessorState for OutputState {
             .default_output_device()
             .expect("No output device available");
         println!("Using output device {}", device.name().unwrap());
-        let supported_configs = device
+        let supported_configs: Vec<OutputDeviceConfigRange> = device
             .supported_output_configs()
             .expect("Error while querying configs")
-            .next()
-            .expect("No supported config!?");
+            .map(|c| OutputDeviceConfigRange::from_cpal(&c))
+            .collect();
 
-        println!(
-            "Supported sample rates are {:?} to {:?}",
-            supported_configs.min_sample_rate().0,
-            supported_configs.max_sample_rate().0
-        );
+        for c in &supported_configs {
+            println!(
+                "Supported sample rates are {:?} to {:?}, supported buffer sizes are {:?}",
+                c.min_sample_rate, c.max_sample_rate, c.buffer_sizes
+            );
+        }
 
-        println!(
-            "Supported buffer sizes are {:?}",
-            supported_configs.buffer_size()
-        );
+        let config = choose_stream_config(
+            &supported_configs,
+            processor.device_sample_rate,
+            processor.device_buffer_size,
+        )
+        .expect("No supported config!?");
 
-        let sample_rate = SampleRate(supported_configs.min_sample_rate().0.max(44_100));
-        let mut config: StreamConfig = supported_configs.with_sample_rate(sample_rate).into();
+        let sample_rate = config.sample_rate;
 
-        config.channels = 2;
-        // config.sample_rate = SampleRate(SAMPLE_FREQUENCY as u32);
-        // config.buffer_size = BufferSize::Fixed(CHUNK_SIZE as u32);
+        processor
+            .shared_data
+            .active_sample_rate
+            .store(sample_rate.0, Ordering::Relaxed);
 
-        let mut chunk_index: usize = 0;
-        let mut current_chunk: Option<SoundChunk> = None;
+        // Device callbacks may ask for any number of frames, while the
+        // sound engine only ever produces whole blocks
+        let mut bridge = BlockBridge::new();
 
         let shared_data = Arc::clone(&processor.shared_data);
 
         let mut get_next_sample = move || {
-            if current_chunk.is_none() || chunk_index >= CHUNK_SIZE {
-                current_chunk = Some(
-                    shared_data
-                        .chunk_receiver
-                        .lock()
-                        .recv()
-                        .unwrap_or_else(|_| SoundChunk::new()),
-                );
-                chunk_index = 0;
-            }
-            let c = current_chunk.as_ref().unwrap();
-            let l = c.l[chunk_index];
-            let r = c.r[chunk_index];
-            chunk_index += 1;
+            let (l, r) = bridge.next_frame(
+                &shared_data.ring,
+                shared_data.block_size.load(Ordering::Relaxed),
+            );
             let l = if l.is_finite() {
                 l.clamp(-1.0, 1.0)
             } else {
@@ -225,7 +659,10 @@ impl ProcessorState for OutputState {
 
         OutputState {
             shared_data: Arc::clone(&processor.shared_data),
+            sample_rate: sample_rate.0,
             stream_end_barrier: barrier2,
+            dc_blockers: [DcBlocker::new(), DcBlocker::new()],
+            limiter: Limiter::new(),
         }
     }
 }
@@ -237,6 +674,13 @@ impl WithObjectType for Output {
 impl Stashable<StashingContext> for Output {
     fn stash(&self, stasher: &mut Stasher<StashingContext>) {
         stasher.object(&self.input);
+        stasher.bool(self.dc_blocker_enabled());
+        stasher.bool(self.limiter_enabled());
+        stasher.f32(self.limiter_ceiling());
+        stasher.f32(self.limiter_release());
+        // Zero means to use the device's default
+        stasher.u32(self.device_sample_rate.unwrap_or(0));
+        stasher.u32(self.device_buffer_size.unwrap_or(0));
     }
 }
 
@@ -246,6 +690,24 @@ impl UnstashableInplace<UnstashingContext<'_>> for Output {
         unstasher: &mut InplaceUnstasher<UnstashingContext>,
     ) -> Result<(), UnstashError> {
         unstasher.object_inplace(&mut self.input)?;
+        let dc_blocker_enabled = unstasher.bool_always()?;
+        if unstasher.time_to_write() {
+            self.set_dc_blocker_enabled(dc_blocker_enabled);
+        }
+        let limiter_enabled = unstasher.bool_always()?;
+        let limiter_ceiling = unstasher.f32_always()?;
+        let limiter_release = unstasher.f32_always()?;
+        if unstasher.time_to_write() {
+            self.set_limiter_enabled(limiter_enabled);
+            self.set_limiter_ceiling(limiter_ceiling);
+            self.set_limiter_release(limiter_release);
+        }
+        let device_sample_rate = unstasher.u32_always()?;
+        let device_buffer_size = unstasher.u32_always()?;
+        if unstasher.time_to_write() {
+            self.device_sample_rate = Some(device_sample_rate).filter(|sr| *sr != 0);
+            self.device_buffer_size = Some(device_buffer_size).filter(|bs| *bs != 0);
+        }
         Ok(())
     }
 }
@@ -276,7 +738,15 @@ mod test {
 
         test_stash_roundtrip_inplace(
             || Output::new(&ParsedArguments::new_empty()),
-            |output| output.input.set_target(Some(SoundProcessorId::new(0123))),
+            |output| {
+                output.input.set_target(Some(SoundProcessorId::new(0123)));
+                output.set_dc_blocker_enabled(true);
+                output.set_limiter_enabled(true);
+                output.set_limiter_ceiling(0.5);
+                output.set_limiter_release(0.25);
+                output.set_device_sample_rate(Some(48_000));
+                output.set_device_buffer_size(Some(512));
+            },
             StashingContext::new_stashing_normally(),
             UnstashingContext::new(&obj_fac, &expr_fac),
         )
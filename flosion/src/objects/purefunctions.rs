@@ -6,6 +6,7 @@ use crate::{
         },
         jit::jit::Jit,
         objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
         stashing::StashingContext,
     },
     ui_core::arguments::{FloatArgument, ParsedArguments},
@@ -38,6 +39,11 @@ impl PureExpressionNode for Constant {
         jit.types.f32_type.const_float(self.value as f64)
     }
 
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        debug_assert!(inputs.is_empty());
+        Some(self.value)
+    }
+
     fn visit(&self, _visitor: &mut dyn ExpressionNodeVisitor) {}
     fn visit_mut(&mut self, _visitor: &mut dyn ExpressionNodeVisitorMut) {}
 }
@@ -125,6 +131,89 @@ impl WithObjectType for Variable {
     const TYPE: ObjectType = ObjectType::new("variable");
 }
 
+/// The number of samples per second at which audio is processed
+pub struct SampleRate {}
+
+impl PureExpressionNode for SampleRate {
+    fn new(_args: &ParsedArguments) -> SampleRate {
+        SampleRate {}
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert!(inputs.is_empty());
+        jit.types.f32_type.const_float(SAMPLE_FREQUENCY as f64)
+    }
+
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        debug_assert!(inputs.is_empty());
+        Some(SAMPLE_FREQUENCY as f32)
+    }
+
+    fn depends_only_on_inputs(&self) -> bool {
+        true
+    }
+
+    fn visit(&self, _visitor: &mut dyn ExpressionNodeVisitor) {}
+    fn visit_mut(&mut self, _visitor: &mut dyn ExpressionNodeVisitorMut) {}
+}
+
+impl Stashable<StashingContext> for SampleRate {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        // The sample frequency is baked into the compiled expression as
+        // a constant, so it is stashed in order that a different sample
+        // frequency is seen as a change requiring recompilation
+        stasher.u64(SAMPLE_FREQUENCY as u64);
+    }
+}
+
+impl UnstashableInplace for SampleRate {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        // Whatever sample frequency was stashed, the current one is used
+        unstasher.u64_always()?;
+        Ok(())
+    }
+}
+
+impl WithObjectType for SampleRate {
+    const TYPE: ObjectType = ObjectType::new("samplerate");
+}
+
+/// The number of values being computed at once, which is the length
+/// of the array that the expression is evaluated into
+pub struct BlockSize {}
+
+impl PureExpressionNode for BlockSize {
+    fn new(_args: &ParsedArguments) -> BlockSize {
+        BlockSize {}
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert!(inputs.is_empty());
+        jit.build_block_size()
+    }
+
+    fn depends_only_on_inputs(&self) -> bool {
+        true
+    }
+
+    fn visit(&self, _visitor: &mut dyn ExpressionNodeVisitor) {}
+    fn visit_mut(&mut self, _visitor: &mut dyn ExpressionNodeVisitorMut) {}
+}
+
+impl Stashable<StashingContext> for BlockSize {
+    fn stash(&self, _stasher: &mut Stasher<StashingContext>) {}
+}
+
+impl UnstashableInplace for BlockSize {
+    fn unstash_inplace(&mut self, _unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        Ok(())
+    }
+}
+
+impl WithObjectType for BlockSize {
+    const TYPE: ObjectType = ObjectType::new("blocksize");
+}
+
 enum LlvmImplementation {
     IntrinsicUnary(&'static str),
     IntrinsicBinary(&'static str),
@@ -201,6 +290,15 @@ macro_rules! unary_expression_node {
                 imp.compile(jit, inputs)
             }
 
+            fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+                let f: fn(f32) -> f32 = $f;
+                Some(f(inputs[0]))
+            }
+
+            fn depends_only_on_inputs(&self) -> bool {
+                true
+            }
+
             fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
                 visitor.input(&self.input);
             }
@@ -256,6 +354,15 @@ macro_rules! binary_expression_node {
                 imp.compile(jit, inputs)
             }
 
+            fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+                let f: fn(f32, f32) -> f32 = $f;
+                Some(f(inputs[0], inputs[1]))
+            }
+
+            fn depends_only_on_inputs(&self) -> bool {
+                true
+            }
+
             fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
                 visitor.input(&self.input_1);
                 visitor.input(&self.input_2);
@@ -317,6 +424,15 @@ macro_rules! ternary_expression_node {
                 imp.compile(jit, inputs)
             }
 
+            fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+                let f: fn(f32, f32, f32) -> f32 = $f;
+                Some(f(inputs[0], inputs[1], inputs[2]))
+            }
+
+            fn depends_only_on_inputs(&self) -> bool {
+                true
+            }
+
             fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
                 visitor.input(&self.input_1);
                 visitor.input(&self.input_2);
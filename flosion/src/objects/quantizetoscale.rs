@@ -0,0 +1,282 @@
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use inkwell::{values::FloatValue, FloatPredicate};
+
+use crate::{
+    core::{
+        expression::{
+            expressioninput::ExpressionInput,
+            expressionnode::{ExpressionNodeVisitor, ExpressionNodeVisitorMut, PureExpressionNode},
+        },
+        jit::jit::Jit,
+        objecttype::{ObjectType, WithObjectType},
+        stashing::StashingContext,
+    },
+    ui_core::arguments::{ArgumentEnum, EnumArgument, ParsedArguments},
+};
+
+/// Frequency of A4, in Hz, to which all scales are tuned
+const REFERENCE_FREQUENCY: f32 = 440.0;
+
+/// Number of semitones from C up to A. All scales are rooted on C.
+const REFERENCE_SEMITONES_ABOVE_ROOT: f32 = 9.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MusicalScale {
+    Chromatic,
+    Major,
+    Minor,
+    Pentatonic,
+}
+
+impl MusicalScale {
+    fn to_u8(self) -> u8 {
+        match self {
+            MusicalScale::Chromatic => 0,
+            MusicalScale::Major => 1,
+            MusicalScale::Minor => 2,
+            MusicalScale::Pentatonic => 3,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<MusicalScale> {
+        match x {
+            0 => Some(MusicalScale::Chromatic),
+            1 => Some(MusicalScale::Major),
+            2 => Some(MusicalScale::Minor),
+            3 => Some(MusicalScale::Pentatonic),
+            _ => None,
+        }
+    }
+
+    /// The notes of the scale within a single octave, in semitones above
+    /// the root and in ascending order
+    pub fn intervals(self) -> &'static [f32] {
+        match self {
+            MusicalScale::Chromatic => {
+                &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0]
+            }
+            MusicalScale::Major => &[0.0, 2.0, 4.0, 5.0, 7.0, 9.0, 11.0],
+            MusicalScale::Minor => &[0.0, 2.0, 3.0, 5.0, 7.0, 8.0, 10.0],
+            MusicalScale::Pentatonic => &[0.0, 2.0, 4.0, 7.0, 9.0],
+        }
+    }
+}
+
+impl ArgumentEnum for MusicalScale {
+    fn all_values() -> &'static [MusicalScale] {
+        &[
+            MusicalScale::Chromatic,
+            MusicalScale::Major,
+            MusicalScale::Minor,
+            MusicalScale::Pentatonic,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            MusicalScale::Chromatic => "chromatic",
+            MusicalScale::Major => "major",
+            MusicalScale::Minor => "minor",
+            MusicalScale::Pentatonic => "pentatonic",
+        }
+    }
+}
+
+/// Snap a frequency in Hz to the nearest note of the given scale.
+/// Notes exactly halfway between two notes of the scale are snapped
+/// down. Frequencies which aren't positive are returned unchanged.
+pub fn quantize_frequency(frequency: f32, scale: MusicalScale) -> f32 {
+    if frequency.is_nan() || frequency <= 0.0 {
+        return frequency;
+    }
+    let semitones =
+        12.0 * (frequency / REFERENCE_FREQUENCY).log2() + REFERENCE_SEMITONES_ABOVE_ROOT;
+    let octave = (semitones / 12.0).floor();
+    let within_octave = semitones - 12.0 * octave;
+
+    // The root of the next octave is also a candidate
+    let mut nearest = 0.0;
+    for interval in scale.intervals().iter().copied().chain([12.0]) {
+        if (within_octave - interval).abs() < (within_octave - nearest).abs() {
+            nearest = interval;
+        }
+    }
+
+    let snapped = 12.0 * octave + nearest;
+    REFERENCE_FREQUENCY * ((snapped - REFERENCE_SEMITONES_ABOVE_ROOT) / 12.0).exp2()
+}
+
+/// Snaps its input frequency to the nearest note in a musical scale
+pub struct QuantizeToScale {
+    input: ExpressionInput,
+    scale: MusicalScale,
+}
+
+impl QuantizeToScale {
+    pub const ARG_SCALE: EnumArgument<MusicalScale> = EnumArgument::new("scale");
+
+    pub fn scale(&self) -> MusicalScale {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: MusicalScale) {
+        self.scale = scale;
+    }
+}
+
+impl PureExpressionNode for QuantizeToScale {
+    fn new(args: &ParsedArguments) -> QuantizeToScale {
+        QuantizeToScale {
+            input: ExpressionInput::new(REFERENCE_FREQUENCY),
+            scale: args
+                .get(&QuantizeToScale::ARG_SCALE)
+                .unwrap_or(MusicalScale::Chromatic),
+        }
+    }
+
+    fn compile<'ctx>(&self, jit: &mut Jit<'ctx>, inputs: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 1);
+        let input = inputs[0];
+        let f32_type = jit.types.f32_type;
+        let twelve = f32_type.const_float(12.0);
+
+        let ratio = jit
+            .builder()
+            .build_float_div(
+                input,
+                f32_type.const_float(REFERENCE_FREQUENCY as f64),
+                "ratio",
+            )
+            .unwrap();
+        let octaves_from_ref = jit.build_unary_intrinsic_call("llvm.log2", ratio);
+        let semitones_from_ref = jit
+            .builder()
+            .build_float_mul(octaves_from_ref, twelve, "semitones_from_ref")
+            .unwrap();
+        let semitones = jit
+            .builder()
+            .build_float_add(
+                semitones_from_ref,
+                f32_type.const_float(REFERENCE_SEMITONES_ABOVE_ROOT as f64),
+                "semitones",
+            )
+            .unwrap();
+        let octaves = jit
+            .builder()
+            .build_float_div(semitones, twelve, "octaves")
+            .unwrap();
+        let octave = jit.build_unary_intrinsic_call("llvm.floor", octaves);
+        let octave_semitones = jit
+            .builder()
+            .build_float_mul(octave, twelve, "octave_semitones")
+            .unwrap();
+        let within_octave = jit
+            .builder()
+            .build_float_sub(semitones, octave_semitones, "within_octave")
+            .unwrap();
+
+        // Search the scale's intervals, which are baked into the compiled
+        // code, for the one nearest to the input. The root of the next
+        // octave is also a candidate.
+        let distance_to = |jit: &mut Jit<'ctx>, interval: FloatValue<'ctx>| {
+            let diff = jit
+                .builder()
+                .build_float_sub(within_octave, interval, "diff")
+                .unwrap();
+            jit.build_unary_intrinsic_call("llvm.fabs", diff)
+        };
+        let mut nearest = f32_type.const_float(0.0);
+        let mut nearest_distance = distance_to(jit, nearest);
+        for interval in self.scale.intervals().iter().skip(1).copied().chain([12.0]) {
+            let interval = f32_type.const_float(interval as f64);
+            let distance = distance_to(jit, interval);
+            let is_nearer = jit
+                .builder()
+                .build_float_compare(FloatPredicate::OLT, distance, nearest_distance, "is_nearer")
+                .unwrap();
+            nearest = jit
+                .builder()
+                .build_select(is_nearer, interval, nearest, "nearest")
+                .unwrap()
+                .into_float_value();
+            nearest_distance = jit
+                .builder()
+                .build_select(is_nearer, distance, nearest_distance, "nearest_distance")
+                .unwrap()
+                .into_float_value();
+        }
+
+        let snapped = jit
+            .builder()
+            .build_float_add(octave_semitones, nearest, "snapped")
+            .unwrap();
+        let snapped_from_ref = jit
+            .builder()
+            .build_float_sub(
+                snapped,
+                f32_type.const_float(REFERENCE_SEMITONES_ABOVE_ROOT as f64),
+                "snapped_from_ref",
+            )
+            .unwrap();
+        let snapped_octaves = jit
+            .builder()
+            .build_float_div(snapped_from_ref, twelve, "snapped_octaves")
+            .unwrap();
+        let snapped_ratio = jit.build_unary_intrinsic_call("llvm.exp2", snapped_octaves);
+        let quantized = jit
+            .builder()
+            .build_float_mul(
+                snapped_ratio,
+                f32_type.const_float(REFERENCE_FREQUENCY as f64),
+                "quantized",
+            )
+            .unwrap();
+
+        let is_positive = jit
+            .builder()
+            .build_float_compare(
+                FloatPredicate::OGT,
+                input,
+                f32_type.const_float(0.0),
+                "is_positive",
+            )
+            .unwrap();
+        jit.builder()
+            .build_select(is_positive, quantized, input, "quantized_or_input")
+            .unwrap()
+            .into_float_value()
+    }
+
+    fn evaluate_constant(&self, inputs: &[f32]) -> Option<f32> {
+        Some(quantize_frequency(inputs[0], self.scale))
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input);
+    }
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input);
+    }
+}
+
+impl Stashable<StashingContext> for QuantizeToScale {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.u8(self.scale.to_u8());
+    }
+}
+
+impl UnstashableInplace for QuantizeToScale {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        let scale = MusicalScale::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.scale = scale;
+        }
+        Ok(())
+    }
+}
+
+impl WithObjectType for QuantizeToScale {
+    const TYPE: ObjectType = ObjectType::new("quantizetoscale");
+}
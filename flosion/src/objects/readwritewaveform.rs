@@ -6,6 +6,7 @@ use crate::{
         expression::context::ExpressionContext,
         jit::compiledexpression::Discretization,
         objecttype::{ObjectType, WithObjectType},
+        resample::{OversampleFactor, Oversampler},
         sound::{
             argument::{ArgumentScope, ProcessorArgument},
             argumenttypes::plainf32array::PlainF32ArrayArgument,
@@ -13,24 +14,77 @@ use crate::{
             expression::ProcessorExpression,
             inputtypes::singleinput::SingleInput,
             soundinput::InputContext,
-            soundprocessor::{SoundProcessor, StreamStatus},
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
         },
-        soundchunk::SoundChunk,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
         stashing::{StashingContext, UnstashingContext},
     },
-    ui_core::arguments::ParsedArguments,
+    ui_core::arguments::{EnumArgument, ParsedArguments},
 };
 
+pub struct ReadWriteWaveformState {
+    oversamplers: [Oversampler; SoundChunk::NUM_CHANNELS],
+    inputs: [Vec<f32>; SoundChunk::NUM_CHANNELS],
+    outputs: [Vec<f32>; SoundChunk::NUM_CHANNELS],
+}
+
+impl ProcessorState for ReadWriteWaveformState {
+    type Processor = ReadWriteWaveform;
+
+    fn new(processor: &ReadWriteWaveform) -> Self {
+        let len = CHUNK_SIZE * processor.oversample.ratio();
+        ReadWriteWaveformState {
+            oversamplers: [
+                Oversampler::new(processor.oversample),
+                Oversampler::new(processor.oversample),
+            ],
+            inputs: [vec![0.0; len], vec![0.0; len]],
+            outputs: [vec![0.0; len], vec![0.0; len]],
+        }
+    }
+}
+
+impl StartOver for ReadWriteWaveformState {
+    fn start_over(&mut self) {
+        for o in &mut self.oversamplers {
+            o.reset();
+        }
+    }
+}
+
 #[derive(ProcessorComponent)]
 pub struct ReadWriteWaveform {
     pub sound_input: SingleInput,
     pub waveform: ProcessorExpression,
     pub input_l: ProcessorArgument<PlainF32ArrayArgument>,
     pub input_r: ProcessorArgument<PlainF32ArrayArgument>,
+
+    #[not_a_component]
+    oversample: OversampleFactor,
+
+    #[state]
+    state: StateMarker<ReadWriteWaveformState>,
+}
+
+impl ReadWriteWaveform {
+    pub const ARG_OVERSAMPLE: EnumArgument<OversampleFactor> = EnumArgument::new("oversample");
+
+    /// How many times faster than the audio rate the waveform is
+    /// evaluated. Oversampling reduces the aliasing of nonlinear
+    /// waveforms such as clipping, at the cost of more computation.
+    pub fn oversample(&self) -> OversampleFactor {
+        self.oversample
+    }
+
+    pub fn set_oversample(&mut self, factor: OversampleFactor) {
+        self.oversample = factor;
+    }
 }
 
 impl SoundProcessor for ReadWriteWaveform {
-    fn new(_args: &ParsedArguments) -> Self {
+    fn new(args: &ParsedArguments) -> Self {
         let input_l = ProcessorArgument::new();
         let input_r = ProcessorArgument::new();
         let waveform_scope = ArgumentScope::new(vec![input_l.id(), input_r.id()]);
@@ -39,6 +93,10 @@ impl SoundProcessor for ReadWriteWaveform {
             waveform: ProcessorExpression::new(&[0.0, 0.0], waveform_scope),
             input_l,
             input_r,
+            oversample: args
+                .get(&ReadWriteWaveform::ARG_OVERSAMPLE)
+                .unwrap_or(OversampleFactor::None),
+            state: StateMarker::new(),
         }
     }
 
@@ -46,6 +104,10 @@ impl SoundProcessor for ReadWriteWaveform {
         false
     }
 
+    fn latency(&self) -> usize {
+        self.oversample.latency()
+    }
+
     fn process_audio(
         rwwf: &mut Self::CompiledType<'_>,
         dst: &mut SoundChunk,
@@ -53,13 +115,34 @@ impl SoundProcessor for ReadWriteWaveform {
     ) -> StreamStatus {
         let mut tmp = SoundChunk::new();
         rwwf.sound_input.step(&mut tmp, InputContext::new(context));
+
+        let state = &mut rwwf.state;
+        if state.oversamplers[0].factor() == OversampleFactor::None {
+            rwwf.waveform.eval(
+                &mut [&mut dst.l, &mut dst.r],
+                Discretization::samplewise_temporal(),
+                ExpressionContext::new(context)
+                    .push(rwwf.input_l, &tmp.l)
+                    .push(rwwf.input_r, &tmp.r),
+            );
+            return StreamStatus::Playing;
+        }
+
+        let ratio = state.oversamplers[0].factor().ratio();
+        for c in 0..SoundChunk::NUM_CHANNELS {
+            state.oversamplers[c].upsample(tmp.channel(c), &mut state.inputs[c]);
+        }
+        let [out_l, out_r] = &mut state.outputs;
         rwwf.waveform.eval(
-            &mut [&mut dst.l, &mut dst.r],
-            Discretization::samplewise_temporal(),
+            &mut [out_l, out_r],
+            Discretization::oversampled_temporal(ratio),
             ExpressionContext::new(context)
-                .push(rwwf.input_l, &tmp.l)
-                .push(rwwf.input_r, &tmp.r),
+                .push(rwwf.input_l, &state.inputs[0])
+                .push(rwwf.input_r, &state.inputs[1]),
         );
+        for c in 0..SoundChunk::NUM_CHANNELS {
+            state.oversamplers[c].downsample(&state.outputs[c], dst.channel_mut(c));
+        }
 
         StreamStatus::Playing
     }
@@ -75,6 +158,7 @@ impl Stashable<StashingContext> for ReadWriteWaveform {
         stasher.object(&self.waveform);
         stasher.object(&self.input_l);
         stasher.object(&self.input_r);
+        stasher.u8(self.oversample.to_u8());
     }
 }
 
@@ -87,6 +171,11 @@ impl<'a> UnstashableInplace<UnstashingContext<'a>> for ReadWriteWaveform {
         unstasher.object_inplace(&mut self.waveform)?;
         unstasher.object_inplace(&mut self.input_l)?;
         unstasher.object_inplace(&mut self.input_r)?;
+        let oversample =
+            OversampleFactor::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.oversample = oversample;
+        }
         Ok(())
     }
 }
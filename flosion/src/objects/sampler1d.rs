@@ -1,34 +1,259 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use atomicslice::AtomicSlice;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
 use inkwell::{
     values::{FloatValue, IntValue, PointerValue},
-    AtomicOrdering, AtomicRMWBinOp, IntPredicate,
+    AtomicOrdering, AtomicRMWBinOp, FloatPredicate, IntPredicate,
 };
 
 use crate::{
     core::{
+        audiofileio::{load_audio_file, save_wav_file},
         expression::{
             expressioninput::ExpressionInput,
             expressionnode::{ExpressionNode, ExpressionNodeVisitor, ExpressionNodeVisitorMut},
         },
         jit::jit::Jit,
         objecttype::{ObjectType, WithObjectType},
+        resample::read_fractional,
         stashing::StashingContext,
     },
-    ui_core::arguments::ParsedArguments,
+    ui_core::arguments::{ArgumentEnum, EnumArgument, NaturalNumberArgument, ParsedArguments},
 };
 
+/// How values are read from between the sampler's entries
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sampler1dInterpolation {
+    /// The value of the nearest entry
+    Nearest,
+    /// A straight line between the two surrounding entries
+    Linear,
+    /// A Catmull-Rom spline through the four surrounding entries
+    Cubic,
+}
+
+impl Sampler1dInterpolation {
+    fn to_u8(self) -> u8 {
+        match self {
+            Sampler1dInterpolation::Nearest => 0,
+            Sampler1dInterpolation::Linear => 1,
+            Sampler1dInterpolation::Cubic => 2,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<Sampler1dInterpolation> {
+        match x {
+            0 => Some(Sampler1dInterpolation::Nearest),
+            1 => Some(Sampler1dInterpolation::Linear),
+            2 => Some(Sampler1dInterpolation::Cubic),
+            _ => None,
+        }
+    }
+
+    /// Read the value at the given position from the given entries,
+    /// which are spread evenly from 0 to 1 and repeat beyond that.
+    /// This matches the compiled expression node.
+    pub fn sample(self, values: &[f32], position: f32) -> f32 {
+        let n = values.len();
+        let index_float = (position - position.floor()) * n as f32;
+        let index_floor = index_float.floor();
+        let t = index_float - index_floor;
+        let i = index_floor as usize;
+        let at = |offset: isize| values[(i as isize + offset).rem_euclid(n as isize) as usize];
+        match self {
+            Sampler1dInterpolation::Nearest => {
+                if t < 0.5 {
+                    at(0)
+                } else {
+                    at(1)
+                }
+            }
+            Sampler1dInterpolation::Linear => at(0) + t * (at(1) - at(0)),
+            Sampler1dInterpolation::Cubic => catmull_rom(at(-1), at(0), at(1), at(2), t),
+        }
+    }
+}
+
+impl ArgumentEnum for Sampler1dInterpolation {
+    fn all_values() -> &'static [Sampler1dInterpolation] {
+        &[
+            Sampler1dInterpolation::Nearest,
+            Sampler1dInterpolation::Linear,
+            Sampler1dInterpolation::Cubic,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Sampler1dInterpolation::Nearest => "nearest",
+            Sampler1dInterpolation::Linear => "linear",
+            Sampler1dInterpolation::Cubic => "cubic",
+        }
+    }
+}
+
+/// The Catmull-Rom spline through v0 and v1 at fraction t between them,
+/// given the entries vm1 before and v2 after
+fn catmull_rom(vm1: f32, v0: f32, v1: f32, v2: f32, t: f32) -> f32 {
+    let a = 3.0 * (v0 - v1) + v2 - vm1;
+    let b = 2.0 * vm1 - 5.0 * v0 + 4.0 * v1 - v2;
+    let c = v1 - vm1;
+    v0 + 0.5 * t * (c + t * (b + t * a))
+}
+
 pub struct Sampler1d {
     input: ExpressionInput,
     value: Arc<AtomicSlice<f32>>,
+    interpolation: Sampler1dInterpolation,
 }
 
 impl Sampler1d {
+    pub const ARG_INTERPOLATION: EnumArgument<Sampler1dInterpolation> =
+        EnumArgument::new("interpolation");
+    pub const ARG_LENGTH: NaturalNumberArgument = NaturalNumberArgument("length");
+
+    pub const DEFAULT_LENGTH: usize = 256;
+    pub const MIN_LENGTH: usize = 2;
+    pub const MAX_LENGTH: usize = 1 << 16;
+
     pub fn value(&self) -> &AtomicSlice<f32> {
         &self.value
     }
+
+    pub fn length(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Change the number of entries, resampling the existing contents
+    /// so that the curve keeps its shape. The length is clamped to lie
+    /// between MIN_LENGTH and MAX_LENGTH. Since the entries are held in
+    /// a new slice, any compiled expressions reading the sampler will
+    /// need to be recompiled.
+    pub fn resize(&mut self, length: usize) {
+        let length = length.clamp(Self::MIN_LENGTH, Self::MAX_LENGTH);
+        if length == self.length() {
+            return;
+        }
+        let old_values = self.value.read().to_vec();
+        let ratio = old_values.len() as f32 / length as f32;
+        let new_values: Vec<f32> = (0..length)
+            .map(|i| read_fractional(&old_values, i as f32 * ratio))
+            .collect();
+        self.value = Arc::new(AtomicSlice::new(new_values));
+    }
+
+    /// Scale the entries so that the largest of them in magnitude is 1,
+    /// unless they are all zero
+    pub fn normalize(&self) {
+        let mut values = self.value.read().to_vec();
+        let peak = values.iter().fold(0.0_f32, |peak, v| peak.max(v.abs()));
+        if peak == 0.0 || !peak.is_finite() {
+            return;
+        }
+        for v in &mut values {
+            *v /= peak;
+        }
+        self.value.write(&values);
+    }
+
+    /// Set all entries to zero
+    pub fn clear(&self) {
+        self.value.write(&vec![0.0; self.length()]);
+    }
+
+    /// Replace the entries, changing the length to match if needed.
+    /// Fails if the number of entries is not between MIN_LENGTH and
+    /// MAX_LENGTH.
+    pub fn set_values(&mut self, values: &[f32]) -> Result<(), String> {
+        if values.len() < Self::MIN_LENGTH || values.len() > Self::MAX_LENGTH {
+            return Err(format!(
+                "A curve must have between {} and {} values, not {}",
+                Self::MIN_LENGTH,
+                Self::MAX_LENGTH,
+                values.len()
+            ));
+        }
+        if values.len() == self.length() {
+            self.value.write(values);
+        } else {
+            self.value = Arc::new(AtomicSlice::new(values.to_vec()));
+        }
+        Ok(())
+    }
+
+    /// Write the entries to the given file, which is either a csv file
+    /// with one value per line or a mono wav file with one value per
+    /// sample, depending on its extension
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let values = self.value.read().to_vec();
+        match CurveFileFormat::from_path(path)? {
+            CurveFileFormat::Csv => {
+                let mut text = String::new();
+                for v in values {
+                    text.push_str(&v.to_string());
+                    text.push('\n');
+                }
+                std::fs::write(path, text).map_err(|e| format!("Failed to write file: {}", e))
+            }
+            CurveFileFormat::Wav => save_wav_file(path, &[&values]),
+        }
+    }
+
+    /// Replace the entries with those read from the given file, which
+    /// is in one of the formats written by `export`. Values in csv files
+    /// may be separated by commas as well as by lines. Only the left
+    /// channel of a stereo wav file is read.
+    pub fn import(&mut self, path: &Path) -> Result<(), String> {
+        let values: Vec<f32> = match CurveFileFormat::from_path(path)? {
+            CurveFileFormat::Csv => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read file: {}", e))?;
+                text.split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        s.parse::<f32>()
+                            .map_err(|_| format!("\"{}\" is not a number", s))
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            CurveFileFormat::Wav => {
+                let buffer = load_audio_file(path)?;
+                buffer.samples_l().take(buffer.sample_len()).collect()
+            }
+        };
+        self.set_values(&values)
+    }
+
+    pub fn interpolation(&self) -> Sampler1dInterpolation {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Sampler1dInterpolation) {
+        self.interpolation = interpolation;
+    }
+}
+
+/// The kinds of files which a Sampler1d's curve can be imported from
+/// and exported to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum CurveFileFormat {
+    Csv,
+    Wav,
+}
+
+impl CurveFileFormat {
+    fn from_path(path: &Path) -> Result<CurveFileFormat, String> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Ok(CurveFileFormat::Csv),
+            Some("wav") => Ok(CurveFileFormat::Wav),
+            _ => Err("Curves can only be stored in csv or wav files".to_string()),
+        }
+    }
 }
 
 pub struct Sampler1dCompileState<'ctx> {
@@ -37,14 +262,26 @@ pub struct Sampler1dCompileState<'ctx> {
     ptr_status: PointerValue<'ctx>,
 }
 
+impl<'ctx> Sampler1dCompileState<'ctx> {
+    /// Pointer to the first entry of the readable half of the slice
+    pub(crate) fn ptr_slice(&self) -> PointerValue<'ctx> {
+        self.ptr_slice
+    }
+}
+
 impl ExpressionNode for Sampler1d {
     fn new(args: &ParsedArguments) -> Sampler1d {
-        // TODO: use args?
-        let mut value = Vec::new();
-        value.resize(256, 0.0);
+        let length = args
+            .get(&Sampler1d::ARG_LENGTH)
+            .unwrap_or(Sampler1d::DEFAULT_LENGTH)
+            .clamp(Sampler1d::MIN_LENGTH, Sampler1d::MAX_LENGTH);
+        let value = vec![0.0; length];
         Sampler1d {
             input: ExpressionInput::new(0.0),
             value: Arc::new(AtomicSlice::new(value)),
+            interpolation: args
+                .get(&Sampler1d::ARG_INTERPOLATION)
+                .unwrap_or(Sampler1dInterpolation::Linear),
         }
     }
 
@@ -57,102 +294,7 @@ impl ExpressionNode for Sampler1d {
     }
 
     fn compile_pre_loop<'ctx>(&self, jit: &mut Jit<'ctx>) -> Sampler1dCompileState<'ctx> {
-        let ptr_data;
-        let ptr_status;
-        unsafe {
-            ptr_data = self.value.raw_data();
-            ptr_status = self.value.raw_status();
-        }
-        let addr_status = jit.types.usize_type.const_int(ptr_status as u64, false);
-        let ptr_status = jit
-            .builder()
-            .build_int_to_ptr(addr_status, jit.types.pointer_type, "p_atomicstatus")
-            .unwrap();
-        let inc_all_slices = jit
-            .types
-            .u64_type
-            .const_int(atomicslice::constants::INC_ALL_SLICES, false);
-        let status_val = jit
-            .builder()
-            .build_atomicrmw(
-                AtomicRMWBinOp::Add,
-                ptr_status,
-                inc_all_slices,
-                AtomicOrdering::SequentiallyConsistent,
-            )
-            .unwrap();
-        let current_slice_mask = jit
-            .types
-            .u64_type
-            .const_int(atomicslice::constants::CURRENT_SLICE_MASK, false);
-        let current_slice = jit
-            .builder()
-            .build_and(status_val, current_slice_mask, "current_slice")
-            .unwrap();
-        let first_slice_is_active = jit
-            .builder()
-            .build_int_compare(
-                IntPredicate::EQ,
-                current_slice,
-                jit.types.u64_type.const_zero(),
-                "current_slice_is_zero_A",
-            )
-            .unwrap();
-        let inc_slice_1 = jit
-            .types
-            .u64_type
-            .const_int(atomicslice::constants::SLICE_1_INC, false);
-        let inc_slice_2 = jit
-            .types
-            .u64_type
-            .const_int(atomicslice::constants::SLICE_2_INC, false);
-        let inc_other_slice = jit
-            .builder()
-            .build_select(
-                first_slice_is_active,
-                inc_slice_2,
-                inc_slice_1,
-                "inc_other_slice",
-            )
-            .unwrap()
-            .into_int_value();
-        jit.builder()
-            .build_atomicrmw(
-                AtomicRMWBinOp::Sub,
-                ptr_status,
-                inc_other_slice,
-                AtomicOrdering::SequentiallyConsistent,
-            )
-            .unwrap();
-        let slice_len = jit
-            .types
-            .usize_type
-            .const_int(self.value.len() as u64, false);
-        let data_addr = jit.types.usize_type.const_int(ptr_data as u64, false);
-        let ptr_data = jit
-            .builder()
-            .build_int_to_ptr(data_addr, jit.types.pointer_type, "ptr_data")
-            .unwrap();
-        let offset = jit
-            .builder()
-            .build_select(
-                first_slice_is_active,
-                jit.types.usize_type.const_zero(),
-                slice_len,
-                "offset",
-            )
-            .unwrap()
-            .into_int_value();
-        let ptr_slice = unsafe {
-            jit.builder()
-                .build_gep(jit.types.f32_type, ptr_data, &[offset], "ptr_slice")
-        }
-        .unwrap();
-        Sampler1dCompileState {
-            ptr_slice,
-            current_slice,
-            ptr_status,
-        }
+        compile_acquire_slice(jit, &self.value)
     }
 
     fn compile_post_loop<'ctx>(
@@ -160,36 +302,7 @@ impl ExpressionNode for Sampler1d {
         jit: &mut Jit<'ctx>,
         compile_state: &Sampler1dCompileState<'ctx>,
     ) {
-        let inc_slice_1 = jit
-            .types
-            .u64_type
-            .const_int(atomicslice::constants::SLICE_1_INC, false);
-        let inc_slice_2 = jit
-            .types
-            .u64_type
-            .const_int(atomicslice::constants::SLICE_2_INC, false);
-        let first_slice_is_active = jit
-            .builder()
-            .build_int_compare(
-                IntPredicate::EQ,
-                compile_state.current_slice,
-                jit.types.u64_type.const_zero(),
-                "current_slice_is_zero_B",
-            )
-            .unwrap();
-        let inc_other_slice = jit
-            .builder()
-            .build_select(first_slice_is_active, inc_slice_1, inc_slice_2, "inc_slice")
-            .unwrap()
-            .into_int_value();
-        jit.builder()
-            .build_atomicrmw(
-                AtomicRMWBinOp::Sub,
-                compile_state.ptr_status,
-                inc_other_slice,
-                AtomicOrdering::SequentiallyConsistent,
-            )
-            .unwrap();
+        compile_release_slice(jit, compile_state);
     }
 
     fn compile_loop<'ctx>(
@@ -219,7 +332,6 @@ impl ExpressionNode for Sampler1d {
             )
             .unwrap();
         let index_floor = jit.build_unary_intrinsic_call("llvm.floor", index_float);
-        let index_ceil = jit.build_unary_intrinsic_call("llvm.ceil", index_float);
         let index_fract = jit
             .builder()
             .build_float_sub(index_float, index_floor, "index_fract")
@@ -228,74 +340,78 @@ impl ExpressionNode for Sampler1d {
             .builder()
             .build_float_to_unsigned_int(index_floor, jit.types.usize_type, "index_floor_int")
             .unwrap();
-        let index_ceil_int = jit
-            .builder()
-            .build_float_to_unsigned_int(index_ceil, jit.types.usize_type, "index_ceil_int")
-            .unwrap();
         let slice_len = jit
             .types
             .usize_type
             .const_int(self.value.len() as u64, false);
-        let zero = jit.types.usize_type.const_zero();
-        let index_floor_int_is_n = jit
-            .builder()
-            .build_int_compare(
-                IntPredicate::EQ,
-                index_floor_int,
-                slice_len,
-                "index_floor_int_is_n",
-            )
-            .unwrap();
-        let index_ceil_int_is_n = jit
-            .builder()
-            .build_int_compare(
-                IntPredicate::EQ,
-                index_ceil_int,
-                slice_len,
-                "index_ceil_int_is_n",
-            )
-            .unwrap();
-        let i0 = jit
-            .builder()
-            .build_select(index_floor_int_is_n, zero, index_floor_int, "i0")
-            .unwrap()
-            .into_int_value();
-        let i1 = jit
-            .builder()
-            .build_select(index_ceil_int_is_n, zero, index_ceil_int, "i0")
-            .unwrap()
-            .into_int_value();
 
         let ptr_slice = compile_state.ptr_slice;
 
-        let ptr_v0 = unsafe {
-            jit.builder()
-                .build_gep(jit.types.f32_type, ptr_slice, &[i0], "ptr_v0")
-        }
-        .unwrap();
-        let ptr_v1 = unsafe {
-            jit.builder()
-                .build_gep(jit.types.f32_type, ptr_slice, &[i1], "ptr_v0")
-        }
-        .unwrap();
-        let v0 = jit
-            .builder()
-            .build_load(jit.types.f32_type, ptr_v0, "v0")
-            .unwrap()
-            .into_float_value();
-        let v1 = jit
-            .builder()
-            .build_load(jit.types.f32_type, ptr_v1, "v1")
-            .unwrap()
-            .into_float_value();
-        let diff = jit.builder().build_float_sub(v1, v0, "diff").unwrap();
-        let scaled_diff = jit
-            .builder()
-            .build_float_mul(index_fract, diff, "scaled_diff")
+        // Load the entry at the given offset from the floor of the index,
+        // wrapping around at either end of the slice. The offset is added
+        // to a multiple of the length to keep negative offsets unsigned.
+        let load = |offset: i64, name: &str| {
+            let shifted_index = jit
+                .builder()
+                .build_int_add(
+                    index_floor_int,
+                    jit.types
+                        .usize_type
+                        .const_int((self.value.len() as i64 + offset) as u64, false),
+                    "shifted_index",
+                )
+                .unwrap();
+            let i = jit
+                .builder()
+                .build_int_unsigned_rem(shifted_index, slice_len, "wrapped_index")
+                .unwrap();
+            let ptr_v = unsafe {
+                jit.builder()
+                    .build_gep(jit.types.f32_type, ptr_slice, &[i], "ptr_v")
+            }
             .unwrap();
-        let v = jit.builder().build_float_add(v0, scaled_diff, "v").unwrap();
+            jit.builder()
+                .build_load(jit.types.f32_type, ptr_v, name)
+                .unwrap()
+                .into_float_value()
+        };
 
-        v
+        match self.interpolation {
+            Sampler1dInterpolation::Nearest => {
+                let v0 = load(0, "v0");
+                let v1 = load(1, "v1");
+                let past_half = jit
+                    .builder()
+                    .build_float_compare(
+                        FloatPredicate::OGE,
+                        index_fract,
+                        jit.types.f32_type.const_float(0.5),
+                        "past_half",
+                    )
+                    .unwrap();
+                jit.builder()
+                    .build_select(past_half, v1, v0, "v")
+                    .unwrap()
+                    .into_float_value()
+            }
+            Sampler1dInterpolation::Linear => {
+                let v0 = load(0, "v0");
+                let v1 = load(1, "v1");
+                let diff = jit.builder().build_float_sub(v1, v0, "diff").unwrap();
+                let scaled_diff = jit
+                    .builder()
+                    .build_float_mul(index_fract, diff, "scaled_diff")
+                    .unwrap();
+                jit.builder().build_float_add(v0, scaled_diff, "v").unwrap()
+            }
+            Sampler1dInterpolation::Cubic => {
+                let vm1 = load(-1, "vm1");
+                let v0 = load(0, "v0");
+                let v1 = load(1, "v1");
+                let v2 = load(2, "v2");
+                build_catmull_rom(jit, [vm1, v0, v1, v2], index_fract)
+            }
+        }
     }
 
     fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
@@ -309,6 +425,7 @@ impl ExpressionNode for Sampler1d {
 impl Stashable<StashingContext> for Sampler1d {
     fn stash(&self, stasher: &mut Stasher<StashingContext>) {
         stasher.object(&self.input);
+        stasher.u8(self.interpolation.to_u8());
 
         if stasher.context().checking_recompilation() {
             // If only checking for changes that require recompilation,
@@ -329,15 +446,204 @@ impl Stashable<StashingContext> for Sampler1d {
 impl UnstashableInplace for Sampler1d {
     fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
         unstasher.object_inplace(&mut self.input)?;
+        let interpolation = Sampler1dInterpolation::from_u8(unstasher.u8_always()?)
+            .ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.interpolation = interpolation;
+        }
         let new_values = unstasher.array_of_f32_iter()?;
         if unstasher.time_to_write() {
             let new_values: Vec<f32> = new_values.collect();
-            self.value.write(&new_values);
+            if new_values.len() == self.value.len() {
+                self.value.write(&new_values);
+            } else {
+                self.value = Arc::new(AtomicSlice::new(new_values));
+            }
         }
         Ok(())
     }
 }
 
+/// Generate the IR which marks the slice as being read from and finds
+/// the half of it which is currently readable. The slice is kept alive
+/// for as long as the compiled expression.
+pub(crate) fn compile_acquire_slice<'ctx>(
+    jit: &mut Jit<'ctx>,
+    value: &Arc<AtomicSlice<f32>>,
+) -> Sampler1dCompileState<'ctx> {
+    let ptr_data;
+    let ptr_status;
+    unsafe {
+        ptr_data = value.raw_data();
+        ptr_status = value.raw_status();
+    }
+    let addr_status = jit.types.usize_type.const_int(ptr_status as u64, false);
+    let ptr_status = jit
+        .builder()
+        .build_int_to_ptr(addr_status, jit.types.pointer_type, "p_atomicstatus")
+        .unwrap();
+    let inc_all_slices = jit
+        .types
+        .u64_type
+        .const_int(atomicslice::constants::INC_ALL_SLICES, false);
+    let status_val = jit
+        .builder()
+        .build_atomicrmw(
+            AtomicRMWBinOp::Add,
+            ptr_status,
+            inc_all_slices,
+            AtomicOrdering::SequentiallyConsistent,
+        )
+        .unwrap();
+    let current_slice_mask = jit
+        .types
+        .u64_type
+        .const_int(atomicslice::constants::CURRENT_SLICE_MASK, false);
+    let current_slice = jit
+        .builder()
+        .build_and(status_val, current_slice_mask, "current_slice")
+        .unwrap();
+    let first_slice_is_active = jit
+        .builder()
+        .build_int_compare(
+            IntPredicate::EQ,
+            current_slice,
+            jit.types.u64_type.const_zero(),
+            "current_slice_is_zero_A",
+        )
+        .unwrap();
+    let inc_slice_1 = jit
+        .types
+        .u64_type
+        .const_int(atomicslice::constants::SLICE_1_INC, false);
+    let inc_slice_2 = jit
+        .types
+        .u64_type
+        .const_int(atomicslice::constants::SLICE_2_INC, false);
+    let inc_other_slice = jit
+        .builder()
+        .build_select(
+            first_slice_is_active,
+            inc_slice_2,
+            inc_slice_1,
+            "inc_other_slice",
+        )
+        .unwrap()
+        .into_int_value();
+    jit.builder()
+        .build_atomicrmw(
+            AtomicRMWBinOp::Sub,
+            ptr_status,
+            inc_other_slice,
+            AtomicOrdering::SequentiallyConsistent,
+        )
+        .unwrap();
+    let slice_len = jit.types.usize_type.const_int(value.len() as u64, false);
+    let data_addr = jit.types.usize_type.const_int(ptr_data as u64, false);
+    let ptr_data = jit
+        .builder()
+        .build_int_to_ptr(data_addr, jit.types.pointer_type, "ptr_data")
+        .unwrap();
+    let offset = jit
+        .builder()
+        .build_select(
+            first_slice_is_active,
+            jit.types.usize_type.const_zero(),
+            slice_len,
+            "offset",
+        )
+        .unwrap()
+        .into_int_value();
+    let ptr_slice = unsafe {
+        jit.builder()
+            .build_gep(jit.types.f32_type, ptr_data, &[offset], "ptr_slice")
+    }
+    .unwrap();
+    // The slice may be replaced when resizing, so the compiled
+    // expression must keep the one it reads from alive
+    jit.keep_alive(Arc::clone(value) as _);
+    Sampler1dCompileState {
+        ptr_slice,
+        current_slice,
+        ptr_status,
+    }
+}
+
+/// Generate the IR which releases the slice acquired by
+/// `compile_acquire_slice`
+pub(crate) fn compile_release_slice<'ctx>(
+    jit: &mut Jit<'ctx>,
+    compile_state: &Sampler1dCompileState<'ctx>,
+) {
+    let inc_slice_1 = jit
+        .types
+        .u64_type
+        .const_int(atomicslice::constants::SLICE_1_INC, false);
+    let inc_slice_2 = jit
+        .types
+        .u64_type
+        .const_int(atomicslice::constants::SLICE_2_INC, false);
+    let first_slice_is_active = jit
+        .builder()
+        .build_int_compare(
+            IntPredicate::EQ,
+            compile_state.current_slice,
+            jit.types.u64_type.const_zero(),
+            "current_slice_is_zero_B",
+        )
+        .unwrap();
+    let inc_other_slice = jit
+        .builder()
+        .build_select(first_slice_is_active, inc_slice_1, inc_slice_2, "inc_slice")
+        .unwrap()
+        .into_int_value();
+    jit.builder()
+        .build_atomicrmw(
+            AtomicRMWBinOp::Sub,
+            compile_state.ptr_status,
+            inc_other_slice,
+            AtomicOrdering::SequentiallyConsistent,
+        )
+        .unwrap();
+}
+
+/// Generate the IR for `catmull_rom`
+fn build_catmull_rom<'ctx>(
+    jit: &mut Jit<'ctx>,
+    [vm1, v0, v1, v2]: [FloatValue<'ctx>; 4],
+    t: FloatValue<'ctx>,
+) -> FloatValue<'ctx> {
+    let f32_type = jit.types.f32_type;
+    let b = jit.builder();
+    let mul = |k: f64, v: FloatValue<'ctx>, name: &str| {
+        b.build_float_mul(f32_type.const_float(k), v, name).unwrap()
+    };
+
+    // a = 3 * (v0 - v1) + v2 - vm1
+    let v0_minus_v1 = b.build_float_sub(v0, v1, "v0_minus_v1").unwrap();
+    let a = mul(3.0, v0_minus_v1, "a_0");
+    let a = b.build_float_add(a, v2, "a_1").unwrap();
+    let a = b.build_float_sub(a, vm1, "a").unwrap();
+
+    // b = 2 * vm1 - 5 * v0 + 4 * v1 - v2
+    let bb = mul(2.0, vm1, "b_0");
+    let bb = b.build_float_sub(bb, mul(5.0, v0, "5v0"), "b_1").unwrap();
+    let bb = b.build_float_add(bb, mul(4.0, v1, "4v1"), "b_2").unwrap();
+    let bb = b.build_float_sub(bb, v2, "b").unwrap();
+
+    // c = v1 - vm1
+    let c = b.build_float_sub(v1, vm1, "c").unwrap();
+
+    // v0 + 0.5 * t * (c + t * (b + t * a))
+    let p = b.build_float_mul(t, a, "t_a").unwrap();
+    let p = b.build_float_add(bb, p, "b_plus_t_a").unwrap();
+    let p = b.build_float_mul(t, p, "t_b_plus_t_a").unwrap();
+    let p = b.build_float_add(c, p, "c_plus").unwrap();
+    let p = b.build_float_mul(t, p, "t_c_plus").unwrap();
+    let p = mul(0.5, p, "half_t_c_plus");
+    b.build_float_add(v0, p, "v").unwrap()
+}
+
 impl WithObjectType for Sampler1d {
     const TYPE: ObjectType = ObjectType::new("sampler1d");
 }
@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use atomicslice::AtomicSlice;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use inkwell::values::{FloatValue, IntValue, PointerValue};
+
+use crate::{
+    core::{
+        expression::{
+            expressioninput::ExpressionInput,
+            expressionnode::{ExpressionNode, ExpressionNodeVisitor, ExpressionNodeVisitorMut},
+        },
+        jit::jit::Jit,
+        objecttype::{ObjectType, WithObjectType},
+        stashing::StashingContext,
+    },
+    objects::sampler1d::{compile_acquire_slice, compile_release_slice, Sampler1dCompileState},
+    ui_core::arguments::{NaturalNumberArgument, ParsedArguments},
+};
+
+/// A grid of values which is read at a point given by two inputs, e.g.
+/// a stack of wavetables to morph between. The entries of each row are
+/// spread evenly from 0 to 1 along x, including both ends, and likewise
+/// the rows are spread from 0 to 1 along y. Positions outside of that
+/// square are clamped to its edges, and positions between entries are
+/// interpolated bilinearly.
+pub struct Sampler2d {
+    input_x: ExpressionInput,
+    input_y: ExpressionInput,
+    width: usize,
+    height: usize,
+    /// The entries, one row after the other
+    value: Arc<AtomicSlice<f32>>,
+}
+
+impl Sampler2d {
+    pub const ARG_WIDTH: NaturalNumberArgument = NaturalNumberArgument("width");
+    pub const ARG_HEIGHT: NaturalNumberArgument = NaturalNumberArgument("height");
+
+    pub const DEFAULT_WIDTH: usize = 64;
+    pub const DEFAULT_HEIGHT: usize = 8;
+    pub const MIN_SIZE: usize = 2;
+    pub const MAX_SIZE: usize = 1024;
+
+    pub fn value(&self) -> &AtomicSlice<f32> {
+        &self.value
+    }
+
+    /// The number of entries in each row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Set all entries to zero
+    pub fn clear(&self) {
+        self.value.write(&vec![0.0; self.width * self.height]);
+    }
+
+    /// Read the value at the given position from the given entries,
+    /// which are laid out as described on `Sampler2d`.
+    /// This matches the compiled expression node.
+    pub fn sample(values: &[f32], width: usize, x: f32, y: f32) -> f32 {
+        debug_assert!(width >= Self::MIN_SIZE);
+        debug_assert_eq!(values.len() % width, 0);
+        let height = values.len() / width;
+        let (i, tx) = grid_position(x, width);
+        let (j, ty) = grid_position(y, height);
+        let at = |i: usize, j: usize| values[j * width + i];
+        let v0 = at(i, j) + tx * (at(i + 1, j) - at(i, j));
+        let v1 = at(i, j + 1) + tx * (at(i + 1, j + 1) - at(i, j + 1));
+        v0 + ty * (v1 - v0)
+    }
+}
+
+/// The index of the entry at or before the given position along an axis
+/// with the given number of entries, and how far the position lies between
+/// that entry and the next one. The index is never that of the last entry,
+/// so that the next entry is always in bounds.
+fn grid_position(position: f32, len: usize) -> (usize, f32) {
+    // NaN is sent to the start, as maxnum does in the compiled node
+    let p = if position >= 0.0 {
+        position.min(1.0)
+    } else {
+        0.0
+    };
+    let p = p * (len - 1) as f32;
+    let p_floor = p.floor().min((len - 2) as f32);
+    (p_floor as usize, p - p_floor)
+}
+
+impl ExpressionNode for Sampler2d {
+    fn new(args: &ParsedArguments) -> Sampler2d {
+        let width = args
+            .get(&Sampler2d::ARG_WIDTH)
+            .unwrap_or(Sampler2d::DEFAULT_WIDTH)
+            .clamp(Sampler2d::MIN_SIZE, Sampler2d::MAX_SIZE);
+        let height = args
+            .get(&Sampler2d::ARG_HEIGHT)
+            .unwrap_or(Sampler2d::DEFAULT_HEIGHT)
+            .clamp(Sampler2d::MIN_SIZE, Sampler2d::MAX_SIZE);
+        Sampler2d {
+            input_x: ExpressionInput::new(0.0),
+            input_y: ExpressionInput::new(0.0),
+            width,
+            height,
+            value: Arc::new(AtomicSlice::new(vec![0.0; width * height])),
+        }
+    }
+
+    const NUM_VARIABLES: usize = 0;
+
+    type CompileState<'ctx> = Sampler1dCompileState<'ctx>;
+
+    fn compile_start_over<'ctx>(&self, _jit: &mut Jit<'ctx>) -> Vec<FloatValue<'ctx>> {
+        vec![]
+    }
+
+    fn compile_pre_loop<'ctx>(&self, jit: &mut Jit<'ctx>) -> Sampler1dCompileState<'ctx> {
+        compile_acquire_slice(jit, &self.value)
+    }
+
+    fn compile_post_loop<'ctx>(
+        &self,
+        jit: &mut Jit<'ctx>,
+        compile_state: &Sampler1dCompileState<'ctx>,
+    ) {
+        compile_release_slice(jit, compile_state);
+    }
+
+    fn compile_loop<'ctx>(
+        &self,
+        jit: &mut Jit<'ctx>,
+        inputs: &[FloatValue<'ctx>],
+        variables: &[PointerValue<'ctx>],
+        compile_state: &Sampler1dCompileState<'ctx>,
+    ) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 2);
+        debug_assert_eq!(variables.len(), 0);
+
+        let (i, tx) = build_grid_position(jit, inputs[0], self.width, "x");
+        let (j, ty) = build_grid_position(jit, inputs[1], self.height, "y");
+
+        let usize_type = jit.types.usize_type;
+        let f32_type = jit.types.f32_type;
+        let ptr_slice = compile_state.ptr_slice();
+
+        // Index of the entry at or before the position
+        let row_offset = jit
+            .builder()
+            .build_int_mul(
+                j,
+                usize_type.const_int(self.width as u64, false),
+                "row_offset",
+            )
+            .unwrap();
+        let index = jit.builder().build_int_add(row_offset, i, "index").unwrap();
+
+        // Load the entry at the given offset from that index
+        let load = |offset: usize, name: &str| {
+            let i = jit
+                .builder()
+                .build_int_add(index, usize_type.const_int(offset as u64, false), "index")
+                .unwrap();
+            let ptr_v =
+                unsafe { jit.builder().build_gep(f32_type, ptr_slice, &[i], "ptr_v") }.unwrap();
+            jit.builder()
+                .build_load(f32_type, ptr_v, name)
+                .unwrap()
+                .into_float_value()
+        };
+
+        let v00 = load(0, "v00");
+        let v10 = load(1, "v10");
+        let v01 = load(self.width, "v01");
+        let v11 = load(self.width + 1, "v11");
+
+        let v0 = build_lerp(jit, v00, v10, tx, "v0");
+        let v1 = build_lerp(jit, v01, v11, tx, "v1");
+        build_lerp(jit, v0, v1, ty, "v")
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input_x);
+        visitor.input(&self.input_y);
+    }
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input_x);
+        visitor.input(&mut self.input_y);
+    }
+}
+
+/// Generate the IR for `grid_position`
+fn build_grid_position<'ctx>(
+    jit: &mut Jit<'ctx>,
+    position: FloatValue<'ctx>,
+    len: usize,
+    name: &str,
+) -> (IntValue<'ctx>, FloatValue<'ctx>) {
+    let f32_type = jit.types.f32_type;
+    let p = jit.build_binary_intrinsic_call("llvm.maxnum", position, f32_type.const_zero());
+    let p = jit.build_binary_intrinsic_call("llvm.minnum", p, f32_type.const_float(1.0));
+    let p = jit
+        .builder()
+        .build_float_mul(
+            p,
+            f32_type.const_float((len - 1) as f64),
+            &format!("{}_position", name),
+        )
+        .unwrap();
+    let p_floor = jit.build_unary_intrinsic_call("llvm.floor", p);
+    let p_floor = jit.build_binary_intrinsic_call(
+        "llvm.minnum",
+        p_floor,
+        f32_type.const_float((len - 2) as f64),
+    );
+    let fraction = jit
+        .builder()
+        .build_float_sub(p, p_floor, &format!("{}_fraction", name))
+        .unwrap();
+    let index = jit
+        .builder()
+        .build_float_to_unsigned_int(p_floor, jit.types.usize_type, &format!("{}_index", name))
+        .unwrap();
+    (index, fraction)
+}
+
+/// Generate the IR for v0 + t * (v1 - v0)
+fn build_lerp<'ctx>(
+    jit: &mut Jit<'ctx>,
+    v0: FloatValue<'ctx>,
+    v1: FloatValue<'ctx>,
+    t: FloatValue<'ctx>,
+    name: &str,
+) -> FloatValue<'ctx> {
+    let diff = jit.builder().build_float_sub(v1, v0, "diff").unwrap();
+    let scaled_diff = jit
+        .builder()
+        .build_float_mul(t, diff, "scaled_diff")
+        .unwrap();
+    jit.builder()
+        .build_float_add(v0, scaled_diff, name)
+        .unwrap()
+}
+
+impl Stashable<StashingContext> for Sampler2d {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input_x);
+        stasher.object(&self.input_y);
+        stasher.u64(self.width as _);
+        stasher.u64(self.height as _);
+
+        if stasher.context().checking_recompilation() {
+            // As with Sampler1d, the values update themselves on the
+            // audio thread, but a different slice requires recompilation
+            let ptr_raw_data: *const f32 = unsafe { self.value.raw_data() };
+            stasher.u64((ptr_raw_data as usize) as _);
+        } else {
+            let reader = self.value.read();
+            stasher.array_of_f32_slice(&reader);
+        }
+    }
+}
+
+impl UnstashableInplace for Sampler2d {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input_x)?;
+        unstasher.object_inplace(&mut self.input_y)?;
+        let width = unstasher.u64_always()? as usize;
+        let height = unstasher.u64_always()? as usize;
+        let size_range = Self::MIN_SIZE..=Self::MAX_SIZE;
+        if !size_range.contains(&width) || !size_range.contains(&height) {
+            return Err(UnstashError::Corrupted);
+        }
+        let new_values = unstasher.array_of_f32_iter()?;
+        if unstasher.time_to_write() {
+            let new_values: Vec<f32> = new_values.collect();
+            if new_values.len() != width * height {
+                return Err(UnstashError::Corrupted);
+            }
+            self.width = width;
+            self.height = height;
+            if new_values.len() == self.value.len() {
+                self.value.write(&new_values);
+            } else {
+                self.value = Arc::new(AtomicSlice::new(new_values));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WithObjectType for Sampler2d {
+    const TYPE: ObjectType = ObjectType::new("sampler2d");
+}
@@ -0,0 +1,155 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Move `previous` towards `target` by no more than the given
+/// amounts per sample, returning the new value
+fn slew_towards(previous: f32, target: f32, max_rise: f32, max_fall: f32) -> f32 {
+    let delta = target - previous;
+    if !delta.is_finite() {
+        return if target.is_finite() { target } else { previous };
+    }
+    previous + delta.clamp(-max_fall, max_rise)
+}
+
+/// Limit the rate of change of both channels of the chunk, given the
+/// previous output and per-sample maximum rates of rise and fall in
+/// units per second. Negative rates are treated as zero, which holds
+/// the signal in place.
+pub(crate) fn apply_slew(
+    chunk: &mut SoundChunk,
+    previous: &mut (f32, f32),
+    rise: &[f32],
+    fall: &[f32],
+) {
+    debug_assert_eq!(rise.len(), CHUNK_SIZE);
+    debug_assert_eq!(fall.len(), CHUNK_SIZE);
+    let step = 1.0 / SAMPLE_FREQUENCY as f32;
+    for (i, (l, r)) in chunk.samples_mut().enumerate() {
+        let max_rise = rise[i].max(0.0) * step;
+        let max_fall = fall[i].max(0.0) * step;
+        previous.0 = slew_towards(previous.0, *l, max_rise, max_fall);
+        previous.1 = slew_towards(previous.1, *r, max_rise, max_fall);
+        *l = previous.0;
+        *r = previous.1;
+    }
+}
+
+pub struct SlewState {
+    previous: (f32, f32),
+}
+
+impl ProcessorState for SlewState {
+    type Processor = Slew;
+
+    fn new(_processor: &Slew) -> Self {
+        SlewState {
+            previous: (0.0, 0.0),
+        }
+    }
+}
+
+impl StartOver for SlewState {
+    fn start_over(&mut self) {
+        self.previous = (0.0, 0.0);
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Slew {
+    pub input: SingleInput,
+
+    /// The fastest that the output may increase, in units per second
+    pub rise: ProcessorExpression,
+
+    /// The fastest that the output may decrease, in units per second
+    pub fall: ProcessorExpression,
+
+    #[state]
+    state: StateMarker<SlewState>,
+}
+
+impl SoundProcessor for Slew {
+    fn new(_args: &ParsedArguments) -> Slew {
+        Slew {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            rise: ProcessorExpression::new(&[10.0], ArgumentScope::new_empty()),
+            fall: ProcessorExpression::new(&[10.0], ArgumentScope::new_empty()),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        slew: &mut CompiledSlew,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = slew.input.step(dst, InputContext::new(context));
+
+        let mut rise = [0.0; CHUNK_SIZE];
+        let mut fall = [0.0; CHUNK_SIZE];
+        slew.rise.eval(
+            &mut [&mut rise],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        slew.fall.eval(
+            &mut [&mut fall],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        apply_slew(dst, &mut slew.state.previous, &rise, &fall);
+
+        status
+    }
+}
+
+impl WithObjectType for Slew {
+    const TYPE: ObjectType = ObjectType::new("slew");
+}
+
+impl Stashable<StashingContext> for Slew {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.rise);
+        stasher.object(&self.fall);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Slew {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.rise)?;
+        unstasher.object_inplace(&mut self.fall)?;
+        Ok(())
+    }
+}
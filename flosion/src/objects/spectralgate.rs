@@ -0,0 +1,361 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use atomic_float::AtomicF32;
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use parking_lot::Mutex;
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+        stft::Stft,
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// The number of samples in each analysis frame
+pub(crate) const FRAME_SIZE: usize = 1024;
+
+/// The number of frequency bins in each analysis frame
+pub(crate) const BIN_COUNT: usize = FRAME_SIZE / 2 + 1;
+
+/// A snapshot of the spectral gate's settings, as used on the audio thread
+#[derive(Clone, Copy)]
+pub(crate) struct SpectralGateParameters {
+    /// The level, in decibels, below which a frequency bin is attenuated
+    pub(crate) threshold: f32,
+
+    /// How far, in decibels, attenuated bins are turned down
+    pub(crate) reduction: f32,
+}
+
+/// Accumulates the average level of each frequency bin over the
+/// frames it is shown, for use as a noise profile
+pub(crate) struct NoiseProfileLearner {
+    sums: Vec<f32>,
+    frames: usize,
+}
+
+impl NoiseProfileLearner {
+    pub(crate) fn new() -> NoiseProfileLearner {
+        NoiseProfileLearner {
+            sums: vec![0.0; BIN_COUNT],
+            frames: 0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.sums.fill(0.0);
+        self.frames = 0;
+    }
+
+    /// Write the average level of each bin so far into `profile`.
+    /// Returns false without writing anything if no frames were seen.
+    pub(crate) fn write_profile(&self, profile: &mut [f32]) -> bool {
+        if self.frames == 0 {
+            return false;
+        }
+        let scale = 1.0 / self.frames as f32;
+        for (p, s) in profile.iter_mut().zip(&self.sums) {
+            *p = s * scale;
+        }
+        true
+    }
+}
+
+/// Gates a single channel of audio in the frequency domain
+pub(crate) struct SpectralGateChannel {
+    stft: Stft,
+}
+
+impl SpectralGateChannel {
+    pub(crate) fn new() -> SpectralGateChannel {
+        SpectralGateChannel {
+            stft: Stft::new(FRAME_SIZE),
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.stft.reset();
+    }
+
+    /// Attenuate every frequency bin whose level is below the threshold
+    /// or, if one is given, the level of the noise profile in that bin.
+    /// If a learner is given, it is shown every frame's levels as well.
+    pub(crate) fn process(
+        &mut self,
+        samples: &mut [f32],
+        parameters: SpectralGateParameters,
+        noise_profile: Option<&[f32]>,
+        mut learner: Option<&mut NoiseProfileLearner>,
+    ) {
+        let amplitude_scale = self.stft.amplitude_scale();
+        let threshold = 10.0_f32.powf(parameters.threshold / 20.0);
+        let gain = 10.0_f32.powf(-parameters.reduction.max(0.0) / 20.0);
+
+        self.stft.process(samples, |bins| {
+            if let Some(learner) = learner.as_deref_mut() {
+                for (s, b) in learner.sums.iter_mut().zip(bins.iter()) {
+                    *s += b.norm() * amplitude_scale;
+                }
+                learner.frames += 1;
+            }
+
+            for (k, b) in bins.iter_mut().enumerate() {
+                let level = b.norm() * amplitude_scale;
+                let floor = noise_profile.map_or(0.0, |p| p[k]);
+                if level < threshold.max(floor) {
+                    *b = b.scale(gain);
+                }
+            }
+        });
+    }
+}
+
+pub struct SpectralGateSettings {
+    threshold: AtomicF32,
+    reduction: AtomicF32,
+    learning: AtomicBool,
+    has_noise_profile: AtomicBool,
+    noise_profile: Mutex<Vec<f32>>,
+}
+
+impl SpectralGateSettings {
+    fn parameters(&self) -> SpectralGateParameters {
+        SpectralGateParameters {
+            threshold: self.threshold.load(Ordering::Relaxed),
+            reduction: self.reduction.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub struct SpectralGateState {
+    settings: Arc<SpectralGateSettings>,
+    left: SpectralGateChannel,
+    right: SpectralGateChannel,
+    learner: NoiseProfileLearner,
+    was_learning: bool,
+    noise_profile: Vec<f32>,
+}
+
+impl ProcessorState for SpectralGateState {
+    type Processor = SpectralGate;
+
+    fn new(processor: &SpectralGate) -> Self {
+        SpectralGateState {
+            settings: Arc::clone(&processor.settings),
+            left: SpectralGateChannel::new(),
+            right: SpectralGateChannel::new(),
+            learner: NoiseProfileLearner::new(),
+            was_learning: false,
+            noise_profile: vec![0.0; BIN_COUNT],
+        }
+    }
+}
+
+impl StartOver for SpectralGateState {
+    fn start_over(&mut self) {
+        self.left.reset();
+        self.right.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct SpectralGate {
+    pub input: SingleInput,
+
+    #[not_a_component]
+    settings: Arc<SpectralGateSettings>,
+
+    #[state]
+    state: StateMarker<SpectralGateState>,
+}
+
+impl SpectralGate {
+    pub fn threshold(&self) -> f32 {
+        self.settings.threshold.load(Ordering::Relaxed)
+    }
+
+    pub fn set_threshold(&self, decibels: f32) {
+        self.settings.threshold.store(decibels, Ordering::Relaxed);
+    }
+
+    pub fn reduction(&self) -> f32 {
+        self.settings.reduction.load(Ordering::Relaxed)
+    }
+
+    pub fn set_reduction(&self, decibels: f32) {
+        self.settings
+            .reduction
+            .store(decibels.max(0.0), Ordering::Relaxed);
+    }
+
+    /// Whether the noise profile is currently being learned from the input
+    pub fn is_learning(&self) -> bool {
+        self.settings.learning.load(Ordering::Relaxed)
+    }
+
+    /// Begin learning a new noise profile from the input. The profile
+    /// is the average level of each frequency bin until learning stops,
+    /// so this should be started and stopped around a segment which
+    /// contains only the noise to be removed.
+    pub fn start_learning(&self) {
+        self.settings.learning.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop_learning(&self) {
+        self.settings.learning.store(false, Ordering::Relaxed);
+    }
+
+    pub fn has_noise_profile(&self) -> bool {
+        self.settings.has_noise_profile.load(Ordering::Relaxed)
+    }
+
+    pub fn clear_noise_profile(&self) {
+        self.settings
+            .has_noise_profile
+            .store(false, Ordering::Relaxed);
+    }
+}
+
+impl SoundProcessor for SpectralGate {
+    fn new(_args: &ParsedArguments) -> SpectralGate {
+        SpectralGate {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            settings: Arc::new(SpectralGateSettings {
+                threshold: AtomicF32::new(-50.0),
+                reduction: AtomicF32::new(30.0),
+                learning: AtomicBool::new(false),
+                has_noise_profile: AtomicBool::new(false),
+                noise_profile: Mutex::new(vec![0.0; BIN_COUNT]),
+            }),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn latency(&self) -> usize {
+        // The delay of an Stft with FRAME_SIZE
+        FRAME_SIZE
+    }
+
+    fn process_audio(
+        gate: &mut CompiledSpectralGate,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = gate.input.step(dst, InputContext::new(context));
+
+        let state = &mut gate.state;
+        let settings = &*state.settings;
+        let parameters = settings.parameters();
+
+        let learning = settings.learning.load(Ordering::Relaxed);
+        if learning && !state.was_learning {
+            state.learner.reset();
+        }
+        state.was_learning = learning;
+
+        // The profile is only copied when it can be done without
+        // waiting, otherwise the previous copy is used for this chunk
+        let has_noise_profile = settings.has_noise_profile.load(Ordering::Relaxed);
+        if has_noise_profile {
+            if let Some(profile) = settings.noise_profile.try_lock() {
+                state.noise_profile.copy_from_slice(&profile);
+            }
+        }
+        let noise_profile = if has_noise_profile && !learning {
+            Some(&state.noise_profile[..])
+        } else {
+            None
+        };
+
+        let mut learner = if learning {
+            Some(&mut state.learner)
+        } else {
+            None
+        };
+        state.left.process(
+            &mut dst.l,
+            parameters,
+            noise_profile,
+            learner.as_deref_mut(),
+        );
+        state
+            .right
+            .process(&mut dst.r, parameters, noise_profile, learner);
+
+        if learning {
+            if let Some(mut profile) = settings.noise_profile.try_lock() {
+                if state.learner.write_profile(&mut profile) {
+                    settings.has_noise_profile.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+
+        status
+    }
+}
+
+impl WithObjectType for SpectralGate {
+    const TYPE: ObjectType = ObjectType::new("spectralgate");
+}
+
+impl Stashable<StashingContext> for SpectralGate {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        if stasher.context().checking_recompilation() {
+            // Settings and the noise profile are shared with the audio
+            // thread, so changing them doesn't require recompiling anything
+            let ptr: *const SpectralGateSettings = &*self.settings;
+            stasher.u64((ptr as usize) as _);
+        } else {
+            stasher.f32(self.threshold());
+            stasher.f32(self.reduction());
+            stasher.bool(self.has_noise_profile());
+            stasher.array_of_f32_slice(&self.settings.noise_profile.lock());
+        }
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for SpectralGate {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        let threshold = unstasher.f32_always()?;
+        let reduction = unstasher.f32_always()?;
+        let has_noise_profile = unstasher.bool_always()?;
+        let noise_profile: Vec<f32> = unstasher.array_of_f32_iter()?.collect();
+        if noise_profile.len() != BIN_COUNT {
+            return Err(UnstashError::Corrupted);
+        }
+        if unstasher.time_to_write() {
+            self.set_threshold(threshold);
+            self.set_reduction(reduction);
+            *self.settings.noise_profile.lock() = noise_profile;
+            self.settings
+                .has_noise_profile
+                .store(has_noise_profile, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
@@ -14,13 +14,12 @@ use crate::{
         objecttype::{ObjectType, WithObjectType},
         stashing::StashingContext,
     },
-    ui_core::arguments::ParsedArguments,
+    ui_core::arguments::{NaturalNumberArgument, ParsedArguments},
 };
 
 // TODO: min
 // TODO: max
 // TODO: prev
-// TODO: random (LFSR?)
 // TODO: flip flop
 
 // TODO: consider renaming to LinearSmooth
@@ -425,3 +424,247 @@ impl UnstashableInplace for WrappingIntegrator {
 impl WithObjectType for WrappingIntegrator {
     const TYPE: ObjectType = ObjectType::new("wrappingintegrator");
 }
+
+/// Outputs a single-sample pulse of 1 whenever its input rises above
+/// the threshold, and 0 otherwise. The input is considered to have
+/// previously been below the threshold when starting over, such that
+/// an input which starts out above the threshold also triggers a pulse.
+pub struct Trigger {
+    input: ExpressionInput,
+    threshold: ExpressionInput,
+}
+
+impl ExpressionNode for Trigger {
+    fn new(_args: &ParsedArguments) -> Trigger {
+        Trigger {
+            input: ExpressionInput::new(0.0),
+            threshold: ExpressionInput::new(0.5),
+        }
+    }
+
+    const NUM_VARIABLES: usize = 1;
+
+    type CompileState<'ctx> = ();
+
+    fn compile_start_over<'ctx>(&self, jit: &mut Jit<'ctx>) -> Vec<FloatValue<'ctx>> {
+        vec![jit.types.f32_type.const_float(f64::NEG_INFINITY)]
+    }
+
+    fn compile_pre_loop<'ctx>(&self, _jit: &mut Jit<'ctx>) {}
+
+    fn compile_post_loop<'ctx>(&self, _jit: &mut Jit<'ctx>, _compile_state: &()) {}
+
+    fn compile_loop<'ctx>(
+        &self,
+        jit: &mut Jit<'ctx>,
+        inputs: &[FloatValue<'ctx>],
+        variables: &[PointerValue<'ctx>],
+        _compile_state: &(),
+    ) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 2);
+        debug_assert_eq!(variables.len(), 1);
+        let input = inputs[0];
+        let threshold = inputs[1];
+        let variable = variables[0];
+
+        let prev_input = jit
+            .builder()
+            .build_load(jit.types.f32_type, variable, "prev_input")
+            .unwrap()
+            .into_float_value();
+        let was_below = jit
+            .builder()
+            .build_float_compare(FloatPredicate::OLE, prev_input, threshold, "was_below")
+            .unwrap();
+        let is_above = jit
+            .builder()
+            .build_float_compare(FloatPredicate::OGT, input, threshold, "is_above")
+            .unwrap();
+        let rising = jit
+            .builder()
+            .build_and(was_below, is_above, "rising")
+            .unwrap();
+        let pulse = jit
+            .builder()
+            .build_select(
+                rising,
+                jit.types.f32_type.const_float(1.0),
+                jit.types.f32_type.const_float(0.0),
+                "pulse",
+            )
+            .unwrap()
+            .into_float_value();
+        jit.builder().build_store(variable, input).unwrap();
+        pulse
+    }
+
+    fn visit(&self, visitor: &mut dyn ExpressionNodeVisitor) {
+        visitor.input(&self.input);
+        visitor.input(&self.threshold);
+    }
+    fn visit_mut(&mut self, visitor: &mut dyn ExpressionNodeVisitorMut) {
+        visitor.input(&mut self.input);
+        visitor.input(&mut self.threshold);
+    }
+}
+
+impl Stashable<StashingContext> for Trigger {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.threshold);
+    }
+}
+
+impl UnstashableInplace for Trigger {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.threshold)?;
+        Ok(())
+    }
+}
+
+impl WithObjectType for Trigger {
+    const TYPE: ObjectType = ObjectType::new("trigger");
+}
+
+/// Produces uniformly distributed noise in [-1, 1] using a 32-bit
+/// xorshift generator. The generator's state is kept in the node's
+/// state variable and is reset to a value derived from the seed when
+/// starting over, so that every instance with the same seed produces
+/// the same sequence.
+pub struct Random {
+    seed: u64,
+}
+
+impl Random {
+    pub const ARG_SEED: NaturalNumberArgument = NaturalNumberArgument("seed");
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// The generator's initial state for the given seed. Xorshift
+    /// generators get stuck at zero, so zero is never returned.
+    fn initial_state(seed: u64) -> u32 {
+        // Mix the seed's bits so that nearby seeds start far apart
+        let mixed = seed
+            .wrapping_add(0x9E3779B97F4A7C15)
+            .wrapping_mul(0xBF58476D1CE4E5B9);
+        let state = ((mixed >> 32) ^ mixed) as u32;
+        if state == 0 {
+            1
+        } else {
+            state
+        }
+    }
+}
+
+impl ExpressionNode for Random {
+    fn new(args: &ParsedArguments) -> Random {
+        Random {
+            seed: args.get(&Random::ARG_SEED).unwrap_or(0) as u64,
+        }
+    }
+
+    const NUM_VARIABLES: usize = 1;
+
+    type CompileState<'ctx> = ();
+
+    fn compile_start_over<'ctx>(&self, jit: &mut Jit<'ctx>) -> Vec<FloatValue<'ctx>> {
+        // The generator's integer state is stored bit-for-bit in the
+        // floating point state variable
+        let state = jit
+            .types
+            .u32_type
+            .const_int(Random::initial_state(self.seed) as u64, false);
+        let state_as_float = jit
+            .builder()
+            .build_bit_cast(state, jit.types.f32_type, "state_as_float")
+            .unwrap()
+            .into_float_value();
+        vec![state_as_float]
+    }
+
+    fn compile_pre_loop<'ctx>(&self, _jit: &mut Jit<'ctx>) {}
+
+    fn compile_post_loop<'ctx>(&self, _jit: &mut Jit<'ctx>, _compile_state: &()) {}
+
+    fn compile_loop<'ctx>(
+        &self,
+        jit: &mut Jit<'ctx>,
+        inputs: &[FloatValue<'ctx>],
+        variables: &[PointerValue<'ctx>],
+        _compile_state: &(),
+    ) -> FloatValue<'ctx> {
+        debug_assert_eq!(inputs.len(), 0);
+        debug_assert_eq!(variables.len(), 1);
+        let variable = variables[0];
+        let u32_type = jit.types.u32_type;
+
+        let mut state = jit
+            .builder()
+            .build_load(u32_type, variable, "prev_state")
+            .unwrap()
+            .into_int_value();
+
+        // x ^= x << 13; x ^= x >> 17; x ^= x << 5;
+        for (shift_left, amount) in [(true, 13), (false, 17), (true, 5)] {
+            let amount = u32_type.const_int(amount, false);
+            let shifted = if shift_left {
+                jit.builder().build_left_shift(state, amount, "shifted")
+            } else {
+                jit.builder()
+                    .build_right_shift(state, amount, false, "shifted")
+            }
+            .unwrap();
+            state = jit.builder().build_xor(state, shifted, "state").unwrap();
+        }
+        jit.builder().build_store(variable, state).unwrap();
+
+        // Use the upper 24 bits, which a float can represent exactly,
+        // to produce a value in [0, 1) which is then mapped to [-1, 1)
+        let upper_bits = jit
+            .builder()
+            .build_right_shift(state, u32_type.const_int(8, false), false, "upper_bits")
+            .unwrap();
+        let upper_bits_float = jit
+            .builder()
+            .build_unsigned_int_to_float(upper_bits, jit.types.f32_type, "upper_bits_float")
+            .unwrap();
+        let unit = jit
+            .builder()
+            .build_float_mul(
+                upper_bits_float,
+                jit.types.f32_type.const_float(2.0 / (1 << 24) as f64),
+                "unit",
+            )
+            .unwrap();
+        jit.builder()
+            .build_float_sub(unit, jit.types.f32_type.const_float(1.0), "random")
+            .unwrap()
+    }
+
+    fn visit(&self, _visitor: &mut dyn ExpressionNodeVisitor) {}
+    fn visit_mut(&mut self, _visitor: &mut dyn ExpressionNodeVisitorMut) {}
+}
+
+impl Stashable<StashingContext> for Random {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.u64(self.seed);
+    }
+}
+
+impl UnstashableInplace for Random {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.u64_inplace(&mut self.seed)?;
+        Ok(())
+    }
+}
+
+impl WithObjectType for Random {
+    const TYPE: ObjectType = ObjectType::new("random");
+}
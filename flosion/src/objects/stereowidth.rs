@@ -0,0 +1,95 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{SoundProcessor, StreamStatus},
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Scale the side (difference) component of the chunk by the given
+/// width while leaving the mid (sum) component intact. A width of 0
+/// collapses the chunk to mono, a width of 1 leaves it unchanged,
+/// and widths above 1 exaggerate the stereo image.
+pub(crate) fn apply_stereo_width(chunk: &mut SoundChunk, width: &[f32]) {
+    debug_assert_eq!(width.len(), CHUNK_SIZE);
+    for ((l, r), w) in chunk.l.iter_mut().zip(chunk.r.iter_mut()).zip(width) {
+        let mid = 0.5 * (*l + *r);
+        let side = 0.5 * (*l - *r) * w;
+        *l = mid + side;
+        *r = mid - side;
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct StereoWidth {
+    pub input: SingleInput,
+    pub width: ProcessorExpression,
+}
+
+impl SoundProcessor for StereoWidth {
+    fn new(_args: &ParsedArguments) -> StereoWidth {
+        StereoWidth {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            width: ProcessorExpression::new(&[1.0], ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        stereo_width: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = stereo_width.input.step(dst, InputContext::new(context));
+
+        let mut width = [0.0; CHUNK_SIZE];
+        stereo_width.width.eval(
+            &mut [&mut width],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        apply_stereo_width(dst, &width);
+
+        status
+    }
+}
+
+impl WithObjectType for StereoWidth {
+    const TYPE: ObjectType = ObjectType::new("stereowidth");
+}
+
+impl Stashable<StashingContext> for StereoWidth {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.width);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for StereoWidth {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.width)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+use parking_lot::Mutex;
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// The number of chunks held by a tap ring. Readers which fall
+/// further behind than this lose the oldest chunks.
+pub(crate) const TAP_RING_CHUNKS: usize = 64;
+
+/// Create a lock-free ring through which chunks of audio are copied
+/// from the audio thread to any number of readers on other threads,
+/// such as the GUI thread
+pub(crate) fn tap_ring() -> (TapWriter, TapReader) {
+    let (reader, writer) = spmcq::ring_buffer(TAP_RING_CHUNKS);
+    (
+        TapWriter(Arc::new(Mutex::new(writer))),
+        TapReader {
+            reader,
+            dropouts: 0,
+        },
+    )
+}
+
+/// The audio thread's end of a tap ring. To share the same ring, simply
+/// clone it.
+// NOTE: using Arc<Mutex<...>> because spmcq::Writer can't be cloned.
+// It might be worth using a different queue or somehow guaranteeing
+// at the type system level that only once instance of a static processor's
+// state exists at one time
+pub(crate) struct TapWriter(Arc<Mutex<spmcq::Writer<SoundChunk>>>);
+
+impl TapWriter {
+    /// Copy the chunk into the ring. This never blocks, and in the
+    /// unlikely event that another writer is busy, the chunk is skipped.
+    pub(crate) fn write(&self, chunk: &SoundChunk) {
+        if let Some(mut writer) = self.0.try_lock() {
+            writer.write(*chunk);
+        }
+    }
+}
+
+impl Clone for TapWriter {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// A reader of the chunks copied into a tap ring. Each clone reads
+/// every chunk independently of the others.
+#[derive(Clone)]
+pub struct TapReader {
+    reader: spmcq::Reader<SoundChunk>,
+    dropouts: usize,
+}
+
+impl TapReader {
+    /// The oldest chunk which hasn't been read yet, if any
+    pub fn read(&mut self) -> Option<SoundChunk> {
+        let result = self.reader.read();
+        if result.is_dropout() {
+            self.dropouts += 1;
+        }
+        result.value()
+    }
+
+    /// The number of times that this reader fell too far behind
+    /// and some chunks were lost
+    pub fn dropouts(&self) -> usize {
+        self.dropouts
+    }
+}
+
+/// Passes its input through unchanged, while also copying it into a
+/// ring from which any number of widgets, such as meters and scopes,
+/// can read it on the GUI thread
+#[derive(ProcessorComponent)]
+pub struct Tap {
+    pub input: SingleInput,
+
+    #[not_a_component]
+    reader: TapReader,
+
+    #[not_a_component]
+    writer: TapWriter,
+
+    #[state]
+    state: StateMarker<TapState>,
+}
+
+impl Tap {
+    /// A new reader of the audio passing through the tap
+    pub fn reader(&self) -> TapReader {
+        self.reader.clone()
+    }
+}
+
+pub struct TapState {
+    writer: TapWriter,
+}
+
+impl ProcessorState for TapState {
+    type Processor = Tap;
+
+    fn new(processor: &Self::Processor) -> Self {
+        TapState {
+            writer: processor.writer.clone(),
+        }
+    }
+}
+
+impl StartOver for TapState {
+    fn start_over(&mut self) {}
+}
+
+impl SoundProcessor for Tap {
+    fn new(_args: &ParsedArguments) -> Tap {
+        let (writer, reader) = tap_ring();
+        Tap {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            reader,
+            writer,
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn process_audio(
+        tap: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        tap.input.step(dst, InputContext::new(context));
+        tap.state.writer.write(dst);
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for Tap {
+    const TYPE: ObjectType = ObjectType::new("tap");
+}
+
+impl Stashable<StashingContext> for Tap {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for Tap {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        Ok(())
+    }
+}
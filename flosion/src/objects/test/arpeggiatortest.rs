@@ -0,0 +1,144 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        expression::expressiongraph::ExpressionTarget,
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ProcessorArgumentLocation,
+            context::{AudioContext, AudioStack},
+            expression::ExpressionParameterTarget,
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::{
+        arpeggiator::{Arpeggiator, ArpeggiatorPattern},
+        keyboard::KeyId,
+        wavegenerator::{WaveGenerator, WaveGeneratorWaveform},
+    },
+};
+
+/// Steps per second
+const RATE: f32 = 20.0;
+
+/// Samples per step
+const STEP_LENGTH: usize = SAMPLE_FREQUENCY / (RATE as usize);
+
+/// Connect an arpeggiator to a wave generator whose amplitude is the
+/// arpeggiator's note frequency, hold the given notes, and render the
+/// given number of steps. Returns the left channel of the output.
+fn render_arpeggiated_frequency(
+    pattern: ArpeggiatorPattern,
+    notes: &[f32],
+    num_steps: usize,
+) -> Vec<f32> {
+    let mut arpeggiator = SoundProcessorWithId::<Arpeggiator>::new_default();
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    wavegen.set_waveform(WaveGeneratorWaveform::Expression);
+
+    arpeggiator.set_pattern(pattern);
+    arpeggiator.rate.graph_mut().results_mut()[0].set_default_value(RATE);
+
+    let arpeggiator_id = arpeggiator.id();
+    let wavegen_id = wavegen.id();
+
+    let argument_location =
+        ProcessorArgumentLocation::new(arpeggiator_id, arpeggiator.note_frequency.id());
+    let param_id = wavegen
+        .amplitude
+        .add_target(ExpressionParameterTarget::Argument(argument_location));
+    let graph = wavegen.amplitude.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(param_id),
+        )
+        .unwrap();
+
+    let input_location = SoundInputLocation::new(arpeggiator_id, arpeggiator.input.id());
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(arpeggiator));
+    graph.add_sound_processor(Box::new(wavegen));
+    graph
+        .connect_sound_input(input_location, wavegen_id)
+        .unwrap();
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let arpeggiator = graph
+        .sound_processor(arpeggiator_id)
+        .unwrap()
+        .downcast::<Arpeggiator>()
+        .unwrap();
+
+    let mut compiled = arpeggiator.compile(arpeggiator_id, &mut compiler);
+
+    for (i, f) in notes.iter().enumerate() {
+        arpeggiator.start_key(KeyId(i), *f);
+    }
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut output = Vec::new();
+
+    while output.len() < num_steps * STEP_LENGTH {
+        let mut context = AudioContext::new(
+            arpeggiator_id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        Arpeggiator::process_audio(&mut compiled, &mut chunk, &mut context);
+        processor_timing.advance_one_chunk();
+
+        output.extend_from_slice(&chunk.l);
+    }
+
+    output.truncate(num_steps * STEP_LENGTH);
+    output
+}
+
+/// Assert that each step of the output plays the expected note,
+/// allowing for a sample of rounding error at each step boundary
+fn assert_steps(output: &[f32], expected: &[f32]) {
+    for (i, step) in output.chunks(STEP_LENGTH).enumerate() {
+        for x in &step[1..(STEP_LENGTH - 1)] {
+            assert_eq!(*x, expected[i], "Wrong note at step {}", i);
+        }
+    }
+}
+
+#[test]
+fn test_up_cycles_notes_in_ascending_order() {
+    // Held out of order to check that they are sorted
+    let output = render_arpeggiated_frequency(ArpeggiatorPattern::Up, &[330.0, 220.0, 440.0], 7);
+    assert_steps(&output, &[220.0, 330.0, 440.0, 220.0, 330.0, 440.0, 220.0]);
+}
+
+#[test]
+fn test_down_cycles_notes_in_descending_order() {
+    let output = render_arpeggiated_frequency(ArpeggiatorPattern::Down, &[330.0, 220.0, 440.0], 4);
+    assert_steps(&output, &[440.0, 330.0, 220.0, 440.0]);
+}
+
+#[test]
+fn test_updown_doesnt_repeat_ends() {
+    let output =
+        render_arpeggiated_frequency(ArpeggiatorPattern::UpDown, &[330.0, 220.0, 440.0], 6);
+    assert_steps(&output, &[220.0, 330.0, 440.0, 330.0, 220.0, 330.0]);
+}
@@ -0,0 +1,129 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorId,
+            },
+        },
+        soundbuffer::SoundBuffer,
+        soundchunk::SoundChunk,
+    },
+    objects::audioclip::AudioClip,
+    ui_core::arguments::ParsedArguments,
+};
+
+const CLIP_LENGTH: usize = 3000;
+
+/// Loop a rising ramp, which jumps from one back to zero at its end,
+/// with the given crossfade and return the left channel of the output
+fn render_looped_ramp(crossfade: usize) -> Vec<f32> {
+    render_ramp(|audioclip| audioclip.set_loop_crossfade(crossfade))
+}
+
+/// Play a rising ramp from zero to just under one through an AudioClip
+/// configured by the given function, and return the left channel of
+/// the output
+fn render_ramp<F: FnOnce(&AudioClip)>(configure: F) -> Vec<f32> {
+    let audioclip = AudioClip::new(&ParsedArguments::new_empty());
+    let mut buffer = SoundBuffer::new_empty();
+    for i in 0..CLIP_LENGTH {
+        let x = i as f32 / CLIP_LENGTH as f32;
+        buffer.push_sample(x, x);
+    }
+    audioclip.set_data(buffer);
+    configure(&audioclip);
+
+    let inkwell_context = inkwell::context::Context::create();
+    let jit_cache = JitCache::new(&inkwell_context);
+    let graph = SoundGraph::new();
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let id = SoundProcessorId::new_unique();
+    let mut compiled_audioclip = audioclip.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let processor_timing = ProcessorTiming::new();
+
+    let mut output = Vec::new();
+    // Enough to wrap around the loop several times
+    for _ in 0..10 {
+        let mut context = AudioContext::new(
+            id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        AudioClip::process_audio(&mut compiled_audioclip, &mut chunk, &mut context);
+        output.extend_from_slice(&chunk.l);
+    }
+    output
+}
+
+fn largest_jump(samples: &[f32]) -> f32 {
+    samples
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_loop_without_crossfade_jumps() {
+    let output = render_looped_ramp(0);
+    assert!(largest_jump(&output) > 0.99);
+}
+
+#[test]
+fn test_loop_with_crossfade_is_continuous() {
+    let crossfade = 500;
+    let output = render_looped_ramp(crossfade);
+
+    // The ramp's own slope is 1/3000 per sample, and the crossfade
+    // from the top of the ramp back to its start spreads the drop over
+    // the crossfade length
+    let ramp_step = 1.0 / CLIP_LENGTH as f32;
+    let fade_step = 1.0 / crossfade as f32;
+    assert!(largest_jump(&output) < 1.5 * (fade_step + ramp_step));
+
+    // The clip is still heard in full on the first pass
+    assert_eq!(output[0], 0.0);
+    let n = CLIP_LENGTH - crossfade - 1;
+    assert!((output[n] - n as f32 / CLIP_LENGTH as f32).abs() < 1e-6);
+}
+
+#[test]
+fn test_reverse_reads_back_to_front() {
+    let output = render_ramp(|audioclip| audioclip.set_reverse(true));
+
+    for (i, x) in output.iter().take(CLIP_LENGTH).enumerate() {
+        let expected = (CLIP_LENGTH - 1 - i) as f32 / CLIP_LENGTH as f32;
+        assert!((x - expected).abs() < 1e-6, "sample {} was {}", i, x);
+    }
+
+    // After reaching the start, the clip loops from the end again
+    let wrapped = output[CLIP_LENGTH];
+    assert!((wrapped - (CLIP_LENGTH - 1) as f32 / CLIP_LENGTH as f32).abs() < 1e-6);
+}
+
+#[test]
+fn test_half_speed_doubles_duration() {
+    let output = render_ramp(|audioclip| audioclip.set_speed(0.5));
+
+    // Where the clip would have restarted at normal speed, it's only
+    // halfway through, and it restarts at twice its length instead
+    assert!((output[CLIP_LENGTH] - 0.5).abs() < 1e-6);
+    assert_eq!(output[2 * CLIP_LENGTH], 0.0);
+
+    // Every other sample is interpolated halfway between two of the clip's
+    for i in 0..(CLIP_LENGTH - 1) {
+        let expected = i as f32 / CLIP_LENGTH as f32;
+        assert!((output[2 * i] - expected).abs() < 1e-6);
+        assert!((output[2 * i + 1] - (expected + 0.5 / CLIP_LENGTH as f32)).abs() < 1e-6);
+    }
+}
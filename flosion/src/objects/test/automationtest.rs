@@ -0,0 +1,236 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stash, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        automation::{Breakpoint, Interpolation},
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        expression::{
+            context::ExpressionContext,
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::{argumentstack::ArgumentStack, cache::JitCache, compiledexpression::Discretization},
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::{AudioContext, AudioStack},
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+                StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+        tempo::Tempo,
+    },
+    objects::automation::Automation,
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A processor which outputs the value of its parameter, one per sample
+#[derive(ProcessorComponent)]
+struct ParameterProbe {
+    parameter: ProcessorExpression,
+}
+
+impl SoundProcessor for ParameterProbe {
+    fn new(_args: &ParsedArguments) -> ParameterProbe {
+        ParameterProbe {
+            parameter: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        probe: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        probe.parameter.eval(
+            &mut [&mut dst.l],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for ParameterProbe {
+    const TYPE: ObjectType = ObjectType::new("parameterprobe");
+}
+
+impl Stashable<StashingContext> for ParameterProbe {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.parameter);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for ParameterProbe {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.parameter)?;
+        Ok(())
+    }
+}
+
+/// Drive the probe's parameter with the given automation node, played
+/// back over the probe's own time, and render its output
+fn render_automation(automation: ExpressionNodeWithId<Automation>, len: usize) -> Vec<f32> {
+    let mut probe = SoundProcessorWithId::<ParameterProbe>::new_default();
+    let id = probe.id();
+
+    let time_param = probe
+        .parameter
+        .add_target(ExpressionParameterTarget::ProcessorTime(id));
+    let graph = probe.parameter.graph_mut();
+    let automation_id = automation.id();
+    let automation_inputs = (&automation as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(automation));
+    graph
+        .connect_input(
+            automation_inputs[0],
+            Some(ExpressionTarget::Parameter(time_param)),
+        )
+        .unwrap();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Node(automation_id),
+        )
+        .unwrap();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(probe));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let probe = graph
+        .sound_processor(id)
+        .unwrap()
+        .downcast::<ParameterProbe>()
+        .unwrap();
+
+    let mut compiled = probe.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut output = Vec::new();
+
+    while output.len() < len {
+        let mut context = AudioContext::new(
+            id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        ParameterProbe::process_audio(&mut compiled, &mut chunk, &mut context);
+        processor_timing.advance_one_chunk();
+
+        output.extend_from_slice(&chunk.l);
+    }
+
+    output.truncate(len);
+    output
+}
+
+#[test]
+fn test_ramp_over_one_bar_drives_parameter_to_half_at_midpoint() {
+    let tempo = Tempo::new(120.0, 4);
+
+    let mut automation = ExpressionNodeWithId::<Automation>::new_default();
+    automation.set_tempo(tempo);
+    let lane = automation.lane_mut();
+    lane.add_breakpoint(Breakpoint::new(0.0, 0.0, Interpolation::Linear));
+    lane.add_breakpoint(Breakpoint::new(1.0, 1.0, Interpolation::Linear));
+
+    // At 120 beats per minute, a bar of four beats lasts two seconds
+    let samples_per_bar = tempo.samples_per_bar() as usize;
+    let values = render_automation(automation, samples_per_bar + 1);
+
+    assert_eq!(values[0], 0.0);
+    assert!(
+        (values[samples_per_bar / 2] - 0.5).abs() < 1e-4,
+        "Expected 0.5 at the midpoint, got {}",
+        values[samples_per_bar / 2]
+    );
+    assert!((values[samples_per_bar] - 1.0).abs() < 1e-4);
+
+    // The parameter changes with every sample, not just every chunk
+    for i in 1..=samples_per_bar {
+        assert!(values[i] > values[i - 1]);
+    }
+}
+
+#[test]
+fn test_compiled_automation_matches_lane() {
+    let tempo = Tempo::new(150.0, 3);
+
+    let mut automation = ExpressionNodeWithId::<Automation>::new_default();
+    automation.set_tempo(tempo);
+    let lane = automation.lane_mut();
+    lane.add_breakpoint(Breakpoint::new(0.25, -1.0, Interpolation::Step));
+    lane.add_breakpoint(Breakpoint::new(0.5, 2.0, Interpolation::Smooth));
+    lane.add_breakpoint(Breakpoint::new(1.0, 0.5, Interpolation::Linear));
+    lane.add_breakpoint(Breakpoint::new(1.5, 1.5, Interpolation::Linear));
+
+    let expected: Vec<f32> = {
+        let seconds_per_sample = 1.0 / tempo.samples_per_bar() / tempo.bars_per_second();
+        (0..(2 * tempo.samples_per_bar() as usize))
+            .map(|i| automation.value_at_time((i as f64 * seconds_per_sample) as f32))
+            .collect()
+    };
+
+    let values = render_automation(automation, expected.len());
+
+    for (i, (v, e)) in values.iter().zip(&expected).enumerate() {
+        assert!(
+            (v - e).abs() < 1e-3,
+            "Sample {} was {} but should be {}",
+            i,
+            v,
+            e
+        );
+    }
+}
+
+#[test]
+fn test_empty_automation_is_zero() {
+    let values = render_automation(ExpressionNodeWithId::<Automation>::new_default(), 100);
+    assert!(values.iter().all(|v| *v == 0.0));
+}
+
+#[test]
+fn test_automation_stash_roundtrip() {
+    let mut automation = ExpressionNodeWithId::<Automation>::new_default();
+    automation.set_tempo(Tempo::new(90.0, 7));
+    let lane = automation.lane_mut();
+    lane.add_breakpoint(Breakpoint::new(0.0, 3.0, Interpolation::Smooth));
+    lane.add_breakpoint(Breakpoint::new(2.0, -1.0, Interpolation::Step));
+
+    let stash = Stash::new();
+    let handle = stash.stash_with_context(&*automation, StashingContext::new_stashing_normally());
+    let mut unstashed = ExpressionNodeWithId::<Automation>::new_default();
+    stash.unstash_inplace(&handle, &mut *unstashed).unwrap();
+
+    assert_eq!(unstashed.tempo(), automation.tempo());
+    assert_eq!(
+        unstashed.lane().breakpoints(),
+        automation.lane().breakpoints()
+    );
+}
@@ -0,0 +1,99 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundbuffer::SoundBuffer,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::binaural::{
+        interaural_time_difference, BinauralRenderer, HrtfTable, HRIR_LENGTH, MAX_LEVEL_DIFFERENCE,
+    },
+};
+
+/// Render a unit impulse at the given azimuth, returning the first
+/// chunk of each ear's response
+fn impulse_response(azimuth: f32, hrtf: Option<&HrtfTable>) -> SoundChunk {
+    let mut renderer = BinauralRenderer::new();
+    let mut chunk = SoundChunk::new();
+    chunk.l[0] = 1.0;
+    chunk.r[0] = 1.0;
+    renderer.process(&mut chunk, &[azimuth; CHUNK_SIZE], hrtf);
+    chunk
+}
+
+/// The centre of mass in samples and the total of a response
+fn centroid_and_sum(response: &[f32]) -> (f32, f32) {
+    let sum: f32 = response.iter().sum();
+    let moment: f32 = response.iter().enumerate().map(|(i, x)| i as f32 * x).sum();
+    (moment / sum, sum)
+}
+
+#[test]
+fn test_hard_left_delays_and_attenuates_right_ear() {
+    let chunk = impulse_response(-90.0, None);
+
+    // A head of radius 8.75 cm delays the far ear by about 0.66 ms
+    let expected_delay = 0.0875 / 343.0 * (std::f32::consts::FRAC_PI_2 + 1.0);
+    assert!((interaural_time_difference(-90.0) + expected_delay).abs() < 1e-7);
+    let expected_delay_samples = expected_delay * SAMPLE_FREQUENCY as f32;
+
+    let (left_delay, left_sum) = centroid_and_sum(&chunk.l);
+    let (right_delay, right_sum) = centroid_and_sum(&chunk.r);
+    assert!(
+        left_delay.abs() < 1e-6,
+        "left ear was delayed by {}",
+        left_delay
+    );
+    assert!(
+        (right_delay - expected_delay_samples).abs() < 1e-3,
+        "right ear was delayed by {} samples, expected {}",
+        right_delay,
+        expected_delay_samples
+    );
+
+    let level_difference = 20.0 * (left_sum / right_sum).log10();
+    assert!(
+        (level_difference - MAX_LEVEL_DIFFERENCE).abs() < 1e-3,
+        "level difference was {} dB",
+        level_difference
+    );
+}
+
+#[test]
+fn test_straight_ahead_is_centred() {
+    for azimuth in [0.0, 180.0] {
+        let chunk = impulse_response(azimuth, None);
+        for (l, r) in chunk.samples() {
+            assert!((l - r).abs() < 1e-4);
+        }
+        assert!((chunk.l[0] - 1.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_hrtf_table_interpolates_between_directions() {
+    // Four directions, whose responses are impulses delayed by the
+    // direction's index in the left ear and its double in the right
+    let num_responses = 4;
+    let mut buffer = SoundBuffer::new_empty();
+    for direction in 0..num_responses {
+        for i in 0..HRIR_LENGTH {
+            let l = if i == direction { 1.0 } else { 0.0 };
+            let r = if i == 2 * direction { 1.0 } else { 0.0 };
+            buffer.push_sample(l, r);
+        }
+    }
+    let hrtf = HrtfTable::from_audio(&buffer).unwrap();
+    assert_eq!(hrtf.num_responses(), num_responses);
+
+    // Straight to the right is the second direction
+    let chunk = impulse_response(90.0, Some(&hrtf));
+    assert!((chunk.l[1] - 1.0).abs() < 1e-6);
+    assert!((chunk.r[2] - 1.0).abs() < 1e-6);
+
+    // Halfway between the second and third directions
+    let chunk = impulse_response(135.0, Some(&hrtf));
+    assert!((chunk.l[1] - 0.5).abs() < 1e-6);
+    assert!((chunk.l[2] - 0.5).abs() < 1e-6);
+    assert!((chunk.r[2] - 0.5).abs() < 1e-6);
+    assert!((chunk.r[4] - 0.5).abs() < 1e-6);
+}
@@ -0,0 +1,57 @@
+use crate::{
+    core::soundchunk::{SoundChunk, CHUNK_SIZE},
+    objects::{
+        downmix::{downmix, DOWNMIX_GAIN},
+        widen::widen,
+    },
+};
+
+fn ramp(scale: f32) -> [f32; CHUNK_SIZE] {
+    std::array::from_fn(|i| scale * (i as f32) / (CHUNK_SIZE as f32))
+}
+
+#[test]
+fn test_downmix_equal_channels() {
+    let mut chunk = SoundChunk::new();
+    for i in 0..SoundChunk::NUM_CHANNELS {
+        *chunk.channel_mut(i) = ramp(0.5);
+    }
+
+    downmix(&mut chunk);
+
+    let expected = ramp(0.5).map(|s| 2.0 * s * DOWNMIX_GAIN);
+    for i in 0..SoundChunk::NUM_CHANNELS {
+        for (actual, expected) in chunk.channel(i).iter().zip(&expected) {
+            assert!((actual - expected).abs() < 1e-6);
+        }
+    }
+
+    // Summing two equal channels and attenuating by 3 dB
+    // should leave the signal about 3 dB louder
+    let last = chunk.l[CHUNK_SIZE - 1] / ramp(0.5)[CHUNK_SIZE - 1];
+    assert!((20.0 * last.log10() - 3.0).abs() < 0.1);
+}
+
+#[test]
+fn test_downmix_opposite_channels_cancel() {
+    let mut chunk = SoundChunk::new();
+    chunk.l = ramp(1.0);
+    chunk.r = ramp(-1.0);
+
+    downmix(&mut chunk);
+
+    assert!(chunk.samples().all(|(l, r)| l == 0.0 && r == 0.0));
+}
+
+#[test]
+fn test_widen_duplicates_first_channel() {
+    let mut chunk = SoundChunk::new();
+    chunk.l = ramp(1.0);
+    chunk.r = ramp(-0.25);
+
+    widen(&mut chunk);
+
+    for i in 0..SoundChunk::NUM_CHANNELS {
+        assert_eq!(*chunk.channel(i), ramp(1.0));
+    }
+}
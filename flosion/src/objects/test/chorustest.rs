@@ -0,0 +1,117 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::chorus::{ChorusParameters, ModulatedDelay},
+};
+
+/// An arbitrary but deterministic input signal
+fn input_sample(index: usize) -> (f32, f32) {
+    let t = index as f32;
+    ((t * 0.37).sin(), (t * 0.11).cos() * 0.5)
+}
+
+#[test]
+fn test_static_delay_is_dry_wet_mix() {
+    let delay_samples = 100;
+    let delay = delay_samples as f32 / SAMPLE_FREQUENCY as f32;
+    let mix = 0.25;
+
+    let mut modulated_delay = ModulatedDelay::new();
+
+    for chunk_index in 0..8 {
+        let mut chunk = SoundChunk::new();
+        for (i, (l, r)) in chunk.samples_mut().enumerate() {
+            (*l, *r) = input_sample(chunk_index * CHUNK_SIZE + i);
+        }
+
+        modulated_delay.process(
+            &mut chunk,
+            ChorusParameters {
+                delay: &[delay; CHUNK_SIZE],
+                rate: &[3.0; CHUNK_SIZE],
+                depth: &[0.0; CHUNK_SIZE],
+                feedback: &[0.0; CHUNK_SIZE],
+                mix: &[mix; CHUNK_SIZE],
+            },
+        );
+
+        for (i, (l, r)) in chunk.samples().enumerate() {
+            let index = chunk_index * CHUNK_SIZE + i;
+            let (dry_l, dry_r) = input_sample(index);
+            let (wet_l, wet_r) = if index >= delay_samples {
+                input_sample(index - delay_samples)
+            } else {
+                (0.0, 0.0)
+            };
+            let expected_l = (1.0 - mix) * dry_l + mix * wet_l;
+            let expected_r = (1.0 - mix) * dry_r + mix * wet_r;
+            assert!((l - expected_l).abs() < 1e-4, "{} != {}", l, expected_l);
+            assert!((r - expected_r).abs() < 1e-4, "{} != {}", r, expected_r);
+        }
+    }
+}
+
+#[test]
+fn test_reset_clears_delay_line() {
+    let mut modulated_delay = ModulatedDelay::new();
+    let parameters = || ChorusParameters {
+        delay: &[0.001; CHUNK_SIZE],
+        rate: &[1.0; CHUNK_SIZE],
+        depth: &[0.0005; CHUNK_SIZE],
+        feedback: &[0.5; CHUNK_SIZE],
+        mix: &[1.0; CHUNK_SIZE],
+    };
+
+    let mut chunk = SoundChunk {
+        l: [1.0; CHUNK_SIZE],
+        r: [1.0; CHUNK_SIZE],
+    };
+    modulated_delay.process(&mut chunk, parameters());
+
+    modulated_delay.reset();
+
+    let mut silence = SoundChunk::new();
+    modulated_delay.process(&mut silence, parameters());
+    assert!(silence.samples().all(|(l, r)| l == 0.0 && r == 0.0));
+}
+
+/// Play an impulse into a delay with lots of feedback, followed by
+/// several seconds of silence while the echoes decay, and return
+/// whether any output sample was denormal
+fn decaying_echoes_are_denormal(anti_denormal: bool) -> bool {
+    let mut modulated_delay = ModulatedDelay::new();
+    modulated_delay.set_anti_denormal(anti_denormal);
+
+    let mut any_denormal = false;
+    for chunk_index in 0..(4 * SAMPLE_FREQUENCY / CHUNK_SIZE) {
+        let mut chunk = SoundChunk::new();
+        if chunk_index == 0 {
+            chunk.l[0] = 1.0;
+            chunk.r[0] = -1.0;
+        }
+        modulated_delay.process(
+            &mut chunk,
+            ChorusParameters {
+                delay: &[0.001; CHUNK_SIZE],
+                rate: &[0.0; CHUNK_SIZE],
+                depth: &[0.0; CHUNK_SIZE],
+                feedback: &[0.9; CHUNK_SIZE],
+                mix: &[1.0; CHUNK_SIZE],
+            },
+        );
+        any_denormal |= chunk
+            .samples()
+            .any(|(l, r)| l.is_subnormal() || r.is_subnormal());
+    }
+    any_denormal
+}
+
+#[test]
+fn test_anti_denormal_keeps_feedback_normal() {
+    // Without the noise, the echoes decay all the way into the denormal range
+    assert!(decaying_echoes_are_denormal(false));
+
+    assert!(!decaying_echoes_are_denormal(true));
+}
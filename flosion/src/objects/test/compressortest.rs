@@ -0,0 +1,69 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::compressor::{CompressorParameters, EnvelopeFollower},
+};
+
+const PARAMETERS: CompressorParameters = CompressorParameters {
+    threshold: -20.0,
+    ratio: 10.0,
+    attack: 0.001,
+    release: 0.05,
+};
+
+fn constant_chunk(value: f32) -> SoundChunk {
+    SoundChunk {
+        l: [value; CHUNK_SIZE],
+        r: [value; CHUNK_SIZE],
+    }
+}
+
+/// Compress a constant quiet main signal, keyed by a sidechain which is
+/// loud for the first half second and silent for the second half second.
+/// Returns the gain applied to the main signal at the end of each chunk.
+fn duck_with_sidechain() -> Vec<f32> {
+    let quiet = 0.01;
+    let num_chunks = SAMPLE_FREQUENCY / CHUNK_SIZE;
+    let mut follower = EnvelopeFollower::new();
+
+    (0..num_chunks)
+        .map(|i| {
+            let key = constant_chunk(if i < num_chunks / 2 { 1.0 } else { 0.0 });
+            let mut main = constant_chunk(quiet);
+            follower.process(&mut main, &key, PARAMETERS);
+            main.l[CHUNK_SIZE - 1] / quiet
+        })
+        .collect()
+}
+
+#[test]
+fn test_quiet_signal_is_not_compressed_by_itself() {
+    let mut follower = EnvelopeFollower::new();
+    for _ in 0..16 {
+        let mut main = constant_chunk(0.01);
+        let key = main;
+        follower.process(&mut main, &key, PARAMETERS);
+        assert!(main.samples().all(|(l, r)| l == 0.01 && r == 0.01));
+    }
+}
+
+#[test]
+fn test_sidechain_ducks_main_signal() {
+    let gains = duck_with_sidechain();
+    let half = gains.len() / 2;
+
+    // While the sidechain is loud, the quiet signal is pushed down by
+    // 20 dB of overshoot at a 10:1 ratio, i.e. 18 dB of gain reduction
+    let expected = 10.0_f32.powf(-18.0 / 20.0);
+    for gain in &gains[2..half] {
+        assert!((gain - expected).abs() < 0.01, "Gain was {}", gain);
+    }
+
+    // Once the sidechain goes quiet, the gain recovers
+    assert!(gains[half] > expected);
+    for gain in &gains[(half + 8)..] {
+        assert!((gain - 1.0).abs() < 1e-3, "Gain was {}", gain);
+    }
+}
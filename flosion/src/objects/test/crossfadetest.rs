@@ -0,0 +1,106 @@
+use crate::{
+    core::{
+        sound::{
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::SoundProcessorWithId,
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::{
+        crossfade::{crossfade, Crossfade},
+        whitenoise::WhiteNoise,
+    },
+    ui_core::{
+        soundobjectpositions::SoundObjectPositions, stackedlayout::stackedlayout::StackedLayout,
+    },
+};
+
+fn make_chunk(value_l: f32, value_r: f32) -> SoundChunk {
+    SoundChunk {
+        l: [value_l; CHUNK_SIZE],
+        r: [value_r; CHUNK_SIZE],
+    }
+}
+
+fn crossfade_with(mix: f32) -> SoundChunk {
+    let a = make_chunk(0.5, -0.25);
+    let b = make_chunk(-1.0, 0.75);
+    let mut dst = SoundChunk::new();
+    crossfade(&a, &b, &[mix; CHUNK_SIZE], &mut dst);
+    dst
+}
+
+fn assert_chunk_near(actual: &SoundChunk, expected_l: f32, expected_r: f32) {
+    for (l, r) in actual.samples() {
+        assert!((l - expected_l).abs() < 1e-6, "{} != {}", l, expected_l);
+        assert!((r - expected_r).abs() < 1e-6, "{} != {}", r, expected_r);
+    }
+}
+
+#[test]
+fn test_crossfade_mix_zero_is_a() {
+    assert_chunk_near(&crossfade_with(0.0), 0.5, -0.25);
+}
+
+#[test]
+fn test_crossfade_mix_one_is_b() {
+    assert_chunk_near(&crossfade_with(1.0), -1.0, 0.75);
+}
+
+#[test]
+fn test_crossfade_mix_half_is_equal_power() {
+    let g = std::f32::consts::FRAC_1_SQRT_2;
+    assert_chunk_near(&crossfade_with(0.5), g * (0.5 + -1.0), g * (-0.25 + 0.75));
+
+    // Equal power: the gains' squares sum to one for any mix
+    for mix in [0.1, 0.3, 0.7, 0.9] {
+        let a = make_chunk(1.0, 0.0);
+        let b = make_chunk(0.0, 1.0);
+        let mut dst = SoundChunk::new();
+        crossfade(&a, &b, &[mix; CHUNK_SIZE], &mut dst);
+        let power = dst.l[0] * dst.l[0] + dst.r[0] * dst.r[0];
+        assert!((power - 1.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_crossfade_is_top_of_stacked_group() {
+    let mut graph = SoundGraph::new();
+
+    let source_a = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let source_b = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let crossfade = SoundProcessorWithId::<Crossfade>::new_default();
+
+    let source_a_id = source_a.id();
+    let source_b_id = source_b.id();
+    let crossfade_id = crossfade.id();
+    let input_a = SoundInputLocation::new(crossfade_id, crossfade.input_a.id());
+    let input_b = SoundInputLocation::new(crossfade_id, crossfade.input_b.id());
+
+    graph.add_sound_processor(Box::new(source_a));
+    graph.add_sound_processor(Box::new(source_b));
+    graph.add_sound_processor(Box::new(crossfade));
+
+    graph.connect_sound_input(input_a, source_a_id).unwrap();
+    graph.connect_sound_input(input_b, source_b_id).unwrap();
+
+    let mut layout = StackedLayout::new();
+    layout.regenerate(&graph, &SoundObjectPositions::new());
+
+    assert!(layout.check_invariants(&graph));
+
+    // With two connected inputs, the crossfade can't be stacked
+    // below either source and must start its own group
+    assert!(layout.is_top_of_group(crossfade_id));
+    assert!(layout.is_bottom_of_group(source_a_id));
+    assert!(layout.is_bottom_of_group(source_b_id));
+    assert_ne!(
+        layout
+            .find_group(source_a_id)
+            .map(|g| g.processors().to_vec()),
+        layout
+            .find_group(crossfade_id)
+            .map(|g| g.processors().to_vec())
+    );
+}
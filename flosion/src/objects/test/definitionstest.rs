@@ -0,0 +1,182 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        expression::{context::ExpressionContext, expressiongraph::ExpressionTarget},
+        jit::{argumentstack::ArgumentStack, cache::JitCache, compiledexpression::Discretization},
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::{ArgumentScope, ProcessorArgumentLocation},
+            context::{AudioContext, AudioStack},
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundinput::AnyProcessorInput,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+                StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    objects::definitions::Definitions,
+    ui_core::arguments::ParsedArguments,
+};
+
+/// A processor with two expressions, whose results are written
+/// to the left and right channels respectively
+#[derive(ProcessorComponent)]
+struct TestConsumer {
+    expression_left: ProcessorExpression,
+    expression_right: ProcessorExpression,
+}
+
+impl SoundProcessor for TestConsumer {
+    fn new(_args: &ParsedArguments) -> TestConsumer {
+        TestConsumer {
+            expression_left: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            expression_right: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        consumer: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        consumer.expression_left.eval(
+            &mut [&mut dst.l],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        consumer.expression_right.eval(
+            &mut [&mut dst.r],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for TestConsumer {
+    const TYPE: ObjectType = ObjectType::new("testconsumer");
+}
+
+impl Stashable<StashingContext> for TestConsumer {
+    fn stash(&self, _stasher: &mut Stasher<StashingContext>) {
+        panic!("Unused")
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for TestConsumer {
+    fn unstash_inplace(
+        &mut self,
+        _unstasher: &mut InplaceUnstasher<UnstashingContext<'a>>,
+    ) -> Result<(), UnstashError> {
+        panic!("unused")
+    }
+}
+
+/// Make the expression's only result refer directly to the given target
+fn connect_result_to(expression: &mut ProcessorExpression, target: ExpressionParameterTarget) {
+    let param_id = expression.add_target(target);
+    let graph = expression.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(param_id),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_definition_referenced_by_two_expressions() {
+    let mut definitions = SoundProcessorWithId::<Definitions>::new_default();
+    let mut consumer = SoundProcessorWithId::<TestConsumer>::new_default();
+
+    let definitions_id = definitions.id();
+    let consumer_id = consumer.id();
+
+    // Define the definition as the time elapsed at the definitions processor,
+    // which is different at every sample
+    connect_result_to(
+        &mut definitions.expression,
+        ExpressionParameterTarget::ProcessorTime(definitions_id),
+    );
+
+    // Refer to the definition from both of the consumer's expressions
+    let definition_location =
+        ProcessorArgumentLocation::new(definitions_id, definitions.argument.id());
+    connect_result_to(
+        &mut consumer.expression_left,
+        ExpressionParameterTarget::Argument(definition_location),
+    );
+    connect_result_to(
+        &mut consumer.expression_right,
+        ExpressionParameterTarget::Argument(definition_location),
+    );
+
+    definitions.sound_input.set_target(Some(consumer_id));
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(definitions));
+    graph.add_sound_processor(Box::new(consumer));
+
+    assert_eq!(graph.validate(), Ok(()));
+
+    //------------------------
+
+    let inkwell_context = inkwell::context::Context::create();
+
+    let mut jit_cache = JitCache::new(&inkwell_context);
+
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let definitions = graph
+        .sound_processor(definitions_id)
+        .unwrap()
+        .downcast::<Definitions>()
+        .unwrap();
+
+    let mut compiled_definitions = definitions.compile(definitions_id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    //------------------------
+
+    let mut previous_value: Option<f32> = None;
+
+    for _ in 0..4 {
+        let mut context = AudioContext::new(
+            definitions_id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+
+        let mut chunk = SoundChunk::new();
+
+        Definitions::process_audio(&mut compiled_definitions, &mut chunk, &mut context);
+
+        for (l, r) in chunk.l.iter().zip(chunk.r.iter()) {
+            assert_eq!(l, r);
+            if let Some(prev) = previous_value {
+                assert!(*l > prev);
+            }
+            previous_value = Some(*l);
+        }
+
+        processor_timing.advance_one_chunk();
+    }
+}
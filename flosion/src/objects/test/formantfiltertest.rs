@@ -0,0 +1,85 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::formantfilter::{formants_at, FormantBank, VOWEL_FORMANTS},
+};
+
+const IMPULSE_RESPONSE_CHUNKS: usize = 4;
+
+/// Feed a unit impulse through a formant bank and record the left channel
+fn impulse_response(vowel: f32) -> Vec<f32> {
+    let mut bank = FormantBank::new(vowel);
+    let mut response = Vec::new();
+    for i in 0..IMPULSE_RESPONSE_CHUNKS {
+        let mut chunk = SoundChunk::new();
+        if i == 0 {
+            chunk.l[0] = 1.0;
+            chunk.r[0] = 1.0;
+        }
+        bank.process(&mut chunk);
+        response.extend_from_slice(&chunk.l);
+    }
+    response
+}
+
+/// The magnitude of the signal's discrete-time Fourier transform at
+/// the given frequency in Hz
+fn magnitude_at(signal: &[f32], frequency: f32) -> f32 {
+    let w = std::f32::consts::TAU * frequency / SAMPLE_FREQUENCY as f32;
+    let (re, im) = signal
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (n, x)| {
+            let phase = w * n as f32;
+            (re + x * phase.cos(), im - x * phase.sin())
+        });
+    (re * re + im * im).sqrt()
+}
+
+#[test]
+fn test_vowel_a_peaks_at_formants() {
+    let response = impulse_response(0.0);
+    assert_eq!(response.len(), IMPULSE_RESPONSE_CHUNKS * CHUNK_SIZE);
+
+    let step = 10.0;
+    let frequencies: Vec<f32> = (1..400).map(|i| i as f32 * step).collect();
+    let magnitudes: Vec<f32> = frequencies
+        .iter()
+        .map(|f| magnitude_at(&response, *f))
+        .collect();
+
+    let peaks: Vec<f32> = (1..(magnitudes.len() - 1))
+        .filter(|i| magnitudes[*i] > magnitudes[i - 1] && magnitudes[*i] >= magnitudes[i + 1])
+        .map(|i| frequencies[i])
+        .collect();
+
+    assert_eq!(peaks.len(), VOWEL_FORMANTS[0].len(), "Peaks: {:?}", peaks);
+    for (peak, formant) in peaks.iter().zip(VOWEL_FORMANTS[0]) {
+        assert!(
+            (peak - formant.frequency).abs() <= 2.0 * step,
+            "Expected a peak near {} Hz, found one at {} Hz",
+            formant.frequency,
+            peak
+        );
+    }
+}
+
+#[test]
+fn test_vowel_morph_interpolates() {
+    let a = VOWEL_FORMANTS[0];
+    let e = VOWEL_FORMANTS[1];
+    let halfway = formants_at(0.5);
+    for j in 0..a.len() {
+        let expected = 0.5 * (a[j].frequency + e[j].frequency);
+        assert!((halfway[j].frequency - expected).abs() < 1e-3);
+    }
+
+    // Out of range vowels are clamped to the first and last vowels
+    assert_eq!(formants_at(-3.0)[0].frequency, a[0].frequency);
+    assert_eq!(
+        formants_at(100.0)[0].frequency,
+        VOWEL_FORMANTS[VOWEL_FORMANTS.len() - 1][0].frequency
+    );
+}
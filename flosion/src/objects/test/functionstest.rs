@@ -12,7 +12,7 @@ use crate::{
             expressiongraphvalidation::find_expression_error,
             expressioninput::ExpressionInput,
             expressionnode::{
-                AnyExpressionNode, ExpressionNodeVisitor, ExpressionNodeVisitorMut,
+                AnyExpressionNode, ExpressionNode, ExpressionNodeVisitor, ExpressionNodeVisitorMut,
                 ExpressionNodeWithId, PureExpressionNode,
             },
         },
@@ -21,6 +21,7 @@ use crate::{
             jit::Jit,
         },
         objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
         sound::{
             argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
             argumenttypes::plainf32array::PlainF32ArrayArgument,
@@ -162,10 +163,17 @@ macro_rules! assert_near {
     };
 }
 
-fn do_expression_test<T, F>(input_ranges: &[(f32, f32)], test_function: F)
-where
-    T: 'static + PureExpressionNode + Stashable<StashingContext> + UnstashableInplace,
-    F: Fn(&[f32]) -> f32,
+/// Compile an expression consisting of a single node of type T, created
+/// from the given arguments, whose inputs are connected to the given input
+/// arrays in order, and evaluate it once into the output array. Every input
+/// array must be as long as the output.
+pub(super) fn eval_single_node_expression<T>(
+    args: &ParsedArguments,
+    input_values: &[&[f32]],
+    output: &mut [f32],
+    discretization: Discretization,
+) where
+    T: 'static + ExpressionNode + WithObjectType + Stashable<StashingContext> + UnstashableInplace,
 {
     let mut proc = SoundProcessorWithId::<TestSoundProcessor>::new_default();
 
@@ -193,7 +201,7 @@ where
 
     let expr_graph = proc.expression.graph_mut();
 
-    let node = ExpressionNodeWithId::<T>::new_default();
+    let node = ExpressionNodeWithId::<T>::new_from_args(args);
     let node_id = node.id();
     let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
 
@@ -255,8 +263,30 @@ where
         stack,
     );
 
-    //------------------------
+    let len = output.len();
+    let unused_input = vec![0.0_f32; len];
+    assert!(input_values.len() <= MAX_NUM_INPUTS);
+    let input_value = |i: usize| -> &[f32] {
+        let values = input_values.get(i).copied().unwrap_or(&unused_input);
+        assert_eq!(values.len(), len);
+        values
+    };
+
+    compiled_proc.expression.eval(
+        &mut [output],
+        discretization,
+        ExpressionContext::new(&mut context)
+            .push(compiled_proc.argument_0, input_value(0))
+            .push(compiled_proc.argument_1, input_value(1))
+            .push(compiled_proc.argument_2, input_value(2)),
+    );
+}
 
+fn do_expression_test<T, F>(input_ranges: &[(f32, f32)], test_function: F)
+where
+    T: 'static + PureExpressionNode + Stashable<StashingContext> + UnstashableInplace,
+    F: Fn(&[f32]) -> f32,
+{
     // Fill input arrays with randomly generated values within the desired ranges
     let mut input_values = [[0.0_f32; TEST_ARRAY_SIZE]; MAX_NUM_INPUTS];
     assert!(input_ranges.len() <= MAX_NUM_INPUTS);
@@ -283,19 +313,19 @@ where
     // test compiled evaluation
     let mut actual_values_compiled = [0.0_f32; TEST_ARRAY_SIZE];
 
-    compiled_proc.expression.eval(
-        &mut [&mut actual_values_compiled],
+    eval_single_node_expression::<T>(
+        &ParsedArguments::new_empty(),
+        &[&input_values[0], &input_values[1], &input_values[2]],
+        &mut actual_values_compiled,
         Discretization::None,
-        ExpressionContext::new(&mut context)
-            .push(compiled_proc.argument_0, &input_values[0])
-            .push(compiled_proc.argument_1, &input_values[1])
-            .push(compiled_proc.argument_2, &input_values[2]),
     );
 
     for (expected, actual) in expected_values
         .into_iter()
         .zip(actual_values_compiled.into_iter())
     {
+        // Non-finite results are written as zero by compiled expressions
+        let expected = if expected.is_finite() { expected } else { 0.0 };
         assert_near!(expected, actual);
     }
 }
@@ -332,6 +362,17 @@ fn do_expression_test_ternary<T>(
     })
 }
 
+#[test]
+fn test_samplerate() {
+    do_expression_test::<SampleRate, _>(&[], |_| SAMPLE_FREQUENCY as f32);
+
+    let node = <SampleRate as PureExpressionNode>::new(&ParsedArguments::new_empty());
+    assert_eq!(
+        PureExpressionNode::evaluate_constant(&node, &[]),
+        Some(SAMPLE_FREQUENCY as f32)
+    );
+}
+
 #[test]
 fn test_identity() {
     do_expression_test_unary::<Identity>((-10.0, 10.0), |x| x);
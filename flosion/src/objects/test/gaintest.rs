@@ -0,0 +1,52 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        smoothing::SmoothedValue,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::gain::{apply_gain, decibels_to_amplitude, GAIN_SMOOTHING_TIME},
+};
+
+fn ones() -> SoundChunk {
+    SoundChunk {
+        l: [1.0; CHUNK_SIZE],
+        r: [1.0; CHUNK_SIZE],
+    }
+}
+
+#[test]
+fn test_minus_six_decibels_after_settling() {
+    let mut gain = SmoothedValue::new(1.0, GAIN_SMOOTHING_TIME);
+    let target = decibels_to_amplitude(-6.0);
+
+    let mut chunk = ones();
+    apply_gain(&mut chunk, &mut gain, target);
+
+    // The gain should not jump straight to the new value
+    assert!(chunk.l[0] > 0.99);
+
+    // Half a second is many time constants
+    for _ in 0..(SAMPLE_FREQUENCY / 2 / CHUNK_SIZE) {
+        chunk = ones();
+        apply_gain(&mut chunk, &mut gain, target);
+    }
+
+    for (l, r) in chunk.samples() {
+        assert!((l - 0.501).abs() < 0.001, "Gain was {}", l);
+        assert_eq!(l, r);
+    }
+}
+
+#[test]
+fn test_smoothed_value_has_no_large_steps() {
+    let mut gain = SmoothedValue::new(0.0, GAIN_SMOOTHING_TIME);
+
+    let mut previous = gain.current();
+    for _ in 0..SAMPLE_FREQUENCY {
+        let value = gain.step(1.0);
+        assert!(value >= previous);
+        assert!(value - previous < 0.01);
+        previous = value;
+    }
+    assert!((previous - 1.0).abs() < 1e-4);
+}
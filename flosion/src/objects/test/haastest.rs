@@ -0,0 +1,48 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::haas::HaasDelay,
+};
+
+fn input_sample(index: usize) -> f32 {
+    (index as f32 * 0.05).sin()
+}
+
+#[test]
+fn test_delayed_channel_lags() {
+    for delay_samples in [100, 441, 1500] {
+        let delay = delay_samples as f32 / SAMPLE_FREQUENCY as f32;
+        let mut haas_delay = HaasDelay::new();
+
+        for chunk_index in 0..4 {
+            let mut chunk = SoundChunk::new();
+            for (i, (l, r)) in chunk.samples_mut().enumerate() {
+                let x = input_sample(chunk_index * CHUNK_SIZE + i);
+                *l = x;
+                *r = x;
+            }
+
+            haas_delay.process(&mut chunk.r, &[delay; CHUNK_SIZE]);
+
+            for (i, (l, r)) in chunk.samples().enumerate() {
+                let index = chunk_index * CHUNK_SIZE + i;
+                assert_eq!(l, input_sample(index));
+                let expected = if index >= delay_samples {
+                    input_sample(index - delay_samples)
+                } else {
+                    0.0
+                };
+                assert!(
+                    (r - expected).abs() < 1e-4,
+                    "With a delay of {} samples, expected {} at {} but got {}",
+                    delay_samples,
+                    expected,
+                    index,
+                    r
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{core::samplefrequency::SAMPLE_FREQUENCY, objects::karplusstrong::PluckedString};
+
+/// Pluck a string and render the given number of samples
+fn render(frequency: f32, damping: f32, len: usize) -> Vec<f32> {
+    let mut string = PluckedString::new(0);
+    let mut output = vec![0.0; len];
+    string.process(&mut output, &vec![frequency; len], &vec![damping; len]);
+    output
+}
+
+/// Estimate the fundamental frequency of the signal by finding the lag,
+/// within a plausible range of periods, at which the signal best
+/// correlates with itself
+fn estimate_fundamental(signal: &[f32]) -> f32 {
+    let min_lag = SAMPLE_FREQUENCY / 2000;
+    let max_lag = SAMPLE_FREQUENCY / 50;
+    let window = signal.len() - max_lag;
+    let correlation =
+        |lag: usize| -> f32 { (0..window).map(|i| signal[i] * signal[i + lag]).sum() };
+    let best_lag = (min_lag..=max_lag)
+        .max_by(|a, b| correlation(*a).total_cmp(&correlation(*b)))
+        .unwrap();
+
+    // Refine the peak by fitting a parabola through its neighbours
+    let (y0, y1, y2) = (
+        correlation(best_lag - 1),
+        correlation(best_lag),
+        correlation(best_lag + 1),
+    );
+    let offset = 0.5 * (y0 - y2) / (y0 - 2.0 * y1 + y2);
+    SAMPLE_FREQUENCY as f32 / (best_lag as f32 + offset)
+}
+
+#[test]
+fn test_fundamental_matches_frequency() {
+    for frequency in [110.0, 220.0, 330.0, 587.33] {
+        for damping in [0.0, 0.5, 1.0] {
+            let output = render(frequency, damping, SAMPLE_FREQUENCY / 8);
+
+            // Skip the very start of the pluck, while it is still noisy
+            let settled = &output[(SAMPLE_FREQUENCY / 100)..];
+            let estimate = estimate_fundamental(settled);
+            assert!(
+                (estimate - frequency).abs() / frequency < 0.005,
+                "Expected {} Hz with damping {}, got {} Hz",
+                frequency,
+                damping,
+                estimate
+            );
+        }
+    }
+}
+
+#[test]
+fn test_damping_decays_faster() {
+    let energy = |damping: f32| -> f32 {
+        let output = render(220.0, damping, SAMPLE_FREQUENCY);
+        output[(SAMPLE_FREQUENCY / 2)..].iter().map(|x| x * x).sum()
+    };
+    assert!(energy(1.0) < energy(0.5));
+    assert!(energy(0.5) < energy(0.0));
+}
+
+#[test]
+fn test_pluck_restarts_string() {
+    let mut string = PluckedString::new(7);
+    let mut first = vec![0.0; 1000];
+    string.process(&mut first, &[440.0; 1000], &[0.5; 1000]);
+
+    string.pluck();
+    let mut second = vec![0.0; 1000];
+    string.process(&mut second, &[440.0; 1000], &[0.5; 1000]);
+
+    assert_eq!(first, second);
+}
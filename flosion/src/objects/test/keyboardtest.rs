@@ -0,0 +1,296 @@
+use crate::{
+    core::{
+        engine::{
+            scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler,
+            voicelimit::VoiceLimit,
+        },
+        expression::expressiongraph::ExpressionTarget,
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::{ProcessorArgumentId, ProcessorArgumentLocation},
+            context::{AudioContext, AudioStack},
+            expression::ExpressionParameterTarget,
+            soundgraph::SoundGraph,
+            soundinput::{AnyProcessorInput, SoundInputLocation},
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::{
+        keyboard::{KeyId, Keyboard, VoiceStealing},
+        wavegenerator::{WaveGenerator, WaveGeneratorWaveform},
+    },
+};
+
+const NUM_CHUNKS: usize = 8;
+
+/// The gain which the keyboard applies to each of its voices
+const VOICE_GAIN: f32 = 0.1;
+
+/// Connect the keyboard to a wave generator whose amplitude is the keyboard
+/// argument chosen by `argument`, then render several chunks from the
+/// keyboard, calling `play` with the chunk index before each chunk.
+/// Returns the left channel of the output, divided by the voice gain.
+fn render_keyboard_argument<A, P>(
+    keyboard: SoundProcessorWithId<Keyboard>,
+    argument: A,
+    mut play: P,
+) -> Vec<f32>
+where
+    A: Fn(&Keyboard) -> ProcessorArgumentId,
+    P: FnMut(usize, &Keyboard),
+{
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    wavegen.set_waveform(WaveGeneratorWaveform::Expression);
+
+    let keyboard_id = keyboard.id();
+    let wavegen_id = wavegen.id();
+
+    let argument_location = ProcessorArgumentLocation::new(keyboard_id, argument(&keyboard));
+    let param_id = wavegen
+        .amplitude
+        .add_target(ExpressionParameterTarget::Argument(argument_location));
+    let graph = wavegen.amplitude.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(param_id),
+        )
+        .unwrap();
+
+    let input_location = SoundInputLocation::new(keyboard_id, keyboard.input.id());
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(keyboard));
+    graph.add_sound_processor(Box::new(wavegen));
+    graph
+        .connect_sound_input(input_location, wavegen_id)
+        .unwrap();
+    assert_eq!(graph.validate(), Ok(()));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    // Stolen notes would otherwise briefly fade out underneath the new ones
+    let voice_limit = VoiceLimit::new();
+    voice_limit.set_release_fade_samples(0);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache).with_voice_limit(voice_limit);
+
+    let keyboard = graph
+        .sound_processor(keyboard_id)
+        .unwrap()
+        .downcast::<Keyboard>()
+        .unwrap();
+
+    let mut compiled = keyboard.compile(keyboard_id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut output = Vec::new();
+
+    for i in 0..NUM_CHUNKS {
+        play(i, keyboard);
+
+        let mut context = AudioContext::new(
+            keyboard_id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        Keyboard::process_audio(&mut compiled, &mut chunk, &mut context);
+        processor_timing.advance_one_chunk();
+
+        output.extend(chunk.l.iter().map(|x| x / VOICE_GAIN));
+    }
+
+    output
+}
+
+fn assert_all_near(values: &[f32], expected: f32) {
+    for v in values {
+        assert!((v - expected).abs() < 1e-4, "{} != {}", v, expected);
+    }
+}
+
+#[test]
+fn test_velocity_is_latched_for_duration_of_note() {
+    let output = render_keyboard_argument(
+        SoundProcessorWithId::new_default(),
+        |keyboard| keyboard.key_velocity.id(),
+        |i, keyboard| match i {
+            0 => keyboard.start_key(KeyId(0), 440.0, 0.75),
+            // A second, quieter note must not disturb the first
+            4 => keyboard.start_key(KeyId(1), 660.0, 0.25),
+            _ => (),
+        },
+    );
+
+    let (first_only, both) = output.split_at(4 * CHUNK_SIZE);
+    assert_all_near(first_only, 0.75);
+    assert_all_near(both, 0.75 + 0.25);
+}
+
+#[test]
+fn test_note_is_latched_for_duration_of_note() {
+    let output = render_keyboard_argument(
+        SoundProcessorWithId::new_default(),
+        |keyboard| keyboard.key_note.id(),
+        |i, keyboard| {
+            if i == 0 {
+                keyboard.start_key(KeyId(0), 220.0, 1.0);
+            }
+        },
+    );
+
+    // A3 is one octave below A4, which is note 69
+    assert_all_near(&output, 57.0);
+}
+
+/// Make a keyboard with the given number of voices and stealing policy
+fn make_keyboard(num_voices: usize, stealing: VoiceStealing) -> SoundProcessorWithId<Keyboard> {
+    let mut keyboard = SoundProcessorWithId::<Keyboard>::new_default();
+    keyboard.input.set_num_keys(num_voices);
+    keyboard.set_voice_stealing(stealing);
+    keyboard
+}
+
+#[test]
+fn test_single_voice_steals_oldest() {
+    let output = render_keyboard_argument(
+        make_keyboard(1, VoiceStealing::Oldest),
+        |keyboard| keyboard.key_velocity.id(),
+        |i, keyboard| match i {
+            0 => keyboard.start_key(KeyId(0), 440.0, 0.75),
+            4 => keyboard.start_key(KeyId(1), 660.0, 0.25),
+            _ => (),
+        },
+    );
+
+    // The new note replaces the one which was sounding
+    let (before, after) = output.split_at(4 * CHUNK_SIZE);
+    assert_all_near(before, 0.75);
+    assert_all_near(after, 0.25);
+}
+
+#[test]
+fn test_steal_quietest() {
+    let output = render_keyboard_argument(
+        make_keyboard(2, VoiceStealing::Quietest),
+        |keyboard| keyboard.key_velocity.id(),
+        |i, keyboard| match i {
+            0 => {
+                keyboard.start_key(KeyId(0), 440.0, 0.25);
+                keyboard.start_key(KeyId(1), 550.0, 0.5);
+            }
+            4 => keyboard.start_key(KeyId(2), 660.0, 0.75),
+            _ => (),
+        },
+    );
+
+    // The quieter first note is replaced even though it isn't the oldest
+    let (before, after) = output.split_at(4 * CHUNK_SIZE);
+    assert_all_near(before, 0.25 + 0.5);
+    assert_all_near(after, 0.5 + 0.75);
+}
+
+/// Play two notes an octave apart on a single voice, the first from
+/// chunk 0 and the second from chunk 4, releasing the first at chunk 2
+/// if `release_first` is set. Returns the frequency argument.
+fn glide_between_two_notes(glide_time: f32, legato_only: bool, release_first: bool) -> Vec<f32> {
+    let keyboard = make_keyboard(1, VoiceStealing::Oldest);
+    keyboard.set_glide_time(glide_time);
+    keyboard.set_legato_only(legato_only);
+    render_keyboard_argument(
+        keyboard,
+        |keyboard| keyboard.key_frequency.id(),
+        |i, keyboard| match i {
+            0 => keyboard.start_key(KeyId(0), 220.0, 1.0),
+            2 if release_first => keyboard.release_key(KeyId(0)),
+            4 => keyboard.start_key(KeyId(1), 440.0, 1.0),
+            _ => (),
+        },
+    )
+}
+
+#[test]
+fn test_glide_takes_glide_time() {
+    let glide_samples = 2 * CHUNK_SIZE + 100;
+    let glide_time = glide_samples as f32 / SAMPLE_FREQUENCY as f32;
+    let output = glide_between_two_notes(glide_time, false, false);
+
+    let (first, second) = output.split_at(4 * CHUNK_SIZE);
+    assert_all_near(first, 220.0);
+
+    // The glide starts from the previous note and rises steadily
+    assert!((second[0] - 220.0).abs() < 1e-2);
+    for w in second[..glide_samples].windows(2) {
+        assert!(w[1] > w[0]);
+    }
+
+    // Halfway through, the frequency is halfway between in octaves
+    let halfway = 220.0 * 2.0_f32.sqrt();
+    assert!((second[glide_samples / 2] - halfway).abs() < 1e-1);
+
+    // It reaches the new note after exactly the glide time
+    assert!(second[glide_samples - 1] < 440.0 - 1e-2);
+    assert_all_near(&second[glide_samples..], 440.0);
+}
+
+#[test]
+fn test_no_glide_by_default() {
+    let output = glide_between_two_notes(0.0, false, false);
+    let (first, second) = output.split_at(4 * CHUNK_SIZE);
+    assert_all_near(first, 220.0);
+    assert_all_near(second, 440.0);
+}
+
+#[test]
+fn test_legato_only_glide() {
+    let glide_time = (2 * CHUNK_SIZE) as f32 / SAMPLE_FREQUENCY as f32;
+
+    // While the first key is held, the second glides
+    let output = glide_between_two_notes(glide_time, true, false);
+    assert!(output[4 * CHUNK_SIZE + CHUNK_SIZE] < 440.0 - 1.0);
+
+    // After the first key is released, the second doesn't glide
+    let output = glide_between_two_notes(glide_time, true, true);
+    assert_all_near(&output[(4 * CHUNK_SIZE)..], 440.0);
+
+    // Unless gliding isn't restricted to legato
+    let output = glide_between_two_notes(glide_time, false, true);
+    assert!(output[4 * CHUNK_SIZE + CHUNK_SIZE] < 440.0 - 1.0);
+}
+
+#[test]
+fn test_scheduled_key_starts_at_exact_sample() {
+    let k = 37;
+    let start_time = (2 * CHUNK_SIZE + k) as u64;
+    let release_time = start_time + (3 * CHUNK_SIZE) as u64;
+    let output = render_keyboard_argument(
+        SoundProcessorWithId::new_default(),
+        |keyboard| keyboard.key_velocity.id(),
+        |i, keyboard| {
+            if i == 0 {
+                assert_eq!(keyboard.current_time(), 0);
+                keyboard.start_key_at(start_time, KeyId(0), 440.0, 0.75);
+                keyboard.release_key_at(release_time, KeyId(0));
+            }
+        },
+    );
+
+    // The key sounds from exactly the sample it was scheduled for
+    // until exactly the sample at which it was released
+    let (start, release) = (start_time as usize, release_time as usize);
+    assert_all_near(&output[..start], 0.0);
+    assert_all_near(&output[start..release], 0.75);
+    assert_all_near(&output[release..], 0.0);
+}
@@ -0,0 +1,141 @@
+use crate::{
+    core::{
+        beatsync::{BeatSync, RetriggerInterval},
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::CHUNK_SIZE,
+        tempo::Tempo,
+    },
+    objects::lfo::{LfoOscillator, LfoOutputs},
+};
+
+/// Run the oscillator for one second with constant settings and
+/// return every chunk of outputs
+fn generate(rate: f32, shape: f32, phase_offset: f32) -> Vec<LfoOutputs> {
+    let mut osc = LfoOscillator::new();
+    (0..(SAMPLE_FREQUENCY / CHUNK_SIZE))
+        .map(|_| {
+            let mut outputs = LfoOutputs::new();
+            osc.generate(
+                &[rate; CHUNK_SIZE],
+                &[shape; CHUNK_SIZE],
+                &[phase_offset; CHUNK_SIZE],
+                &[false; CHUNK_SIZE],
+                &mut outputs,
+            );
+            outputs
+        })
+        .collect()
+}
+
+fn samples<F: Fn(&LfoOutputs) -> &[f32; CHUNK_SIZE]>(chunks: &[LfoOutputs], f: F) -> Vec<f32> {
+    chunks.iter().flat_map(|c| f(c).iter().copied()).collect()
+}
+
+#[test]
+fn test_sine_cycle_matches_rate() {
+    for rate in [2.0, 5.0, 7.5] {
+        let sine = samples(&generate(rate, 0.5, 0.0), |o| &o.sine);
+        let period = SAMPLE_FREQUENCY as f32 / rate;
+
+        // The sine starts at zero and rises
+        assert_eq!(sine[0], 0.0);
+        assert!(sine[1] > 0.0);
+
+        let crossings: Vec<f32> = sine
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] < 0.0 && w[1] >= 0.0)
+            .map(|(i, w)| i as f32 + w[0] / (w[0] - w[1]))
+            .collect();
+        assert_eq!(crossings.len(), ((sine.len() - 1) as f32 / period) as usize);
+        for (i, crossing) in crossings.iter().enumerate() {
+            let expected = (i + 1) as f32 * period;
+            assert!(
+                (crossing - expected).abs() < 0.5,
+                "At {} Hz, expected cycle {} to end at {}, but it ended at {}",
+                rate,
+                i + 1,
+                expected,
+                crossing
+            );
+        }
+    }
+}
+
+#[test]
+fn test_shapes_follow_phase() {
+    // Exactly 128 samples per cycle, so that the phase is always exact
+    let rate = SAMPLE_FREQUENCY as f32 / 128.0;
+    let chunks = generate(rate, 0.25, 0.0);
+    let ramp = samples(&chunks, |o| &o.ramp);
+    let square = samples(&chunks, |o| &o.square);
+    let triangle = samples(&chunks, |o| &o.triangle);
+    let held = samples(&chunks, |o| &o.sample_and_hold);
+
+    for i in 0..1024 {
+        let p = (i % 128) as f32 / 128.0;
+        assert_eq!(ramp[i], 2.0 * p - 1.0);
+        assert_eq!(square[i] > 0.0, p < 0.25, "At sample {}", i);
+        assert!(triangle[i] >= -1.0 && triangle[i] <= 1.0);
+    }
+
+    // The triangle peaks a quarter of the way through each cycle
+    assert_eq!(triangle[32], 1.0);
+
+    // The held value only changes at the start of each cycle
+    for i in 1..1024 {
+        if i % 128 != 0 {
+            assert_eq!(held[i], held[i - 1]);
+        }
+    }
+    assert!((1..8).any(|c| held[c * 128] != held[c * 128 - 1]));
+
+    // A phase offset of a quarter cycle starts the ramp a quarter
+    // of the way up
+    let offset_ramp = samples(&generate(rate, 0.25, 0.25), |o| &o.ramp);
+    assert_eq!(offset_ramp[0], -0.5);
+}
+
+#[test]
+fn test_beat_synced_lfo_resets_at_each_beat() {
+    // At 120 beats per minute, a beat is exactly 22050 samples long,
+    // during which a 3 Hz LFO completes one and a half cycles
+    let beat_sync = BeatSync {
+        tempo: Tempo::new(120.0, 4),
+        interval: RetriggerInterval::Beat,
+    };
+    let beat = SAMPLE_FREQUENCY / 2;
+
+    let mut osc = LfoOscillator::new();
+    let mut chunks = Vec::new();
+    for c in 0..(2 * SAMPLE_FREQUENCY / CHUNK_SIZE) {
+        let mut outputs = LfoOutputs::new();
+        osc.generate(
+            &[3.0; CHUNK_SIZE],
+            &[0.5; CHUNK_SIZE],
+            &[0.0; CHUNK_SIZE],
+            &beat_sync.retriggered_samples(c * CHUNK_SIZE),
+            &mut outputs,
+        );
+        chunks.push(outputs);
+    }
+    let ramp = samples(&chunks, |o| &o.ramp);
+    let sine = samples(&chunks, |o| &o.sine);
+    let held = samples(&chunks, |o| &o.sample_and_hold);
+
+    for b in 1..=3 {
+        let start = b * beat;
+
+        // Halfway through a cycle just before the beat
+        assert!((ramp[start - 1]).abs() < 1e-3);
+
+        // Every beat starts exactly like the first
+        for i in 0..100 {
+            assert_eq!(ramp[start + i], ramp[i], "At sample {} of beat {}", i, b);
+            assert_eq!(sine[start + i], sine[i], "At sample {} of beat {}", i, b);
+        }
+
+        // A new value is held at each beat
+        assert_ne!(held[start], held[start - 1]);
+    }
+}
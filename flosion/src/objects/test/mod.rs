@@ -1 +1,32 @@
+mod arpeggiatortest;
+mod audiocliptest;
+mod automationtest;
+mod binauraltest;
+mod channelstest;
+mod chorustest;
+mod compressortest;
+mod crossfadetest;
+mod definitionstest;
+mod formantfiltertest;
 mod functionstest;
+mod gaintest;
+mod haastest;
+mod karplusstrongtest;
+mod keyboardtest;
+mod lfotest;
+mod noisetest;
+mod outputtest;
+mod quantizetoscaletest;
+mod randomtest;
+mod sampler1dtest;
+mod sampler2dtest;
+mod slewtest;
+mod spectralgatetest;
+mod stereowidthtest;
+mod taptest;
+mod tremolotest;
+mod triggertest;
+mod vocodertest;
+mod wavegeneratortest;
+mod wavetabletest;
+mod whitenoisetest;
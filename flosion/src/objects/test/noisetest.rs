@@ -0,0 +1,162 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorId,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::noise::{Noise, NoiseColor},
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Number of samples in each segment whose spectrum is measured
+const SEGMENT_SIZE: usize = 1024;
+
+/// Number of segments whose spectra are averaged
+const NUM_SEGMENTS: usize = 48;
+
+/// Render the left channel of a noise processor with the given color
+fn render_noise(color: NoiseColor, num_samples: usize) -> Vec<f32> {
+    let args = ParsedArguments::new_empty()
+        .add_or_replace(&Noise::ARG_COLOR, color)
+        .add_or_replace(&Noise::ARG_SEED, 42);
+
+    let noise = Noise::new(&args);
+
+    let inkwell_context = inkwell::context::Context::create();
+    let jit_cache = JitCache::new(&inkwell_context);
+    let graph = SoundGraph::new();
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let id = SoundProcessorId::new_unique();
+
+    let mut compiled_noise = noise.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let processor_timing = ProcessorTiming::new();
+
+    let mut samples = Vec::with_capacity(num_samples);
+
+    while samples.len() < num_samples {
+        let mut context = AudioContext::new(
+            id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+
+        let mut chunk = SoundChunk::new();
+
+        Noise::process_audio(&mut compiled_noise, &mut chunk, &mut context);
+
+        samples.extend_from_slice(&chunk.l[..CHUNK_SIZE.min(num_samples - samples.len())]);
+    }
+
+    samples
+}
+
+/// Estimate the power in each octave band [2^k, 2^(k+1)) of bins, for
+/// each of the given values of k, by averaging the Hann-windowed
+/// periodograms of consecutive segments
+fn octave_band_powers(samples: &[f32], octaves: &[usize]) -> Vec<f64> {
+    let window: Vec<f64> = (0..SEGMENT_SIZE)
+        .map(|i| {
+            let x = (i as f64) / (SEGMENT_SIZE as f64);
+            0.5 - 0.5 * (std::f64::consts::TAU * x).cos()
+        })
+        .collect();
+
+    let twiddles: Vec<(f64, f64)> = (0..SEGMENT_SIZE)
+        .map(|i| {
+            let angle = std::f64::consts::TAU * (i as f64) / (SEGMENT_SIZE as f64);
+            (angle.cos(), angle.sin())
+        })
+        .collect();
+
+    let mut powers = vec![0.0; octaves.len()];
+
+    for segment in samples.chunks_exact(SEGMENT_SIZE) {
+        for (power, k) in powers.iter_mut().zip(octaves) {
+            for bin in (1 << k)..(1 << (k + 1)) {
+                let mut re = 0.0;
+                let mut im = 0.0;
+                for (i, (s, w)) in segment.iter().zip(&window).enumerate() {
+                    let (c, s_) = twiddles[(i * bin) % SEGMENT_SIZE];
+                    let v = (*s as f64) * w;
+                    re += v * c;
+                    im -= v * s_;
+                }
+                *power += re * re + im * im;
+            }
+        }
+    }
+
+    powers
+}
+
+/// Measure the average slope of the power spectral density in dB per octave
+fn measure_spectral_slope(color: NoiseColor) -> f64 {
+    let samples = render_noise(color, SEGMENT_SIZE * NUM_SEGMENTS);
+
+    // Octave bands from roughly 350 Hz to 11 kHz
+    let octaves = [3, 4, 5, 6, 7];
+    let powers = octave_band_powers(&samples, &octaves);
+
+    // Each octave band contains twice as many bins as the previous,
+    // so the band power of a flat spectrum rises by 3 dB per octave
+    let band_width_slope = 10.0 * 2.0_f64.log10();
+
+    let slopes: Vec<f64> = powers
+        .windows(2)
+        .map(|w| 10.0 * (w[1] / w[0]).log10() - band_width_slope)
+        .collect();
+
+    slopes.iter().sum::<f64>() / (slopes.len() as f64)
+}
+
+#[test]
+fn test_white_noise_spectral_slope() {
+    let slope = measure_spectral_slope(NoiseColor::White);
+    assert!(
+        slope.abs() < 1.0,
+        "White noise slope was {} dB/octave",
+        slope
+    );
+}
+
+#[test]
+fn test_pink_noise_spectral_slope() {
+    let slope = measure_spectral_slope(NoiseColor::Pink);
+    assert!(
+        (slope + 3.0).abs() < 1.0,
+        "Pink noise slope was {} dB/octave",
+        slope
+    );
+}
+
+#[test]
+fn test_brown_noise_spectral_slope() {
+    let slope = measure_spectral_slope(NoiseColor::Brown);
+    assert!(
+        (slope + 6.0).abs() < 1.0,
+        "Brown noise slope was {} dB/octave",
+        slope
+    );
+}
+
+#[test]
+fn test_noise_is_reproducible() {
+    for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+        let a = render_noise(color, 4 * CHUNK_SIZE);
+        let b = render_noise(color, 4 * CHUNK_SIZE);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,246 @@
+use cpal::{BufferSize, SampleRate};
+
+use crate::{
+    core::{samplefrequency::SAMPLE_FREQUENCY, soundchunk::CHUNK_SIZE},
+    objects::output::{
+        choose_stream_config, ChannelMeter, DcBlocker, Limiter, OutputDeviceConfigRange,
+        LIMITER_LOOKAHEAD,
+    },
+};
+
+/// Run a signal through a DC blocker one chunk at a time, as the
+/// output processor does
+fn dc_block(mut samples: Vec<f32>) -> Vec<f32> {
+    let mut dc_blocker = DcBlocker::new();
+    for chunk in samples.chunks_mut(CHUNK_SIZE) {
+        dc_blocker.process(chunk);
+    }
+    samples
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0, |p, s| p.max(s.abs()))
+}
+
+#[test]
+fn test_dc_blocker_removes_constant_offset() {
+    let output = dc_block(vec![0.5; SAMPLE_FREQUENCY]);
+
+    // After a second, the offset should be all but gone
+    let tail = &output[(SAMPLE_FREQUENCY - CHUNK_SIZE)..];
+    assert!(peak(tail) < 0.001, "Remaining offset was {}", peak(tail));
+}
+
+#[test]
+fn test_dc_blocker_passes_tone() {
+    let frequency = 1000.0;
+    let input: Vec<f32> = (0..SAMPLE_FREQUENCY)
+        .map(|i| {
+            let t = (i as f32) / (SAMPLE_FREQUENCY as f32);
+            0.5 * (std::f32::consts::TAU * frequency * t).sin()
+        })
+        .collect();
+
+    let output = dc_block(input);
+
+    let tail = &output[(SAMPLE_FREQUENCY / 2)..];
+    assert!(
+        (peak(tail) - 0.5).abs() < 0.01,
+        "Tone amplitude was {}",
+        peak(tail)
+    );
+}
+
+/// Run a mono signal through a limiter one chunk at a time, as the
+/// output processor does
+fn limit(samples: &[f32], ceiling: f32, release_seconds: f32) -> Vec<f32> {
+    let mut limiter = Limiter::new();
+    let mut l = samples.to_vec();
+    let mut r = samples.to_vec();
+    for (cl, cr) in l.chunks_mut(CHUNK_SIZE).zip(r.chunks_mut(CHUNK_SIZE)) {
+        limiter.process(cl, cr, ceiling, release_seconds);
+    }
+    assert_eq!(l, r);
+    l
+}
+
+#[test]
+fn test_limiter_respects_ceiling() {
+    let input: Vec<f32> = (0..SAMPLE_FREQUENCY)
+        .map(|i| {
+            let t = (i as f32) / (SAMPLE_FREQUENCY as f32);
+            2.0 * (std::f32::consts::TAU * 440.0 * t).sin()
+        })
+        .collect();
+
+    let output = limit(&input, 0.5, 0.1);
+
+    assert!(peak(&output) <= 0.5 + 1e-6, "Peak was {}", peak(&output));
+
+    // The output is the input delayed by the lookahead, only quieter
+    for (o, i) in output[LIMITER_LOOKAHEAD..].iter().zip(&input) {
+        assert!(o.abs() <= i.abs() + 1e-6);
+        assert!(o * i >= 0.0);
+    }
+}
+
+#[test]
+fn test_limiter_passes_quiet_signal() {
+    let input: Vec<f32> = (0..(4 * CHUNK_SIZE))
+        .map(|i| ((i % 7) as f32 - 3.0) * 0.1)
+        .collect();
+
+    let output = limit(&input, 0.5, 0.1);
+
+    assert_eq!(output[..LIMITER_LOOKAHEAD], [0.0; LIMITER_LOOKAHEAD]);
+    assert_eq!(
+        output[LIMITER_LOOKAHEAD..],
+        input[..(input.len() - LIMITER_LOOKAHEAD)]
+    );
+}
+
+#[test]
+fn test_limiter_release() {
+    let release_seconds = 0.05;
+    let release_samples = (release_seconds * SAMPLE_FREQUENCY as f32) as usize;
+    let loud_samples = 4 * CHUNK_SIZE;
+    let quiet_level = 0.1;
+
+    // A loud burst which needs a gain of 0.25 followed by a quiet signal
+    let mut input = vec![2.0; loud_samples];
+    input.resize(loud_samples + 4 * release_samples, quiet_level);
+
+    let output = limit(&input, 0.5, release_seconds);
+
+    let gains: Vec<f32> = output[(loud_samples + LIMITER_LOOKAHEAD)..]
+        .iter()
+        .map(|s| s / quiet_level)
+        .collect();
+
+    // The gain recovers monotonically and never overshoots
+    for w in gains.windows(2) {
+        assert!(w[1] >= w[0]);
+        assert!(w[1] <= 1.0);
+    }
+
+    // After one time constant, the remaining gain reduction has
+    // fallen to about 1/e of what it was
+    let expected_gain = 1.0 - 0.75 * (-1.0_f32).exp();
+    let gain = gains[release_samples];
+    assert!(
+        (gain - expected_gain).abs() < 0.01,
+        "Gain after release time was {}, expected {}",
+        gain,
+        expected_gain
+    );
+}
+
+#[test]
+fn test_limiter_attack_is_smooth() {
+    let quiet_samples = 4 * CHUNK_SIZE;
+    let quiet_level = 0.1;
+
+    // A quiet signal followed by a loud one which needs a gain of 0.25
+    let mut input = vec![quiet_level; quiet_samples];
+    input.resize(quiet_samples + 4 * CHUNK_SIZE, 2.0);
+
+    let output = limit(&input, 0.5, 0.1);
+
+    let gains: Vec<f32> = output[LIMITER_LOOKAHEAD..]
+        .iter()
+        .zip(&input)
+        .map(|(o, i)| o / i)
+        .collect();
+
+    assert!(peak(&output) <= 0.5 + 1e-6, "Peak was {}", peak(&output));
+
+    // The gain is fully reduced by the time the loud signal comes out
+    assert!((gains[quiet_samples] - 0.25).abs() < 1e-6);
+
+    // Until then, it falls gradually over the lookahead instead of all at once
+    let max_step = 0.75 / (LIMITER_LOOKAHEAD as f32) + 1e-6;
+    for w in gains[..=quiet_samples].windows(2) {
+        assert!(w[1] <= w[0] + 1e-6);
+        assert!(
+            w[0] - w[1] <= max_step,
+            "Gain fell from {} to {}",
+            w[0],
+            w[1]
+        );
+    }
+    assert_eq!(gains[quiet_samples - LIMITER_LOOKAHEAD - 1], 1.0);
+}
+
+#[test]
+fn test_meter_clip_latches() {
+    let meter = ChannelMeter::new();
+
+    meter.update(&[0.0, 0.5, -0.9, 1.0]);
+    assert!(!meter.clipped());
+
+    meter.update(&[0.0, 1.2, 0.0, 0.0]);
+    assert!(meter.clipped());
+    assert_eq!(meter.peak(), 1.2);
+
+    // The clip indicator stays on after the signal becomes quiet again
+    for _ in 0..100 {
+        meter.update(&[0.1; CHUNK_SIZE]);
+    }
+    assert!(meter.clipped());
+    assert!(meter.peak() < 1.2);
+
+    meter.reset_clip();
+    assert!(!meter.clipped());
+
+    meter.update(&[0.1; CHUNK_SIZE]);
+    assert!(!meter.clipped());
+}
+
+fn test_device_configs() -> Vec<OutputDeviceConfigRange> {
+    vec![
+        OutputDeviceConfigRange {
+            min_sample_rate: 44_100,
+            max_sample_rate: 48_000,
+            buffer_sizes: Some(64..=1024),
+        },
+        OutputDeviceConfigRange {
+            min_sample_rate: 96_000,
+            max_sample_rate: 96_000,
+            buffer_sizes: None,
+        },
+    ]
+}
+
+#[test]
+fn test_choose_default_stream_config() {
+    let config = choose_stream_config(&test_device_configs(), None, None).unwrap();
+    assert_eq!(config.channels, 2);
+    assert_eq!(config.sample_rate, SampleRate(SAMPLE_FREQUENCY as u32));
+    assert_eq!(config.buffer_size, BufferSize::Default);
+}
+
+#[test]
+fn test_choose_supported_stream_config() {
+    let configs = test_device_configs();
+
+    let config = choose_stream_config(&configs, Some(48_000), Some(256)).unwrap();
+    assert_eq!(config.sample_rate, SampleRate(48_000));
+    assert_eq!(config.buffer_size, BufferSize::Fixed(256));
+
+    // The buffer size is only used if the range with the requested
+    // sample rate supports it
+    let config = choose_stream_config(&configs, Some(96_000), Some(256)).unwrap();
+    assert_eq!(config.sample_rate, SampleRate(96_000));
+    assert_eq!(config.buffer_size, BufferSize::Default);
+}
+
+#[test]
+fn test_choose_unsupported_stream_config() {
+    let configs = test_device_configs();
+
+    let config = choose_stream_config(&configs, Some(22_050), Some(8192)).unwrap();
+    assert_eq!(config.sample_rate, SampleRate(SAMPLE_FREQUENCY as u32));
+    assert_eq!(config.buffer_size, BufferSize::Default);
+
+    assert_eq!(choose_stream_config(&[], Some(48_000), None), None);
+}
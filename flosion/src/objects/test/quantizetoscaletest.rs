@@ -0,0 +1,70 @@
+use crate::{
+    core::jit::compiledexpression::Discretization,
+    objects::quantizetoscale::{quantize_frequency, MusicalScale, QuantizeToScale},
+    ui_core::arguments::{ArgumentEnum, ParsedArguments},
+};
+
+use super::functionstest::eval_single_node_expression;
+
+/// Frequency of the note the given number of semitones away from A4
+fn note(semitones_from_a4: f32) -> f32 {
+    440.0 * (semitones_from_a4 / 12.0).exp2()
+}
+
+fn assert_near(expected: f32, actual: f32) {
+    assert!(
+        (expected - actual).abs() < 1e-3 * expected.abs().max(1.0),
+        "Expected {} but got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_detuned_note_snaps_to_nearest_semitone() {
+    for semitones in [-30, -13, -1, 0, 1, 2, 11, 24] {
+        let semitones = semitones as f32;
+        for detune in [-0.45, -0.2, 0.0, 0.1, 0.4] {
+            assert_near(
+                note(semitones),
+                quantize_frequency(note(semitones + detune), MusicalScale::Chromatic),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_snaps_to_notes_of_scale() {
+    // C#5 (4 semitones above A4), 30 cents sharp, is nearest to D5 in C major
+    assert_near(
+        note(5.0),
+        quantize_frequency(note(4.3), MusicalScale::Major),
+    );
+    // F#4 is a semitone from both F4 and G4 in C minor, and so snaps down
+    assert_near(
+        note(-4.0),
+        quantize_frequency(note(-3.0), MusicalScale::Minor),
+    );
+    // B4 snaps up to C5 in C major pentatonic
+    assert_near(
+        note(3.0),
+        quantize_frequency(note(2.0), MusicalScale::Pentatonic),
+    );
+}
+
+#[test]
+fn test_compiled_matches_reference() {
+    let input: Vec<f32> = (0..500).map(|i| -10.0 + 5.0 * i as f32).collect();
+    let mut output = vec![0.0; input.len()];
+    for scale in MusicalScale::all_values() {
+        eval_single_node_expression::<QuantizeToScale>(
+            &ParsedArguments::new_empty().add_or_replace(&QuantizeToScale::ARG_SCALE, *scale),
+            &[&input],
+            &mut output,
+            Discretization::None,
+        );
+        for (x, y) in input.iter().zip(&output) {
+            assert_near(quantize_frequency(*x, *scale), *y);
+        }
+    }
+}
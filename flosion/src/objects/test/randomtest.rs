@@ -0,0 +1,46 @@
+use crate::{
+    core::jit::compiledexpression::Discretization, objects::statefulfunctions::Random,
+    ui_core::arguments::ParsedArguments,
+};
+
+use super::functionstest::eval_single_node_expression;
+
+fn random_sequence(seed: u64) -> Vec<f32> {
+    let mut output = vec![0.0; 1000];
+    eval_single_node_expression::<Random>(
+        &ParsedArguments::new_empty().add_or_replace(&Random::ARG_SEED, seed as _),
+        &[],
+        &mut output,
+        Discretization::samplewise_temporal(),
+    );
+    output
+}
+
+#[test]
+fn test_same_seed_is_reproducible() {
+    for seed in [0, 1, 12345] {
+        assert_eq!(random_sequence(seed), random_sequence(seed));
+    }
+}
+
+#[test]
+fn test_different_seeds_differ() {
+    let a = random_sequence(0);
+    let b = random_sequence(1);
+    let num_equal = a.iter().zip(&b).filter(|(x, y)| x == y).count();
+    assert!(num_equal < 5);
+}
+
+#[test]
+fn test_values_are_uniform_in_range() {
+    let values = random_sequence(42);
+    assert!(values.iter().all(|x| (-1.0..1.0).contains(x)));
+
+    // The values shouldn't repeat, and should be roughly centred
+    let num_repeats = values.windows(2).filter(|w| w[0] == w[1]).count();
+    assert_eq!(num_repeats, 0);
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    assert!(mean.abs() < 0.1, "Mean was {}", mean);
+    let num_positive = values.iter().filter(|x| **x > 0.0).count();
+    assert!((400..600).contains(&num_positive));
+}
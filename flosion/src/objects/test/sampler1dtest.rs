@@ -0,0 +1,247 @@
+use std::{f32::consts::TAU, path::PathBuf};
+
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+        },
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::sampler1d::{Sampler1d, Sampler1dInterpolation},
+    ui_core::arguments::{ArgumentEnum, ParsedArguments},
+};
+
+const NUM_SAMPLES: usize = 1000;
+
+/// Evaluate a sampler holding the given values with the given
+/// interpolation over one period, i.e. with its input swept from 0 to 1
+fn eval_sampler(values: &[f32], interpolation: Sampler1dInterpolation) -> Vec<f32> {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let sampler = ExpressionNodeWithId::<Sampler1d>::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Sampler1d::ARG_INTERPOLATION, interpolation),
+    );
+    assert_eq!(sampler.value().len(), values.len());
+    sampler.value().write(values);
+    let sampler_id = sampler.id();
+    let input_locations = (&sampler as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(sampler));
+
+    graph
+        .connect_input(
+            input_locations[0],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(sampler_id))
+        .unwrap();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::WithRespectTo(
+                argument_location,
+                Interval::Linear { from: 0.0, to: 1.0 },
+            )),
+        )
+        .unwrap();
+
+    let mut output = vec![0.0; NUM_SAMPLES];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+    output
+}
+
+/// One period of a sine wave, at the sampler's default resolution
+fn sine_values() -> Vec<f32> {
+    let len = ExpressionNodeWithId::<Sampler1d>::new_default()
+        .value()
+        .len();
+    (0..len)
+        .map(|i| (TAU * i as f32 / len as f32).sin())
+        .collect()
+}
+
+fn max_error_from_sine(output: &[f32]) -> f32 {
+    output
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (v - (TAU * i as f32 / NUM_SAMPLES as f32).sin()).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_cubic_is_more_accurate_than_linear() {
+    let values = sine_values();
+
+    let nearest_error =
+        max_error_from_sine(&eval_sampler(&values, Sampler1dInterpolation::Nearest));
+    let linear_error = max_error_from_sine(&eval_sampler(&values, Sampler1dInterpolation::Linear));
+    let cubic_error = max_error_from_sine(&eval_sampler(&values, Sampler1dInterpolation::Cubic));
+
+    assert!(
+        linear_error < nearest_error,
+        "linear error {} should be less than nearest error {}",
+        linear_error,
+        nearest_error
+    );
+    assert!(
+        cubic_error < linear_error,
+        "cubic error {} should be less than linear error {}",
+        cubic_error,
+        linear_error
+    );
+}
+
+#[test]
+fn test_compiled_sampler_matches_sample() {
+    // Something with kinks and wraparound to tell the modes apart
+    let values: Vec<f32> = sine_values()
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| if i % 7 == 0 { -v } else { v * v })
+        .collect();
+
+    for interpolation in Sampler1dInterpolation::all_values() {
+        let output = eval_sampler(&values, *interpolation);
+        for (i, v) in output.iter().enumerate() {
+            let expected = interpolation.sample(&values, i as f32 / NUM_SAMPLES as f32);
+            assert!(
+                (v - expected).abs() < 1e-4,
+                "With {} interpolation, sample {} was {} but should be {}",
+                interpolation.name(),
+                i,
+                v,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn test_doubling_length_preserves_curve() {
+    let values = sine_values();
+
+    let mut sampler = ExpressionNodeWithId::<Sampler1d>::new_default();
+    sampler.value().write(&values);
+
+    sampler.resize(2 * values.len());
+    assert_eq!(sampler.length(), 2 * values.len());
+
+    let resampled = sampler.value().read().to_vec();
+
+    for interpolation in Sampler1dInterpolation::all_values() {
+        for (i, v) in values.iter().enumerate() {
+            let position = i as f32 / values.len() as f32;
+            assert_eq!(resampled[2 * i], *v);
+            assert!((interpolation.sample(&resampled, position) - v).abs() < 1e-6);
+        }
+    }
+
+    // New entries lie between the original ones
+    for i in 0..values.len() {
+        let expected = 0.5 * (values[i] + values[(i + 1) % values.len()]);
+        assert!((resampled[2 * i + 1] - expected).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_normalize_and_clear() {
+    let sampler = ExpressionNodeWithId::<Sampler1d>::new_default();
+    let values: Vec<f32> = sine_values().into_iter().map(|v| 0.25 * v).collect();
+    sampler.value().write(&values);
+
+    sampler.normalize();
+    let normalized = sampler.value().read().to_vec();
+    let peak = normalized.iter().fold(0.0_f32, |p, v| p.max(v.abs()));
+    assert!((peak - 1.0).abs() < 1e-6);
+    for (n, v) in normalized.iter().zip(&values) {
+        assert!((n - 4.0 * v).abs() < 1e-5);
+    }
+
+    sampler.clear();
+    assert!(sampler.value().read().iter().all(|v| *v == 0.0));
+
+    // Normalizing silence leaves it alone
+    sampler.normalize();
+    assert!(sampler.value().read().iter().all(|v| *v == 0.0));
+}
+
+fn temp_file_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("flosion_{}_{}", std::process::id(), name))
+}
+
+/// Export a curve to a file with the given name and import it into a
+/// fresh sampler
+fn round_trip(values: &[f32], file_name: &str) -> Vec<f32> {
+    let mut sampler = ExpressionNodeWithId::<Sampler1d>::new_default();
+    sampler.set_values(values).unwrap();
+
+    let path = temp_file_path(file_name);
+    sampler.export(&path).unwrap();
+
+    let mut imported = ExpressionNodeWithId::<Sampler1d>::new_default();
+    let result = imported.import(&path);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    let values = imported.value().read().to_vec();
+    values
+}
+
+#[test]
+fn test_curve_round_trips_through_files() {
+    // A length other than the default, to check that it is restored too
+    let values: Vec<f32> = (0..100)
+        .map(|i| (TAU * i as f32 / 100.0).sin() * 0.9 + 0.01 * i as f32)
+        .collect();
+
+    for file_name in ["curve.csv", "curve.wav", "CURVE.CSV"] {
+        let imported = round_trip(&values, file_name);
+        assert_eq!(imported.len(), values.len(), "{}", file_name);
+        for (a, b) in imported.iter().zip(&values) {
+            assert!((a - b).abs() < 1e-6, "{}: {} vs {}", file_name, a, b);
+        }
+    }
+}
+
+#[test]
+fn test_curve_import_errors() {
+    let mut sampler = ExpressionNodeWithId::<Sampler1d>::new_default();
+
+    assert!(sampler.export(&temp_file_path("curve.txt")).is_err());
+
+    let path = temp_file_path("bad_curve.csv");
+    std::fs::write(&path, "0.5, 1.0\nhello\n").unwrap();
+    let result = sampler.import(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+
+    // Too few values
+    assert!(sampler.set_values(&[1.0]).is_err());
+    assert_eq!(sampler.length(), Sampler1d::DEFAULT_LENGTH);
+}
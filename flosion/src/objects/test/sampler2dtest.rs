@@ -0,0 +1,164 @@
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::{
+            compiledexpression::Discretization,
+            jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+        },
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::{purefunctions::Constant, sampler2d::Sampler2d},
+    ui_core::arguments::ParsedArguments,
+};
+
+const NUM_SAMPLES: usize = 1000;
+
+/// Evaluate a sampler holding the given grid at the given y, with x swept
+/// from 0 to 2, such that x reaches 1 halfway through
+fn eval_sampler(values: &[f32], width: usize, height: usize, y: f32) -> Vec<f32> {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+
+    let arg_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+
+    let graph = expr.graph_mut();
+
+    let sampler = ExpressionNodeWithId::<Sampler2d>::new_from_args(
+        &ParsedArguments::new_empty()
+            .add_or_replace(&Sampler2d::ARG_WIDTH, width)
+            .add_or_replace(&Sampler2d::ARG_HEIGHT, height),
+    );
+    assert_eq!(sampler.width(), width);
+    assert_eq!(sampler.height(), height);
+    sampler.value().write(values);
+    let sampler_id = sampler.id();
+    let input_locations = (&sampler as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(sampler));
+
+    let constant = ExpressionNodeWithId::<Constant>::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, y as f64),
+    );
+    let constant_id = constant.id();
+    graph.add_expression_node(Box::new(constant));
+
+    graph
+        .connect_input(
+            input_locations[0],
+            Some(ExpressionTarget::Parameter(arg_param)),
+        )
+        .unwrap();
+    graph
+        .connect_input(
+            input_locations[1],
+            Some(ExpressionTarget::Node(constant_id)),
+        )
+        .unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(sampler_id))
+        .unwrap();
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(ExpressionTestDomain::WithRespectTo(
+                argument_location,
+                Interval::Linear { from: 0.0, to: 2.0 },
+            )),
+        )
+        .unwrap();
+
+    let mut output = vec![0.0; NUM_SAMPLES];
+    artefact
+        .make_function()
+        .eval_in_test_mode(&mut [&mut output], Discretization::None);
+    output
+}
+
+/// The x value at which the sample at the given index was read
+fn x_at(i: usize) -> f32 {
+    2.0 * i as f32 / NUM_SAMPLES as f32
+}
+
+#[test]
+fn test_corners_return_stored_values() {
+    let width = 5;
+    let height = 3;
+    let values: Vec<f32> = (0..(width * height))
+        .map(|i| ((i * 7) % 11) as f32 - 5.0)
+        .collect();
+
+    let bottom_left = values[0];
+    let bottom_right = values[width - 1];
+    let top_left = values[(height - 1) * width];
+    let top_right = values[height * width - 1];
+
+    for (y, left, right) in [(0.0, bottom_left, bottom_right), (1.0, top_left, top_right)] {
+        assert_eq!(Sampler2d::sample(&values, width, 0.0, y), left);
+        assert_eq!(Sampler2d::sample(&values, width, 1.0, y), right);
+
+        let output = eval_sampler(&values, width, height, y);
+        assert_eq!(output[0], left);
+        assert_eq!(output[NUM_SAMPLES / 2], right);
+
+        // Reads beyond the grid are clamped to its edges
+        for v in &output[(NUM_SAMPLES / 2)..] {
+            assert_eq!(*v, right);
+        }
+    }
+}
+
+#[test]
+fn test_interior_reads_interpolate_bilinearly() {
+    let width = 4;
+    let height = 3;
+
+    // Bilinear interpolation reproduces a bilinear function exactly
+    let f = |x: f32, y: f32| 0.5 - 2.0 * x + 3.0 * y + 4.0 * x * y;
+    let values: Vec<f32> = (0..height)
+        .flat_map(|j| {
+            (0..width).map(move |i| {
+                f(
+                    i as f32 / (width - 1) as f32,
+                    j as f32 / (height - 1) as f32,
+                )
+            })
+        })
+        .collect();
+
+    for y in [0.0, 0.2, 0.5, 0.9, 1.0] {
+        let output = eval_sampler(&values, width, height, y);
+        for (i, v) in output[..=(NUM_SAMPLES / 2)].iter().enumerate() {
+            let x = x_at(i);
+            let expected = f(x, y);
+            assert!(
+                (v - expected).abs() < 1e-5,
+                "At ({}, {}), read {} but expected {}",
+                x,
+                y,
+                v,
+                expected
+            );
+            assert!((Sampler2d::sample(&values, width, x, y) - expected).abs() < 1e-5);
+        }
+    }
+
+    // Halfway between four entries is their average
+    let values = [0.0, 1.0, 2.0, 7.0];
+    let v = Sampler2d::sample(&values, 2, 0.5, 0.5);
+    assert!((v - 2.5).abs() < 1e-6);
+}
@@ -0,0 +1,80 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::slew::apply_slew,
+};
+
+fn make_chunk(value: f32) -> SoundChunk {
+    SoundChunk {
+        l: [value; CHUNK_SIZE],
+        r: [value; CHUNK_SIZE],
+    }
+}
+
+/// Run a constant signal through the slew limiter for the given number
+/// of chunks, starting from the given previous output, and return the
+/// left channel of every processed sample
+fn slew(value: f32, start: f32, rise: f32, fall: f32, chunks: usize) -> Vec<f32> {
+    let mut previous = (start, start);
+    let mut samples = Vec::new();
+    for _ in 0..chunks {
+        let mut chunk = make_chunk(value);
+        apply_slew(
+            &mut chunk,
+            &mut previous,
+            &[rise; CHUNK_SIZE],
+            &[fall; CHUNK_SIZE],
+        );
+        assert_eq!(chunk.l, chunk.r);
+        samples.extend_from_slice(&chunk.l);
+    }
+    samples
+}
+
+#[test]
+fn test_step_rises_at_slew_rate() {
+    // Rising by 10 units per second takes a tenth of a second to reach 1
+    let rise = 10.0;
+    let samples = slew(1.0, 0.0, rise, 100.0, SAMPLE_FREQUENCY / CHUNK_SIZE / 5);
+    let step = rise / SAMPLE_FREQUENCY as f32;
+    let rise_samples = SAMPLE_FREQUENCY / 10;
+
+    // Every sample until the output arrives is one step further
+    let arrival = samples.iter().position(|s| *s == 1.0).unwrap();
+    assert!(
+        arrival.abs_diff(rise_samples - 1) <= 1,
+        "Expected to arrive after {} samples, arrived after {}",
+        rise_samples,
+        arrival + 1
+    );
+    assert!((samples[0] - step).abs() < 1e-9);
+    for (i, w) in samples[..arrival].windows(2).enumerate() {
+        assert!(
+            (w[1] - w[0] - step).abs() < 1e-6,
+            "At sample {}, expected a step of {}, got {}",
+            i + 1,
+            step,
+            w[1] - w[0]
+        );
+    }
+
+    // Once the output arrives, it stays put
+    assert!(samples[arrival..].iter().all(|s| *s == 1.0));
+}
+
+#[test]
+fn test_rise_and_fall_are_separate() {
+    let len = SAMPLE_FREQUENCY / CHUNK_SIZE;
+
+    // A fast fall reaches the target immediately while a slow
+    // rise barely moves at all
+    let falling = slew(0.0, 1.0, 0.1, 1e6, len);
+    assert!(falling.iter().all(|s| *s == 0.0));
+    let rising = slew(1.0, 0.0, 0.1, 1e6, len);
+    assert!((rising.last().unwrap() - 0.1).abs() < 1e-3);
+
+    // A rate of zero holds the output in place
+    assert!(slew(1.0, 0.5, 0.0, 0.0, 4).iter().all(|s| *s == 0.5));
+}
@@ -0,0 +1,136 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    core::samplefrequency::SAMPLE_FREQUENCY,
+    objects::spectralgate::{
+        NoiseProfileLearner, SpectralGateChannel, SpectralGateParameters, BIN_COUNT, FRAME_SIZE,
+    },
+};
+
+const PARAMETERS: SpectralGateParameters = SpectralGateParameters {
+    threshold: -30.0,
+    reduction: 40.0,
+};
+
+/// Number of samples processed in each test
+const LEN: usize = 32768;
+
+/// The gate's output lags its input by one frame
+const LATENCY: usize = FRAME_SIZE;
+
+fn white_noise(amplitude: f32, seed: u64) -> Vec<f32> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    (0..LEN)
+        .map(|_| amplitude * rng.gen_range(-1.0..=1.0))
+        .collect()
+}
+
+fn tone(amplitude: f32, frequency: f32) -> Vec<f32> {
+    (0..LEN)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_FREQUENCY as f32;
+            amplitude * (std::f32::consts::TAU * frequency * t).sin()
+        })
+        .collect()
+}
+
+/// Energy of the output compared to that of the input, ignoring the
+/// first second of output while the gate fills up and aligning the
+/// output with the input
+fn energy_ratio(input: &[f32], output: &[f32], latency: usize) -> f32 {
+    let start = 2 * latency;
+    let input_energy: f32 = input[(start - latency)..(LEN - latency)]
+        .iter()
+        .map(|x| x * x)
+        .sum();
+    let output_energy: f32 = output[start..].iter().map(|x| x * x).sum();
+    output_energy / input_energy
+}
+
+/// Process the signal in chunk-sized pieces
+fn gate(
+    channel: &mut SpectralGateChannel,
+    signal: &[f32],
+    parameters: SpectralGateParameters,
+    noise_profile: Option<&[f32]>,
+    mut learner: Option<&mut NoiseProfileLearner>,
+) -> Vec<f32> {
+    let mut output = signal.to_vec();
+    for piece in output.chunks_mut(1024) {
+        channel.process(piece, parameters, noise_profile, learner.as_deref_mut());
+    }
+    output
+}
+
+#[test]
+fn test_quiet_noise_is_attenuated() {
+    let noise = white_noise(0.1, 1);
+    let mut channel = SpectralGateChannel::new();
+    let output = gate(&mut channel, &noise, PARAMETERS, None, None);
+    let ratio = energy_ratio(&noise, &output, LATENCY);
+    assert!(ratio < 0.01, "noise energy ratio was {}", ratio);
+}
+
+#[test]
+fn test_loud_tone_is_preserved() {
+    let noise = white_noise(0.1, 2);
+    let tone = tone(0.5, 1000.0);
+    let noisy_tone: Vec<f32> = tone.iter().zip(&noise).map(|(a, b)| a + b).collect();
+
+    let mut channel = SpectralGateChannel::new();
+    let output = gate(&mut channel, &noisy_tone, PARAMETERS, None, None);
+
+    // Almost all of the noise is removed while the tone passes through
+    let residual: Vec<f32> = (0..LEN)
+        .map(|i| {
+            if i < LATENCY {
+                0.0
+            } else {
+                output[i] - tone[i - LATENCY]
+            }
+        })
+        .collect();
+    let tone_ratio = energy_ratio(&tone, &output, LATENCY);
+    assert!(
+        (tone_ratio - 1.0).abs() < 0.05,
+        "tone energy ratio was {}",
+        tone_ratio
+    );
+    let residual_energy: f32 = residual[(2 * LATENCY)..].iter().map(|x| x * x).sum();
+    let noise_energy: f32 = noise[LATENCY..(LEN - LATENCY)].iter().map(|x| x * x).sum();
+    assert!(
+        residual_energy < 0.1 * noise_energy,
+        "residual energy was {} of the noise's",
+        residual_energy / noise_energy
+    );
+}
+
+#[test]
+fn test_learned_noise_profile_gates_noise() {
+    // With the threshold out of the way, noise passes through the gate
+    // until its profile has been learned
+    let parameters = SpectralGateParameters {
+        threshold: -100.0,
+        ..PARAMETERS
+    };
+    let noise = white_noise(0.1, 3);
+    let mut channel = SpectralGateChannel::new();
+    let output = gate(&mut channel, &noise, parameters, None, None);
+    let ratio = energy_ratio(&noise, &output, LATENCY);
+    assert!(ratio > 0.9, "noise energy ratio was {}", ratio);
+
+    let mut learner = NoiseProfileLearner::new();
+    let mut channel = SpectralGateChannel::new();
+    gate(&mut channel, &noise, parameters, None, Some(&mut learner));
+    let mut profile = vec![0.0; BIN_COUNT];
+    assert!(learner.write_profile(&mut profile));
+
+    // Gating with a profile three times as high as the average level catches
+    // nearly every bin of fresh noise with the same spectrum
+    let profile: Vec<f32> = profile.iter().map(|p| 3.0 * p).collect();
+    let fresh_noise = white_noise(0.1, 4);
+    let mut channel = SpectralGateChannel::new();
+    let output = gate(&mut channel, &fresh_noise, parameters, Some(&profile), None);
+    let ratio = energy_ratio(&fresh_noise, &output, LATENCY);
+    assert!(ratio < 0.05, "noise energy ratio was {}", ratio);
+}
@@ -0,0 +1,48 @@
+use crate::{
+    core::soundchunk::{SoundChunk, CHUNK_SIZE},
+    objects::stereowidth::apply_stereo_width,
+};
+
+fn make_stereo_chunk() -> SoundChunk {
+    let mut chunk = SoundChunk::new();
+    for i in 0..CHUNK_SIZE {
+        let t = i as f32 / CHUNK_SIZE as f32;
+        chunk.l[i] = (t * std::f32::consts::TAU).sin();
+        chunk.r[i] = 0.25 - t;
+    }
+    chunk
+}
+
+fn widen_by(width: f32) -> (SoundChunk, SoundChunk) {
+    let original = make_stereo_chunk();
+    let mut chunk = original;
+    apply_stereo_width(&mut chunk, &[width; CHUNK_SIZE]);
+    (original, chunk)
+}
+
+#[test]
+fn test_zero_width_is_mono() {
+    let (original, chunk) = widen_by(0.0);
+    for ((l, r), (orig_l, orig_r)) in chunk.samples().zip(original.samples()) {
+        assert_eq!(l, r);
+        assert!((l - 0.5 * (orig_l + orig_r)).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_unit_width_is_unchanged() {
+    let (original, chunk) = widen_by(1.0);
+    for ((l, r), (orig_l, orig_r)) in chunk.samples().zip(original.samples()) {
+        assert!((l - orig_l).abs() < 1e-6);
+        assert!((r - orig_r).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_double_width_doubles_side() {
+    let (original, chunk) = widen_by(2.0);
+    for ((l, r), (orig_l, orig_r)) in chunk.samples().zip(original.samples()) {
+        assert!(((l + r) - (orig_l + orig_r)).abs() < 1e-5);
+        assert!(((l - r) - 2.0 * (orig_l - orig_r)).abs() < 1e-5);
+    }
+}
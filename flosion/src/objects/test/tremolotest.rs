@@ -0,0 +1,84 @@
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::tremolo::{apply_tremolo, TremoloWaveform},
+};
+
+fn make_chunk(value: f32) -> SoundChunk {
+    SoundChunk {
+        l: [value; CHUNK_SIZE],
+        r: [value; CHUNK_SIZE],
+    }
+}
+
+/// Run one second of a constant signal through the tremolo and
+/// return every processed sample
+fn modulate(depth: f32, waveform: TremoloWaveform, auto_pan: bool) -> Vec<(f32, f32)> {
+    let mut phase = 0.0;
+    let mut samples = Vec::new();
+    for _ in 0..(SAMPLE_FREQUENCY / CHUNK_SIZE) {
+        let mut chunk = make_chunk(0.5);
+        apply_tremolo(
+            &mut chunk,
+            &mut phase,
+            &[5.0; CHUNK_SIZE],
+            &[depth; CHUNK_SIZE],
+            waveform,
+            auto_pan,
+        );
+        samples.extend(chunk.samples());
+    }
+    samples
+}
+
+#[test]
+fn test_zero_depth_is_unchanged() {
+    for waveform in [
+        TremoloWaveform::Sine,
+        TremoloWaveform::Triangle,
+        TremoloWaveform::Square,
+    ] {
+        for auto_pan in [false, true] {
+            for (l, r) in modulate(0.0, waveform, auto_pan) {
+                assert_eq!(l, 0.5);
+                assert_eq!(r, 0.5);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_full_depth_fully_modulates() {
+    for waveform in [
+        TremoloWaveform::Sine,
+        TremoloWaveform::Triangle,
+        TremoloWaveform::Square,
+    ] {
+        let samples = modulate(1.0, waveform, false);
+        let min = samples
+            .iter()
+            .map(|(l, _)| *l)
+            .fold(f32::INFINITY, f32::min);
+        let max = samples
+            .iter()
+            .map(|(l, _)| *l)
+            .fold(f32::NEG_INFINITY, f32::max);
+        assert!(min.abs() < 1e-3, "{:?} minimum was {}", waveform, min);
+        assert!(
+            (max - 0.5).abs() < 1e-3,
+            "{:?} maximum was {}",
+            waveform,
+            max
+        );
+        assert!(samples.iter().all(|(l, r)| l == r));
+    }
+}
+
+#[test]
+fn test_auto_pan_is_opposite() {
+    for (l, r) in modulate(1.0, TremoloWaveform::Sine, true) {
+        assert!((l + r - 0.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    core::jit::compiledexpression::Discretization, objects::statefulfunctions::Trigger,
+    ui_core::arguments::ParsedArguments,
+};
+
+use super::functionstest::eval_single_node_expression;
+
+fn trigger(input: &[f32], threshold: f32) -> Vec<f32> {
+    let threshold = vec![threshold; input.len()];
+    let mut output = vec![0.0; input.len()];
+    eval_single_node_expression::<Trigger>(
+        &ParsedArguments::new_empty(),
+        &[input, &threshold],
+        &mut output,
+        Discretization::samplewise_temporal(),
+    );
+    output
+}
+
+#[test]
+fn test_rising_ramp_triggers_once() {
+    let ramp: Vec<f32> = (0..100).map(|i| i as f32 / 100.0).collect();
+    let output = trigger(&ramp, 0.5);
+
+    // The pulse lands on the first sample above the threshold
+    let pulses: Vec<usize> = (0..output.len()).filter(|i| output[*i] != 0.0).collect();
+    assert_eq!(pulses, vec![51]);
+    assert_eq!(output[51], 1.0);
+}
+
+#[test]
+fn test_falling_ramp_triggers_only_at_start() {
+    // The ramp starts above the threshold, which counts as rising
+    // above it, but never crosses it upwards again
+    let ramp: Vec<f32> = (0..100).map(|i| -(i as f32) / 100.0).collect();
+    let output = trigger(&ramp, -0.5);
+    let pulses: Vec<usize> = (0..output.len()).filter(|i| output[*i] != 0.0).collect();
+    assert_eq!(pulses, vec![0]);
+}
+
+#[test]
+fn test_gate_triggers_on_each_rising_edge() {
+    let gate: Vec<f32> = (0..100)
+        .map(|i| if (i / 10) % 2 == 1 { 1.0 } else { 0.0 })
+        .collect();
+    let output = trigger(&gate, 0.5);
+    let pulses: Vec<usize> = (0..output.len()).filter(|i| output[*i] != 0.0).collect();
+    assert_eq!(pulses, vec![10, 30, 50, 70, 90]);
+}
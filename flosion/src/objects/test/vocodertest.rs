@@ -0,0 +1,153 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+    },
+    objects::vocoder::{band_frequencies, VocoderBank},
+};
+
+const LOW_FREQUENCY: f32 = 300.0;
+const HIGH_FREQUENCY: f32 = 4000.0;
+
+/// A crude spoken phrase: a voiced syllable, a pause, an unvoiced
+/// syllable, and another pause. Each entry is the segment's length
+/// in seconds and the frequency of its tone, if any.
+const PHRASE: [(f32, Option<f32>); 4] = [
+    (0.15, Some(LOW_FREQUENCY)),
+    (0.1, None),
+    (0.15, Some(HIGH_FREQUENCY)),
+    (0.1, None),
+];
+
+/// Segment boundaries in samples
+fn segments() -> Vec<(usize, usize, Option<f32>)> {
+    let mut start = 0;
+    PHRASE
+        .iter()
+        .map(|(seconds, frequency)| {
+            let len = (seconds * SAMPLE_FREQUENCY as f32) as usize;
+            let segment = (start, start + len, *frequency);
+            start += len;
+            segment
+        })
+        .collect()
+}
+
+/// Vocode white noise with the phrase, returning the left channel
+fn vocode_phrase(num_bands: usize) -> Vec<f32> {
+    let segments = segments();
+    let len = segments.last().unwrap().1;
+    let modulator: Vec<f32> = (0..len)
+        .map(|i| {
+            let frequency = segments
+                .iter()
+                .find(|(start, end, _)| i >= *start && i < *end)
+                .and_then(|(_, _, f)| *f);
+            match frequency {
+                Some(f) => {
+                    let t = i as f32 / SAMPLE_FREQUENCY as f32;
+                    0.5 * (std::f32::consts::TAU * f * t).sin()
+                }
+                None => 0.0,
+            }
+        })
+        .collect();
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    let mut bank = VocoderBank::new(num_bands);
+    let mut output = Vec::new();
+    for piece in modulator.chunks(CHUNK_SIZE) {
+        let mut modulator_chunk = SoundChunk::new();
+        modulator_chunk.l[..piece.len()].copy_from_slice(piece);
+        modulator_chunk.r[..piece.len()].copy_from_slice(piece);
+        let mut carrier = SoundChunk::new();
+        for s in carrier.l.iter_mut().chain(carrier.r.iter_mut()) {
+            *s = rng.gen_range(-0.5..=0.5);
+        }
+        bank.process(&mut carrier, &modulator_chunk);
+        output.extend_from_slice(&carrier.l[..piece.len()]);
+    }
+    output
+}
+
+/// The average magnitude of the signal's discrete-time Fourier
+/// transform within 100 Hz of the given frequency
+fn magnitude_near(signal: &[f32], frequency: f32) -> f32 {
+    let offsets: Vec<f32> = (-10..=10).map(|i| i as f32 * 10.0).collect();
+    let total: f32 = offsets
+        .iter()
+        .map(|offset| {
+            let w = std::f32::consts::TAU * (frequency + offset) / SAMPLE_FREQUENCY as f32;
+            let (re, im) = signal
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (n, x)| {
+                    let phase = w * n as f32;
+                    (re + x * phase.cos(), im - x * phase.sin())
+                });
+            (re * re + im * im).sqrt()
+        })
+        .sum();
+    total / offsets.len() as f32
+}
+
+fn rms(signal: &[f32]) -> f32 {
+    (signal.iter().map(|x| x * x).sum::<f32>() / signal.len() as f32).sqrt()
+}
+
+#[test]
+fn test_bands_span_speech_range() {
+    let frequencies = band_frequencies(16);
+    assert_eq!(frequencies.len(), 16);
+    assert!(frequencies[0] <= LOW_FREQUENCY);
+    assert!(*frequencies.last().unwrap() >= HIGH_FREQUENCY);
+    for pair in frequencies.windows(2) {
+        assert!(pair[1] > pair[0]);
+    }
+}
+
+#[test]
+fn test_modulator_envelope_shapes_carrier_spectrum() {
+    let output = vocode_phrase(16);
+
+    // Skip the start of each segment, while band envelopes settle
+    let settle = (0.06 * SAMPLE_FREQUENCY as f32) as usize;
+    let mut syllable_levels = Vec::new();
+    let mut pause_levels = Vec::new();
+    for (start, end, frequency) in segments() {
+        let segment = &output[(start + settle)..end];
+        match frequency {
+            Some(f) => {
+                let (this, other) = if f == LOW_FREQUENCY {
+                    (LOW_FREQUENCY, HIGH_FREQUENCY)
+                } else {
+                    (HIGH_FREQUENCY, LOW_FREQUENCY)
+                };
+                let here = magnitude_near(segment, this);
+                let there = magnitude_near(segment, other);
+                assert!(
+                    here > 10.0 * there,
+                    "At {} Hz, the carrier's magnitude was {} compared to {} at {} Hz",
+                    this,
+                    here,
+                    there,
+                    other
+                );
+                syllable_levels.push(rms(segment));
+            }
+            None => pause_levels.push(rms(segment)),
+        }
+    }
+
+    // The carrier dies away between syllables
+    let quietest_syllable = syllable_levels.iter().cloned().fold(f32::MAX, f32::min);
+    let loudest_pause = pause_levels.iter().cloned().fold(0.0, f32::max);
+    assert!(
+        loudest_pause < 0.1 * quietest_syllable,
+        "Pauses reached {} while syllables reached {}",
+        loudest_pause,
+        quietest_syllable
+    );
+}
@@ -0,0 +1,337 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            context::{AudioContext, AudioStack},
+            expression::ExpressionParameterTarget,
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::{
+        purefunctions::{Constant, Multiply, SineWave},
+        wavegenerator::{
+            bandlimited_pulse, bandlimited_wave, WaveGenerator, WaveGeneratorWaveform,
+        },
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+const FREQUENCY: f64 = 3000.0;
+
+/// One tenth of a second, which holds a whole number of cycles
+const LEN: usize = SAMPLE_FREQUENCY / 10;
+
+/// Render one tenth of a second of the given waveform function of phase
+fn render<F: Fn(f32) -> f32>(f: F) -> Vec<f32> {
+    let dt = FREQUENCY / SAMPLE_FREQUENCY as f64;
+    (0..LEN)
+        .map(|i| f((i as f64 * dt).fract() as f32))
+        .collect()
+}
+
+/// Render the left channel of a wave generator with the given waveform,
+/// constant frequency and constant pulse width
+fn render_wavegenerator(
+    waveform: WaveGeneratorWaveform,
+    frequency: f32,
+    pulse_width: f32,
+    len: usize,
+) -> Vec<f32> {
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    wavegen.set_waveform(waveform);
+    wavegen.frequency.graph_mut().results_mut()[0].set_default_value(frequency);
+    wavegen.pulse_width.graph_mut().results_mut()[0].set_default_value(pulse_width);
+    render_processor(wavegen, len)
+}
+
+/// Render the left channel of the given wave generator
+fn render_processor(wavegen: SoundProcessorWithId<WaveGenerator>, len: usize) -> Vec<f32> {
+    let id = wavegen.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(wavegen));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let wavegen = graph
+        .sound_processor(id)
+        .unwrap()
+        .downcast::<WaveGenerator>()
+        .unwrap();
+
+    let mut compiled = wavegen.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut output = Vec::new();
+
+    while output.len() < len {
+        let mut context = AudioContext::new(
+            id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        WaveGenerator::process_audio(&mut compiled, &mut chunk, &mut context);
+        processor_timing.advance_one_chunk();
+
+        output.extend_from_slice(&chunk.l);
+    }
+
+    output.truncate(len);
+    output
+}
+
+/// Connect the sync expression of a wave generator so that it rises
+/// above zero at the start of every cycle of the given frequency
+fn sync_to_frequency(wavegen: &mut SoundProcessorWithId<WaveGenerator>, frequency: f32) {
+    let id = wavegen.id();
+    let time_param = wavegen
+        .sync
+        .add_target(ExpressionParameterTarget::ProcessorTime(id));
+    let graph = wavegen.sync.graph_mut();
+
+    let constant = ExpressionNodeWithId::<Constant>::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, frequency as f64),
+    );
+    let constant_id = constant.id();
+    graph.add_expression_node(Box::new(constant));
+
+    let multiply = ExpressionNodeWithId::<Multiply>::new_default();
+    let multiply_id = multiply.id();
+    let multiply_inputs = (&multiply as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(multiply));
+    graph
+        .connect_input(
+            multiply_inputs[0],
+            Some(ExpressionTarget::Parameter(time_param)),
+        )
+        .unwrap();
+    graph
+        .connect_input(
+            multiply_inputs[1],
+            Some(ExpressionTarget::Node(constant_id)),
+        )
+        .unwrap();
+
+    let sine = ExpressionNodeWithId::<SineWave>::new_default();
+    let sine_id = sine.id();
+    let sine_inputs = (&sine as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(sine));
+    graph
+        .connect_input(sine_inputs[0], Some(ExpressionTarget::Node(multiply_id)))
+        .unwrap();
+
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(sine_id))
+        .unwrap();
+}
+
+/// The energy of a signal in the k-th bin of its discrete Fourier transform
+fn bin_energy(signal: &[f32], k: usize) -> f64 {
+    let n = signal.len();
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, x) in signal.iter().enumerate() {
+        let angle = std::f64::consts::TAU * (k * i % n) as f64 / n as f64;
+        re += *x as f64 * angle.cos();
+        im -= *x as f64 * angle.sin();
+    }
+    re * re + im * im
+}
+
+/// The fraction of a signal's energy which lies outside of the harmonics
+/// of `FREQUENCY` below the Nyquist frequency, i.e. which is aliased
+fn alias_fraction(signal: &[f32]) -> f64 {
+    let n = signal.len();
+    let total: f64 = signal.iter().map(|x| (*x as f64) * (*x as f64)).sum();
+    let cycles = (FREQUENCY * n as f64 / SAMPLE_FREQUENCY as f64).round() as usize;
+
+    let mut harmonic = bin_energy(signal, 0);
+    let mut k = cycles;
+    while 2 * k < n {
+        harmonic += 2.0 * bin_energy(signal, k);
+        k += cycles;
+    }
+
+    1.0 - harmonic / (n as f64 * total)
+}
+
+#[test]
+fn test_bandlimited_waveforms_reduce_aliasing() {
+    let dt = (FREQUENCY / SAMPLE_FREQUENCY as f64) as f32;
+
+    let naive_saw = |p: f32| 2.0 * p - 1.0;
+    let naive_square = |p: f32| if p < 0.5 { 1.0 } else { -1.0 };
+    let naive_triangle = |p: f32| 1.0 - 4.0 * (p - 0.5).abs();
+
+    let cases: [(WaveGeneratorWaveform, &dyn Fn(f32) -> f32); 3] = [
+        (WaveGeneratorWaveform::Saw, &naive_saw),
+        (WaveGeneratorWaveform::Square, &naive_square),
+        (WaveGeneratorWaveform::Triangle, &naive_triangle),
+    ];
+
+    for (waveform, naive) in cases {
+        let naive_aliasing = alias_fraction(&render(naive));
+        let bandlimited_aliasing = alias_fraction(&render(|p| bandlimited_wave(waveform, p, dt)));
+        assert!(
+            bandlimited_aliasing < 0.25 * naive_aliasing,
+            "{:?}: expected much less aliasing than {} with bandlimiting, got {}",
+            waveform,
+            naive_aliasing,
+            bandlimited_aliasing
+        );
+    }
+}
+
+#[test]
+fn test_bandlimited_waveforms_stay_in_range() {
+    for waveform in [
+        WaveGeneratorWaveform::Sine,
+        WaveGeneratorWaveform::Saw,
+        WaveGeneratorWaveform::Square,
+        WaveGeneratorWaveform::Triangle,
+    ] {
+        for dt in [0.001, 0.01, 0.1, 0.5] {
+            for i in 0..1000 {
+                let v = bandlimited_wave(waveform, i as f32 / 1000.0, dt);
+                assert!(
+                    v.abs() <= 1.01,
+                    "{:?} with dt {} reached {}",
+                    waveform,
+                    dt,
+                    v
+                );
+            }
+        }
+    }
+}
+
+/// A frequency with exactly 100 samples per cycle
+const PULSE_FREQUENCY: f32 = 441.0;
+
+/// A whole number of cycles at `PULSE_FREQUENCY`
+const PULSE_LEN: usize = 4400;
+
+#[test]
+fn test_pulse_width_sets_duty_cycle() {
+    for width in [0.5, 0.25, 0.8] {
+        let output = render_wavegenerator(
+            WaveGeneratorWaveform::Square,
+            PULSE_FREQUENCY,
+            width,
+            PULSE_LEN,
+        );
+        let duty_cycle = output.iter().filter(|x| **x > 0.0).count() as f32 / PULSE_LEN as f32;
+        assert!(
+            (duty_cycle - width).abs() < 0.01,
+            "With pulse width {}, expected a duty cycle of {}, got {}",
+            width,
+            width,
+            duty_cycle
+        );
+    }
+
+    // Widths at and beyond the extremes still give a pulse
+    for width in [0.0, -1.0, 1.0, 2.0, f32::NAN] {
+        let output = render_wavegenerator(
+            WaveGeneratorWaveform::Square,
+            PULSE_FREQUENCY,
+            width,
+            PULSE_LEN,
+        );
+        assert!(output.iter().all(|x| x.is_finite() && x.abs() <= 1.01));
+        assert!(output.iter().any(|x| *x > 0.5));
+        assert!(output.iter().any(|x| *x < -0.5));
+    }
+}
+
+#[test]
+fn test_pulse_width_shapes_harmonics() {
+    let dt = PULSE_FREQUENCY / SAMPLE_FREQUENCY as f32;
+    let cycles = PULSE_LEN / 100;
+    let harmonics = |width: f32| -> Vec<f64> {
+        let signal: Vec<f32> = (0..PULSE_LEN)
+            .map(|i| bandlimited_pulse((i as f32 * dt).fract(), dt, width))
+            .collect();
+        (1..=4).map(|k| bin_energy(&signal, cycles * k)).collect()
+    };
+
+    // The k-th harmonic of a pulse with width w has amplitude
+    // proportional to |sin(pi * k * w)| / k, so a square wave has no
+    // even harmonics and a quarter-width pulse has no fourth harmonic
+    let square = harmonics(0.5);
+    assert!(square[1] / square[0] < 1e-4);
+    assert!(square[3] / square[0] < 1e-4);
+
+    let quarter = harmonics(0.25);
+    assert!((quarter[1] / quarter[0] - 0.5).abs() < 0.02);
+    assert!(quarter[3] / quarter[0] < 1e-4);
+
+    // Narrower pulses are brighter, with relatively stronger upper harmonics
+    let narrow = harmonics(0.1);
+    assert!(narrow[1] / narrow[0] > quarter[1] / quarter[0]);
+    assert!(narrow[2] / narrow[0] > quarter[2] / quarter[0]);
+}
+
+#[test]
+fn test_hard_sync_locks_period_to_master() {
+    // 300 Hz is exactly 147 samples per cycle
+    let master_frequency = 300.0;
+    let master_period = 147;
+
+    // The slave runs at a frequency which is not a multiple of the master's
+    let render_slave = |synced: bool| -> Vec<f32> {
+        let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+        wavegen.set_waveform(WaveGeneratorWaveform::Saw);
+        wavegen.frequency.graph_mut().results_mut()[0].set_default_value(700.0);
+        if synced {
+            sync_to_frequency(&mut wavegen, master_frequency);
+        }
+        render_processor(wavegen, LEN)
+    };
+
+    let max_period_difference = |output: &[f32]| -> f32 {
+        output[master_period..]
+            .iter()
+            .zip(output)
+            .skip(master_period)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max)
+    };
+
+    let unsynced = render_slave(false);
+    let synced = render_slave(true);
+
+    assert!(max_period_difference(&unsynced) > 0.5);
+    assert!(
+        max_period_difference(&synced) < 0.01,
+        "Expected the synced output to repeat every {} samples, but it differed by {}",
+        master_period,
+        max_period_difference(&synced)
+    );
+
+    // The phase is reset partway through the slave's cycle, so
+    // the output is no longer a plain saw
+    assert!(unsynced
+        .iter()
+        .zip(&synced)
+        .any(|(a, b)| (a - b).abs() > 0.5));
+}
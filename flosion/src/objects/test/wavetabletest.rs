@@ -0,0 +1,135 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorWithId,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::wavetable::{Wavetable, WAVETABLE_SIZE},
+};
+
+fn sine_table() -> Vec<f32> {
+    (0..WAVETABLE_SIZE)
+        .map(|i| (std::f32::consts::TAU * i as f32 / WAVETABLE_SIZE as f32).sin())
+        .collect()
+}
+
+fn saw_table() -> Vec<f32> {
+    (0..WAVETABLE_SIZE)
+        .map(|i| 2.0 * i as f32 / WAVETABLE_SIZE as f32 - 1.0)
+        .collect()
+}
+
+/// Render the left channel of a wavetable oscillator with the given
+/// tables, constant frequency and constant morph
+fn render(tables: Vec<Vec<f32>>, frequency: f32, morph: f32, len: usize) -> Vec<f32> {
+    let mut wavetable = SoundProcessorWithId::<Wavetable>::new_default();
+    wavetable.set_tables(tables);
+    wavetable.frequency.graph_mut().results_mut()[0].set_default_value(frequency);
+    wavetable.morph.graph_mut().results_mut()[0].set_default_value(morph);
+
+    let id = wavetable.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(wavetable));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let wavetable = graph
+        .sound_processor(id)
+        .unwrap()
+        .downcast::<Wavetable>()
+        .unwrap();
+
+    let mut compiled = wavetable.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let mut processor_timing = ProcessorTiming::new();
+
+    let mut output = Vec::new();
+
+    while output.len() < len {
+        let mut context = AudioContext::new(
+            id,
+            &processor_timing,
+            &scratch_arena,
+            argument_stack.view_at_bottom(),
+            AudioStack::Root,
+        );
+        let mut chunk = SoundChunk::new();
+        Wavetable::process_audio(&mut compiled, &mut chunk, &mut context);
+        processor_timing.advance_one_chunk();
+
+        output.extend_from_slice(&chunk.l);
+    }
+
+    output.truncate(len);
+    output
+}
+
+/// Estimate the fundamental frequency of a signal from the times at
+/// which it first and last crosses zero going upwards
+fn estimate_fundamental(signal: &[f32]) -> f32 {
+    let crossings: Vec<f32> = signal
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0] < 0.0 && w[1] >= 0.0)
+        .map(|(i, w)| i as f32 + w[0] / (w[0] - w[1]))
+        .collect();
+    let first = crossings.first().unwrap();
+    let last = crossings.last().unwrap();
+    let periods = (crossings.len() - 1) as f32;
+    SAMPLE_FREQUENCY as f32 * periods / (last - first)
+}
+
+#[test]
+fn test_fundamental_matches_frequency() {
+    for frequency in [55.0, 220.0, 440.0, 1234.5] {
+        let output = render(vec![sine_table()], frequency, 0.0, SAMPLE_FREQUENCY / 2);
+        let estimate = estimate_fundamental(&output);
+        assert!(
+            (estimate - frequency).abs() / frequency < 0.001,
+            "Expected {} Hz, got {} Hz",
+            frequency,
+            estimate
+        );
+    }
+}
+
+#[test]
+fn test_morph_interpolates_between_tables() {
+    let len = 4096;
+    let tables = vec![sine_table(), saw_table()];
+    let first = render(tables.clone(), 330.0, 0.0, len);
+    let second = render(tables.clone(), 330.0, 1.0, len);
+
+    for morph in [0.25, 0.5, 0.75] {
+        let output = render(tables.clone(), 330.0, morph, len);
+        for ((o, a), b) in output.iter().zip(&first).zip(&second) {
+            let expected = a + morph * (b - a);
+            assert!(
+                (o - expected).abs() < 1e-5,
+                "With morph {}, expected {}, got {}",
+                morph,
+                expected,
+                o
+            );
+        }
+    }
+
+    // The morph is clamped to the available tables
+    assert_eq!(render(tables.clone(), 330.0, -1.0, len), first);
+    assert_eq!(render(tables, 330.0, 5.0, len), second);
+}
@@ -0,0 +1,91 @@
+use crate::{
+    core::{
+        engine::{scratcharena::ScratchArena, soundgraphcompiler::SoundGraphCompiler},
+        jit::{argumentstack::ArgumentStack, cache::JitCache},
+        sound::{
+            context::{AudioContext, AudioStack},
+            soundgraph::SoundGraph,
+            soundprocessor::{
+                ProcessorComponent, ProcessorTiming, SoundProcessor, SoundProcessorId, StartOver,
+            },
+        },
+        soundchunk::SoundChunk,
+    },
+    objects::whitenoise::WhiteNoise,
+    ui_core::arguments::ParsedArguments,
+};
+
+const NUM_CHUNKS: usize = 4;
+
+/// Render several chunks of white noise with the given seed, starting over
+/// before each of the given number of passes, and return all passes
+fn render_whitenoise(seed: u64, num_passes: usize) -> Vec<Vec<SoundChunk>> {
+    let args = ParsedArguments::new_empty().add_or_replace(&WhiteNoise::ARG_SEED, seed as usize);
+
+    let whitenoise = WhiteNoise::new(&args);
+
+    let inkwell_context = inkwell::context::Context::create();
+    let jit_cache = JitCache::new(&inkwell_context);
+    let graph = SoundGraph::new();
+    let mut compiler = SoundGraphCompiler::new(&graph, &jit_cache);
+
+    let id = SoundProcessorId::new_unique();
+
+    let mut compiled_whitenoise = whitenoise.compile(id, &mut compiler);
+
+    let scratch_arena = ScratchArena::new();
+    let argument_stack = ArgumentStack::new();
+    let processor_timing = ProcessorTiming::new();
+
+    let mut passes = Vec::new();
+
+    for _ in 0..num_passes {
+        compiled_whitenoise.start_over();
+
+        let mut chunks = Vec::new();
+
+        for _ in 0..NUM_CHUNKS {
+            let mut context = AudioContext::new(
+                id,
+                &processor_timing,
+                &scratch_arena,
+                argument_stack.view_at_bottom(),
+                AudioStack::Root,
+            );
+
+            let mut chunk = SoundChunk::new();
+
+            WhiteNoise::process_audio(&mut compiled_whitenoise, &mut chunk, &mut context);
+
+            chunks.push(chunk);
+        }
+
+        passes.push(chunks);
+    }
+
+    passes
+}
+
+fn chunks_equal(a: &[SoundChunk], b: &[SoundChunk]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(ca, cb)| ca.l == cb.l && ca.r == cb.r)
+}
+
+#[test]
+fn test_same_seed_is_reproducible() {
+    let a = render_whitenoise(7, 1).pop().unwrap();
+    let b = render_whitenoise(7, 1).pop().unwrap();
+    assert!(chunks_equal(&a, &b));
+}
+
+#[test]
+fn test_different_seeds_differ() {
+    let a = render_whitenoise(7, 1).pop().unwrap();
+    let b = render_whitenoise(8, 1).pop().unwrap();
+    assert!(!chunks_equal(&a, &b));
+}
+
+#[test]
+fn test_start_over_repeats_sequence() {
+    let passes = render_whitenoise(7, 2);
+    assert!(chunks_equal(&passes[0], &passes[1]));
+}
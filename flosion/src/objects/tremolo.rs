@@ -0,0 +1,256 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        expression::context::ExpressionContext,
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{ArgumentEnum, EnumArgument, ParsedArguments},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TremoloWaveform {
+    Sine,
+    Triangle,
+    Square,
+}
+
+impl TremoloWaveform {
+    fn to_u8(self) -> u8 {
+        match self {
+            TremoloWaveform::Sine => 0,
+            TremoloWaveform::Triangle => 1,
+            TremoloWaveform::Square => 2,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<TremoloWaveform> {
+        match x {
+            0 => Some(TremoloWaveform::Sine),
+            1 => Some(TremoloWaveform::Triangle),
+            2 => Some(TremoloWaveform::Square),
+            _ => None,
+        }
+    }
+
+    /// Evaluate the waveform at the given phase in [0, 1), producing
+    /// a value in [0, 1]. Every waveform starts at its peak, so that
+    /// the signal begins at full amplitude.
+    fn eval(self, phase: f32) -> f32 {
+        match self {
+            TremoloWaveform::Sine => 0.5 + 0.5 * (phase * std::f32::consts::TAU).cos(),
+            TremoloWaveform::Triangle => (2.0 * phase - 1.0).abs(),
+            TremoloWaveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+impl ArgumentEnum for TremoloWaveform {
+    fn all_values() -> &'static [TremoloWaveform] {
+        &[
+            TremoloWaveform::Sine,
+            TremoloWaveform::Triangle,
+            TremoloWaveform::Square,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            TremoloWaveform::Sine => "sine",
+            TremoloWaveform::Triangle => "triangle",
+            TremoloWaveform::Square => "square",
+        }
+    }
+}
+
+/// Modulate the amplitude of the chunk using an LFO with the given
+/// per-sample rate (in Hz) and depth, advancing the phase as it goes.
+/// A depth of 0 leaves the chunk unchanged while a depth of 1 swings
+/// the amplitude all the way down to silence. In auto-pan mode, the
+/// right channel is modulated opposite to the left channel.
+pub(crate) fn apply_tremolo(
+    chunk: &mut SoundChunk,
+    phase: &mut f32,
+    rate: &[f32],
+    depth: &[f32],
+    waveform: TremoloWaveform,
+    auto_pan: bool,
+) {
+    debug_assert_eq!(rate.len(), CHUNK_SIZE);
+    debug_assert_eq!(depth.len(), CHUNK_SIZE);
+    for (i, (l, r)) in chunk.samples_mut().enumerate() {
+        let lfo = waveform.eval(*phase);
+        let depth = depth[i].clamp(0.0, 1.0);
+        *l *= 1.0 - depth * (1.0 - lfo);
+        *r *= if auto_pan {
+            1.0 - depth * lfo
+        } else {
+            1.0 - depth * (1.0 - lfo)
+        };
+        *phase = (*phase + rate[i] / SAMPLE_FREQUENCY as f32).rem_euclid(1.0);
+    }
+}
+
+pub struct TremoloState {
+    waveform: TremoloWaveform,
+    auto_pan: bool,
+    phase: f32,
+}
+
+impl ProcessorState for TremoloState {
+    type Processor = Tremolo;
+
+    fn new(processor: &Tremolo) -> Self {
+        TremoloState {
+            waveform: processor.waveform,
+            auto_pan: processor.auto_pan,
+            phase: 0.0,
+        }
+    }
+}
+
+impl StartOver for TremoloState {
+    fn start_over(&mut self) {
+        self.phase = 0.0;
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Tremolo {
+    pub input: SingleInput,
+    pub rate: ProcessorExpression,
+    pub depth: ProcessorExpression,
+
+    #[not_a_component]
+    waveform: TremoloWaveform,
+
+    #[not_a_component]
+    auto_pan: bool,
+
+    #[state]
+    state: StateMarker<TremoloState>,
+}
+
+impl Tremolo {
+    pub const ARG_WAVEFORM: EnumArgument<TremoloWaveform> = EnumArgument::new("waveform");
+
+    pub fn waveform(&self) -> TremoloWaveform {
+        self.waveform
+    }
+
+    pub fn set_waveform(&mut self, waveform: TremoloWaveform) {
+        self.waveform = waveform;
+    }
+
+    pub fn auto_pan(&self) -> bool {
+        self.auto_pan
+    }
+
+    pub fn set_auto_pan(&mut self, auto_pan: bool) {
+        self.auto_pan = auto_pan;
+    }
+}
+
+impl SoundProcessor for Tremolo {
+    fn new(args: &ParsedArguments) -> Tremolo {
+        Tremolo {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            rate: ProcessorExpression::new(&[4.0], ArgumentScope::new_empty()),
+            depth: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+            waveform: args
+                .get(&Tremolo::ARG_WAVEFORM)
+                .unwrap_or(TremoloWaveform::Sine),
+            auto_pan: false,
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        tremolo: &mut CompiledTremolo,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = tremolo.input.step(dst, InputContext::new(context));
+
+        let mut rate = [0.0; CHUNK_SIZE];
+        let mut depth = [0.0; CHUNK_SIZE];
+        tremolo.rate.eval(
+            &mut [&mut rate],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        tremolo.depth.eval(
+            &mut [&mut depth],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        apply_tremolo(
+            dst,
+            &mut tremolo.state.phase,
+            &rate,
+            &depth,
+            tremolo.state.waveform,
+            tremolo.state.auto_pan,
+        );
+
+        status
+    }
+}
+
+impl WithObjectType for Tremolo {
+    const TYPE: ObjectType = ObjectType::new("tremolo");
+}
+
+impl Stashable<StashingContext> for Tremolo {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+        stasher.object(&self.rate);
+        stasher.object(&self.depth);
+        stasher.u8(self.waveform.to_u8());
+        stasher.bool(self.auto_pan);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Tremolo {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        unstasher.object_inplace(&mut self.rate)?;
+        unstasher.object_inplace(&mut self.depth)?;
+        let waveform =
+            TremoloWaveform::from_u8(unstasher.u8_always()?).ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.waveform = waveform;
+        }
+        unstasher.bool_inplace(&mut self.auto_pan)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        biquad::{BiquadCoefficients, BiquadState},
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{NaturalNumberArgument, ParsedArguments},
+};
+
+/// Centre frequency of the lowest band, in Hz
+const LOWEST_FREQUENCY: f32 = 100.0;
+
+/// Centre frequency of the highest band, in Hz
+const HIGHEST_FREQUENCY: f32 = 8000.0;
+
+/// Time constant, in seconds, with which band envelopes rise
+const ENVELOPE_ATTACK: f32 = 0.005;
+
+/// Time constant, in seconds, with which band envelopes fall
+const ENVELOPE_RELEASE: f32 = 0.02;
+
+pub(crate) const MAX_BANDS: usize = 64;
+
+fn time_constant_coefficient(seconds: f32) -> f32 {
+    let samples = (seconds * SAMPLE_FREQUENCY as f32).max(1.0);
+    1.0 - (-1.0 / samples).exp()
+}
+
+/// The centre frequencies in Hz of the given number of bands, spaced
+/// evenly in pitch between the lowest and highest frequencies
+pub(crate) fn band_frequencies(num_bands: usize) -> Vec<f32> {
+    if num_bands <= 1 {
+        return vec![(LOWEST_FREQUENCY * HIGHEST_FREQUENCY).sqrt()];
+    }
+    let ratio = (HIGHEST_FREQUENCY / LOWEST_FREQUENCY).powf(1.0 / (num_bands - 1) as f32);
+    (0..num_bands)
+        .map(|i| LOWEST_FREQUENCY * ratio.powi(i as i32))
+        .collect()
+}
+
+/// Two identical bandpass filters in series, for steeper skirts
+/// than a single biquad gives
+#[derive(Clone, Copy, Default)]
+struct BandFilter {
+    stages: [BiquadState; 2],
+}
+
+impl BandFilter {
+    fn reset(&mut self) {
+        for stage in &mut self.stages {
+            stage.reset();
+        }
+    }
+
+    fn process(&mut self, coefficients: &BiquadCoefficients, x: f32) -> f32 {
+        self.stages
+            .iter_mut()
+            .fold(x, |x, stage| stage.process(coefficients, x))
+    }
+}
+
+/// A single band of the vocoder, which follows the envelope of the
+/// modulator within the band and imposes it on the carrier within
+/// the same band
+#[derive(Clone, Copy)]
+struct VocoderBand {
+    coefficients: BiquadCoefficients,
+    modulator_filters: [BandFilter; SoundChunk::NUM_CHANNELS],
+    carrier_filters: [BandFilter; SoundChunk::NUM_CHANNELS],
+    envelopes: [f32; SoundChunk::NUM_CHANNELS],
+}
+
+/// A bank of matching bandpass filters applied to the modulator and
+/// the carrier
+pub(crate) struct VocoderBank {
+    bands: Vec<VocoderBand>,
+    attack: f32,
+    release: f32,
+}
+
+impl VocoderBank {
+    pub(crate) fn new(num_bands: usize) -> VocoderBank {
+        let frequencies = band_frequencies(num_bands.clamp(1, MAX_BANDS));
+        // Neighbouring bands meet halfway in pitch, which for a bandpass
+        // filter spanning a frequency ratio of r gives q = sqrt(r) / (r - 1)
+        let ratio = if frequencies.len() > 1 {
+            frequencies[1] / frequencies[0]
+        } else {
+            HIGHEST_FREQUENCY / LOWEST_FREQUENCY
+        };
+        let q = ratio.sqrt() / (ratio - 1.0);
+        let bands = frequencies
+            .into_iter()
+            .map(|f| VocoderBand {
+                coefficients: BiquadCoefficients::bandpass(f, q),
+                modulator_filters: [BandFilter::default(); SoundChunk::NUM_CHANNELS],
+                carrier_filters: [BandFilter::default(); SoundChunk::NUM_CHANNELS],
+                envelopes: [0.0; SoundChunk::NUM_CHANNELS],
+            })
+            .collect();
+        VocoderBank {
+            bands,
+            attack: time_constant_coefficient(ENVELOPE_ATTACK),
+            release: time_constant_coefficient(ENVELOPE_RELEASE),
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        for band in &mut self.bands {
+            for f in band
+                .modulator_filters
+                .iter_mut()
+                .chain(band.carrier_filters.iter_mut())
+            {
+                f.reset();
+            }
+            band.envelopes = [0.0; SoundChunk::NUM_CHANNELS];
+        }
+    }
+
+    /// Replace the carrier with the sum of its bands, each scaled by
+    /// the envelope of the modulator in the same band
+    pub(crate) fn process(&mut self, carrier: &mut SoundChunk, modulator: &SoundChunk) {
+        // The mean of a rectified sine wave is 2/pi times its amplitude
+        let envelope_scale = std::f32::consts::FRAC_PI_2;
+        for c in 0..SoundChunk::NUM_CHANNELS {
+            for (s, m) in carrier.channel_mut(c).iter_mut().zip(modulator.channel(c)) {
+                let x = *s;
+                let mut y = 0.0;
+                for band in &mut self.bands {
+                    let level = band.modulator_filters[c]
+                        .process(&band.coefficients, *m)
+                        .abs();
+                    let envelope = &mut band.envelopes[c];
+                    let coefficient = if level > *envelope {
+                        self.attack
+                    } else {
+                        self.release
+                    };
+                    *envelope += coefficient * (level - *envelope);
+
+                    let carrier_band = band.carrier_filters[c].process(&band.coefficients, x);
+                    y += carrier_band * *envelope * envelope_scale;
+                }
+                *s = y;
+            }
+        }
+    }
+}
+
+pub struct VocoderState {
+    bank: VocoderBank,
+    modulator: SoundChunk,
+}
+
+impl ProcessorState for VocoderState {
+    type Processor = Vocoder;
+
+    fn new(processor: &Vocoder) -> Self {
+        VocoderState {
+            bank: VocoderBank::new(processor.num_bands),
+            modulator: SoundChunk::new(),
+        }
+    }
+}
+
+impl StartOver for VocoderState {
+    fn start_over(&mut self) {
+        self.bank.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Vocoder {
+    pub carrier: SingleInput,
+    pub modulator: SingleInput,
+
+    #[not_a_component]
+    num_bands: usize,
+
+    #[state]
+    state: StateMarker<VocoderState>,
+}
+
+impl Vocoder {
+    pub const ARG_NUM_BANDS: NaturalNumberArgument = NaturalNumberArgument("num_bands");
+
+    pub fn num_bands(&self) -> usize {
+        self.num_bands
+    }
+
+    pub fn set_num_bands(&mut self, num_bands: usize) {
+        self.num_bands = num_bands.clamp(1, MAX_BANDS);
+    }
+}
+
+impl SoundProcessor for Vocoder {
+    fn new(args: &ParsedArguments) -> Vocoder {
+        Vocoder {
+            carrier: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            modulator: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+            num_bands: args
+                .get(&Vocoder::ARG_NUM_BANDS)
+                .unwrap_or(16)
+                .clamp(1, MAX_BANDS),
+            state: StateMarker::new(),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        vocoder: &mut CompiledVocoder,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = vocoder.carrier.step(dst, InputContext::new(context));
+
+        let state = &mut vocoder.state;
+        vocoder
+            .modulator
+            .step(&mut state.modulator, InputContext::new(context));
+
+        state.bank.process(dst, &state.modulator);
+
+        status
+    }
+}
+
+impl WithObjectType for Vocoder {
+    const TYPE: ObjectType = ObjectType::new("vocoder");
+}
+
+impl Stashable<StashingContext> for Vocoder {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.carrier);
+        stasher.object(&self.modulator);
+        stasher.u64(self.num_bands as _);
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Vocoder {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.carrier)?;
+        unstasher.object_inplace(&mut self.modulator)?;
+        let num_bands = unstasher.u64_always()? as usize;
+        if unstasher.time_to_write() {
+            self.set_num_bands(num_bands);
+        }
+        Ok(())
+    }
+}
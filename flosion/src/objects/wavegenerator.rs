@@ -4,6 +4,7 @@ use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableI
 use crate::{
     core::{
         expression::context::ExpressionContext,
+        hardsync::{step_residuals, HardSync},
         jit::compiledexpression::Discretization,
         objecttype::{ObjectType, WithObjectType},
         samplefrequency::SAMPLE_FREQUENCY,
@@ -19,19 +20,170 @@ use crate::{
         soundchunk::{SoundChunk, CHUNK_SIZE},
         stashing::{StashingContext, UnstashingContext},
     },
-    ui_core::arguments::ParsedArguments,
+    ui_core::arguments::{ArgumentEnum, EnumArgument, ParsedArguments},
 };
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WaveGeneratorWaveform {
+    Sine,
+    Saw,
+    Square,
+    Triangle,
+    /// The amplitude expression alone, evaluated with respect to
+    /// phase, is the waveform. This is not bandlimited.
+    Expression,
+}
+
+impl WaveGeneratorWaveform {
+    fn to_u8(self) -> u8 {
+        match self {
+            WaveGeneratorWaveform::Sine => 0,
+            WaveGeneratorWaveform::Saw => 1,
+            WaveGeneratorWaveform::Square => 2,
+            WaveGeneratorWaveform::Triangle => 3,
+            WaveGeneratorWaveform::Expression => 4,
+        }
+    }
+
+    fn from_u8(x: u8) -> Option<WaveGeneratorWaveform> {
+        match x {
+            0 => Some(WaveGeneratorWaveform::Sine),
+            1 => Some(WaveGeneratorWaveform::Saw),
+            2 => Some(WaveGeneratorWaveform::Square),
+            3 => Some(WaveGeneratorWaveform::Triangle),
+            4 => Some(WaveGeneratorWaveform::Expression),
+            _ => None,
+        }
+    }
+}
+
+impl ArgumentEnum for WaveGeneratorWaveform {
+    fn all_values() -> &'static [WaveGeneratorWaveform] {
+        &[
+            WaveGeneratorWaveform::Sine,
+            WaveGeneratorWaveform::Saw,
+            WaveGeneratorWaveform::Square,
+            WaveGeneratorWaveform::Triangle,
+            WaveGeneratorWaveform::Expression,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            WaveGeneratorWaveform::Sine => "sine",
+            WaveGeneratorWaveform::Saw => "saw",
+            WaveGeneratorWaveform::Square => "square",
+            WaveGeneratorWaveform::Triangle => "triangle",
+            WaveGeneratorWaveform::Expression => "expression",
+        }
+    }
+}
+
+/// The polynomial bandlimited step (PolyBLEP) residual, which smooths
+/// a jump from -1 to 1 at phase 0 over the samples on either side of it.
+/// `dt` is the phase increment per sample.
+fn poly_blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let x = t / dt;
+        2.0 * x - x * x - 1.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt;
+        x * x + 2.0 * x + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// The polynomial bandlimited ramp (PolyBLAMP) residual, the integral
+/// of the PolyBLEP residual, which smooths an increase in slope of 2
+/// per sample at phase 0 in the same way
+fn poly_blamp(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let x = t / dt - 1.0;
+        -x * x * x / 3.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt + 1.0;
+        x * x * x / 3.0
+    } else {
+        0.0
+    }
+}
+
+/// Compute one sample of a bandlimited waveform with a peak amplitude
+/// of 1 at the given phase (from 0 to 1) and phase increment per sample.
+/// The jumps and corners of the naive waveforms are smoothed with
+/// polynomial corrections, which removes most of the aliasing they
+/// would otherwise cause at high frequencies.
+pub(crate) fn bandlimited_wave(waveform: WaveGeneratorWaveform, phase: f32, dt: f32) -> f32 {
+    // Corrections span dt on either side of each discontinuity, and
+    // must not overlap the next one
+    let dt = dt.abs().clamp(1e-6, 0.25);
+    let half_phase = (phase + 0.5).fract();
+    match waveform {
+        WaveGeneratorWaveform::Sine => (std::f32::consts::TAU * phase).sin(),
+        WaveGeneratorWaveform::Saw => 2.0 * phase - 1.0 - poly_blep(phase, dt),
+        WaveGeneratorWaveform::Square => bandlimited_pulse(phase, dt, 0.5),
+        WaveGeneratorWaveform::Triangle => {
+            // The slope changes by 8 per cycle, or 8 * dt per sample, at
+            // each corner, and the PolyBLAMP residual is for a change of 2
+            let naive = 1.0 - 4.0 * (phase - 0.5).abs();
+            naive + 4.0 * dt * (poly_blamp(phase, dt) - poly_blamp(half_phase, dt))
+        }
+        WaveGeneratorWaveform::Expression => 0.0,
+    }
+}
+
+/// Compute one sample of a waveform without bandlimiting. The pulse
+/// width is only used by the square waveform.
+fn naive_wave(waveform: WaveGeneratorWaveform, phase: f32, width: f32) -> f32 {
+    match waveform {
+        WaveGeneratorWaveform::Sine => (std::f32::consts::TAU * phase).sin(),
+        WaveGeneratorWaveform::Saw => 2.0 * phase - 1.0,
+        WaveGeneratorWaveform::Square => {
+            if phase < width {
+                1.0
+            } else {
+                -1.0
+            }
+        }
+        WaveGeneratorWaveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        WaveGeneratorWaveform::Expression => 0.0,
+    }
+}
+
+/// Compute one sample of a bandlimited pulse wave which is 1 for the
+/// given fraction of each cycle starting at phase 0, and -1 for the
+/// rest. The pulse is the difference of two bandlimited saws, one of
+/// which is shifted in phase by the width. The width is kept far enough
+/// from 0 and 1 that the pulse never vanishes and its two edges never
+/// overlap one another's corrections.
+pub(crate) fn bandlimited_pulse(phase: f32, dt: f32, width: f32) -> f32 {
+    let dt = dt.abs().clamp(1e-6, 0.25);
+    let min_width = (2.0 * dt).max(0.01);
+    let width = if width.is_finite() {
+        width.clamp(min_width, 1.0 - min_width)
+    } else {
+        0.5
+    };
+    let saw = |p: f32| 2.0 * p - 1.0 - poly_blep(p, dt);
+    let shifted_phase = (phase - width + 1.0).fract();
+    saw(shifted_phase) - saw(phase) + 2.0 * width - 1.0
+}
+
 pub struct WaveGeneratorState {
+    waveform: WaveGeneratorWaveform,
     phase: [f32; CHUNK_SIZE],
+    hard_sync: HardSync,
 }
 
 impl ProcessorState for WaveGeneratorState {
     type Processor = WaveGenerator;
 
-    fn new(_processor: &Self::Processor) -> Self {
+    fn new(processor: &Self::Processor) -> Self {
         WaveGeneratorState {
+            waveform: processor.waveform,
             phase: [0.0; CHUNK_SIZE],
+            hard_sync: HardSync::new(),
         }
     }
 }
@@ -39,6 +191,7 @@ impl ProcessorState for WaveGeneratorState {
 impl StartOver for WaveGeneratorState {
     fn start_over(&mut self) {
         slicemath::fill(&mut self.phase, 0.0);
+        self.hard_sync.reset();
     }
 }
 
@@ -47,19 +200,44 @@ pub struct WaveGenerator {
     pub phase: ProcessorArgument<PlainF32ArrayArgument>,
     pub amplitude: ProcessorExpression,
     pub frequency: ProcessorExpression,
+    pub pulse_width: ProcessorExpression,
+
+    /// The phase is reset to the start of the cycle whenever this
+    /// rises above zero, to synchronize with another oscillator
+    pub sync: ProcessorExpression,
+
+    #[not_a_component]
+    waveform: WaveGeneratorWaveform,
 
     #[state]
     state: StateMarker<WaveGeneratorState>,
 }
 
+impl WaveGenerator {
+    pub const ARG_WAVEFORM: EnumArgument<WaveGeneratorWaveform> = EnumArgument::new("waveform");
+
+    pub fn waveform(&self) -> WaveGeneratorWaveform {
+        self.waveform
+    }
+
+    pub fn set_waveform(&mut self, waveform: WaveGeneratorWaveform) {
+        self.waveform = waveform;
+    }
+}
+
 impl SoundProcessor for WaveGenerator {
-    fn new(_args: &ParsedArguments) -> WaveGenerator {
+    fn new(args: &ParsedArguments) -> WaveGenerator {
         let phase = ProcessorArgument::new();
         let phase_id = phase.id();
         WaveGenerator {
             phase,
-            amplitude: ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![phase_id])),
+            amplitude: ProcessorExpression::new(&[1.0], ArgumentScope::new(vec![phase_id])),
             frequency: ProcessorExpression::new(&[250.0], ArgumentScope::new_empty()),
+            pulse_width: ProcessorExpression::new(&[0.5], ArgumentScope::new_empty()),
+            sync: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            waveform: args
+                .get(&WaveGenerator::ARG_WAVEFORM)
+                .unwrap_or(WaveGeneratorWaveform::Sine),
             state: StateMarker::new(),
         }
     }
@@ -73,23 +251,98 @@ impl SoundProcessor for WaveGenerator {
         dst: &mut SoundChunk,
         context: &mut AudioContext,
     ) -> StreamStatus {
-        // NOTE: this is made redundant by WriteWaveform and WrappingIntegrator
-
-        let prev_phase: f32 = wavegen.state.phase.last().unwrap().clone();
+        let mut phase_increment = [0.0; CHUNK_SIZE];
         wavegen.frequency.eval(
-            &mut [&mut wavegen.state.phase],
+            &mut [&mut phase_increment],
             Discretization::samplewise_temporal(),
             ExpressionContext::new(context),
         );
-        slicemath::div_scalar_inplace(&mut wavegen.state.phase, SAMPLE_FREQUENCY as f32);
-        slicemath::exclusive_scan_inplace(&mut wavegen.state.phase, prev_phase, |p1, p2| p1 + p2);
-        slicemath::apply_unary_inplace(&mut wavegen.state.phase, |x| x - x.floor());
+        slicemath::div_scalar_inplace(&mut phase_increment, SAMPLE_FREQUENCY as f32);
+
+        let mut sync = [0.0; CHUNK_SIZE];
+        wavegen.sync.eval(
+            &mut [&mut sync],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        // For each sample at which the phase was reset by hard sync, the
+        // time since the reset and the phase from which it was reset
+        let mut resets: [Option<(f32, f32)>; CHUNK_SIZE] = [None; CHUNK_SIZE];
+
+        let state = &mut wavegen.state;
+        let mut phase = *state.phase.last().unwrap();
+        for (((p, dt), sync), reset) in state
+            .phase
+            .iter_mut()
+            .zip(&phase_increment)
+            .zip(&sync)
+            .zip(&mut resets)
+        {
+            match state.hard_sync.step(*sync) {
+                Some(elapsed) => {
+                    let reset_phase = phase + (1.0 - elapsed) * dt;
+                    *reset = Some((elapsed, reset_phase - reset_phase.floor()));
+                    phase = elapsed * dt;
+                }
+                None => phase += dt,
+            }
+            phase -= phase.floor();
+            if !phase.is_finite() {
+                phase = 0.0;
+            }
+            *p = phase;
+        }
 
         wavegen.amplitude.eval(
             &mut [&mut dst.l],
             Discretization::samplewise_temporal(),
             ExpressionContext::new(context).push(wavegen.phase, &wavegen.state.phase),
         );
+
+        let waveform = wavegen.state.waveform;
+        if waveform != WaveGeneratorWaveform::Expression {
+            let mut width = [0.5; CHUNK_SIZE];
+            if waveform == WaveGeneratorWaveform::Square {
+                wavegen.pulse_width.eval(
+                    &mut [&mut width],
+                    Discretization::samplewise_temporal(),
+                    ExpressionContext::new(context),
+                );
+            }
+
+            let mut wave = [0.0; CHUNK_SIZE];
+            for (((w, phase), dt), width) in wave
+                .iter_mut()
+                .zip(&wavegen.state.phase)
+                .zip(&phase_increment)
+                .zip(&width)
+            {
+                *w = match waveform {
+                    WaveGeneratorWaveform::Square => bandlimited_pulse(*phase, *dt, *width),
+                    _ => bandlimited_wave(waveform, *phase, *dt),
+                };
+            }
+
+            // Smooth the jumps in the waveform where the phase was reset.
+            // After each reset, the jump from the end of a cycle back to
+            // its start is already smoothed as though the cycle had ended
+            // naturally, and only the remainder needs correcting.
+            for (i, reset) in resets.iter().enumerate() {
+                let Some((elapsed, reset_phase)) = *reset else {
+                    continue;
+                };
+                let start = naive_wave(waveform, 0.0, width[i]);
+                let jump = start - naive_wave(waveform, reset_phase, width[i]);
+                let wrap_jump = start - naive_wave(waveform, 1.0, width[i]);
+                if i > 0 {
+                    wave[i - 1] += step_residuals(jump, elapsed).0;
+                }
+                wave[i] += step_residuals(jump - wrap_jump, elapsed).1;
+            }
+
+            slicemath::mul_inplace(&mut dst.l, &wave);
+        }
         slicemath::copy(&dst.l, &mut dst.r);
 
         StreamStatus::Playing
@@ -105,6 +358,9 @@ impl Stashable<StashingContext> for WaveGenerator {
         stasher.object(&self.phase);
         stasher.object(&self.amplitude);
         stasher.object(&self.frequency);
+        stasher.object(&self.pulse_width);
+        stasher.object(&self.sync);
+        stasher.u8(self.waveform.to_u8());
     }
 }
 
@@ -116,6 +372,13 @@ impl UnstashableInplace<UnstashingContext<'_>> for WaveGenerator {
         unstasher.object_inplace(&mut self.phase)?;
         unstasher.object_inplace(&mut self.amplitude)?;
         unstasher.object_inplace(&mut self.frequency)?;
+        unstasher.object_inplace(&mut self.pulse_width)?;
+        unstasher.object_inplace(&mut self.sync)?;
+        let waveform = WaveGeneratorWaveform::from_u8(unstasher.u8_always()?)
+            .ok_or(UnstashError::Corrupted)?;
+        if unstasher.time_to_write() {
+            self.waveform = waveform;
+        }
         Ok(())
     }
 }
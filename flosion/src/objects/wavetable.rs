@@ -0,0 +1,335 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Order, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        audiofileio::{default_base_directory, load_audio_file, AudioFilePath},
+        expression::context::ExpressionContext,
+        hardsync::{step_residuals, HardSync},
+        jit::compiledexpression::Discretization,
+        objecttype::{ObjectType, WithObjectType},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            expression::ProcessorExpression,
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
+        },
+        soundbuffer::SoundBuffer,
+        soundchunk::{SoundChunk, CHUNK_SIZE},
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::{FilePathArgument, ParsedArguments},
+};
+
+/// The number of samples in every single-cycle table. Audio files are
+/// split into consecutive tables of this length when loaded, which is
+/// the convention followed by most wavetable files.
+pub const WAVETABLE_SIZE: usize = 2048;
+
+/// The most tables which are kept when loading from an audio file
+pub const WAVETABLE_MAX_TABLES: usize = 256;
+
+/// A single cycle of a sine wave, used as the initial table
+fn sine_table() -> Vec<f32> {
+    (0..WAVETABLE_SIZE)
+        .map(|i| (std::f32::consts::TAU * i as f32 / WAVETABLE_SIZE as f32).sin())
+        .collect()
+}
+
+/// Stretch or squash the given samples to fit exactly one table,
+/// using linear interpolation
+pub fn resample_to_table(samples: &[f32]) -> Vec<f32> {
+    if samples.is_empty() {
+        return vec![0.0; WAVETABLE_SIZE];
+    }
+    let step = samples.len() as f32 / WAVETABLE_SIZE as f32;
+    (0..WAVETABLE_SIZE)
+        .map(|i| {
+            let position = i as f32 * step;
+            let i0 = position.floor() as usize;
+            let i1 = (i0 + 1) % samples.len();
+            let t = position - position.floor();
+            samples[i0] + t * (samples[i1] - samples[i0])
+        })
+        .collect()
+}
+
+/// Split the left channel of an audio buffer into tables. A buffer
+/// which is shorter than a single table is taken to be one cycle,
+/// and is resampled to fit.
+pub fn tables_from_audio(buffer: &SoundBuffer) -> Vec<Vec<f32>> {
+    let samples: Vec<f32> = buffer.samples_l().collect();
+    if samples.len() < WAVETABLE_SIZE {
+        return vec![resample_to_table(&samples)];
+    }
+    samples
+        .chunks_exact(WAVETABLE_SIZE)
+        .take(WAVETABLE_MAX_TABLES)
+        .map(|c| c.to_vec())
+        .collect()
+}
+
+/// Read a table at the given phase, from 0 to 1, with linear
+/// interpolation between samples
+fn read_table(table: &[f32], phase: f32) -> f32 {
+    let position = phase * WAVETABLE_SIZE as f32;
+    let i0 = (position.floor() as usize) % WAVETABLE_SIZE;
+    let i1 = (i0 + 1) % WAVETABLE_SIZE;
+    let t = position - position.floor();
+    table[i0] + t * (table[i1] - table[i0])
+}
+
+/// Read the tables at the given phase, crossfading between the two
+/// tables on either side of the morph position. A morph of 0 gives the
+/// first table, 1 gives the second, and so on.
+pub(crate) fn read_morphed(tables: &[Vec<f32>], morph: f32, phase: f32) -> f32 {
+    if tables.is_empty() {
+        return 0.0;
+    }
+    let last = (tables.len() - 1) as f32;
+    let morph = if morph.is_finite() {
+        morph.clamp(0.0, last)
+    } else {
+        0.0
+    };
+    let i0 = morph.floor() as usize;
+    let i1 = (i0 + 1).min(tables.len() - 1);
+    let t = morph - morph.floor();
+    let v0 = read_table(&tables[i0], phase);
+    if t == 0.0 {
+        return v0;
+    }
+    let v1 = read_table(&tables[i1], phase);
+    v0 + t * (v1 - v0)
+}
+
+pub struct WavetableState {
+    tables: Vec<Vec<f32>>,
+    phase: f32,
+    hard_sync: HardSync,
+}
+
+impl ProcessorState for WavetableState {
+    type Processor = Wavetable;
+
+    fn new(processor: &Wavetable) -> Self {
+        WavetableState {
+            tables: processor.tables.clone(),
+            phase: 0.0,
+            hard_sync: HardSync::new(),
+        }
+    }
+}
+
+impl StartOver for WavetableState {
+    fn start_over(&mut self) {
+        self.phase = 0.0;
+        self.hard_sync.reset();
+    }
+}
+
+#[derive(ProcessorComponent)]
+pub struct Wavetable {
+    pub frequency: ProcessorExpression,
+    pub morph: ProcessorExpression,
+
+    /// The phase is reset to the start of the table whenever this
+    /// rises above zero, to synchronize with another oscillator
+    pub sync: ProcessorExpression,
+
+    #[not_a_component]
+    tables: Vec<Vec<f32>>,
+
+    /// The file which the tables were last loaded from, if any
+    #[not_a_component]
+    source: Option<AudioFilePath>,
+
+    #[state]
+    state: StateMarker<WavetableState>,
+}
+
+impl Wavetable {
+    pub const ARG_PATH: FilePathArgument = FilePathArgument("path");
+
+    pub fn tables(&self) -> &[Vec<f32>] {
+        &self.tables
+    }
+
+    /// Replace all tables. Each table is resampled if it does
+    /// not have exactly `WAVETABLE_SIZE` samples.
+    pub fn set_tables(&mut self, tables: Vec<Vec<f32>>) {
+        self.tables = tables
+            .into_iter()
+            .map(|t| {
+                if t.len() == WAVETABLE_SIZE {
+                    t
+                } else {
+                    resample_to_table(&t)
+                }
+            })
+            .collect();
+    }
+
+    /// Replace all tables with those split from the given audio file,
+    /// which then becomes their source
+    pub fn load_file(&mut self, file: AudioFilePath) -> Result<(), String> {
+        let buffer = load_audio_file(file.absolute())?;
+        self.tables = tables_from_audio(&buffer);
+        self.source = Some(file);
+        Ok(())
+    }
+
+    /// The file which the tables were last loaded from, if any
+    pub fn source(&self) -> Option<&AudioFilePath> {
+        self.source.as_ref()
+    }
+
+    /// The samples of the table at the given index, for drawing into
+    pub fn table_mut(&mut self, index: usize) -> &mut [f32] {
+        &mut self.tables[index]
+    }
+
+    /// Add a copy of the table at the given index after it
+    pub fn duplicate_table(&mut self, index: usize) {
+        let table = self.tables[index].clone();
+        self.tables.insert(index + 1, table);
+    }
+
+    pub fn remove_table(&mut self, index: usize) {
+        self.tables.remove(index);
+    }
+}
+
+impl SoundProcessor for Wavetable {
+    fn new(args: &ParsedArguments) -> Wavetable {
+        let mut wavetable = Wavetable {
+            frequency: ProcessorExpression::new(&[220.0], ArgumentScope::new_empty()),
+            morph: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            sync: ProcessorExpression::new(&[0.0], ArgumentScope::new_empty()),
+            tables: vec![sine_table()],
+            source: None,
+            state: StateMarker::new(),
+        };
+        if let Some(path) = args.get(&Self::ARG_PATH) {
+            let file = AudioFilePath::new(&path, &default_base_directory());
+            if let Err(e) = wavetable.load_file(file) {
+                println!(
+                    "Failed to load wavetable from \"{}\": {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+        wavetable
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        wavetable: &mut CompiledWavetable,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let mut frequency = [0.0; CHUNK_SIZE];
+        let mut morph = [0.0; CHUNK_SIZE];
+        wavetable.frequency.eval(
+            &mut [&mut frequency],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        wavetable.morph.eval(
+            &mut [&mut morph],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+        let mut sync = [0.0; CHUNK_SIZE];
+        wavetable.sync.eval(
+            &mut [&mut sync],
+            Discretization::samplewise_temporal(),
+            ExpressionContext::new(context),
+        );
+
+        let state = &mut wavetable.state;
+        for i in 0..CHUNK_SIZE {
+            let dt = frequency[i] / SAMPLE_FREQUENCY as f32;
+            match state.hard_sync.step(sync[i]) {
+                Some(elapsed) => {
+                    // Reset the phase, and smooth the resulting jump
+                    // between the previous sample and this one
+                    let reset_phase = state.phase - elapsed * dt;
+                    let reset_phase = reset_phase - reset_phase.floor();
+                    let jump = read_morphed(&state.tables, morph[i], 0.0)
+                        - read_morphed(&state.tables, morph[i], reset_phase);
+                    let (before, after) = step_residuals(jump, elapsed);
+                    state.phase = elapsed * dt;
+                    dst.l[i] = read_morphed(&state.tables, morph[i], state.phase) + after;
+                    if i > 0 {
+                        dst.l[i - 1] += before;
+                    }
+                }
+                None => dst.l[i] = read_morphed(&state.tables, morph[i], state.phase),
+            }
+            state.phase += dt;
+            state.phase -= state.phase.floor();
+            if !state.phase.is_finite() {
+                state.phase = 0.0;
+            }
+        }
+        dst.r = dst.l;
+
+        StreamStatus::Playing
+    }
+}
+
+impl WithObjectType for Wavetable {
+    const TYPE: ObjectType = ObjectType::new("wavetable");
+}
+
+impl Stashable<StashingContext> for Wavetable {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.frequency);
+        stasher.object(&self.morph);
+        stasher.object(&self.sync);
+        stasher.array_of_proxy_objects(
+            self.tables.iter(),
+            |table, stasher| stasher.array_of_f32_slice(table),
+            Order::Ordered,
+        );
+        stasher.bool(self.source.is_some());
+        if let Some(file) = &self.source {
+            stasher.object_with_context(file, ());
+        }
+    }
+}
+
+impl<'a> UnstashableInplace<UnstashingContext<'a>> for Wavetable {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.frequency)?;
+        unstasher.object_inplace(&mut self.morph)?;
+        unstasher.object_inplace(&mut self.sync)?;
+        let mut tables = Vec::new();
+        unstasher.array_of_proxy_objects(|unstasher| {
+            tables.push(unstasher.array_of_f32_iter()?.collect());
+            Ok(())
+        })?;
+        let source: Option<AudioFilePath> = if unstasher.bool_always()? {
+            Some(unstasher.object_always_with_context(())?)
+        } else {
+            None
+        };
+        if unstasher.time_to_write() {
+            self.tables = tables;
+            let base_directory = unstasher.context().base_directory();
+            self.source = source.map(|f| f.resolve(&base_directory));
+        }
+        Ok(())
+    }
+}
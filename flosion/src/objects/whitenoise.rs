@@ -1,26 +1,71 @@
 use flosion_macros::ProcessorComponent;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
-use rand::prelude::*;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 use crate::{
     core::{
         objecttype::{ObjectType, WithObjectType},
         sound::{
             context::AudioContext,
-            soundprocessor::{SoundProcessor, StreamStatus},
+            soundprocessor::{
+                ProcessorState, SoundProcessor, StartOver, StateMarker, StreamStatus,
+            },
         },
         soundchunk::SoundChunk,
         stashing::{StashingContext, UnstashingContext},
     },
-    ui_core::arguments::ParsedArguments,
+    ui_core::arguments::{NaturalNumberArgument, ParsedArguments},
 };
 
+pub struct WhiteNoiseState {
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl ProcessorState for WhiteNoiseState {
+    type Processor = WhiteNoise;
+
+    fn new(processor: &WhiteNoise) -> Self {
+        WhiteNoiseState {
+            seed: processor.seed,
+            rng: SmallRng::seed_from_u64(processor.seed),
+        }
+    }
+}
+
+impl StartOver for WhiteNoiseState {
+    fn start_over(&mut self) {
+        self.rng = SmallRng::seed_from_u64(self.seed);
+    }
+}
+
 #[derive(ProcessorComponent)]
-pub struct WhiteNoise {}
+pub struct WhiteNoise {
+    #[not_a_component]
+    seed: u64,
+
+    #[state]
+    state: StateMarker<WhiteNoiseState>,
+}
+
+impl WhiteNoise {
+    pub const ARG_SEED: NaturalNumberArgument = NaturalNumberArgument("seed");
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+}
 
 impl SoundProcessor for WhiteNoise {
-    fn new(_args: &ParsedArguments) -> WhiteNoise {
-        WhiteNoise {}
+    fn new(args: &ParsedArguments) -> WhiteNoise {
+        WhiteNoise {
+            seed: args.get(&WhiteNoise::ARG_SEED).unwrap_or(0) as u64,
+            state: StateMarker::new(),
+        }
     }
 
     fn is_static(&self) -> bool {
@@ -28,16 +73,17 @@ impl SoundProcessor for WhiteNoise {
     }
 
     fn process_audio(
-        _whitenoise: &mut CompiledWhiteNoise,
+        whitenoise: &mut CompiledWhiteNoise,
         dst: &mut SoundChunk,
         _context: &mut AudioContext,
     ) -> StreamStatus {
+        let rng = &mut whitenoise.state.rng;
         for s in dst.l.iter_mut() {
-            let r: f32 = thread_rng().gen();
+            let r: f32 = rng.gen();
             *s = 0.2 * r - 0.1;
         }
         for s in dst.r.iter_mut() {
-            let r: f32 = thread_rng().gen();
+            let r: f32 = rng.gen();
             *s = 0.2 * r - 0.1;
         }
         StreamStatus::Playing
@@ -49,14 +95,49 @@ impl WithObjectType for WhiteNoise {
 }
 
 impl Stashable<StashingContext> for WhiteNoise {
-    fn stash(&self, _stasher: &mut Stasher<StashingContext>) {}
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.u64(self.seed);
+    }
 }
 
 impl<'a> UnstashableInplace<UnstashingContext<'a>> for WhiteNoise {
     fn unstash_inplace(
         &mut self,
-        _unstasher: &mut InplaceUnstasher<UnstashingContext>,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
     ) -> Result<(), UnstashError> {
+        unstasher.u64_inplace(&mut self.seed)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use hashstash::test_stash_roundtrip_inplace;
+
+    use crate::{
+        core::{
+            expression::expressionobject::ExpressionObjectFactory,
+            sound::{soundobject::SoundObjectFactory, soundprocessor::SoundProcessor},
+            stashing::{StashingContext, UnstashingContext},
+        },
+        ui_core::arguments::ParsedArguments,
+    };
+
+    use super::WhiteNoise;
+
+    #[test]
+    fn test_stash() {
+        let obj_fac = SoundObjectFactory::new_empty();
+        let expr_fac = ExpressionObjectFactory::new_empty();
+
+        test_stash_roundtrip_inplace(
+            || WhiteNoise::new(&ParsedArguments::new_empty()),
+            |whitenoise| {
+                whitenoise.set_seed(5678);
+            },
+            StashingContext::new_stashing_normally(),
+            UnstashingContext::new(&obj_fac, &expr_fac),
+        )
+        .unwrap();
+    }
+}
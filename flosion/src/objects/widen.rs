@@ -0,0 +1,75 @@
+use flosion_macros::ProcessorComponent;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        objecttype::{ObjectType, WithObjectType},
+        sound::{
+            argument::ArgumentScope,
+            context::AudioContext,
+            inputtypes::singleinput::SingleInput,
+            soundinput::InputContext,
+            soundprocessor::{SoundProcessor, StreamStatus},
+        },
+        soundchunk::SoundChunk,
+        stashing::{StashingContext, UnstashingContext},
+    },
+    ui_core::arguments::ParsedArguments,
+};
+
+/// Treats the first channel of its input as a mono signal and
+/// copies it to every channel
+#[derive(ProcessorComponent)]
+pub struct Widen {
+    pub input: SingleInput,
+}
+
+/// Copy the first channel of the chunk into all other channels
+pub(crate) fn widen(chunk: &mut SoundChunk) {
+    let mono = *chunk.channel(0);
+    for i in 1..SoundChunk::NUM_CHANNELS {
+        *chunk.channel_mut(i) = mono;
+    }
+}
+
+impl SoundProcessor for Widen {
+    fn new(_args: &ParsedArguments) -> Widen {
+        Widen {
+            input: SingleInput::new_isochronic(ArgumentScope::new_empty()),
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    fn process_audio(
+        widen: &mut Self::CompiledType<'_>,
+        dst: &mut SoundChunk,
+        context: &mut AudioContext,
+    ) -> StreamStatus {
+        let status = widen.input.step(dst, InputContext::new(context));
+        self::widen(dst);
+        status
+    }
+}
+
+impl WithObjectType for Widen {
+    const TYPE: ObjectType = ObjectType::new("widen");
+}
+
+impl Stashable<StashingContext> for Widen {
+    fn stash(&self, stasher: &mut Stasher<StashingContext>) {
+        stasher.object(&self.input);
+    }
+}
+
+impl UnstashableInplace<UnstashingContext<'_>> for Widen {
+    fn unstash_inplace(
+        &mut self,
+        unstasher: &mut InplaceUnstasher<UnstashingContext>,
+    ) -> Result<(), UnstashError> {
+        unstasher.object_inplace(&mut self.input)?;
+        Ok(())
+    }
+}
@@ -9,9 +9,13 @@ use crate::core::{
 };
 
 use super::{
-    factories::Factories, graph_properties::GraphProperties, history::SnapshotFlag,
-    soundgraphuistate::SoundGraphUiState, stackedlayout::stackedlayout::StackedLayout,
+    factories::Factories,
+    graph_properties::GraphProperties,
+    history::SnapshotFlag,
+    soundgraphuistate::{SharedUiResources, SoundGraphUiState},
+    stackedlayout::stackedlayout::StackedLayout,
     stashing::UiUnstashingContext,
+    summon_widget::SummonHistories,
 };
 
 pub(crate) struct AppState {
@@ -36,6 +40,14 @@ impl AppState {
         }
     }
 
+    pub(crate) fn summon_histories(&self) -> &SummonHistories {
+        self.ui_state.summon_histories()
+    }
+
+    pub(crate) fn summon_histories_mut(&mut self) -> &mut SummonHistories {
+        self.ui_state.summon_histories_mut()
+    }
+
     pub(crate) fn interact_and_draw(
         &mut self,
         ui: &mut egui::Ui,
@@ -60,12 +72,15 @@ impl AppState {
 
         self.ui_state.interact_and_draw(
             ui,
-            factories,
             graph,
-            &self.properties,
             &mut self.graph_layout,
-            stash,
-            &snapshot_flag,
+            &SharedUiResources {
+                factories,
+                properties: &self.properties,
+                jit_cache,
+                stash,
+                snapshot_flag,
+            },
         );
     }
 
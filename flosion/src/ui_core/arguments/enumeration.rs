@@ -0,0 +1,44 @@
+use std::marker::PhantomData;
+
+use super::Argument;
+
+// ArgumentEnum is a trait for types with a small, fixed
+// set of values which can each be referred to by name,
+// such that they can be chosen using an EnumArgument.
+pub trait ArgumentEnum: 'static + Copy {
+    // All possible values, in the order they should
+    // be suggested
+    fn all_values() -> &'static [Self];
+
+    // The name of the value, e.g. 'pink', which is
+    // also what must be typed to choose it
+    fn name(&self) -> &'static str;
+}
+
+pub struct EnumArgument<T>(pub &'static str, PhantomData<T>);
+
+impl<T> EnumArgument<T> {
+    pub const fn new(name: &'static str) -> EnumArgument<T> {
+        EnumArgument(name, PhantomData)
+    }
+}
+
+impl<T: ArgumentEnum> Argument for EnumArgument<T> {
+    type ValueType = T;
+
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn suggestions(s: &str) -> Vec<(Self::ValueType, String)> {
+        T::all_values()
+            .iter()
+            .filter(|v| v.name().starts_with(s))
+            .map(|v| (*v, v.name().to_string()))
+            .collect()
+    }
+
+    fn try_parse(s: &str) -> Option<Self::ValueType> {
+        T::all_values().iter().find(|v| v.name() == s).cloned()
+    }
+}
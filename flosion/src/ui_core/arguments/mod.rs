@@ -1,11 +1,13 @@
 use std::any::Any;
 
+pub mod enumeration;
 pub mod filepath;
 pub mod float;
 pub mod floatrange;
 pub mod naturalnumber;
 pub mod stringidentifier;
 
+pub use enumeration::{ArgumentEnum, EnumArgument};
 pub use filepath::FilePathArgument;
 pub use float::FloatArgument;
 pub use floatrange::FloatRangeArgument;
@@ -164,6 +166,11 @@ impl ParsedArguments {
         )
     }
 
+    /// Whether a value was parsed for the argument with the given name
+    pub fn contains(&self, name: &str) -> bool {
+        self.argument_values.iter().any(|(n, _)| *n == name)
+    }
+
     #[cfg(test)]
     pub(super) fn values(&self) -> &[(&'static str, AnyArgumentValue)] {
         &self.argument_values
@@ -0,0 +1,41 @@
+use eframe::egui;
+
+use crate::core::beatsync::{BeatSync, RetriggerInterval};
+
+/// Show controls for how often a processor is retriggered and at what
+/// tempo. Returns true if anything was changed.
+pub fn beat_sync_ui(ui: &mut egui::Ui, beat_sync: &mut BeatSync) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Retrigger");
+        for interval in RetriggerInterval::ALL {
+            if ui
+                .selectable_label(beat_sync.interval == interval, interval.name())
+                .clicked()
+            {
+                beat_sync.interval = interval;
+                changed = true;
+            }
+        }
+    });
+    if beat_sync.interval != RetriggerInterval::Never {
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut beat_sync.tempo.beats_per_minute)
+                        .range(20.0..=400.0)
+                        .speed(0.5)
+                        .suffix(" bpm"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut beat_sync.tempo.beats_per_bar)
+                        .range(1..=16)
+                        .suffix(" beats/bar"),
+                )
+                .changed();
+        });
+    }
+    changed
+}
@@ -0,0 +1,94 @@
+use crate::{
+    core::{
+        expression::expressionnode::ExpressionNodeId,
+        sound::{expression::ProcessorExpressionLocation, soundgraph::SoundGraph},
+    },
+    objects::purefunctions::Constant,
+};
+
+/// Constants whose magnitude is at least this large are suspicious,
+/// since no sensible quantity in a sound graph gets anywhere near it
+pub(crate) const LARGEST_SENSIBLE_CONSTANT: f32 = 1e9;
+
+/// Nonzero constants whose magnitude is smaller than this are
+/// suspicious, since they are indistinguishable from zero in practice
+pub(crate) const SMALLEST_SENSIBLE_CONSTANT: f32 = 1e-9;
+
+/// The reason that a constant's value likely indicates a mistake
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ConstantProblem {
+    NotANumber,
+    Infinite,
+    ExtremeMagnitude,
+}
+
+impl ConstantProblem {
+    /// The problem with the given constant value, if there is one
+    pub(crate) fn of(value: f32) -> Option<ConstantProblem> {
+        if value.is_nan() {
+            Some(ConstantProblem::NotANumber)
+        } else if value.is_infinite() {
+            Some(ConstantProblem::Infinite)
+        } else if value.abs() >= LARGEST_SENSIBLE_CONSTANT
+            || (value != 0.0 && value.abs() < SMALLEST_SENSIBLE_CONSTANT)
+        {
+            Some(ConstantProblem::ExtremeMagnitude)
+        } else {
+            None
+        }
+    }
+}
+
+/// A constant node with a suspicious value within one of the graph's
+/// processor expressions
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct ConstantDiagnostic {
+    /// The expression containing the constant
+    pub(crate) expression: ProcessorExpressionLocation,
+
+    /// The constant node itself
+    pub(crate) node: ExpressionNodeId,
+
+    pub(crate) value: f32,
+
+    pub(crate) problem: ConstantProblem,
+}
+
+impl ConstantDiagnostic {
+    pub(crate) fn explain(&self) -> String {
+        match self.problem {
+            ConstantProblem::NotANumber => "A constant is not a number".to_string(),
+            ConstantProblem::Infinite => format!("A constant is {}", self.value),
+            ConstantProblem::ExtremeMagnitude => {
+                format!("A constant has the extreme value {:e}", self.value)
+            }
+        }
+    }
+}
+
+/// Find every constant node in the graph's processor expressions whose
+/// value is NaN, infinite, or of an extreme magnitude, which likely
+/// indicates a mistake. Constants within the definitions of expression
+/// macros aren't owned by any one expression and are not included.
+pub(crate) fn find_suspicious_constants(graph: &SoundGraph) -> Vec<ConstantDiagnostic> {
+    let mut diagnostics = Vec::new();
+    for proc in graph.sound_processors().values() {
+        proc.foreach_expression(|expr, location| {
+            for (node_id, node) in expr.graph().nodes() {
+                let Some(constant) = node.downcast::<Constant>() else {
+                    continue;
+                };
+                let value = constant.value();
+                if let Some(problem) = ConstantProblem::of(value) {
+                    diagnostics.push(ConstantDiagnostic {
+                        expression: location,
+                        node: *node_id,
+                        value,
+                        problem,
+                    });
+                }
+            }
+        });
+    }
+    diagnostics
+}
@@ -6,6 +6,7 @@ use crate::core::{
     expression::{
         expressiongraph::{ExpressionGraph, ExpressionGraphParameterId},
         expressioninput::ExpressionInputId,
+        expressionmacro::ExpressionMacroLibrary,
         expressionobject::ExpressionObjectFactory,
     },
     jit::cache::JitCache,
@@ -19,17 +20,24 @@ use crate::core::{
 };
 
 use super::{
-    expressionobjectui::ExpressionObjectUiFactory, history::SnapshotFlag,
-    soundgraphuinames::SoundGraphUiNames, stackedlayout::timeaxis::TimeAxis,
+    expressionobjectui::ExpressionObjectUiFactory, graph_properties::GraphProperties,
+    history::SnapshotFlag, soundgraphuinames::SoundGraphUiNames, stackedlayout::timeaxis::TimeAxis,
 };
 
+/// A parameter which an expression may refer to, along with a friendly
+/// name by which it can be summoned
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct AvailableParameter {
+    pub(crate) name: String,
+    pub(crate) target: ExpressionParameterTarget,
+}
+
 pub(crate) struct OuterProcessorExpressionContext<'a> {
     location: ProcessorExpressionLocation,
     parameter_mapping: &'a mut ExpressionParameterMapping,
     sound_graph_names: &'a SoundGraphUiNames,
     time_axis: TimeAxis,
-    available_sound_inputs: &'a HashSet<SoundInputLocation>,
-    available_arguments: &'a HashSet<ProcessorArgumentLocation>,
+    properties: &'a GraphProperties,
     snapshot_flag: &'a SnapshotFlag,
 }
 
@@ -39,8 +47,7 @@ impl<'a> OuterProcessorExpressionContext<'a> {
         parameter_mapping: &'a mut ExpressionParameterMapping,
         sound_graph_names: &'a SoundGraphUiNames,
         time_axis: TimeAxis,
-        available_sound_inputs: &'a HashSet<SoundInputLocation>,
-        available_arguments: &'a HashSet<ProcessorArgumentLocation>,
+        properties: &'a GraphProperties,
         snapshot_flag: &'a SnapshotFlag,
     ) -> Self {
         Self {
@@ -48,8 +55,7 @@ impl<'a> OuterProcessorExpressionContext<'a> {
             parameter_mapping,
             sound_graph_names,
             time_axis,
-            available_sound_inputs,
-            available_arguments,
+            properties,
             snapshot_flag,
         }
     }
@@ -63,11 +69,71 @@ impl<'a> OuterProcessorExpressionContext<'a> {
     }
 
     pub(super) fn available_sound_inputs(&self) -> &HashSet<SoundInputLocation> {
-        self.available_sound_inputs
+        self.properties
+            .available_inputs(self.location.processor())
+            .unwrap()
     }
 
     pub(super) fn available_arguments(&self) -> &HashSet<ProcessorArgumentLocation> {
-        self.available_arguments
+        self.properties.available_arguments(self.location).unwrap()
+    }
+
+    /// Every argument, time source, and input time which is in scope for
+    /// the expression, each with a friendly name. Some parameters may be
+    /// listed under more than one name. The processor's own time comes
+    /// first, followed by the times of available inputs and then the
+    /// available arguments, each sorted by name.
+    pub(crate) fn available_parameters(&self) -> Vec<AvailableParameter> {
+        let names = self.sound_graph_names();
+        let own_time = ExpressionParameterTarget::ProcessorTime(self.location.processor());
+
+        let mut parameters = vec![
+            AvailableParameter {
+                name: "time".to_string(),
+                target: own_time,
+            },
+            AvailableParameter {
+                name: format!(
+                    "{}.time",
+                    names.sound_processor(self.location.processor()).unwrap()
+                ),
+                target: own_time,
+            },
+        ];
+
+        let mut input_times = Vec::new();
+        for input_loc in self.available_sound_inputs() {
+            input_times.push(AvailableParameter {
+                name: format!(
+                    "{}.time",
+                    names.sound_processor(input_loc.processor()).unwrap()
+                ),
+                target: ExpressionParameterTarget::ProcessorTime(input_loc.processor()),
+            });
+            input_times.push(AvailableParameter {
+                name: format!("{}.time", names.combined_input_name(*input_loc)),
+                target: ExpressionParameterTarget::InputTime(*input_loc),
+            });
+        }
+        input_times.sort_by(|a, b| a.name.cmp(&b.name));
+        parameters.extend(input_times);
+
+        let mut arguments: Vec<AvailableParameter> = self
+            .available_arguments()
+            .iter()
+            .map(|arg_loc| AvailableParameter {
+                name: names.combined_argument_name(*arg_loc),
+                target: ExpressionParameterTarget::Argument(*arg_loc),
+            })
+            .collect();
+        arguments.sort_by(|a, b| a.name.cmp(&b.name));
+        parameters.extend(arguments);
+
+        parameters
+    }
+
+    pub(super) fn expression_macros(&self) -> &ExpressionMacroLibrary {
+        self.properties.expression_macros()
     }
 
     pub(crate) fn sound_graph_names(&self) -> &SoundGraphUiNames {
@@ -78,6 +144,23 @@ impl<'a> OuterProcessorExpressionContext<'a> {
         &self.time_axis
     }
 
+    pub(crate) fn target_name(&self, target: ExpressionParameterTarget) -> String {
+        let names = self.sound_graph_names();
+        match target {
+            ExpressionParameterTarget::Argument(arg_loc) => names.combined_argument_name(arg_loc),
+            ExpressionParameterTarget::ProcessorTime(spid) => {
+                if spid == self.location.processor() {
+                    "time".to_string()
+                } else {
+                    format!("{}.time", names.sound_processor(spid).unwrap())
+                }
+            }
+            ExpressionParameterTarget::InputTime(input_loc) => {
+                format!("{}.time", names.combined_input_name(input_loc))
+            }
+        }
+    }
+
     pub(crate) fn find_graph_id_for_target(
         &self,
         target: ExpressionParameterTarget,
@@ -122,22 +205,7 @@ impl<'a> OuterExpressionGraphUiContext<'a> {
                     .parameter_mapping
                     .target_from_parameter(parameter_id)
                     .unwrap();
-                let names = ctx.sound_graph_names();
-                match target {
-                    ExpressionParameterTarget::Argument(arg_loc) => {
-                        names.combined_argument_name(arg_loc)
-                    }
-                    ExpressionParameterTarget::ProcessorTime(spid) => {
-                        if spid == ctx.location.processor() {
-                            "time".to_string()
-                        } else {
-                            format!("{}.time", names.sound_processor(spid).unwrap())
-                        }
-                    }
-                    ExpressionParameterTarget::InputTime(input_loc) => {
-                        format!("{}.time", names.combined_input_name(input_loc))
-                    }
-                }
+                ctx.target_name(target)
             }
         }
     }
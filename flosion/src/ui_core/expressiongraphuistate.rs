@@ -4,7 +4,12 @@ use hashstash::{Order, Stashable, Stasher, UnstashError, Unstashable, Unstasher}
 
 use crate::core::{
     expression::{expressiongraph::ExpressionGraph, expressionnode::ExpressionNodeId},
+    jit::{
+        jit::{ExpressionTestDomain, Interval},
+        lookuptable::LookupTable,
+    },
     sound::{
+        argument::ProcessorArgumentLocation,
         expression::{ProcessorExpressionId, ProcessorExpressionLocation},
         soundgraph::SoundGraph,
         soundprocessor::SoundProcessorId,
@@ -134,6 +139,20 @@ impl Unstashable<ExpressionUiUnstashingContext<'_>> for ExpressionNodeObjectUiSt
 /// displaying any expression graph node's ui.
 pub struct ExpressionGraphUiState {
     object_states: ExpressionNodeObjectUiStates,
+
+    /// What the expression's plot is drawn against, if the user has
+    /// chosen something other than the plot's default
+    plot_domain: Option<ExpressionTestDomain>,
+
+    /// An expression node whose subexpression the user has asked to
+    /// freeze into a constant, to be done once the ui has been shown.
+    /// This is transient and not stashed.
+    node_to_freeze: Option<ExpressionNodeId>,
+
+    /// A lookup table which the user has asked the expression to be
+    /// evaluated with (or without, if None), to be applied once the ui
+    /// has been shown. This is transient and not stashed.
+    lookup_table_change: Option<Option<LookupTable>>,
 }
 
 impl ExpressionGraphUiState {
@@ -145,7 +164,12 @@ impl ExpressionGraphUiState {
     ) -> ExpressionGraphUiState {
         let object_states = ExpressionNodeObjectUiStates::generate(graph, factory);
 
-        ExpressionGraphUiState { object_states }
+        ExpressionGraphUiState {
+            object_states,
+            plot_domain: None,
+            node_to_freeze: None,
+            lookup_table_change: None,
+        }
     }
 
     /// Get a reference to the object ui states
@@ -158,6 +182,39 @@ impl ExpressionGraphUiState {
         &mut self.object_states
     }
 
+    /// Get the domain chosen for the expression's plot, if any
+    pub(crate) fn plot_domain(&self) -> Option<ExpressionTestDomain> {
+        self.plot_domain
+    }
+
+    /// Get a mutable reference to the domain chosen for the expression's plot
+    pub(crate) fn plot_domain_mut(&mut self) -> &mut Option<ExpressionTestDomain> {
+        &mut self.plot_domain
+    }
+
+    /// Ask for the subexpression rooted at the given node to be frozen
+    /// into a constant
+    pub(crate) fn request_freeze(&mut self, id: ExpressionNodeId) {
+        self.node_to_freeze = Some(id);
+    }
+
+    /// Take the node whose subexpression was asked to be frozen, if any
+    pub(crate) fn take_node_to_freeze(&mut self) -> Option<ExpressionNodeId> {
+        self.node_to_freeze.take()
+    }
+
+    /// Ask for the expression to be evaluated using the given lookup
+    /// table, or directly if None
+    pub(crate) fn request_lookup_table(&mut self, table: Option<LookupTable>) {
+        self.lookup_table_change = Some(table);
+    }
+
+    /// Take the lookup table which was asked to be used, if any change
+    /// was asked for
+    pub(crate) fn take_lookup_table_change(&mut self) -> Option<Option<LookupTable>> {
+        self.lookup_table_change.take()
+    }
+
     /// Remove any data associated with objects that no longer exist in
     /// the given graph.
     fn cleanup(&mut self, graph: &ExpressionGraph) {
@@ -168,6 +225,26 @@ impl ExpressionGraphUiState {
 impl Stashable for ExpressionGraphUiState {
     fn stash(&self, stasher: &mut Stasher<()>) {
         self.object_states.stash(stasher);
+        match self.plot_domain {
+            None => stasher.u8(0),
+            Some(ExpressionTestDomain::Temporal) => stasher.u8(1),
+            Some(ExpressionTestDomain::WithRespectTo(arg, interval)) => {
+                stasher.u8(2);
+                stasher.object(&arg);
+                let (from, to) = match interval {
+                    Interval::Linear { from, to } => {
+                        stasher.u8(0);
+                        (from, to)
+                    }
+                    Interval::Logarithmic { from, to } => {
+                        stasher.u8(1);
+                        (from, to)
+                    }
+                };
+                stasher.f32(from);
+                stasher.f32(to);
+            }
+        }
     }
 }
 
@@ -175,8 +252,33 @@ impl Unstashable<ExpressionUiUnstashingContext<'_>> for ExpressionGraphUiState {
     fn unstash(
         unstasher: &mut Unstasher<ExpressionUiUnstashingContext>,
     ) -> Result<Self, UnstashError> {
+        let object_states = ExpressionNodeObjectUiStates::unstash(unstasher)?;
+        let plot_domain = match unstasher.u8()? {
+            0 => None,
+            1 => Some(ExpressionTestDomain::Temporal),
+            2 => {
+                let arg: ProcessorArgumentLocation = unstasher.object_with_context(())?;
+                let logarithmic = match unstasher.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(UnstashError::Corrupted),
+                };
+                let from = unstasher.f32()?;
+                let to = unstasher.f32()?;
+                let interval = if logarithmic {
+                    Interval::Logarithmic { from, to }
+                } else {
+                    Interval::Linear { from, to }
+                };
+                Some(ExpressionTestDomain::WithRespectTo(arg, interval))
+            }
+            _ => return Err(UnstashError::Corrupted),
+        };
         Ok(ExpressionGraphUiState {
-            object_states: ExpressionNodeObjectUiStates::unstash(unstasher)?,
+            object_states,
+            plot_domain,
+            node_to_freeze: None,
+            lookup_table_change: None,
         })
     }
 }
@@ -9,7 +9,7 @@ use super::{
     expressiongraphuicontext::ExpressionGraphUiContext,
     expressiongraphuistate::ExpressionGraphUiState,
     lexicallayout::lexicallayout::ExpressionNodeLayout,
-    object_ui::ObjectUiState,
+    object_ui::{ObjectUiState, SummonCategory},
 };
 
 pub trait ExpressionObjectUi: Default {
@@ -31,6 +31,8 @@ pub trait ExpressionObjectUi: Default {
         ArgumentList::new_empty()
     }
 
+    fn summon_category(&self) -> SummonCategory;
+
     fn make_properties(&self) -> ExpressionNodeLayout;
 
     fn make_ui_state(
@@ -54,6 +56,8 @@ pub trait AnyExpressionObjectUi {
 
     fn summon_arguments(&self) -> ArgumentList;
 
+    fn summon_category(&self) -> SummonCategory;
+
     fn object_type(&self) -> ObjectType;
 
     fn make_properties(&self) -> ExpressionNodeLayout;
@@ -93,6 +97,10 @@ impl<T: 'static + ExpressionObjectUi> AnyExpressionObjectUi for T {
         T::summon_arguments(self)
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        T::summon_category(self)
+    }
+
     fn object_type(&self) -> ObjectType {
         <T::ObjectType as ExpressionObject>::get_type()
     }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use eframe::egui;
 
 use crate::core::{
@@ -6,6 +8,7 @@ use crate::core::{
         cache::JitCache,
         compiledexpression::{CompiledExpressionFunction, Discretization},
         jit::{ExpressionTestDomain, Interval, JitMode},
+        lookuptable::LookupTable,
     },
     sound::{
         argument::ProcessorArgumentLocation,
@@ -15,12 +18,14 @@ use crate::core::{
 
 use super::{soundgraphuinames::SoundGraphUiNames, stackedlayout::timeaxis::TimeAxis};
 
+#[derive(Clone)]
 enum VerticalRange {
     Automatic,
     // TODO: log plots?
     Linear(std::ops::RangeInclusive<f32>),
 }
 
+#[derive(Clone)]
 pub struct PlotConfig {
     // TODO: whether to always plot temporally or w.r.t. an input, e.g. wave generator amplitude vs phase
     vertical_range: VerticalRange,
@@ -54,6 +59,170 @@ impl PlotConfig {
         );
         self
     }
+
+    pub(crate) fn horizontal_domain(&self) -> ExpressionTestDomain {
+        self.horizontal_domain
+    }
+
+    pub(crate) fn with_horizontal_domain(mut self, domain: ExpressionTestDomain) -> Self {
+        self.horizontal_domain = domain;
+        self
+    }
+}
+
+/// Evaluate a function which was compiled in test mode for the given
+/// domain into `len` samples per result, spanning either the requested
+/// interval or `len` pixels' worth of time along the given time axis.
+pub(crate) fn sample_plot(
+    compiled_fn: &mut CompiledExpressionFunction,
+    len: usize,
+    horizontal_domain: &ExpressionTestDomain,
+    time_axis: TimeAxis,
+) -> Vec<Vec<f32>> {
+    let mut dsts: Vec<Vec<f32>> = Vec::new();
+    dsts.resize_with(compiled_fn.num_destination_arrays(), || {
+        let mut v = Vec::new();
+        v.resize(len, 0.0);
+        v
+    });
+
+    let mut dst_slices: Vec<&mut [f32]> = dsts.iter_mut().map(|v| &mut v[..]).collect();
+
+    let discretization = match horizontal_domain {
+        ExpressionTestDomain::Temporal => Discretization::Temporal(time_axis.time_per_x_pixel),
+        ExpressionTestDomain::WithRespectTo(_, _) => Discretization::None,
+    };
+
+    compiled_fn.eval_in_test_mode(&mut dst_slices, discretization);
+
+    dsts
+}
+
+/// The index of the sample nearest to the given horizontal screen
+/// position, for a plot of `len` samples spread evenly across `rect`
+/// from its left edge to its right edge
+pub(crate) fn nearest_sample_index(x: f32, rect: egui::Rect, len: usize) -> usize {
+    if len < 2 {
+        return 0;
+    }
+    let dx = rect.width() / (len - 1) as f32;
+    let i = ((x - rect.left()) / dx).round().max(0.0) as usize;
+    i.min(len - 1)
+}
+
+/// The horizontal value which the sample at the given index of a plot
+/// of `len` samples was evaluated at, i.e. the time since the start of
+/// the plot or the swept argument's value
+pub(crate) fn sample_horizontal_value(
+    horizontal_domain: &ExpressionTestDomain,
+    index: usize,
+    len: usize,
+    time_axis: TimeAxis,
+) -> f32 {
+    match horizontal_domain {
+        ExpressionTestDomain::Temporal => index as f32 * time_axis.time_per_x_pixel,
+        ExpressionTestDomain::WithRespectTo(_, interval) => interval.sample_value(index, len),
+    }
+}
+
+/// The interval which an argument is first plotted over after
+/// being chosen as the horizontal domain
+const DEFAULT_PLOT_INTERVAL: Interval = Interval::Linear {
+    from: -1.0,
+    to: 1.0,
+};
+
+/// Show controls for choosing what to plot an expression against,
+/// either time or one of the given arguments over some interval.
+/// `domain` is None when the plot's default domain is being used.
+pub(crate) fn show_plot_domain_picker(
+    ui: &mut egui::Ui,
+    location: ProcessorExpressionLocation,
+    domain: &mut Option<ExpressionTestDomain>,
+    default_domain: ExpressionTestDomain,
+    available_arguments: &HashSet<ProcessorArgumentLocation>,
+    names: &SoundGraphUiNames,
+) {
+    let argument_name =
+        |arg: ProcessorArgumentLocation| names.argument(arg).unwrap_or("???").to_string();
+
+    let mut arguments: Vec<(ProcessorArgumentLocation, String)> = available_arguments
+        .iter()
+        .map(|arg| (*arg, argument_name(*arg)))
+        .collect();
+    arguments.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let current = domain.unwrap_or(default_domain);
+
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("plot vs")
+                .small()
+                .color(egui::Color32::GRAY),
+        );
+
+        let selected_text = match current {
+            ExpressionTestDomain::Temporal => "time".to_string(),
+            ExpressionTestDomain::WithRespectTo(arg, _) => argument_name(arg),
+        };
+
+        let mut new_domain = current;
+
+        egui::ComboBox::from_id_salt(("plot_domain", location))
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(current == ExpressionTestDomain::Temporal, "time")
+                    .clicked()
+                {
+                    new_domain = ExpressionTestDomain::Temporal;
+                }
+                for (arg, name) in &arguments {
+                    let selected = match current {
+                        ExpressionTestDomain::WithRespectTo(a, _) => a == *arg,
+                        ExpressionTestDomain::Temporal => false,
+                    };
+                    if ui.selectable_label(selected, name).clicked() && !selected {
+                        let interval = match current {
+                            ExpressionTestDomain::WithRespectTo(_, interval) => interval,
+                            ExpressionTestDomain::Temporal => DEFAULT_PLOT_INTERVAL,
+                        };
+                        new_domain = ExpressionTestDomain::WithRespectTo(*arg, interval);
+                    }
+                }
+            });
+
+        if let ExpressionTestDomain::WithRespectTo(arg, interval) = new_domain {
+            let (mut from, mut to, mut logarithmic) = match interval {
+                Interval::Linear { from, to } => (from, to, false),
+                Interval::Logarithmic { from, to } => (from, to, true),
+            };
+            ui.add(egui::DragValue::new(&mut from).speed(0.01).prefix("from "));
+            ui.add(egui::DragValue::new(&mut to).speed(0.01).prefix("to "));
+            // Logarithmic intervals can't include zero or negative values
+            let can_be_logarithmic = from > 0.0 && to > 0.0;
+            ui.add_enabled(
+                can_be_logarithmic,
+                egui::Checkbox::new(&mut logarithmic, "log"),
+            );
+            if from != to {
+                let interval = if logarithmic && can_be_logarithmic {
+                    Interval::Logarithmic { from, to }
+                } else {
+                    Interval::Linear { from, to }
+                };
+                new_domain = ExpressionTestDomain::WithRespectTo(arg, interval);
+            }
+        }
+
+        if new_domain != current {
+            *domain = if new_domain == default_domain {
+                None
+            } else {
+                Some(new_domain)
+            };
+        }
+    });
 }
 
 pub(crate) struct ExpressionPlot {
@@ -131,21 +300,7 @@ impl ExpressionPlot {
         names: &SoundGraphUiNames,
     ) {
         let len = rect.width().floor() as usize;
-        let mut dsts: Vec<Vec<f32>> = Vec::new();
-        dsts.resize_with(compiled_fn.num_destination_arrays(), || {
-            let mut v = Vec::new();
-            v.resize(len, 0.0);
-            v
-        });
-
-        let mut dst_slices: Vec<&mut [f32]> = dsts.iter_mut().map(|v| &mut v[..]).collect();
-
-        let discretization = match horizontal_domain {
-            ExpressionTestDomain::Temporal => Discretization::Temporal(time_axis.time_per_x_pixel),
-            ExpressionTestDomain::WithRespectTo(_, _) => Discretization::None,
-        };
-
-        compiled_fn.eval_in_test_mode(&mut dst_slices, discretization);
+        let dsts = sample_plot(&mut compiled_fn, len, horizontal_domain, time_axis);
 
         let (vmin, vmax) = match vertical_range {
             VerticalRange::Automatic => {
@@ -174,7 +329,7 @@ impl ExpressionPlot {
 
         // TODO: different colours for different arrays?
         // And match those colours to colours in the expression ui?
-        for dst in dsts {
+        for dst in &dsts {
             for (i, (v0, v1)) in dst.iter().zip(&dst[1..]).enumerate() {
                 let x0 = rect.left() + i as f32 * dx;
                 let x1 = rect.left() + (i + 1) as f32 * dx;
@@ -206,6 +361,35 @@ impl ExpressionPlot {
 
         let font_id = egui::FontId::monospace(10.0);
 
+        // Read off the exact values of the sample nearest the pointer
+        if let Some(pointer_pos) = ui.ctx().pointer_hover_pos() {
+            if rect.contains(pointer_pos) && len > 1 {
+                let i = nearest_sample_index(pointer_pos.x, rect, len);
+                let x = rect.left() + i as f32 * dx;
+                let h = sample_horizontal_value(horizontal_domain, i, len, time_axis);
+                let mut readout = format!("{:.3}", h);
+                for dst in &dsts {
+                    let v = dst[i];
+                    readout += &format!(", {:.3}", v);
+                    if v.is_finite() {
+                        let t = ((v - plot_vmin) / plot_v_range).clamp(0.0, 1.0);
+                        ui.painter().circle_filled(
+                            egui::pos2(x, rect.bottom() - t * rect.height()),
+                            3.0,
+                            egui::Color32::WHITE,
+                        );
+                    }
+                }
+                ui.painter().text(
+                    rect.right_top(),
+                    egui::Align2::RIGHT_TOP,
+                    format!("({})", readout),
+                    font_id.clone(),
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+
         // Write the vertical max at the top left
         ui.painter().text(
             rect.left_top(),
@@ -239,6 +423,7 @@ impl ExpressionPlot {
 
                 let (domain_start, domain_end) = match domain {
                     Interval::Linear { from, to } => (from, to),
+                    Interval::Logarithmic { from, to } => (from, to),
                 };
 
                 // write domain min at left
@@ -307,3 +492,78 @@ impl ExpressionPlot {
         ui.ctx().request_repaint();
     }
 }
+
+/// Show controls for evaluating an expression by reading from a lookup
+/// table instead of evaluating it directly. A new table spans the
+/// argument and interval which the expression is plotted against, and
+/// follows them for as long as they are plotted. `applies` is whether
+/// the table can stand in for the expression at all. Returns the table
+/// to use if it was changed.
+pub(crate) fn show_lookup_table_picker(
+    ui: &mut egui::Ui,
+    current: Option<LookupTable>,
+    domain: ExpressionTestDomain,
+    applies: bool,
+    names: &SoundGraphUiNames,
+) -> Option<Option<LookupTable>> {
+    let plotted = match domain {
+        ExpressionTestDomain::WithRespectTo(arg, interval) => Some((arg, interval)),
+        ExpressionTestDomain::Temporal => None,
+    };
+
+    // A table can only be started from an argument being plotted against
+    if current.is_none() && plotted.is_none() {
+        return None;
+    }
+
+    let mut new_table = current;
+
+    ui.horizontal(|ui| {
+        let mut enabled = current.is_some();
+        ui.checkbox(&mut enabled, "lookup table");
+
+        new_table = match (enabled, current) {
+            (false, _) => None,
+            (true, None) => {
+                let (arg, interval) = plotted.unwrap();
+                Some(LookupTable::new(arg, interval, LookupTable::DEFAULT_SIZE))
+            }
+            (true, Some(table)) => {
+                let mut size = table.size();
+                ui.add(
+                    egui::DragValue::new(&mut size)
+                        .range(LookupTable::MIN_SIZE..=LookupTable::MAX_SIZE)
+                        .prefix("size "),
+                );
+                let (arg, interval) = plotted.unwrap_or((table.argument(), table.interval()));
+                Some(LookupTable::new(arg, interval, size))
+            }
+        };
+
+        if let Some(table) = new_table {
+            let mut notes = Vec::new();
+            if plotted.is_none() {
+                notes.push(format!(
+                    "over {}",
+                    names.argument(table.argument()).unwrap_or("???")
+                ));
+            }
+            if !applies {
+                notes.push("unused, depends on more than one parameter".to_string());
+            }
+            if !notes.is_empty() {
+                ui.label(
+                    egui::RichText::new(notes.join(", "))
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+            }
+        }
+    });
+
+    if new_table != current {
+        Some(new_table)
+    } else {
+        None
+    }
+}
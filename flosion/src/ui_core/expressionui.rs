@@ -1,11 +1,13 @@
 use eframe::egui;
 
-use crate::core::expression::expressiongraph::ExpressionGraph;
+use crate::core::{expression::expressiongraph::ExpressionGraph, jit::jit::ExpressionTestDomain};
 
 use super::{
     expressiongraphuicontext::{ExpressionGraphUiContext, OuterExpressionGraphUiContext},
     expressiongraphuistate::ExpressionGraphUiState,
-    expressionplot::{ExpressionPlot, PlotConfig},
+    expressionplot::{
+        show_lookup_table_picker, show_plot_domain_picker, ExpressionPlot, PlotConfig,
+    },
     lexicallayout::lexicallayout::LexicalLayout,
 };
 
@@ -39,6 +41,20 @@ impl SoundExpressionUi {
                         OuterExpressionGraphUiContext::ProcessorExpression(proc_expr_ctx) => {
                             layout.show(ui, ui_state, expr_graph, ctx, outer_context);
 
+                            // Forget the chosen domain if its argument is no longer in scope
+                            if let Some(ExpressionTestDomain::WithRespectTo(arg, _)) =
+                                ui_state.plot_domain()
+                            {
+                                if !proc_expr_ctx.available_arguments().contains(&arg) {
+                                    *ui_state.plot_domain_mut() = None;
+                                }
+                            }
+
+                            let chosen_plot_config = match ui_state.plot_domain() {
+                                Some(domain) => plot_config.clone().with_horizontal_domain(domain),
+                                None => plot_config.clone(),
+                            };
+
                             ExpressionPlot::new().show(
                                 ui,
                                 ctx.jit_cache(),
@@ -46,9 +62,52 @@ impl SoundExpressionUi {
                                 expr_graph,
                                 proc_expr_ctx.mapping(),
                                 *proc_expr_ctx.time_axis(),
-                                plot_config,
+                                &chosen_plot_config,
+                                proc_expr_ctx.sound_graph_names(),
+                            );
+
+                            show_plot_domain_picker(
+                                ui,
+                                proc_expr_ctx.location(),
+                                ui_state.plot_domain_mut(),
+                                plot_config.horizontal_domain(),
+                                proc_expr_ctx.available_arguments(),
+                                proc_expr_ctx.sound_graph_names(),
+                            );
+
+                            let lookup_table = proc_expr_ctx.mapping().lookup_table();
+                            let table_change = show_lookup_table_picker(
+                                ui,
+                                lookup_table,
+                                ui_state
+                                    .plot_domain()
+                                    .unwrap_or(plot_config.horizontal_domain()),
+                                lookup_table.is_none_or(|table| {
+                                    table.applies_to(expr_graph, proc_expr_ctx.mapping())
+                                }),
                                 proc_expr_ctx.sound_graph_names(),
                             );
+                            if let Some(table) = table_change {
+                                ui_state.request_lookup_table(table);
+                            }
+
+                            let mut dependencies: Vec<String> = proc_expr_ctx
+                                .mapping()
+                                .dependencies(expr_graph)
+                                .into_iter()
+                                .map(|target| proc_expr_ctx.target_name(target))
+                                .collect();
+                            dependencies.sort();
+                            if !dependencies.is_empty() {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "depends on: {}",
+                                        dependencies.join(", ")
+                                    ))
+                                    .small()
+                                    .color(egui::Color32::GRAY),
+                                );
+                            }
                         }
                     }
                 });
@@ -3,10 +3,15 @@ use std::thread::{self, ScopedJoinHandle};
 use crate::core::{
     engine::{
         garbage::GarbageDisposer,
-        soundengine::{create_sound_engine, SoundEngineInterface, StopButton},
+        soundengine::{
+            create_sound_engine, SoundEngineInterface, StopButton, TestToneSignal, MAX_BLOCK_SIZE,
+        },
+        voicelimit::{VoiceLimitPolicy, MAX_RELEASE_FADE_SAMPLES, MAX_TRACKED_VOICES},
     },
     jit::cache::JitCache,
+    samplefrequency::SAMPLE_FREQUENCY,
     sound::soundgraph::SoundGraph,
+    soundchunk::CHUNK_SIZE,
 };
 use eframe::{
     self,
@@ -19,8 +24,18 @@ use super::{
     appstate::AppState,
     factories::Factories,
     history::{History, SnapshotFlag},
+    summon_widget::SummonHistories,
 };
 
+/// The greatest number of items of garbage from the audio thread to
+/// dispose of per frame, so that the ui doesn't stall when lots of
+/// garbage arrives at once
+const GARBAGE_ITEMS_PER_FRAME: usize = 64;
+
+/// The number of voices to which the engine is limited when the
+/// voice limit is first switched on
+const DEFAULT_VOICE_LIMIT: usize = 32;
+
 /// The very root of the GUI, which manages a SoundGraph instance,
 /// responds to inputs, and draws the up-to-date ui via egui
 pub struct FlosionApp<'ctx> {
@@ -49,7 +64,7 @@ pub struct FlosionApp<'ctx> {
 
 impl<'ctx> FlosionApp<'ctx> {
     pub fn new(
-        _cc: &eframe::CreationContext,
+        cc: &eframe::CreationContext,
         inkwell_context: &'ctx inkwell::context::Context,
         scope: &'ctx thread::Scope<'ctx, '_>,
     ) -> FlosionApp<'ctx> {
@@ -66,7 +81,12 @@ impl<'ctx> FlosionApp<'ctx> {
 
         let graph = SoundGraph::new();
 
-        let state = AppState::new();
+        let mut state = AppState::new();
+
+        // Summon histories are saved between sessions, apart from the graph
+        if let Some(storage) = cc.storage {
+            *state.summon_histories_mut() = SummonHistories::load(storage);
+        }
 
         let mut app = FlosionApp {
             graph,
@@ -117,13 +137,19 @@ impl<'ctx> FlosionApp<'ctx> {
                 .push_snapshot(&self.stash, &self.graph, &self.state);
         }
 
-        let (ctrl_z, ctrl_y) = ui.input_mut(|i| {
+        let (ctrl_z, ctrl_y, ctrl_m) = ui.input_mut(|i| {
             (
                 i.consume_shortcut(&KeyboardShortcut::new(Modifiers::CTRL, Key::Z)),
                 i.consume_shortcut(&KeyboardShortcut::new(Modifiers::CTRL, Key::Y)),
+                i.consume_shortcut(&KeyboardShortcut::new(Modifiers::CTRL, Key::M)),
             )
         });
 
+        if ctrl_m {
+            let panic_switch = self.engine_interface.panic_switch();
+            panic_switch.set_engaged(!panic_switch.is_engaged());
+        }
+
         if ctrl_z {
             self.history.undo(
                 &self.stash,
@@ -164,6 +190,149 @@ impl<'ctx> FlosionApp<'ctx> {
 
 impl<'ctx> eframe::App for FlosionApp<'ctx> {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let underruns = self.engine_interface.underrun_count();
+                let text = format!("Underruns: {}", underruns);
+                if underruns > 0 {
+                    ui.colored_label(egui::Color32::from_rgb(224, 160, 32), text)
+                        .on_hover_text(
+                            "The audio engine could not keep up and audio may have dropped out",
+                        );
+                } else {
+                    ui.label(text);
+                }
+                ui.separator();
+                let panic_switch = self.engine_interface.panic_switch();
+                let mut engaged = panic_switch.is_engaged();
+                let text = if engaged { "Muted" } else { "Mute" };
+                if ui
+                    .toggle_value(&mut engaged, text)
+                    .on_hover_text("Silence all output and stop starting new voices (Ctrl+M)")
+                    .changed()
+                {
+                    panic_switch.set_engaged(engaged);
+                }
+                ui.separator();
+                let test_tone = self.engine_interface.test_tone();
+                let mut enabled = test_tone.is_enabled();
+                if ui
+                    .toggle_value(&mut enabled, "Test tone")
+                    .on_hover_text("Replace all output with a steady signal at a known level")
+                    .changed()
+                {
+                    test_tone.set_enabled(enabled);
+                }
+                if enabled {
+                    for (signal, name) in [
+                        (TestToneSignal::Sine, "1 kHz sine"),
+                        (TestToneSignal::PinkNoise, "Pink noise"),
+                    ] {
+                        if ui
+                            .selectable_label(test_tone.signal() == signal, name)
+                            .clicked()
+                        {
+                            test_tone.set_signal(signal);
+                        }
+                    }
+                    let mut level = test_tone.level();
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut level)
+                                .range(-60.0..=0.0)
+                                .speed(0.1)
+                                .suffix(" dBFS RMS"),
+                        )
+                        .changed()
+                    {
+                        test_tone.set_level(level);
+                    }
+                }
+                ui.separator();
+                let voice_limit = self.engine_interface.voice_limit();
+                let mut limited = voice_limit.max_voices().is_some();
+                if ui
+                    .toggle_value(&mut limited, "Voice limit")
+                    .on_hover_text("Cap the number of voices playing at once across all processors")
+                    .changed()
+                {
+                    voice_limit.set_max_voices(limited.then_some(DEFAULT_VOICE_LIMIT));
+                }
+                if let Some(mut max_voices) = voice_limit.max_voices() {
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut max_voices)
+                                .range(1..=MAX_TRACKED_VOICES)
+                                .suffix(" voices"),
+                        )
+                        .changed()
+                    {
+                        voice_limit.set_max_voices(Some(max_voices));
+                    }
+                    for (policy, name) in [
+                        (VoiceLimitPolicy::StealOldest, "Steal oldest"),
+                        (VoiceLimitPolicy::StealQuietest, "Steal quietest"),
+                        (VoiceLimitPolicy::RejectNew, "Reject new"),
+                    ] {
+                        if ui
+                            .selectable_label(voice_limit.policy() == policy, name)
+                            .clicked()
+                        {
+                            voice_limit.set_policy(policy);
+                        }
+                    }
+                }
+                ui.label(format!("Voices: {}", voice_limit.active_voices()));
+                ui.label("Release fade");
+                let samples_per_ms = SAMPLE_FREQUENCY as f32 / 1000.0;
+                let mut fade_ms = voice_limit.release_fade_samples() as f32 / samples_per_ms;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut fade_ms)
+                            .range(0.0..=(MAX_RELEASE_FADE_SAMPLES as f32 / samples_per_ms))
+                            .speed(0.1)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text("How quickly voices which are stolen or cut off fade out")
+                    .changed()
+                {
+                    voice_limit
+                        .set_release_fade_samples((fade_ms * samples_per_ms).round() as usize);
+                }
+                ui.separator();
+                ui.label("Block size");
+                let block_size = self.engine_interface.block_size();
+                let mut frames = block_size.frames();
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut frames)
+                            .range(CHUNK_SIZE..=MAX_BLOCK_SIZE)
+                            .speed(CHUNK_SIZE as f64 / 16.0)
+                            .suffix(" samples"),
+                    )
+                    .on_hover_text(
+                        "How much audio is processed at once. Larger blocks drop out \
+                        less often but take longer to be heard.",
+                    )
+                    .changed()
+                {
+                    block_size.set_frames(frames);
+                }
+                ui.separator();
+                let report = self.engine_interface.report();
+                ui.label(format!(
+                    "Scratch chunks: {} used / {} reserved",
+                    report.scratch_chunks_high_water_mark(),
+                    report.scratch_chunks_reserved()
+                ));
+                let leaked = self.garbage_disposer.leaked();
+                if leaked > 0 {
+                    ui.label(format!("Leaked garbage: {} items", leaked))
+                        .on_hover_text("Items the audio thread had no room to send away");
+                }
+            });
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
             #[cfg(debug_assertions)]
             self.check_invariants();
@@ -180,12 +349,22 @@ impl<'ctx> eframe::App for FlosionApp<'ctx> {
                 )
                 .expect("Failed to update engine");
 
-            self.garbage_disposer.clear();
+            self.garbage_disposer.clear_at_most(GARBAGE_ITEMS_PER_FRAME);
+
+            // Come back soon for whatever garbage is left over
+            if self.garbage_disposer.pending() > 0 {
+                ctx.request_repaint();
+            }
         });
     }
 
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.state.summon_histories().save(storage);
+    }
+
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         self.stop_button.stop();
         self.audio_thread.take().unwrap().join().unwrap();
+        self.garbage_disposer.flush();
     }
 }
@@ -0,0 +1,189 @@
+use eframe::egui;
+
+use crate::core::samplefrequency::SAMPLE_FREQUENCY;
+
+/// Lowest frequency shown by a frequency response plot, in Hz
+const MIN_FREQUENCY: f32 = 20.0;
+
+/// Lowest and highest levels shown by a frequency response plot, in decibels
+const MIN_DECIBELS: f32 = -48.0;
+const MAX_DECIBELS: f32 = 12.0;
+
+/// Number of frequencies at which the response is evaluated
+const NUM_SAMPLES: usize = 128;
+
+/// Highest frequency shown by a frequency response plot, in Hz
+fn max_frequency() -> f32 {
+    0.5 * SAMPLE_FREQUENCY as f32
+}
+
+/// A filter's response at a single frequency
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrequencyResponseSample {
+    /// Frequency, in Hz
+    pub frequency: f32,
+    /// Magnitude, in decibels
+    pub decibels: f32,
+    /// Phase, in radians
+    pub phase: f32,
+}
+
+/// Evaluate a filter's complex gain, given as a (real, imaginary) pair
+/// for a frequency in Hz, at logarithmically spaced frequencies
+/// spanning the audible range up to the Nyquist frequency.
+pub(crate) fn sample_frequency_response<F: Fn(f32) -> (f32, f32)>(
+    response: F,
+    num_samples: usize,
+) -> Vec<FrequencyResponseSample> {
+    debug_assert!(num_samples >= 2);
+    let log_min = MIN_FREQUENCY.ln();
+    let log_max = max_frequency().ln();
+    (0..num_samples)
+        .map(|i| {
+            let t = i as f32 / (num_samples - 1) as f32;
+            let frequency = (log_min + t * (log_max - log_min)).exp();
+            let (re, im) = response(frequency);
+            FrequencyResponseSample {
+                frequency,
+                decibels: 10.0 * (re * re + im * im).log10(),
+                phase: im.atan2(re),
+            }
+        })
+        .collect()
+}
+
+/// Plot of a filter's magnitude response in decibels against frequency
+/// on a logarithmic scale, optionally with its phase response, and with
+/// markers at frequencies of interest such as a cutoff.
+pub struct FrequencyResponsePlot {
+    samples: Vec<FrequencyResponseSample>,
+    markers: Vec<f32>,
+    show_phase: bool,
+    width: f32,
+}
+
+impl FrequencyResponsePlot {
+    pub(crate) fn new<F: Fn(f32) -> (f32, f32)>(response: F) -> FrequencyResponsePlot {
+        FrequencyResponsePlot {
+            samples: sample_frequency_response(response, NUM_SAMPLES),
+            markers: Vec::new(),
+            show_phase: false,
+            width: 200.0,
+        }
+    }
+
+    pub fn marker(mut self, frequency: f32) -> FrequencyResponsePlot {
+        self.markers.push(frequency);
+        self
+    }
+
+    pub fn show_phase(mut self, show_phase: bool) -> FrequencyResponsePlot {
+        self.show_phase = show_phase;
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> FrequencyResponsePlot {
+        self.width = width;
+        self
+    }
+
+    /// Map a frequency to a fraction of the plot's width
+    fn frequency_to_fraction(frequency: f32) -> f32 {
+        let log_min = MIN_FREQUENCY.ln();
+        let log_max = max_frequency().ln();
+        ((frequency.ln() - log_min) / (log_max - log_min)).clamp(0.0, 1.0)
+    }
+}
+
+impl egui::Widget for FrequencyResponsePlot {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let height = 60.0;
+
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(self.width, height), egui::Sense::hover());
+
+        let painter = ui.painter();
+
+        painter.rect_filled(rect, 0.0, egui::Color32::BLACK);
+
+        let x_of =
+            |frequency: f32| rect.left() + Self::frequency_to_fraction(frequency) * rect.width();
+        let y_of_decibels = |decibels: f32| {
+            let t = ((decibels - MIN_DECIBELS) / (MAX_DECIBELS - MIN_DECIBELS)).clamp(0.0, 1.0);
+            rect.bottom() - t * rect.height()
+        };
+
+        // Unity gain
+        let y_zero = y_of_decibels(0.0);
+        painter.line_segment(
+            [
+                egui::pos2(rect.left(), y_zero),
+                egui::pos2(rect.right(), y_zero),
+            ],
+            egui::Stroke::new(1.0, egui::Color32::from_white_alpha(32)),
+        );
+
+        for frequency in &self.markers {
+            let x = x_of(*frequency);
+            painter.line_segment(
+                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(224, 160, 64)),
+            );
+        }
+
+        if self.show_phase {
+            let points: Vec<egui::Pos2> = self
+                .samples
+                .iter()
+                .map(|s| {
+                    let t = 0.5 + 0.5 * s.phase / std::f32::consts::PI;
+                    egui::pos2(x_of(s.frequency), rect.bottom() - t * rect.height())
+                })
+                .collect();
+            painter.add(egui::Shape::line(
+                points,
+                egui::Stroke::new(
+                    1.0,
+                    egui::Color32::from_rgba_unmultiplied(64, 128, 255, 128),
+                ),
+            ));
+        }
+
+        let points: Vec<egui::Pos2> = self
+            .samples
+            .iter()
+            .map(|s| egui::pos2(x_of(s.frequency), y_of_decibels(s.decibels)))
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(2.0, egui::Color32::from_white_alpha(128)),
+        ));
+
+        let font_id = egui::FontId::monospace(10.0);
+        painter.text(
+            rect.left_top(),
+            egui::Align2::LEFT_TOP,
+            format!("{} dB", MAX_DECIBELS),
+            font_id.clone(),
+            egui::Color32::from_white_alpha(128),
+        );
+        painter.text(
+            rect.left_bottom(),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{} Hz", MIN_FREQUENCY),
+            font_id.clone(),
+            egui::Color32::from_white_alpha(128),
+        );
+        painter.text(
+            rect.right_bottom(),
+            egui::Align2::RIGHT_BOTTOM,
+            format!("{} Hz", max_frequency()),
+            font_id,
+            egui::Color32::from_white_alpha(128),
+        );
+
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(2.0, egui::Color32::GRAY));
+
+        response
+    }
+}
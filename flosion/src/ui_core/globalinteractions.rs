@@ -1,9 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use eframe::egui;
 use hashstash::{Order, Stash, Stashable, Stasher, UnstashError, Unstashable, Unstasher};
 
 use crate::core::{
+    jit::cache::JitCache,
     objecttype::ObjectType,
     sound::{
         soundgraph::SoundGraph, soundgraphid::SoundObjectId, soundprocessor::SoundProcessorId,
@@ -11,20 +12,32 @@ use crate::core::{
 };
 
 use super::{
+    arguments::ParsedArguments,
     expressiongraphuistate::ExpressionUiCollection,
     factories::Factories,
     graph_properties::GraphProperties,
     history::SnapshotFlag,
     interactions::{
+        bounce::{bounce_processor, BOUNCE_MAX_SECONDS, BOUNCE_MIN_SECONDS},
         draganddrop::{DragDropSubject, DragInteraction, DropInteraction},
+        duplicateprocessors::{
+            copy_processors, duplicate_processors, paste_processors, CopiedProcessors,
+        },
         keyboardnav::KeyboardNavInteraction,
+        replaceprocessor::{can_replace_processor, replace_processor_in_graph},
     },
+    processorpalette::{build_processor_palette, jump_to_processor},
     soundgraphuinames::SoundGraphUiNames,
     soundobjectpositions::SoundObjectPositions,
     soundobjectui::SoundObjectUiFactory,
     soundobjectuistate::SoundObjectUiStates,
-    stackedlayout::stackedlayout::StackedLayout,
-    summon_widget::{SummonWidget, SummonWidgetState, SummonWidgetStateBuilder},
+    stackedlayout::{
+        alignment::{align_left, align_top, distribute_horizontally, Arrangement},
+        stackedlayout::StackedLayout,
+    },
+    summon_widget::{
+        SummonHistories, SummonHistory, SummonWidget, SummonWidgetState, SummonWidgetStateBuilder,
+    },
 };
 
 struct SelectingArea {
@@ -76,6 +89,34 @@ enum UiMode {
     /// The summon widget is open and an object's name is being typed
     /// along with any of its options
     Summoning(SummonWidgetState<ObjectType>),
+
+    /// The summon widget is open to choose a new processor which will
+    /// take the place of an existing one, keeping its connections
+    Replacing {
+        processor: SoundProcessorId,
+        summon_widget: SummonWidgetState<ObjectType>,
+    },
+
+    /// The summon widget is open to find an existing processor by name,
+    /// which the view will jump to
+    SearchingProcessors(SummonWidgetState<SoundProcessorId>),
+
+    /// A duration is being chosen for rendering a processor's output
+    /// into a new audio clip
+    Bouncing {
+        processor: SoundProcessorId,
+        seconds: f32,
+    },
+}
+
+/// Processors which were copied with ctrl+C and can be pasted with
+/// ctrl+V, along with where they were placed when they were copied
+pub(crate) struct ProcessorClipboard {
+    processors: CopiedProcessors,
+
+    /// The body and outer rectangles of each copied processor, keyed by
+    /// the id of the processor it was copied from
+    rects: HashMap<SoundProcessorId, (egui::Rect, egui::Rect)>,
 }
 
 pub(crate) struct GlobalInteractions {
@@ -84,6 +125,22 @@ pub(crate) struct GlobalInteractions {
     mode: UiMode,
 }
 
+/// The parts of the sound graph ui's state which the top-level
+/// interactions read and modify, besides the graph and its layout
+pub(crate) struct GlobalInteractionContext<'a, 'ctx> {
+    pub(crate) factories: &'a Factories,
+    pub(crate) properties: &'a GraphProperties,
+    pub(crate) names: &'a SoundGraphUiNames,
+    pub(crate) jit_cache: &'a JitCache<'ctx>,
+    pub(crate) stash: &'a Stash,
+    pub(crate) snapshot_flag: &'a SnapshotFlag,
+    pub(crate) object_states: &'a mut SoundObjectUiStates,
+    pub(crate) positions: &'a mut SoundObjectPositions,
+    pub(crate) expression_uis: &'a mut ExpressionUiCollection,
+    pub(crate) clipboard: &'a mut Option<ProcessorClipboard>,
+    pub(crate) summon_histories: &'a mut SummonHistories,
+}
+
 /// Public methods
 impl GlobalInteractions {
     /// Create a new GlobalInteractions instance
@@ -97,18 +154,40 @@ impl GlobalInteractions {
     pub(crate) fn interact_and_draw(
         &mut self,
         ui: &mut egui::Ui,
-        factories: &Factories,
+        ctx: &mut GlobalInteractionContext,
         graph: &mut SoundGraph,
-        properties: &GraphProperties,
         layout: &mut StackedLayout,
-        object_states: &mut SoundObjectUiStates,
-        positions: &mut SoundObjectPositions,
-        expression_uis: &mut ExpressionUiCollection,
-        names: &SoundGraphUiNames,
         bg_response: egui::Response,
-        stash: &Stash,
-        snapshot_flag: &SnapshotFlag,
     ) {
+        if let UiMode::Passive | UiMode::Selecting(_) = &self.mode {
+            let pressed_ctrl_v =
+                ui.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::V));
+            if let (true, Some(clipboard)) = (pressed_ctrl_v, ctx.clipboard.as_ref()) {
+                let position = ui
+                    .ctx()
+                    .pointer_latest_pos()
+                    .unwrap_or(egui::pos2(50.0, 50.0));
+                let Some(copies) = Self::paste(
+                    clipboard,
+                    position,
+                    ctx.factories,
+                    graph,
+                    ctx.object_states,
+                    ctx.positions,
+                    ctx.stash,
+                ) else {
+                    return;
+                };
+                layout.regenerate(graph, ctx.positions);
+                self.mode = UiMode::Selecting(SelectingState {
+                    objects: copies.values().map(|spid| (*spid).into()).collect(),
+                    selecting_area: None,
+                });
+                ctx.snapshot_flag.request_snapshot();
+                return;
+            }
+        }
+
         match &mut self.mode {
             UiMode::Passive => {
                 let pressed_tab =
@@ -120,7 +199,11 @@ impl GlobalInteractions {
                         .ctx()
                         .pointer_latest_pos()
                         .unwrap_or(egui::pos2(50.0, 50.0));
-                    self.start_summoning(position, factories.sound_uis())
+                    self.start_summoning(
+                        position,
+                        ctx.factories.sound_uis(),
+                        &ctx.summon_histories.sound,
+                    )
                 } else if bg_response.drag_started() {
                     // If the background was just clicked and dragged, start making a selection
                     let pointer_pos = bg_response.interact_pointer_pos().unwrap();
@@ -134,39 +217,72 @@ impl GlobalInteractions {
                 }
             }
             UiMode::UsingKeyboardNav(keyboard_nav) => {
-                keyboard_nav.interact_and_draw(
-                    ui,
-                    graph,
-                    layout,
-                    positions,
-                    expression_uis,
-                    factories,
-                    stash,
-                    names,
-                    properties,
-                    snapshot_flag,
-                );
+                keyboard_nav.interact_and_draw(ui, ctx, graph, layout);
             }
             UiMode::Selecting(selection) => {
-                let (pressed_esc, pressed_delete) = ui.input_mut(|i| {
+                let (
+                    pressed_esc,
+                    pressed_delete,
+                    pressed_tab,
+                    pressed_ctrl_d,
+                    pressed_ctrl_b,
+                    pressed_ctrl_c,
+                ) = ui.input_mut(|i| {
                     (
                         i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
                         i.consume_key(egui::Modifiers::NONE, egui::Key::Delete),
+                        i.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+                        i.consume_key(egui::Modifiers::CTRL, egui::Key::D),
+                        i.consume_key(egui::Modifiers::CTRL, egui::Key::B),
+                        i.consume_key(egui::Modifiers::CTRL, egui::Key::C),
                     )
                 });
 
                 if pressed_esc {
                     self.mode = UiMode::Passive;
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
+                    return;
+                }
+
+                // If tab was pressed while a single processor is selected,
+                // summon a processor to replace it with
+                if pressed_tab && selection.objects.len() == 1 {
+                    let SoundObjectId::Sound(spid) = *selection.objects.iter().next().unwrap();
+                    let position = ctx
+                        .positions
+                        .find_processor(spid)
+                        .unwrap()
+                        .body_rect
+                        .left_top();
+                    let summon_widget = Self::build_summon_widget(
+                        position,
+                        ctx.factories.sound_uis(),
+                        &ctx.summon_histories.sound,
+                    );
+                    self.mode = UiMode::Replacing {
+                        processor: spid,
+                        summon_widget,
+                    };
+                    return;
+                }
+
+                // If ctrl+B was pressed while a single processor is selected,
+                // choose how much of its output to render into a new audio clip
+                if pressed_ctrl_b && selection.objects.len() == 1 {
+                    let SoundObjectId::Sound(spid) = *selection.objects.iter().next().unwrap();
+                    self.mode = UiMode::Bouncing {
+                        processor: spid,
+                        seconds: Self::DEFAULT_BOUNCE_SECONDS,
+                    };
                     return;
                 }
 
                 if pressed_delete {
                     graph
                         .try_make_change(
-                            stash,
-                            factories.sound_objects(),
-                            factories.expression_objects(),
+                            ctx.stash,
+                            ctx.factories.sound_objects(),
+                            ctx.factories.expression_objects(),
                             |graph| {
                                 let objects: Vec<SoundObjectId> =
                                     selection.objects.iter().cloned().collect();
@@ -182,10 +298,116 @@ impl GlobalInteractions {
                         )
                         .expect("Nah you can't delete those, sorry");
                     self.mode = UiMode::Passive;
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
+                    return;
+                }
+
+                // If ctrl+C was pressed, copy the selected processors, which
+                // can then be pasted any number of times with ctrl+V
+                if pressed_ctrl_c {
+                    let originals: HashSet<SoundProcessorId> = selection
+                        .objects
+                        .iter()
+                        .map(|oid| match oid {
+                            SoundObjectId::Sound(spid) => *spid,
+                        })
+                        .collect();
+
+                    *ctx.clipboard = Some(ProcessorClipboard {
+                        processors: copy_processors(
+                            graph,
+                            &originals,
+                            ctx.stash,
+                            ctx.factories.sound_objects(),
+                            ctx.factories.expression_objects(),
+                        ),
+                        rects: Self::processor_rects(&originals, ctx.positions),
+                    });
+                    return;
+                }
+
+                // If ctrl+D was pressed, duplicate the selected processors and
+                // place the copies to the right of the originals, then select them
+                if pressed_ctrl_d {
+                    let originals: HashSet<SoundProcessorId> = selection
+                        .objects
+                        .iter()
+                        .map(|oid| match oid {
+                            SoundObjectId::Sound(spid) => *spid,
+                        })
+                        .collect();
+
+                    let res = graph.try_make_change(
+                        ctx.stash,
+                        ctx.factories.sound_objects(),
+                        ctx.factories.expression_objects(),
+                        |graph| {
+                            Ok(duplicate_processors(
+                                graph,
+                                &originals,
+                                ctx.stash,
+                                ctx.factories.sound_objects(),
+                                ctx.factories.expression_objects(),
+                            ))
+                        },
+                    );
+
+                    let copies = match res {
+                        Ok(copies) => copies,
+                        Err(e) => {
+                            println!("Can't duplicate those processors: {}", e.explain(graph));
+                            return;
+                        }
+                    };
+
+                    let bounds = originals
+                        .iter()
+                        .map(|spid| ctx.positions.find_processor(*spid).unwrap().outer_rect)
+                        .reduce(|a, b| a.union(b))
+                        .unwrap();
+                    let offset = egui::vec2(bounds.width() + Self::DUPLICATE_SPACING, 0.0);
+
+                    let rects = Self::processor_rects(&originals, ctx.positions);
+                    Self::place_copies(
+                        &copies,
+                        &rects,
+                        offset,
+                        ctx.factories,
+                        graph,
+                        ctx.object_states,
+                        ctx.positions,
+                    );
+
+                    layout.regenerate(graph, ctx.positions);
+
+                    selection.objects = copies.values().map(|spid| (*spid).into()).collect();
+                    ctx.snapshot_flag.request_snapshot();
                     return;
                 }
 
+                // Arrange the groups of the selected processors if asked to
+                // Alt+L aligns left, Alt+T aligns top, Alt+H distributes horizontally
+                let arrangements: [(egui::Key, Arrangement); 3] = [
+                    (egui::Key::L, align_left),
+                    (egui::Key::T, align_top),
+                    (egui::Key::H, distribute_horizontally),
+                ];
+                let arrangement = ui.input_mut(|i| {
+                    arrangements
+                        .into_iter()
+                        .find(|(key, _)| i.consume_key(egui::Modifiers::ALT, *key))
+                        .map(|(_, arrangement)| arrangement)
+                });
+                if let Some(arrangement) = arrangement {
+                    Self::arrange_selected_groups(
+                        &selection.objects,
+                        layout,
+                        ctx.positions,
+                        arrangement,
+                    );
+                    ctx.snapshot_flag.request_snapshot();
+                }
+
                 let previous_selection = selection.objects.clone();
 
                 // If the background was clicked and dragged, start another selection area while
@@ -207,7 +429,7 @@ impl GlobalInteractions {
 
                     if bg_response.drag_stopped() {
                         let new_objects =
-                            Self::find_objects_touching_selection_area(area, positions);
+                            Self::find_objects_touching_selection_area(area, ctx.positions);
 
                         if shift_held {
                             // If shift is held, add the new objects to the selection
@@ -229,14 +451,14 @@ impl GlobalInteractions {
                 }
 
                 if selection.objects != previous_selection {
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
                 }
 
                 // Highlight all selected objects
                 for oid in &selection.objects {
                     let rect = match oid {
                         SoundObjectId::Sound(spid) => {
-                            positions.find_processor(*spid).unwrap().body_rect
+                            ctx.positions.find_processor(*spid).unwrap().body_rect
                         }
                     };
 
@@ -263,34 +485,50 @@ impl GlobalInteractions {
                 drag.interact_and_draw(
                     ui,
                     graph,
-                    object_states,
+                    ctx.object_states,
                     layout,
-                    positions,
-                    stash,
-                    factories,
+                    ctx.positions,
+                    ctx.stash,
+                    ctx.factories,
                     // TODO
                     // snapshot_flag,
                 );
             }
             UiMode::Dropping(dropped_proc) => {
-                dropped_proc.handle_drop(graph, layout, positions, stash, factories, snapshot_flag);
+                dropped_proc.handle_drop(
+                    graph,
+                    layout,
+                    ctx.positions,
+                    ctx.stash,
+                    ctx.factories,
+                    ctx.snapshot_flag,
+                );
                 self.mode = UiMode::Passive;
             }
             UiMode::Summoning(summon_widget) => {
                 ui.add(SummonWidget::new(summon_widget));
 
+                if let Some(object_type) = summon_widget.take_toggled_favorite() {
+                    ctx.summon_histories.sound.toggle_favorite(object_type);
+                }
+
                 if let Some((object_type, args)) = summon_widget.final_choice() {
-                    let new_obj = factories.sound_objects().create(object_type.name(), &args);
+                    ctx.summon_histories.sound.record_summoned(object_type);
 
-                    let object_ui = factories.sound_uis().get(new_obj.get_dynamic_type());
+                    let new_obj = ctx
+                        .factories
+                        .sound_objects()
+                        .create(object_type.name(), &args);
+
+                    let object_ui = ctx.factories.sound_uis().get(new_obj.get_dynamic_type());
                     let state = object_ui.make_ui_state(&*new_obj, &args).unwrap();
 
-                    object_states.set_object_data(new_obj.id(), state);
+                    ctx.object_states.set_object_data(new_obj.id(), state);
 
                     // Move the processor to the cursor location
                     let pos = summon_widget.position();
                     match new_obj.id() {
-                        SoundObjectId::Sound(id) => positions.record_processor(
+                        SoundObjectId::Sound(id) => ctx.positions.record_processor(
                             id,
                             egui::Rect::from_min_size(pos, egui::Vec2::ZERO),
                             egui::Rect::from_min_size(pos, egui::Vec2::ZERO),
@@ -299,22 +537,172 @@ impl GlobalInteractions {
 
                     graph.add_sound_processor(new_obj.into_boxed_sound_processor().unwrap());
 
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
+
+                    self.mode = UiMode::Passive;
+                } else if summon_widget.was_cancelled() {
+                    self.mode = UiMode::Passive;
+                }
+            }
+            UiMode::Replacing {
+                processor,
+                summon_widget,
+            } => {
+                let old_processor_id = *processor;
+
+                ui.add(SummonWidget::new(summon_widget));
+
+                if let Some(object_type) = summon_widget.take_toggled_favorite() {
+                    ctx.summon_histories.sound.toggle_favorite(object_type);
+                }
+
+                if let Some((object_type, args)) = summon_widget.final_choice() {
+                    ctx.summon_histories.sound.record_summoned(object_type);
+
+                    let new_obj = ctx
+                        .factories
+                        .sound_objects()
+                        .create(object_type.name(), &args);
+
+                    let object_ui = ctx.factories.sound_uis().get(new_obj.get_dynamic_type());
+                    let state = object_ui.make_ui_state(&*new_obj, &args).unwrap();
+
+                    let new_obj_id = new_obj.id();
+                    let new_proc = new_obj.into_boxed_sound_processor().unwrap();
+                    let new_proc_id = new_proc.id();
+
+                    let old_proc = graph.sound_processor(old_processor_id).unwrap();
+                    if !can_replace_processor(old_proc, &*new_proc) {
+                        println!(
+                            "Can't replace {} with a {}, they have different numbers of sound inputs",
+                            old_proc.as_graph_object().friendly_name(),
+                            object_type.name()
+                        );
+                        self.mode = UiMode::Passive;
+                        return;
+                    }
+
+                    let res = graph.try_make_change(
+                        ctx.stash,
+                        ctx.factories.sound_objects(),
+                        ctx.factories.expression_objects(),
+                        |graph| replace_processor_in_graph(graph, old_processor_id, new_proc),
+                    );
+
+                    match res {
+                        Ok(()) => {
+                            ctx.object_states.set_object_data(new_obj_id, state);
+                            layout.replace_processor(old_processor_id, new_proc_id);
+                            ctx.snapshot_flag.request_snapshot();
+                        }
+                        Err(e) => {
+                            println!("Can't replace that processor: {}", e.explain(graph));
+                        }
+                    }
 
                     self.mode = UiMode::Passive;
                 } else if summon_widget.was_cancelled() {
                     self.mode = UiMode::Passive;
                 }
             }
+            UiMode::Bouncing { processor, seconds } => {
+                let processor = *processor;
+                let Some(source_position) = ctx.positions.find_processor(processor) else {
+                    self.mode = UiMode::Passive;
+                    return;
+                };
+                let source_rect = source_position.outer_rect;
+
+                let mut bounce = false;
+                let mut cancel = false;
+                egui::Area::new(egui::Id::new("bounce_processor"))
+                    .order(egui::Order::Foreground)
+                    .fixed_pos(source_rect.left_bottom() + egui::vec2(0.0, 5.0))
+                    .show(ui.ctx(), |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Bounce");
+                                ui.add(
+                                    egui::DragValue::new(seconds)
+                                        .range(BOUNCE_MIN_SECONDS..=BOUNCE_MAX_SECONDS)
+                                        .speed(0.1)
+                                        .suffix(" s"),
+                                );
+                                bounce = ui.button("Bounce").clicked();
+                                cancel = ui.button("Cancel").clicked();
+                            });
+                        });
+                    });
+
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)) {
+                    bounce = true;
+                }
+
+                if bounce {
+                    let clip_id = bounce_processor(graph, ctx.jit_cache, processor, *seconds);
+
+                    let clip = graph.sound_processor(clip_id).unwrap().as_graph_object();
+                    let object_ui = ctx.factories.sound_uis().get(clip.get_dynamic_type());
+                    let state = object_ui
+                        .make_ui_state(clip, &ParsedArguments::new_empty())
+                        .unwrap();
+                    ctx.object_states.set_object_data(clip.id(), state);
+
+                    // Place the clip in a new group to the right of the
+                    // processor's group
+                    let group_rect = layout
+                        .find_group(processor)
+                        .and_then(|g| g.rect(ctx.positions))
+                        .unwrap_or(source_rect);
+                    let pos = group_rect.right_top() + egui::vec2(Self::DUPLICATE_SPACING, 0.0);
+                    ctx.positions.record_processor(
+                        clip_id,
+                        egui::Rect::from_min_size(pos, egui::Vec2::ZERO),
+                        egui::Rect::from_min_size(pos, egui::Vec2::ZERO),
+                    );
+
+                    layout.regenerate(graph, ctx.positions);
+
+                    self.mode = UiMode::Selecting(SelectingState {
+                        objects: HashSet::from([clip_id.into()]),
+                        selecting_area: None,
+                    });
+                    ctx.snapshot_flag.request_snapshot();
+                    return;
+                } else if cancel {
+                    self.mode = UiMode::Passive;
+                }
+            }
+            UiMode::SearchingProcessors(palette) => {
+                ui.add(SummonWidget::new(palette));
+
+                if let Some((processor, _)) = palette.final_choice() {
+                    if jump_to_processor(processor, layout, ctx.positions, ui.clip_rect()) {
+                        self.focus_on_processor(processor);
+                        ctx.snapshot_flag.request_snapshot();
+                    } else {
+                        self.mode = UiMode::Passive;
+                    }
+                } else if palette.was_cancelled() {
+                    self.mode = UiMode::Passive;
+                }
+            }
         }
 
-        let (pressed_ctrl_a, pressed_esc) = ui.input_mut(|i| {
+        let (pressed_ctrl_a, pressed_ctrl_p, pressed_esc) = ui.input_mut(|i| {
             (
                 i.consume_key(egui::Modifiers::CTRL, egui::Key::A),
+                i.consume_key(egui::Modifiers::CTRL, egui::Key::P),
                 i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
             )
         });
 
+        // If ctrl+P was pressed, search for a processor to jump to
+        if pressed_ctrl_p {
+            let position = ui.clip_rect().center_top() + egui::vec2(0.0, 50.0);
+            self.mode = UiMode::SearchingProcessors(build_processor_palette(graph, position));
+        }
+
         // If ctrl+A was pressed, select everything
         if pressed_ctrl_a {
             self.mode = UiMode::Selecting(SelectingState {
@@ -322,21 +710,21 @@ impl GlobalInteractions {
                 selecting_area: None,
             });
             // TODO: did anything change?
-            snapshot_flag.request_snapshot();
+            ctx.snapshot_flag.request_snapshot();
         }
 
         // If escape was pressed, go into passive mode
         if pressed_esc {
             self.mode = UiMode::Passive;
             // TODO: did anything change?
-            snapshot_flag.request_snapshot();
+            ctx.snapshot_flag.request_snapshot();
         }
 
         // If the background was just clicked, go into passive mode
         if bg_response.clicked() {
             self.mode = UiMode::Passive;
             // TODO: did anything change?
-            snapshot_flag.request_snapshot();
+            ctx.snapshot_flag.request_snapshot();
         }
     }
 
@@ -399,6 +787,17 @@ impl GlobalInteractions {
                 }
             }
             UiMode::Summoning(_) => (),
+            UiMode::Replacing { processor, .. } => {
+                if !graph.contains(*processor) {
+                    self.mode = UiMode::Passive;
+                }
+            }
+            UiMode::SearchingProcessors(_) => (),
+            UiMode::Bouncing { processor, .. } => {
+                if !graph.contains(*processor) {
+                    self.mode = UiMode::Passive;
+                }
+            }
         }
     }
 
@@ -420,6 +819,40 @@ impl GlobalInteractions {
             .collect()
     }
 
+    /// Move each stacked group containing any of the selected objects
+    /// by the amount that the given arrangement finds for its rect
+    fn arrange_selected_groups(
+        objects: &HashSet<SoundObjectId>,
+        layout: &mut StackedLayout,
+        positions: &SoundObjectPositions,
+        arrangement: Arrangement,
+    ) {
+        let mut top_processors: Vec<SoundProcessorId> = Vec::new();
+        let mut rects: Vec<egui::Rect> = Vec::new();
+        for oid in objects {
+            let SoundObjectId::Sound(spid) = oid;
+            let Some(group) = layout.find_group(*spid) else {
+                continue;
+            };
+            let top_processor = group.processors()[0];
+            if top_processors.contains(&top_processor) {
+                continue;
+            }
+            let Some(rect) = group.rect(positions) else {
+                continue;
+            };
+            top_processors.push(top_processor);
+            rects.push(rect);
+        }
+
+        for (top_processor, offset) in top_processors.into_iter().zip(arrangement(&rects)) {
+            layout
+                .find_group_mut(top_processor)
+                .unwrap()
+                .translate(offset);
+        }
+    }
+
     fn draw_selecting_area(ui: &mut egui::Ui, area: &SelectingArea) {
         let select_rect = egui::Rect::from_two_pos(area.start_location, area.end_location);
 
@@ -437,26 +870,151 @@ impl GlobalInteractions {
     }
 
     // TODO:
-    // - cut/copy/paste
+    // - cut
     // - file save/open
 }
 
 /// Internal methods
 impl GlobalInteractions {
+    /// The horizontal gap between duplicated processors and their originals, in pixels
+    const DUPLICATE_SPACING: f32 = 20.0;
+
+    /// The duration initially offered when bouncing a processor, in seconds
+    const DEFAULT_BOUNCE_SECONDS: f32 = 5.0;
+
+    /// The body and outer rectangles of each of the given processors
+    fn processor_rects(
+        processors: &HashSet<SoundProcessorId>,
+        positions: &SoundObjectPositions,
+    ) -> HashMap<SoundProcessorId, (egui::Rect, egui::Rect)> {
+        processors
+            .iter()
+            .map(|spid| {
+                let position = positions.find_processor(*spid).unwrap();
+                (*spid, (position.body_rect, position.outer_rect))
+            })
+            .collect()
+    }
+
+    /// Create the ui state of each copied processor and place it where
+    /// the processor it was copied from was, moved by the given offset
+    fn place_copies(
+        copies: &HashMap<SoundProcessorId, SoundProcessorId>,
+        rects: &HashMap<SoundProcessorId, (egui::Rect, egui::Rect)>,
+        offset: egui::Vec2,
+        factories: &Factories,
+        graph: &SoundGraph,
+        object_states: &mut SoundObjectUiStates,
+        positions: &mut SoundObjectPositions,
+    ) {
+        for (original_id, copy_id) in copies {
+            let copy = graph.sound_processor(*copy_id).unwrap().as_graph_object();
+            let object_ui = factories.sound_uis().get(copy.get_dynamic_type());
+            let state = object_ui
+                .make_ui_state(copy, &ParsedArguments::new_empty())
+                .unwrap();
+            object_states.set_object_data(copy.id(), state);
+
+            let (body_rect, outer_rect) = rects[original_id];
+            positions.record_processor(
+                *copy_id,
+                body_rect.translate(offset),
+                outer_rect.translate(offset),
+            );
+        }
+    }
+
+    /// Paste the processors on the clipboard into the graph with their
+    /// top left corner at the given position. Returns the id of each
+    /// pasted processor, keyed by the id of the processor it was copied
+    /// from, or None if they couldn't be pasted.
+    fn paste(
+        clipboard: &ProcessorClipboard,
+        position: egui::Pos2,
+        factories: &Factories,
+        graph: &mut SoundGraph,
+        object_states: &mut SoundObjectUiStates,
+        positions: &mut SoundObjectPositions,
+        stash: &Stash,
+    ) -> Option<HashMap<SoundProcessorId, SoundProcessorId>> {
+        let res = graph.try_make_change(
+            stash,
+            factories.sound_objects(),
+            factories.expression_objects(),
+            |graph| {
+                Ok(paste_processors(
+                    graph,
+                    &clipboard.processors,
+                    stash,
+                    factories.sound_objects(),
+                    factories.expression_objects(),
+                ))
+            },
+        );
+
+        let copies = match res {
+            Ok(Ok(copies)) => copies,
+            Ok(Err(e)) => {
+                println!("Can't paste those processors: {:?}", e);
+                return None;
+            }
+            Err(e) => {
+                println!("Can't paste those processors: {}", e.explain(graph));
+                return None;
+            }
+        };
+
+        let bounds = clipboard
+            .rects
+            .values()
+            .map(|(_, outer_rect)| *outer_rect)
+            .reduce(|a, b| a.union(b))?;
+        let offset = position - bounds.left_top();
+
+        Self::place_copies(
+            &copies,
+            &clipboard.rects,
+            offset,
+            factories,
+            graph,
+            object_states,
+            positions,
+        );
+
+        Some(copies)
+    }
+
     /// Switch to using the summon widget
-    fn start_summoning(&mut self, position: egui::Pos2, factory: &SoundObjectUiFactory) {
+    fn start_summoning(
+        &mut self,
+        position: egui::Pos2,
+        factory: &SoundObjectUiFactory,
+        history: &SummonHistory,
+    ) {
+        let widget = Self::build_summon_widget(position, factory, history);
+        self.mode = UiMode::Summoning(widget);
+    }
+
+    /// Create a summon widget listing every sound object, with the
+    /// favorite and recently-summoned ones first
+    fn build_summon_widget(
+        position: egui::Pos2,
+        factory: &SoundObjectUiFactory,
+        history: &SummonHistory,
+    ) -> SummonWidgetState<ObjectType> {
         let mut builder = SummonWidgetStateBuilder::new(position);
+        builder.with_history(history, |t| Some(*t));
         for object_ui in factory.all_object_uis() {
             for name in object_ui.summon_names() {
                 builder.add_name_with_arguments(
                     name.to_string(),
                     object_ui.summon_arguments(),
                     object_ui.object_type(),
+                    object_ui.summon_category(),
                 );
             }
         }
-        let widget = builder.build();
-        self.mode = UiMode::Summoning(widget);
+        builder.build()
     }
 }
 
@@ -484,6 +1042,18 @@ impl Stashable for GlobalInteractions {
                 // same as passive
                 stasher.u8(0);
             }
+            UiMode::Replacing { .. } => {
+                // same as passive
+                stasher.u8(0);
+            }
+            UiMode::SearchingProcessors(_) => {
+                // same as passive
+                stasher.u8(0);
+            }
+            UiMode::Bouncing { .. } => {
+                // same as passive
+                stasher.u8(0);
+            }
         }
     }
 }
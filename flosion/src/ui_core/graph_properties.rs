@@ -3,18 +3,36 @@ use std::collections::{HashMap, HashSet};
 use hashstash::HashCacheProperty;
 
 use crate::core::{
+    expression::expressionmacro::ExpressionMacroLibrary,
     sound::{
         argument::ProcessorArgumentLocation, expression::ProcessorExpressionLocation,
-        soundgraph::SoundGraph, soundinput::SoundInputLocation, soundprocessor::SoundProcessorId,
+        latency::processor_latencies, sounderror::SoundError, soundgraph::SoundGraph,
+        soundgraphvalidation::find_branch_count_mismatches, soundinput::SoundInputLocation,
+        soundprocessor::SoundProcessorId,
     },
     stashing::StashingContext,
 };
 
+use super::constantdiagnostics::{find_suspicious_constants, ConstantDiagnostic};
+
 pub(crate) struct GraphProperties {
     available_inputs: HashCacheProperty<HashMap<SoundProcessorId, HashSet<SoundInputLocation>>>,
 
     available_arguments:
         HashCacheProperty<HashMap<ProcessorExpressionLocation, HashSet<ProcessorArgumentLocation>>>,
+
+    /// Explanations of synchronous inputs whose branch count doesn't
+    /// match the processor they're connected to
+    branch_count_mismatches: HashCacheProperty<HashMap<SoundInputLocation, String>>,
+
+    /// The graph's expression macros, for summoning within expressions
+    expression_macros: HashCacheProperty<ExpressionMacroLibrary>,
+
+    /// The total latency of each processor's output, in samples
+    latencies: HashCacheProperty<HashMap<SoundProcessorId, usize>>,
+
+    /// Constants within expressions whose values likely indicate a mistake
+    suspicious_constants: HashCacheProperty<Vec<ConstantDiagnostic>>,
 }
 
 impl GraphProperties {
@@ -22,6 +40,10 @@ impl GraphProperties {
         GraphProperties {
             available_inputs: HashCacheProperty::new(),
             available_arguments: HashCacheProperty::new(),
+            branch_count_mismatches: HashCacheProperty::new(),
+            expression_macros: HashCacheProperty::new(),
+            latencies: HashCacheProperty::new(),
+            suspicious_constants: HashCacheProperty::new(),
         }
     }
 
@@ -42,6 +64,37 @@ impl GraphProperties {
             .get(&location)
     }
 
+    /// If the input's branch count doesn't match the processor it is
+    /// connected to, an explanation of the mismatch
+    pub(crate) fn branch_count_mismatch(&self, location: SoundInputLocation) -> Option<&str> {
+        self.branch_count_mismatches
+            .get_cached()
+            .unwrap()
+            .get(&location)
+            .map(|s| s.as_str())
+    }
+
+    pub(crate) fn expression_macros(&self) -> &ExpressionMacroLibrary {
+        self.expression_macros.get_cached().unwrap()
+    }
+
+    /// The total latency of the processor's output in samples, including
+    /// that of all processors upstream of it
+    pub(crate) fn latency(&self, processor: SoundProcessorId) -> usize {
+        self.latencies
+            .get_cached()
+            .unwrap()
+            .get(&processor)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// Every constant within the graph's expressions whose value is
+    /// NaN, infinite, or of an extreme magnitude
+    pub(crate) fn suspicious_constants(&self) -> &[ConstantDiagnostic] {
+        self.suspicious_constants.get_cached().unwrap()
+    }
+
     pub(crate) fn refresh(&mut self, graph: &SoundGraph) {
         self.available_inputs.refresh1_with_context(
             available_sound_inputs,
@@ -59,9 +112,43 @@ impl GraphProperties {
             graph,
             StashingContext::new_checking_recompilation(),
         );
+
+        self.branch_count_mismatches.refresh1_with_context(
+            explain_branch_count_mismatches,
+            graph,
+            StashingContext::new_checking_recompilation(),
+        );
+
+        self.expression_macros.refresh1_with_context(
+            |graph| graph.expression_macros().clone(),
+            graph,
+            StashingContext::new_checking_recompilation(),
+        );
+
+        self.latencies.refresh1_with_context(
+            processor_latencies,
+            graph,
+            StashingContext::new_checking_recompilation(),
+        );
+
+        self.suspicious_constants.refresh1_with_context(
+            find_suspicious_constants,
+            graph,
+            StashingContext::new_checking_recompilation(),
+        );
     }
 }
 
+fn explain_branch_count_mismatches(graph: &SoundGraph) -> HashMap<SoundInputLocation, String> {
+    find_branch_count_mismatches(graph)
+        .into_iter()
+        .filter_map(|err| match err {
+            SoundError::InputHasNoBranches(location) => Some((location, err.explain(graph))),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Returns a hashmap containing for each sound processor, the full set
 /// of sound inputs that are always up the audio stack when it is invoked
 pub(super) fn available_sound_inputs(
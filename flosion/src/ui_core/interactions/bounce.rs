@@ -0,0 +1,41 @@
+use crate::{
+    core::{
+        engine::offlinerender::render_processor_offline,
+        jit::cache::JitCache,
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{
+            soundgraph::SoundGraph,
+            soundprocessor::{SoundProcessorId, SoundProcessorWithId},
+        },
+    },
+    objects::audioclip::AudioClip,
+};
+
+/// The shortest and longest durations that a processor can be bounced for, in seconds
+pub(crate) const BOUNCE_MIN_SECONDS: f32 = 0.1;
+pub(crate) const BOUNCE_MAX_SECONDS: f32 = 600.0;
+
+/// Render the output of the given processor for the given number of seconds
+/// and place the result in a new AudioClip, which is added to the graph
+/// without any connections. The original processor is left untouched, so
+/// that it can be removed in favour of the clip if desired. Returns the id
+/// of the new AudioClip.
+pub(crate) fn bounce_processor(
+    graph: &mut SoundGraph,
+    jit_cache: &JitCache,
+    processor_id: SoundProcessorId,
+    seconds: f32,
+) -> SoundProcessorId {
+    let seconds = seconds.clamp(BOUNCE_MIN_SECONDS, BOUNCE_MAX_SECONDS);
+    let num_samples = (seconds * SAMPLE_FREQUENCY as f32).round() as usize;
+
+    let buffer = render_processor_offline(graph, jit_cache, processor_id, num_samples);
+
+    let audioclip = SoundProcessorWithId::<AudioClip>::new_default();
+    audioclip.set_data(buffer);
+    let clip_id = audioclip.id();
+
+    graph.add_sound_processor(Box::new(audioclip));
+
+    clip_id
+}
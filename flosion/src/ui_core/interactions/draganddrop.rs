@@ -15,8 +15,14 @@ use crate::{
         stashing::{StashingContext, UnstashingContext},
     },
     ui_core::{
-        factories::Factories, history::SnapshotFlag, soundobjectpositions::SoundObjectPositions,
-        soundobjectuistate::SoundObjectUiStates, stackedlayout::stackedlayout::StackedLayout,
+        factories::Factories,
+        history::SnapshotFlag,
+        soundobjectpositions::SoundObjectPositions,
+        soundobjectuistate::SoundObjectUiStates,
+        stackedlayout::{
+            alignment::{snap_to_alignment, AlignmentGuide},
+            stackedlayout::StackedLayout,
+        },
     },
 };
 
@@ -377,6 +383,14 @@ impl<'a> Stashable for StackedLayoutWrapper<'a> {
     }
 }
 
+/// The legality of every available drop site, along with an explanation
+/// for those sites which are illegal because dropping there would leave
+/// the sound graph invalid
+struct LegalDropSites {
+    statuses: HashMap<DragDropSubject, DragDropLegality>,
+    errors: HashMap<DragDropSubject, String>,
+}
+
 fn compute_legal_drop_sites(
     graph: &SoundGraph,
     layout: &StackedLayoutWrapper,
@@ -384,9 +398,10 @@ fn compute_legal_drop_sites(
     drop_sites: AvailableDropSites,
     stash: &Stash,
     factories: &Factories,
-) -> HashMap<DragDropSubject, DragDropLegality> {
+) -> LegalDropSites {
     debug_assert_eq!(graph.validate(), Ok(()));
     let mut site_statuses = HashMap::new();
+    let mut site_errors = HashMap::new();
     for drop_site in drop_sites.0.keys() {
         let (mut graph_clone, _) = stash_clone_with_context(
             graph,
@@ -401,13 +416,13 @@ fn compute_legal_drop_sites(
         // resulting graph is valid.
         let status =
             match drag_and_drop_in_graph(&mut graph_clone, layout.0, drag_subject, *drop_site) {
-                DragDropLegality::Legal => {
-                    if graph_clone.validate().is_err() {
+                DragDropLegality::Legal => match graph_clone.validate() {
+                    Ok(()) => DragDropLegality::Legal,
+                    Err(e) => {
+                        site_errors.insert(*drop_site, e.explain(&graph_clone));
                         DragDropLegality::Illegal
-                    } else {
-                        DragDropLegality::Legal
                     }
-                }
+                },
                 DragDropLegality::LegalButInvisible => {
                     if graph_clone.validate().is_err() {
                         DragDropLegality::Irrelevant
@@ -420,7 +435,10 @@ fn compute_legal_drop_sites(
             };
         site_statuses.insert(*drop_site, status);
     }
-    site_statuses
+    LegalDropSites {
+        statuses: site_statuses,
+        errors: site_errors,
+    }
 }
 
 fn find_closest_legal_drop_site(
@@ -451,14 +469,47 @@ fn find_closest_legal_drop_site(
     best_subject
 }
 
+/// Find the explanation for why the illegal drop site that most overlaps
+/// the given rect can't be dropped onto, if any
+fn find_closest_drop_site_error<'a>(
+    rect: egui::Rect,
+    positions: &SoundObjectPositions,
+    min_overlap: f32,
+    legal_sites: &'a LegalDropSites,
+) -> Option<&'a str> {
+    let mut best_overlap = min_overlap;
+    let mut best_error = None;
+
+    for (subject, error) in &legal_sites.errors {
+        let Some(subject_rect) = positions.drag_drop_subjects().get(subject) else {
+            continue;
+        };
+
+        let intersection = subject_rect.intersect(rect);
+        if !intersection.is_positive() {
+            continue;
+        }
+        let area = intersection.area();
+        if area > best_overlap {
+            best_overlap = area;
+            best_error = Some(error.as_str());
+        }
+    }
+
+    best_error
+}
+
 const MIN_DROP_OVERLAP: f32 = 1000.0;
 
 pub struct DragInteraction {
     subject: DragDropSubject,
     rect: egui::Rect,
     original_rect: egui::Rect,
-    legal_drop_sites: HashCacheProperty<HashMap<DragDropSubject, DragDropLegality>>,
+    legal_drop_sites: HashCacheProperty<LegalDropSites>,
     closest_legal_site: Option<DragDropSubject>,
+    /// How far the subject will be moved when dropped to align it with
+    /// other groups
+    snap_offset: egui::Vec2,
 }
 
 impl DragInteraction {
@@ -469,6 +520,7 @@ impl DragInteraction {
             original_rect,
             legal_drop_sites: HashCacheProperty::new(),
             closest_legal_site: None,
+            snap_offset: egui::Vec2::ZERO,
         }
     }
 
@@ -499,11 +551,24 @@ impl DragInteraction {
             self.rect,
             positions,
             MIN_DROP_OVERLAP,
-            self.legal_drop_sites.get_cached().unwrap(),
+            &self.legal_drop_sites.get_cached().unwrap().statuses,
         );
 
+        // A processor dropped away from anything ends up in a group of its
+        // own, so show how that group would align with the others
+        self.snap_offset = egui::Vec2::ZERO;
+        if self.closest_legal_site.is_none() {
+            if let DragDropSubject::Processor(spid) = self.subject {
+                let (offset, guides) = self.find_alignment(spid, layout, positions);
+                self.snap_offset = offset;
+                for guide in guides {
+                    guide.draw(ui);
+                }
+            }
+        }
+
         // Highlight the legal and illegal drop sites
-        for (drop_site, legality) in self.legal_drop_sites.get_cached().unwrap() {
+        for (drop_site, legality) in &self.legal_drop_sites.get_cached().unwrap().statuses {
             let color = match legality {
                 DragDropLegality::Legal => egui::Color32::WHITE,
                 DragDropLegality::LegalButInvisible => continue,
@@ -548,12 +613,54 @@ impl DragInteraction {
             ui.painter()
                 .rect_filled(self.rect, egui::Rounding::same(5.0), color);
         }
+
+        // Explain why the illegal drop site being hovered over can't be
+        // dropped onto, e.g. because doing so would create a cycle
+        if self.closest_legal_site.is_none() {
+            if let Some(error) = find_closest_drop_site_error(
+                self.rect,
+                positions,
+                MIN_DROP_OVERLAP,
+                self.legal_drop_sites.get_cached().unwrap(),
+            ) {
+                ui.painter().text(
+                    self.rect.left_bottom() + egui::vec2(0.0, 5.0),
+                    egui::Align2::LEFT_TOP,
+                    error,
+                    egui::FontId::proportional(14.0),
+                    egui::Color32::from_rgb(255, 128, 128),
+                );
+            }
+        }
     }
 
     pub(crate) fn rect(&self) -> egui::Rect {
         self.rect
     }
 
+    /// Find how far the group of the dragged processor should move to
+    /// snap to the edges of other groups, as if it were dropped by itself
+    fn find_alignment(
+        &self,
+        processor: SoundProcessorId,
+        layout: &StackedLayout,
+        positions: &SoundObjectPositions,
+    ) -> (egui::Vec2, Vec<AlignmentGuide>) {
+        let Some(position) = positions.find_processor(processor) else {
+            return (egui::Vec2::ZERO, Vec::new());
+        };
+        let dragged_rect = position
+            .outer_rect
+            .translate(self.rect.left_top() - self.original_rect.left_top());
+        let other_rects: Vec<egui::Rect> = layout
+            .groups()
+            .iter()
+            .filter(|g| !g.processors().contains(&processor))
+            .filter_map(|g| g.rect(positions))
+            .collect();
+        snap_to_alignment(dragged_rect, &other_rects)
+    }
+
     pub(crate) fn set_rect(&mut self, rect: egui::Rect) {
         self.rect = rect;
     }
@@ -575,9 +682,9 @@ impl DropInteraction {
     pub(crate) fn new_from_drag(drag: &DragInteraction) -> DropInteraction {
         DropInteraction {
             subject: drag.subject,
-            rect: drag.rect,
+            rect: drag.rect.translate(drag.snap_offset),
             original_rect: drag.original_rect,
-            legal_sites: drag.legal_drop_sites.get_cached().cloned().unwrap(),
+            legal_sites: drag.legal_drop_sites.get_cached().unwrap().statuses.clone(),
         }
     }
 
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+
+use hashstash::{stash_clone_with_context, Stash, StashHandle, UnstashError};
+
+use crate::core::{
+    expression::expressionobject::ExpressionObjectFactory,
+    sound::{
+        argument::{AnyProcessorArgument, ProcessorArgumentId, ProcessorArgumentLocation},
+        expression::{ExpressionParameterTarget, ProcessorExpression, ProcessorExpressionId},
+        soundgraph::SoundGraph,
+        soundinput::{AnyProcessorInput, ProcessorInputId, SoundInputLocation},
+        soundobject::SoundObjectFactory,
+        soundprocessor::{AnySoundProcessor, ProcessorComponentVisitorMut, SoundProcessorId},
+    },
+    stashing::{StashingContext, UnstashingContext},
+};
+
+/// The new ids given to every part of the duplicated processors
+#[derive(Default)]
+struct IdRemapping {
+    processors: HashMap<SoundProcessorId, SoundProcessorId>,
+    inputs: HashMap<ProcessorInputId, ProcessorInputId>,
+    arguments: HashMap<ProcessorArgumentId, ProcessorArgumentId>,
+}
+
+impl IdRemapping {
+    /// Find the counterpart of an expression parameter target among the
+    /// duplicates, if it refers to one of the duplicated processors
+    fn remap_target(&self, target: ExpressionParameterTarget) -> Option<ExpressionParameterTarget> {
+        match target {
+            ExpressionParameterTarget::Argument(loc) => Some(ExpressionParameterTarget::Argument(
+                ProcessorArgumentLocation::new(
+                    *self.processors.get(&loc.processor())?,
+                    *self.arguments.get(&loc.argument())?,
+                ),
+            )),
+            ExpressionParameterTarget::ProcessorTime(spid) => Some(
+                ExpressionParameterTarget::ProcessorTime(*self.processors.get(&spid)?),
+            ),
+            ExpressionParameterTarget::InputTime(loc) => Some(
+                ExpressionParameterTarget::InputTime(SoundInputLocation::new(
+                    *self.processors.get(&loc.processor())?,
+                    *self.inputs.get(&loc.input())?,
+                )),
+            ),
+        }
+    }
+}
+
+/// Gives every input, argument, and expression of a processor a new id
+struct ComponentIdRenewer<'a> {
+    remapping: &'a mut IdRemapping,
+}
+
+impl<'a> ProcessorComponentVisitorMut for ComponentIdRenewer<'a> {
+    fn input(&mut self, input: &mut dyn AnyProcessorInput) {
+        let new_id = ProcessorInputId::new_unique();
+        self.remapping.inputs.insert(input.id(), new_id);
+        input.set_id(new_id);
+    }
+
+    fn expression(&mut self, expression: &mut ProcessorExpression) {
+        expression.set_id(ProcessorExpressionId::new_unique());
+    }
+
+    fn argument(&mut self, argument: &mut dyn AnyProcessorArgument) {
+        let new_id = ProcessorArgumentId::new_unique();
+        self.remapping.arguments.insert(argument.id(), new_id);
+        argument.set_id(new_id);
+    }
+}
+
+fn remap_arguments(arguments: &mut [ProcessorArgumentId], remapping: &IdRemapping) {
+    for arg in arguments {
+        if let Some(new_arg) = remapping.arguments.get(arg) {
+            *arg = *new_arg;
+        }
+    }
+}
+
+/// Points every reference which a copied processor makes to one of
+/// the original processors at its copy instead
+struct ReferenceRemapper<'a> {
+    remapping: &'a IdRemapping,
+}
+
+impl<'a> ProcessorComponentVisitorMut for ReferenceRemapper<'a> {
+    fn input(&mut self, input: &mut dyn AnyProcessorInput) {
+        // Connections to processors which weren't copied are dropped,
+        // leaving the copies independent of the rest of the graph
+        let new_target = input
+            .target()
+            .and_then(|target| self.remapping.processors.get(&target).cloned());
+        input.set_target(new_target);
+
+        remap_arguments(input.argument_scope_mut().arguments_mut(), self.remapping);
+    }
+
+    fn expression(&mut self, expression: &mut ProcessorExpression) {
+        remap_arguments(expression.scope_mut().arguments_mut(), self.remapping);
+
+        let targets: Vec<ExpressionParameterTarget> =
+            expression.mapping().items().values().cloned().collect();
+        for target in targets {
+            // Processors which weren't copied are no longer upstream
+            // of the copies, so references to them can't be kept
+            match self.remapping.remap_target(target) {
+                Some(new_target) => expression.retarget(target, new_target),
+                None => expression.remove_target(target),
+            }
+        }
+    }
+}
+
+/// Records the ids of a processor's inputs and arguments as its own,
+/// so that references among the copied processors are kept as they are
+struct ComponentIdKeeper<'a> {
+    remapping: &'a mut IdRemapping,
+}
+
+impl<'a> ProcessorComponentVisitorMut for ComponentIdKeeper<'a> {
+    fn input(&mut self, input: &mut dyn AnyProcessorInput) {
+        self.remapping.inputs.insert(input.id(), input.id());
+    }
+
+    fn expression(&mut self, _expression: &mut ProcessorExpression) {}
+
+    fn argument(&mut self, argument: &mut dyn AnyProcessorArgument) {
+        self.remapping
+            .arguments
+            .insert(argument.id(), argument.id());
+    }
+}
+
+/// A self-contained copy of some processors together with their
+/// expressions and arguments, detached from the graph they were copied
+/// from, which can be pasted into any graph any number of times
+pub(crate) struct CopiedProcessors {
+    subgraph: StashHandle<SoundGraph>,
+}
+
+/// Copy the given processors out of the graph, including their
+/// expressions and arguments. Connections among the given processors
+/// are kept, while connections to and expression parameters referring
+/// to any other processor are dropped.
+pub(crate) fn copy_processors(
+    graph: &SoundGraph,
+    processors: &HashSet<SoundProcessorId>,
+    stash: &Stash,
+    sound_object_factory: &SoundObjectFactory,
+    expression_object_factory: &ExpressionObjectFactory,
+) -> CopiedProcessors {
+    let (graph_copy, _) = stash_clone_with_context(
+        graph,
+        stash,
+        StashingContext::new_stashing_normally(),
+        UnstashingContext::new(sound_object_factory, expression_object_factory),
+    )
+    .unwrap();
+
+    let mut copies: Vec<Box<dyn AnySoundProcessor>> = graph_copy
+        .into_sound_processors()
+        .into_iter()
+        .filter_map(|(id, proc)| processors.contains(&id).then_some(proc))
+        .collect();
+
+    let mut remapping = IdRemapping::default();
+
+    for copy in &mut copies {
+        remapping.processors.insert(copy.id(), copy.id());
+        copy.visit_mut(&mut ComponentIdKeeper {
+            remapping: &mut remapping,
+        });
+    }
+
+    let mut subgraph = SoundGraph::new();
+    for mut copy in copies {
+        copy.visit_mut(&mut ReferenceRemapper {
+            remapping: &remapping,
+        });
+        subgraph.add_sound_processor(copy);
+    }
+    debug_assert_eq!(subgraph.validate(), Ok(()));
+
+    CopiedProcessors {
+        subgraph: stash.stash_with_context(&subgraph, StashingContext::new_stashing_normally()),
+    }
+}
+
+/// Add the copied processors to the graph, giving every processor,
+/// input, argument, and expression a new id. Returns the id of each
+/// pasted processor, keyed by the id of the processor it was copied
+/// from.
+pub(crate) fn paste_processors(
+    graph: &mut SoundGraph,
+    copied: &CopiedProcessors,
+    stash: &Stash,
+    sound_object_factory: &SoundObjectFactory,
+    expression_object_factory: &ExpressionObjectFactory,
+) -> Result<HashMap<SoundProcessorId, SoundProcessorId>, UnstashError> {
+    let subgraph: SoundGraph = stash.unstash_with_context(
+        &copied.subgraph,
+        UnstashingContext::new(sound_object_factory, expression_object_factory),
+    )?;
+
+    let mut copies: Vec<Box<dyn AnySoundProcessor>> =
+        subgraph.into_sound_processors().into_values().collect();
+
+    let mut remapping = IdRemapping::default();
+
+    for copy in &mut copies {
+        let new_id = SoundProcessorId::new_unique();
+        remapping.processors.insert(copy.id(), new_id);
+        copy.set_id(new_id);
+        copy.visit_mut(&mut ComponentIdRenewer {
+            remapping: &mut remapping,
+        });
+    }
+
+    for mut copy in copies {
+        copy.visit_mut(&mut ReferenceRemapper {
+            remapping: &remapping,
+        });
+        graph.add_sound_processor(copy);
+    }
+
+    Ok(remapping.processors)
+}
+
+/// Add a copy of each of the given processors to the graph, including
+/// their expressions and arguments. Connections among the given processors
+/// are mirrored among the copies, while connections to any other processor
+/// are not. Returns the id of each copy, keyed by the id of its original.
+pub(crate) fn duplicate_processors(
+    graph: &mut SoundGraph,
+    processors: &HashSet<SoundProcessorId>,
+    stash: &Stash,
+    sound_object_factory: &SoundObjectFactory,
+    expression_object_factory: &ExpressionObjectFactory,
+) -> HashMap<SoundProcessorId, SoundProcessorId> {
+    let copied = copy_processors(
+        graph,
+        processors,
+        stash,
+        sound_object_factory,
+        expression_object_factory,
+    );
+    paste_processors(
+        graph,
+        &copied,
+        stash,
+        sound_object_factory,
+        expression_object_factory,
+    )
+    .unwrap()
+}
@@ -1,5 +1,5 @@
 use eframe::egui;
-use hashstash::{Stash, Stashable, Stasher, UnstashError, Unstashable, Unstasher};
+use hashstash::{Stashable, Stasher, UnstashError, Unstashable, Unstasher};
 
 use crate::{
     core::sound::{
@@ -8,10 +8,9 @@ use crate::{
     },
     ui_core::{
         expressiongraphuicontext::OuterProcessorExpressionContext,
-        expressiongraphuistate::ExpressionUiCollection, factories::Factories,
-        graph_properties::GraphProperties, history::SnapshotFlag,
-        lexicallayout::lexicallayout::LexicalLayoutFocus, soundgraphuinames::SoundGraphUiNames,
-        soundobjectpositions::SoundObjectPositions, stackedlayout::stackedlayout::StackedLayout,
+        globalinteractions::GlobalInteractionContext,
+        lexicallayout::lexicallayout::{LexicalLayoutEditContext, LexicalLayoutFocus},
+        stackedlayout::stackedlayout::StackedLayout,
     },
 };
 
@@ -129,15 +128,9 @@ impl KeyboardNavInteraction {
     pub(crate) fn interact_and_draw(
         &mut self,
         ui: &mut egui::Ui,
+        ctx: &mut GlobalInteractionContext,
         graph: &mut SoundGraph,
         layout: &StackedLayout,
-        positions: &SoundObjectPositions,
-        expression_uis: &mut ExpressionUiCollection,
-        factories: &Factories,
-        stash: &Stash,
-        names: &SoundGraphUiNames,
-        properties: &GraphProperties,
-        snapshot_flag: &SnapshotFlag,
     ) {
         let rect;
         let mut allowed_dirs = DirectionsToGo::nowhere();
@@ -145,11 +138,12 @@ impl KeyboardNavInteraction {
 
         match self {
             KeyboardNavInteraction::AroundSoundProcessor(spid) => {
-                rect = positions.find_processor(*spid).unwrap().body_rect;
+                rect = ctx.positions.find_processor(*spid).unwrap().body_rect;
                 let proc_data = graph.sound_processor(*spid).unwrap();
                 let last_input = proc_data.input_locations().last().cloned();
 
-                let first_expr: Option<ProcessorExpressionLocation> = positions
+                let first_expr: Option<ProcessorExpressionLocation> = ctx
+                    .positions
                     .processor_expressions_top_down(*spid)
                     .first()
                     .cloned();
@@ -160,27 +154,35 @@ impl KeyboardNavInteraction {
 
                 let requested_dirs = allowed_dirs.filter_keypresses(ui);
 
+                // Alt+S toggles whether the processor is soloed
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::ALT, egui::Key::S)) {
+                    let soloed = graph.is_soloed(*spid);
+                    graph.set_soloed(*spid, !soloed);
+                    ctx.snapshot_flag.request_snapshot();
+                }
+
                 if requested_dirs.go_up {
                     // go the processor's last input, if it has any inputs
                     if let Some(last_input) = last_input {
                         *self = KeyboardNavInteraction::AroundInputSocket(last_input);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     }
                 } else if requested_dirs.go_down {
                     // go to the processor's plug
                     *self = KeyboardNavInteraction::AroundProcessorPlug(*spid);
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
                 } else if requested_dirs.go_in {
                     // go to the processor's first expression
 
                     if let Some(eid) = first_expr {
                         *self = KeyboardNavInteraction::AroundExpression(eid);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     }
                 }
             }
             KeyboardNavInteraction::AroundProcessorPlug(spid) => {
-                rect = positions
+                rect = ctx
+                    .positions
                     .drag_drop_subjects()
                     .get(&DragDropSubject::Plug(*spid))
                     .unwrap()
@@ -195,7 +197,7 @@ impl KeyboardNavInteraction {
                 if requested_dirs.go_up {
                     // go to the processor
                     *self = KeyboardNavInteraction::AroundSoundProcessor(*spid);
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
                 } else if requested_dirs.go_down {
                     // if there's a processor below, go to its first input
                     if let Some(proc_below) = proc_below {
@@ -207,14 +209,15 @@ impl KeyboardNavInteraction {
                             .cloned()
                             .unwrap();
                         *self = KeyboardNavInteraction::AroundInputSocket(first_input);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     } else {
                         // TODO: ???
                     }
                 }
             }
             KeyboardNavInteraction::AroundInputSocket(siid) => {
-                rect = positions
+                rect = ctx
+                    .positions
                     .drag_drop_subjects()
                     .get(&DragDropSubject::Socket(*siid))
                     .unwrap()
@@ -233,32 +236,33 @@ impl KeyboardNavInteraction {
                         // go to the target processor if there is one
                         if let Some(proc_above) = layout.processor_above(owner) {
                             *self = KeyboardNavInteraction::AroundProcessorPlug(proc_above);
-                            snapshot_flag.request_snapshot();
+                            ctx.snapshot_flag.request_snapshot();
                         } else {
                             // TODO: ???
                         }
                     } else {
                         // go the previous input
                         *self = KeyboardNavInteraction::AroundInputSocket(other_inputs[index - 1]);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     }
                 } else if requested_dirs.go_down {
                     if index + 1 == other_inputs.len() {
                         // go to the processor
                         *self = KeyboardNavInteraction::AroundSoundProcessor(owner);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     } else {
                         // go the the next input
                         *self = KeyboardNavInteraction::AroundInputSocket(other_inputs[index + 1]);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     }
                 }
             }
             KeyboardNavInteraction::AroundExpression(eid) => {
-                rect = positions.expressions().get(eid).unwrap().clone();
+                rect = ctx.positions.expressions().get(eid).unwrap().clone();
 
-                let other_exprs: Vec<ProcessorExpressionLocation> =
-                    positions.processor_expressions_top_down(eid.processor());
+                let other_exprs: Vec<ProcessorExpressionLocation> = ctx
+                    .positions
+                    .processor_expressions_top_down(eid.processor());
                 let index = other_exprs.iter().position(|id| *id == *eid).unwrap();
 
                 allowed_dirs.go_up = index > 0;
@@ -271,24 +275,24 @@ impl KeyboardNavInteraction {
                 if requested_dirs.go_up {
                     if index > 0 {
                         *self = KeyboardNavInteraction::AroundExpression(other_exprs[index - 1]);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     }
                 } else if requested_dirs.go_down {
                     if index + 1 < other_exprs.len() {
                         *self = KeyboardNavInteraction::AroundExpression(other_exprs[index + 1]);
-                        snapshot_flag.request_snapshot();
+                        ctx.snapshot_flag.request_snapshot();
                     }
                 } else if requested_dirs.go_in {
                     *self =
                         KeyboardNavInteraction::InsideExpression(*eid, LexicalLayoutFocus::new());
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
                 } else if requested_dirs.go_out {
                     *self = KeyboardNavInteraction::AroundSoundProcessor(eid.processor());
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
                 }
             }
             KeyboardNavInteraction::InsideExpression(eid, ll_focus) => {
-                rect = positions.expressions().get(eid).unwrap().clone();
+                rect = ctx.positions.expressions().get(eid).unwrap().clone();
                 faint_highlight = true;
 
                 allowed_dirs.go_out = true;
@@ -297,9 +301,9 @@ impl KeyboardNavInteraction {
 
                 if requested_dirs.go_out {
                     *self = KeyboardNavInteraction::AroundExpression(*eid);
-                    snapshot_flag.request_snapshot();
+                    ctx.snapshot_flag.request_snapshot();
                 } else {
-                    let (expr_ui_state, ll) = expression_uis.get_mut(*eid).unwrap();
+                    let (expr_ui_state, ll) = ctx.expression_uis.get_mut(*eid).unwrap();
 
                     // TODO: why does this sometimes not find a node?
                     // Answer: because the cursor is over a variable name.
@@ -313,9 +317,6 @@ impl KeyboardNavInteraction {
 
                     let time_axis = layout.find_group(eid.processor()).unwrap().time_axis();
 
-                    let available_inputs = properties.available_inputs(eid.processor()).unwrap();
-                    let available_arguments = properties.available_arguments(*eid).unwrap();
-
                     graph
                         .sound_processor_mut(eid.processor())
                         .unwrap()
@@ -325,19 +326,21 @@ impl KeyboardNavInteraction {
                             let outer_context = OuterProcessorExpressionContext::new(
                                 *eid,
                                 mapping,
-                                names,
+                                ctx.names,
                                 time_axis,
-                                available_inputs,
-                                available_arguments,
-                                snapshot_flag,
+                                ctx.properties,
+                                ctx.snapshot_flag,
                             );
 
                             ll.handle_keypress(
                                 ui,
                                 ll_focus,
                                 expr_graph,
-                                factories,
-                                stash,
+                                &mut LexicalLayoutEditContext {
+                                    factories: ctx.factories,
+                                    stash: ctx.stash,
+                                    summon_history: &mut ctx.summon_histories.expression,
+                                },
                                 expr_ui_state.object_states_mut(),
                                 &mut outer_context.into(),
                             );
@@ -1,2 +1,5 @@
+pub mod bounce;
 pub mod draganddrop;
+pub mod duplicateprocessors;
 pub mod keyboardnav;
+pub mod replaceprocessor;
@@ -0,0 +1,133 @@
+use crate::core::sound::{
+    argument::{ProcessorArgumentId, ProcessorArgumentLocation},
+    expression::ExpressionParameterTarget,
+    sounderror::SoundError,
+    soundgraph::SoundGraph,
+    soundinput::{DisconnectBehavior, SoundInputLocation},
+    soundprocessor::{AnySoundProcessor, SoundProcessorId},
+};
+
+/// Returns true if the new processor can take the place of the old one,
+/// which requires both to have the same number of sound inputs.
+pub(crate) fn can_replace_processor(
+    old_processor: &dyn AnySoundProcessor,
+    new_processor: &dyn AnySoundProcessor,
+) -> bool {
+    old_processor.input_locations().len() == new_processor.input_locations().len()
+}
+
+/// The parts of a sound input which are carried over to the input
+/// in the same position on the replacement processor
+struct ReplacedInput {
+    location: SoundInputLocation,
+    target: Option<SoundProcessorId>,
+    speed: f32,
+    disconnect_behavior: DisconnectBehavior,
+    arguments: Vec<ProcessorArgumentId>,
+}
+
+fn replaced_inputs(processor: &dyn AnySoundProcessor) -> Vec<ReplacedInput> {
+    let mut inputs = Vec::new();
+    processor.foreach_input(|input, location| {
+        inputs.push(ReplacedInput {
+            location,
+            target: input.target(),
+            speed: input.speed(),
+            disconnect_behavior: input.disconnect_behavior(),
+            arguments: input.argument_scope().arguments().to_vec(),
+        });
+    });
+    inputs
+}
+
+/// Swap the processor with the given id for a new processor, which must
+/// have the same number of sound inputs (see `can_replace_processor`).
+/// The new processor's inputs are connected to whatever the old processor's
+/// inputs were connected to, in order, and every input that was connected
+/// to the old processor is connected to the new one. Expressions elsewhere
+/// in the graph which referred to the old processor's time, its inputs'
+/// times, or arguments of its inputs are made to refer to their
+/// counterparts on the new processor, matching arguments by their position
+/// within each input's scope. References without a counterpart are removed.
+pub(crate) fn replace_processor_in_graph(
+    graph: &mut SoundGraph,
+    old_processor_id: SoundProcessorId,
+    mut new_processor: Box<dyn AnySoundProcessor>,
+) -> Result<(), SoundError> {
+    let Some(old_processor) = graph.sound_processor(old_processor_id) else {
+        return Err(SoundError::ProcessorNotFound(old_processor_id));
+    };
+    assert!(can_replace_processor(old_processor, &*new_processor));
+
+    let new_processor_id = new_processor.id();
+    let old_inputs = replaced_inputs(old_processor);
+    let new_inputs = replaced_inputs(&*new_processor);
+    let dependent_inputs = graph.inputs_connected_to(old_processor_id);
+
+    // Pairs of old and new expression parameter targets
+    let mut retargets = vec![(
+        ExpressionParameterTarget::ProcessorTime(old_processor_id),
+        ExpressionParameterTarget::ProcessorTime(new_processor_id),
+    )];
+    for (old_input, new_input) in old_inputs.iter().zip(&new_inputs) {
+        retargets.push((
+            ExpressionParameterTarget::InputTime(old_input.location),
+            ExpressionParameterTarget::InputTime(new_input.location),
+        ));
+        for (old_arg, new_arg) in old_input.arguments.iter().zip(&new_input.arguments) {
+            retargets.push((
+                ExpressionParameterTarget::Argument(ProcessorArgumentLocation::new(
+                    old_processor_id,
+                    *old_arg,
+                )),
+                ExpressionParameterTarget::Argument(ProcessorArgumentLocation::new(
+                    new_processor_id,
+                    *new_arg,
+                )),
+            ));
+        }
+    }
+
+    // Carry over the connections and settings of the old processor's inputs
+    let mut old_inputs_iter = old_inputs.iter();
+    new_processor.foreach_input_mut(|input, _| {
+        let old_input = old_inputs_iter.next().unwrap();
+        input.set_target(old_input.target);
+        input.set_speed(old_input.speed);
+        input.set_disconnect_behavior(old_input.disconnect_behavior);
+    });
+
+    graph.remove_sound_processor(old_processor_id)?;
+    graph.add_sound_processor(new_processor);
+
+    for input_location in dependent_inputs {
+        graph.connect_sound_input(input_location, new_processor_id)?;
+    }
+
+    let processor_ids: Vec<SoundProcessorId> = graph.sound_processors().keys().cloned().collect();
+    for processor_id in processor_ids {
+        let processor = graph.sound_processor_mut(processor_id).unwrap();
+        processor.foreach_expression_mut(|expr, _| {
+            let targets: Vec<ExpressionParameterTarget> =
+                expr.mapping().items().values().cloned().collect();
+            for target in targets {
+                let refers_to_old_processor = match target {
+                    ExpressionParameterTarget::Argument(loc) => loc.processor() == old_processor_id,
+                    ExpressionParameterTarget::ProcessorTime(spid) => spid == old_processor_id,
+                    ExpressionParameterTarget::InputTime(loc) => {
+                        loc.processor() == old_processor_id
+                    }
+                };
+                if !refers_to_old_processor {
+                    continue;
+                }
+                match retargets.iter().find(|(old, _)| *old == target) {
+                    Some((_, new_target)) => expr.retarget(target, *new_target),
+                    None => expr.remove_target(target),
+                }
+            }
+        });
+    }
+
+    Ok(())
+}
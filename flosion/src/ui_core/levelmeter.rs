@@ -0,0 +1,90 @@
+use eframe::egui;
+
+/// Lowest level shown by a level meter, in decibels
+const MIN_DECIBELS: f32 = -60.0;
+
+/// Horizontal bar showing the RMS and peak levels of a signal, followed
+/// by a clip indicator. Clicking the meter is the cue to reset the
+/// clip indicator.
+pub struct LevelMeter {
+    peak: f32,
+    rms: f32,
+    clipped: bool,
+    width: f32,
+}
+
+impl LevelMeter {
+    pub fn new(peak: f32, rms: f32, clipped: bool) -> LevelMeter {
+        LevelMeter {
+            peak,
+            rms,
+            clipped,
+            width: 100.0,
+        }
+    }
+
+    pub fn width(mut self, width: f32) -> LevelMeter {
+        self.width = width;
+        self
+    }
+
+    /// Map an amplitude to a fraction of the meter's length on a decibel scale
+    fn amplitude_to_fraction(amplitude: f32) -> f32 {
+        if amplitude <= 0.0 {
+            return 0.0;
+        }
+        let decibels = 20.0 * amplitude.log10();
+        (1.0 - decibels / MIN_DECIBELS).clamp(0.0, 1.0)
+    }
+}
+
+impl egui::Widget for LevelMeter {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let height = 8.0;
+        let clip_width = height;
+        let spacing = 2.0;
+
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(self.width + spacing + clip_width, height),
+            egui::Sense::click(),
+        );
+
+        let bar_rect = egui::Rect::from_min_size(rect.min, egui::vec2(self.width, height));
+        let clip_rect = egui::Rect::from_min_size(
+            egui::pos2(bar_rect.right() + spacing, rect.top()),
+            egui::vec2(clip_width, height),
+        );
+
+        let painter = ui.painter();
+
+        painter.rect_filled(bar_rect, 0.0, egui::Color32::from_black_alpha(128));
+
+        let rms_x = bar_rect.left() + Self::amplitude_to_fraction(self.rms) * bar_rect.width();
+        painter.rect_filled(
+            egui::Rect::from_min_max(bar_rect.min, egui::pos2(rms_x, bar_rect.bottom())),
+            0.0,
+            egui::Color32::from_rgb(0, 160, 64),
+        );
+
+        let peak_x = bar_rect.left() + Self::amplitude_to_fraction(self.peak) * bar_rect.width();
+        painter.line_segment(
+            [
+                egui::pos2(peak_x, bar_rect.top()),
+                egui::pos2(peak_x, bar_rect.bottom()),
+            ],
+            egui::Stroke::new(2.0, egui::Color32::from_rgb(224, 224, 64)),
+        );
+
+        painter.rect_filled(
+            clip_rect,
+            0.0,
+            if self.clipped {
+                egui::Color32::from_rgb(224, 32, 32)
+            } else {
+                egui::Color32::from_black_alpha(128)
+            },
+        );
+
+        response
+    }
+}
@@ -18,7 +18,6 @@ pub(crate) struct ASTPath {
 }
 
 impl ASTPath {
-    #[cfg(test)]
     pub(super) fn new(steps: Vec<usize>) -> ASTPath {
         ASTPath { steps }
     }
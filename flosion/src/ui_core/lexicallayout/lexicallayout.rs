@@ -5,9 +5,11 @@ use crate::{
     core::{
         expression::{
             expressiongraph::ExpressionGraph, expressiongraph::ExpressionTarget,
-            expressionnode::ExpressionNodeId,
+            expressionnode::ExpressionNodeId, macrocall::MacroCall,
         },
+        jit::constantsubexpression::evaluate_constant_subexpression,
         objecttype::{ObjectType, WithObjectType},
+        sound::expression::ProcessorExpression,
     },
     objects::purefunctions::Constant,
     ui_core::{
@@ -27,7 +29,7 @@ use crate::{
 use crate::ui_core::{
     expressiongraphuicontext::ExpressionGraphUiContext,
     expressiongraphuistate::ExpressionNodeObjectUiStates,
-    summon_widget::{SummonWidget, SummonWidgetState},
+    summon_widget::{SummonHistory, SummonWidget, SummonWidgetState},
 };
 
 use super::{
@@ -198,6 +200,14 @@ fn algebraic_key(key: egui::Key, modifiers: egui::Modifiers) -> Option<char> {
     }
 }
 
+/// The resources shared between all expressions which are needed
+/// to edit an expression from the keyboard
+pub(crate) struct LexicalLayoutEditContext<'a> {
+    pub(crate) factories: &'a Factories,
+    pub(crate) stash: &'a Stash,
+    pub(crate) summon_history: &'a mut SummonHistory,
+}
+
 pub(crate) struct LexicalLayout {
     variable_definitions: Vec<VariableDefinition>,
     final_expressions: Vec<FinalExpression>,
@@ -631,8 +641,20 @@ impl LexicalLayout {
         let graph_object = expr_graph.node_mut(id).unwrap().as_graph_object_mut();
 
         ui.horizontal_centered(|ui| {
-            // Huh?
-            show_expression_node_ui(ctx.ui_factory(), graph_object, ui_state, ui, ctx);
+            let response = ui
+                .scope_builder(egui::UiBuilder::new().sense(egui::Sense::click()), |ui| {
+                    // Huh?
+                    show_expression_node_ui(ctx.ui_factory(), graph_object, ui_state, ui, ctx);
+                })
+                .response;
+
+            // Right click to freeze the node and its inputs into a constant
+            response.context_menu(|ui| {
+                if ui.button("Freeze to constant").clicked() {
+                    ui_state.request_freeze(id);
+                    ui.close_menu();
+                }
+            });
         })
         .response
         .rect
@@ -651,8 +673,7 @@ impl LexicalLayout {
         ui: &mut egui::Ui,
         focus: &mut LexicalLayoutFocus,
         expr_graph: &mut ExpressionGraph,
-        factories: &Factories,
-        stash: &Stash,
+        edit_context: &mut LexicalLayoutEditContext,
         object_ui_states: &mut ExpressionNodeObjectUiStates,
         outer_context: &mut OuterExpressionGraphUiContext,
     ) {
@@ -662,8 +683,7 @@ impl LexicalLayout {
             ui,
             focus,
             expr_graph,
-            factories,
-            stash,
+            edit_context,
             object_ui_states,
             outer_context,
         );
@@ -705,7 +725,13 @@ impl LexicalLayout {
             });
 
             if pressed_delete {
-                delete_from_graph_at_cursor(self, cursor, expr_graph, stash, factories);
+                delete_from_graph_at_cursor(
+                    self,
+                    cursor,
+                    expr_graph,
+                    edit_context.stash,
+                    edit_context.factories,
+                );
                 remove_unreferenced_parameters(self, outer_context, expr_graph);
                 outer_context.request_snapshot();
             }
@@ -757,8 +783,7 @@ impl LexicalLayout {
         ui: &mut egui::Ui,
         focus: &mut LexicalLayoutFocus,
         expr_graph: &mut ExpressionGraph,
-        factories: &Factories,
-        stash: &Stash,
+        edit_context: &mut LexicalLayoutEditContext,
         object_ui_states: &mut ExpressionNodeObjectUiStates,
         outer_context: &mut OuterExpressionGraphUiContext,
     ) {
@@ -766,52 +791,55 @@ impl LexicalLayout {
             return;
         }
 
-        // Check for space/tab presses
-        let pressed_space_or_tab = ui.input_mut(|i| {
-            i.consume_key(egui::Modifiers::NONE, egui::Key::Space)
-                || i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)
-        });
+        // Keys are only captured while the summon widget is closed, since
+        // once it is open they are needed for typing into and navigating it
+        if focus.summon_widget_state().is_none() {
+            // Check for space/tab presses
+            let pressed_space_or_tab = ui.input_mut(|i| {
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Space)
+                    || i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)
+            });
 
-        // Check for typing
-        let algebraic_keys_pressed = ui.input_mut(|input| {
-            let mut out_chars = Vec::new();
-            input.events = input
-                .events
-                .iter()
-                .filter(|e| {
-                    if let egui::Event::Key {
-                        key,
-                        pressed,
-                        repeat: _,
-                        modifiers,
-                        physical_key: _,
-                    } = e
-                    {
-                        if *pressed && modifiers.is_none() {
-                            if let Some(ch) = algebraic_key(*key, *modifiers) {
-                                out_chars.push(ch);
-                                return false;
+            // Check for typing
+            let algebraic_keys_pressed = ui.input_mut(|input| {
+                let mut out_chars = Vec::new();
+                input.events = input
+                    .events
+                    .iter()
+                    .filter(|e| {
+                        if let egui::Event::Key {
+                            key,
+                            pressed,
+                            repeat: _,
+                            modifiers,
+                            physical_key: _,
+                        } = e
+                        {
+                            if *pressed && modifiers.is_none() {
+                                if let Some(ch) = algebraic_key(*key, *modifiers) {
+                                    out_chars.push(ch);
+                                    return false;
+                                }
                             }
                         }
-                    }
-                    true
-                })
-                .cloned()
-                .collect();
-            out_chars
-        });
+                        true
+                    })
+                    .cloned()
+                    .collect();
+                out_chars
+            });
 
-        // open summon widget when space/tab is pressed or something was typed
-        if focus.summon_widget_state().is_none() {
+            // open summon widget when space/tab is pressed or something was typed
             if pressed_space_or_tab || !algebraic_keys_pressed.is_empty() {
                 if let Some(node_at_cursor) = focus.cursor().get_node(self) {
                     let mut widget_state = match outer_context {
                         OuterExpressionGraphUiContext::ProcessorExpression(sni_ctx) => {
                             build_summon_widget_for_processor_expression(
                                 node_at_cursor.rect().center_bottom(),
-                                factories.expression_uis(),
+                                edit_context.factories.expression_uis(),
                                 sni_ctx,
                                 focus.cursor().get_variables_in_scope(self),
+                                edit_context.summon_history,
                             )
                         }
                     };
@@ -828,6 +856,10 @@ impl LexicalLayout {
             let summon_widget = SummonWidget::new(summon_widget_state);
             ui.add(summon_widget);
 
+            if let Some(object_type) = summon_widget_state.take_toggled_favorite() {
+                edit_context.summon_history.toggle_favorite(object_type);
+            }
+
             if summon_widget_state.was_cancelled() {
                 focus.close_summon_widget();
             }
@@ -845,15 +877,17 @@ impl LexicalLayout {
             debug_assert!(lexical_layout_matches_expression_graph(self, expr_graph));
 
             let (new_node, layout) = match summon_value {
-                ExpressionSummonValue::ExpressionNodeType(ns_type) => self
-                    .create_new_expression_node_from_type(
+                ExpressionSummonValue::ExpressionNodeType(ns_type) => {
+                    edit_context.summon_history.record_summoned(ns_type);
+                    self.create_new_expression_node_from_type(
                         ns_type,
                         arguments,
-                        factories,
+                        edit_context.factories,
                         object_ui_states,
                         expr_graph,
                     )
-                    .unwrap(),
+                    .unwrap()
+                }
                 ExpressionSummonValue::ParameterTarget(target) => {
                     let node;
                     {
@@ -880,11 +914,37 @@ impl LexicalLayout {
                         .create_new_expression_node_from_type(
                             Constant::TYPE,
                             arguments.add_or_replace(&Constant::ARG_VALUE, constant_value as f64),
-                            factories,
+                            edit_context.factories,
+                            object_ui_states,
+                            expr_graph,
+                        )
+                        .unwrap();
+                    (node, layout)
+                }
+                ExpressionSummonValue::Macro(index) => {
+                    let OuterExpressionGraphUiContext::ProcessorExpression(ctx) = outer_context;
+                    let expr_macro = ctx.expression_macros().macros()[index].clone();
+                    let (node, layout) = self
+                        .create_new_expression_node_from_type(
+                            MacroCall::TYPE,
+                            arguments
+                                .add_or_replace(&MacroCall::ARG_NAME, expr_macro.name().to_string())
+                                .add_or_replace(
+                                    &MacroCall::ARG_NUM_INPUTS,
+                                    expr_macro.num_inputs(),
+                                ),
+                            edit_context.factories,
                             object_ui_states,
                             expr_graph,
                         )
                         .unwrap();
+                    let node_id = node.as_internal_node().unwrap().expression_node_id();
+                    expr_graph
+                        .node_mut(node_id)
+                        .unwrap()
+                        .downcast_mut::<MacroCall>()
+                        .unwrap()
+                        .set_definition(Some(expr_macro));
                     (node, layout)
                 }
             };
@@ -894,8 +954,8 @@ impl LexicalLayout {
                 focus.cursor_mut(),
                 new_node,
                 expr_graph,
-                stash,
-                factories,
+                edit_context.stash,
+                edit_context.factories,
             );
             remove_unreferenced_parameters(self, outer_context, expr_graph);
 
@@ -959,6 +1019,88 @@ impl LexicalLayout {
         Ok((node, layout))
     }
 
+    /// Find the cursor position of the AST node which shows the given
+    /// expression node, if it appears in the layout
+    fn find_cursor_for_node(&self, id: ExpressionNodeId) -> Option<LexicalLayoutCursor> {
+        fn find_path(node: &ASTNode, id: ExpressionNodeId, steps: &mut Vec<usize>) -> bool {
+            let Some(internal_node) = node.as_internal_node() else {
+                return false;
+            };
+            if internal_node.expression_node_id() == id {
+                return true;
+            }
+            for i in 0..internal_node.num_children() {
+                steps.push(i);
+                if find_path(internal_node.get_child(i), id, steps) {
+                    return true;
+                }
+                steps.pop();
+            }
+            false
+        }
+
+        for (i, defn) in self.variable_definitions.iter().enumerate() {
+            let mut steps = Vec::new();
+            if find_path(defn.value(), id, &mut steps) {
+                return Some(LexicalLayoutCursor::AtVariableValue(i, ASTPath::new(steps)));
+            }
+        }
+        for (i, final_expr) in self.final_expressions.iter().enumerate() {
+            let mut steps = Vec::new();
+            if find_path(final_expr.value(), id, &mut steps) {
+                return Some(LexicalLayoutCursor::AtFinalExpression(
+                    i,
+                    ASTPath::new(steps),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Replace the subexpression rooted at the given expression node with
+    /// a single constant node carrying its value. This is refused if the
+    /// subexpression depends on time or any arguments, or if it otherwise
+    /// doesn't evaluate to a constant.
+    pub(crate) fn freeze_to_constant(
+        &mut self,
+        id: ExpressionNodeId,
+        expr: &mut ProcessorExpression,
+        inkwell_context: &inkwell::context::Context,
+        factories: &Factories,
+        stash: &Stash,
+        object_ui_states: &mut ExpressionNodeObjectUiStates,
+    ) -> Result<(), String> {
+        let (mapping, expr_graph) = expr.parts_mut();
+
+        debug_assert!(lexical_layout_matches_expression_graph(self, expr_graph));
+
+        let mut cursor = self
+            .find_cursor_for_node(id)
+            .ok_or_else(|| format!("Expression node {} is not in the layout", id.value()))?;
+
+        let value = evaluate_constant_subexpression(inkwell_context, expr_graph, mapping, id)
+            .ok_or_else(|| {
+                format!(
+                    "Expression node {} does not evaluate to a constant",
+                    id.value()
+                )
+            })?;
+
+        let (node, _layout) = self.create_new_expression_node_from_type(
+            Constant::TYPE,
+            ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, value as f64),
+            factories,
+            object_ui_states,
+            expr_graph,
+        )?;
+
+        insert_to_graph_at_cursor(self, &mut cursor, node, expr_graph, stash, factories);
+
+        debug_assert!(lexical_layout_matches_expression_graph(self, expr_graph));
+
+        Ok(())
+    }
+
     pub(super) fn visit<F: FnMut(&ASTNode, ASTPathBuilder)>(&self, mut f: F) {
         for vardef in &self.variable_definitions {
             vardef.value().visit(
@@ -5,7 +5,7 @@ use crate::{
     ui_core::{
         expressiongraphuicontext::OuterProcessorExpressionContext,
         expressionobjectui::ExpressionObjectUiFactory,
-        summon_widget::{SummonWidgetState, SummonWidgetStateBuilder},
+        summon_widget::{SummonHistory, SummonWidgetState, SummonWidgetStateBuilder},
     },
 };
 
@@ -17,6 +17,8 @@ pub(super) enum ExpressionSummonValue {
     Constant(f32),
     ParameterTarget(ExpressionParameterTarget),
     Variable(VariableId),
+    /// The index of a macro in the sound graph's macro library
+    Macro(usize),
 }
 
 pub(super) fn build_summon_widget_for_processor_expression(
@@ -24,72 +26,44 @@ pub(super) fn build_summon_widget_for_processor_expression(
     ui_factory: &ExpressionObjectUiFactory,
     ctx: &OuterProcessorExpressionContext,
     variable_definitions: &[VariableDefinition],
+    summon_history: &SummonHistory,
 ) -> SummonWidgetState<ExpressionSummonValue> {
     let mut builder = SummonWidgetStateBuilder::new(position);
+    builder.with_history(summon_history, |v| match v {
+        ExpressionSummonValue::ExpressionNodeType(t) => Some(*t),
+        _ => None,
+    });
     for object_ui in ui_factory.all_object_uis() {
         for name in object_ui.summon_names() {
             builder.add_name_with_arguments(
                 name.to_string(),
                 object_ui.summon_arguments(),
                 ExpressionSummonValue::ExpressionNodeType(object_ui.object_type()),
+                object_ui.summon_category(),
             );
         }
     }
 
-    builder.add_basic_name(
-        "time".to_string(),
-        ExpressionSummonValue::ParameterTarget(ExpressionParameterTarget::ProcessorTime(
-            ctx.location().processor(),
-        )),
-    );
-
-    builder.add_basic_name(
-        format!(
-            "{}.time",
-            ctx.sound_graph_names()
-                .sound_processor(ctx.location().processor())
-                .unwrap()
-        ),
-        ExpressionSummonValue::ParameterTarget(ExpressionParameterTarget::ProcessorTime(
-            ctx.location().processor(),
-        )),
-    );
-
-    for input_loc in ctx.available_sound_inputs() {
-        builder.add_basic_name(
-            format!(
-                "{}.time",
-                ctx.sound_graph_names()
-                    .sound_processor(input_loc.processor())
-                    .unwrap()
-            ),
-            ExpressionSummonValue::ParameterTarget(ExpressionParameterTarget::ProcessorTime(
-                input_loc.processor(),
-            )),
-        );
-
+    for parameter in ctx.available_parameters() {
         builder.add_basic_name(
-            format!(
-                "{}.time",
-                ctx.sound_graph_names().combined_input_name(*input_loc)
-            ),
-            ExpressionSummonValue::ParameterTarget(ExpressionParameterTarget::InputTime(
-                *input_loc,
-            )),
+            parameter.name,
+            ExpressionSummonValue::ParameterTarget(parameter.target),
         );
     }
 
-    for snsid in ctx.available_arguments() {
+    // Variables defined before the cursor, so that typing part of a
+    // variable's name and pressing tab or enter refers to it
+    for var_defn in variable_definitions {
         builder.add_basic_name(
-            ctx.sound_graph_names().combined_argument_name(*snsid),
-            ExpressionSummonValue::ParameterTarget(ExpressionParameterTarget::Argument(*snsid)),
+            var_defn.name().to_string(),
+            ExpressionSummonValue::Variable(var_defn.id()),
         );
     }
 
-    for var_defn in variable_definitions {
+    for (index, expr_macro) in ctx.expression_macros().macros().iter().enumerate() {
         builder.add_basic_name(
-            var_defn.name().to_string(),
-            ExpressionSummonValue::Variable(var_defn.id()),
+            expr_macro.name().to_string(),
+            ExpressionSummonValue::Macro(index),
         );
     }
 
@@ -0,0 +1,121 @@
+use hashstash::Stash;
+
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeId, ExpressionNodeWithId},
+        },
+        sound::{
+            argument::ArgumentScope,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::{Constant, Multiply},
+    ui_core::{
+        arguments::ParsedArguments, expressiongraphuistate::ExpressionGraphUiState,
+        factories::Factories, lexicallayout::lexicallayout::LexicalLayout,
+        lexicallayout::validation::lexical_layout_matches_expression_graph,
+    },
+};
+
+/// Builds 2 * x where x is either the constant pi or the processor time,
+/// returning the expression and the id of the multiplication
+fn make_double_expression(time_dependent: bool) -> (ProcessorExpression, ExpressionNodeId) {
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new_empty());
+
+    let x = if time_dependent {
+        ExpressionTarget::Parameter(expr.add_target(ExpressionParameterTarget::ProcessorTime(
+            SoundProcessorId::new(1),
+        )))
+    } else {
+        let pi = ExpressionNodeWithId::<Constant>::new_from_args(
+            &ParsedArguments::new_empty()
+                .add_or_replace(&Constant::ARG_VALUE, std::f32::consts::PI as f64),
+        );
+        let pi_id = pi.id();
+        expr.graph_mut().add_expression_node(Box::new(pi));
+        ExpressionTarget::Node(pi_id)
+    };
+
+    let graph = expr.graph_mut();
+
+    let two = ExpressionNodeWithId::<Constant>::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, 2.0),
+    );
+    let two_id = two.id();
+    graph.add_expression_node(Box::new(two));
+
+    let multiply = ExpressionNodeWithId::<Multiply>::new_default();
+    let multiply_id = multiply.id();
+    let multiply_inputs = (&multiply as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(multiply));
+
+    graph
+        .connect_input(multiply_inputs[0], Some(ExpressionTarget::Node(two_id)))
+        .unwrap();
+    graph.connect_input(multiply_inputs[1], Some(x)).unwrap();
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(multiply_id))
+        .unwrap();
+
+    (expr, multiply_id)
+}
+
+/// Attempt to freeze the subexpression rooted at the given node
+fn freeze(expr: &mut ProcessorExpression, id: ExpressionNodeId) -> Result<LexicalLayout, String> {
+    let factories = Factories::new_all_objects();
+    let stash = Stash::new();
+    let inkwell_context = inkwell::context::Context::create();
+
+    let mut ui_state = ExpressionGraphUiState::generate(expr.graph(), factories.expression_uis());
+    let mut layout = LexicalLayout::generate(
+        expr.graph(),
+        ui_state.object_states(),
+        factories.expression_uis(),
+    );
+
+    layout.freeze_to_constant(
+        id,
+        expr,
+        &inkwell_context,
+        &factories,
+        &stash,
+        ui_state.object_states_mut(),
+    )?;
+
+    Ok(layout)
+}
+
+#[test]
+fn test_freeze_two_pi() {
+    let (mut expr, multiply_id) = make_double_expression(false);
+
+    let layout = freeze(&mut expr, multiply_id).unwrap();
+
+    let graph = expr.graph();
+    assert_eq!(graph.nodes().len(), 1);
+    let (constant_id, constant) = graph.nodes().iter().next().unwrap();
+    let constant = constant.downcast::<Constant>().unwrap();
+    assert_eq!(constant.value(), 2.0 * std::f32::consts::PI);
+    assert_eq!(
+        graph.results()[0].target(),
+        Some(ExpressionTarget::Node(*constant_id))
+    );
+    assert!(lexical_layout_matches_expression_graph(&layout, graph));
+}
+
+#[test]
+fn test_time_dependent_subtree_refuses_to_freeze() {
+    let (mut expr, multiply_id) = make_double_expression(true);
+
+    assert!(freeze(&mut expr, multiply_id).is_err());
+
+    let graph = expr.graph();
+    assert!(graph.node(multiply_id).is_some());
+    assert_eq!(
+        graph.results()[0].target(),
+        Some(ExpressionTarget::Node(multiply_id))
+    );
+}
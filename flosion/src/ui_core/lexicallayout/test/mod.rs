@@ -1 +1,3 @@
+mod freezetest;
 mod lexicallayouttest;
+mod summontest;
@@ -0,0 +1,91 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::{
+        expression::ProcessorExpressionLocation, soundgraph::SoundGraph,
+        soundprocessor::SoundProcessorWithId,
+    },
+    objects::wavegenerator::WaveGenerator,
+    ui_core::{
+        expressiongraphuicontext::OuterProcessorExpressionContext,
+        factories::Factories,
+        graph_properties::GraphProperties,
+        history::SnapshotFlag,
+        lexicallayout::{
+            ast::{ASTNode, ASTNodeValue, VariableDefinition, VariableId},
+            summon::{build_summon_widget_for_processor_expression, ExpressionSummonValue},
+        },
+        soundgraphuinames::SoundGraphUiNames,
+        stackedlayout::timeaxis::TimeAxis,
+        summon_widget::{SummonHistory, SummonWidgetState},
+    },
+};
+
+/// The variable which would be summoned by the current text, if any
+fn best_variable(widget: &SummonWidgetState<ExpressionSummonValue>) -> Option<VariableId> {
+    match widget.best_choice() {
+        Some((ExpressionSummonValue::Variable(id), _)) => Some(id),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_partial_variable_name_completes_to_variable() {
+    let wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let location = ProcessorExpressionLocation::new(wavegen.id(), wavegen.amplitude.id());
+    let mut mapping = wavegen.amplitude.mapping().clone();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(wavegen));
+
+    let mut names = SoundGraphUiNames::new();
+    names.cleanup(&graph);
+    let mut properties = GraphProperties::new();
+    properties.refresh(&graph);
+    let snapshot_flag = SnapshotFlag::new();
+    let ctx = OuterProcessorExpressionContext::new(
+        location,
+        &mut mapping,
+        &names,
+        TimeAxis {
+            time_per_x_pixel: 0.01,
+        },
+        &properties,
+        &snapshot_flag,
+    );
+
+    let x1 = VariableId::new(1);
+    let curve = VariableId::new(2);
+    let variables = [
+        VariableDefinition::new(x1, "x1".to_string(), ASTNode::new(ASTNodeValue::Empty)),
+        VariableDefinition::new(
+            curve,
+            "velocity_curve".to_string(),
+            ASTNode::new(ASTNodeValue::Empty),
+        ),
+    ];
+
+    let factories = Factories::new_all_objects();
+    let mut widget = build_summon_widget_for_processor_expression(
+        egui::pos2(0.0, 0.0),
+        factories.expression_uis(),
+        &ctx,
+        &variables,
+        &SummonHistory::new(),
+    );
+
+    widget.set_text("veloc".to_string());
+    assert_eq!(best_variable(&widget), Some(curve));
+
+    widget.set_text("x".to_string());
+    assert_eq!(best_variable(&widget), Some(x1));
+
+    // Both variables are listed as candidates
+    let listed: Vec<&str> = widget
+        .listed_groups()
+        .into_iter()
+        .flat_map(|(_, names)| names)
+        .collect();
+    assert!(listed.contains(&"x1"));
+    assert!(listed.contains(&"velocity_curve"));
+}
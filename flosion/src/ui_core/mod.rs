@@ -1,5 +1,7 @@
 pub mod appstate;
 pub mod arguments;
+pub mod beatsyncui;
+pub mod constantdiagnostics;
 pub mod expressiongraphuicontext;
 pub mod expressiongraphuistate;
 pub mod expressionobjectui;
@@ -8,12 +10,16 @@ pub mod expressionplot;
 pub mod expressionui;
 pub mod factories;
 pub mod flosion_ui;
+pub mod frequencyresponseplot;
 pub mod globalinteractions;
 pub mod graph_properties;
 pub mod history;
 pub mod interactions;
+pub mod levelmeter;
 pub mod lexicallayout;
 pub mod object_ui;
+mod processorpalette;
+pub mod processorsearch;
 pub mod soundgraphuicontext;
 pub mod soundgraphuinames;
 pub mod soundgraphuistate;
@@ -27,6 +27,31 @@ pub fn random_object_color() -> egui::Color32 {
     color.into()
 }
 
+/// The broad kind of an object, by which the candidates of the summon
+/// widget are grouped
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum SummonCategory {
+    Oscillators,
+    Filters,
+    Dynamics,
+    Math,
+    Logic,
+    Utilities,
+}
+
+impl SummonCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SummonCategory::Oscillators => "Oscillators",
+            SummonCategory::Filters => "Filters",
+            SummonCategory::Dynamics => "Dynamics",
+            SummonCategory::Math => "Math",
+            SummonCategory::Logic => "Logic",
+            SummonCategory::Utilities => "Utilities",
+        }
+    }
+}
+
 pub trait ObjectUiState {
     fn as_any(&self) -> &dyn Any;
     fn as_mut_any(&mut self) -> &mut dyn Any;
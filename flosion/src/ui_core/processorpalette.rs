@@ -0,0 +1,38 @@
+use eframe::egui;
+
+use crate::core::sound::{soundgraph::SoundGraph, soundprocessor::SoundProcessorId};
+
+use super::{
+    soundobjectpositions::SoundObjectPositions,
+    stackedlayout::stackedlayout::StackedLayout,
+    summon_widget::{SummonWidgetState, SummonWidgetStateBuilder},
+};
+
+/// Create a summon widget listing every sound processor in the graph by
+/// its friendly name, showing only those which match the typed text
+pub(super) fn build_processor_palette(
+    graph: &SoundGraph,
+    position: egui::Pos2,
+) -> SummonWidgetState<SoundProcessorId> {
+    let mut builder = SummonWidgetStateBuilder::new(position);
+    builder.with_filtering();
+    for proc in graph.sound_processors().values() {
+        builder.add_basic_name(proc.as_graph_object().friendly_name(), proc.id());
+    }
+    builder.build()
+}
+
+/// Move the layout such that the given processor is centered within
+/// the given view. Returns false if the processor hasn't been drawn yet.
+pub(super) fn jump_to_processor(
+    processor: SoundProcessorId,
+    layout: &mut StackedLayout,
+    positions: &SoundObjectPositions,
+    view: egui::Rect,
+) -> bool {
+    let Some(position) = positions.find_processor(processor) else {
+        return false;
+    };
+    layout.translate(view.center() - position.body_rect.center());
+    true
+}
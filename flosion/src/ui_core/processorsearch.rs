@@ -0,0 +1,116 @@
+use eframe::egui;
+
+use crate::core::sound::soundprocessor::SoundProcessorId;
+
+use super::{
+    processorpalette::jump_to_processor, soundgraphuinames::SoundGraphUiNames,
+    soundobjectpositions::SoundObjectPositions, stackedlayout::stackedlayout::StackedLayout,
+};
+
+/// Find every processor whose name contains the query, ignoring case,
+/// sorted by name. An empty query matches nothing.
+pub(super) fn find_processors_by_name(
+    names: &SoundGraphUiNames,
+    query: &str,
+) -> Vec<SoundProcessorId> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<(&str, SoundProcessorId)> = names
+        .sound_processors()
+        .filter(|(_, name)| name.to_lowercase().contains(&query))
+        .map(|(id, name)| (name, id))
+        .collect();
+    matches.sort_by_key(|(name, id)| (*name, id.value()));
+    matches.into_iter().map(|(_, id)| id).collect()
+}
+
+/// A search box for finding processors on the canvas by name. Matching
+/// processors are highlighted, and the view is moved to show them one
+/// at a time.
+pub(crate) struct ProcessorSearch {
+    query: String,
+
+    /// The index among the matching processors of the one most recently
+    /// jumped to
+    current: usize,
+}
+
+impl ProcessorSearch {
+    pub(crate) fn new() -> ProcessorSearch {
+        ProcessorSearch {
+            query: String::new(),
+            current: 0,
+        }
+    }
+
+    /// Show the search box in the corner of the canvas and highlight every
+    /// matching processor. Whenever the query changes, the view jumps to
+    /// the first match, and pressing enter jumps to the next one. Ctrl+F
+    /// focuses the search box.
+    pub(crate) fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        names: &SoundGraphUiNames,
+        layout: &mut StackedLayout,
+        positions: &SoundObjectPositions,
+    ) {
+        let view = ui.clip_rect();
+        let text_edit_id = egui::Id::new("processor_search");
+
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::F)) {
+            ui.memory_mut(|m| m.request_focus(text_edit_id));
+        }
+
+        let mut changed = false;
+        let mut pressed_enter = false;
+
+        egui::Area::new(egui::Id::new("processor_search_area"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-20.0, 20.0))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query)
+                            .id(text_edit_id)
+                            .hint_text("Find processor (Ctrl+F)")
+                            .desired_width(160.0),
+                    );
+                    changed = response.changed();
+                    pressed_enter =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if pressed_enter {
+                        // Keep typing or press enter again for the next match
+                        response.request_focus();
+                    }
+                });
+            });
+
+        let matches = find_processors_by_name(names, &self.query);
+        if matches.is_empty() {
+            self.current = 0;
+            return;
+        }
+
+        if changed {
+            self.current = 0;
+            jump_to_processor(matches[0], layout, positions, view);
+        } else if pressed_enter {
+            self.current = (self.current + 1) % matches.len();
+            jump_to_processor(matches[self.current], layout, positions, view);
+        }
+
+        for (i, spid) in matches.iter().enumerate() {
+            let Some(position) = positions.find_processor(*spid) else {
+                continue;
+            };
+            let width = if i == self.current { 4.0 } else { 2.0 };
+            ui.painter().rect_stroke(
+                position.body_rect,
+                egui::Rounding::same(3.0),
+                egui::Stroke::new(width, egui::Color32::from_rgb(255, 128, 0)),
+            );
+        }
+    }
+}
@@ -11,6 +11,13 @@ use super::{
     stackedlayout::timeaxis::TimeAxis,
 };
 
+/// The stacked group which a processor's ui is being drawn in
+pub(crate) struct StackedGroupView<'a> {
+    pub(crate) time_axis: TimeAxis,
+    pub(crate) width: f32,
+    pub(crate) processors: &'a [SoundProcessorId],
+}
+
 pub struct SoundGraphUiContext<'a, 'ctx> {
     factories: &'a Factories,
     time_axis: TimeAxis,
@@ -20,13 +27,14 @@ pub struct SoundGraphUiContext<'a, 'ctx> {
     stash: &'a Stash,
     snapshot_flag: &'a SnapshotFlag,
     sound_engine_report: &'a SoundEngineReport,
+    /// The processors in the stacked group being drawn
+    group_processors: &'a [SoundProcessorId],
 }
 
 impl<'a, 'ctx> SoundGraphUiContext<'a, 'ctx> {
     pub(crate) fn new(
         factories: &'a Factories,
-        time_axis: TimeAxis,
-        width: f32,
+        group: StackedGroupView<'a>,
         properties: &'a GraphProperties,
         jit_cache: &'a JitCache<'ctx>,
         stash: &'a Stash,
@@ -35,13 +43,14 @@ impl<'a, 'ctx> SoundGraphUiContext<'a, 'ctx> {
     ) -> SoundGraphUiContext<'a, 'ctx> {
         SoundGraphUiContext {
             factories,
-            time_axis,
-            width,
+            time_axis: group.time_axis,
+            width: group.width,
             properties,
             jit_cache,
             stash,
             snapshot_flag,
             sound_engine_report,
+            group_processors: group.processors,
         }
     }
 
@@ -73,6 +82,10 @@ impl<'a, 'ctx> SoundGraphUiContext<'a, 'ctx> {
         self.snapshot_flag
     }
 
+    pub(crate) fn group_processors(&self) -> &'a [SoundProcessorId] {
+        self.group_processors
+    }
+
     pub fn request_snapshot(&self) {
         self.snapshot_flag.request_snapshot();
     }
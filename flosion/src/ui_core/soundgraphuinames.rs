@@ -46,6 +46,44 @@ impl SoundGraphUiNames {
         next_highest_number
     }
 
+    /// The given name with its first letter in upper case
+    fn capitalized(name: &str) -> String {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// The given name if no processor other than the given one has it,
+    /// and otherwise the name with the lowest number after it which no
+    /// other processor has. A number already at the end of the name is
+    /// counted upwards from, such that e.g. "Mixer 1" becomes "Mixer 2".
+    fn unique_processor_name(&self, processor: SoundProcessorId, name: &str) -> String {
+        let is_taken = |candidate: &str| {
+            self.sound_processors
+                .iter()
+                .any(|(id, other)| *id != processor && other == candidate)
+        };
+        if !is_taken(name) {
+            return name.to_string();
+        }
+        let (stem, mut i) = match name.rsplit_once(' ') {
+            Some((stem, number)) => match number.parse::<usize>() {
+                Ok(n) => (stem, n + 1),
+                Err(_) => (name, 2),
+            },
+            None => (name, 2),
+        };
+        loop {
+            let candidate = format!("{} {}", stem, i);
+            if !is_taken(&candidate) {
+                return candidate;
+            }
+            i += 1;
+        }
+    }
+
     pub(crate) fn cleanup(&mut self, graph: &SoundGraph) {
         self.arguments.retain(|k, _v| graph.contains(k));
         self.expression_results.retain(|k, _v| graph.contains(k.0));
@@ -56,20 +94,31 @@ impl SoundGraphUiNames {
             names: &'a mut SoundGraphUiNames,
             processor_id: SoundProcessorId,
             next_argument_number: usize,
-            next_sound_input_number: usize,
         }
 
         const PREFIX_ARGUMENT: &str = "argument";
-        const PREFIX_INPUT: &str = "input";
+        const PREFIX_INPUT: &str = "Input ";
 
         impl<'a> ProcessorComponentVisitor for DefaultNameVisitor<'a> {
             fn input(&mut self, input: &dyn AnyProcessorInput) {
                 let location = SoundInputLocation::new(self.processor_id, input.id());
-                self.names.sound_inputs.entry(location).or_insert_with(|| {
-                    let i = self.next_sound_input_number;
-                    self.next_sound_input_number += 1;
-                    format!("{}{}", PREFIX_INPUT, i)
-                });
+                if self.names.sound_inputs.contains_key(&location) {
+                    return;
+                }
+                // Inputs are numbered separately within each processor,
+                // since their names are always shown next to it
+                let processor_id = self.processor_id;
+                let i = SoundGraphUiNames::parse_next_highest_number(
+                    self.names
+                        .sound_inputs
+                        .iter()
+                        .filter(|(l, _)| l.processor() == processor_id)
+                        .map(|(_, name)| name),
+                    PREFIX_INPUT,
+                );
+                self.names
+                    .sound_inputs
+                    .insert(location, format!("{}{}", PREFIX_INPUT, i));
             }
 
             fn expression(&mut self, expression: &ProcessorExpression) {
@@ -96,27 +145,24 @@ impl SoundGraphUiNames {
         }
 
         for proc_data in graph.sound_processors().values() {
-            let type_name = proc_data.as_graph_object().get_dynamic_type().name();
-            let mut next_processor_number =
-                Self::parse_next_highest_number(self.sound_processors.values(), type_name);
-            self.sound_processors
-                .entry(proc_data.id())
-                .or_insert_with(|| {
-                    let i = next_processor_number;
-                    next_processor_number += 1;
-                    format!("{}{}", type_name, i)
-                });
+            if !self.sound_processors.contains_key(&proc_data.id()) {
+                // e.g. "Mixer 1", "Mixer 2", etc
+                let prefix = format!(
+                    "{} ",
+                    Self::capitalized(proc_data.as_graph_object().get_dynamic_type().name())
+                );
+                let i = Self::parse_next_highest_number(self.sound_processors.values(), &prefix);
+                let name = self.unique_processor_name(proc_data.id(), &format!("{}{}", prefix, i));
+                self.sound_processors.insert(proc_data.id(), name);
+            }
 
             let next_argument_number =
                 Self::parse_next_highest_number(self.arguments.values(), PREFIX_ARGUMENT);
-            let next_sound_input_number =
-                Self::parse_next_highest_number(self.sound_inputs.values(), PREFIX_INPUT);
 
             let mut visitor = DefaultNameVisitor {
                 names: self,
                 processor_id: proc_data.id(),
                 next_argument_number,
-                next_sound_input_number,
             };
             proc_data.visit(&mut visitor);
         }
@@ -144,6 +190,13 @@ impl SoundGraphUiNames {
         self.sound_processors.get(&id).map(|s| s.as_str())
     }
 
+    /// The name of every processor
+    pub(crate) fn sound_processors(&self) -> impl Iterator<Item = (SoundProcessorId, &str)> {
+        self.sound_processors
+            .iter()
+            .map(|(id, name)| (*id, name.as_str()))
+    }
+
     pub(crate) fn record_argument_name(
         &mut self,
         location: ProcessorArgumentLocation,
@@ -160,6 +213,18 @@ impl SoundGraphUiNames {
         *self.sound_processors.get_mut(&id).unwrap() = name;
     }
 
+    /// Give the processor a new name, which is changed if needed to be
+    /// different from the name of every other processor. Names which are
+    /// empty or only whitespace are ignored. Returns the processor's name.
+    pub(crate) fn rename_sound_processor(&mut self, id: SoundProcessorId, name: &str) -> &str {
+        let name = name.trim();
+        if !name.is_empty() {
+            let name = self.unique_processor_name(id, name);
+            *self.sound_processors.get_mut(&id).unwrap() = name;
+        }
+        self.sound_processors.get(&id).unwrap()
+    }
+
     pub(crate) fn record_expression_result_name(
         &mut self,
         location: ProcessorExpressionLocation,
@@ -1,12 +1,16 @@
+use std::path::Path;
+
 use eframe::egui;
 use hashstash::{InplaceUnstasher, Stash, Stashable, Stasher, UnstashError, UnstashableInplace};
 
 use crate::{
     core::{
-        audiofileio::load_audio_file,
+        jit::cache::JitCache,
+        objecttype::WithObjectType,
         sound::{
             expression::{ProcessorExpression, ProcessorExpressionLocation},
             soundgraph::SoundGraph,
+            soundgraphid::SoundObjectId,
             soundprocessor::{SoundProcessorId, SoundProcessorWithId},
         },
     },
@@ -14,22 +18,37 @@ use crate::{
 };
 
 use super::{
+    arguments::ParsedArguments,
     expressiongraphuicontext::{ExpressionGraphUiContext, OuterProcessorExpressionContext},
     expressiongraphuistate::ExpressionUiCollection,
     expressionplot::PlotConfig,
     expressionui::SoundExpressionUi,
     factories::Factories,
-    globalinteractions::GlobalInteractions,
+    globalinteractions::{GlobalInteractionContext, GlobalInteractions, ProcessorClipboard},
     graph_properties::GraphProperties,
     history::SnapshotFlag,
+    processorpalette::jump_to_processor,
+    processorsearch::ProcessorSearch,
     soundgraphuicontext::SoundGraphUiContext,
     soundgraphuinames::SoundGraphUiNames,
     soundobjectpositions::SoundObjectPositions,
     soundobjectuistate::SoundObjectUiStates,
     stackedlayout::stackedlayout::StackedLayout,
     stashing::UiUnstashingContext,
+    summon_widget::SummonHistories,
 };
 
+/// The app-wide resources which the sound graph ui reads from
+/// while interacting with and drawing the graph
+#[derive(Clone, Copy)]
+pub(crate) struct SharedUiResources<'a, 'ctx> {
+    pub(crate) factories: &'a Factories,
+    pub(crate) properties: &'a GraphProperties,
+    pub(crate) jit_cache: &'a JitCache<'ctx>,
+    pub(crate) stash: &'a Stash,
+    pub(crate) snapshot_flag: &'a SnapshotFlag,
+}
+
 pub struct SoundGraphUiState {
     /// The ui information needed for all expression uis
     expression_uis: ExpressionUiCollection,
@@ -47,6 +66,49 @@ pub struct SoundGraphUiState {
 
     /// The positions of on-screen things that need tracking for later lookup
     positions: SoundObjectPositions,
+
+    /// The most recent error from a file being dropped onto the canvas,
+    /// if any, which is shown until dismissed
+    file_drop_error: Option<String>,
+
+    /// The processors most recently copied, if any, which are kept
+    /// across undo and redo
+    clipboard: Option<ProcessorClipboard>,
+
+    /// Favorite and recently-summoned sound objects and expression nodes,
+    /// which are kept across undo and redo and saved by the app
+    summon_histories: SummonHistories,
+
+    /// The search box for finding processors by name
+    search: ProcessorSearch,
+}
+
+/// The file extensions of audio files which can be dropped onto the canvas
+const DROPPABLE_AUDIO_EXTENSIONS: &[&str] = &["wav", "ogg", "flac"];
+
+/// Get the arguments with which to create an AudioClip from a file that
+/// was dropped onto the canvas, or an error message if the file's type
+/// is not supported
+pub(crate) fn audioclip_arguments_for_dropped_file(path: &Path) -> Result<ParsedArguments, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    let supported = match &extension {
+        Some(e) => DROPPABLE_AUDIO_EXTENSIONS.contains(&e.as_str()),
+        None => false,
+    };
+
+    if !supported {
+        return Err(format!(
+            "Can't open \"{}\": only {} files are supported",
+            path.display(),
+            DROPPABLE_AUDIO_EXTENSIONS.join(", ")
+        ));
+    }
+
+    Ok(ParsedArguments::new_empty().add_or_replace(&AudioClip::ARG_PATH, path.to_path_buf()))
 }
 
 impl SoundGraphUiState {
@@ -57,6 +119,10 @@ impl SoundGraphUiState {
             names: SoundGraphUiNames::new(),
             interactions: GlobalInteractions::new(),
             positions: SoundObjectPositions::new(),
+            file_drop_error: None,
+            clipboard: None,
+            summon_histories: SummonHistories::new(),
+            search: ProcessorSearch::new(),
         }
     }
 
@@ -64,20 +130,37 @@ impl SoundGraphUiState {
         &self.object_states
     }
 
+    pub(crate) fn object_states_mut(&mut self) -> &mut SoundObjectUiStates {
+        &mut self.object_states
+    }
+
     pub(crate) fn interactions_mut(&mut self) -> &mut GlobalInteractions {
         &mut self.interactions
     }
 
+    pub(crate) fn summon_histories(&self) -> &SummonHistories {
+        &self.summon_histories
+    }
+
+    pub(crate) fn summon_histories_mut(&mut self) -> &mut SummonHistories {
+        &mut self.summon_histories
+    }
+
     pub(crate) fn interact_and_draw(
         &mut self,
         ui: &mut egui::Ui,
-        factories: &Factories,
         graph: &mut SoundGraph,
-        properties: &GraphProperties,
         layout: &mut StackedLayout,
-        stash: &Stash,
-        snapshot_flag: &SnapshotFlag,
+        resources: &SharedUiResources,
     ) {
+        let SharedUiResources {
+            factories,
+            properties,
+            jit_cache,
+            stash,
+            snapshot_flag,
+        } = *resources;
+
         let bg_response = ui.interact_bg(egui::Sense::click_and_drag());
 
         ui.with_layer_id(
@@ -86,37 +169,192 @@ impl SoundGraphUiState {
                 egui::Id::new("foreground_interactions"),
             ),
             |ui| {
-                self.interactions.interact_and_draw(
-                    ui,
+                let mut ctx = GlobalInteractionContext {
                     factories,
-                    graph,
                     properties,
-                    layout,
-                    &mut self.object_states,
-                    &mut self.positions,
-                    &mut self.expression_uis,
-                    &self.names,
-                    bg_response,
+                    names: &self.names,
+                    jit_cache,
                     stash,
                     snapshot_flag,
-                );
+                    object_states: &mut self.object_states,
+                    positions: &mut self.positions,
+                    expression_uis: &mut self.expression_uis,
+                    clipboard: &mut self.clipboard,
+                    summon_histories: &mut self.summon_histories,
+                };
+                self.interactions
+                    .interact_and_draw(ui, &mut ctx, graph, layout, bg_response);
             },
         );
 
         let dropped_files = ui.input(|i| i.raw.dropped_files.clone());
 
-        for dropped_file in dropped_files {
-            let path = dropped_file.path.as_ref().unwrap();
-            println!("Loading {}", path.display());
-            if let Ok(buf) = load_audio_file(path) {
-                let audioclip = SoundProcessorWithId::<AudioClip>::new_default();
-                audioclip.set_data(buf);
-                graph.add_sound_processor(Box::new(audioclip));
-                println!("Loaded {}", path.display());
-            } else {
-                println!("Failed to load {}", path.display());
+        if !dropped_files.is_empty() {
+            let mut position = ui
+                .ctx()
+                .pointer_latest_pos()
+                .unwrap_or(egui::pos2(50.0, 50.0));
+
+            for dropped_file in dropped_files {
+                let Some(path) = &dropped_file.path else {
+                    continue;
+                };
+                match self.handle_dropped_file(path, position, factories, graph) {
+                    Ok(_) => {
+                        self.file_drop_error = None;
+                        snapshot_flag.request_snapshot();
+                    }
+                    Err(e) => self.file_drop_error = Some(e),
+                }
+                // Stagger multiple dropped files so they don't overlap
+                position += egui::vec2(20.0, 20.0);
             }
         }
+
+        self.show_file_drop_error(ui);
+
+        self.show_problems(ui, graph, properties, layout);
+
+        self.search.show(ui, &self.names, layout, &self.positions);
+    }
+
+    /// Create a new AudioClip processor at the given position which
+    /// is pre-loaded with the audio file at the given path
+    pub(super) fn handle_dropped_file(
+        &mut self,
+        path: &Path,
+        position: egui::Pos2,
+        factories: &Factories,
+        graph: &mut SoundGraph,
+    ) -> Result<SoundProcessorId, String> {
+        let args = audioclip_arguments_for_dropped_file(path)?;
+
+        let new_obj = factories
+            .sound_objects()
+            .create(AudioClip::TYPE.name(), &args);
+
+        let audioclip = new_obj
+            .as_any()
+            .downcast_ref::<SoundProcessorWithId<AudioClip>>()
+            .unwrap();
+        if audioclip.get_data().sample_len() == 0 {
+            return Err(format!("Failed to load audio from \"{}\"", path.display()));
+        }
+
+        let object_ui = factories.sound_uis().get(new_obj.get_dynamic_type());
+        let state = object_ui.make_ui_state(&*new_obj, &args).unwrap();
+
+        self.object_states.set_object_data(new_obj.id(), state);
+
+        let SoundObjectId::Sound(id) = new_obj.id();
+        self.positions.record_processor(
+            id,
+            egui::Rect::from_min_size(position, egui::Vec2::ZERO),
+            egui::Rect::from_min_size(position, egui::Vec2::ZERO),
+        );
+
+        graph.add_sound_processor(new_obj.into_boxed_sound_processor().unwrap());
+
+        Ok(id)
+    }
+
+    fn show_file_drop_error(&mut self, ui: &mut egui::Ui) {
+        let Some(error) = &self.file_drop_error else {
+            return;
+        };
+
+        let mut dismissed = false;
+
+        egui::Area::new(egui::Id::new("file_drop_error"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -20.0))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::RED, error);
+                        dismissed = ui.button("Dismiss").clicked();
+                    });
+                });
+            });
+
+        if dismissed {
+            self.file_drop_error = None;
+        }
+    }
+
+    /// List every suspicious constant found in the graph's expressions,
+    /// each labeled with the processor and expression it belongs to.
+    /// Clicking a problem jumps to its processor.
+    fn show_problems(
+        &self,
+        ui: &mut egui::Ui,
+        graph: &SoundGraph,
+        properties: &GraphProperties,
+        layout: &mut StackedLayout,
+    ) {
+        let problems = properties.suspicious_constants();
+        if problems.is_empty() {
+            return;
+        }
+
+        let view = ui.clip_rect();
+        let mut jump_to = None;
+
+        egui::Area::new(egui::Id::new("problems"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-20.0, -20.0))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    egui::CollapsingHeader::new(format!("Problems ({})", problems.len()))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for problem in problems {
+                                let location = problem.expression;
+                                let processor_name = self
+                                    .names
+                                    .sound_processor(location.processor())
+                                    .unwrap_or("???");
+                                let result_names: Vec<&str> = graph
+                                    .sound_processor(location.processor())
+                                    .and_then(|proc| {
+                                        proc.with_expression(location.expression(), |expr| {
+                                            expr.graph()
+                                                .results()
+                                                .iter()
+                                                .filter_map(|r| {
+                                                    self.names.expression_result(location, r.id())
+                                                })
+                                                .collect()
+                                        })
+                                    })
+                                    .unwrap_or_default();
+                                let text = format!(
+                                    "{} / {}: {}",
+                                    processor_name,
+                                    result_names.join(", "),
+                                    problem.explain()
+                                );
+                                if ui
+                                    .add(
+                                        egui::Label::new(
+                                            egui::RichText::new(text)
+                                                .color(egui::Color32::from_rgb(224, 160, 32)),
+                                        )
+                                        .sense(egui::Sense::click()),
+                                    )
+                                    .on_hover_text("Click to jump to the processor")
+                                    .clicked()
+                                {
+                                    jump_to = Some(location.processor());
+                                }
+                            }
+                        });
+                });
+            });
+
+        if let Some(processor) = jump_to {
+            jump_to_processor(processor, layout, &self.positions, view);
+        }
     }
 
     /// Remove any state associated with objects that are no longer present
@@ -173,10 +411,7 @@ impl SoundGraphUiState {
             mapping,
             &self.names,
             *ctx.time_axis(),
-            ctx.properties()
-                .available_inputs(location.processor())
-                .unwrap(),
-            ctx.properties().available_arguments(location).unwrap(),
+            ctx.properties(),
             snapshot_flag,
         );
         let inner_ctx = ExpressionGraphUiContext::new(
@@ -200,6 +435,26 @@ impl SoundGraphUiState {
             &outer_ctx.into(),
             plot_config,
         );
+
+        if let Some(table) = expr_ui_state.take_lookup_table_change() {
+            expr.parts_mut().0.set_lookup_table(table);
+            snapshot_flag.request_snapshot();
+        }
+
+        if let Some(node_id) = expr_ui_state.take_node_to_freeze() {
+            let res = expr_ui_layout.freeze_to_constant(
+                node_id,
+                expr,
+                ctx.jit_cache().inkwell_context(),
+                ctx.factories(),
+                ctx.stash(),
+                expr_ui_state.object_states_mut(),
+            );
+            match res {
+                Ok(()) => snapshot_flag.request_snapshot(),
+                Err(e) => println!("Can't freeze to a constant: {}", e),
+            }
+        }
     }
 }
 
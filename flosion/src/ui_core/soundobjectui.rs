@@ -6,7 +6,7 @@ use crate::core::{objecttype::ObjectType, sound::soundobject::SoundGraphObject};
 
 use super::{
     arguments::{ArgumentList, ParsedArguments},
-    object_ui::ObjectUiState,
+    object_ui::{ObjectUiState, SummonCategory},
     soundgraphuicontext::SoundGraphUiContext,
     soundgraphuistate::SoundGraphUiState,
 };
@@ -30,6 +30,8 @@ pub trait SoundObjectUi: Default {
         ArgumentList::new_empty()
     }
 
+    fn summon_category(&self) -> SummonCategory;
+
     // TODO: remove
     fn make_properties(&self) -> ();
 
@@ -54,6 +56,8 @@ pub trait AnySoundObjectUi {
 
     fn summon_arguments(&self) -> ArgumentList;
 
+    fn summon_category(&self) -> SummonCategory;
+
     fn object_type(&self) -> ObjectType;
 
     // TODO: remove
@@ -93,6 +97,10 @@ impl<T: 'static + SoundObjectUi> AnySoundObjectUi for T {
         T::summon_arguments(self)
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        T::summon_category(self)
+    }
+
     fn object_type(&self) -> ObjectType {
         <T::ObjectType as SoundGraphObject>::get_type()
     }
@@ -16,6 +16,9 @@ use super::{
 struct SoundObjectUiData {
     state: Rc<RefCell<dyn ObjectUiState>>,
     color: egui::Color32,
+    /// Free text chosen by the user to help organize the graph,
+    /// which is empty if the object hasn't been labelled
+    label: String,
 }
 
 pub struct SoundObjectUiStates {
@@ -39,6 +42,7 @@ impl SoundObjectUiStates {
             SoundObjectUiData {
                 state,
                 color: random_object_color(),
+                label: String::new(),
             },
         );
     }
@@ -51,6 +55,18 @@ impl SoundObjectUiStates {
         self.data.get(&id).unwrap().color
     }
 
+    pub(super) fn set_object_color(&mut self, id: SoundObjectId, color: egui::Color32) {
+        self.data.get_mut(&id).unwrap().color = color;
+    }
+
+    pub(super) fn get_object_label(&self, id: SoundObjectId) -> &str {
+        &self.data.get(&id).unwrap().label
+    }
+
+    pub(super) fn set_object_label(&mut self, id: SoundObjectId, label: String) {
+        self.data.get_mut(&id).unwrap().label = label;
+    }
+
     pub(super) fn cleanup(&mut self, graph: &SoundGraph) {
         self.data.retain(|i, _| match i {
             SoundObjectId::Sound(spid) => graph.sound_processors().contains_key(spid),
@@ -95,6 +111,7 @@ impl Stashable for SoundObjectUiStates {
                 stasher.u8(ui_data.color.g());
                 stasher.u8(ui_data.color.b());
                 stasher.u8(ui_data.color.a());
+                stasher.string(&ui_data.label);
             },
             Order::Unordered,
         );
@@ -138,11 +155,14 @@ impl Unstashable<UiUnstashingContext<'_>> for SoundObjectUiStates {
                 unstasher.u8()?,
             );
 
+            let label = unstasher.string()?;
+
             data.insert(
                 proc_id.into(),
                 SoundObjectUiData {
                     state: ui_state,
                     color,
+                    label,
                 },
             );
 
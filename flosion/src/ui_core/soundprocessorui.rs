@@ -230,15 +230,8 @@ impl ProcessorUi {
                 // Show the processor name and also type name if it differs
                 ui.horizontal(|ui| {
                     ui.spacing();
+                    Self::show_name(processor.id(), ui, ctx, ui_state);
                     let name = ui_state.names().sound_processor(processor.id()).unwrap();
-                    ui.add(
-                        egui::Label::new(
-                            egui::RichText::new(name)
-                                .color(egui::Color32::BLACK)
-                                .strong(),
-                        )
-                        .wrap_mode(egui::TextWrapMode::Extend),
-                    );
 
                     if !name.to_lowercase().contains(&self.label.to_lowercase()) {
                         ui.add(
@@ -250,6 +243,20 @@ impl ProcessorUi {
                             .selectable(false),
                         );
                     }
+
+                    let latency = ctx.properties().latency(processor.id());
+                    if latency > 0 {
+                        let ms = latency as f32 * 1000.0 / SAMPLE_FREQUENCY as f32;
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(format!("{:.1} ms", ms))
+                                    .color(egui::Color32::from_black_alpha(192))
+                                    .small(),
+                            )
+                            .selectable(false),
+                        )
+                        .on_hover_text(format!("Latency: {} samples", latency));
+                    }
                 });
 
                 // Add any per-processor custom contents
@@ -286,6 +293,11 @@ impl ProcessorUi {
                         .focus_on_processor(processor.id());
                     ctx.request_snapshot();
                 }
+
+                // Right click to change the color and label
+                bg_response.context_menu(|ui| {
+                    Self::show_appearance_menu(processor.id(), ui, ctx, ui_state);
+                });
             });
         });
 
@@ -314,6 +326,96 @@ impl ProcessorUi {
         }
     }
 
+    /// Show the processor's name, which can be double-clicked to edit it.
+    /// The edited name is applied when the text field loses focus, and is
+    /// made different from every other processor's name if needed.
+    fn show_name(
+        processor_id: SoundProcessorId,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        ui_state: &mut SoundGraphUiState,
+    ) {
+        let edit_id = ui.id().with("editing_name");
+        let editing: Option<String> = ui.data(|d| d.get_temp(edit_id));
+
+        let Some(mut text) = editing else {
+            let name = ui_state.names().sound_processor(processor_id).unwrap();
+            let response = ui
+                .add(
+                    egui::Label::new(
+                        egui::RichText::new(name)
+                            .color(egui::Color32::BLACK)
+                            .strong(),
+                    )
+                    .wrap_mode(egui::TextWrapMode::Extend)
+                    .sense(egui::Sense::click()),
+                )
+                .on_hover_text("Double-click to rename");
+            if response.double_clicked() {
+                let name = name.to_string();
+                ui.data_mut(|d| d.insert_temp(edit_id, name));
+            }
+            return;
+        };
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut text)
+                .desired_width(100.0)
+                .font(egui::TextStyle::Body),
+        );
+        if !response.has_focus() && !response.lost_focus() {
+            response.request_focus();
+        }
+
+        if response.lost_focus() {
+            ui.data_mut(|d| d.remove::<String>(edit_id));
+            let cancelled = ui.input(|i| i.key_pressed(egui::Key::Escape));
+            let names = ui_state.names_mut();
+            if !cancelled && names.sound_processor(processor_id) != Some(text.trim()) {
+                names.rename_sound_processor(processor_id, &text);
+                ctx.request_snapshot();
+            }
+        } else {
+            ui.data_mut(|d| d.insert_temp(edit_id, text));
+        }
+    }
+
+    /// Show controls for changing the color and label of the processor,
+    /// or of its entire stacked group at once
+    fn show_appearance_menu(
+        processor_id: SoundProcessorId,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        ui_state: &mut SoundGraphUiState,
+    ) {
+        let states = ui_state.object_states_mut();
+        let mut color = states.get_object_color(processor_id.into());
+        let mut label = states.get_object_label(processor_id.into()).to_string();
+
+        ui.horizontal(|ui| {
+            ui.label("Color");
+            if ui.color_edit_button_srgba(&mut color).changed() {
+                states.set_object_color(processor_id.into(), color);
+                ctx.request_snapshot();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Label");
+            if ui.text_edit_singleline(&mut label).changed() {
+                states.set_object_label(processor_id.into(), label.clone());
+                ctx.request_snapshot();
+            }
+        });
+        if ui.button("Apply to whole group").clicked() {
+            for spid in ctx.group_processors() {
+                states.set_object_color((*spid).into(), color);
+                states.set_object_label((*spid).into(), label.clone());
+            }
+            ctx.request_snapshot();
+            ui.close_menu();
+        }
+    }
+
     fn show_expression(
         processor: &mut dyn AnySoundProcessor,
         ui: &mut egui::Ui,
@@ -357,6 +459,12 @@ impl ProcessorUi {
                     ui,
                     ctx.snapshot_flag(),
                 );
+                if let Some(error) = ctx
+                    .jit_cache()
+                    .compilation_error(expr.graph(), expr.mapping())
+                {
+                    ui.colored_label(egui::Color32::RED, error.explain());
+                }
             });
         });
 
@@ -0,0 +1,122 @@
+use eframe::egui;
+
+/// How close two edges must be, in pixels, for one to snap to the other
+const SNAP_DISTANCE: f32 = 8.0;
+
+/// A line along which the edges of two or more groups are aligned
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum AlignmentGuide {
+    /// A vertical line at the given x coordinate
+    Vertical(f32),
+
+    /// A horizontal line at the given y coordinate
+    Horizontal(f32),
+}
+
+impl AlignmentGuide {
+    pub(crate) fn draw(&self, ui: &egui::Ui) {
+        let area = ui.clip_rect();
+        let stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(0, 192, 255));
+        match self {
+            AlignmentGuide::Vertical(x) => ui.painter().vline(*x, area.y_range(), stroke),
+            AlignmentGuide::Horizontal(y) => ui.painter().hline(area.x_range(), *y, stroke),
+        };
+    }
+}
+
+/// Find the shortest distance to move one of the given edges by such that
+/// it lines up with one of the other edges, if any are close enough
+fn nearest_snap(edges: [f32; 2], other_edges: &[f32]) -> Option<f32> {
+    let mut best: Option<f32> = None;
+    for edge in edges {
+        for other_edge in other_edges {
+            let distance = other_edge - edge;
+            if distance.abs() <= SNAP_DISTANCE && best.is_none_or(|b| distance.abs() < b.abs()) {
+                best = Some(distance);
+            }
+        }
+    }
+    best
+}
+
+/// Find how far the given rect should be moved such that its edges snap to
+/// any nearby edges of the other rects, along with the guides showing
+/// which edges are aligned once it has been moved
+pub(crate) fn snap_to_alignment(
+    rect: egui::Rect,
+    others: &[egui::Rect],
+) -> (egui::Vec2, Vec<AlignmentGuide>) {
+    let other_xs: Vec<f32> = others.iter().flat_map(|r| [r.left(), r.right()]).collect();
+    let other_ys: Vec<f32> = others.iter().flat_map(|r| [r.top(), r.bottom()]).collect();
+
+    let offset = egui::vec2(
+        nearest_snap([rect.left(), rect.right()], &other_xs).unwrap_or(0.0),
+        nearest_snap([rect.top(), rect.bottom()], &other_ys).unwrap_or(0.0),
+    );
+    let snapped_rect = rect.translate(offset);
+
+    let is_aligned = |a: f32, b: f32| (a - b).abs() < 0.5;
+
+    let mut guides = Vec::new();
+    for x in [snapped_rect.left(), snapped_rect.right()] {
+        if other_xs.iter().any(|other_x| is_aligned(x, *other_x)) {
+            guides.push(AlignmentGuide::Vertical(x));
+        }
+    }
+    for y in [snapped_rect.top(), snapped_rect.bottom()] {
+        if other_ys.iter().any(|other_y| is_aligned(y, *other_y)) {
+            guides.push(AlignmentGuide::Horizontal(y));
+        }
+    }
+
+    (offset, guides)
+}
+
+/// A way of arranging rects, which finds how far each rect must move
+pub(crate) type Arrangement = fn(&[egui::Rect]) -> Vec<egui::Vec2>;
+
+/// Find how far each rect must move for all of their left edges to line
+/// up with the leftmost one
+pub(crate) fn align_left(rects: &[egui::Rect]) -> Vec<egui::Vec2> {
+    let left = rects.iter().map(|r| r.left()).fold(f32::INFINITY, f32::min);
+    rects
+        .iter()
+        .map(|r| egui::vec2(left - r.left(), 0.0))
+        .collect()
+}
+
+/// Find how far each rect must move for all of their top edges to line
+/// up with the topmost one
+pub(crate) fn align_top(rects: &[egui::Rect]) -> Vec<egui::Vec2> {
+    let top = rects.iter().map(|r| r.top()).fold(f32::INFINITY, f32::min);
+    rects
+        .iter()
+        .map(|r| egui::vec2(0.0, top - r.top()))
+        .collect()
+}
+
+/// Find how far each rect must move horizontally such that the gaps between
+/// neighbouring rects are all equal. The leftmost and rightmost rects stay
+/// where they are, and the order of the rects from left to right is kept.
+pub(crate) fn distribute_horizontally(rects: &[egui::Rect]) -> Vec<egui::Vec2> {
+    let mut offsets = vec![egui::Vec2::ZERO; rects.len()];
+    if rects.len() < 3 {
+        return offsets;
+    }
+
+    let mut order: Vec<usize> = (0..rects.len()).collect();
+    order.sort_by(|a, b| rects[*a].left().total_cmp(&rects[*b].left()));
+
+    let first = rects[order[0]];
+    let last = rects[*order.last().unwrap()];
+    let total_width: f32 = rects.iter().map(|r| r.width()).sum();
+    let gap = (last.right() - first.left() - total_width) / (rects.len() - 1) as f32;
+
+    let mut left = first.left();
+    for i in order {
+        offsets[i] = egui::vec2(left - rects[i].left(), 0.0);
+        left += rects[i].width() + gap;
+    }
+
+    offsets
+}
@@ -1,3 +1,4 @@
+pub mod alignment;
 pub mod interconnect;
 pub mod stackedgroup;
 pub mod stackedlayout;
@@ -11,15 +11,22 @@ use crate::{
         engine::soundenginereport::SoundEngineReport,
         jit::cache::JitCache,
         sound::{
-            soundgraph::SoundGraph, soundinput::SoundInputCategory, soundobject::SoundGraphObject,
+            soundgraph::SoundGraph,
+            soundinput::{DisconnectBehavior, SoundInputCategory},
+            soundobject::SoundGraphObject,
             soundprocessor::SoundProcessorId,
         },
     },
     ui_core::{
-        factories::Factories, graph_properties::GraphProperties, history::SnapshotFlag,
-        interactions::draganddrop::DragDropSubject, soundgraphuicontext::SoundGraphUiContext,
-        soundgraphuistate::SoundGraphUiState, soundobjectpositions::SoundObjectPositions,
-        soundobjectui::show_sound_object_ui, stackedlayout::stackedlayout::StackedLayout,
+        factories::Factories,
+        graph_properties::GraphProperties,
+        history::SnapshotFlag,
+        interactions::draganddrop::DragDropSubject,
+        soundgraphuicontext::{SoundGraphUiContext, StackedGroupView},
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectpositions::SoundObjectPositions,
+        soundobjectui::show_sound_object_ui,
+        stackedlayout::stackedlayout::StackedLayout,
     },
 };
 
@@ -82,10 +89,26 @@ impl StackedGroup {
         self.time_axis
     }
 
+    #[cfg(test)]
+    pub(crate) fn origin(&self) -> egui::Pos2 {
+        self.origin
+    }
+
     pub(crate) fn translate(&mut self, delta: egui::Vec2) {
         self.origin = self.origin + delta;
     }
 
+    /// The area covered by the group's processors when they were last
+    /// drawn, if all of them have been drawn
+    pub(crate) fn rect(&self, positions: &SoundObjectPositions) -> Option<egui::Rect> {
+        let mut rect: Option<egui::Rect> = None;
+        for spid in &self.processors {
+            let outer_rect = positions.find_processor(*spid)?.outer_rect;
+            rect = Some(rect.map_or(outer_rect, |r| r.union(outer_rect)));
+        }
+        rect
+    }
+
     pub(crate) fn processors(&self) -> &[SoundProcessorId] {
         &self.processors
     }
@@ -128,6 +151,18 @@ impl StackedGroup {
         self.processors.retain(|i| *i != processor);
     }
 
+    pub(crate) fn replace_processor(
+        &mut self,
+        old_processor: SoundProcessorId,
+        new_processor: SoundProcessorId,
+    ) {
+        for p in &mut self.processors {
+            if *p == old_processor {
+                *p = new_processor;
+            }
+        }
+    }
+
     pub(crate) fn split_off_processor_and_everything_below(
         &mut self,
         processor: SoundProcessorId,
@@ -202,16 +237,18 @@ impl StackedGroup {
                         self.draw_barrier(ui);
                     } else {
                         for input_loc in inputs {
-                            let (input_socket, target) = processor_data
+                            let (input_socket, target, speed, disconnect_behavior) = processor_data
                                 .with_input(input_loc.input(), |input| {
                                     (
                                         InputSocket::from_input_data(input_loc.processor(), input),
                                         input.target(),
+                                        input.speed(),
+                                        input.disconnect_behavior(),
                                     )
                                 })
                                 .unwrap();
                             let top_of_stack = spid == *self.processors.first().unwrap();
-                            self.draw_input_socket(
+                            let socket_response = self.draw_input_socket(
                                 ui,
                                 ui_state,
                                 target,
@@ -219,6 +256,36 @@ impl StackedGroup {
                                 processor_color,
                                 top_of_stack,
                             );
+                            // Inputs whose branches don't match what they're
+                            // connected to are highlighted, with an explanation
+                            let socket_response = match properties.branch_count_mismatch(input_loc)
+                            {
+                                Some(explanation) => {
+                                    ui.painter().rect_filled(
+                                        socket_response.rect,
+                                        egui::Rounding::ZERO,
+                                        egui::Color32::from_rgba_unmultiplied(255, 0, 0, 64),
+                                    );
+                                    socket_response.on_hover_text(explanation)
+                                }
+                                None => socket_response,
+                            };
+                            let (new_speed, new_disconnect_behavior) = self.input_options_ui(
+                                ui,
+                                &socket_response,
+                                speed,
+                                disconnect_behavior,
+                            );
+                            if let Some(new_speed) = new_speed {
+                                processor_data.with_input_mut(input_loc.input(), |input| {
+                                    input.set_speed(new_speed);
+                                });
+                            }
+                            if let Some(behavior) = new_disconnect_behavior {
+                                processor_data.with_input_mut(input_loc.input(), |input| {
+                                    input.set_disconnect_behavior(behavior);
+                                });
+                            }
                         }
                     }
 
@@ -226,8 +293,11 @@ impl StackedGroup {
                     let object: &mut dyn SoundGraphObject = processor_data.as_graph_object_mut();
                     let ctx = SoundGraphUiContext::new(
                         factories,
-                        self.time_axis,
-                        self.width_pixels as f32,
+                        StackedGroupView {
+                            time_axis: self.time_axis,
+                            width: self.width_pixels as f32,
+                            processors: &self.processors,
+                        },
                         properties,
                         jit_cache,
                         stash,
@@ -269,9 +339,55 @@ impl StackedGroup {
             ui_state
                 .positions_mut()
                 .record_processor(spid, body_rect, outer_rect);
+
+            // Show the processor's label beside it, unless the whole
+            // group shares it, in which case it's shown once above
+            let label = ui_state.object_states().get_object_label(spid.into());
+            if !label.is_empty() && self.shared_label(ui_state).is_none() {
+                self.draw_bubbled_text(
+                    label.to_string(),
+                    outer_rect.right_top() + egui::vec2(5.0, 15.0),
+                    ui,
+                );
+            }
+
+            // Outline soloed processors
+            if graph.is_soloed(spid) {
+                ui.painter().rect_stroke(
+                    outer_rect,
+                    egui::Rounding::ZERO,
+                    egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                );
+            }
+        }
+
+        if let Some(label) = self.shared_label(ui_state) {
+            let top_rect = ui_state
+                .positions()
+                .find_processor(self.processors[0])
+                .unwrap()
+                .outer_rect;
+            self.draw_bubbled_text(
+                label.to_string(),
+                top_rect.left_top() - egui::vec2(0.0, 5.0),
+                ui,
+            );
         }
     }
 
+    /// The label given to every processor in the group, if they all
+    /// have the same non-empty label
+    fn shared_label<'a>(&self, ui_state: &'a SoundGraphUiState) -> Option<&'a str> {
+        let states = ui_state.object_states();
+        let label = states.get_object_label(self.processors[0].into());
+        let shared = !label.is_empty()
+            && self
+                .processors
+                .iter()
+                .all(|spid| states.get_object_label((*spid).into()) == label);
+        shared.then_some(label)
+    }
+
     fn draw_input_socket(
         &self,
         ui: &mut egui::Ui,
@@ -280,7 +396,7 @@ impl StackedGroup {
         socket: InputSocket,
         color: egui::Color32,
         top_of_stack: bool,
-    ) {
+    ) -> egui::Response {
         if top_of_stack {
             // If the input is at the top of the stack, draw an extra field
             // to hold end of a jumper cable to the target processor, if any
@@ -336,6 +452,63 @@ impl StackedGroup {
                 self.draw_bubbled_text("TODO: ???".to_string(), bar_rect.center(), ui)
             }
         }
+
+        response
+    }
+
+    /// Show the input's speed on its socket if it isn't the default,
+    /// and allow it and the input's disconnect behavior to be edited
+    /// from the socket's context menu. Returns whichever of the two
+    /// were changed.
+    fn input_options_ui(
+        &self,
+        ui: &mut egui::Ui,
+        socket_response: &egui::Response,
+        speed: f32,
+        disconnect_behavior: DisconnectBehavior,
+    ) -> (Option<f32>, Option<DisconnectBehavior>) {
+        if speed != 1.0 {
+            self.draw_bubbled_text(
+                format!("{:.2}x", speed),
+                socket_response.rect.right_center() - egui::vec2(40.0, 0.0),
+                ui,
+            );
+        }
+
+        let mut new_speed = None;
+        let mut new_disconnect_behavior = None;
+        socket_response.context_menu(|ui| {
+            let mut s = speed;
+            ui.horizontal(|ui| {
+                ui.label("Speed");
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut s)
+                            .range(0.01..=16.0)
+                            .speed(0.01)
+                            .suffix("x"),
+                    )
+                    .changed()
+                {
+                    new_speed = Some(s);
+                }
+            });
+            if ui.button("Reset speed").clicked() {
+                new_speed = Some(1.0);
+                ui.close_menu();
+            }
+            let mut hold = disconnect_behavior == DisconnectBehavior::HoldAndFade;
+            if ui
+                .checkbox(&mut hold, "Fade out when disconnected")
+                .changed()
+            {
+                new_disconnect_behavior = Some(match hold {
+                    true => DisconnectBehavior::HoldAndFade,
+                    false => DisconnectBehavior::Silence,
+                });
+            }
+        });
+        (new_speed, new_disconnect_behavior)
     }
 
     fn draw_processor_plug(
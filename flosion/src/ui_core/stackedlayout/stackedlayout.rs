@@ -44,6 +44,13 @@ impl StackedLayout {
         &self.groups
     }
 
+    /// Move every group by the same amount, as if panning the view
+    pub(crate) fn translate(&mut self, delta: egui::Vec2) {
+        for group in &mut self.groups {
+            group.translate(delta);
+        }
+    }
+
     /// Find the stacked group that a sound processor belongs to, if any.
     pub(crate) fn find_group(&self, id: SoundProcessorId) -> Option<&StackedGroup> {
         for g in &self.groups {
@@ -208,7 +215,7 @@ impl StackedLayout {
                 if self.is_bottom_of_group(target) {
                     let existing_group = self.find_group_mut(target).unwrap();
                     existing_group.insert_processor_at_bottom(*spid);
-                    added_processor = Some(target);
+                    added_processor = Some(*spid);
                     break;
                 }
             }
@@ -476,6 +483,18 @@ impl StackedLayout {
         });
     }
 
+    /// Put a new processor in the place of an existing one, e.g.
+    /// after the latter was replaced in the graph
+    pub(crate) fn replace_processor(
+        &mut self,
+        old_processor: SoundProcessorId,
+        new_processor: SoundProcessorId,
+    ) {
+        if let Some(group) = self.find_group_mut(old_processor) {
+            group.replace_processor(old_processor, new_processor);
+        }
+    }
+
     pub(crate) fn insert_processor_above(
         &mut self,
         processor_to_insert: SoundProcessorId,
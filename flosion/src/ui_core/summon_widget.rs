@@ -2,36 +2,189 @@ use std::cmp::Ordering;
 
 use eframe::egui;
 
-use super::arguments::{ArgumentList, ParsedArguments};
+use crate::core::objecttype::ObjectType;
+
+use super::{
+    arguments::{ArgumentList, ParsedArguments},
+    object_ui::SummonCategory,
+};
+
+/// The greatest number of recently-summoned object types to remember
+const MAX_RECENTS: usize = 8;
+
+/// The object types which were most recently summoned and those which
+/// were starred as favorites, which are shown at the top of the summon
+/// widget. Object types are remembered by name so that the history can
+/// be saved independently of any factories.
+#[derive(Clone)]
+pub(crate) struct SummonHistory {
+    /// Names of recently-summoned object types, most recent first
+    recents: Vec<String>,
+
+    /// Names of favorite object types, in the order they were starred
+    favorites: Vec<String>,
+}
+
+impl SummonHistory {
+    pub(crate) fn new() -> SummonHistory {
+        SummonHistory {
+            recents: Vec::new(),
+            favorites: Vec::new(),
+        }
+    }
+
+    /// Move the object type to the front of the recents, forgetting
+    /// the oldest one if there are too many
+    pub(crate) fn record_summoned(&mut self, object_type: ObjectType) {
+        self.recents.retain(|name| name != object_type.name());
+        self.recents.insert(0, object_type.name().to_string());
+        self.recents.truncate(MAX_RECENTS);
+    }
+
+    /// How many other object types were summoned since the given one,
+    /// if it was summoned recently
+    pub(crate) fn recency(&self, object_type: ObjectType) -> Option<usize> {
+        self.recents
+            .iter()
+            .position(|name| name == object_type.name())
+    }
+
+    pub(crate) fn is_favorite(&self, object_type: ObjectType) -> bool {
+        self.favorites.iter().any(|name| name == object_type.name())
+    }
+
+    pub(crate) fn toggle_favorite(&mut self, object_type: ObjectType) {
+        if self.is_favorite(object_type) {
+            self.favorites.retain(|name| name != object_type.name());
+        } else {
+            self.favorites.push(object_type.name().to_string());
+        }
+    }
+}
+
+/// The summon histories of sound objects and of expression nodes. These
+/// are kept across undo and redo and are saved between sessions in the
+/// app's storage rather than in the undo history.
+pub(crate) struct SummonHistories {
+    pub(crate) sound: SummonHistory,
+    pub(crate) expression: SummonHistory,
+}
+
+impl SummonHistories {
+    pub(crate) fn new() -> SummonHistories {
+        SummonHistories {
+            sound: SummonHistory::new(),
+            expression: SummonHistory::new(),
+        }
+    }
+
+    /// Load the histories saved in the given storage, missing ones being empty
+    pub(crate) fn load(storage: &dyn eframe::Storage) -> SummonHistories {
+        SummonHistories {
+            sound: SummonHistory::load(storage, "sound_summon"),
+            expression: SummonHistory::load(storage, "expression_summon"),
+        }
+    }
+
+    pub(crate) fn save(&self, storage: &mut dyn eframe::Storage) {
+        self.sound.save(storage, "sound_summon");
+        self.expression.save(storage, "expression_summon");
+    }
+}
+
+impl SummonHistory {
+    /// Read a list of names saved under the given key, one per line
+    fn load_names(storage: &dyn eframe::Storage, key: &str) -> Vec<String> {
+        let Some(names) = storage.get_string(key) else {
+            return Vec::new();
+        };
+        names
+            .lines()
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn load(storage: &dyn eframe::Storage, prefix: &str) -> SummonHistory {
+        let mut recents = Self::load_names(storage, &format!("{}_recents", prefix));
+        recents.truncate(MAX_RECENTS);
+        SummonHistory {
+            recents,
+            favorites: Self::load_names(storage, &format!("{}_favorites", prefix)),
+        }
+    }
+
+    fn save(&self, storage: &mut dyn eframe::Storage, prefix: &str) {
+        storage.set_string(&format!("{}_recents", prefix), self.recents.join("\n"));
+        storage.set_string(&format!("{}_favorites", prefix), self.favorites.join("\n"));
+    }
+}
 
-fn score_match(query: &str, content: &str) -> f32 {
+/// Score how well a typed query matches a name, where higher is better.
+/// The characters of the query are matched against the name in order,
+/// but not necessarily contiguously, such that abbreviations like "mul"
+/// for "multiply" score highly. Matches at the start of the name and
+/// runs of consecutive matches are favoured, while query characters which
+/// can't be matched and extra characters in the name are penalized
+/// without excluding the name altogether, so that e.g. "lpf" still
+/// surfaces "lowpass".
+pub(super) fn score_match(query: &str, name: &str) -> f32 {
+    let name: Vec<char> = name.chars().map(|c| c.to_ascii_lowercase()).collect();
     let mut score: f32 = 0.0;
-    let mut qi = query.chars();
-    let mut qc = qi.next();
-    let mut first = true;
-    for cc in content.chars() {
-        let cc = cc.to_ascii_lowercase();
-        if let Some(c) = qc {
-            let c = c.to_ascii_lowercase();
-            if cc == c {
-                score += if first { 2.0 } else { 1.0 };
-                qc = qi.next();
-            } else {
-                score -= 0.2;
-                first = false;
+    let mut num_matched = 0;
+    // Index into the name just past the previous match
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+    for qc in query.chars() {
+        let qc = qc.to_ascii_lowercase();
+        match name[position..].iter().position(|c| *c == qc) {
+            Some(offset) => {
+                let i = position + offset;
+                score += 1.0;
+                if i == 0 {
+                    score += 2.0;
+                }
+                if previous_match.is_some_and(|p| p + 1 == i) {
+                    score += 1.0;
+                }
+                num_matched += 1;
+                previous_match = Some(i);
+                position = i + 1;
+            }
+            None => {
+                score -= 1.5;
             }
-        } else {
-            score -= 0.1;
-            first = false;
         }
     }
+    // Prefer shorter names among equally good matches
+    score -= 0.05 * (name.len() - num_matched) as f32;
     score
 }
 
+/// Text typed into the summon widget, split into the name being searched
+/// for and any trailing terms, which are parsed as arguments, such that
+/// e.g. "mixer 4" summons a mixer with four inputs
+struct SummonPrompt<'a> {
+    text: &'a str,
+    name: &'a str,
+    argument_terms: Vec<String>,
+}
+
+impl<'a> SummonPrompt<'a> {
+    fn new(text: &'a str) -> SummonPrompt<'a> {
+        let mut terms = text.split_whitespace();
+        SummonPrompt {
+            text,
+            name: terms.next().unwrap_or(""),
+            argument_terms: terms.map(str::to_string).collect(),
+        }
+    }
+}
+
 enum SummonRule<T> {
     BasicName(String, T),
     Pattern(String, fn(&str) -> Option<T>),
-    NameWithArguments(String, ArgumentList, T),
+    NameWithArguments(String, ArgumentList, T, SummonCategory),
 }
 
 impl<T: Copy> SummonRule<T> {
@@ -39,40 +192,86 @@ impl<T: Copy> SummonRule<T> {
         match self {
             SummonRule::BasicName(name, _) => name,
             SummonRule::Pattern(name, _) => name,
-            SummonRule::NameWithArguments(name, _, _) => name,
+            SummonRule::NameWithArguments(name, _, _, _) => name,
+        }
+    }
+
+    fn category(&self) -> Option<SummonCategory> {
+        match self {
+            SummonRule::BasicName(_, _) => None,
+            SummonRule::Pattern(_, _) => None,
+            SummonRule::NameWithArguments(_, _, _, category) => Some(*category),
         }
     }
 
-    fn evaluate(&self, prompt: &str) -> Option<(T, ParsedArguments)> {
+    /// The value which is always summoned by the rule, if it doesn't
+    /// depend on what was typed
+    fn fixed_value(&self) -> Option<T> {
+        match self {
+            SummonRule::BasicName(_, value) => Some(*value),
+            SummonRule::Pattern(_, _) => None,
+            SummonRule::NameWithArguments(_, _, value, _) => Some(*value),
+        }
+    }
+
+    fn evaluate(&self, prompt: &SummonPrompt) -> Option<(T, ParsedArguments)> {
         match self {
             SummonRule::BasicName(_, value) => Some((*value, ParsedArguments::new_empty())),
-            SummonRule::Pattern(_, f) => {
-                f(prompt).and_then(|v| Some((v, ParsedArguments::new_empty())))
-            }
-            Self::NameWithArguments(_, args, value) => {
-                let terms: Vec<String> = prompt
-                    .split_whitespace()
-                    .skip(1)
-                    .map(str::to_string)
-                    .collect();
-                Some((*value, args.parse(terms)))
+            SummonRule::Pattern(_, f) => f(prompt.text).map(|v| (v, ParsedArguments::new_empty())),
+            Self::NameWithArguments(_, args, value, _) => {
+                Some((*value, args.parse(prompt.argument_terms.clone())))
             }
         }
     }
 }
+
 struct ScoredRule<T> {
     rule: SummonRule<T>,
     score: f32,
     value_and_args: Option<(T, ParsedArguments)>,
+    /// The object type summoned by the rule, if any, which can be starred
+    object_type: Option<ObjectType>,
+    favorite: bool,
+    recency: Option<usize>,
+    /// Where the rule is pinned among favorites and recents at the top
+    /// of the list, which only happens while nothing has been typed
+    pinned_rank: Option<usize>,
+    /// The category under which the rule is listed, if any, which only
+    /// happens while nothing has been typed and the rule isn't pinned
+    group: Option<SummonCategory>,
 }
 
 impl<T: Copy> ScoredRule<T> {
-    fn update(&mut self, prompt: &str) {
+    fn update(&mut self, prompt: &SummonPrompt) {
         self.value_and_args = self.rule.evaluate(prompt);
+        let name_query = prompt.name;
         self.score = match &self.rule {
-            SummonRule::BasicName(name, _) => score_match(prompt, name),
+            SummonRule::BasicName(name, _) => score_match(name_query, name),
             SummonRule::Pattern(_, _) => 0.0,
-            SummonRule::NameWithArguments(name, _, _) => score_match(prompt, name),
+            SummonRule::NameWithArguments(name, _, _, _) => score_match(name_query, name),
+        };
+        self.pinned_rank = if name_query.is_empty() {
+            self.history_rank()
+        } else {
+            None
+        };
+        self.update_group(name_query.is_empty());
+    }
+
+    fn update_group(&mut self, grouping: bool) {
+        self.group = if grouping && self.pinned_rank.is_none() {
+            self.rule.category()
+        } else {
+            None
+        };
+    }
+
+    /// Favorites come first, followed by recents from newest to oldest
+    fn history_rank(&self) -> Option<usize> {
+        if self.favorite {
+            Some(0)
+        } else {
+            self.recency.map(|i| i + 1)
         }
     }
 }
@@ -105,7 +304,7 @@ impl<T: Copy> Ord for ScoredRule<T> {
                         2
                     }
                 }
-                SummonRule::NameWithArguments(_, _, _) => 1,
+                SummonRule::NameWithArguments(_, _, _, _) => 1,
             }
         };
 
@@ -114,6 +313,21 @@ impl<T: Copy> Ord for ScoredRule<T> {
             cmp => return cmp,
         };
 
+        // pinned rules come first in order of their rank
+        match (self.pinned_rank, other.pinned_rank) {
+            (Some(a), Some(b)) if a != b => return a.cmp(&b),
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            _ => (),
+        };
+
+        // uncategorized rules come before categories, which are
+        // listed in order
+        match self.group.cmp(&other.group) {
+            Ordering::Equal => (),
+            cmp => return cmp,
+        };
+
         // higher scores come before higher scores
         match self
             .score
@@ -134,13 +348,24 @@ pub(super) struct SummonWidgetState<T> {
     finalized: bool,
     current_choice: Option<(T, ParsedArguments)>,
     rules: Vec<ScoredRule<T>>,
+    /// Whether rules which don't match the typed text are hidden,
+    /// rather than merely being listed after those which do
+    filtering: bool,
+    /// The number of rules at the front of `rules` which are listed
+    num_listed: usize,
     focus_index: Option<usize>,
     just_opened: bool,
+    toggled_favorite: Option<ObjectType>,
 }
 
+/// Finds the object type that a summoned value would create, if any
+type ObjectTypeOf<T> = fn(&T) -> Option<ObjectType>;
+
 pub(super) struct SummonWidgetStateBuilder<T> {
     position: egui::Pos2,
     rules: Vec<SummonRule<T>>,
+    history: Option<(SummonHistory, ObjectTypeOf<T>)>,
+    filtering: bool,
 }
 
 impl<T: Copy> SummonWidgetStateBuilder<T> {
@@ -148,9 +373,31 @@ impl<T: Copy> SummonWidgetStateBuilder<T> {
         SummonWidgetStateBuilder {
             position,
             rules: Vec::new(),
+            history: None,
+            filtering: false,
         }
     }
 
+    /// Hide choices which don't match the typed text at all. This is
+    /// intended for lists of basic names, which are sorted purely by
+    /// how well they match.
+    pub(super) fn with_filtering(&mut self) -> &mut Self {
+        self.filtering = true;
+        self
+    }
+
+    /// Show favorite and recently-summoned object types at the top,
+    /// where `object_type_of` finds the object type that a value
+    /// would summon, if any
+    pub(super) fn with_history(
+        &mut self,
+        history: &SummonHistory,
+        object_type_of: ObjectTypeOf<T>,
+    ) -> &mut Self {
+        self.history = Some((history.clone(), object_type_of));
+        self
+    }
+
     pub(super) fn add_basic_name(&mut self, name: String, value: T) -> &mut Self {
         self.rules.push(SummonRule::BasicName(name, value));
         self
@@ -166,34 +413,57 @@ impl<T: Copy> SummonWidgetStateBuilder<T> {
         name: String,
         arguments: ArgumentList,
         value: T,
+        category: SummonCategory,
     ) -> &mut Self {
-        self.rules
-            .push(SummonRule::NameWithArguments(name, arguments, value));
+        self.rules.push(SummonRule::NameWithArguments(
+            name, arguments, value, category,
+        ));
         self
     }
 
     pub(super) fn build(self) -> SummonWidgetState<T> {
+        let history = self.history;
+        let empty_prompt = SummonPrompt::new("");
         let mut rules: Vec<ScoredRule<T>> = self
             .rules
             .into_iter()
             .map(|rule| {
-                let value_and_args = rule.evaluate("");
-                ScoredRule {
+                let object_type = history.as_ref().and_then(|(_, object_type_of)| {
+                    rule.fixed_value().and_then(|v| object_type_of(&v))
+                });
+                let (favorite, recency) = match (&history, object_type) {
+                    (Some((history, _)), Some(t)) => (history.is_favorite(t), history.recency(t)),
+                    _ => (false, None),
+                };
+                let value_and_args = rule.evaluate(&empty_prompt);
+                let mut scored_rule = ScoredRule {
                     rule,
                     score: 0.0,
                     value_and_args,
-                }
+                    object_type,
+                    favorite,
+                    recency,
+                    pinned_rank: None,
+                    group: None,
+                };
+                scored_rule.pinned_rank = scored_rule.history_rank();
+                scored_rule.update_group(true);
+                scored_rule
             })
             .collect();
         rules.sort();
+        let num_listed = rules.len();
         SummonWidgetState {
             position: self.position,
             text: String::new(),
             finalized: false,
             current_choice: None,
             rules,
+            filtering: self.filtering,
+            num_listed,
             focus_index: None,
             just_opened: true,
+            toggled_favorite: None,
         }
     }
 }
@@ -207,6 +477,60 @@ impl<T: Copy> SummonWidgetState<T> {
         }
     }
 
+    /// The value and arguments of the best match for the current text
+    pub(super) fn best_choice(&self) -> Option<(T, ParsedArguments)> {
+        self.listed_rules()
+            .first()
+            .and_then(|rule| rule.value_and_args.clone())
+    }
+
+    /// The rules which are currently listed, in order
+    fn listed_rules(&self) -> &[ScoredRule<T>] {
+        &self.rules[..self.num_listed]
+    }
+
+    /// The names of all listed choices, in order, along with the
+    /// category each is grouped under, if any
+    #[cfg(test)]
+    pub(super) fn listed_groups(&self) -> Vec<(Option<SummonCategory>, Vec<&str>)> {
+        let mut groups: Vec<(Option<SummonCategory>, Vec<&str>)> = Vec::new();
+        for rule in self.listed_rules() {
+            match groups.last_mut() {
+                Some((group, names)) if *group == rule.group => {
+                    names.push(rule.rule.display_name())
+                }
+                _ => groups.push((rule.group, vec![rule.rule.display_name()])),
+            }
+        }
+        groups
+    }
+
+    /// The object type whose star was clicked, if any, which is
+    /// forgotten once taken
+    pub(super) fn take_toggled_favorite(&mut self) -> Option<ObjectType> {
+        self.toggled_favorite.take()
+    }
+
+    /// Star or unstar all choices which summon the given object type
+    fn toggle_favorite(&mut self, object_type: ObjectType) {
+        for rule in &mut self.rules {
+            if rule.object_type == Some(object_type) {
+                rule.favorite = !rule.favorite;
+            }
+        }
+        self.toggled_favorite = Some(object_type);
+        self.update_matches();
+    }
+
+    /// The value and arguments of the entry highlighted using the arrow
+    /// keys, or else of the best match for the current text
+    fn highlighted_choice(&self) -> Option<(T, ParsedArguments)> {
+        match self.focus_index {
+            Some(i) => self.rules[i].value_and_args.clone(),
+            None => self.best_choice(),
+        }
+    }
+
     pub(super) fn was_cancelled(&self) -> bool {
         self.finalized && self.current_choice.is_none()
     }
@@ -217,10 +541,23 @@ impl<T: Copy> SummonWidgetState<T> {
     }
 
     fn update_matches(&mut self) {
+        let prompt = SummonPrompt::new(&self.text);
         for rule in &mut self.rules {
-            rule.update(&self.text);
+            rule.update(&prompt);
         }
         self.rules.sort();
+        self.num_listed = if self.filtering && !prompt.name.is_empty() {
+            // Matching rules are sorted first, so they're all listed together
+            self.rules
+                .iter()
+                .take_while(|rule| rule.score > 0.0)
+                .count()
+        } else {
+            self.rules.len()
+        };
+        if self.focus_index.is_some_and(|i| i >= self.num_listed) {
+            self.focus_index = None;
+        }
     }
 
     pub(super) fn position(&self) -> egui::Pos2 {
@@ -228,6 +565,79 @@ impl<T: Copy> SummonWidgetState<T> {
     }
 }
 
+/// What happened to a single choice listed in the summon widget
+struct RuleRowResponse {
+    response: egui::Response,
+    star_clicked: bool,
+}
+
+/// Show a single choice, its arguments, and a star to click if it
+/// summons an object type which can be a favorite
+fn show_rule_row<T: Copy>(
+    ui: &mut egui::Ui,
+    scored_rule: &ScoredRule<T>,
+    highlighted: bool,
+) -> RuleRowResponse {
+    let mut layout_job = egui::text::LayoutJob::default();
+    layout_job.append(
+        scored_rule.rule.display_name(),
+        0.0,
+        egui::TextFormat {
+            color: egui::Color32::WHITE,
+            background: if highlighted {
+                egui::Color32::from_white_alpha(48)
+            } else {
+                egui::Color32::TRANSPARENT
+            },
+            ..Default::default()
+        },
+    );
+
+    if let SummonRule::NameWithArguments(_, args, _, _) = &scored_rule.rule {
+        let parsed_args = scored_rule.value_and_args.as_ref().map(|(_, a)| a);
+        for arg in args.arguments() {
+            // Arguments which were typed inline are shown brighter
+            let was_typed = parsed_args.is_some_and(|a| a.contains(arg.name()));
+            layout_job.append(
+                arg.name(),
+                5.0,
+                egui::TextFormat {
+                    color: if was_typed {
+                        egui::Color32::GREEN
+                    } else {
+                        egui::Color32::DARK_GREEN
+                    },
+                    italics: true,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    ui.horizontal(|ui| {
+        let mut star_clicked = false;
+        if scored_rule.object_type.is_some() {
+            let (star, color) = if scored_rule.favorite {
+                ("★", egui::Color32::YELLOW)
+            } else {
+                ("☆", egui::Color32::DARK_GRAY)
+            };
+            star_clicked = ui
+                .add(
+                    egui::Label::new(egui::RichText::new(star).color(color))
+                        .sense(egui::Sense::click()),
+                )
+                .clicked();
+        }
+        let response = ui.add(egui::Label::new(layout_job).sense(egui::Sense::click()));
+        RuleRowResponse {
+            response,
+            star_clicked,
+        }
+    })
+    .inner
+}
+
 pub(super) struct SummonWidget<'a, T> {
     state: &'a mut SummonWidgetState<T>,
 }
@@ -260,7 +670,7 @@ impl<'a, T: Copy> egui::Widget for SummonWidget<'a, T> {
                         let focus_changed;
                         {
                             let mut new_focus_index = self.state.focus_index;
-                            let num_rules = self.state.rules.len();
+                            let num_rules = self.state.num_listed;
                             if num_rules == 0 {
                                 new_focus_index = None;
                             } else {
@@ -293,10 +703,33 @@ impl<'a, T: Copy> egui::Widget for SummonWidget<'a, T> {
                             self.state.focus_index = new_focus_index;
                         }
 
+                        // Typing while an entry is highlighted continues
+                        // typing into the text box
+                        let mut typed_ahead = false;
+                        if self.state.focus_index.is_some() {
+                            let typed = ui.input_mut(|i| {
+                                let mut typed = String::new();
+                                i.events.retain(|e| match e {
+                                    egui::Event::Text(s) => {
+                                        typed.push_str(s);
+                                        false
+                                    }
+                                    _ => true,
+                                });
+                                typed
+                            });
+                            if !typed.is_empty() {
+                                self.state.text.push_str(&typed);
+                                self.state.update_matches();
+                                self.state.focus_index = None;
+                                typed_ahead = true;
+                            }
+                        }
+
                         let textedit =
                             egui::TextEdit::singleline(&mut self.state.text).cursor_at_end(true);
                         let t = textedit.ui(ui);
-                        if self.state.just_opened {
+                        if self.state.just_opened || typed_ahead {
                             t.request_focus();
                             self.state.just_opened = false;
                         }
@@ -308,12 +741,7 @@ impl<'a, T: Copy> egui::Widget for SummonWidget<'a, T> {
                                 || i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)
                         }) {
                             self.state.finalized = true;
-                            self.state.current_choice = self
-                                .state
-                                .rules
-                                .get(0)
-                                .map(|x| x.value_and_args.clone())
-                                .flatten();
+                            self.state.current_choice = self.state.highlighted_choice();
                         }
                         if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
                         {
@@ -326,38 +754,63 @@ impl<'a, T: Copy> egui::Widget for SummonWidget<'a, T> {
                             t.request_focus();
                         }
 
+                        let mut toggled_favorite = None;
+
                         egui::ScrollArea::vertical().show(ui, |ui| {
-                            for (index, scored_rule) in self.state.rules.iter().enumerate() {
-                                let mut layout_job = egui::text::LayoutJob::default();
-                                layout_job.append(
-                                    scored_rule.rule.display_name(),
-                                    0.0,
-                                    egui::TextFormat {
-                                        color: egui::Color32::WHITE,
-                                        ..Default::default()
-                                    },
-                                );
-
-                                if let SummonRule::NameWithArguments(_, args, _) = &scored_rule.rule
+                            let rules = &self.state.rules[..self.state.num_listed];
+                            let focus_index = self.state.focus_index;
+                            let mut rows = Vec::new();
+                            let mut index = 0;
+                            while index < rules.len() {
+                                // Separate the pinned favorites and recents from the rest
+                                if index > 0
+                                    && rules[index].pinned_rank.is_none()
+                                    && rules[index - 1].pinned_rank.is_some()
                                 {
-                                    for arg in args.arguments() {
-                                        layout_job.append(
-                                            arg.name(),
-                                            5.0,
-                                            egui::TextFormat {
-                                                color: egui::Color32::GREEN,
-                                                italics: true,
-                                                ..Default::default()
-                                            },
-                                        );
-                                    }
+                                    ui.separator();
                                 }
 
-                                let r = ui
-                                    .add(egui::Label::new(layout_job).sense(egui::Sense::click()));
+                                let Some(category) = rules[index].group else {
+                                    rows.push((
+                                        index,
+                                        show_rule_row(
+                                            ui,
+                                            &rules[index],
+                                            focus_index == Some(index),
+                                        ),
+                                    ));
+                                    index += 1;
+                                    continue;
+                                };
+
+                                let group_end = index
+                                    + rules[index..]
+                                        .iter()
+                                        .take_while(|r| r.group == Some(category))
+                                        .count();
+                                egui::CollapsingHeader::new(category.name())
+                                    .id_salt(("summon category", category))
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        for (i, rule) in
+                                            rules.iter().enumerate().take(group_end).skip(index)
+                                        {
+                                            rows.push((
+                                                i,
+                                                show_rule_row(ui, rule, focus_index == Some(i)),
+                                            ));
+                                        }
+                                    });
+                                index = group_end;
+                            }
 
+                            for (index, row) in rows {
+                                if row.star_clicked {
+                                    toggled_favorite = rules[index].object_type;
+                                }
+                                let r = row.response;
                                 if r.clicked() {
-                                    self.state.current_choice = scored_rule.value_and_args.clone();
+                                    self.state.current_choice = rules[index].value_and_args.clone();
                                     self.state.finalized = true;
                                 }
                                 if r.hovered() {
@@ -377,11 +830,9 @@ impl<'a, T: Copy> egui::Widget for SummonWidget<'a, T> {
                             }
                         });
 
-                        // if focus_changed {
-                        if let Some(i) = self.state.focus_index {
-                            self.state.current_choice = self.state.rules[i].value_and_args.clone();
+                        if let Some(object_type) = toggled_favorite {
+                            self.state.toggle_favorite(object_type);
                         }
-                        // }
                     });
             },
         )
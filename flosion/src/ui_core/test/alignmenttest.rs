@@ -0,0 +1,83 @@
+use eframe::egui;
+
+use crate::ui_core::stackedlayout::alignment::{
+    align_left, distribute_horizontally, snap_to_alignment, AlignmentGuide,
+};
+
+fn rect(left: f32, top: f32, width: f32, height: f32) -> egui::Rect {
+    egui::Rect::from_min_size(egui::pos2(left, top), egui::vec2(width, height))
+}
+
+#[test]
+fn test_distribute_horizontally_evenly_spaces_three_groups() {
+    // Given out of order, with uneven gaps of 10 and 190 pixels
+    let rects = [
+        rect(400.0, 30.0, 100.0, 50.0),
+        rect(0.0, 0.0, 100.0, 50.0),
+        rect(110.0, 60.0, 100.0, 50.0),
+    ];
+
+    let offsets = distribute_horizontally(&rects);
+    let moved: Vec<egui::Rect> = rects
+        .iter()
+        .zip(&offsets)
+        .map(|(r, o)| r.translate(*o))
+        .collect();
+
+    // The outermost groups stay put and the middle one is centered between them
+    assert_eq!(moved[1], rects[1]);
+    assert_eq!(moved[0], rects[0]);
+    assert_eq!(moved[2], rect(200.0, 60.0, 100.0, 50.0));
+
+    // Only horizontal positions change
+    assert!(offsets.iter().all(|o| o.y == 0.0));
+}
+
+#[test]
+fn test_distribute_horizontally_with_different_widths() {
+    let rects = [
+        rect(0.0, 0.0, 50.0, 10.0),
+        rect(60.0, 0.0, 200.0, 10.0),
+        rect(300.0, 0.0, 100.0, 10.0),
+        rect(700.0, 0.0, 50.0, 10.0),
+    ];
+
+    let moved: Vec<egui::Rect> = rects
+        .iter()
+        .zip(distribute_horizontally(&rects))
+        .map(|(r, o)| r.translate(o))
+        .collect();
+
+    // 750 pixels spanned, 400 pixels of groups, leaving three gaps of 350 / 3
+    let gap = 350.0 / 3.0;
+    for pair in moved.windows(2) {
+        assert!((pair[1].left() - pair[0].right() - gap).abs() < 1e-3);
+    }
+    assert_eq!(moved[0], rects[0]);
+    assert_eq!(moved[3], rects[3]);
+}
+
+#[test]
+fn test_align_left_moves_to_leftmost_edge() {
+    let rects = [rect(30.0, 0.0, 10.0, 10.0), rect(-20.0, 50.0, 30.0, 10.0)];
+    assert_eq!(
+        align_left(&rects),
+        vec![egui::vec2(-50.0, 0.0), egui::vec2(0.0, 0.0)]
+    );
+}
+
+#[test]
+fn test_nearby_edges_snap_into_alignment() {
+    let other = rect(100.0, 100.0, 200.0, 100.0);
+
+    // Left edge is 5 pixels right of the other's left edge, top
+    // edge is far from any other edge
+    let (offset, guides) = snap_to_alignment(rect(105.0, 400.0, 50.0, 50.0), &[other]);
+    assert_eq!(offset, egui::vec2(-5.0, 0.0));
+    assert_eq!(guides, vec![AlignmentGuide::Vertical(100.0)]);
+
+    // Nothing is close enough to snap to
+    let (offset, guides) = snap_to_alignment(rect(500.0, 400.0, 50.0, 50.0), &[other]);
+    assert_eq!(offset, egui::Vec2::ZERO);
+    assert!(guides.is_empty());
+}
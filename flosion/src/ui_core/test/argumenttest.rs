@@ -1,6 +1,6 @@
 use crate::ui_core::arguments::{
-    Argument, ArgumentList, FloatArgument, FloatRangeArgument, NaturalNumberArgument,
-    StringIdentifierArgument,
+    Argument, ArgumentEnum, ArgumentList, EnumArgument, FloatArgument, FloatRangeArgument,
+    NaturalNumberArgument, StringIdentifierArgument,
 };
 
 fn split_vec_str(s: &str) -> Vec<String> {
@@ -733,3 +733,56 @@ fn test_value_name_range() {
         assert_eq!(parsed_args.get(&RANGE_ARG).unwrap(), 5.0..=10.0);
     }
 }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Fruit {
+    Apple,
+    Apricot,
+    Banana,
+}
+
+impl ArgumentEnum for Fruit {
+    fn all_values() -> &'static [Fruit] {
+        &[Fruit::Apple, Fruit::Apricot, Fruit::Banana]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Fruit::Apple => "apple",
+            Fruit::Apricot => "apricot",
+            Fruit::Banana => "banana",
+        }
+    }
+}
+
+#[test]
+fn test_enum_argument() {
+    const FRUIT_ARG: EnumArgument<Fruit> = EnumArgument::new("fruit");
+    const COUNT_ARG: NaturalNumberArgument = NaturalNumberArgument("count");
+
+    let arg_list = ArgumentList::new_empty().add(&FRUIT_ARG).add(&COUNT_ARG);
+
+    {
+        let parsed_args = arg_list.parse(split_vec_str("banana 3"));
+
+        assert_eq!(parsed_args.values().len(), 2);
+
+        assert_eq!(parsed_args.get(&FRUIT_ARG).unwrap(), Fruit::Banana);
+        assert_eq!(parsed_args.get(&COUNT_ARG).unwrap(), 3);
+    }
+
+    {
+        let parsed_args = arg_list.parse(split_vec_str("3 cherry"));
+
+        assert_eq!(parsed_args.values().len(), 1);
+
+        assert!(parsed_args.get(&FRUIT_ARG).is_none());
+        assert_eq!(parsed_args.get(&COUNT_ARG).unwrap(), 3);
+    }
+
+    let suggestions: Vec<Fruit> = EnumArgument::<Fruit>::suggestions("ap")
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect();
+    assert_eq!(suggestions, vec![Fruit::Apple, Fruit::Apricot]);
+}
@@ -0,0 +1,73 @@
+use crate::{
+    core::sound::{
+        argument::ProcessorArgumentLocation,
+        expression::{ExpressionParameterTarget, ProcessorExpressionLocation},
+        soundgraph::SoundGraph,
+        soundprocessor::SoundProcessorWithId,
+    },
+    objects::readwritewaveform::ReadWriteWaveform,
+    ui_core::{
+        expressiongraphuicontext::{AvailableParameter, OuterProcessorExpressionContext},
+        graph_properties::GraphProperties,
+        history::SnapshotFlag,
+        soundgraphuinames::SoundGraphUiNames,
+        stackedlayout::timeaxis::TimeAxis,
+    },
+};
+
+#[test]
+fn test_processor_with_two_arguments_reports_both() {
+    let rww = SoundProcessorWithId::<ReadWriteWaveform>::new_default();
+    let rww_id = rww.id();
+    let l = ProcessorArgumentLocation::new(rww_id, rww.input_l.id());
+    let r = ProcessorArgumentLocation::new(rww_id, rww.input_r.id());
+    let location = ProcessorExpressionLocation::new(rww_id, rww.waveform.id());
+
+    let mut graph = SoundGraph::new();
+    let mut mapping = rww.waveform.mapping().clone();
+    graph.add_sound_processor(Box::new(rww));
+
+    let mut names = SoundGraphUiNames::new();
+    names.cleanup(&graph);
+    names.record_sound_processor_name(rww_id, "rww".to_string());
+    names.record_argument_name(l, "l".to_string());
+    names.record_argument_name(r, "r".to_string());
+
+    let mut properties = GraphProperties::new();
+    properties.refresh(&graph);
+
+    let snapshot_flag = SnapshotFlag::new();
+    let ctx = OuterProcessorExpressionContext::new(
+        location,
+        &mut mapping,
+        &names,
+        TimeAxis {
+            time_per_x_pixel: 0.01,
+        },
+        &properties,
+        &snapshot_flag,
+    );
+
+    let own_time = ExpressionParameterTarget::ProcessorTime(rww_id);
+    assert_eq!(
+        ctx.available_parameters(),
+        vec![
+            AvailableParameter {
+                name: "time".to_string(),
+                target: own_time,
+            },
+            AvailableParameter {
+                name: "rww.time".to_string(),
+                target: own_time,
+            },
+            AvailableParameter {
+                name: "rww.l".to_string(),
+                target: ExpressionParameterTarget::Argument(l),
+            },
+            AvailableParameter {
+                name: "rww.r".to_string(),
+                target: ExpressionParameterTarget::Argument(r),
+            },
+        ]
+    );
+}
@@ -0,0 +1,54 @@
+use crate::{
+    core::{
+        jit::cache::JitCache,
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{soundgraph::SoundGraph, soundprocessor::SoundProcessorWithId},
+    },
+    objects::{audioclip::AudioClip, wavegenerator::WaveGenerator},
+    ui_core::interactions::bounce::bounce_processor,
+};
+
+#[test]
+fn test_bounce_sine_tone() {
+    // A default wave generator plays a 250 Hz sine wave at full amplitude
+    let frequency = 250.0;
+    let wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let wavegen_id = wavegen.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(wavegen));
+
+    let inkwell_context = inkwell::context::Context::create();
+    let mut jit_cache = JitCache::new(&inkwell_context);
+    jit_cache.refresh(&graph);
+
+    let clip_id = bounce_processor(&mut graph, &jit_cache, wavegen_id, 0.5);
+
+    // The original processor remains and the clip is unconnected
+    assert!(graph.contains(wavegen_id));
+    assert_eq!(graph.sound_processors().len(), 2);
+    assert!(graph.inputs_connected_to(clip_id).is_empty());
+
+    let clip = graph
+        .sound_processor(clip_id)
+        .unwrap()
+        .downcast::<AudioClip>()
+        .unwrap();
+    let data = clip.get_data();
+
+    assert_eq!(data.sample_len(), SAMPLE_FREQUENCY / 2);
+
+    // The generator's phase advances before each sample is produced
+    for (i, [l, r]) in data.samples().take(data.sample_len()).enumerate() {
+        let t = (i + 1) as f32 / SAMPLE_FREQUENCY as f32;
+        let expected = (std::f32::consts::TAU * frequency * t).sin();
+        assert!(
+            (l - expected).abs() < 1e-2,
+            "sample {} is {} instead of {}",
+            i,
+            l,
+            expected
+        );
+        assert_eq!(l, r);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{
+    core::{
+        expression::{expressiongraph::ExpressionTarget, expressionnode::ExpressionNodeWithId},
+        sound::{
+            expression::ProcessorExpressionLocation, soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorWithId,
+        },
+    },
+    objects::{purefunctions::Constant, wavegenerator::WaveGenerator},
+    ui_core::{
+        arguments::ParsedArguments,
+        constantdiagnostics::{find_suspicious_constants, ConstantProblem},
+    },
+};
+
+fn make_constant(value: f64) -> ExpressionNodeWithId<Constant> {
+    ExpressionNodeWithId::new_from_args(
+        &ParsedArguments::new_empty().add_or_replace(&Constant::ARG_VALUE, value),
+    )
+}
+
+#[test]
+fn test_nan_constant_is_reported_once() {
+    let mut wavegen = SoundProcessorWithId::<WaveGenerator>::new_default();
+    let wavegen_id = wavegen.id();
+    let amplitude_location = ProcessorExpressionLocation::new(wavegen_id, wavegen.amplitude.id());
+
+    // A NaN constant feeding the amplitude, next to a sensible constant
+    // feeding the frequency
+    let nan = make_constant(f64::NAN);
+    let nan_id = nan.id();
+    let amplitude_graph = wavegen.amplitude.graph_mut();
+    amplitude_graph.add_expression_node(Box::new(nan));
+    let amplitude_result = amplitude_graph.results()[0].id();
+    amplitude_graph
+        .connect_result(amplitude_result, ExpressionTarget::Node(nan_id))
+        .unwrap();
+
+    let frequency = make_constant(440.0);
+    let frequency_id = frequency.id();
+    let frequency_graph = wavegen.frequency.graph_mut();
+    frequency_graph.add_expression_node(Box::new(frequency));
+    let frequency_result = frequency_graph.results()[0].id();
+    frequency_graph
+        .connect_result(frequency_result, ExpressionTarget::Node(frequency_id))
+        .unwrap();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(wavegen));
+    assert_eq!(graph.validate(), Ok(()));
+
+    let diagnostics = find_suspicious_constants(&graph);
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.expression, amplitude_location);
+    assert_eq!(diagnostic.node, nan_id);
+    assert_eq!(diagnostic.problem, ConstantProblem::NotANumber);
+}
+
+#[test]
+fn test_constant_problems() {
+    assert_eq!(ConstantProblem::of(0.0), None);
+    assert_eq!(ConstantProblem::of(-0.5), None);
+    assert_eq!(ConstantProblem::of(48000.0 * 3600.0), None);
+    assert_eq!(
+        ConstantProblem::of(f32::NAN),
+        Some(ConstantProblem::NotANumber)
+    );
+    assert_eq!(
+        ConstantProblem::of(f32::NEG_INFINITY),
+        Some(ConstantProblem::Infinite)
+    );
+    assert_eq!(
+        ConstantProblem::of(1e20),
+        Some(ConstantProblem::ExtremeMagnitude)
+    );
+    assert_eq!(
+        ConstantProblem::of(-1e-20),
+        Some(ConstantProblem::ExtremeMagnitude)
+    );
+}
@@ -0,0 +1,123 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use crate::{
+    core::{
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::{soundgraph::SoundGraph, soundprocessor::SoundProcessorWithId},
+    },
+    objects::audioclip::AudioClip,
+    ui_core::{
+        factories::Factories,
+        soundgraphuistate::{audioclip_arguments_for_dropped_file, SoundGraphUiState},
+    },
+};
+
+/// Write a short stereo 16-bit PCM wav file to the given path
+pub(super) fn write_test_wav_file(path: &Path, num_frames: usize) {
+    let num_channels: u16 = 2;
+    let bits_per_sample: u16 = 16;
+    let block_align = num_channels * bits_per_sample / 8;
+    let byte_rate = SAMPLE_FREQUENCY as u32 * block_align as u32;
+    let data_len = (num_frames * block_align as usize) as u32;
+
+    let mut bytes: Vec<u8> = Vec::new();
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&num_channels.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_FREQUENCY as u32).to_le_bytes());
+    bytes.extend_from_slice(&byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&bits_per_sample.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for i in 0..num_frames {
+        let v = ((i % 64) as i16 - 32) * 256;
+        bytes.extend_from_slice(&v.to_le_bytes());
+        bytes.extend_from_slice(&(-v).to_le_bytes());
+    }
+
+    std::fs::write(path, bytes).unwrap();
+}
+
+pub(super) fn temp_file_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("flosion_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn test_dropped_supported_file_arguments() {
+    for name in ["clip.wav", "clip.ogg", "clip.flac", "CLIP.WAV"] {
+        let path = PathBuf::from(name);
+        let args = audioclip_arguments_for_dropped_file(&path).unwrap();
+        assert_eq!(args.get(&AudioClip::ARG_PATH), Some(path));
+    }
+}
+
+#[test]
+fn test_dropped_unsupported_file_arguments() {
+    for name in ["clip.txt", "clip.mp3", "clip"] {
+        let path = PathBuf::from(name);
+        assert!(audioclip_arguments_for_dropped_file(&path).is_err());
+    }
+}
+
+#[test]
+fn test_dropped_file_creates_audioclip() {
+    let path = temp_file_path("dropped.wav");
+    write_test_wav_file(&path, 1000);
+
+    let factories = Factories::new_all_objects();
+    let mut graph = SoundGraph::new();
+    let mut ui_state = SoundGraphUiState::new();
+
+    let position = egui::pos2(123.0, 45.0);
+
+    let result = ui_state.handle_dropped_file(&path, position, &factories, &mut graph);
+
+    std::fs::remove_file(&path).unwrap();
+
+    let id = result.unwrap();
+
+    let audioclip = graph
+        .sound_processor(id)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<SoundProcessorWithId<AudioClip>>()
+        .unwrap();
+    assert_eq!(audioclip.get_data().sample_len(), 1000);
+
+    assert_eq!(
+        ui_state
+            .positions()
+            .find_processor(id)
+            .unwrap()
+            .body_rect
+            .min,
+        position
+    );
+
+    // The object ui state must exist for the new processor
+    ui_state.object_states().get_object_data(id.into());
+}
+
+#[test]
+fn test_dropped_unsupported_file_creates_nothing() {
+    let factories = Factories::new_all_objects();
+    let mut graph = SoundGraph::new();
+    let mut ui_state = SoundGraphUiState::new();
+
+    let result = ui_state.handle_dropped_file(
+        Path::new("notes.txt"),
+        egui::pos2(0.0, 0.0),
+        &factories,
+        &mut graph,
+    );
+
+    assert!(result.is_err());
+    assert_eq!(graph.sound_processors().len(), 0);
+}
@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+
+use hashstash::Stash;
+
+use crate::{
+    core::sound::{
+        expression::ExpressionParameterTarget,
+        soundgraph::SoundGraph,
+        soundinput::{AnyProcessorInput, SoundInputLocation},
+        soundprocessor::SoundProcessorWithId,
+    },
+    objects::{
+        tremolo::{Tremolo, TremoloWaveform},
+        whitenoise::WhiteNoise,
+    },
+    ui_core::{
+        factories::Factories,
+        interactions::duplicateprocessors::{
+            copy_processors, duplicate_processors, paste_processors,
+        },
+        soundobjectpositions::SoundObjectPositions,
+        stackedlayout::stackedlayout::StackedLayout,
+    },
+};
+
+#[test]
+fn test_duplicating_group_yields_independent_identical_group() {
+    let mut graph = SoundGraph::new();
+
+    // noise -> tremolo, whose rate depends on its own input's time
+    let noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let mut tremolo = SoundProcessorWithId::<Tremolo>::new_default();
+
+    let noise_id = noise.id();
+    let tremolo_id = tremolo.id();
+    let tremolo_input = SoundInputLocation::new(tremolo_id, tremolo.input.id());
+
+    tremolo.set_waveform(TremoloWaveform::Square);
+    tremolo
+        .rate
+        .add_target(ExpressionParameterTarget::InputTime(tremolo_input));
+
+    graph.add_sound_processor(Box::new(noise));
+    graph.add_sound_processor(Box::new(tremolo));
+    graph.connect_sound_input(tremolo_input, noise_id).unwrap();
+
+    let mut layout = StackedLayout::new();
+    layout.regenerate(&graph, &SoundObjectPositions::new());
+    assert_eq!(layout.groups().len(), 1);
+
+    let factories = Factories::new_all_objects();
+    let stash = Stash::new();
+    let copies = duplicate_processors(
+        &mut graph,
+        &HashSet::from([noise_id, tremolo_id]),
+        &stash,
+        factories.sound_objects(),
+        factories.expression_objects(),
+    );
+
+    assert_eq!(graph.validate(), Ok(()));
+    assert_eq!(graph.sound_processors().len(), 4);
+    assert_eq!(copies.len(), 2);
+
+    let noise_copy_id = copies[&noise_id];
+    let tremolo_copy_id = copies[&tremolo_id];
+    assert!(noise_copy_id != noise_id);
+    assert!(tremolo_copy_id != tremolo_id);
+
+    let tremolo_copy = graph
+        .sound_processor(tremolo_copy_id)
+        .unwrap()
+        .downcast::<Tremolo>()
+        .unwrap();
+    let tremolo_copy_input = SoundInputLocation::new(tremolo_copy_id, tremolo_copy.input.id());
+
+    // The copy has the same settings as the original
+    assert_eq!(tremolo_copy.waveform(), TremoloWaveform::Square);
+
+    // The copies are connected to each other rather than to the originals
+    assert_eq!(
+        graph.with_sound_input(tremolo_copy_input, |i| i.target()),
+        Some(Some(noise_copy_id))
+    );
+    assert_eq!(
+        graph.with_sound_input(tremolo_input, |i| i.target()),
+        Some(Some(noise_id))
+    );
+
+    // The copy's expression refers to the copy's own input
+    let mapping_targets: Vec<ExpressionParameterTarget> = tremolo_copy
+        .rate
+        .mapping()
+        .items()
+        .values()
+        .cloned()
+        .collect();
+    assert_eq!(
+        mapping_targets,
+        vec![ExpressionParameterTarget::InputTime(tremolo_copy_input)]
+    );
+
+    // The copies form a separate group of their own
+    layout.regenerate(&graph, &SoundObjectPositions::new());
+    assert!(layout.check_invariants(&graph));
+    assert_eq!(layout.groups().len(), 2);
+    assert_eq!(
+        layout.find_group(tremolo_copy_id).unwrap().processors(),
+        &[noise_copy_id, tremolo_copy_id]
+    );
+    assert_eq!(
+        layout.find_group(tremolo_id).unwrap().processors(),
+        &[noise_id, tremolo_id]
+    );
+
+    // Changing the copy leaves the original untouched
+    graph
+        .sound_processor_mut(tremolo_copy_id)
+        .unwrap()
+        .downcast_mut::<Tremolo>()
+        .unwrap()
+        .set_waveform(TremoloWaveform::Sine);
+    let tremolo = graph
+        .sound_processor(tremolo_id)
+        .unwrap()
+        .downcast::<Tremolo>()
+        .unwrap();
+    assert_eq!(tremolo.waveform(), TremoloWaveform::Square);
+}
+
+#[test]
+fn test_copied_processors_paste_into_another_graph_without_outside_references() {
+    let mut graph = SoundGraph::new();
+
+    // noise -> tremolo, whose rate depends on its own input's time and
+    // on the noise's time
+    let noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let mut tremolo = SoundProcessorWithId::<Tremolo>::new_default();
+
+    let noise_id = noise.id();
+    let tremolo_id = tremolo.id();
+    let tremolo_input = SoundInputLocation::new(tremolo_id, tremolo.input.id());
+
+    tremolo.set_waveform(TremoloWaveform::Square);
+    tremolo
+        .rate
+        .add_target(ExpressionParameterTarget::InputTime(tremolo_input));
+    tremolo
+        .rate
+        .add_target(ExpressionParameterTarget::ProcessorTime(noise_id));
+
+    graph.add_sound_processor(Box::new(noise));
+    graph.add_sound_processor(Box::new(tremolo));
+    graph.connect_sound_input(tremolo_input, noise_id).unwrap();
+    assert_eq!(graph.validate(), Ok(()));
+
+    // Copy only the tremolo, leaving the noise behind
+    let factories = Factories::new_all_objects();
+    let stash = Stash::new();
+    let copied = copy_processors(
+        &graph,
+        &HashSet::from([tremolo_id]),
+        &stash,
+        factories.sound_objects(),
+        factories.expression_objects(),
+    );
+
+    // The original is untouched by copying
+    assert_eq!(
+        graph.with_sound_input(tremolo_input, |i| i.target()),
+        Some(Some(noise_id))
+    );
+
+    // Paste twice into a different graph
+    let mut other_graph = SoundGraph::new();
+    let mut pasted_ids = Vec::new();
+    for _ in 0..2 {
+        let pasted = paste_processors(
+            &mut other_graph,
+            &copied,
+            &stash,
+            factories.sound_objects(),
+            factories.expression_objects(),
+        )
+        .unwrap();
+        assert_eq!(pasted.len(), 1);
+        pasted_ids.push(pasted[&tremolo_id]);
+    }
+
+    assert_eq!(other_graph.validate(), Ok(()));
+    assert_eq!(other_graph.sound_processors().len(), 2);
+    assert!(pasted_ids[0] != pasted_ids[1]);
+
+    for pasted_id in pasted_ids {
+        // Every pasted processor has a fresh id
+        assert!(pasted_id != tremolo_id && pasted_id != noise_id);
+
+        let pasted = other_graph
+            .sound_processor(pasted_id)
+            .unwrap()
+            .downcast::<Tremolo>()
+            .unwrap();
+        assert_eq!(pasted.waveform(), TremoloWaveform::Square);
+        assert!(pasted.input.id() != tremolo_input.input());
+        let pasted_input = SoundInputLocation::new(pasted_id, pasted.input.id());
+
+        // The connection to the noise which wasn't copied is gone
+        assert_eq!(
+            other_graph.with_sound_input(pasted_input, |i| i.target()),
+            Some(None)
+        );
+
+        // Only the expression's reference to the pasted processor's own
+        // input remains
+        let mapping_targets: Vec<ExpressionParameterTarget> =
+            pasted.rate.mapping().items().values().cloned().collect();
+        assert_eq!(
+            mapping_targets,
+            vec![ExpressionParameterTarget::InputTime(pasted_input)]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use eframe::egui;
+
+use crate::{
+    core::{
+        expression::{
+            expressiongraph::ExpressionTarget,
+            expressionnode::{AnyExpressionNode, ExpressionNodeWithId},
+        },
+        jit::jit::{ExpressionTestDomain, Interval, Jit, JitMode},
+        sound::{
+            argument::{ArgumentScope, ProcessorArgument, ProcessorArgumentLocation},
+            argumenttypes::plainf32array::PlainF32ArrayArgument,
+            expression::{ExpressionParameterTarget, ProcessorExpression},
+            soundgraph::SoundGraph,
+            soundprocessor::SoundProcessorId,
+        },
+    },
+    objects::purefunctions::Multiply,
+    ui_core::{
+        expressionplot::{nearest_sample_index, sample_horizontal_value, sample_plot},
+        stackedlayout::timeaxis::TimeAxis,
+    },
+};
+
+#[test]
+fn test_plot_of_square_is_parabola() {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    // x * x
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+    let x_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+    let graph = expr.graph_mut();
+    let node = ExpressionNodeWithId::<Multiply>::new_default();
+    let node_id = node.id();
+    let input_locations = (&node as &dyn AnyExpressionNode).input_locations();
+    graph.add_expression_node(Box::new(node));
+    for input_location in input_locations {
+        graph
+            .connect_input(input_location, Some(ExpressionTarget::Parameter(x_param)))
+            .unwrap();
+    }
+    graph
+        .connect_result(graph.results()[0].id(), ExpressionTarget::Node(node_id))
+        .unwrap();
+
+    let domain = ExpressionTestDomain::WithRespectTo(
+        argument_location,
+        Interval::Linear {
+            from: -1.0,
+            to: 1.0,
+        },
+    );
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(domain),
+        )
+        .unwrap();
+
+    let len = 8;
+    let samples = sample_plot(
+        &mut artefact.make_function(),
+        len,
+        &domain,
+        TimeAxis {
+            time_per_x_pixel: 0.01,
+        },
+    );
+
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].len(), len);
+
+    // Samples are evenly spaced from the start of the interval
+    for (i, y) in samples[0].iter().enumerate() {
+        let x = -1.0 + 2.0 * (i as f32) / (len as f32);
+        assert!((y - x * x).abs() < 1e-6, "{} != {}", y, x * x);
+    }
+}
+
+#[test]
+fn test_screen_position_to_nearest_sample() {
+    let rect = egui::Rect::from_min_size(egui::pos2(10.0, 0.0), egui::vec2(100.0, 30.0));
+    let len = 11;
+
+    // Samples lie every 10 pixels from the left edge to the right edge
+    assert_eq!(nearest_sample_index(10.0, rect, len), 0);
+    assert_eq!(nearest_sample_index(14.9, rect, len), 0);
+    assert_eq!(nearest_sample_index(15.1, rect, len), 1);
+    assert_eq!(nearest_sample_index(60.0, rect, len), 5);
+    assert_eq!(nearest_sample_index(110.0, rect, len), 10);
+
+    // Positions beyond the edges snap to the outermost samples
+    assert_eq!(nearest_sample_index(-50.0, rect, len), 0);
+    assert_eq!(nearest_sample_index(500.0, rect, len), 10);
+}
+
+fn assert_interval_inverts(interval: Interval) {
+    let len = 100;
+    for i in 0..len {
+        let value = interval.sample_value(i, len);
+        assert_eq!(interval.nearest_sample(value, len), i);
+        let fraction = i as f32 / len as f32;
+        assert!((interval.fraction_of(value) - fraction).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_linear_interval_inversion() {
+    let interval = Interval::Linear {
+        from: -2.0,
+        to: 6.0,
+    };
+    assert_eq!(interval.value_at(0.0), -2.0);
+    assert_eq!(interval.value_at(0.5), 2.0);
+    assert_eq!(interval.value_at(1.0), 6.0);
+    assert_interval_inverts(interval);
+
+    // Values between samples snap to the nearest one
+    assert_eq!(interval.nearest_sample(-1.95, 100), 1);
+    assert_eq!(interval.nearest_sample(100.0, 100), 99);
+}
+
+#[test]
+fn test_logarithmic_interval_inversion() {
+    let interval = Interval::Logarithmic {
+        from: 10.0,
+        to: 10000.0,
+    };
+    assert!((interval.value_at(0.0) - 10.0).abs() < 1e-3);
+    assert!((interval.value_at(1.0 / 3.0) - 100.0).abs() < 1e-2);
+    assert!((interval.value_at(1.0) - 10000.0).abs() < 1e-1);
+    assert_interval_inverts(interval);
+
+    // Nearness is proportional rather than absolute
+    assert_eq!(interval.nearest_sample(1.0, 100), 0);
+    assert_eq!(interval.nearest_sample(1e9, 100), 99);
+}
+
+#[test]
+fn test_logarithmic_interval_is_sampled_as_compiled() {
+    let proc_id = SoundProcessorId::new(1);
+    let argument = ProcessorArgument::<PlainF32ArrayArgument>::new();
+    let argument_location = ProcessorArgumentLocation::new(proc_id, argument.id());
+
+    // Just x
+    let mut expr = ProcessorExpression::new(&[0.0], ArgumentScope::new(vec![argument.id()]));
+    let x_param = expr.add_target(ExpressionParameterTarget::Argument(argument_location));
+    let graph = expr.graph_mut();
+    graph
+        .connect_result(
+            graph.results()[0].id(),
+            ExpressionTarget::Parameter(x_param),
+        )
+        .unwrap();
+
+    let interval = Interval::Logarithmic {
+        from: 20.0,
+        to: 20000.0,
+    };
+    let domain = ExpressionTestDomain::WithRespectTo(argument_location, interval);
+
+    let inkwell_context = inkwell::context::Context::create();
+    let artefact = Jit::new(&inkwell_context)
+        .compile_expression(
+            expr.graph(),
+            expr.mapping(),
+            &SoundGraph::new(),
+            JitMode::Test(domain),
+        )
+        .unwrap();
+
+    let len = 16;
+    let time_axis = TimeAxis {
+        time_per_x_pixel: 0.01,
+    };
+    let samples = sample_plot(&mut artefact.make_function(), len, &domain, time_axis);
+
+    for (i, x) in samples[0].iter().enumerate() {
+        let expected = sample_horizontal_value(&domain, i, len, time_axis);
+        assert!(
+            (x - expected).abs() < 1e-4 * expected,
+            "{} != {}",
+            x,
+            expected
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{
+    core::biquad::BiquadCoefficients, ui_core::frequencyresponseplot::sample_frequency_response,
+};
+
+#[test]
+fn test_lowpass_is_3db_down_at_cutoff() {
+    let cutoff = 1000.0;
+    let lowpass = BiquadCoefficients::lowpass(cutoff, std::f32::consts::FRAC_1_SQRT_2);
+
+    let samples = sample_frequency_response(|f| lowpass.frequency_response(f), 256);
+
+    // The passband is flat
+    assert!(samples[0].decibels.abs() < 0.01);
+
+    // Find where the response first drops below -3 dB and interpolate
+    // between the adjacent samples in log-frequency
+    let i = samples.iter().position(|s| s.decibels < -3.0).unwrap();
+    let (a, b) = (samples[i - 1], samples[i]);
+    let t = (-3.0 - a.decibels) / (b.decibels - a.decibels);
+    let crossing = (a.frequency.ln() + t * (b.frequency.ln() - a.frequency.ln())).exp();
+    assert!(
+        (crossing - cutoff).abs() < 0.01 * cutoff,
+        "-3 dB at {} Hz instead of {} Hz",
+        crossing,
+        cutoff
+    );
+
+    // The response keeps falling above the cutoff
+    assert!(samples.last().unwrap().decibels < -40.0);
+}
@@ -0,0 +1,157 @@
+use hashstash::Stash;
+
+use crate::{
+    core::{
+        audiofileio::AudioFilePath,
+        sound::{
+            soundgraph::SoundGraph,
+            soundprocessor::{SoundProcessorId, SoundProcessorWithId},
+        },
+        stashing::{StashingContext, UnstashingContext},
+    },
+    objects::audioclip::AudioClip,
+    ui_core::{arguments::ParsedArguments, factories::Factories, soundobjectui::SoundObjectUi},
+    ui_objects::audioclip_ui::{load_file, AudioClipUi, AudioClipUiState},
+};
+
+use super::droppedfiletest::{temp_file_path, write_test_wav_file};
+
+/// Create an audioclip and its ui state from the given arguments, as
+/// when summoning one
+fn create_audioclip(args: &ParsedArguments) -> (SoundProcessorWithId<AudioClip>, AudioClipUiState) {
+    let audioclip = SoundProcessorWithId::<AudioClip>::new_from_args(args);
+    let ui_state = AudioClipUi::default()
+        .make_ui_state(&audioclip, args)
+        .unwrap();
+    (audioclip, ui_state)
+}
+
+/// Stash and unstash the graph, as when undoing or redoing
+fn stash_roundtrip(graph: &SoundGraph, factories: &Factories) -> SoundGraph {
+    let stash = Stash::new();
+    let handle = stash.stash_with_context(graph, StashingContext::new_stashing_normally());
+    stash
+        .unstash_with_context(
+            &handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        )
+        .unwrap()
+}
+
+fn audioclip_in(graph: &SoundGraph, id: SoundProcessorId) -> &AudioClip {
+    graph
+        .sound_processor(id)
+        .unwrap()
+        .downcast::<AudioClip>()
+        .unwrap()
+}
+
+#[test]
+fn test_dangling_audio_file_loads_empty_clip_with_warning() {
+    let factories = Factories::new_all_objects();
+    let mut graph = SoundGraph::new();
+
+    let path = temp_file_path("this_file_does_not_exist.wav");
+    assert!(!path.exists());
+
+    let args = ParsedArguments::new_empty().add_or_replace(&AudioClip::ARG_PATH, path.clone());
+
+    let (audioclip, ui_state) = create_audioclip(&args);
+
+    assert_eq!(audioclip.get_data().sample_len(), 0);
+    assert_eq!(audioclip.missing_file(), Some(path.as_path()));
+
+    // The missing file is warned about once, not also as a load error
+    assert_eq!(ui_state.load_error(), None);
+
+    let id = audioclip.id();
+    graph.add_sound_processor(Box::new(audioclip));
+
+    // The warning must survive being stashed and unstashed
+    let graph = stash_roundtrip(&graph, &factories);
+    assert_eq!(
+        audioclip_in(&graph, id).missing_file(),
+        Some(path.as_path())
+    );
+}
+
+#[test]
+fn test_audioclip_without_file_has_no_warning() {
+    let (audioclip, ui_state) = create_audioclip(&ParsedArguments::new_empty());
+
+    assert_eq!(audioclip.missing_file(), None);
+    assert_eq!(ui_state.load_error(), None);
+}
+
+#[test]
+fn test_deleted_audio_file_is_detected_on_unstash() {
+    let factories = Factories::new_all_objects();
+    let mut graph = SoundGraph::new();
+
+    let path = temp_file_path("deleted_after_stashing.wav");
+    write_test_wav_file(&path, 1024);
+
+    let args = ParsedArguments::new_empty().add_or_replace(&AudioClip::ARG_PATH, path.clone());
+    let (audioclip, _) = create_audioclip(&args);
+
+    assert_eq!(audioclip.get_data().sample_len(), 1024);
+    assert_eq!(audioclip.missing_file(), None);
+
+    let id = audioclip.id();
+    graph.add_sound_processor(Box::new(audioclip));
+
+    let stash = Stash::new();
+    let handle = stash.stash_with_context(&graph, StashingContext::new_stashing_normally());
+
+    std::fs::remove_file(&path).unwrap();
+
+    let graph: SoundGraph = stash
+        .unstash_with_context(
+            &handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects()),
+        )
+        .unwrap();
+
+    let audioclip = audioclip_in(&graph, id);
+    assert_eq!(
+        audioclip.source().map(AudioFilePath::absolute),
+        Some(path.as_path())
+    );
+    assert_eq!(audioclip.missing_file(), Some(path.as_path()));
+
+    // The stashed data still plays in the meantime
+    assert_eq!(audioclip.get_data().sample_len(), 1024);
+}
+
+#[test]
+fn test_load_errors_go_into_ui_state() {
+    let bad_path = temp_file_path("not_really_audio.wav");
+    std::fs::write(&bad_path, b"this is not an audio file").unwrap();
+
+    // Failing to load the file given when creating the audioclip
+    let args = ParsedArguments::new_empty().add_or_replace(&AudioClip::ARG_PATH, bad_path.clone());
+    let (mut audioclip, mut ui_state) = create_audioclip(&args);
+    assert_eq!(audioclip.missing_file(), None);
+    assert!(ui_state.load_error().is_some());
+
+    // Failing to load a file picked later on
+    let good_path = temp_file_path("really_audio.wav");
+    write_test_wav_file(&good_path, 500);
+    load_file(&mut audioclip, &mut ui_state, &good_path);
+    assert_eq!(ui_state.load_error(), None);
+    assert_eq!(audioclip.get_data().sample_len(), 500);
+
+    load_file(&mut audioclip, &mut ui_state, &bad_path);
+
+    std::fs::remove_file(&bad_path).unwrap();
+    std::fs::remove_file(&good_path).unwrap();
+
+    assert!(ui_state.load_error().is_some());
+
+    // The previously loaded file is kept
+    assert_eq!(audioclip.get_data().sample_len(), 500);
+    assert_eq!(
+        audioclip.source().map(AudioFilePath::absolute),
+        Some(good_path.as_path())
+    );
+}
@@ -1 +1,17 @@
+mod alignmenttest;
 mod argumenttest;
+mod availableparameterstest;
+mod bouncetest;
+mod constantdiagnosticstest;
+mod droppedfiletest;
+mod duplicateprocessorstest;
+mod expressionplottest;
+mod frequencyresponsetest;
+mod missingaudiofiletest;
+mod movedpatchtest;
+mod processorpalettetest;
+mod processorsearchtest;
+mod replaceprocessortest;
+mod soundgraphuinamestest;
+mod soundobjectuistatetest;
+mod summonwidgettest;
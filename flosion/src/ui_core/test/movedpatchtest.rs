@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+
+use hashstash::{Stash, StashHandle};
+
+use crate::{
+    core::{
+        audiofileio::AudioFilePath,
+        sound::{
+            soundgraph::SoundGraph,
+            soundprocessor::{SoundProcessorId, SoundProcessorWithId},
+        },
+        stashing::{StashingContext, UnstashingContext},
+    },
+    objects::{audioclip::AudioClip, wavetable::Wavetable},
+    ui_core::factories::Factories,
+};
+
+use super::droppedfiletest::{temp_file_path, write_test_wav_file};
+
+/// A patch containing an audioclip and a wavetable which were loaded
+/// from files in the given directory
+fn stash_patch_in(
+    directory: &Path,
+    stash: &Stash,
+) -> (StashHandle<SoundGraph>, SoundProcessorId, SoundProcessorId) {
+    let clip_path = directory.join("clip.wav");
+    let table_path = directory.join("tables").join("table.wav");
+    std::fs::create_dir_all(table_path.parent().unwrap()).unwrap();
+    write_test_wav_file(&clip_path, 1024);
+    write_test_wav_file(&table_path, 4096);
+
+    let mut audioclip = SoundProcessorWithId::<AudioClip>::new_default();
+    audioclip
+        .load_file(AudioFilePath::new(&clip_path, directory))
+        .unwrap();
+    let mut wavetable = SoundProcessorWithId::<Wavetable>::new_default();
+    wavetable
+        .load_file(AudioFilePath::new(&table_path, directory))
+        .unwrap();
+
+    let audioclip_id = audioclip.id();
+    let wavetable_id = wavetable.id();
+
+    let mut graph = SoundGraph::new();
+    graph.add_sound_processor(Box::new(audioclip));
+    graph.add_sound_processor(Box::new(wavetable));
+
+    let handle = stash.stash_with_context(&graph, StashingContext::new_stashing_normally());
+    (handle, audioclip_id, wavetable_id)
+}
+
+fn unstash_patch_in(
+    directory: &Path,
+    stash: &Stash,
+    handle: &StashHandle<SoundGraph>,
+    factories: &Factories,
+) -> SoundGraph {
+    stash
+        .unstash_with_context(
+            handle,
+            UnstashingContext::new(factories.sound_objects(), factories.expression_objects())
+                .with_base_directory(directory),
+        )
+        .unwrap()
+}
+
+fn fresh_directory(name: &str) -> PathBuf {
+    let directory = temp_file_path(name);
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn test_file_paths_are_relative_to_base_directory() {
+    let base = Path::new("/patches/song");
+
+    let inside = AudioFilePath::new(Path::new("/patches/song/drums/kick.wav"), base);
+    assert_eq!(inside.relative(), Some(Path::new("drums/kick.wav")));
+    assert_eq!(inside.absolute(), Path::new("/patches/song/drums/kick.wav"));
+
+    let given_relative = AudioFilePath::new(Path::new("drums/kick.wav"), base);
+    assert_eq!(given_relative, inside);
+
+    let outside = AudioFilePath::new(Path::new("/samples/snare.wav"), base);
+    assert_eq!(outside.relative(), None);
+    assert_eq!(outside.absolute(), Path::new("/samples/snare.wav"));
+}
+
+#[test]
+fn test_patch_moved_with_its_audio_still_resolves() {
+    let factories = Factories::new_all_objects();
+    let stash = Stash::new();
+
+    let old_directory = fresh_directory("patch_before_moving");
+    let (handle, audioclip_id, wavetable_id) = stash_patch_in(&old_directory, &stash);
+
+    let new_directory = temp_file_path("patch_after_moving");
+    if new_directory.exists() {
+        std::fs::remove_dir_all(&new_directory).unwrap();
+    }
+    std::fs::rename(&old_directory, &new_directory).unwrap();
+
+    let graph = unstash_patch_in(&new_directory, &stash, &handle, &factories);
+
+    std::fs::remove_dir_all(&new_directory).unwrap();
+
+    let audioclip = graph
+        .sound_processor(audioclip_id)
+        .unwrap()
+        .downcast::<AudioClip>()
+        .unwrap();
+    let source = audioclip.source().unwrap();
+    assert_eq!(source.absolute(), new_directory.join("clip.wav"));
+    assert_eq!(source.relative(), Some(Path::new("clip.wav")));
+    assert_eq!(audioclip.missing_file(), None);
+
+    let wavetable = graph
+        .sound_processor(wavetable_id)
+        .unwrap()
+        .downcast::<Wavetable>()
+        .unwrap();
+    assert_eq!(
+        wavetable.source().unwrap().absolute(),
+        new_directory.join("tables").join("table.wav")
+    );
+}
+
+#[test]
+fn test_patch_moved_without_its_audio_falls_back_to_absolute_paths() {
+    let factories = Factories::new_all_objects();
+    let stash = Stash::new();
+
+    let audio_directory = fresh_directory("audio_left_behind");
+    let (handle, audioclip_id, wavetable_id) = stash_patch_in(&audio_directory, &stash);
+
+    // The patch moves somewhere else, but the audio stays put
+    let patch_directory = fresh_directory("patch_moved_alone");
+
+    let graph = unstash_patch_in(&patch_directory, &stash, &handle, &factories);
+
+    std::fs::remove_dir_all(&audio_directory).unwrap();
+    std::fs::remove_dir_all(&patch_directory).unwrap();
+
+    let audioclip = graph
+        .sound_processor(audioclip_id)
+        .unwrap()
+        .downcast::<AudioClip>()
+        .unwrap();
+    assert_eq!(
+        audioclip.source().unwrap().absolute(),
+        audio_directory.join("clip.wav")
+    );
+    assert_eq!(audioclip.missing_file(), None);
+
+    let wavetable = graph
+        .sound_processor(wavetable_id)
+        .unwrap()
+        .downcast::<Wavetable>()
+        .unwrap();
+    assert_eq!(
+        wavetable.source().unwrap().absolute(),
+        audio_directory.join("tables").join("table.wav")
+    );
+}
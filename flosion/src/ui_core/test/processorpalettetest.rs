@@ -0,0 +1,90 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::{
+        soundgraph::SoundGraph,
+        soundprocessor::{AnySoundProcessor, SoundProcessorWithId},
+    },
+    objects::{tremolo::Tremolo, whitenoise::WhiteNoise, widen::Widen},
+    ui_core::{
+        processorpalette::{build_processor_palette, jump_to_processor},
+        soundobjectpositions::SoundObjectPositions,
+        stackedlayout::stackedlayout::StackedLayout,
+    },
+};
+
+fn listed_names(graph: &SoundGraph, text: &str) -> Vec<String> {
+    let mut palette = build_processor_palette(graph, egui::pos2(0.0, 0.0));
+    palette.set_text(text.to_string());
+    palette
+        .listed_groups()
+        .into_iter()
+        .flat_map(|(_, names)| names)
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_palette_filters_processors_by_typed_text() {
+    let mut graph = SoundGraph::new();
+
+    let noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let tremolo1 = SoundProcessorWithId::<Tremolo>::new_default();
+    let tremolo2 = SoundProcessorWithId::<Tremolo>::new_default();
+    let widen = SoundProcessorWithId::<Widen>::new_default();
+
+    let noise_name = (&noise as &dyn AnySoundProcessor)
+        .as_graph_object()
+        .friendly_name();
+    let mut tremolo_names = vec![
+        (&tremolo1 as &dyn AnySoundProcessor)
+            .as_graph_object()
+            .friendly_name(),
+        (&tremolo2 as &dyn AnySoundProcessor)
+            .as_graph_object()
+            .friendly_name(),
+    ];
+    tremolo_names.sort();
+
+    graph.add_sound_processor(Box::new(noise));
+    graph.add_sound_processor(Box::new(tremolo1));
+    graph.add_sound_processor(Box::new(tremolo2));
+    graph.add_sound_processor(Box::new(widen));
+
+    // Everything is listed until something is typed
+    assert_eq!(listed_names(&graph, "").len(), 4);
+
+    let mut matches = listed_names(&graph, "trem");
+    matches.sort();
+    assert_eq!(matches, tremolo_names);
+
+    assert_eq!(listed_names(&graph, "noise"), vec![noise_name]);
+
+    assert!(listed_names(&graph, "xyz").is_empty());
+}
+
+#[test]
+fn test_jumping_to_processor_centers_it_in_view() {
+    let mut graph = SoundGraph::new();
+    let noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let noise_id = noise.id();
+    graph.add_sound_processor(Box::new(noise));
+
+    let mut positions = SoundObjectPositions::new();
+    let body_rect = egui::Rect::from_min_size(egui::pos2(1000.0, 2000.0), egui::vec2(100.0, 50.0));
+    positions.record_processor(noise_id, body_rect, body_rect);
+
+    let mut layout = StackedLayout::new();
+    layout.regenerate(&graph, &positions);
+    let origin_before = layout.find_group(noise_id).unwrap().origin();
+
+    let view = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(800.0, 600.0));
+    assert!(jump_to_processor(noise_id, &mut layout, &positions, view));
+
+    // The group moves by however far the processor was from the center
+    let origin_after = layout.find_group(noise_id).unwrap().origin();
+    assert_eq!(
+        origin_after - origin_before,
+        view.center() - body_rect.center()
+    );
+}
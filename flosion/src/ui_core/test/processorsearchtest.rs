@@ -0,0 +1,36 @@
+use crate::{
+    core::sound::{soundgraph::SoundGraph, soundprocessor::SoundProcessorWithId},
+    objects::{gain::Gain, tremolo::Tremolo},
+    ui_core::{processorsearch::find_processors_by_name, soundgraphuinames::SoundGraphUiNames},
+};
+
+#[test]
+fn test_search_finds_processors_by_name_substring() {
+    let mut graph = SoundGraph::new();
+    let gain_a = SoundProcessorWithId::<Gain>::new_default();
+    let gain_b = SoundProcessorWithId::<Gain>::new_default();
+    let tremolo = SoundProcessorWithId::<Tremolo>::new_default();
+    let id_a = gain_a.id();
+    let id_b = gain_b.id();
+    let tremolo_id = tremolo.id();
+    graph.add_sound_processor(Box::new(gain_a));
+    graph.add_sound_processor(Box::new(gain_b));
+    graph.add_sound_processor(Box::new(tremolo));
+
+    let mut names = SoundGraphUiNames::new();
+    names.cleanup(&graph);
+    names.rename_sound_processor(id_a, "Bass level");
+    names.rename_sound_processor(id_b, "Lead level");
+
+    // A unique substring finds exactly the one processor, ignoring case
+    assert_eq!(find_processors_by_name(&names, "lead"), vec![id_b]);
+    assert_eq!(find_processors_by_name(&names, "TREM"), vec![tremolo_id]);
+
+    // A shared substring finds every match, sorted by name
+    assert_eq!(find_processors_by_name(&names, "level"), vec![id_a, id_b]);
+
+    // Nothing matches an empty query or an unknown name
+    assert_eq!(find_processors_by_name(&names, ""), vec![]);
+    assert_eq!(find_processors_by_name(&names, "  "), vec![]);
+    assert_eq!(find_processors_by_name(&names, "reverb"), vec![]);
+}
@@ -0,0 +1,113 @@
+use crate::{
+    core::sound::{
+        expression::ExpressionParameterTarget,
+        soundgraph::SoundGraph,
+        soundinput::{AnyProcessorInput, SoundInputLocation},
+        soundprocessor::SoundProcessorWithId,
+    },
+    objects::{stereowidth::StereoWidth, tremolo::Tremolo, whitenoise::WhiteNoise, widen::Widen},
+    ui_core::{
+        interactions::replaceprocessor::{can_replace_processor, replace_processor_in_graph},
+        soundobjectpositions::SoundObjectPositions,
+        stackedlayout::stackedlayout::StackedLayout,
+    },
+};
+
+#[test]
+fn test_replacing_processor_keeps_connections() {
+    let mut graph = SoundGraph::new();
+
+    // noise -> tremolo -> widen -> tremolo
+    let source = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let mut upstream = SoundProcessorWithId::<Tremolo>::new_default();
+    let old = SoundProcessorWithId::<Widen>::new_default();
+    let downstream = SoundProcessorWithId::<Tremolo>::new_default();
+
+    let source_id = source.id();
+    let upstream_id = upstream.id();
+    let old_id = old.id();
+    let downstream_id = downstream.id();
+    let upstream_input = SoundInputLocation::new(upstream_id, upstream.input.id());
+    let old_input = SoundInputLocation::new(old_id, old.input.id());
+    let downstream_input = SoundInputLocation::new(downstream_id, downstream.input.id());
+
+    // The upstream tremolo's rate depends on the time of the processor
+    // being replaced
+    upstream
+        .rate
+        .add_target(ExpressionParameterTarget::ProcessorTime(old_id));
+
+    graph.add_sound_processor(Box::new(source));
+    graph.add_sound_processor(Box::new(upstream));
+    graph.add_sound_processor(Box::new(old));
+    graph.add_sound_processor(Box::new(downstream));
+
+    graph
+        .connect_sound_input(upstream_input, source_id)
+        .unwrap();
+    graph.connect_sound_input(old_input, upstream_id).unwrap();
+    graph.connect_sound_input(downstream_input, old_id).unwrap();
+
+    let mut layout = StackedLayout::new();
+    layout.regenerate(&graph, &SoundObjectPositions::new());
+    assert!(layout.check_invariants(&graph));
+
+    let new = SoundProcessorWithId::<StereoWidth>::new_default();
+    let new_id = new.id();
+    let new_input = SoundInputLocation::new(new_id, new.input.id());
+
+    assert!(can_replace_processor(
+        graph.sound_processor(old_id).unwrap(),
+        &new
+    ));
+
+    replace_processor_in_graph(&mut graph, old_id, Box::new(new)).unwrap();
+    layout.replace_processor(old_id, new_id);
+
+    assert_eq!(graph.validate(), Ok(()));
+    assert!(!graph.contains(old_id));
+
+    // The new processor sits between the same upstream and downstream processors
+    assert_eq!(
+        graph.with_sound_input(new_input, |i| i.target()),
+        Some(Some(upstream_id))
+    );
+    assert_eq!(
+        graph.with_sound_input(downstream_input, |i| i.target()),
+        Some(Some(new_id))
+    );
+    assert_eq!(
+        graph.with_sound_input(upstream_input, |i| i.target()),
+        Some(Some(source_id))
+    );
+
+    // The expression now depends on the new processor's time instead
+    let mapping_targets: Vec<ExpressionParameterTarget> = graph
+        .sound_processor(upstream_id)
+        .unwrap()
+        .downcast::<Tremolo>()
+        .unwrap()
+        .rate
+        .mapping()
+        .items()
+        .values()
+        .cloned()
+        .collect();
+    assert_eq!(
+        mapping_targets,
+        vec![ExpressionParameterTarget::ProcessorTime(new_id)]
+    );
+
+    // The new processor takes the old one's place in the layout
+    assert!(layout.check_invariants(&graph));
+    assert_eq!(layout.processor_above(new_id), Some(upstream_id));
+    assert_eq!(layout.processor_below(new_id), Some(downstream_id));
+}
+
+#[test]
+fn test_processors_with_different_inputs_cannot_be_replaced() {
+    let widen = SoundProcessorWithId::<Widen>::new_default();
+    let noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+
+    assert!(!can_replace_processor(&widen, &noise));
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+
+use crate::{
+    core::sound::{
+        soundgraph::SoundGraph,
+        soundinput::{AnyProcessorInput, SoundInputLocation},
+        soundprocessor::SoundProcessorWithId,
+    },
+    objects::{crossfade::Crossfade, gain::Gain},
+    ui_core::soundgraphuinames::SoundGraphUiNames,
+};
+
+#[test]
+fn test_new_processors_get_distinct_friendly_names() {
+    let mut graph = SoundGraph::new();
+    let gain_a = SoundProcessorWithId::<Gain>::new_default();
+    let gain_b = SoundProcessorWithId::<Gain>::new_default();
+    let id_a = gain_a.id();
+    let id_b = gain_b.id();
+    graph.add_sound_processor(Box::new(gain_a));
+    graph.add_sound_processor(Box::new(gain_b));
+
+    let mut names = SoundGraphUiNames::new();
+    names.cleanup(&graph);
+
+    let name_a = names.sound_processor(id_a).unwrap().to_string();
+    let name_b = names.sound_processor(id_b).unwrap().to_string();
+    assert_eq!(
+        HashSet::from([name_a.as_str(), name_b.as_str()]),
+        HashSet::from(["Gain 1", "Gain 2"])
+    );
+
+    // A third processor added later continues the numbering
+    let gain_c = SoundProcessorWithId::<Gain>::new_default();
+    let id_c = gain_c.id();
+    graph.add_sound_processor(Box::new(gain_c));
+    names.cleanup(&graph);
+    assert_eq!(names.sound_processor(id_c), Some("Gain 3"));
+
+    // Existing names are left alone
+    assert_eq!(names.sound_processor(id_a), Some(name_a.as_str()));
+    assert_eq!(names.sound_processor(id_b), Some(name_b.as_str()));
+    names.check_invariants(&graph);
+}
+
+#[test]
+fn test_renaming_to_an_existing_name_is_disambiguated() {
+    let mut graph = SoundGraph::new();
+    let gain_a = SoundProcessorWithId::<Gain>::new_default();
+    let gain_b = SoundProcessorWithId::<Gain>::new_default();
+    let gain_c = SoundProcessorWithId::<Gain>::new_default();
+    let id_a = gain_a.id();
+    let id_b = gain_b.id();
+    let id_c = gain_c.id();
+    graph.add_sound_processor(Box::new(gain_a));
+    graph.add_sound_processor(Box::new(gain_b));
+    graph.add_sound_processor(Box::new(gain_c));
+
+    let mut names = SoundGraphUiNames::new();
+    names.cleanup(&graph);
+
+    assert_eq!(names.rename_sound_processor(id_a, "Lead"), "Lead");
+    assert_eq!(names.rename_sound_processor(id_b, "Lead"), "Lead 2");
+    assert_eq!(names.rename_sound_processor(id_c, " Lead "), "Lead 3");
+    assert_eq!(names.sound_processor(id_a), Some("Lead"));
+
+    // A name ending in a number which is taken counts up from it
+    assert_eq!(names.rename_sound_processor(id_a, "Lead 2"), "Lead 4");
+
+    // Renaming a processor to its own name keeps it
+    assert_eq!(names.rename_sound_processor(id_c, "Lead 3"), "Lead 3");
+
+    // Empty names are ignored
+    assert_eq!(names.rename_sound_processor(id_c, "  "), "Lead 3");
+}
+
+#[test]
+fn test_default_input_names_are_distinct_within_processor() {
+    let mut graph = SoundGraph::new();
+    let crossfade = SoundProcessorWithId::<Crossfade>::new_default();
+    let a = SoundInputLocation::new(crossfade.id(), crossfade.input_a.id());
+    let b = SoundInputLocation::new(crossfade.id(), crossfade.input_b.id());
+    graph.add_sound_processor(Box::new(crossfade));
+
+    let mut names = SoundGraphUiNames::new();
+    names.cleanup(&graph);
+
+    assert_eq!(
+        HashSet::from([names.sound_input(a).unwrap(), names.sound_input(b).unwrap()]),
+        HashSet::from(["Input 1", "Input 2"])
+    );
+}
@@ -0,0 +1,49 @@
+use eframe::egui;
+use hashstash::Stash;
+
+use crate::{
+    core::sound::{
+        soundgraph::SoundGraph,
+        soundprocessor::{AnySoundProcessor, SoundProcessorWithId},
+    },
+    objects::whitenoise::WhiteNoise,
+    ui_core::{
+        arguments::ParsedArguments, factories::Factories, soundobjectuistate::SoundObjectUiStates,
+        stashing::UiUnstashingContext,
+    },
+};
+
+#[test]
+fn test_color_and_label_round_trip_through_stash() {
+    let factories = Factories::new_all_objects();
+    let mut graph = SoundGraph::new();
+
+    let noise = SoundProcessorWithId::<WhiteNoise>::new_default();
+    let noise_id = noise.id();
+    let noise_object = (&noise as &dyn AnySoundProcessor).as_graph_object();
+    let ui_state = factories
+        .sound_uis()
+        .get(noise_object.get_dynamic_type())
+        .make_ui_state(noise_object, &ParsedArguments::new_empty())
+        .unwrap();
+    graph.add_sound_processor(Box::new(noise));
+
+    let mut states = SoundObjectUiStates::new();
+    states.set_object_data(noise_id.into(), ui_state);
+
+    // Nothing is labelled to begin with
+    assert_eq!(states.get_object_label(noise_id.into()), "");
+
+    let color = egui::Color32::from_rgb(12, 34, 56);
+    states.set_object_color(noise_id.into(), color);
+    states.set_object_label(noise_id.into(), "drums".to_string());
+
+    let stash = Stash::new();
+    let handle = stash.stash(&states);
+    let unstashed: SoundObjectUiStates = stash
+        .unstash_with_context(&handle, UiUnstashingContext::new(&factories, &graph))
+        .unwrap();
+
+    assert_eq!(unstashed.get_object_color(noise_id.into()), color);
+    assert_eq!(unstashed.get_object_label(noise_id.into()), "drums");
+}
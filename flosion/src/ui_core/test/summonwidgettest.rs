@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use crate::{
+    core::{
+        expression::expressionnode::ExpressionNodeWithId,
+        objecttype::{ObjectType, WithObjectType},
+        sound::soundprocessor::SoundProcessorWithId,
+    },
+    objects::{mixer::Mixer, purefunctions::Constant},
+    ui_core::{
+        arguments::Argument,
+        factories::Factories,
+        object_ui::SummonCategory,
+        summon_widget::{
+            score_match, SummonHistories, SummonHistory, SummonWidget, SummonWidgetState,
+            SummonWidgetStateBuilder,
+        },
+    },
+};
+
+/// A representative mix of sound processor and expression node names
+const NAMES: &[&str] = &[
+    "add",
+    "adsr",
+    "arpeggiator",
+    "audioclip",
+    "chorus",
+    "compressor",
+    "constant",
+    "crossfade",
+    "divide",
+    "downmix",
+    "ensemble",
+    "exponentialapproach",
+    "formantfilter",
+    "gain",
+    "highpass",
+    "integrator",
+    "keyboard",
+    "linearapproach",
+    "lowpass",
+    "max",
+    "min",
+    "mixer",
+    "modulo",
+    "multiply",
+    "noise",
+    "output",
+    "sampler1d",
+    "scatter",
+    "scheduler",
+    "slider",
+    "stereowidth",
+    "subtract",
+    "tremolo",
+    "wavegenerator",
+    "whitenoise",
+    "wrappingintegrator",
+];
+
+/// The name which is chosen for the given query
+fn best_match(query: &str) -> &'static str {
+    let mut builder = SummonWidgetStateBuilder::new(egui::pos2(0.0, 0.0));
+    for (i, name) in NAMES.iter().enumerate() {
+        builder.add_basic_name(name.to_string(), i);
+    }
+    let mut state = builder.build();
+    state.set_text(query.to_string());
+    let (index, _) = state.best_choice().unwrap();
+    NAMES[index]
+}
+
+#[test]
+fn test_full_names_rank_first() {
+    for name in NAMES {
+        assert_eq!(best_match(name), *name);
+    }
+}
+
+#[test]
+fn test_abbreviations_rank_intended_name_first() {
+    assert_eq!(best_match("mul"), "multiply");
+    assert_eq!(best_match("lpf"), "lowpass");
+    assert_eq!(best_match("hpf"), "highpass");
+    assert_eq!(best_match("wavgen"), "wavegenerator");
+    assert_eq!(best_match("arp"), "arpeggiator");
+    assert_eq!(best_match("kb"), "keyboard");
+    assert_eq!(best_match("wn"), "whitenoise");
+    assert_eq!(best_match("sub"), "subtract");
+}
+
+#[test]
+fn test_matching_is_case_insensitive() {
+    assert_eq!(best_match("MUL"), "multiply");
+    assert_eq!(score_match("Gain", "gain"), score_match("gain", "gain"));
+}
+
+#[test]
+fn test_arguments_do_not_affect_ranking() {
+    assert_eq!(best_match("mul 2"), "multiply");
+}
+
+#[test]
+fn test_shorter_names_win_ties() {
+    assert!(score_match("integ", "integrator") > score_match("integ", "integratorfoo"));
+}
+
+const ADD: ObjectType = ObjectType::new("add");
+const GAIN: ObjectType = ObjectType::new("gain");
+const MIXER: ObjectType = ObjectType::new("mixer");
+
+#[test]
+fn test_summoning_moves_object_to_front_of_recents() {
+    let mut history = SummonHistory::new();
+    assert_eq!(history.recency(ADD), None);
+
+    history.record_summoned(ADD);
+    history.record_summoned(GAIN);
+    history.record_summoned(MIXER);
+    assert_eq!(history.recency(MIXER), Some(0));
+    assert_eq!(history.recency(GAIN), Some(1));
+    assert_eq!(history.recency(ADD), Some(2));
+
+    // Summoning again moves it to the front without duplicating it
+    history.record_summoned(ADD);
+    assert_eq!(history.recency(ADD), Some(0));
+    assert_eq!(history.recency(MIXER), Some(1));
+    assert_eq!(history.recency(GAIN), Some(2));
+}
+
+#[test]
+fn test_oldest_recents_are_forgotten() {
+    let mut history = SummonHistory::new();
+    for name in NAMES {
+        history.record_summoned(ObjectType::new(name));
+    }
+    assert_eq!(
+        history.recency(ObjectType::new(NAMES[NAMES.len() - 1])),
+        Some(0)
+    );
+    assert_eq!(history.recency(ObjectType::new(NAMES[0])), None);
+}
+
+/// The name listed first before anything is typed
+fn first_listed(history: &SummonHistory) -> &'static str {
+    let mut builder = SummonWidgetStateBuilder::new(egui::pos2(0.0, 0.0));
+    builder.with_history(history, |t| Some(*t));
+    for name in NAMES {
+        builder.add_basic_name(name.to_string(), ObjectType::new(name));
+    }
+    let state = builder.build();
+    state.best_choice().unwrap().0.name()
+}
+
+#[test]
+fn test_favorites_and_recents_are_listed_first() {
+    let mut history = SummonHistory::new();
+    assert_eq!(first_listed(&history), "add");
+
+    history.record_summoned(MIXER);
+    history.record_summoned(GAIN);
+    assert_eq!(first_listed(&history), "gain");
+
+    history.toggle_favorite(MIXER);
+    assert!(history.is_favorite(MIXER));
+    assert_eq!(first_listed(&history), "mixer");
+
+    history.toggle_favorite(MIXER);
+    assert!(!history.is_favorite(MIXER));
+    assert_eq!(first_listed(&history), "gain");
+}
+
+/// Storage which keeps everything in memory, like the app's storage
+/// between one session and the next
+struct MemoryStorage {
+    values: HashMap<String, String>,
+}
+
+impl eframe::Storage for MemoryStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.values.insert(key.to_string(), value);
+    }
+
+    fn flush(&mut self) {}
+}
+
+#[test]
+fn test_summon_histories_are_saved_and_loaded() {
+    let mut storage = MemoryStorage {
+        values: HashMap::new(),
+    };
+
+    // Nothing saved yet
+    let histories = SummonHistories::load(&storage);
+    assert_eq!(histories.sound.recency(ADD), None);
+    assert!(!histories.expression.is_favorite(ADD));
+
+    let mut histories = SummonHistories::new();
+    histories.sound.record_summoned(MIXER);
+    histories.sound.record_summoned(GAIN);
+    histories.sound.toggle_favorite(MIXER);
+    histories.expression.record_summoned(ADD);
+    histories.save(&mut storage);
+
+    let loaded = SummonHistories::load(&storage);
+    assert_eq!(loaded.sound.recency(GAIN), Some(0));
+    assert_eq!(loaded.sound.recency(MIXER), Some(1));
+    assert_eq!(loaded.sound.recency(ADD), None);
+    assert!(loaded.sound.is_favorite(MIXER));
+    assert!(!loaded.sound.is_favorite(GAIN));
+    assert_eq!(loaded.expression.recency(ADD), Some(0));
+    assert_eq!(loaded.expression.recency(GAIN), None);
+    assert!(!loaded.expression.is_favorite(MIXER));
+}
+
+/// The summon widget for sound objects, as it is first opened
+fn sound_object_summon_widget(factories: &Factories) -> SummonWidgetState<ObjectType> {
+    let mut builder = SummonWidgetStateBuilder::new(egui::pos2(0.0, 0.0));
+    for object_ui in factories.sound_uis().all_object_uis() {
+        for name in object_ui.summon_names() {
+            builder.add_name_with_arguments(
+                name.to_string(),
+                object_ui.summon_arguments(),
+                object_ui.object_type(),
+                object_ui.summon_category(),
+            );
+        }
+    }
+    builder.build()
+}
+
+/// The category under which the given name is listed
+fn listed_category(
+    groups: &[(Option<SummonCategory>, Vec<&str>)],
+    name: &str,
+) -> Option<SummonCategory> {
+    groups
+        .iter()
+        .find(|(_, names)| names.contains(&name))
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_candidates_are_bucketed_by_category() {
+    let factories = Factories::new_all_objects();
+    let state = sound_object_summon_widget(&factories);
+    let groups = state.listed_groups();
+
+    // Every category is listed once, in order
+    let categories: Vec<SummonCategory> = groups.iter().filter_map(|(c, _)| *c).collect();
+    let mut sorted_categories = categories.clone();
+    sorted_categories.sort();
+    sorted_categories.dedup();
+    assert_eq!(categories, sorted_categories);
+
+    use SummonCategory::*;
+    assert_eq!(listed_category(&groups, "wavegenerator"), Some(Oscillators));
+    assert_eq!(listed_category(&groups, "whitenoise"), Some(Oscillators));
+    assert_eq!(listed_category(&groups, "formantfilter"), Some(Filters));
+    assert_eq!(listed_category(&groups, "vowel"), Some(Filters));
+    assert_eq!(listed_category(&groups, "gain"), Some(Dynamics));
+    assert_eq!(listed_category(&groups, "compressor"), Some(Dynamics));
+    assert_eq!(listed_category(&groups, "output"), Some(Utilities));
+    assert_eq!(listed_category(&groups, "mixer"), Some(Utilities));
+}
+
+#[test]
+fn test_expression_nodes_have_categories() {
+    let factories = Factories::new_all_objects();
+    let category_of = |name: &str| {
+        factories
+            .expression_uis()
+            .all_object_uis()
+            .find(|ui| ui.summon_names().contains(&name))
+            .unwrap()
+            .summon_category()
+    };
+    assert_eq!(category_of("sinewave"), SummonCategory::Oscillators);
+    assert_eq!(category_of("linearapproach"), SummonCategory::Filters);
+    assert_eq!(category_of("add"), SummonCategory::Math);
+    assert_eq!(category_of("trigger"), SummonCategory::Logic);
+    assert_eq!(category_of("constant"), SummonCategory::Utilities);
+}
+
+#[test]
+fn test_candidates_are_not_grouped_while_typing() {
+    let factories = Factories::new_all_objects();
+    let mut state = sound_object_summon_widget(&factories);
+    state.set_text("gai".to_string());
+    let groups = state.listed_groups();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].0, None);
+    assert_eq!(groups[0].1[0], "gain");
+}
+
+/// The summon widget for expression nodes, as it is first opened
+fn expression_node_summon_widget(factories: &Factories) -> SummonWidgetState<ObjectType> {
+    let mut builder = SummonWidgetStateBuilder::new(egui::pos2(0.0, 0.0));
+    for object_ui in factories.expression_uis().all_object_uis() {
+        for name in object_ui.summon_names() {
+            builder.add_name_with_arguments(
+                name.to_string(),
+                object_ui.summon_arguments(),
+                object_ui.object_type(),
+                object_ui.summon_category(),
+            );
+        }
+    }
+    builder.build()
+}
+
+#[test]
+fn test_inline_argument_initializes_constant() {
+    let factories = Factories::new_all_objects();
+    let mut state = expression_node_summon_widget(&factories);
+    state.set_text("constant 0.5".to_string());
+
+    let (object_type, args) = state.best_choice().unwrap();
+    assert_eq!(object_type.name(), Constant::TYPE.name());
+    assert!(args.contains(Constant::ARG_VALUE.name()));
+
+    let object = factories
+        .expression_objects()
+        .create(object_type.name(), &args);
+    let constant = object
+        .as_any()
+        .downcast_ref::<ExpressionNodeWithId<Constant>>()
+        .unwrap();
+    assert_eq!(constant.value(), 0.5);
+}
+
+#[test]
+fn test_inline_argument_sets_number_of_mixer_inputs() {
+    let factories = Factories::new_all_objects();
+    let mut state = sound_object_summon_widget(&factories);
+    state.set_text("mixer 4".to_string());
+
+    let (object_type, args) = state.best_choice().unwrap();
+    let object = factories.sound_objects().create(object_type.name(), &args);
+    let mixer = object
+        .as_any()
+        .downcast_ref::<SoundProcessorWithId<Mixer>>()
+        .unwrap();
+    assert_eq!(mixer.inputs().len(), 4);
+}
+
+#[test]
+fn test_omitted_arguments_are_absent() {
+    let factories = Factories::new_all_objects();
+    let mut state = expression_node_summon_widget(&factories);
+    state.set_text("constant".to_string());
+    let (_, args) = state.best_choice().unwrap();
+    assert!(!args.contains(Constant::ARG_VALUE.name()));
+}
+
+/// Show the summon widget for a single frame while the given events
+/// are received
+fn run_frame(ctx: &egui::Context, state: &mut SummonWidgetState<usize>, events: Vec<egui::Event>) {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(800.0, 600.0),
+        )),
+        events,
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.add(SummonWidget::new(state));
+        });
+    });
+}
+
+fn key_press(key: egui::Key) -> egui::Event {
+    egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::NONE,
+    }
+}
+
+fn open_summon_widget(ctx: &egui::Context) -> SummonWidgetState<usize> {
+    let mut builder = SummonWidgetStateBuilder::new(egui::pos2(0.0, 0.0));
+    for (i, name) in NAMES.iter().enumerate() {
+        builder.add_basic_name(name.to_string(), i);
+    }
+    let mut state = builder.build();
+    run_frame(ctx, &mut state, Vec::new());
+    state
+}
+
+#[test]
+fn test_arrow_keys_navigate_to_chosen_entry() {
+    let ctx = egui::Context::default();
+    let mut state = open_summon_widget(&ctx);
+
+    // Entries are listed alphabetically: add, adsr, arpeggiator, ...
+    for _ in 0..4 {
+        run_frame(&ctx, &mut state, vec![key_press(egui::Key::ArrowDown)]);
+    }
+    run_frame(&ctx, &mut state, vec![key_press(egui::Key::ArrowUp)]);
+    assert!(state.final_choice().is_none());
+
+    run_frame(&ctx, &mut state, vec![key_press(egui::Key::Enter)]);
+    let (index, _) = state.final_choice().unwrap();
+    assert_eq!(NAMES[index], "arpeggiator");
+}
+
+#[test]
+fn test_typing_while_navigating_filters_entries() {
+    let ctx = egui::Context::default();
+    let mut state = open_summon_widget(&ctx);
+
+    run_frame(&ctx, &mut state, vec![key_press(egui::Key::ArrowDown)]);
+    run_frame(&ctx, &mut state, vec![egui::Event::Text("mul".to_string())]);
+    run_frame(&ctx, &mut state, vec![key_press(egui::Key::Enter)]);
+    let (index, _) = state.final_choice().unwrap();
+    assert_eq!(NAMES[index], "multiply");
+}
+
+#[test]
+fn test_escape_cancels() {
+    let ctx = egui::Context::default();
+    let mut state = open_summon_widget(&ctx);
+
+    run_frame(&ctx, &mut state, vec![key_press(egui::Key::ArrowDown)]);
+    run_frame(&ctx, &mut state, vec![key_press(egui::Key::Escape)]);
+    assert!(state.was_cancelled());
+    assert!(state.final_choice().is_none());
+}
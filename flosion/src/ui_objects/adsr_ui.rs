@@ -2,9 +2,13 @@ use crate::{
     core::sound::soundprocessor::SoundProcessorWithId,
     objects::adsr::ADSR,
     ui_core::{
-        arguments::ParsedArguments, expressionplot::PlotConfig, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -36,6 +40,10 @@ impl SoundObjectUi for ADSRUi {
         &["adsr"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Dynamics
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
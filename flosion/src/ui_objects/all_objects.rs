@@ -11,30 +11,55 @@ use crate::{
 
 use super::{
     adsr_ui::ADSRUi,
+    arpeggiator_ui::ArpeggiatorUi,
     audioclip_ui::AudioClipUi,
+    automation_ui::AutomationUi,
+    binaural_ui::BinauralUi,
+    chorus_ui::ChorusUi,
+    compressor_ui::CompressorUi,
+    crossfade_ui::CrossfadeUi,
     definitions_ui::DefinitionsUi,
+    downmix_ui::DownmixUi,
     ensemble_ui::EnsembleUi,
+    formantfilter_ui::FormantFilterUi,
+    gain_ui::GainUi,
+    haas_ui::HaasUi,
     input_ui::InputUi,
+    karplusstrong_ui::KarplusStrongUi,
     keyboard_ui::KeyboardUi,
+    lfo_ui::LfoUi,
+    macrocall_ui::MacroCallUi,
     mixer_ui::MixerUi,
+    noise_ui::NoiseUi,
     oscilloscope_ui::OscilloscopeUi,
     output_ui::OutputUi,
     pure_function_uis::{
-        AbsUi, AddUi, CeilUi, ConstantUi, CopysignUi, CosUi, CosineWaveUi, DivideUi, Exp10Ui,
+        AbsUi, AddUi, BlockSizeUi, CeilUi, ConstantUi, CopysignUi, CosUi, CosineWaveUi, DivideUi, Exp10Ui,
         Exp2Ui, ExpUi, FloorUi, FractUi, LerpUi, Log10Ui, Log2Ui, LogUi, MultiplyUi, NegateUi,
-        PowUi, RoundUi, SawWaveUi, SignumUi, SinUi, SineWaveUi, SliderUi, SquareWaveUi, SubtractUi,
+        PowUi, RoundUi, SampleRateUi, SawWaveUi, SignumUi, SinUi, SineWaveUi, SliderUi, SquareWaveUi, SubtractUi,
         TriangleWaveUi, TruncUi,
     },
+    quantizetoscale_ui::QuantizeToScaleUi,
     readwritewaveform_ui::ReadWriteWaveformUi,
     resampler_ui::ResamplerUi,
     sampler1d_ui::Sampler1dUi,
+    sampler2d_ui::Sampler2dUi,
     scatter_ui::ScatterUi,
     scheduler_ui::SchedulerUi,
+    slew_ui::SlewUi,
+    spectralgate_ui::SpectralGateUi,
     stateful_function_uis::{
-        ExponentialApproachUi, IntegratorUi, LinearApproachUi, WrappingIntegratorUi,
+        ExponentialApproachUi, IntegratorUi, LinearApproachUi, RandomUi, TriggerUi,
+        WrappingIntegratorUi,
     },
+    stereowidth_ui::StereoWidthUi,
+    tap_ui::TapUi,
+    tremolo_ui::TremoloUi,
+    vocoder_ui::VocoderUi,
     wavegenerator_ui::WaveGeneratorUi,
+    wavetable_ui::WavetableUi,
     whitenoise_ui::WhiteNoiseUi,
+    widen_ui::WidenUi,
     writewaveform_ui::WriteWaveformUi,
 };
 
@@ -91,22 +116,42 @@ pub(crate) fn all_sound_graph_objects() -> (SoundObjectFactory, SoundObjectUiFac
     helper.register::<OutputUi>();
     helper.register::<InputUi>();
     helper.register::<KeyboardUi>();
+    helper.register::<ArpeggiatorUi>();
     // helper.register::<RecorderUi>();
     helper.register::<OscilloscopeUi>();
+    helper.register::<TapUi>();
 
     // Dynamic sound processors
     helper.register::<ADSRUi>();
     helper.register::<AudioClipUi>();
+    helper.register::<BinauralUi>();
+    helper.register::<ChorusUi>();
+    helper.register::<CompressorUi>();
+    helper.register::<CrossfadeUi>();
     helper.register::<DefinitionsUi>();
+    helper.register::<DownmixUi>();
     helper.register::<EnsembleUi>();
+    helper.register::<FormantFilterUi>();
+    helper.register::<GainUi>();
+    helper.register::<HaasUi>();
+    helper.register::<KarplusStrongUi>();
+    helper.register::<LfoUi>();
     // helper.register::<MelodyUi>();
     helper.register::<MixerUi>();
+    helper.register::<NoiseUi>();
     helper.register::<ReadWriteWaveformUi>();
     helper.register::<ResamplerUi>();
     helper.register::<ScatterUi>();
     helper.register::<SchedulerUi>();
+    helper.register::<SlewUi>();
+    helper.register::<SpectralGateUi>();
+    helper.register::<StereoWidthUi>();
+    helper.register::<TremoloUi>();
+    helper.register::<VocoderUi>();
     helper.register::<WaveGeneratorUi>();
+    helper.register::<WavetableUi>();
     helper.register::<WhiteNoiseUi>();
+    helper.register::<WidenUi>();
     helper.register::<WriteWaveformUi>();
 
     (object_factory, ui_factory)
@@ -121,12 +166,20 @@ pub(crate) fn all_expression_graph_objects() -> (ExpressionObjectFactory, Expres
 
     helper.register::<ConstantUi>();
     helper.register::<SliderUi>();
+    helper.register::<SampleRateUi>();
+    helper.register::<BlockSizeUi>();
+    helper.register::<MacroCallUi>();
 
     helper.register::<LinearApproachUi>();
     helper.register::<ExponentialApproachUi>();
     helper.register::<IntegratorUi>();
     helper.register::<WrappingIntegratorUi>();
+    helper.register::<TriggerUi>();
+    helper.register::<RandomUi>();
+    helper.register::<AutomationUi>();
     helper.register::<Sampler1dUi>();
+    helper.register::<Sampler2dUi>();
+    helper.register::<QuantizeToScaleUi>();
 
     helper.register::<NegateUi>();
     helper.register::<FloorUi>();
@@ -0,0 +1,114 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::arpeggiator::{Arpeggiator, ArpeggiatorPattern},
+    ui_core::{
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        beatsyncui::beat_sync_ui,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+use super::keyboard_ui::play_with_computer_keyboard;
+
+#[derive(Default)]
+pub struct ArpeggiatorUi {}
+
+impl SoundObjectUi for ArpeggiatorUi {
+    type ObjectType = SoundProcessorWithId<Arpeggiator>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        arpeggiator: &mut SoundProcessorWithId<Arpeggiator>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Arpeggiator")
+            .add_sound_input(&arpeggiator.input, "input")
+            .add_argument(&arpeggiator.note_frequency, "notefrequency")
+            .add_expression(
+                &arpeggiator.rate,
+                &["rate"],
+                PlotConfig::new().linear_vertical_range(0.0..=32.0),
+            )
+            .show_with(
+                arpeggiator,
+                ui,
+                ctx,
+                graph_ui_state,
+                |arpeggiator, ui, _uistate| {
+                    ui.horizontal(|ui| {
+                        for pattern in ArpeggiatorPattern::all_values() {
+                            if ui
+                                .selectable_label(arpeggiator.pattern() == *pattern, pattern.name())
+                                .clicked()
+                            {
+                                arpeggiator.set_pattern(*pattern);
+                            }
+                        }
+                    });
+
+                    // When nothing is connected to the rate expression,
+                    // its constant value can be set directly
+                    let result = &mut arpeggiator.rate.graph_mut().results_mut()[0];
+                    if result.target().is_none() {
+                        let mut rate = result.default_value();
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut rate, 0.5..=32.0)
+                                    .logarithmic(true)
+                                    .text("steps/s"),
+                            )
+                            .changed()
+                        {
+                            result.set_default_value(rate);
+                        }
+                    }
+
+                    let mut beat_sync = arpeggiator.beat_sync();
+                    if beat_sync_ui(ui, &mut beat_sync) {
+                        arpeggiator.set_beat_sync(beat_sync);
+                    }
+
+                    play_with_computer_keyboard(
+                        ui,
+                        egui::Id::new("arpeggiator_has_focus").with(arpeggiator.id()),
+                        |id, f| arpeggiator.start_key(id, f),
+                        |id| arpeggiator.release_key(id),
+                        || arpeggiator.release_all_keys(),
+                    );
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["arpeggiator", "arp"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Arpeggiator::ARG_PATTERN)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -1,11 +1,18 @@
+use std::path::Path;
+
 use eframe::egui;
 use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
 
 use crate::{
-    core::{audiofileio::load_audio_file, sound::soundprocessor::SoundProcessorWithId},
-    objects::audioclip::AudioClip,
+    core::{
+        audiofileio::{default_base_directory, AudioFilePath},
+        samplefrequency::SAMPLE_FREQUENCY,
+        sound::soundprocessor::SoundProcessorWithId,
+    },
+    objects::audioclip::{AudioClip, AUDIOCLIP_MAX_SPEED, AUDIOCLIP_MIN_SPEED},
     ui_core::{
         arguments::{ArgumentList, ParsedArguments},
+        object_ui::SummonCategory,
         soundgraphuicontext::SoundGraphUiContext,
         soundgraphuistate::SoundGraphUiState,
         soundobjectui::SoundObjectUi,
@@ -18,21 +25,64 @@ pub struct AudioClipUi {}
 
 pub struct AudioClipUiState {
     name: String,
+    /// Why the most recent attempt at loading a file into the audioclip
+    /// failed, if it did
+    load_error: Option<String>,
+}
+
+impl AudioClipUiState {
+    pub(crate) fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
 }
 
 impl Stashable for AudioClipUiState {
     fn stash(&self, stasher: &mut Stasher) {
+        // The load error is only of interest until the next attempt
+        // and isn't stashed
         stasher.string(&self.name);
     }
 }
 
 impl UnstashableInplace for AudioClipUiState {
     fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
-        unstasher.string_inplace(&mut self.name)?;
-        Ok(())
+        unstasher.string_inplace(&mut self.name)
     }
 }
 
+/// Load the given audio file into the audioclip, keeping track of any
+/// failure to do so in the ui state
+pub(crate) fn load_file(audioclip: &mut AudioClip, state: &mut AudioClipUiState, path: &Path) {
+    match audioclip.load_file(AudioFilePath::new(path, &default_base_directory())) {
+        Ok(()) => {
+            state.name = file_name(path);
+            state.load_error = None;
+        }
+        Err(e) => {
+            state.load_error = Some(load_error_message(path, &e));
+        }
+    }
+}
+
+/// Ask the user for an audio file and load it into the audioclip
+fn pick_and_load_file(audioclip: &mut AudioClip, state: &mut AudioClipUiState) {
+    let dialog = rfd::FileDialog::new()
+        .add_filter("Audio files", &["aiff", "ogg", "wav", "flac", "mp3", "m4a"]);
+    if let Some(path) = dialog.pick_file() {
+        load_file(audioclip, state, &path);
+    }
+}
+
+fn load_error_message(path: &Path, error: &str) -> String {
+    format!("Failed to load \"{}\": {}", path.display(), error)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
 impl SoundObjectUi for AudioClipUi {
     type ObjectType = SoundProcessorWithId<AudioClip>;
     type StateType = AudioClipUiState;
@@ -61,23 +111,61 @@ impl SoundObjectUi for AudioClipUi {
                     // TODO
                     // - button to save to a file
 
-                    if ui.button("Load").clicked() {
-                        let dialog = rfd::FileDialog::new().add_filter(
-                            "Audio files",
-                            &["aiff", "ogg", "wav", "flac", "mp3", "m4a"],
+                    if let Some(path) = audioclip.missing_file().map(Path::to_path_buf) {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            format!("Missing file \"{}\"", path.display()),
                         );
-                        if let Some(path) = dialog.pick_file() {
-                            println!("Loading audioclip from {}", path.display());
-                            match load_audio_file(&path) {
-                                Ok(buf) => {
-                                    audioclip.set_data(buf);
-                                    state.name =
-                                        path.file_name().unwrap().to_str().unwrap().to_string();
-                                }
-                                Err(e) => println!("Failed to load file: {}", e),
-                            }
+                        if ui.button("Relink").clicked() {
+                            pick_and_load_file(audioclip, state);
                         }
+                    } else if ui.button("Load").clicked() {
+                        pick_and_load_file(audioclip, state);
+                    }
+                    if let Some(error) = state.load_error() {
+                        ui.colored_label(egui::Color32::RED, error);
                     }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Loop crossfade");
+                        let mut crossfade = audioclip.loop_crossfade();
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut crossfade)
+                                    .range(0..=SAMPLE_FREQUENCY)
+                                    .suffix(" samples"),
+                            )
+                            .changed()
+                        {
+                            audioclip.set_loop_crossfade(crossfade);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let mut reverse = audioclip.reverse();
+                        if ui.checkbox(&mut reverse, "Reverse").changed() {
+                            audioclip.set_reverse(reverse);
+                        }
+                        ui.label("Speed");
+                        let mut speed = audioclip.speed();
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut speed)
+                                    .range(AUDIOCLIP_MIN_SPEED..=AUDIOCLIP_MAX_SPEED)
+                                    .speed(0.01)
+                                    .suffix("x"),
+                            )
+                            .changed()
+                        {
+                            audioclip.set_speed(speed);
+                        }
+                        if ui.button("Half").clicked() {
+                            audioclip.set_speed(0.5);
+                        }
+                        if ui.button("Normal").clicked() {
+                            audioclip.set_speed(1.0);
+                        }
+                    });
                 });
             },
         );
@@ -91,18 +179,26 @@ impl SoundObjectUi for AudioClipUi {
         &["audioclip"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
 
     fn make_ui_state(
         &self,
-        _handle: &Self::ObjectType,
-        _args: &ParsedArguments,
+        handle: &Self::ObjectType,
+        args: &ParsedArguments,
     ) -> Result<AudioClipUiState, ()> {
-        // TODO: use args
-        Ok(AudioClipUiState {
-            name: "".to_string(),
-        })
+        let path = args.get(&AudioClip::ARG_PATH);
+        let name = path.as_deref().map(file_name).unwrap_or_default();
+        // A missing file is already warned about by the audioclip itself
+        let load_error = match (&path, handle.load_error(), handle.missing_file()) {
+            (Some(path), Some(e), None) => Some(load_error_message(path, e)),
+            _ => None,
+        };
+        Ok(AudioClipUiState { name, load_error })
     }
 }
@@ -0,0 +1,294 @@
+use eframe::egui;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        automation::{AutomationLane, Breakpoint, Interpolation},
+        expression::expressionnode::ExpressionNodeWithId,
+    },
+    objects::automation::Automation,
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        expressiongraphuicontext::ExpressionGraphUiContext,
+        expressiongraphuistate::ExpressionGraphUiState,
+        expressionobjectui::ExpressionObjectUi,
+        expressionodeui::{DisplayStyle, ExpressionNodeUi},
+        lexicallayout::lexicallayout::ExpressionNodeLayout,
+        object_ui::SummonCategory,
+    },
+};
+
+/// How close in pixels the pointer must be to a breakpoint to grab it
+const GRAB_DISTANCE: f32 = 6.0;
+
+#[derive(Default)]
+pub struct AutomationUi {}
+
+pub struct AutomationUiState {
+    min_value: f32,
+    max_value: f32,
+    /// The number of bars shown
+    length: f64,
+    /// The index of the breakpoint last clicked or dragged
+    selected: Option<usize>,
+}
+
+impl Stashable for AutomationUiState {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.f32(self.min_value);
+        stasher.f32(self.max_value);
+        stasher.f64(self.length);
+    }
+}
+
+impl UnstashableInplace for AutomationUiState {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.f32_inplace(&mut self.min_value)?;
+        unstasher.f32_inplace(&mut self.max_value)?;
+        unstasher.f64_inplace(&mut self.length)?;
+        Ok(())
+    }
+}
+
+impl AutomationUiState {
+    fn lane_to_screen(&self, rect: egui::Rect, position: f64, value: f32) -> egui::Pos2 {
+        let x = (position / self.length) as f32;
+        let t = (value - self.min_value) / (self.max_value - self.min_value);
+        egui::pos2(
+            rect.left() + x * rect.width(),
+            rect.bottom() - t.clamp(0.0, 1.0) * rect.height(),
+        )
+    }
+
+    fn screen_to_lane(&self, rect: egui::Rect, pos: egui::Pos2) -> (f64, f32) {
+        let x = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        let t = ((rect.bottom() - pos.y) / rect.height()).clamp(0.0, 1.0);
+        (
+            x as f64 * self.length,
+            self.min_value + t * (self.max_value - self.min_value),
+        )
+    }
+
+    /// The index of the breakpoint within grabbing distance of the
+    /// given point on screen, if any
+    fn breakpoint_near(
+        &self,
+        lane: &AutomationLane,
+        rect: egui::Rect,
+        pos: egui::Pos2,
+    ) -> Option<usize> {
+        lane.breakpoints()
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                (
+                    i,
+                    self.lane_to_screen(rect, b.position, b.value).distance(pos),
+                )
+            })
+            .filter(|(_, d)| *d <= GRAB_DISTANCE)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(i, _)| i)
+    }
+}
+
+impl ExpressionObjectUi for AutomationUi {
+    type ObjectType = ExpressionNodeWithId<Automation>;
+    type StateType = AutomationUiState;
+
+    fn ui(
+        &self,
+        automation: &mut ExpressionNodeWithId<Automation>,
+        _graph_ui_state: &mut ExpressionGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        state: &mut AutomationUiState,
+    ) {
+        ExpressionNodeUi::new_named(
+            automation.id(),
+            "Automation".to_string(),
+            DisplayStyle::Framed,
+        )
+        .show_with(ui, ctx, |ui| {
+            if state
+                .selected
+                .is_some_and(|i| i >= automation.lane().breakpoints().len())
+            {
+                state.selected = None;
+            }
+
+            let (id, rect) = ui.allocate_space(egui::vec2(200.0, 100.0));
+            let painter = ui.painter();
+
+            painter.rect_filled(rect, egui::Rounding::ZERO, egui::Color32::BLACK);
+
+            // Bar lines
+            for bar in 1..(state.length.ceil() as usize) {
+                let x = state.lane_to_screen(rect, bar as f64, 0.0).x;
+                painter.vline(
+                    x,
+                    rect.y_range(),
+                    egui::Stroke::new(1.0, egui::Color32::DARK_GRAY),
+                );
+            }
+
+            // Draw the lane as it is played, one segment per pixel
+            let num_segments = (rect.width().ceil() as usize).max(1);
+            let lane = automation.lane();
+            if !lane.breakpoints().is_empty() {
+                let points: Vec<egui::Pos2> = (0..=num_segments)
+                    .map(|i| {
+                        let position = i as f64 / num_segments as f64 * state.length;
+                        state.lane_to_screen(rect, position, lane.value_at(position).unwrap())
+                    })
+                    .collect();
+                painter.add(egui::Shape::line(
+                    points,
+                    egui::Stroke::new(2.0, egui::Color32::WHITE),
+                ));
+            }
+
+            for (i, breakpoint) in lane.breakpoints().iter().enumerate() {
+                let color = if state.selected == Some(i) {
+                    egui::Color32::YELLOW
+                } else {
+                    egui::Color32::WHITE
+                };
+                painter.circle_filled(
+                    state.lane_to_screen(rect, breakpoint.position, breakpoint.value),
+                    3.0,
+                    color,
+                );
+            }
+
+            let r = ui.interact(rect, id, egui::Sense::click_and_drag());
+
+            if let Some(pos) = r.interact_pointer_pos() {
+                if r.drag_started() || r.clicked() || r.secondary_clicked() {
+                    state.selected = state.breakpoint_near(automation.lane(), rect, pos);
+                }
+                let (position, value) = state.screen_to_lane(rect, pos);
+                if r.secondary_clicked() {
+                    // Right click removes a breakpoint
+                    if let Some(i) = state.selected.take() {
+                        automation.lane_mut().remove_breakpoint(i);
+                        ctx.request_snapshot();
+                    }
+                } else if r.clicked() && state.selected.is_none() {
+                    // Clicking elsewhere adds a breakpoint
+                    let interpolation = automation
+                        .lane()
+                        .breakpoints()
+                        .iter()
+                        .rev()
+                        .find(|b| b.position <= position)
+                        .map_or(Interpolation::Linear, |b| b.interpolation);
+                    automation.lane_mut().add_breakpoint(Breakpoint::new(
+                        position,
+                        value,
+                        interpolation,
+                    ));
+                    state.selected = automation
+                        .lane()
+                        .breakpoints()
+                        .iter()
+                        .position(|b| b.position == position);
+                    ctx.request_snapshot();
+                } else if r.dragged() {
+                    if let Some(i) = state.selected {
+                        state.selected =
+                            Some(automation.lane_mut().move_breakpoint(i, position, value));
+                    }
+                }
+            }
+
+            if r.drag_stopped() {
+                ctx.request_snapshot();
+            }
+
+            if let Some(i) = state.selected {
+                ui.horizontal(|ui| {
+                    let current = automation.lane().breakpoints()[i].interpolation;
+                    for interpolation in Interpolation::ALL {
+                        if ui
+                            .selectable_label(current == interpolation, interpolation.name())
+                            .clicked()
+                            && current != interpolation
+                        {
+                            automation.lane_mut().set_interpolation(i, interpolation);
+                            ctx.request_snapshot();
+                        }
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                let mut tempo = automation.tempo();
+                let mut changed = ui
+                    .add(
+                        egui::DragValue::new(&mut tempo.beats_per_minute)
+                            .range(20.0..=400.0)
+                            .speed(0.5)
+                            .suffix(" bpm"),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut tempo.beats_per_bar)
+                            .range(1..=16)
+                            .suffix(" beats/bar"),
+                    )
+                    .changed();
+                if changed {
+                    automation.set_tempo(tempo);
+                    ctx.request_snapshot();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut state.length)
+                        .range(1.0..=64.0)
+                        .speed(0.1)
+                        .suffix(" bars"),
+                );
+                ui.label("from");
+                ui.add(egui::DragValue::new(&mut state.min_value).speed(0.01));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut state.max_value).speed(0.01));
+                if state.max_value <= state.min_value {
+                    state.max_value = state.min_value + 1.0;
+                }
+            });
+        });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["automation"]
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Automation::ARG_BPM)
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<AutomationUiState, ()> {
+        Ok(AutomationUiState {
+            min_value: 0.0,
+            max_value: 1.0,
+            length: 1.0,
+            selected: None,
+        })
+    }
+}
@@ -0,0 +1,94 @@
+use eframe::egui;
+
+use crate::{
+    core::{audiofileio::load_audio_file, sound::soundprocessor::SoundProcessorWithId},
+    objects::binaural::{Binaural, HrtfTable},
+    ui_core::{
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct BinauralUi {}
+
+impl SoundObjectUi for BinauralUi {
+    type ObjectType = SoundProcessorWithId<Binaural>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        binaural: &mut SoundProcessorWithId<Binaural>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Binaural")
+            .add_sound_input(&binaural.input, "input")
+            .add_expression(
+                &binaural.azimuth,
+                &["azimuth"],
+                PlotConfig::new().linear_vertical_range(-180.0..=180.0),
+            )
+            .show_with(
+                binaural,
+                ui,
+                ctx,
+                graph_ui_state,
+                |binaural, ui, _uistate| {
+                    ui.horizontal(|ui| {
+                        match binaural.hrtf() {
+                            Some(hrtf) => {
+                                ui.label(format!("HRTF: {} directions", hrtf.num_responses()));
+                                if ui.button("Clear").clicked() {
+                                    binaural.set_hrtf(None);
+                                    ctx.request_snapshot();
+                                }
+                            }
+                            None => {
+                                ui.label("Time and level differences");
+                            }
+                        }
+                        if ui.button("Load HRTF").clicked() {
+                            let dialog = rfd::FileDialog::new().add_filter("Audio files", &["wav"]);
+                            if let Some(path) = dialog.pick_file() {
+                                match load_audio_file(&path).map(|buf| HrtfTable::from_audio(&buf))
+                                {
+                                    Ok(Some(hrtf)) => {
+                                        binaural.set_hrtf(Some(hrtf));
+                                        ctx.request_snapshot();
+                                    }
+                                    Ok(None) => println!("HRTF file is too short"),
+                                    Err(e) => println!("Failed to load file: {}", e),
+                                }
+                            }
+                        }
+                    });
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["binaural", "hrtf"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
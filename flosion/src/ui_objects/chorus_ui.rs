@@ -0,0 +1,80 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::chorus::{Chorus, CHORUS_MAX_DELAY},
+    ui_core::{
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct ChorusUi {}
+
+impl SoundObjectUi for ChorusUi {
+    type ObjectType = SoundProcessorWithId<Chorus>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        chorus: &mut SoundProcessorWithId<Chorus>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Chorus")
+            .add_sound_input(&chorus.input, "input")
+            .add_expression(
+                &chorus.delay,
+                &["delay"],
+                PlotConfig::new().linear_vertical_range(0.0..=CHORUS_MAX_DELAY),
+            )
+            .add_expression(&chorus.rate, &["rate"], PlotConfig::new())
+            .add_expression(&chorus.depth, &["depth"], PlotConfig::new())
+            .add_expression(
+                &chorus.feedback,
+                &["feedback"],
+                PlotConfig::new().linear_vertical_range(-1.0..=1.0),
+            )
+            .add_expression(
+                &chorus.mix,
+                &["mix"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            )
+            .show_with(chorus, ui, ctx, graph_ui_state, |chorus, ui, _uistate| {
+                let mut anti_denormal = chorus.anti_denormal();
+                if ui
+                    .checkbox(&mut anti_denormal, "Anti-denormal")
+                    .on_hover_text("Add inaudible noise to the feedback to keep it fast to process")
+                    .changed()
+                {
+                    chorus.set_anti_denormal(anti_denormal);
+                }
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["chorus", "flanger"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -0,0 +1,130 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::compressor::Compressor,
+    ui_core::{
+        arguments::ParsedArguments,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct CompressorUi {}
+
+impl SoundObjectUi for CompressorUi {
+    type ObjectType = SoundProcessorWithId<Compressor>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        compressor: &mut SoundProcessorWithId<Compressor>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Compressor")
+            .add_sound_input(&compressor.input, "input")
+            .add_sound_input(&compressor.sidechain, "sidechain")
+            .show_with(
+                compressor,
+                ui,
+                ctx,
+                graph_ui_state,
+                |compressor, ui, _uistate| {
+                    ui.horizontal(|ui| {
+                        let mut threshold = compressor.threshold();
+                        ui.label("Threshold");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut threshold)
+                                    .range(-60.0..=0.0)
+                                    .speed(0.1)
+                                    .suffix(" dB"),
+                            )
+                            .changed()
+                        {
+                            compressor.set_threshold(threshold);
+                        }
+
+                        let mut ratio = compressor.ratio();
+                        ui.label("Ratio");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut ratio)
+                                    .range(1.0..=20.0)
+                                    .speed(0.05),
+                            )
+                            .changed()
+                        {
+                            compressor.set_ratio(ratio);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let mut attack = compressor.attack();
+                        ui.label("Attack");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut attack)
+                                    .range(0.0001..=1.0)
+                                    .speed(0.001)
+                                    .suffix(" s"),
+                            )
+                            .changed()
+                        {
+                            compressor.set_attack(attack);
+                        }
+
+                        let mut release = compressor.release();
+                        ui.label("Release");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut release)
+                                    .range(0.001..=5.0)
+                                    .speed(0.005)
+                                    .suffix(" s"),
+                            )
+                            .changed()
+                        {
+                            compressor.set_release(release);
+                        }
+                    });
+
+                    let mut sidechain_enabled = compressor.sidechain_enabled();
+                    if ui
+                        .checkbox(&mut sidechain_enabled, "Sidechain")
+                        .on_hover_text(
+                            "Detect the level of the sidechain input instead of the main input",
+                        )
+                        .changed()
+                    {
+                        compressor.set_sidechain_enabled(sidechain_enabled);
+                    }
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["compressor"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Dynamics
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
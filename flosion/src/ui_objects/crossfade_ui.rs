@@ -0,0 +1,79 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::crossfade::Crossfade,
+    ui_core::{
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct CrossfadeUi {}
+
+impl SoundObjectUi for CrossfadeUi {
+    type ObjectType = SoundProcessorWithId<Crossfade>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        crossfade: &mut SoundProcessorWithId<Crossfade>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Crossfade")
+            .add_sound_input(&crossfade.input_a, "a")
+            .add_sound_input(&crossfade.input_b, "b")
+            .add_expression(
+                &crossfade.mix,
+                &["mix"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            )
+            .show_with(
+                crossfade,
+                ui,
+                ctx,
+                graph_ui_state,
+                |crossfade, ui, _uistate| {
+                    // When nothing is connected to the mix expression,
+                    // its constant value can be set directly
+                    let result = &mut crossfade.mix.graph_mut().results_mut()[0];
+                    if result.target().is_none() {
+                        let mut mix = result.default_value();
+                        if ui
+                            .add(egui::Slider::new(&mut mix, 0.0..=1.0).text("mix"))
+                            .changed()
+                        {
+                            result.set_default_value(mix);
+                        }
+                    }
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["crossfade"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
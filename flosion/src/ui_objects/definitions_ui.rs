@@ -1,39 +1,87 @@
+use eframe::egui;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
 use crate::{
     core::sound::soundprocessor::SoundProcessorWithId,
     objects::definitions::Definitions,
     ui_core::{
-        arguments::ParsedArguments, expressionplot::PlotConfig, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::{Argument, ArgumentList, ParsedArguments, StringIdentifierArgument},
+        expressionplot::PlotConfig,
+        object_ui::SummonCategory,
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
 #[derive(Default)]
 pub struct DefinitionsUi {}
 
+impl DefinitionsUi {
+    pub const ARG_NAME: StringIdentifierArgument = StringIdentifierArgument("name");
+}
+
+pub struct DefinitionsUiState {
+    /// The name by which the definition is referred to in expressions
+    /// further down the stack
+    name: String,
+
+    /// The name currently being typed in, which replaces the definition's
+    /// name only once it is a valid identifier
+    pending_name: String,
+}
+
+impl Stashable for DefinitionsUiState {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.string(&self.name);
+    }
+}
+
+impl UnstashableInplace for DefinitionsUiState {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        unstasher.string_inplace(&mut self.name)?;
+        if unstasher.time_to_write() {
+            self.pending_name = self.name.clone();
+        }
+        Ok(())
+    }
+}
+
 impl SoundObjectUi for DefinitionsUi {
     type ObjectType = SoundProcessorWithId<Definitions>;
-    type StateType = NoObjectUiState;
+    type StateType = DefinitionsUiState;
 
     fn ui<'a, 'b>(
         &self,
         definitions: &mut SoundProcessorWithId<Definitions>,
         graph_ui_state: &mut SoundGraphUiState,
-        ui: &mut eframe::egui::Ui,
+        ui: &mut egui::Ui,
         ctx: &SoundGraphUiContext,
-        _state: &mut NoObjectUiState,
+        state: &mut DefinitionsUiState,
     ) {
         ProcessorUi::new("Definitions")
-            .add_expression(&definitions.expression, &["a"], PlotConfig::new())
-            .add_argument(&definitions.argument, "a")
+            .add_expression(&definitions.expression, &[&state.name], PlotConfig::new())
+            .add_argument(&definitions.argument, &state.name)
             .add_sound_input(&definitions.sound_input, "input")
             .show_with(
                 definitions,
                 ui,
                 ctx,
                 graph_ui_state,
-                |_definitions, _ui, _uistate| {
-                    // TODO: controls to rename source
+                |_definitions, ui, _uistate| {
+                    let r = ui.add(
+                        egui::TextEdit::singleline(&mut state.pending_name).desired_width(80.0),
+                    );
+                    if r.changed() && !state.pending_name.is_empty() {
+                        if let Some(name) = StringIdentifierArgument::try_parse(&state.pending_name)
+                        {
+                            state.name = name;
+                        }
+                    }
+                    if r.lost_focus() {
+                        state.pending_name = state.name.clone();
+                    }
                     // TODO: buttons to add/remove terms
                 },
             )
@@ -43,6 +91,14 @@ impl SoundObjectUi for DefinitionsUi {
         &["definitions"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&DefinitionsUi::ARG_NAME)
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -50,8 +106,14 @@ impl SoundObjectUi for DefinitionsUi {
     fn make_ui_state(
         &self,
         _handle: &Self::ObjectType,
-        _args: &ParsedArguments,
-    ) -> Result<NoObjectUiState, ()> {
-        Ok(NoObjectUiState)
+        args: &ParsedArguments,
+    ) -> Result<DefinitionsUiState, ()> {
+        let name = args
+            .get(&DefinitionsUi::ARG_NAME)
+            .unwrap_or_else(|| "a".to_string());
+        Ok(DefinitionsUiState {
+            pending_name: name.clone(),
+            name,
+        })
     }
 }
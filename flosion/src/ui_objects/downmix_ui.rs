@@ -0,0 +1,50 @@
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::downmix::Downmix,
+    ui_core::{
+        arguments::ParsedArguments,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct DownmixUi {}
+
+impl SoundObjectUi for DownmixUi {
+    type ObjectType = SoundProcessorWithId<Downmix>;
+    type StateType = NoObjectUiState;
+    fn ui(
+        &self,
+        downmix: &mut SoundProcessorWithId<Downmix>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut eframe::egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Downmix")
+            .add_sound_input(&downmix.input, "input")
+            .show(downmix, ui, ctx, graph_ui_state);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["downmix"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -4,9 +4,13 @@ use crate::{
     core::sound::soundprocessor::SoundProcessorWithId,
     objects::ensemble::Ensemble,
     ui_core::{
-        arguments::ParsedArguments, expressionplot::PlotConfig, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -61,6 +65,10 @@ impl SoundObjectUi for EnsembleUi {
         &["ensemble"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
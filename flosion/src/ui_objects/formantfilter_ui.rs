@@ -0,0 +1,101 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::formantfilter::{formants_at, FormantBank, FormantFilter, VOWEL_NAMES},
+    ui_core::{
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        frequencyresponseplot::FrequencyResponsePlot,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct FormantFilterUi {}
+
+impl SoundObjectUi for FormantFilterUi {
+    type ObjectType = SoundProcessorWithId<FormantFilter>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        formant_filter: &mut SoundProcessorWithId<FormantFilter>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        let last_vowel = (VOWEL_NAMES.len() - 1) as f32;
+        ProcessorUi::new("FormantFilter")
+            .add_sound_input(&formant_filter.input, "input")
+            .add_expression(
+                &formant_filter.vowel,
+                &["vowel"],
+                PlotConfig::new().linear_vertical_range(0.0..=last_vowel),
+            )
+            .show_with(
+                formant_filter,
+                ui,
+                ctx,
+                graph_ui_state,
+                |formant_filter, ui, _uistate| {
+                    // When nothing is connected to the vowel expression,
+                    // its constant value can be set directly
+                    let result = &mut formant_filter.vowel.graph_mut().results_mut()[0];
+                    if result.target().is_none() {
+                        let mut vowel = result.default_value();
+                        ui.horizontal(|ui| {
+                            for (i, name) in VOWEL_NAMES.iter().enumerate() {
+                                if ui.selectable_label(vowel == i as f32, *name).clicked() {
+                                    vowel = i as f32;
+                                }
+                            }
+                        });
+                        ui.add(egui::Slider::new(&mut vowel, 0.0..=last_vowel).text("morph"));
+                        if vowel != result.default_value() {
+                            result.set_default_value(vowel);
+                        }
+
+                        // The response can only be shown for a constant vowel
+                        let show_phase_id = ui.id().with("show_phase");
+                        let mut show_phase =
+                            ui.memory_mut(|m| m.data.get_temp(show_phase_id).unwrap_or(false));
+                        let bank = FormantBank::new(vowel);
+                        let mut plot = FrequencyResponsePlot::new(|f| bank.frequency_response(f))
+                            .show_phase(show_phase)
+                            .width(ui.available_width());
+                        for formant in formants_at(vowel) {
+                            plot = plot.marker(formant.frequency);
+                        }
+                        ui.add(plot);
+                        if ui.checkbox(&mut show_phase, "Phase").changed() {
+                            ui.memory_mut(|m| m.data.insert_temp(show_phase_id, show_phase));
+                        }
+                    }
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["formantfilter", "vowel"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -0,0 +1,72 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::gain::Gain,
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct GainUi {}
+
+impl SoundObjectUi for GainUi {
+    type ObjectType = SoundProcessorWithId<Gain>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        gain: &mut SoundProcessorWithId<Gain>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Gain")
+            .add_sound_input(&gain.input, "input")
+            .show_with(gain, ui, ctx, graph_ui_state, |gain, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    let mut decibels = gain.decibels();
+                    let slider =
+                        ui.add(egui::Slider::new(&mut decibels, -60.0..=12.0).show_value(false));
+                    let drag = ui.add(
+                        egui::DragValue::new(&mut decibels)
+                            .range(-120.0..=24.0)
+                            .speed(0.1)
+                            .suffix(" dB"),
+                    );
+                    if slider.changed() || drag.changed() {
+                        gain.set_decibels(decibels);
+                    }
+                });
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["gain", "trim"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Dynamics
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Gain::ARG_DECIBELS)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -0,0 +1,75 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::haas::{Haas, HaasSide, HAAS_MAX_DELAY, HAAS_MIN_DELAY},
+    ui_core::{
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct HaasUi {}
+
+impl SoundObjectUi for HaasUi {
+    type ObjectType = SoundProcessorWithId<Haas>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        haas: &mut SoundProcessorWithId<Haas>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Haas")
+            .add_sound_input(&haas.input, "input")
+            .add_expression(
+                &haas.delay,
+                &["delay"],
+                PlotConfig::new().linear_vertical_range(HAAS_MIN_DELAY..=HAAS_MAX_DELAY),
+            )
+            .show_with(haas, ui, ctx, graph_ui_state, |haas, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    ui.label("Delay");
+                    for side in HaasSide::all_values() {
+                        if ui
+                            .selectable_label(haas.side() == *side, side.name())
+                            .clicked()
+                        {
+                            haas.set_side(*side);
+                        }
+                    }
+                });
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["haas"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Haas::ARG_SIDE)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -8,9 +8,9 @@ use crate::{
     },
     objects::input::Input,
     ui_core::{
-        arguments::ParsedArguments, soundgraphuicontext::SoundGraphUiContext,
-        soundgraphuistate::SoundGraphUiState, soundobjectui::SoundObjectUi,
-        soundprocessorui::ProcessorUi,
+        arguments::ParsedArguments, object_ui::SummonCategory,
+        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
     },
 };
 
@@ -102,6 +102,10 @@ impl SoundObjectUi for InputUi {
         &["input"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -0,0 +1,109 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::karplusstrong::KarplusStrong,
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct KarplusStrongUi {}
+
+impl SoundObjectUi for KarplusStrongUi {
+    type ObjectType = SoundProcessorWithId<KarplusStrong>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        karplus_strong: &mut SoundProcessorWithId<KarplusStrong>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("KarplusStrong")
+            .add_expression(&karplus_strong.frequency, &["frequency"], PlotConfig::new())
+            .add_expression(
+                &karplus_strong.damping,
+                &["damping"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            )
+            .show_with(
+                karplus_strong,
+                ui,
+                ctx,
+                graph_ui_state,
+                |karplus_strong, ui, _uistate| {
+                    // When nothing is connected to the frequency or damping
+                    // expressions, their constant values can be set directly
+                    let frequency = &mut karplus_strong.frequency.graph_mut().results_mut()[0];
+                    if frequency.target().is_none() {
+                        let mut value = frequency.default_value();
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut value, 20.0..=2000.0)
+                                    .logarithmic(true)
+                                    .suffix(" Hz")
+                                    .text("frequency"),
+                            )
+                            .changed()
+                        {
+                            frequency.set_default_value(value);
+                        }
+                    }
+
+                    let damping = &mut karplus_strong.damping.graph_mut().results_mut()[0];
+                    if damping.target().is_none() {
+                        let mut value = damping.default_value();
+                        if ui
+                            .add(egui::Slider::new(&mut value, 0.0..=1.0).text("damping"))
+                            .changed()
+                        {
+                            damping.set_default_value(value);
+                        }
+                    }
+
+                    let mut anti_denormal = karplus_strong.anti_denormal();
+                    if ui
+                        .checkbox(&mut anti_denormal, "Anti-denormal")
+                        .on_hover_text(
+                            "Add inaudible noise to the feedback to keep it fast to process",
+                        )
+                        .changed()
+                    {
+                        karplus_strong.set_anti_denormal(anti_denormal);
+                    }
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["karplusstrong", "pluck", "string"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&KarplusStrong::ARG_SEED)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -1,12 +1,18 @@
 use eframe::egui;
 
 use crate::{
-    core::sound::soundprocessor::SoundProcessorWithId,
-    objects::keyboard::{KeyId, Keyboard},
+    core::{
+        engine::soundenginereport::VoiceReport, samplefrequency::SAMPLE_FREQUENCY,
+        sound::soundprocessor::SoundProcessorWithId,
+    },
+    objects::keyboard::{KeyId, Keyboard, VoiceStealing},
     ui_core::{
-        arguments::ParsedArguments, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -28,79 +34,65 @@ impl SoundObjectUi for KeyboardUi {
         ProcessorUi::new("Keyboard")
             .add_sound_input(&keyboard.input, "input")
             .add_argument(&keyboard.key_frequency, "keyfrequency")
+            .add_argument(&keyboard.key_velocity, "keyvelocity")
+            .add_argument(&keyboard.key_note, "keynote")
             .show_with(
                 keyboard,
                 ui,
                 ctx,
                 graph_ui_state,
                 |keyboard, ui, _ui_state| {
-                    let has_focus_id = egui::Id::new("keyboard_has_focus").with(keyboard.id());
-
-                    let had_focus =
-                        ui.memory_mut(|m| m.data.get_temp(has_focus_id).unwrap_or(false));
-
-                    let mut has_focus = had_focus;
-
-                    let label = if has_focus { "Stop" } else { "Play" };
-                    // TODO: fix the colour here
-                    let r = ui.toggle_value(&mut has_focus, label);
-
-                    if r.clicked_elsewhere() {
-                        has_focus = false;
-                    }
-
-                    ui.memory_mut(|m| m.data.insert_temp(has_focus_id, has_focus));
+                    ui.horizontal(|ui| {
+                        ui.label("Steal");
+                        for stealing in VoiceStealing::all_values() {
+                            if ui
+                                .selectable_label(
+                                    keyboard.voice_stealing() == *stealing,
+                                    stealing.name(),
+                                )
+                                .clicked()
+                            {
+                                keyboard.set_voice_stealing(*stealing);
+                            }
+                        }
+                    });
 
-                    if !has_focus {
-                        if had_focus {
-                            keyboard.release_all_keys();
+                    ui.horizontal(|ui| {
+                        let mut glide_time = keyboard.glide_time();
+                        ui.label("Glide");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut glide_time)
+                                    .range(0.0..=5.0)
+                                    .speed(0.005)
+                                    .suffix(" s"),
+                            )
+                            .changed()
+                        {
+                            keyboard.set_glide_time(glide_time);
                         }
-                        return;
-                    }
 
-                    let all_keys = [
-                        egui::Key::A, // C
-                        egui::Key::W, // C#
-                        egui::Key::S, // D
-                        egui::Key::E, // D#
-                        egui::Key::D, // E
-                        egui::Key::F, // F nice
-                        egui::Key::T, // F#
-                        egui::Key::G, // G nice
-                        egui::Key::Y, // G#
-                        egui::Key::H, // A
-                        egui::Key::U, // A#
-                        egui::Key::J, // B
-                        egui::Key::K, // C
-                        egui::Key::O, // C#
-                        egui::Key::L, // D
-                        egui::Key::P, // D#
-                    ];
-
-                    for e in ui.input(|i| i.events.clone()) {
-                        if let egui::Event::Key {
-                            key,
-                            pressed,
-                            repeat,
-                            modifiers,
-                            physical_key: _,
-                        } = e
+                        let mut legato_only = keyboard.legato_only();
+                        if ui
+                            .checkbox(&mut legato_only, "Legato")
+                            .on_hover_text("Only glide while another key is being held")
+                            .changed()
                         {
-                            if repeat || modifiers.any() {
-                                continue;
-                            }
-                            let Some(i) = all_keys.iter().position(|k| *k == key) else {
-                                continue;
-                            };
-                            if pressed {
-                                let f = 256.0_f32 * (2.0_f32).powf((i as f32) / 12.0_f32);
-                                // let f = 128.0_f32 * ((i + 1) as f32); // heh
-                                keyboard.start_key(KeyId(i), f);
-                            } else {
-                                keyboard.release_key(KeyId(i));
-                            }
+                            keyboard.set_legato_only(legato_only);
                         }
+                    });
+
+                    if let Some(report) = ctx.compiled_processor_report(keyboard.id()) {
+                        show_voices(ui, report.voices());
                     }
+
+                    play_with_computer_keyboard(
+                        ui,
+                        egui::Id::new("keyboard_has_focus").with(keyboard.id()),
+                        |id, f| keyboard.start_key(id, f, 1.0),
+                        |id| keyboard.release_key(id),
+                        || keyboard.release_all_keys(),
+                    );
                 },
             );
     }
@@ -109,6 +101,14 @@ impl SoundObjectUi for KeyboardUi {
         &["keyboard"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Keyboard::ARG_STEALING)
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -121,3 +121,130 @@ impl SoundObjectUi for KeyboardUi {
         Ok(NoObjectUiState)
     }
 }
+
+/// The names of the twelve pitch classes, starting from C
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// The name and octave of the nearest note to the given MIDI-style note number
+fn note_name(note: f32) -> String {
+    let n = note.round() as i32;
+    format!(
+        "{}{}",
+        NOTE_NAMES[n.rem_euclid(12) as usize],
+        n.div_euclid(12) - 1
+    )
+}
+
+/// Show each playing voice as a bar as tall as its level, labeled with
+/// its note. Released voices are drawn fainter.
+fn show_voices(ui: &mut egui::Ui, voices: &[VoiceReport]) {
+    ui.horizontal(|ui| {
+        ui.label(format!("Voices: {}", voices.len()));
+        for voice in voices {
+            let (rect, response) =
+                ui.allocate_exact_size(egui::vec2(24.0, 24.0), egui::Sense::hover());
+            let alpha = if voice.released() { 64 } else { 192 };
+            let level = voice.level().clamp(0.0, 1.0);
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(rect.left(), rect.bottom() - level * rect.height()),
+                rect.max,
+            );
+            let painter = ui.painter();
+            painter.rect_filled(bar, 2.0, egui::Color32::from_white_alpha(alpha / 2));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                note_name(voice.note()),
+                egui::FontId::monospace(9.0),
+                egui::Color32::from_white_alpha(alpha),
+            );
+            response.on_hover_text(format!(
+                "Note {:.2}, playing for {:.2} s",
+                voice.note(),
+                voice.age_samples() as f32 / SAMPLE_FREQUENCY as f32
+            ));
+        }
+    });
+}
+
+/// Show a toggle which, while active, plays notes using the letter keys
+/// of the computer keyboard laid out like a piano keyboard. Computer
+/// keyboards aren't velocity-sensitive, so only frequencies are given.
+pub(crate) fn play_with_computer_keyboard<S, R, A>(
+    ui: &mut egui::Ui,
+    has_focus_id: egui::Id,
+    mut start_key: S,
+    mut release_key: R,
+    release_all_keys: A,
+) where
+    S: FnMut(KeyId, f32),
+    R: FnMut(KeyId),
+    A: FnOnce(),
+{
+    let had_focus = ui.memory_mut(|m| m.data.get_temp(has_focus_id).unwrap_or(false));
+
+    let mut has_focus = had_focus;
+
+    let label = if has_focus { "Stop" } else { "Play" };
+    // TODO: fix the colour here
+    let r = ui.toggle_value(&mut has_focus, label);
+
+    if r.clicked_elsewhere() {
+        has_focus = false;
+    }
+
+    ui.memory_mut(|m| m.data.insert_temp(has_focus_id, has_focus));
+
+    if !has_focus {
+        if had_focus {
+            release_all_keys();
+        }
+        return;
+    }
+
+    let all_keys = [
+        egui::Key::A, // C
+        egui::Key::W, // C#
+        egui::Key::S, // D
+        egui::Key::E, // D#
+        egui::Key::D, // E
+        egui::Key::F, // F nice
+        egui::Key::T, // F#
+        egui::Key::G, // G nice
+        egui::Key::Y, // G#
+        egui::Key::H, // A
+        egui::Key::U, // A#
+        egui::Key::J, // B
+        egui::Key::K, // C
+        egui::Key::O, // C#
+        egui::Key::L, // D
+        egui::Key::P, // D#
+    ];
+
+    for e in ui.input(|i| i.events.clone()) {
+        if let egui::Event::Key {
+            key,
+            pressed,
+            repeat,
+            modifiers,
+            physical_key: _,
+        } = e
+        {
+            if repeat || modifiers.any() {
+                continue;
+            }
+            let Some(i) = all_keys.iter().position(|k| *k == key) else {
+                continue;
+            };
+            if pressed {
+                let f = 256.0_f32 * (2.0_f32).powf((i as f32) / 12.0_f32);
+                // let f = 128.0_f32 * ((i + 1) as f32); // heh
+                start_key(KeyId(i), f);
+            } else {
+                release_key(KeyId(i));
+            }
+        }
+    }
+}
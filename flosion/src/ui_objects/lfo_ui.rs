@@ -0,0 +1,81 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::lfo::Lfo,
+    ui_core::{
+        arguments::ParsedArguments,
+        beatsyncui::beat_sync_ui,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct LfoUi {}
+
+impl SoundObjectUi for LfoUi {
+    type ObjectType = SoundProcessorWithId<Lfo>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        lfo: &mut SoundProcessorWithId<Lfo>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("LFO")
+            .add_sound_input(&lfo.input, "input")
+            .add_expression(&lfo.rate, &["rate"], PlotConfig::new())
+            .add_expression(
+                &lfo.shape,
+                &["shape"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            )
+            .add_expression(
+                &lfo.phase_offset,
+                &["phase_offset"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            )
+            .add_argument(&lfo.sine, "lfosine")
+            .add_argument(&lfo.triangle, "lfotriangle")
+            .add_argument(&lfo.ramp, "lforamp")
+            .add_argument(&lfo.square, "lfosquare")
+            .add_argument(&lfo.sample_and_hold, "lfosampleandhold")
+            .show_with(lfo, ui, ctx, graph_ui_state, |lfo, ui, _uistate| {
+                let mut sync_to_time = lfo.sync_to_time();
+                if ui.checkbox(&mut sync_to_time, "Sync to time").changed() {
+                    lfo.set_sync_to_time(sync_to_time);
+                }
+
+                let mut beat_sync = lfo.beat_sync();
+                if beat_sync_ui(ui, &mut beat_sync) {
+                    lfo.set_beat_sync(beat_sync);
+                }
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["lfo"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -0,0 +1,66 @@
+use eframe::egui;
+
+use crate::{
+    core::expression::{expressionnode::ExpressionNodeWithId, macrocall::MacroCall},
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        expressiongraphuicontext::ExpressionGraphUiContext,
+        expressiongraphuistate::ExpressionGraphUiState,
+        expressionobjectui::ExpressionObjectUi,
+        expressionodeui::{DisplayStyle, ExpressionNodeUi},
+        lexicallayout::lexicallayout::ExpressionNodeLayout,
+        object_ui::{NoObjectUiState, SummonCategory},
+    },
+};
+
+#[derive(Default)]
+pub struct MacroCallUi {}
+
+impl ExpressionObjectUi for MacroCallUi {
+    type ObjectType = ExpressionNodeWithId<MacroCall>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        call: &mut ExpressionNodeWithId<MacroCall>,
+        _graph_ui_state: &mut ExpressionGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        let name = if call.definition().is_some() {
+            call.name().to_string()
+        } else {
+            format!("{} (undefined)", call.name())
+        };
+        ExpressionNodeUi::new_named(call.id(), name, DisplayStyle::Framed).show(ui, ctx);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        // Macros are summoned by their own names, which are
+        // provided by the sound graph's macro library
+        &[]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty()
+            .add(&MacroCall::ARG_NAME)
+            .add(&MacroCall::ARG_NUM_INPUTS)
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
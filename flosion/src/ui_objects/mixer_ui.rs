@@ -4,9 +4,12 @@ use crate::{
     core::sound::{soundinput::AnyProcessorInput, soundprocessor::SoundProcessorWithId},
     objects::mixer::Mixer,
     ui_core::{
-        arguments::ParsedArguments, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::{ArgumentList, ParsedArguments},
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -52,6 +55,14 @@ impl SoundObjectUi for MixerUi {
         &["mixer"]
     }
 
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Mixer::ARG_NUM_INPUTS)
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -1,21 +1,45 @@
 pub mod adsr_ui;
 pub mod all_objects;
+pub mod arpeggiator_ui;
 pub mod audioclip_ui;
+pub mod automation_ui;
+pub mod binaural_ui;
+pub mod chorus_ui;
+pub mod compressor_ui;
+pub mod crossfade_ui;
 pub mod definitions_ui;
+pub mod downmix_ui;
 pub mod ensemble_ui;
+pub mod formantfilter_ui;
+pub mod gain_ui;
+pub mod haas_ui;
+pub mod karplusstrong_ui;
 pub mod input_ui;
 pub mod keyboard_ui;
+pub mod lfo_ui;
+pub mod macrocall_ui;
 pub mod mixer_ui;
+pub mod noise_ui;
 pub mod oscilloscope_ui;
 pub mod output_ui;
 pub mod pure_function_uis;
+pub mod quantizetoscale_ui;
 pub mod readwritewaveform_ui;
 // pub mod recorder_ui;
 pub mod resampler_ui;
 pub mod sampler1d_ui;
+pub mod sampler2d_ui;
 pub mod scatter_ui;
 pub mod scheduler_ui;
+pub mod slew_ui;
+pub mod spectralgate_ui;
 pub mod stateful_function_uis;
+pub mod stereowidth_ui;
+pub mod tap_ui;
+pub mod tremolo_ui;
+pub mod vocoder_ui;
 pub mod wavegenerator_ui;
+pub mod wavetable_ui;
 pub mod whitenoise_ui;
+pub mod widen_ui;
 pub mod writewaveform_ui;
@@ -0,0 +1,81 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::noise::{Noise, NoiseColor},
+    ui_core::{
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct NoiseUi {}
+
+impl SoundObjectUi for NoiseUi {
+    type ObjectType = SoundProcessorWithId<Noise>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        noise: &mut SoundProcessorWithId<Noise>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Noise").show_with(
+            noise,
+            ui,
+            ctx,
+            graph_ui_state,
+            |noise, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    for color in NoiseColor::all_values() {
+                        if ui
+                            .selectable_label(noise.color() == *color, color.name())
+                            .clicked()
+                        {
+                            noise.set_color(*color);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("seed");
+                    let mut seed = noise.seed();
+                    if ui.add(egui::DragValue::new(&mut seed)).changed() {
+                        noise.set_seed(seed);
+                    }
+                });
+            },
+        );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["noise"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty()
+            .add(&Noise::ARG_COLOR)
+            .add(&Noise::ARG_SEED)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -2,12 +2,12 @@ use eframe::egui::{self, Color32, ColorImage, TextureHandle, TextureOptions};
 use hashstash::{InplaceUnstasher, Stashable, UnstashError, UnstashableInplace};
 
 use crate::{
-    core::{sound::soundprocessor::SoundProcessorWithId, soundchunk::SoundChunk},
-    objects::oscilloscope::Oscilloscope,
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::{oscilloscope::Oscilloscope, tap::TapReader},
     ui_core::{
-        arguments::ParsedArguments, soundgraphuicontext::SoundGraphUiContext,
-        soundgraphuistate::SoundGraphUiState, soundobjectui::SoundObjectUi,
-        soundprocessorui::ProcessorUi,
+        arguments::ParsedArguments, object_ui::SummonCategory,
+        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
     },
 };
 
@@ -15,7 +15,7 @@ use crate::{
 pub struct OscilloscopeUi {}
 
 pub struct OscilloscopeUiState {
-    buffer_reader: spmcq::Reader<SoundChunk>,
+    buffer_reader: TapReader,
     exposure: f32,
     size: f32,
     gain: f32,
@@ -143,7 +143,7 @@ impl OscilloscopeUi {
     }
 
     fn update_image(state: &mut OscilloscopeUiState) {
-        while let Some(chunk) = state.buffer_reader.read().value() {
+        while let Some(chunk) = state.buffer_reader.read() {
             let img = &mut state.image;
             let w = img.width() as f32;
             let h = img.height() as f32;
@@ -330,6 +330,10 @@ impl SoundObjectUi for OscilloscopeUi {
         &["oscilloscope"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
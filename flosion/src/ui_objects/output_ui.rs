@@ -1,10 +1,11 @@
 pub(crate) use eframe::egui;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
 
 use crate::{
-    core::sound::soundprocessor::SoundProcessorWithId,
-    objects::output::Output,
+    core::{samplefrequency::SAMPLE_FREQUENCY, sound::soundprocessor::SoundProcessorWithId},
+    objects::output::{supported_output_configs, Output, OutputDeviceConfigRange},
     ui_core::{
-        arguments::ParsedArguments, object_ui::NoObjectUiState,
+        arguments::ParsedArguments, levelmeter::LevelMeter, object_ui::SummonCategory,
         soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
         soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
     },
@@ -13,26 +14,181 @@ use crate::{
 #[derive(Default)]
 pub struct OutputUi {}
 
+pub struct OutputUiState {
+    /// The configurations supported by the output device, which are
+    /// queried once rather than every frame since doing so can be slow
+    supported_configs: Vec<OutputDeviceConfigRange>,
+}
+
+impl Stashable for OutputUiState {
+    fn stash(&self, _stasher: &mut Stasher) {}
+}
+
+impl UnstashableInplace for OutputUiState {
+    fn unstash_inplace(&mut self, _unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        Ok(())
+    }
+}
+
+/// Sample rates offered for the output device, where supported
+const COMMON_SAMPLE_RATES: [u32; 6] = [22_050, 44_100, 48_000, 88_200, 96_000, 192_000];
+
+/// Buffer sizes offered for the output device, where supported
+const COMMON_BUFFER_SIZES: [u32; 7] = [64, 128, 256, 512, 1024, 2048, 4096];
+
+impl OutputUi {
+    fn show_device_settings(output: &mut Output, state: &OutputUiState, ui: &mut egui::Ui) {
+        fn describe(value: Option<u32>, unit: &str) -> String {
+            match value {
+                Some(v) => format!("{} {}", v, unit),
+                None => "Default".to_string(),
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Sample rate");
+            let mut sample_rate = output.device_sample_rate();
+            egui::ComboBox::from_id_salt("output_sample_rate")
+                .selected_text(describe(sample_rate, "Hz"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut sample_rate, None, "Default");
+                    let supported = &state.supported_configs;
+                    for sr in COMMON_SAMPLE_RATES {
+                        if supported
+                            .iter()
+                            .any(|c| (c.min_sample_rate..=c.max_sample_rate).contains(&sr))
+                        {
+                            ui.selectable_value(
+                                &mut sample_rate,
+                                Some(sr),
+                                describe(Some(sr), "Hz"),
+                            );
+                        }
+                    }
+                });
+            if sample_rate != output.device_sample_rate() {
+                output.set_device_sample_rate(sample_rate);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Buffer size");
+            let mut buffer_size = output.device_buffer_size();
+            egui::ComboBox::from_id_salt("output_buffer_size")
+                .selected_text(describe(buffer_size, "frames"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut buffer_size, None, "Default");
+                    let supported = &state.supported_configs;
+                    for bs in COMMON_BUFFER_SIZES {
+                        if supported
+                            .iter()
+                            .any(|c| c.buffer_sizes.as_ref().is_some_and(|r| r.contains(&bs)))
+                        {
+                            ui.selectable_value(
+                                &mut buffer_size,
+                                Some(bs),
+                                describe(Some(bs), "frames"),
+                            );
+                        }
+                    }
+                });
+            if buffer_size != output.device_buffer_size() {
+                output.set_device_buffer_size(buffer_size);
+            }
+        });
+
+        // The sound engine always runs at SAMPLE_FREQUENCY, and the device
+        // is fed by resampling, so say when the two differ
+        if let Some(sr) = output.active_sample_rate() {
+            if sr == SAMPLE_FREQUENCY as u32 {
+                ui.label(format!("Running at {} Hz", sr));
+            } else {
+                ui.label(format!(
+                    "Running at {} Hz, resampled from {} Hz",
+                    sr, SAMPLE_FREQUENCY
+                ))
+                .on_hover_text(
+                    "Sound is always processed at a fixed sample rate. \
+                    Choosing a different device sample rate doesn't change \
+                    how patches sound, only how they're played back.",
+                );
+            }
+        }
+    }
+}
+
 impl SoundObjectUi for OutputUi {
     type ObjectType = SoundProcessorWithId<Output>;
-    type StateType = NoObjectUiState;
+    type StateType = OutputUiState;
     fn ui(
         &self,
         output: &mut SoundProcessorWithId<Output>,
         graph_ui_state: &mut SoundGraphUiState,
         ui: &mut egui::Ui,
         ctx: &SoundGraphUiContext,
-        _state: &mut NoObjectUiState,
+        state: &mut OutputUiState,
     ) {
         ProcessorUi::new("Output")
             .add_sound_input(&output.input, "input")
             .show_with(output, ui, ctx, graph_ui_state, |output, ui, _ui_state| {
+                for (meter, label) in output.meters().iter().zip(["L", "R"]) {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        if ui
+                            .add(LevelMeter::new(meter.peak(), meter.rms(), meter.clipped()))
+                            .clicked()
+                        {
+                            meter.reset_clip();
+                        }
+                    });
+                }
+                ui.ctx().request_repaint();
                 if ui
                     .add(egui::Button::new("Start over").wrap_mode(egui::TextWrapMode::Extend))
                     .clicked()
                 {
                     output.start_over();
                 }
+                let mut dc_blocker_enabled = output.dc_blocker_enabled();
+                if ui.checkbox(&mut dc_blocker_enabled, "Block DC").changed() {
+                    output.set_dc_blocker_enabled(dc_blocker_enabled);
+                }
+                let mut limiter_enabled = output.limiter_enabled();
+                if ui.checkbox(&mut limiter_enabled, "Limit").changed() {
+                    output.set_limiter_enabled(limiter_enabled);
+                }
+                if limiter_enabled {
+                    ui.horizontal(|ui| {
+                        ui.label("Ceiling");
+                        let mut ceiling = output.limiter_ceiling();
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut ceiling)
+                                    .speed(0.01)
+                                    .range(0.01..=1.0),
+                            )
+                            .changed()
+                        {
+                            output.set_limiter_ceiling(ceiling);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Release");
+                        let mut release = output.limiter_release();
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut release)
+                                    .speed(0.01)
+                                    .range(0.001..=5.0)
+                                    .suffix(" s"),
+                            )
+                            .changed()
+                        {
+                            output.set_limiter_release(release);
+                        }
+                    });
+                }
+                OutputUi::show_device_settings(output, state, ui);
             });
     }
 
@@ -40,6 +196,10 @@ impl SoundObjectUi for OutputUi {
         &["output"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -48,7 +208,9 @@ impl SoundObjectUi for OutputUi {
         &self,
         _handle: &Self::ObjectType,
         _args: &ParsedArguments,
-    ) -> Result<NoObjectUiState, ()> {
-        Ok(NoObjectUiState)
+    ) -> Result<OutputUiState, ()> {
+        Ok(OutputUiState {
+            supported_configs: supported_output_configs(),
+        })
     }
 }
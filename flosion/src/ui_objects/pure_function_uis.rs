@@ -11,7 +11,7 @@ use crate::{
         expressionobjectui::ExpressionObjectUi,
         expressionodeui::{DisplayStyle, ExpressionNodeUi},
         lexicallayout::lexicallayout::ExpressionNodeLayout,
-        object_ui::NoObjectUiState,
+        object_ui::{NoObjectUiState, SummonCategory},
     },
 };
 
@@ -46,6 +46,10 @@ impl ExpressionObjectUi for ConstantUi {
         &["constant"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn summon_arguments(&self) -> ArgumentList {
         ArgumentList::new_empty()
             .add(&Constant::ARG_VALUE)
@@ -137,6 +141,10 @@ impl ExpressionObjectUi for SliderUi {
         &["slider"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn summon_arguments(&self) -> ArgumentList {
         ArgumentList::new_empty()
             .add(&Variable::ARG_VALUE)
@@ -186,8 +194,96 @@ impl ExpressionObjectUi for SliderUi {
     }
 }
 
+#[derive(Default)]
+pub struct SampleRateUi {}
+
+impl ExpressionObjectUi for SampleRateUi {
+    type ObjectType = ExpressionNodeWithId<SampleRate>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        sample_rate: &mut ExpressionNodeWithId<SampleRate>,
+        _graph_ui_state: &mut ExpressionGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ExpressionNodeUi::new_named(
+            sample_rate.id(),
+            "samplerate".to_string(),
+            DisplayStyle::Framed,
+        )
+        .show(ui, ctx);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["samplerate", "samplefrequency"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
+
+#[derive(Default)]
+pub struct BlockSizeUi {}
+
+impl ExpressionObjectUi for BlockSizeUi {
+    type ObjectType = ExpressionNodeWithId<BlockSize>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        block_size: &mut ExpressionNodeWithId<BlockSize>,
+        _graph_ui_state: &mut ExpressionGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ExpressionNodeUi::new_named(
+            block_size.id(),
+            "blocksize".to_string(),
+            DisplayStyle::Framed,
+        )
+        .show(ui, ctx);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["blocksize"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
+
 macro_rules! unary_expression_node_ui {
-    ($name: ident, $object: ident, $display_name: literal, $display_style: expr, $summon_names: expr, $layout: expr) => {
+    ($name: ident, $object: ident, $display_name: literal, $display_style: expr, $summon_names: expr, $layout: expr, $category: expr) => {
         #[derive(Default)]
         pub struct $name {}
 
@@ -210,6 +306,10 @@ macro_rules! unary_expression_node_ui {
                 &$summon_names
             }
 
+            fn summon_category(&self) -> SummonCategory {
+                $category
+            }
+
             fn make_properties(&self) -> ExpressionNodeLayout {
                 $layout
             }
@@ -226,7 +326,7 @@ macro_rules! unary_expression_node_ui {
 }
 
 macro_rules! binary_expression_node_ui {
-    ($name: ident, $object: ident, $display_name: literal, $display_style: expr, $summon_names: expr, $layout: expr) => {
+    ($name: ident, $object: ident, $display_name: literal, $display_style: expr, $summon_names: expr, $layout: expr, $category: expr) => {
         #[derive(Default)]
         pub struct $name {}
 
@@ -249,6 +349,10 @@ macro_rules! binary_expression_node_ui {
                 &$summon_names
             }
 
+            fn summon_category(&self) -> SummonCategory {
+                $category
+            }
+
             fn make_properties(&self) -> ExpressionNodeLayout {
                 $layout
             }
@@ -265,7 +369,7 @@ macro_rules! binary_expression_node_ui {
 }
 
 macro_rules! ternary_expression_node_ui {
-    ($name: ident, $object: ident, $display_name: literal, $display_style: expr, $summon_names: expr) => {
+    ($name: ident, $object: ident, $display_name: literal, $display_style: expr, $summon_names: expr, $category: expr) => {
         #[derive(Default)]
         pub struct $name {}
 
@@ -288,6 +392,10 @@ macro_rules! ternary_expression_node_ui {
                 &$summon_names
             }
 
+            fn summon_category(&self) -> SummonCategory {
+                $category
+            }
+
             fn make_properties(&self) -> ExpressionNodeLayout {
                 ExpressionNodeLayout::Function
             }
@@ -309,7 +417,8 @@ unary_expression_node_ui!(
     "Negate",
     DisplayStyle::Framed,
     ["negate"],
-    ExpressionNodeLayout::Prefix
+    ExpressionNodeLayout::Prefix,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     FloorUi,
@@ -317,7 +426,8 @@ unary_expression_node_ui!(
     "Floor",
     DisplayStyle::Framed,
     ["floor"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     CeilUi,
@@ -325,7 +435,8 @@ unary_expression_node_ui!(
     "Ceil",
     DisplayStyle::Framed,
     ["ceil"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     RoundUi,
@@ -333,7 +444,8 @@ unary_expression_node_ui!(
     "Round",
     DisplayStyle::Framed,
     ["round"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     TruncUi,
@@ -341,7 +453,8 @@ unary_expression_node_ui!(
     "Trunc",
     DisplayStyle::Framed,
     ["trunc"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     FractUi,
@@ -349,7 +462,8 @@ unary_expression_node_ui!(
     "Fract",
     DisplayStyle::Framed,
     ["fract"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     AbsUi,
@@ -357,7 +471,8 @@ unary_expression_node_ui!(
     "Abs",
     DisplayStyle::Framed,
     ["abs"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     SignumUi,
@@ -365,7 +480,8 @@ unary_expression_node_ui!(
     "Signum",
     DisplayStyle::Framed,
     ["signum"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     ExpUi,
@@ -373,7 +489,8 @@ unary_expression_node_ui!(
     "Exp",
     DisplayStyle::Framed,
     ["exp"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     Exp2Ui,
@@ -381,7 +498,8 @@ unary_expression_node_ui!(
     "Exp2",
     DisplayStyle::Framed,
     ["exp2"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     Exp10Ui,
@@ -389,7 +507,8 @@ unary_expression_node_ui!(
     "Exp10",
     DisplayStyle::Framed,
     ["exp10"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     LogUi,
@@ -397,7 +516,8 @@ unary_expression_node_ui!(
     "Log",
     DisplayStyle::Framed,
     ["log"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     Log2Ui,
@@ -405,7 +525,8 @@ unary_expression_node_ui!(
     "Log2",
     DisplayStyle::Framed,
     ["log2"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     Log10Ui,
@@ -413,7 +534,8 @@ unary_expression_node_ui!(
     "Log10",
     DisplayStyle::Framed,
     ["log10"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     SqrtUi,
@@ -421,7 +543,8 @@ unary_expression_node_ui!(
     "Sqrt",
     DisplayStyle::Framed,
     ["sqrt"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     SinUi,
@@ -429,7 +552,8 @@ unary_expression_node_ui!(
     "Sin",
     DisplayStyle::Framed,
     ["sin"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 unary_expression_node_ui!(
     CosUi,
@@ -437,7 +561,8 @@ unary_expression_node_ui!(
     "Cos",
     DisplayStyle::Framed,
     ["cos"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 
 unary_expression_node_ui!(
@@ -446,7 +571,8 @@ unary_expression_node_ui!(
     "SineWave",
     DisplayStyle::Framed,
     ["sinewave"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Oscillators
 );
 unary_expression_node_ui!(
     CosineWaveUi,
@@ -454,7 +580,8 @@ unary_expression_node_ui!(
     "CosineWave",
     DisplayStyle::Framed,
     ["cosinewave"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Oscillators
 );
 unary_expression_node_ui!(
     SquareWaveUi,
@@ -462,7 +589,8 @@ unary_expression_node_ui!(
     "SquareWave",
     DisplayStyle::Framed,
     ["squarewave"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Oscillators
 );
 unary_expression_node_ui!(
     SawWaveUi,
@@ -470,7 +598,8 @@ unary_expression_node_ui!(
     "SawWave",
     DisplayStyle::Frameless,
     ["sawwave"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Oscillators
 );
 unary_expression_node_ui!(
     TriangleWaveUi,
@@ -478,7 +607,8 @@ unary_expression_node_ui!(
     "TriangleWave",
     DisplayStyle::Framed,
     ["trianglewave"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Oscillators
 );
 
 binary_expression_node_ui!(
@@ -487,7 +617,8 @@ binary_expression_node_ui!(
     "+",
     DisplayStyle::Frameless,
     ["add", "+", "plus"],
-    ExpressionNodeLayout::Infix
+    ExpressionNodeLayout::Infix,
+    SummonCategory::Math
 );
 binary_expression_node_ui!(
     SubtractUi,
@@ -495,7 +626,8 @@ binary_expression_node_ui!(
     "-",
     DisplayStyle::Frameless,
     ["subtract", "-", "minus"],
-    ExpressionNodeLayout::Infix
+    ExpressionNodeLayout::Infix,
+    SummonCategory::Math
 );
 binary_expression_node_ui!(
     MultiplyUi,
@@ -503,7 +635,8 @@ binary_expression_node_ui!(
     "*",
     DisplayStyle::Frameless,
     ["multiply", "*", "times"],
-    ExpressionNodeLayout::Infix
+    ExpressionNodeLayout::Infix,
+    SummonCategory::Math
 );
 binary_expression_node_ui!(
     DivideUi,
@@ -511,7 +644,8 @@ binary_expression_node_ui!(
     "/",
     DisplayStyle::Frameless,
     ["divide", "/"],
-    ExpressionNodeLayout::Infix
+    ExpressionNodeLayout::Infix,
+    SummonCategory::Math
 );
 binary_expression_node_ui!(
     CopysignUi,
@@ -519,7 +653,8 @@ binary_expression_node_ui!(
     "Copysign",
     DisplayStyle::Framed,
     ["copysign"],
-    ExpressionNodeLayout::Function
+    ExpressionNodeLayout::Function,
+    SummonCategory::Math
 );
 binary_expression_node_ui!(
     PowUi,
@@ -527,7 +662,15 @@ binary_expression_node_ui!(
     "^",
     DisplayStyle::Frameless,
     ["pow", "^"],
-    ExpressionNodeLayout::Infix
+    ExpressionNodeLayout::Infix,
+    SummonCategory::Math
 );
 
-ternary_expression_node_ui!(LerpUi, Lerp, "Lerp", DisplayStyle::Framed, ["lerp"]);
+ternary_expression_node_ui!(
+    LerpUi,
+    Lerp,
+    "Lerp",
+    DisplayStyle::Framed,
+    ["lerp"],
+    SummonCategory::Math
+);
@@ -0,0 +1,63 @@
+use eframe::egui;
+
+use crate::{
+    core::expression::expressionnode::ExpressionNodeWithId,
+    objects::quantizetoscale::QuantizeToScale,
+    ui_core::{
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        expressiongraphuicontext::ExpressionGraphUiContext,
+        expressiongraphuistate::ExpressionGraphUiState,
+        expressionobjectui::ExpressionObjectUi,
+        expressionodeui::{DisplayStyle, ExpressionNodeUi},
+        lexicallayout::lexicallayout::ExpressionNodeLayout,
+        object_ui::{NoObjectUiState, SummonCategory},
+    },
+};
+
+#[derive(Default)]
+pub struct QuantizeToScaleUi {}
+
+impl ExpressionObjectUi for QuantizeToScaleUi {
+    type ObjectType = ExpressionNodeWithId<QuantizeToScale>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        quantize: &mut ExpressionNodeWithId<QuantizeToScale>,
+        _graph_ui_state: &mut ExpressionGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ExpressionNodeUi::new_named(
+            quantize.id(),
+            format!("QuantizeToScale ({})", quantize.scale().name()),
+            DisplayStyle::Framed,
+        )
+        .show(ui, ctx);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["quantizetoscale", "quantize"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Math
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&QuantizeToScale::ARG_SCALE)
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
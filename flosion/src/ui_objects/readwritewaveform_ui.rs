@@ -1,10 +1,14 @@
 use crate::{
-    core::sound::soundprocessor::SoundProcessorWithId,
+    core::{resample::OversampleFactor, sound::soundprocessor::SoundProcessorWithId},
     objects::readwritewaveform::ReadWriteWaveform,
     ui_core::{
-        arguments::ParsedArguments, expressionplot::PlotConfig, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -28,13 +32,33 @@ impl SoundObjectUi for ReadWriteWaveformUi {
             .add_argument(&rww.input_l, "l")
             .add_argument(&rww.input_r, "r")
             .add_expression(&rww.waveform, &["l", "r"], PlotConfig::new())
-            .show(rww, ui, ctx, graph_ui_state);
+            .show_with(rww, ui, ctx, graph_ui_state, |rww, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    ui.label("Oversample");
+                    for factor in OversampleFactor::all_values() {
+                        if ui
+                            .selectable_label(rww.oversample() == *factor, factor.name())
+                            .clicked()
+                        {
+                            rww.set_oversample(*factor);
+                        }
+                    }
+                });
+            });
     }
 
     fn summon_names(&self) -> &'static [&'static str] {
         &["readwritewaveform"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&ReadWriteWaveform::ARG_OVERSAMPLE)
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
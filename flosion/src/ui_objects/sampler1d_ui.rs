@@ -2,15 +2,15 @@ use eframe::egui;
 
 use crate::{
     core::expression::expressionnode::ExpressionNodeWithId,
-    objects::sampler1d::Sampler1d,
+    objects::sampler1d::{Sampler1d, Sampler1dInterpolation},
     ui_core::{
-        arguments::ParsedArguments,
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
         expressiongraphuicontext::ExpressionGraphUiContext,
         expressiongraphuistate::ExpressionGraphUiState,
         expressionobjectui::ExpressionObjectUi,
         expressionodeui::{DisplayStyle, ExpressionNodeUi},
         lexicallayout::lexicallayout::ExpressionNodeLayout,
-        object_ui::NoObjectUiState,
+        object_ui::{NoObjectUiState, SummonCategory},
     },
 };
 
@@ -44,20 +44,26 @@ impl ExpressionObjectUi for Sampler1dUi {
 
             painter.rect_filled(rect, egui::Rounding::ZERO, egui::Color32::BLACK);
 
-            let dx = rect.width() / (values.len() - 1) as f32;
-            for (i, (v0, v1)) in values.iter().zip(&values[1..]).enumerate() {
-                let x0 = rect.left() + i as f32 * dx;
-                let x1 = rect.left() + (i + 1) as f32 * dx;
-                // HACK assuming range of -1 to 1
-                let t0 = (0.5 * (*v0 + 1.0)).clamp(0.0, 1.0);
-                let t1 = (0.5 * (*v1 + 1.0)).clamp(0.0, 1.0);
-                let y0 = rect.bottom() - t0 * rect.height();
-                let y1 = rect.bottom() - t1 * rect.height();
-                painter.line_segment(
-                    [egui::pos2(x0, y0), egui::pos2(x1, y1)],
-                    egui::Stroke::new(2.0, egui::Color32::WHITE),
-                );
-            }
+            // Draw the values as they are read, one segment per pixel
+            let interpolation = sampler1d.interpolation();
+            let num_segments = (rect.width().ceil() as usize).max(1);
+            let points: Vec<egui::Pos2> = (0..=num_segments)
+                .map(|i| {
+                    let x = i as f32 / num_segments as f32;
+                    // Stop just short of the end, where the values wrap around
+                    let v = interpolation.sample(&values, x.min(1.0 - f32::EPSILON));
+                    // HACK assuming range of -1 to 1
+                    let t = (0.5 * (v + 1.0)).clamp(0.0, 1.0);
+                    egui::pos2(
+                        rect.left() + x * rect.width(),
+                        rect.bottom() - t * rect.height(),
+                    )
+                })
+                .collect();
+            painter.add(egui::Shape::line(
+                points,
+                egui::Stroke::new(2.0, egui::Color32::WHITE),
+            ));
 
             let r = ui.interact(rect, id, egui::Sense::drag());
 
@@ -99,6 +105,68 @@ impl ExpressionObjectUi for Sampler1dUi {
             if r.drag_stopped() {
                 ctx.request_snapshot();
             }
+
+            ui.horizontal(|ui| {
+                for mode in Sampler1dInterpolation::all_values() {
+                    if ui
+                        .selectable_label(interpolation == *mode, mode.name())
+                        .clicked()
+                        && interpolation != *mode
+                    {
+                        sampler1d.set_interpolation(*mode);
+                        ctx.request_snapshot();
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let length = sampler1d.length();
+                ui.label(format!("{} entries", length));
+                if ui
+                    .add_enabled(length > Sampler1d::MIN_LENGTH, egui::Button::new("halve"))
+                    .clicked()
+                {
+                    sampler1d.resize(length / 2);
+                    ctx.request_snapshot();
+                }
+                if ui
+                    .add_enabled(length < Sampler1d::MAX_LENGTH, egui::Button::new("double"))
+                    .clicked()
+                {
+                    sampler1d.resize(length * 2);
+                    ctx.request_snapshot();
+                }
+                if ui.button("normalize").clicked() {
+                    sampler1d.normalize();
+                    ctx.request_snapshot();
+                }
+                if ui.button("clear").clicked() {
+                    sampler1d.clear();
+                    ctx.request_snapshot();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("import").clicked() {
+                    let dialog = rfd::FileDialog::new().add_filter("Curves", &["csv", "wav"]);
+                    if let Some(path) = dialog.pick_file() {
+                        match sampler1d.import(&path) {
+                            Ok(()) => ctx.request_snapshot(),
+                            Err(e) => println!("Failed to import curve: {}", e),
+                        }
+                    }
+                }
+                if ui.button("export").clicked() {
+                    let dialog = rfd::FileDialog::new()
+                        .add_filter("CSV files", &["csv"])
+                        .add_filter("Audio files", &["wav"]);
+                    if let Some(path) = dialog.save_file() {
+                        if let Err(e) = sampler1d.export(&path) {
+                            println!("Failed to export curve: {}", e);
+                        }
+                    }
+                }
+            });
         });
     }
 
@@ -106,6 +174,16 @@ impl ExpressionObjectUi for Sampler1dUi {
         &["sampler1d"]
     }
 
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty()
+            .add(&Sampler1d::ARG_INTERPOLATION)
+            .add(&Sampler1d::ARG_LENGTH)
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> ExpressionNodeLayout {
         ExpressionNodeLayout::Function
     }
@@ -0,0 +1,153 @@
+use eframe::egui;
+
+use crate::{
+    core::expression::expressionnode::ExpressionNodeWithId,
+    objects::sampler2d::Sampler2d,
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        expressiongraphuicontext::ExpressionGraphUiContext,
+        expressiongraphuistate::ExpressionGraphUiState,
+        expressionobjectui::ExpressionObjectUi,
+        expressionodeui::{DisplayStyle, ExpressionNodeUi},
+        lexicallayout::lexicallayout::ExpressionNodeLayout,
+        object_ui::{NoObjectUiState, SummonCategory},
+    },
+};
+
+/// How quickly painting moves entries towards 1 or -1, per second
+const PAINT_RATE: f32 = 2.0;
+
+/// The colour of a value, from blue at -1 through black to orange at 1
+// HACK assuming range of -1 to 1
+fn heatmap_color(v: f32) -> egui::Color32 {
+    let t = v.clamp(-1.0, 1.0);
+    if t >= 0.0 {
+        egui::Color32::from_rgb((255.0 * t) as u8, (160.0 * t) as u8, (32.0 * t) as u8)
+    } else {
+        let t = -t;
+        egui::Color32::from_rgb((32.0 * t) as u8, (96.0 * t) as u8, (255.0 * t) as u8)
+    }
+}
+
+#[derive(Default)]
+pub struct Sampler2dUi {}
+
+impl ExpressionObjectUi for Sampler2dUi {
+    type ObjectType = ExpressionNodeWithId<Sampler2d>;
+    type StateType = NoObjectUiState;
+
+    fn ui<'a, 'b>(
+        &self,
+        sampler2d: &mut ExpressionNodeWithId<Sampler2d>,
+        _graph_ui_state: &mut ExpressionGraphUiState,
+        ui: &mut eframe::egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ExpressionNodeUi::new_named(
+            sampler2d.id(),
+            "Sampler2d".to_string(),
+            DisplayStyle::Framed,
+        )
+        .show_with(ui, ctx, |ui| {
+            let mut values = sampler2d.value().read().to_vec();
+            let width = sampler2d.width();
+            let height = sampler2d.height();
+
+            let (id, rect) = ui.allocate_space(egui::vec2(200.0, 100.0));
+            let painter = ui.painter();
+
+            painter.rect_filled(rect, egui::Rounding::ZERO, egui::Color32::BLACK);
+
+            // Draw the values as they are read, with at most one cell
+            // per pixel. x increases to the right and y increases upwards.
+            let num_columns = width.min(rect.width() as usize).max(1);
+            let num_rows = height.min(rect.height() as usize).max(1);
+            let cell_size = egui::vec2(
+                rect.width() / num_columns as f32,
+                rect.height() / num_rows as f32,
+            );
+            for row in 0..num_rows {
+                for column in 0..num_columns {
+                    let x = (column as f32 + 0.5) / num_columns as f32;
+                    let y = (row as f32 + 0.5) / num_rows as f32;
+                    let v = Sampler2d::sample(&values, width, x, y);
+                    let min = egui::pos2(
+                        rect.left() + column as f32 * cell_size.x,
+                        rect.bottom() - (row + 1) as f32 * cell_size.y,
+                    );
+                    painter.rect_filled(
+                        egui::Rect::from_min_size(min, cell_size),
+                        egui::Rounding::ZERO,
+                        heatmap_color(v),
+                    );
+                }
+            }
+
+            // Paint with the primary button to raise entries and with
+            // the secondary button to lower them
+            let r = ui.interact(rect, id, egui::Sense::drag());
+
+            if r.dragged() {
+                let target = if r.dragged_by(egui::PointerButton::Secondary) {
+                    -1.0
+                } else {
+                    1.0
+                };
+                let p = r.interact_pointer_pos().unwrap();
+                let x = ((p.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                let y = ((rect.bottom() - p.y) / rect.height()).clamp(0.0, 1.0);
+                let i = (x * (width - 1) as f32).round() as usize;
+                let j = (y * (height - 1) as f32).round() as usize;
+                let step = PAINT_RATE * ui.input(|i| i.stable_dt);
+                let v = &mut values[j * width + i];
+                *v = if target > *v {
+                    (*v + step).min(target)
+                } else {
+                    (*v - step).max(target)
+                };
+
+                sampler2d.value().write(&values);
+                ui.ctx().request_repaint();
+            }
+
+            if r.drag_stopped() {
+                ctx.request_snapshot();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(format!("{} x {} entries", width, height));
+                if ui.button("clear").clicked() {
+                    sampler2d.clear();
+                    ctx.request_snapshot();
+                }
+            });
+        });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["sampler2d"]
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty()
+            .add(&Sampler2d::ARG_WIDTH)
+            .add(&Sampler2d::ARG_HEIGHT)
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
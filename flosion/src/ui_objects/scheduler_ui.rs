@@ -4,9 +4,12 @@ use crate::{
     core::sound::soundprocessor::SoundProcessorWithId,
     objects::scheduler::Scheduler,
     ui_core::{
-        arguments::ParsedArguments, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::ParsedArguments,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -34,6 +37,10 @@ impl SoundObjectUi for SchedulerUi {
         &["scheduler"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -0,0 +1,56 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::slew::Slew,
+    ui_core::{
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct SlewUi {}
+
+impl SoundObjectUi for SlewUi {
+    type ObjectType = SoundProcessorWithId<Slew>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        slew: &mut SoundProcessorWithId<Slew>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Slew")
+            .add_sound_input(&slew.input, "input")
+            .add_expression(&slew.rise, &["rise"], PlotConfig::new())
+            .add_expression(&slew.fall, &["fall"], PlotConfig::new())
+            .show(slew, ui, ctx, graph_ui_state);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["slew", "portamento"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -0,0 +1,104 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::spectralgate::SpectralGate,
+    ui_core::{
+        arguments::ParsedArguments,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct SpectralGateUi {}
+
+impl SoundObjectUi for SpectralGateUi {
+    type ObjectType = SoundProcessorWithId<SpectralGate>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        gate: &mut SoundProcessorWithId<SpectralGate>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Spectral Gate")
+            .add_sound_input(&gate.input, "input")
+            .show_with(gate, ui, ctx, graph_ui_state, |gate, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    let mut threshold = gate.threshold();
+                    ui.label("Threshold");
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut threshold)
+                                .range(-100.0..=0.0)
+                                .speed(0.1)
+                                .suffix(" dB"),
+                        )
+                        .changed()
+                    {
+                        gate.set_threshold(threshold);
+                    }
+
+                    let mut reduction = gate.reduction();
+                    ui.label("Reduction");
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut reduction)
+                                .range(0.0..=100.0)
+                                .speed(0.1)
+                                .suffix(" dB"),
+                        )
+                        .changed()
+                    {
+                        gate.set_reduction(reduction);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let mut learning = gate.is_learning();
+                    if ui
+                        .toggle_value(&mut learning, "Learn noise")
+                        .on_hover_text(
+                            "Learn the noise profile from the input while this is selected",
+                        )
+                        .changed()
+                    {
+                        if learning {
+                            gate.start_learning();
+                        } else {
+                            gate.stop_learning();
+                        }
+                    }
+
+                    if gate.has_noise_profile() && !learning && ui.button("Clear noise").clicked() {
+                        gate.clear_noise_profile();
+                    }
+                });
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["spectralgate", "noisereduction", "denoise"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -1,16 +1,16 @@
 use crate::{
     core::expression::expressionnode::ExpressionNodeWithId,
     objects::statefulfunctions::{
-        ExponentialApproach, Integrator, LinearApproach, WrappingIntegrator,
+        ExponentialApproach, Integrator, LinearApproach, Random, Trigger, WrappingIntegrator,
     },
     ui_core::{
-        arguments::ParsedArguments,
+        arguments::{ArgumentList, ParsedArguments},
         expressiongraphuicontext::ExpressionGraphUiContext,
         expressiongraphuistate::ExpressionGraphUiState,
         expressionobjectui::ExpressionObjectUi,
         expressionodeui::{DisplayStyle, ExpressionNodeUi},
         lexicallayout::lexicallayout::ExpressionNodeLayout,
-        object_ui::NoObjectUiState,
+        object_ui::{NoObjectUiState, SummonCategory},
     },
 };
 
@@ -41,6 +41,10 @@ impl ExpressionObjectUi for LinearApproachUi {
         &["linearapproach"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
     fn make_properties(&self) -> ExpressionNodeLayout {
         ExpressionNodeLayout::Function
     }
@@ -81,6 +85,10 @@ impl ExpressionObjectUi for ExponentialApproachUi {
         &["exponentialapproach"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
     fn make_properties(&self) -> ExpressionNodeLayout {
         ExpressionNodeLayout::Function
     }
@@ -117,6 +125,10 @@ impl ExpressionObjectUi for IntegratorUi {
         &["integrator"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Math
+    }
+
     fn make_properties(&self) -> ExpressionNodeLayout {
         ExpressionNodeLayout::Function
     }
@@ -157,6 +169,98 @@ impl ExpressionObjectUi for WrappingIntegratorUi {
         &["wrappingintegrator"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Math
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
+
+#[derive(Default)]
+pub struct TriggerUi {}
+
+impl ExpressionObjectUi for TriggerUi {
+    type ObjectType = ExpressionNodeWithId<Trigger>;
+    type StateType = NoObjectUiState;
+
+    fn ui<'a, 'b>(
+        &self,
+        object: &mut ExpressionNodeWithId<Trigger>,
+        _ui_state: &mut ExpressionGraphUiState,
+        ui: &mut eframe::egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _data: &mut NoObjectUiState,
+    ) {
+        ExpressionNodeUi::new_named(object.id(), "Trigger".to_string(), DisplayStyle::Framed)
+            .show(ui, ctx);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["trigger"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Logic
+    }
+
+    fn make_properties(&self) -> ExpressionNodeLayout {
+        ExpressionNodeLayout::Function
+    }
+
+    fn make_ui_state(
+        &self,
+        _object: &Self::ObjectType,
+        _args: ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
+
+#[derive(Default)]
+pub struct RandomUi {}
+
+impl ExpressionObjectUi for RandomUi {
+    type ObjectType = ExpressionNodeWithId<Random>;
+    type StateType = NoObjectUiState;
+
+    fn ui<'a, 'b>(
+        &self,
+        object: &mut ExpressionNodeWithId<Random>,
+        _ui_state: &mut ExpressionGraphUiState,
+        ui: &mut eframe::egui::Ui,
+        ctx: &ExpressionGraphUiContext,
+        _data: &mut NoObjectUiState,
+    ) {
+        ExpressionNodeUi::new_named(
+            object.id(),
+            format!("Random (seed {})", object.seed()),
+            DisplayStyle::Framed,
+        )
+        .show(ui, ctx);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["random"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Random::ARG_SEED)
+    }
+
     fn make_properties(&self) -> ExpressionNodeLayout {
         ExpressionNodeLayout::Function
     }
@@ -0,0 +1,78 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::stereowidth::StereoWidth,
+    ui_core::{
+        arguments::ParsedArguments,
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct StereoWidthUi {}
+
+impl SoundObjectUi for StereoWidthUi {
+    type ObjectType = SoundProcessorWithId<StereoWidth>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        stereo_width: &mut SoundProcessorWithId<StereoWidth>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("StereoWidth")
+            .add_sound_input(&stereo_width.input, "input")
+            .add_expression(
+                &stereo_width.width,
+                &["width"],
+                PlotConfig::new().linear_vertical_range(0.0..=2.0),
+            )
+            .show_with(
+                stereo_width,
+                ui,
+                ctx,
+                graph_ui_state,
+                |stereo_width, ui, _uistate| {
+                    // When nothing is connected to the width expression,
+                    // its constant value can be set directly
+                    let result = &mut stereo_width.width.graph_mut().results_mut()[0];
+                    if result.target().is_none() {
+                        let mut width = result.default_value();
+                        if ui
+                            .add(egui::Slider::new(&mut width, 0.0..=2.0).text("width"))
+                            .changed()
+                        {
+                            result.set_default_value(width);
+                        }
+                    }
+                },
+            );
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["stereowidth", "width"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
@@ -0,0 +1,89 @@
+use eframe::egui;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::{
+        output::ChannelMeter,
+        tap::{Tap, TapReader},
+    },
+    ui_core::{
+        arguments::ParsedArguments, levelmeter::LevelMeter, object_ui::SummonCategory,
+        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct TapUi {}
+
+pub struct TapUiState {
+    reader: TapReader,
+    meters: [ChannelMeter; 2],
+}
+
+impl Stashable for TapUiState {
+    fn stash(&self, _stasher: &mut Stasher) {}
+}
+
+impl UnstashableInplace for TapUiState {
+    fn unstash_inplace(&mut self, _unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        Ok(())
+    }
+}
+
+impl SoundObjectUi for TapUi {
+    type ObjectType = SoundProcessorWithId<Tap>;
+    type StateType = TapUiState;
+
+    fn ui(
+        &self,
+        tap: &mut SoundProcessorWithId<Tap>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        state: &mut TapUiState,
+    ) {
+        ProcessorUi::new("Tap")
+            .add_sound_input(&tap.input, "input")
+            .show_with(tap, ui, ctx, graph_ui_state, |_tap, ui, _ui_state| {
+                while let Some(chunk) = state.reader.read() {
+                    state.meters[0].update(&chunk.l);
+                    state.meters[1].update(&chunk.r);
+                }
+                for (meter, label) in state.meters.iter().zip(["L", "R"]) {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        if ui
+                            .add(LevelMeter::new(meter.peak(), meter.rms(), meter.clipped()))
+                            .clicked()
+                        {
+                            meter.reset_clip();
+                        }
+                    });
+                }
+                ui.ctx().request_repaint();
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["tap"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Utilities
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<TapUiState, ()> {
+        Ok(TapUiState {
+            reader: handle.reader(),
+            meters: [ChannelMeter::new(), ChannelMeter::new()],
+        })
+    }
+}
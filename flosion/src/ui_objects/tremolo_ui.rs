@@ -0,0 +1,79 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::tremolo::{Tremolo, TremoloWaveform},
+    ui_core::{
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct TremoloUi {}
+
+impl SoundObjectUi for TremoloUi {
+    type ObjectType = SoundProcessorWithId<Tremolo>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        tremolo: &mut SoundProcessorWithId<Tremolo>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Tremolo")
+            .add_sound_input(&tremolo.input, "input")
+            .add_expression(&tremolo.rate, &["rate"], PlotConfig::new())
+            .add_expression(
+                &tremolo.depth,
+                &["depth"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            )
+            .show_with(tremolo, ui, ctx, graph_ui_state, |tremolo, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    for waveform in TremoloWaveform::all_values() {
+                        if ui
+                            .selectable_label(tremolo.waveform() == *waveform, waveform.name())
+                            .clicked()
+                        {
+                            tremolo.set_waveform(*waveform);
+                        }
+                    }
+                });
+                let mut auto_pan = tremolo.auto_pan();
+                if ui.checkbox(&mut auto_pan, "Auto-pan").changed() {
+                    tremolo.set_auto_pan(auto_pan);
+                }
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["tremolo", "autopan"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Dynamics
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Tremolo::ARG_WAVEFORM)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
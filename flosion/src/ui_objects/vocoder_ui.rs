@@ -0,0 +1,69 @@
+use eframe::egui;
+
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::vocoder::{Vocoder, MAX_BANDS},
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct VocoderUi {}
+
+impl SoundObjectUi for VocoderUi {
+    type ObjectType = SoundProcessorWithId<Vocoder>;
+    type StateType = NoObjectUiState;
+
+    fn ui(
+        &self,
+        vocoder: &mut SoundProcessorWithId<Vocoder>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Vocoder")
+            .add_sound_input(&vocoder.carrier, "carrier")
+            .add_sound_input(&vocoder.modulator, "modulator")
+            .show_with(vocoder, ui, ctx, graph_ui_state, |vocoder, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    ui.label("bands");
+                    let mut num_bands = vocoder.num_bands();
+                    if ui
+                        .add(egui::DragValue::new(&mut num_bands).range(1..=MAX_BANDS))
+                        .changed()
+                    {
+                        vocoder.set_num_bands(num_bands);
+                    }
+                });
+            });
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["vocoder"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Vocoder::ARG_NUM_BANDS)
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}
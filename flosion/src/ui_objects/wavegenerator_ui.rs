@@ -1,10 +1,14 @@
 use crate::{
     core::sound::{argument::ProcessorArgumentLocation, soundprocessor::SoundProcessorWithId},
-    objects::wavegenerator::WaveGenerator,
+    objects::wavegenerator::{WaveGenerator, WaveGeneratorWaveform},
     ui_core::{
-        arguments::ParsedArguments, expressionplot::PlotConfig, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::{ArgumentEnum, ArgumentList, ParsedArguments},
+        expressionplot::PlotConfig,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -23,12 +27,17 @@ impl SoundObjectUi for WaveGeneratorUi {
         ctx: &SoundGraphUiContext,
         _state: &mut NoObjectUiState,
     ) {
-        ProcessorUi::new("WaveGenerator")
+        let amplitude_range = if wavgen.waveform() == WaveGeneratorWaveform::Expression {
+            -1.0..=1.0
+        } else {
+            0.0..=1.0
+        };
+        let mut objwin = ProcessorUi::new("WaveGenerator")
             .add_expression(
                 &wavgen.amplitude,
                 &["amplitude"],
                 PlotConfig::new()
-                    .linear_vertical_range(-1.0..=1.0)
+                    .linear_vertical_range(amplitude_range)
                     .with_respect_to(
                         // TODO: ew, why not just `wavgen.phase`?
                         ProcessorArgumentLocation::new(wavgen.id(), wavgen.phase.id()),
@@ -36,14 +45,43 @@ impl SoundObjectUi for WaveGeneratorUi {
                     ),
             )
             .add_expression(&wavgen.frequency, &["frequency"], PlotConfig::new())
-            .add_argument(&wavgen.phase, "phase")
-            .show(wavgen, ui, ctx, graph_ui_state);
+            .add_expression(&wavgen.sync, &["sync"], PlotConfig::new())
+            .add_argument(&wavgen.phase, "phase");
+
+        if wavgen.waveform() == WaveGeneratorWaveform::Square {
+            objwin = objwin.add_expression(
+                &wavgen.pulse_width,
+                &["pulse_width"],
+                PlotConfig::new().linear_vertical_range(0.0..=1.0),
+            );
+        }
+
+        objwin.show_with(wavgen, ui, ctx, graph_ui_state, |wavgen, ui, _uistate| {
+            ui.horizontal(|ui| {
+                for waveform in WaveGeneratorWaveform::all_values() {
+                    if ui
+                        .selectable_label(wavgen.waveform() == *waveform, waveform.name())
+                        .clicked()
+                    {
+                        wavgen.set_waveform(*waveform);
+                    }
+                }
+            });
+        });
     }
 
     fn summon_names(&self) -> &'static [&'static str] {
         &["wavegenerator"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&WaveGenerator::ARG_WAVEFORM)
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
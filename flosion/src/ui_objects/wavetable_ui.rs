@@ -0,0 +1,209 @@
+use eframe::egui;
+use hashstash::{InplaceUnstasher, Stashable, Stasher, UnstashError, UnstashableInplace};
+
+use crate::{
+    core::{
+        audiofileio::{default_base_directory, AudioFilePath},
+        sound::soundprocessor::SoundProcessorWithId,
+    },
+    objects::wavetable::{Wavetable, WAVETABLE_SIZE},
+    ui_core::{
+        arguments::{ArgumentList, ParsedArguments},
+        expressionplot::PlotConfig,
+        object_ui::SummonCategory,
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct WavetableUi {}
+
+pub struct WavetableUiState {
+    /// The table which is shown and can be drawn into
+    selected_table: usize,
+}
+
+impl Stashable for WavetableUiState {
+    fn stash(&self, stasher: &mut Stasher) {
+        stasher.u64(self.selected_table as u64);
+    }
+}
+
+impl UnstashableInplace for WavetableUiState {
+    fn unstash_inplace(&mut self, unstasher: &mut InplaceUnstasher) -> Result<(), UnstashError> {
+        let selected_table = unstasher.u64_always()? as usize;
+        if unstasher.time_to_write() {
+            self.selected_table = selected_table;
+        }
+        Ok(())
+    }
+}
+
+/// Show the given table and let it be redrawn by dragging across it
+fn draw_table(ui: &mut egui::Ui, table: &mut [f32]) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width().max(200.0), 80.0),
+        egui::Sense::drag(),
+    );
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, egui::Rounding::same(3.0), egui::Color32::BLACK);
+    painter.hline(
+        rect.x_range(),
+        rect.center().y,
+        egui::Stroke::new(1.0, egui::Color32::DARK_GRAY),
+    );
+
+    let index_at = |x: f32| -> usize {
+        let t = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        ((t * WAVETABLE_SIZE as f32) as usize).min(WAVETABLE_SIZE - 1)
+    };
+    let value_at =
+        |y: f32| -> f32 { (1.0 - 2.0 * (y - rect.top()) / rect.height()).clamp(-1.0, 1.0) };
+
+    if response.dragged() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            // Fill in every sample between the previous and current
+            // pointer positions so that fast strokes leave no gaps
+            let prev = pos - response.drag_delta();
+            let (i0, v0) = (index_at(prev.x), value_at(prev.y));
+            let (i1, v1) = (index_at(pos.x), value_at(pos.y));
+            let (lo, hi) = (i0.min(i1), i0.max(i1));
+            for (i, s) in table.iter_mut().enumerate().take(hi + 1).skip(lo) {
+                let t = if i1 == i0 {
+                    1.0
+                } else {
+                    (i as f32 - i0 as f32) / (i1 as f32 - i0 as f32)
+                };
+                *s = v0 + t * (v1 - v0);
+            }
+        }
+    }
+
+    let points: Vec<egui::Pos2> = table
+        .iter()
+        .enumerate()
+        .step_by((WAVETABLE_SIZE / rect.width().max(1.0) as usize).max(1))
+        .map(|(i, v)| {
+            egui::pos2(
+                rect.left() + rect.width() * i as f32 / WAVETABLE_SIZE as f32,
+                rect.center().y - 0.5 * rect.height() * v.clamp(-1.0, 1.0),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(2.0, egui::Color32::WHITE),
+    ));
+
+    response
+}
+
+impl SoundObjectUi for WavetableUi {
+    type ObjectType = SoundProcessorWithId<Wavetable>;
+    type StateType = WavetableUiState;
+
+    fn ui(
+        &self,
+        wavetable: &mut SoundProcessorWithId<Wavetable>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut egui::Ui,
+        ctx: &SoundGraphUiContext,
+        state: &mut WavetableUiState,
+    ) {
+        let max_morph = (wavetable.tables().len().max(1) - 1) as f32;
+        ProcessorUi::new("Wavetable")
+            .add_expression(&wavetable.frequency, &["frequency"], PlotConfig::new())
+            .add_expression(
+                &wavetable.morph,
+                &["morph"],
+                PlotConfig::new().linear_vertical_range(0.0..=max_morph),
+            )
+            .add_expression(&wavetable.sync, &["sync"], PlotConfig::new())
+            .show_with(
+                wavetable,
+                ui,
+                ctx,
+                graph_ui_state,
+                |wavetable, ui, _uistate| {
+                    ui.vertical(|ui| {
+                        let num_tables = wavetable.tables().len();
+                        if num_tables == 0 {
+                            ui.label("No tables");
+                            return;
+                        }
+                        state.selected_table = state.selected_table.min(num_tables - 1);
+
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label("Table");
+                            for i in 0..num_tables {
+                                if ui
+                                    .selectable_label(state.selected_table == i, format!("{}", i))
+                                    .clicked()
+                                {
+                                    state.selected_table = i;
+                                }
+                            }
+                        });
+
+                        if draw_table(ui, wavetable.table_mut(state.selected_table)).drag_stopped()
+                        {
+                            ctx.request_snapshot();
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.button("Duplicate").clicked() {
+                                wavetable.duplicate_table(state.selected_table);
+                                state.selected_table += 1;
+                                ctx.request_snapshot();
+                            }
+                            if num_tables > 1 && ui.button("Remove").clicked() {
+                                wavetable.remove_table(state.selected_table);
+                                state.selected_table = state.selected_table.saturating_sub(1);
+                                ctx.request_snapshot();
+                            }
+                            if ui.button("Load").clicked() {
+                                let dialog =
+                                    rfd::FileDialog::new().add_filter("Audio files", &["wav"]);
+                                if let Some(path) = dialog.pick_file() {
+                                    let file = AudioFilePath::new(&path, &default_base_directory());
+                                    match wavetable.load_file(file) {
+                                        Ok(()) => {
+                                            state.selected_table = 0;
+                                            ctx.request_snapshot();
+                                        }
+                                        Err(e) => println!("Failed to load file: {}", e),
+                                    }
+                                }
+                            }
+                        });
+                    });
+                },
+            );
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&Wavetable::ARG_PATH)
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["wavetable"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<WavetableUiState, ()> {
+        Ok(WavetableUiState { selected_table: 0 })
+    }
+}
@@ -4,9 +4,12 @@ use crate::{
     core::sound::soundprocessor::SoundProcessorWithId,
     objects::whitenoise::WhiteNoise,
     ui_core::{
-        arguments::ParsedArguments, object_ui::NoObjectUiState,
-        soundgraphuicontext::SoundGraphUiContext, soundgraphuistate::SoundGraphUiState,
-        soundobjectui::SoundObjectUi, soundprocessorui::ProcessorUi,
+        arguments::{ArgumentList, ParsedArguments},
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
     },
 };
 
@@ -25,13 +28,35 @@ impl SoundObjectUi for WhiteNoiseUi {
         ctx: &SoundGraphUiContext,
         _state: &mut NoObjectUiState,
     ) {
-        ProcessorUi::new("WhiteNoise").show(whitenoise, ui, ctx, graph_ui_state);
+        ProcessorUi::new("WhiteNoise").show_with(
+            whitenoise,
+            ui,
+            ctx,
+            graph_ui_state,
+            |whitenoise, ui, _uistate| {
+                ui.horizontal(|ui| {
+                    ui.label("seed");
+                    let mut seed = whitenoise.seed();
+                    if ui.add(egui::DragValue::new(&mut seed)).changed() {
+                        whitenoise.set_seed(seed);
+                    }
+                });
+            },
+        );
     }
 
     fn summon_names(&self) -> &'static [&'static str] {
         &["whitenoise"]
     }
 
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Oscillators
+    }
+
+    fn summon_arguments(&self) -> ArgumentList {
+        ArgumentList::new_empty().add(&WhiteNoise::ARG_SEED)
+    }
+
     fn make_properties(&self) -> () {
         ()
     }
@@ -0,0 +1,50 @@
+use crate::{
+    core::sound::soundprocessor::SoundProcessorWithId,
+    objects::widen::Widen,
+    ui_core::{
+        arguments::ParsedArguments,
+        object_ui::{NoObjectUiState, SummonCategory},
+        soundgraphuicontext::SoundGraphUiContext,
+        soundgraphuistate::SoundGraphUiState,
+        soundobjectui::SoundObjectUi,
+        soundprocessorui::ProcessorUi,
+    },
+};
+
+#[derive(Default)]
+pub struct WidenUi {}
+
+impl SoundObjectUi for WidenUi {
+    type ObjectType = SoundProcessorWithId<Widen>;
+    type StateType = NoObjectUiState;
+    fn ui(
+        &self,
+        widen: &mut SoundProcessorWithId<Widen>,
+        graph_ui_state: &mut SoundGraphUiState,
+        ui: &mut eframe::egui::Ui,
+        ctx: &SoundGraphUiContext,
+        _state: &mut NoObjectUiState,
+    ) {
+        ProcessorUi::new("Widen")
+            .add_sound_input(&widen.input, "input")
+            .show(widen, ui, ctx, graph_ui_state);
+    }
+
+    fn summon_names(&self) -> &'static [&'static str] {
+        &["widen"]
+    }
+
+    fn summon_category(&self) -> SummonCategory {
+        SummonCategory::Filters
+    }
+
+    fn make_properties(&self) {}
+
+    fn make_ui_state(
+        &self,
+        _handle: &Self::ObjectType,
+        _args: &ParsedArguments,
+    ) -> Result<NoObjectUiState, ()> {
+        Ok(NoObjectUiState)
+    }
+}